@@ -1,6 +1,7 @@
 //! アカウントのライフサイクル手続き (#782 R3)。
 //!
-//! delete / logout の「credential cache 無効化 → keychain 削除 → DB」の順序、
+//! delete / logout の「credential cache 無効化 → keychain 削除 → DB」の順序と
+//! アプリ側に残るアカウント別の記録 (アップロード履歴) の後始末、
 //! ゲストの連番採番、has_token 判定 (AccountPublic 化) を一元化する。
 //! has_token 判定は従来 load_accounts と emit_accounts_early に重複していた。
 
@@ -8,11 +9,15 @@ use notecli::db::Database;
 use notecli::error::NoteDeckError;
 use notecli::keychain;
 use notecli::models::{Account, AccountPublic};
+use tauri::AppHandle;
 
 use crate::commands::invalidate_credentials;
 
 type Result<T> = std::result::Result<T, NoteDeckError>;
 
+/// ゲスト (未認証) アカウントの user_id。
+pub const GUEST_USER_ID: &str = "__guest__";
+
 /// keychain / DB フォールバックを見て has_token を判定し AccountPublic 化する。
 pub fn to_public(account: &Account) -> AccountPublic {
     let has_token =
@@ -26,24 +31,30 @@ pub fn list_public(db: &Database) -> Result<Vec<AccountPublic>> {
 
 /// アカウント完全削除。cache 無効化 → keychain → DB の順で行う
 /// (使用中の資格情報を先に無効化してから実体を消す)。
-pub fn delete(db: &Database, id: &str) -> Result<()> {
+pub fn delete(app: &AppHandle, db: &Database, id: &str) -> Result<()> {
     invalidate_credentials(id);
     let _ = keychain::delete_token(id);
     db.delete_account(id)?;
+    crate::upload_history::forget_account(app, id);
     Ok(())
 }
 
 /// ログアウト: トークンのみ削除し、アカウント行とカラムは維持する。
-pub fn logout(db: &Database, id: &str) -> Result<()> {
+/// アップロード履歴はそのアカウントのドライブの中身なので消す。
+pub fn logout(app: &AppHandle, db: &Database, id: &str) -> Result<()> {
     invalidate_credentials(id);
     let _ = keychain::delete_token(id);
     db.clear_token(id)?;
+    crate::upload_history::forget_account(app, id);
     Ok(())
 }
 
 /// 既存アカウント一覧からゲストの連番表示名 (「ゲスト N」) を決める。
 pub fn next_guest_display_name(accounts: &[Account]) -> String {
-    let guest_count = accounts.iter().filter(|a| a.user_id == "__guest__").count();
+    let guest_count = accounts
+        .iter()
+        .filter(|a| a.user_id == GUEST_USER_ID)
+        .count();
     format!("ゲスト{}", guest_count + 1)
 }
 
@@ -58,7 +69,7 @@ pub fn create_guest(db: &Database, host: String, software: String) -> Result<Acc
         id,
         host,
         token: String::new(),
-        user_id: "__guest__".to_string(),
+        user_id: GUEST_USER_ID.to_string(),
         username,
         display_name,
         avatar_url: None,
@@ -77,7 +88,7 @@ mod tests {
             id: format!("g{n}"),
             host: "misskey.io".into(),
             token: String::new(),
-            user_id: "__guest__".into(),
+            user_id: GUEST_USER_ID.into(),
             username: format!("guest_{n}"),
             display_name: None,
             avatar_url: None,
@@ -0,0 +1,80 @@
+//! notecli.db の外に置くアプリ側の小さな状態の DB (`app-state.db`)。
+//!
+//! notecli.db のスキーマは notecli の持ち物で、しかも Phase 2 まで開けない。
+//! ウィンドウのジオメトリ (window_geometry.rs)・タイムラインの gap
+//! (timeline_gaps.rs)・アップロード履歴 (upload_history.rs) はどれも数十行規模の
+//! 表なので、ファイルを分けずにこの 1 つの SQLite にまとめる。ウィンドウ表示前に
+//! 復元するジオメトリのため Phase 1 で同期に開き、各ストアは接続を共有して
+//! 自分の表だけを作る。
+//!
+//! ジョブ (jobs.rs) と予約投稿 (scheduler.rs) は一覧を丸ごと JSON で
+//! `runtime_state` 表に置く (`load_state` / `save_state`)。どちらも進捗や
+//! 実行のたびに書き換わる実行時の状態なので、設定ファイルには入れない。
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const DB_FILE: &str = "app-state.db";
+
+/// ストア間で共有する接続。
+pub type SharedConn = Arc<Mutex<Connection>>;
+
+/// `app_dir/app-state.db` を開く (無ければ作成)。
+pub fn open(app_dir: &Path) -> rusqlite::Result<SharedConn> {
+    let conn = Connection::open(app_dir.join(DB_FILE))?;
+    Ok(Arc::new(Mutex::new(conn)))
+}
+
+/// メモリ上の DB。テストと、`app-state.db` を開けなかったときの代わり
+/// (その場合は保存されないが、ジョブ等は動く)。
+pub fn open_in_memory() -> rusqlite::Result<SharedConn> {
+    Ok(Arc::new(Mutex::new(Connection::open_in_memory()?)))
+}
+
+fn init_state(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS runtime_state (
+            key   TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+}
+
+/// `key` に保存した値を読む。まだ保存していなければ `None`。
+pub fn load_state<T: DeserializeOwned>(
+    conn: &SharedConn,
+    key: &str,
+) -> rusqlite::Result<Option<T>> {
+    let conn = conn.lock().unwrap();
+    init_state(&conn)?;
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT value FROM runtime_state WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    json.map(|json| {
+        serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+    })
+    .transpose()
+}
+
+/// `key` の値を丸ごと置き換える。
+pub fn save_state<T: Serialize>(conn: &SharedConn, key: &str, value: &T) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let conn = conn.lock().unwrap();
+    init_state(&conn)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO runtime_state (key, value) VALUES (?1, ?2)",
+        params![key, json],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,893 @@
+//! 自動化ルール (トリガー → アクション)。
+//!
+//! ストリーミングで届いたノート / 通知を [`TauriEmitter`](crate::streaming::TauriEmitter)
+//! から [`on_stream_event`] に渡し、Rust 側でルールと照合する。フロントが
+//! 閉じていても (トレイ常駐中など) 動く。
+//!
+//! - トリガー: 列に流れたノートのキーワード一致 / 特定ユーザーからのメンション /
+//!   投票した (または自分の) 投票の終了
+//! - アクション: OS 通知 / 自動リアクション / クリップへの追加 / Webhook /
+//!   通知の音と優先度 (`cue`、[`event_cue`](crate::event_cue) の既定の分類を上書き)
+//!
+//! 同じノートが複数の購読 (ホーム + ローカル等) から届いても、ルールごとに
+//! 1 回だけ発火する。ルールはバックエンド設定 (`settings.rs`) の `automation`
+//! セクションに保存する。
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notecli::error::NoteDeckError;
+use notecli::models::{NormalizedNote, NormalizedNotification};
+use notecli::streaming::StreamEvent;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::event_cue::{CuePriority, CueSound};
+use crate::query_runtime::QueryKey;
+use crate::settings::SettingsStore;
+
+/// ルールが発火してアクションを実行し終えるたびに emit する (payload は [`AutomationFired`])。
+pub const AUTOMATION_FIRED_EVENT: &str = "nd:automation-fired";
+
+/// 発火済み (ルール, ノート / 通知) を覚えておく件数。
+const FIRED_CAPACITY: usize = 1000;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// OS 通知の本文に載せるノート本文の最大文字数。
+const NOTIFY_BODY_MAX_CHARS: usize = 120;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AutomationTrigger {
+    /// ノート本文 (CW 含む) にキーワードのいずれかが含まれる (大文字小文字を無視)。
+    /// `source` を指定するとその列 (query) に流れたノートだけを対象にする。
+    #[serde(rename_all = "camelCase")]
+    NoteKeyword {
+        account_id: String,
+        source: Option<QueryKey>,
+        keywords: Vec<String>,
+    },
+    /// 指定ユーザーからのメンション / リプライ。`user` は `alice` (同じサーバー)
+    /// か `alice@example.com`。
+    #[serde(rename_all = "camelCase")]
+    MentionFrom { account_id: String, user: String },
+    /// 投票した (または自分が作った) 投票が終了した。
+    #[serde(rename_all = "camelCase")]
+    PollEnded { account_id: String },
+}
+
+impl AutomationTrigger {
+    fn account_id(&self) -> &str {
+        match self {
+            Self::NoteKeyword { account_id, .. }
+            | Self::MentionFrom { account_id, .. }
+            | Self::PollEnded { account_id } => account_id,
+        }
+    }
+
+    fn matches(&self, incoming: &Incoming<'_>) -> bool {
+        if self.account_id() != incoming.account_id() {
+            return false;
+        }
+        match (self, incoming) {
+            (
+                Self::NoteKeyword {
+                    source, keywords, ..
+                },
+                Incoming::Note {
+                    note,
+                    source: note_source,
+                    ..
+                },
+            ) => {
+                if let Some(source) = source {
+                    let Ok(want) = crate::query_runtime::canonicalize_key(source) else {
+                        return false;
+                    };
+                    if note_source.as_deref() != Some(want.as_str()) {
+                        return false;
+                    }
+                }
+                let haystack = [note.cw.as_deref(), note.text.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("\n")
+                    .to_lowercase();
+                keywords
+                    .iter()
+                    .map(|k| k.trim())
+                    .filter(|k| !k.is_empty())
+                    .any(|k| haystack.contains(&k.to_lowercase()))
+            }
+            (Self::MentionFrom { user, .. }, Incoming::Notification { notification, .. }) => {
+                matches!(notification.notification_type.as_str(), "mention" | "reply")
+                    && notification.user.as_ref().is_some_and(|u| {
+                        acct_matches(
+                            user,
+                            &u.username,
+                            u.host.as_deref(),
+                            &notification.server_host,
+                        )
+                    })
+            }
+            (Self::PollEnded { .. }, Incoming::Notification { notification, .. }) => {
+                notification.notification_type == "pollEnded"
+            }
+            _ => false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        if self.account_id().trim().is_empty() {
+            return Err(invalid("trigger accountId must not be empty"));
+        }
+        match self {
+            Self::NoteKeyword {
+                account_id,
+                source,
+                keywords,
+            } => {
+                if keywords.iter().all(|k| k.trim().is_empty()) {
+                    return Err(invalid("noteKeyword needs at least one keyword"));
+                }
+                if source
+                    .as_ref()
+                    .is_some_and(|s| crate::query_runtime::account_id(s) != account_id)
+                {
+                    return Err(invalid("noteKeyword source belongs to another account"));
+                }
+            }
+            Self::MentionFrom { user, .. } => {
+                if user.trim().trim_start_matches('@').is_empty() {
+                    return Err(invalid("mentionFrom user must not be empty"));
+                }
+            }
+            Self::PollEnded { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// `spec` (`alice` / `@alice` / `alice@host`) が通知の送り主を指しているか。
+/// ホスト省略はアカウントと同じサーバーのユーザーを意味する。
+fn acct_matches(spec: &str, username: &str, host: Option<&str>, server_host: &str) -> bool {
+    let spec = spec.trim().trim_start_matches('@');
+    let (want_user, want_host) = match spec.split_once('@') {
+        Some((user, host)) => (user, Some(host)),
+        None => (spec, None),
+    };
+    if !want_user.eq_ignore_ascii_case(username) {
+        return false;
+    }
+    match (want_host, host) {
+        (None, None) => true,
+        (Some(want), None) => want.eq_ignore_ascii_case(server_host),
+        (Some(want), Some(host)) => want.eq_ignore_ascii_case(host),
+        (None, Some(_)) => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AutomationAction {
+    /// OS 通知を出す。`title` 省略時はルール名。
+    #[serde(rename_all = "camelCase")]
+    Notify { title: Option<String> },
+    /// 対象ノートにリアクションする (`:emoji:` またはユニコード絵文字)。
+    #[serde(rename_all = "camelCase")]
+    React { reaction: String },
+    /// 対象ノートをクリップに追加する。
+    #[serde(rename_all = "camelCase")]
+    AddToClip { clip_id: String },
+    /// ルール・ノート・通知を JSON で POST する。https か、http はループバックのみ。
+    #[serde(rename_all = "camelCase")]
+    Webhook { url: String },
+    /// 通知の音 / 優先度を決める (省略した方は通知の種類による既定のまま)。
+    /// 通知トリガー (メンション / 投票終了) のルールでだけ使える。
+    #[serde(rename_all = "camelCase")]
+    Cue {
+        sound: Option<CueSound>,
+        priority: Option<CuePriority>,
+    },
+}
+
+impl AutomationAction {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Notify { .. } => "notify",
+            Self::React { .. } => "react",
+            Self::AddToClip { .. } => "addToClip",
+            Self::Webhook { .. } => "webhook",
+            Self::Cue { .. } => "cue",
+        }
+    }
+
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        match self {
+            Self::Notify { .. } => Ok(()),
+            Self::React { reaction } if reaction.trim().is_empty() => {
+                Err(invalid("react reaction must not be empty"))
+            }
+            Self::AddToClip { clip_id } if clip_id.trim().is_empty() => {
+                Err(invalid("addToClip clipId must not be empty"))
+            }
+            Self::Webhook { url } => validate_webhook_url(url),
+            Self::Cue {
+                sound: None,
+                priority: None,
+            } => Err(invalid("cue needs a sound or a priority")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn validate_webhook_url(raw: &str) -> Result<(), NoteDeckError> {
+    let url = url::Url::parse(raw).map_err(|e| invalid(format!("invalid webhook url: {e}")))?;
+    let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" if loopback => Ok(()),
+        _ => Err(invalid(
+            "webhook url must be https (http is allowed only for localhost)",
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRule {
+    /// 新規作成時は空文字で渡すと採番する。
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: AutomationTrigger,
+    pub actions: Vec<AutomationAction>,
+}
+
+impl AutomationRule {
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        if self.name.trim().is_empty() {
+            return Err(invalid("rule name must not be empty"));
+        }
+        if self.actions.is_empty() {
+            return Err(invalid("rule needs at least one action"));
+        }
+        self.trigger.validate()?;
+        let sets_cue = self
+            .actions
+            .iter()
+            .any(|a| matches!(a, AutomationAction::Cue { .. }));
+        if sets_cue && matches!(self.trigger, AutomationTrigger::NoteKeyword { .. }) {
+            return Err(invalid("cue applies only to notification triggers"));
+        }
+        self.actions.iter().try_for_each(AutomationAction::validate)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationFired {
+    pub rule_id: String,
+    pub account_id: String,
+    /// 対象ノート (通知トリガーでノートを伴わない場合は None)。
+    pub note_id: Option<String>,
+    pub notification_id: Option<String>,
+    /// 失敗したアクション (`"react: ..."` 形式)。全成功なら空。
+    pub errors: Vec<String>,
+}
+
+/// 照合対象 (ストリームから借りたまま照合し、発火したときだけ複製する)。
+enum Incoming<'a> {
+    Note {
+        account_id: &'a str,
+        /// 流れてきた列の正規化 query キー (列に紐づかない購読なら None)。
+        source: Option<String>,
+        note: &'a Arc<NormalizedNote>,
+    },
+    Notification {
+        account_id: &'a str,
+        notification: &'a NormalizedNotification,
+    },
+}
+
+impl Incoming<'_> {
+    fn account_id(&self) -> &str {
+        match self {
+            Self::Note { account_id, .. } | Self::Notification { account_id, .. } => account_id,
+        }
+    }
+
+    /// 発火の重複排除キー。
+    fn fired_key(&self, rule_id: &str) -> String {
+        match self {
+            Self::Note { note, .. } => format!("{rule_id}:note:{}", note.id),
+            Self::Notification { notification, .. } => {
+                format!("{rule_id}:notification:{}", notification.id)
+            }
+        }
+    }
+
+    fn to_target(&self) -> Target {
+        match self {
+            Self::Note {
+                account_id, note, ..
+            } => Target {
+                account_id: account_id.to_string(),
+                note: Some(Arc::clone(note)),
+                notification: None,
+            },
+            Self::Notification {
+                account_id,
+                notification,
+            } => Target {
+                account_id: account_id.to_string(),
+                note: notification
+                    .note
+                    .as_ref()
+                    .map(|n| Arc::new(NormalizedNote::clone(n))),
+                notification: Some((*notification).clone()),
+            },
+        }
+    }
+}
+
+/// アクションの対象 (タスクへ持ち出すための所有版)。
+struct Target {
+    account_id: String,
+    note: Option<Arc<NormalizedNote>>,
+    notification: Option<NormalizedNotification>,
+}
+
+/// 直近に発火した (ルール, 対象) の集合。古いものから忘れる。
+#[derive(Default)]
+struct FiredSet {
+    order: VecDeque<String>,
+    keys: HashSet<String>,
+}
+
+impl FiredSet {
+    /// 未発火なら記録して true。
+    fn insert(&mut self, key: String) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > FIRED_CAPACITY {
+            if let Some(old) = self.order.pop_front() {
+                self.keys.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+pub struct AutomationEngine {
+    settings: Arc<SettingsStore>,
+    rules: Mutex<Vec<AutomationRule>>,
+    fired: Mutex<FiredSet>,
+}
+
+impl AutomationEngine {
+    /// バックエンド設定の `automation` セクションからルールを読み込む。
+    pub fn load(settings: Arc<SettingsStore>) -> Self {
+        let rules = settings.read(|s| s.automation.clone());
+        Self {
+            settings,
+            rules: Mutex::new(rules),
+            fired: Mutex::new(FiredSet::default()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<AutomationRule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    fn persist(&self, rules: &[AutomationRule]) -> Result<(), NoteDeckError> {
+        self.settings
+            .update(|s| {
+                s.automation = rules.to_vec();
+                Ok(())
+            })
+            .map(|_| ())
+    }
+
+    /// ルールを追加 (id が空 / 未登録) または置き換える。
+    fn save(&self, mut rule: AutomationRule) -> Result<AutomationRule, NoteDeckError> {
+        rule.validate()?;
+        if rule.id.trim().is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+        }
+        let mut rules = self.rules.lock().unwrap();
+        let mut next = rules.clone();
+        match next.iter_mut().find(|r| r.id == rule.id) {
+            Some(existing) => *existing = rule.clone(),
+            None => next.push(rule.clone()),
+        }
+        self.persist(&next)?;
+        *rules = next;
+        Ok(rule)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), NoteDeckError> {
+        let mut rules = self.rules.lock().unwrap();
+        let next: Vec<_> = rules.iter().filter(|r| r.id != id).cloned().collect();
+        if next.len() == rules.len() {
+            return Err(invalid(format!("automation rule not found: {id}")));
+        }
+        self.persist(&next)?;
+        *rules = next;
+        Ok(())
+    }
+
+    /// 有効なルールのうち、まだ発火していないものを照合して返す。
+    fn evaluate(&self, incoming: &Incoming<'_>) -> Vec<AutomationRule> {
+        let matched: Vec<AutomationRule> = self
+            .rules
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.enabled && r.trigger.matches(incoming))
+            .cloned()
+            .collect();
+        if matched.is_empty() {
+            return matched;
+        }
+        let mut fired = self.fired.lock().unwrap();
+        matched
+            .into_iter()
+            .filter(|r| fired.insert(incoming.fired_key(&r.id)))
+            .collect()
+    }
+
+    /// 通知に合う有効なルールの `cue` アクションが決める音 / 優先度。
+    /// 発火の重複排除とは別に cue を作るたびに照合し、複数あれば後のルールを優先する。
+    pub fn cue_override(
+        &self,
+        notification: &NormalizedNotification,
+    ) -> (Option<CueSound>, Option<CuePriority>) {
+        let incoming = Incoming::Notification {
+            account_id: &notification.account_id,
+            notification,
+        };
+        let rules = self.rules.lock().unwrap();
+        let mut sound = None;
+        let mut priority = None;
+        for rule in rules
+            .iter()
+            .filter(|r| r.enabled && r.trigger.matches(&incoming))
+        {
+            for action in &rule.actions {
+                if let AutomationAction::Cue {
+                    sound: s,
+                    priority: p,
+                } = action
+                {
+                    sound = s.or(sound);
+                    priority = p.or(priority);
+                }
+            }
+        }
+        (sound, priority)
+    }
+
+    fn has_rules_for(&self, account_id: &str) -> bool {
+        self.rules
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|r| r.enabled && r.trigger.account_id() == account_id)
+    }
+}
+
+/// ストリームイベントをルールと照合し、発火したルールのアクションを非同期に実行する。
+pub fn on_stream_event<R: Runtime>(app: &AppHandle<R>, event: &StreamEvent) {
+    let Some(engine) = app.try_state::<AutomationEngine>() else {
+        return;
+    };
+    let incoming = match event {
+        StreamEvent::Note(e) if engine.has_rules_for(&e.account_id) => Incoming::Note {
+            account_id: &e.account_id,
+            source: app
+                .try_state::<crate::query_runtime::QueryRuntime>()
+                .and_then(|runtime| runtime.canonical_key_for_subscription(&e.subscription_id)),
+            note: &e.note,
+        },
+        StreamEvent::Notification(e) if engine.has_rules_for(&e.account_id) => {
+            Incoming::Notification {
+                account_id: &e.account_id,
+                notification: &e.notification,
+            }
+        }
+        _ => return,
+    };
+    let rules = engine.evaluate(&incoming);
+    if rules.is_empty() {
+        return;
+    }
+    let target = Arc::new(incoming.to_target());
+    for rule in rules {
+        let app = app.clone();
+        let target = Arc::clone(&target);
+        tauri::async_runtime::spawn(async move {
+            let fired = run_rule(&app, &rule, &target).await;
+            if !fired.errors.is_empty() {
+                tracing::warn!(
+                    "[automation] rule {} ({}) failed: {:?}",
+                    rule.id,
+                    rule.name,
+                    fired.errors
+                );
+            }
+            let _ = app.emit(AUTOMATION_FIRED_EVENT, &fired);
+        });
+    }
+}
+
+async fn run_rule<R: Runtime>(
+    app: &AppHandle<R>,
+    rule: &AutomationRule,
+    target: &Target,
+) -> AutomationFired {
+    let mut errors = Vec::new();
+    for action in &rule.actions {
+        if let Err(e) = run_action(app, rule, action, target).await {
+            errors.push(format!("{}: {e}", action.label()));
+        }
+    }
+    AutomationFired {
+        rule_id: rule.id.clone(),
+        account_id: target.account_id.clone(),
+        note_id: target.note.as_ref().map(|n| n.id.clone()),
+        notification_id: target.notification.as_ref().map(|n| n.id.clone()),
+        errors,
+    }
+}
+
+async fn run_action<R: Runtime>(
+    app: &AppHandle<R>,
+    rule: &AutomationRule,
+    action: &AutomationAction,
+    target: &Target,
+) -> Result<(), NoteDeckError> {
+    let note_id = || {
+        target
+            .note
+            .as_ref()
+            .map(|n| n.id.clone())
+            .ok_or_else(|| invalid("trigger has no note"))
+    };
+    match action {
+        AutomationAction::Notify { title } => {
+            let title = title.as_deref().unwrap_or(&rule.name);
+            let body = target.note.as_ref().and_then(|n| notify_body(n.as_ref()));
+            let context = crate::os_notify::NotificationClicked {
+                account_id: target.account_id.clone(),
+                note_id: target.note.as_ref().map(|n| n.id.clone()),
+                user_id: target.note.as_ref().map(|n| n.user.id.clone()),
+            };
+            crate::streaming::show_os_notification(
+                app,
+                title,
+                body.as_deref(),
+                Some(&context),
+                None,
+                None,
+            );
+            Ok(())
+        }
+        AutomationAction::React { reaction } => {
+            let note_id = note_id()?;
+            let (client, host, token) = app_state(app)?.authed(&target.account_id).await?;
+            client
+                .create_reaction(&host, &token, &note_id, reaction)
+                .await
+        }
+        AutomationAction::AddToClip { clip_id } => {
+            let note_id = note_id()?;
+            let (client, host, token) = app_state(app)?.authed(&target.account_id).await?;
+            client
+                .add_note_to_clip(&host, &token, clip_id, &note_id)
+                .await
+        }
+        AutomationAction::Webhook { url } => {
+            let http = app
+                .try_state::<reqwest::Client>()
+                .ok_or_else(|| invalid("http client is not ready"))?;
+            let payload = serde_json::json!({
+                "rule": { "id": rule.id, "name": rule.name },
+                "accountId": target.account_id,
+                "note": target.note,
+                "notification": target.notification,
+            });
+            http.post(url)
+                .json(&payload)
+                .timeout(WEBHOOK_TIMEOUT)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map(|_| ())
+                .map_err(|e| invalid(format!("webhook failed: {e}")))
+        }
+        // event_cue が cue を作るときに `cue_override` で反映済み
+        AutomationAction::Cue { .. } => Ok(()),
+    }
+}
+
+fn app_state<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<State<'_, crate::commands::AppState>, NoteDeckError> {
+    app.try_state::<crate::commands::AppState>()
+        .ok_or_else(|| invalid("app state is not ready"))
+}
+
+fn notify_body(note: &NormalizedNote) -> Option<String> {
+    let text = note.cw.as_deref().or(note.text.as_deref())?;
+    let mut body: String = text.chars().take(NOTIFY_BODY_MAX_CHARS).collect();
+    if text.chars().count() > NOTIFY_BODY_MAX_CHARS {
+        body.push('…');
+    }
+    Some(body)
+}
+
+fn invalid(message: impl Into<String>) -> NoteDeckError {
+    NoteDeckError::InvalidInput(message.into())
+}
+
+/// 登録済みの自動化ルールを登録順に返す。
+#[tauri::command]
+#[specta::specta]
+pub fn automation_list(engine: State<'_, AutomationEngine>) -> Vec<AutomationRule> {
+    engine.list()
+}
+
+/// ルールを追加または更新して保存し、保存後のルール (採番済み id) を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn automation_save(
+    engine: State<'_, AutomationEngine>,
+    rule: AutomationRule,
+) -> Result<AutomationRule, NoteDeckError> {
+    engine.save(rule)
+}
+
+/// 自動化ルールを削除する。
+#[tauri::command]
+#[specta::specta]
+pub fn automation_delete(
+    engine: State<'_, AutomationEngine>,
+    id: String,
+) -> Result<(), NoteDeckError> {
+    engine.delete(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notecli::models::TimelineType;
+    use serde_json::json;
+
+    fn note(text: &str) -> Arc<NormalizedNote> {
+        Arc::new(
+            serde_json::from_value(json!({
+                "id": "n1",
+                "_accountId": "acct-1",
+                "_serverHost": "misskey.example",
+                "createdAt": "2026-01-01T00:00:00.000Z",
+                "text": text,
+                "user": { "id": "u1", "username": "alice" },
+                "visibility": "public",
+                "renoteCount": 0,
+                "repliesCount": 0
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn notification(
+        notif_type: &str,
+        username: &str,
+        host: Option<&str>,
+    ) -> NormalizedNotification {
+        serde_json::from_value(json!({
+            "id": "notif-1",
+            "_accountId": "acct-1",
+            "_serverHost": "misskey.example",
+            "createdAt": "2026-01-01T00:00:00.000Z",
+            "type": notif_type,
+            "user": { "id": "u2", "username": username, "host": host },
+        }))
+        .unwrap()
+    }
+
+    fn home() -> QueryKey {
+        QueryKey::Timeline {
+            account_id: "acct-1".into(),
+            timeline_type: TimelineType::new("home"),
+            list_id: None,
+        }
+    }
+
+    fn rule(trigger: AutomationTrigger) -> AutomationRule {
+        AutomationRule {
+            id: String::new(),
+            name: "rule".into(),
+            enabled: true,
+            trigger,
+            actions: vec![AutomationAction::Notify { title: None }],
+        }
+    }
+
+    fn keyword(source: Option<QueryKey>, keywords: &[&str]) -> AutomationTrigger {
+        AutomationTrigger::NoteKeyword {
+            account_id: "acct-1".into(),
+            source,
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn keyword_matches_case_insensitively_within_the_source_column() {
+        let n = note("Released NoteDeck 2.0");
+        let home_key = crate::query_runtime::canonicalize_key(&home()).unwrap();
+        let from_home = Incoming::Note {
+            account_id: "acct-1",
+            source: Some(home_key),
+            note: &n,
+        };
+        let unbound = Incoming::Note {
+            account_id: "acct-1",
+            source: None,
+            note: &n,
+        };
+
+        assert!(keyword(None, &["notedeck"]).matches(&from_home));
+        assert!(keyword(None, &["notedeck"]).matches(&unbound));
+        assert!(!keyword(None, &["misskey"]).matches(&from_home));
+        assert!(keyword(Some(home()), &["NOTEDECK"]).matches(&from_home));
+        assert!(!keyword(Some(home()), &["notedeck"]).matches(&unbound));
+    }
+
+    #[test]
+    fn mention_from_matches_local_and_remote_accts() {
+        let trigger = |user: &str| AutomationTrigger::MentionFrom {
+            account_id: "acct-1".into(),
+            user: user.into(),
+        };
+        let local = notification("mention", "Bob", None);
+        let local = Incoming::Notification {
+            account_id: "acct-1",
+            notification: &local,
+        };
+        let remote = notification("reply", "bob", Some("remote.example"));
+        let remote = Incoming::Notification {
+            account_id: "acct-1",
+            notification: &remote,
+        };
+        let follow = notification("follow", "bob", None);
+        let follow = Incoming::Notification {
+            account_id: "acct-1",
+            notification: &follow,
+        };
+
+        assert!(trigger("@bob").matches(&local));
+        assert!(trigger("bob@misskey.example").matches(&local));
+        assert!(!trigger("bob").matches(&remote));
+        assert!(trigger("bob@remote.example").matches(&remote));
+        assert!(!trigger("bob").matches(&follow));
+    }
+
+    #[test]
+    fn poll_ended_is_scoped_to_its_account() {
+        let ended = notification("pollEnded", "bob", None);
+        let incoming = Incoming::Notification {
+            account_id: "acct-1",
+            notification: &ended,
+        };
+        let other = Incoming::Notification {
+            account_id: "acct-2",
+            notification: &ended,
+        };
+        let trigger = AutomationTrigger::PollEnded {
+            account_id: "acct-1".into(),
+        };
+        assert!(trigger.matches(&incoming));
+        assert!(!trigger.matches(&other));
+    }
+
+    #[test]
+    fn fires_once_per_rule_and_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = AutomationEngine::load(Arc::new(SettingsStore::load(dir.path())));
+        let saved = engine.save(rule(keyword(None, &["hello"]))).unwrap();
+        assert!(!saved.id.is_empty());
+
+        let n = note("hello world");
+        let incoming = Incoming::Note {
+            account_id: "acct-1",
+            source: None,
+            note: &n,
+        };
+        assert_eq!(engine.evaluate(&incoming).len(), 1);
+        // 別の購読から同じノートが届いても再発火しない
+        assert!(engine.evaluate(&incoming).is_empty());
+    }
+
+    #[test]
+    fn rules_persist_and_validate() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = AutomationEngine::load(Arc::new(SettingsStore::load(dir.path())));
+        let saved = engine.save(rule(keyword(None, &["a"]))).unwrap();
+        assert_eq!(
+            AutomationEngine::load(Arc::new(SettingsStore::load(dir.path()))).list(),
+            vec![saved.clone()]
+        );
+
+        assert!(engine.save(rule(keyword(None, &[" "]))).is_err());
+        let mut webhook = rule(keyword(None, &["a"]));
+        webhook.actions = vec![AutomationAction::Webhook {
+            url: "http://example.com/hook".into(),
+        }];
+        assert!(engine.save(webhook.clone()).is_err());
+        webhook.actions = vec![AutomationAction::Webhook {
+            url: "http://127.0.0.1:8080/hook".into(),
+        }];
+        assert!(engine.save(webhook).is_ok());
+
+        let mut cue_on_keyword = rule(keyword(None, &["a"]));
+        cue_on_keyword.actions = vec![AutomationAction::Cue {
+            sound: Some(CueSound::Mention),
+            priority: None,
+        }];
+        assert!(engine.save(cue_on_keyword).is_err());
+
+        engine.delete(&saved.id).unwrap();
+        assert!(engine.delete(&saved.id).is_err());
+        assert_eq!(
+            AutomationEngine::load(Arc::new(SettingsStore::load(dir.path())))
+                .list()
+                .len(),
+            1
+        );
+    }
+    #[test]
+    fn cue_rules_override_sound_and_priority() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = AutomationEngine::load(Arc::new(SettingsStore::load(dir.path())));
+        let from_bob = AutomationTrigger::MentionFrom {
+            account_id: "acct-1".into(),
+            user: "bob".into(),
+        };
+        let mut loud = rule(from_bob.clone());
+        loud.actions = vec![AutomationAction::Cue {
+            sound: Some(CueSound::FollowRequest),
+            priority: Some(CuePriority::High),
+        }];
+        engine.save(loud).unwrap();
+        let mut quiet = rule(from_bob);
+        quiet.actions = vec![AutomationAction::Cue {
+            sound: None,
+            priority: Some(CuePriority::Low),
+        }];
+        engine.save(quiet).unwrap();
+
+        assert_eq!(
+            engine.cue_override(&notification("mention", "bob", None)),
+            (Some(CueSound::FollowRequest), Some(CuePriority::Low))
+        );
+        assert_eq!(
+            engine.cue_override(&notification("mention", "carol", None)),
+            (None, None)
+        );
+
+        let mut empty = rule(AutomationTrigger::PollEnded {
+            account_id: "acct-1".into(),
+        });
+        empty.actions = vec![AutomationAction::Cue {
+            sound: None,
+            priority: None,
+        }];
+        assert!(engine.save(empty).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! アバター / バナー画像のキャッシュ温め。
+//!
+//! 起動時とアカウント追加時に、保存済みアカウントのアバター・バナーを
+//! `ImageCache` へ先読みしておき、アカウント切り替えやカラムヘッダーが
+//! オフラインでもすぐ描けるようにする。ユーザーカラムにピン留めされた
+//! ユーザーはフロントが `warm_user_images` で渡す。
+//!
+//! URL は `users/show` で取り直す (DB の avatar_url は追加時点のもので古い
+//! ことがある)。失敗はすべて debug ログに留め、表示側の通常取得に任せる。
+
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use notecli::error::NoteDeckError;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::account_service::GUEST_USER_ID;
+use crate::commands::{show_users_raw, AppState, Result};
+use crate::image_cache::{ImageCache, StreamingFetchResult};
+
+/// 同時に取りに行く画像数。表示中の画像取得を圧迫しない程度に抑える。
+const WARM_CONCURRENCY: usize = 4;
+/// `warm_user_images` が 1 回に受け付けるユーザー数の上限。
+const MAX_WARM_USERS: usize = 500;
+
+/// `users/show` の 1 ユーザー分からアバター・バナーの URL を取り出す。
+fn image_urls(user: &Value) -> impl Iterator<Item = String> + '_ {
+    ["avatarUrl", "bannerUrl"]
+        .into_iter()
+        .filter_map(|key| user.get(key)?.as_str())
+        .filter(|url| url.starts_with("https://"))
+        .map(str::to_string)
+}
+
+/// 1 枚をキャッシュへ載せる。取得中のストリームは最後まで読み切り、
+/// ディスクへの書き込みを待ってから返る。
+async fn prefetch(cache: &ImageCache, url: &str) -> bool {
+    if cache.check_cache_only(url).await.is_some() {
+        return true;
+    }
+    match cache.fetch_streaming(url).await {
+        Ok(StreamingFetchResult::Cached(_)) => true,
+        Ok(StreamingFetchResult::Streaming {
+            mut byte_stream, ..
+        }) => {
+            while let Some(chunk) = byte_stream.next().await {
+                if chunk.is_err() {
+                    return false;
+                }
+            }
+            true
+        }
+        Err(e) => {
+            tracing::debug!(url, "[avatar-warm] prefetch failed: {e}");
+            false
+        }
+    }
+}
+
+/// `account_id` の資格情報で `user_ids` を引き、画像を先読みする。
+/// キャッシュに載った枚数を返す。
+async fn warm_users(
+    app_state: &AppState,
+    cache: &ImageCache,
+    account_id: &str,
+    user_ids: &[String],
+) -> Result<u32> {
+    let (client, host, token) = app_state.authed_or_anon(account_id).await?;
+    let users = show_users_raw(&client, &host, &token, user_ids).await?;
+    let mut urls: Vec<String> = users.iter().flat_map(image_urls).collect();
+    urls.sort_unstable();
+    urls.dedup();
+    let warmed = futures_util::stream::iter(urls)
+        .map(|url| async move { prefetch(cache, &url).await })
+        .buffer_unordered(WARM_CONCURRENCY)
+        .filter(|ok| std::future::ready(*ok))
+        .count()
+        .await;
+    Ok(warmed as u32)
+}
+
+/// 保存済みアカウント自身の画像を温める。`account_ids` が None なら全件。
+async fn warm_accounts(app: &AppHandle, account_ids: Option<&[String]>) {
+    let Some(cache) = app.try_state::<Arc<ImageCache>>() else {
+        return;
+    };
+    let app_state = app.state::<AppState>();
+    let (db, _) = app_state.ready().await;
+    let accounts = match db.load_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            tracing::debug!("[avatar-warm] failed to load accounts: {e}");
+            return;
+        }
+    };
+    for account in accounts {
+        if account.user_id == GUEST_USER_ID
+            || account_ids.is_some_and(|ids| !ids.contains(&account.id))
+        {
+            continue;
+        }
+        let ids = [account.user_id.clone()];
+        match warm_users(&app_state, &cache, &account.id, &ids).await {
+            Ok(n) => tracing::debug!(account_id = %account.id, "[avatar-warm] warmed {n} images"),
+            Err(e) => tracing::debug!(account_id = %account.id, "[avatar-warm] skipped: {e}"),
+        }
+    }
+}
+
+/// バックグラウンドで保存済みアカウントの画像を温める (起動時 / アカウント追加時)。
+pub fn spawn(app: &AppHandle, account_ids: Option<Vec<String>>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        warm_accounts(&app, account_ids.as_deref()).await;
+    });
+}
+
+/// ピン留めユーザー (ユーザーカラム等) のアバター・バナーを先読みする。
+/// キャッシュに載った枚数を返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn warm_user_images(
+    app: AppHandle,
+    app_state: State<'_, AppState>,
+    account_id: String,
+    user_ids: Vec<String>,
+) -> Result<u32> {
+    if user_ids.len() > MAX_WARM_USERS {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Too many users (max {MAX_WARM_USERS})"
+        )));
+    }
+    let Some(cache) = app.try_state::<Arc<ImageCache>>() else {
+        return Ok(0);
+    };
+    let mut ids = user_ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    warm_users(&app_state, &cache, &account_id, &ids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_https_avatar_and_banner() {
+        let user = serde_json::json!({
+            "id": "u1",
+            "avatarUrl": "https://misskey.example/avatar.webp",
+            "bannerUrl": "https://misskey.example/banner.webp",
+        });
+        assert_eq!(
+            image_urls(&user).collect::<Vec<_>>(),
+            [
+                "https://misskey.example/avatar.webp",
+                "https://misskey.example/banner.webp"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_missing_and_non_https_urls() {
+        let user = serde_json::json!({
+            "avatarUrl": "http://misskey.example/avatar.webp",
+            "bannerUrl": null,
+        });
+        assert_eq!(image_urls(&user).count(), 0);
+    }
+}
@@ -0,0 +1,387 @@
+//! トレイ格納中のバックグラウンド同期。
+//!
+//! メインウィンドウが非表示 (トレイに格納) の間だけ、`interval_minutes` ごとに
+//! 各アカウントのホームタイムラインを取得してノートキャッシュへ保存し、新着
+//! 通知を OS 通知として出す。ウィンドウを開き直したときはキャッシュから
+//! 描画されるので、閉じていた間の投稿がすぐに見える。
+//!
+//! 通知は格納した時点の最新 ID を基準にし、それより新しいものだけを出す
+//! (格納前に見ていた通知を出し直さない)。OS 通知はストリーミングの
+//! TauriEmitter を経由するので、ストリームが生きていて同じ通知を受けても
+//! 二重には出ず、バースト集約・トレイの「最近の通知」もそのまま効く。
+//!
+//! 設定はバックエンド設定 (`settings.rs`) の `backgroundSync` セクションに
+//! 保存する (既定は無効)。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+use tokio::sync::Notify;
+
+use crate::settings::SettingsStore;
+
+/// 同期間隔の下限 / 上限 (分)。
+const MIN_INTERVAL_MINUTES: u32 = 1;
+const MAX_INTERVAL_MINUTES: u32 = 60;
+/// ウィンドウの表示状態を確かめる間隔。格納を検知して基準 ID を取るまでの遅れになる。
+#[cfg_attr(mobile, allow(dead_code))]
+const TICK: Duration = Duration::from_secs(15);
+/// 1 回の同期で取得する通知の上限。超えた分は次回に回さず捨てる。
+#[cfg_attr(mobile, allow(dead_code))]
+const NOTIFICATION_PAGE: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackgroundSyncSettings {
+    /// トレイ格納中に同期する。
+    pub enabled: bool,
+    /// 同期の間隔 (分)。
+    pub interval_minutes: u32,
+}
+
+impl Default for BackgroundSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 5,
+        }
+    }
+}
+
+impl BackgroundSyncSettings {
+    pub(crate) fn validate(&self) -> Result<(), NoteDeckError> {
+        if !(MIN_INTERVAL_MINUTES..=MAX_INTERVAL_MINUTES).contains(&self.interval_minutes) {
+            return Err(NoteDeckError::InvalidInput(format!(
+                "intervalMinutes must be between {MIN_INTERVAL_MINUTES} and {MAX_INTERVAL_MINUTES}"
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.interval_minutes) * 60)
+    }
+}
+
+/// worker の 1 tick でやること。
+#[cfg_attr(mobile, allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Idle,
+    /// 格納した直後。通知の基準 ID だけ取る。
+    Baseline,
+    /// 間隔が経った。タイムラインと新着通知を取得する。
+    Refresh,
+}
+
+/// `active` (有効かつ格納中) の間の直近の実行時刻から次の Step を決める。
+/// 格納が解けたら `last_run` を捨て、次に格納したときは Baseline から始める。
+#[cfg_attr(mobile, allow(dead_code))]
+fn next_step(
+    last_run: &mut Option<Instant>,
+    active: bool,
+    now: Instant,
+    interval: Duration,
+) -> Step {
+    if !active {
+        *last_run = None;
+        return Step::Idle;
+    }
+    match *last_run {
+        None => {
+            *last_run = Some(now);
+            Step::Baseline
+        }
+        Some(at) if now.duration_since(at) >= interval => {
+            *last_run = Some(now);
+            Step::Refresh
+        }
+        Some(_) => Step::Idle,
+    }
+}
+
+/// 取得した通知を古い順に並べる (サーバーは sinceId 指定時に昇順で返すことがある)。
+/// `key` は (created_at, id)。
+#[cfg_attr(mobile, allow(dead_code))]
+fn chronological<T>(mut items: Vec<T>, key: impl Fn(&T) -> (&str, &str)) -> Vec<T> {
+    items.sort_by(|a, b| key(a).cmp(&key(b)));
+    items
+}
+
+pub struct BackgroundSync {
+    settings: Arc<SettingsStore>,
+    /// 設定の変更で worker の待機を切り上げる。
+    wake: Notify,
+    /// account_id → これより新しい通知だけを出す基準 ID。格納中だけ保持する。
+    #[cfg_attr(mobile, allow(dead_code))]
+    cursors: Mutex<HashMap<String, String>>,
+}
+
+impl BackgroundSync {
+    pub fn new(settings: Arc<SettingsStore>) -> Self {
+        Self {
+            settings,
+            wake: Notify::new(),
+            cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn settings(&self) -> BackgroundSyncSettings {
+        self.settings.read(|s| s.background_sync)
+    }
+
+    fn save(&self, settings: BackgroundSyncSettings) -> Result<(), NoteDeckError> {
+        settings.validate()?;
+        self.settings.update(|s| {
+            s.background_sync = settings;
+            Ok(())
+        })?;
+        self.wake.notify_one();
+        Ok(())
+    }
+}
+
+/// メインウィンドウが非表示 (トレイに格納) か。
+#[cfg(not(mobile))]
+fn main_window_hidden(app: &tauri::AppHandle) -> bool {
+    use tauri::Manager;
+
+    app.get_webview_window("main")
+        .is_some_and(|w| !w.is_visible().unwrap_or(true))
+}
+
+/// 同期の対象にするアカウント (ゲストを除く)。
+#[cfg(not(mobile))]
+async fn account_ids(app: &tauri::AppHandle) -> Vec<String> {
+    use tauri::Manager;
+
+    let app_state = app.state::<crate::commands::AppState>();
+    let db = app_state.db().await;
+    match db.load_accounts() {
+        Ok(accounts) => accounts
+            .into_iter()
+            .filter(|a| a.user_id != crate::account_service::GUEST_USER_ID)
+            .map(|a| a.id)
+            .collect(),
+        Err(e) => {
+            tracing::debug!("[background-sync] failed to load accounts: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// 基準 ID より新しい通知を取得し、OS 通知を出して基準を進める。基準が
+/// まだ無い (格納した直後 / 格納中に追加されたアカウント) なら最新 1 件を
+/// 基準にするだけで通知は出さない。
+#[cfg(not(mobile))]
+async fn sync_notifications(app: &tauri::AppHandle, account_id: &str) -> Result<usize, String> {
+    use notecli::models::TimelineOptions;
+    use tauri::Manager;
+
+    let sync = app.state::<BackgroundSync>();
+    let since_id = sync.cursors.lock().unwrap().get(account_id).cloned();
+    let baseline = since_id.is_none();
+
+    let app_state = app.state::<crate::commands::AppState>();
+    let (db, client) = app_state.ready().await;
+    let (host, token) =
+        crate::commands::get_credentials(&db, account_id).map_err(|e| e.to_string())?;
+    let options = TimelineOptions {
+        limit: Some(if baseline { 1 } else { NOTIFICATION_PAGE }),
+        since_id,
+        ..TimelineOptions::default()
+    };
+    let fetched = app
+        .state::<crate::host_queue::HostQueue>()
+        .run(
+            &host,
+            crate::host_queue::Priority::Background,
+            client.get_notifications(&host, &token, account_id, options),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let fetched = chronological(fetched, |n| (n.created_at.as_str(), n.id.as_str()));
+    let Some(newest) = fetched.last() else {
+        return Ok(0);
+    };
+    sync.cursors
+        .lock()
+        .unwrap()
+        .insert(account_id.to_string(), newest.id.clone());
+    if baseline {
+        return Ok(0);
+    }
+    if let Some(emitter) = app.try_state::<std::sync::Arc<crate::streaming::TauriEmitter>>() {
+        for notification in &fetched {
+            emitter.notify_fetched(notification);
+        }
+    }
+    Ok(fetched.len())
+}
+
+#[cfg(not(mobile))]
+async fn run(app: &tauri::AppHandle, step: Step) {
+    use tauri::Manager;
+
+    if step == Step::Baseline {
+        app.state::<BackgroundSync>()
+            .cursors
+            .lock()
+            .unwrap()
+            .clear();
+    }
+    let home = notecli::models::TimelineType::new("home");
+    for account_id in account_ids(app).await {
+        if step == Step::Refresh {
+            if let Err(e) = crate::scheduler::sync_timeline(app, &account_id, &home, None).await {
+                tracing::debug!(account_id = %account_id, "[background-sync] timeline skipped: {e}");
+            }
+        }
+        match sync_notifications(app, &account_id).await {
+            Ok(0) => {}
+            Ok(n) => {
+                tracing::debug!(account_id = %account_id, "[background-sync] {n} new notifications")
+            }
+            Err(e) => {
+                tracing::debug!(account_id = %account_id, "[background-sync] notifications skipped: {e}")
+            }
+        }
+    }
+}
+
+/// 格納中の同期 worker を起動する。ウィンドウの表示状態は TICK ごとに確かめる。
+#[cfg(not(mobile))]
+pub fn spawn_worker(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        let sync = app.state::<BackgroundSync>();
+        let mut last_run = None;
+        loop {
+            let settings = sync.settings();
+            let active = settings.enabled && main_window_hidden(&app);
+            let step = next_step(&mut last_run, active, Instant::now(), settings.interval());
+            if step != Step::Idle {
+                run(&app, step).await;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(TICK) => {}
+                _ = sync.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// 現在のバックグラウンド同期の設定を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn background_sync_get_settings(
+    state: State<'_, BackgroundSync>,
+) -> Result<BackgroundSyncSettings, NoteDeckError> {
+    Ok(state.settings())
+}
+
+/// バックグラウンド同期の設定を保存し、反映後の値を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn background_sync_set_settings(
+    state: State<'_, BackgroundSync>,
+    settings: BackgroundSyncSettings,
+) -> Result<BackgroundSyncSettings, NoteDeckError> {
+    state.save(settings)?;
+    Ok(state.settings())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn hiding_starts_with_baseline_then_refreshes_each_interval() {
+        let start = Instant::now();
+        let mut last_run = None;
+        assert_eq!(
+            next_step(&mut last_run, true, start, INTERVAL),
+            Step::Baseline
+        );
+        assert_eq!(
+            next_step(
+                &mut last_run,
+                true,
+                start + Duration::from_secs(60),
+                INTERVAL
+            ),
+            Step::Idle
+        );
+        assert_eq!(
+            next_step(&mut last_run, true, start + INTERVAL, INTERVAL),
+            Step::Refresh
+        );
+        assert_eq!(
+            next_step(
+                &mut last_run,
+                true,
+                start + INTERVAL + Duration::from_secs(1),
+                INTERVAL
+            ),
+            Step::Idle
+        );
+    }
+
+    #[test]
+    fn showing_the_window_resets_to_baseline() {
+        let start = Instant::now();
+        let mut last_run = None;
+        next_step(&mut last_run, true, start, INTERVAL);
+        assert_eq!(
+            next_step(
+                &mut last_run,
+                false,
+                start + Duration::from_secs(10),
+                INTERVAL
+            ),
+            Step::Idle
+        );
+        assert_eq!(last_run, None);
+        assert_eq!(
+            next_step(
+                &mut last_run,
+                true,
+                start + Duration::from_secs(20),
+                INTERVAL
+            ),
+            Step::Baseline
+        );
+    }
+
+    #[test]
+    fn orders_notifications_oldest_first() {
+        let items = vec![
+            ("2024-01-01T00:00:02.000Z", "c"),
+            ("2024-01-01T00:00:00.000Z", "a"),
+            ("2024-01-01T00:00:02.000Z", "b"),
+        ];
+        let ordered = chronological(items, |&(at, id)| (at, id));
+        let ids: Vec<_> = ordered.iter().map(|&(_, id)| id).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_interval() {
+        let settings = |interval_minutes| BackgroundSyncSettings {
+            enabled: true,
+            interval_minutes,
+        };
+        assert!(settings(0).validate().is_err());
+        assert!(settings(MAX_INTERVAL_MINUTES + 1).validate().is_err());
+        assert!(settings(5).validate().is_ok());
+    }
+}
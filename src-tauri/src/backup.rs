@@ -0,0 +1,799 @@
+//! 暗号化バックアップ。
+//!
+//! notecli.db (トークン列を空にした複製) と設定ファイル一式 (`notedeck/`)、
+//! バックエンド設定 (`settings.json`) を 1 つの `.ndbak` ファイルにまとめ、パスフレーズから導いた鍵で暗号化して
+//! 書き出す。定期実行は scheduler.rs の `EncryptedBackup` → jobs.rs の `Backup`
+//! ジョブで、保存先フォルダには新しいものから `keep` 個だけ残す。
+//!
+//! 形式: ヘッダ (`MAGIC` | version | PBKDF2 反復回数 | salt | nonce prefix) の後に
+//! 1 MiB ごとの ChaCha20-Poly1305 チャンク (`last u8 | len u32 | 暗号文`) が続く。
+//! ヘッダは全チャンクの AAD にし、nonce にチャンク番号と最終フラグを入れて
+//! 並べ替え・切り詰めを検出する。平文は `name_len u16 | name | len u64 | data`
+//! のエントリ列で、name_len 0 で終わる。
+//!
+//! パスフレーズは OS キーチェーン (`backup.passphrase`) に置く。トークンも
+//! キーチェーン側に残っているので、同じ端末へ戻すなら再ログインは要らない。
+//! プラグインの承認は戻さない (`SettingsFile::from_backup`)。
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use notecli::error::NoteDeckError;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use specta::Type;
+use zeroize::Zeroizing;
+
+use crate::commands::Result;
+use crate::settings::SettingsFile;
+
+const MAGIC: &[u8; 8] = b"NDBACKUP";
+const VERSION: u8 = 1;
+#[cfg(not(test))]
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// テストでは鍵導出を軽くする (形式は同じ)。
+#[cfg(test)]
+const PBKDF2_ITERATIONS: u32 = 1_000;
+/// 読み込み時に受け付ける反復回数の上限 (細工したヘッダで固まらないように)。
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+/// nonce (12 バイト) = prefix | チャンク番号 u32 | 最終フラグ u8
+const NONCE_PREFIX_LEN: usize = NONCE_LEN - 5;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_PREFIX_LEN;
+const CHUNK_LEN: usize = 1024 * 1024;
+const TAG_LEN: usize = 16;
+
+const FILE_PREFIX: &str = "notedeck-backup-";
+const FILE_SUFFIX: &str = ".ndbak";
+const KEYCHAIN_ID: &str = "backup.passphrase";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+const DB_FILE: &str = "notecli.db";
+/// 設定ファイルのディレクトリ (`commands/settings.rs` の SETTINGS_DIR)。
+const SETTINGS_DIR: &str = "notedeck";
+const DB_ENTRY: &str = "notecli.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+/// `app_dir/settings.json` (settings.rs)。古いバックアップには無い。
+const BACKEND_SETTINGS_ENTRY: &str = "backend-settings.json";
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn invalid(e: impl std::fmt::Display) -> NoteDeckError {
+    NoteDeckError::InvalidInput(e.to_string())
+}
+
+struct Header {
+    iterations: u32,
+    salt: [u8; SALT_LEN],
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+}
+
+impl Header {
+    fn generate() -> io::Result<Self> {
+        let rng = SystemRandom::new();
+        let mut header = Self {
+            iterations: PBKDF2_ITERATIONS,
+            salt: [0; SALT_LEN],
+            nonce_prefix: [0; NONCE_PREFIX_LEN],
+        };
+        rng.fill(&mut header.salt)
+            .and_then(|()| rng.fill(&mut header.nonce_prefix))
+            .map_err(|_| io::Error::other("failed to generate random bytes"))?;
+        Ok(header)
+    }
+
+    fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        let (magic, rest) = bytes.split_at_mut(MAGIC.len());
+        magic.copy_from_slice(MAGIC);
+        rest[0] = VERSION;
+        rest[1..5].copy_from_slice(&self.iterations.to_be_bytes());
+        rest[5..5 + SALT_LEN].copy_from_slice(&self.salt);
+        rest[5 + SALT_LEN..].copy_from_slice(&self.nonce_prefix);
+        bytes
+    }
+
+    fn parse(bytes: &[u8; HEADER_LEN]) -> io::Result<Self> {
+        let (magic, rest) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(invalid_data("not a NoteDeck backup file"));
+        }
+        if rest[0] != VERSION {
+            return Err(invalid_data("unsupported backup version"));
+        }
+        let iterations = u32::from_be_bytes(rest[1..5].try_into().unwrap_or_default());
+        if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS {
+            return Err(invalid_data("invalid backup header"));
+        }
+        let mut header = Self {
+            iterations,
+            salt: [0; SALT_LEN],
+            nonce_prefix: [0; NONCE_PREFIX_LEN],
+        };
+        header.salt.copy_from_slice(&rest[5..5 + SALT_LEN]);
+        header.nonce_prefix.copy_from_slice(&rest[5 + SALT_LEN..]);
+        Ok(header)
+    }
+
+    fn key(&self, passphrase: &str) -> io::Result<LessSafeKey> {
+        let iterations = NonZeroU32::new(self.iterations)
+            .ok_or_else(|| invalid_data("invalid backup header"))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &self.salt,
+            passphrase.as_bytes(),
+            key.as_mut(),
+        );
+        let key = UnboundKey::new(&CHACHA20_POLY1305, key.as_ref())
+            .map_err(|_| io::Error::other("failed to create key"))?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    fn nonce(&self, counter: u32, last: bool) -> Nonce {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+        nonce[NONCE_LEN - 1] = u8::from(last);
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+/// 書き込んだ平文をチャンクごとに暗号化する。最後に必ず [`finish`](Self::finish) を呼ぶ。
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    header: Header,
+    aad: [u8; HEADER_LEN],
+    key: LessSafeKey,
+    counter: u32,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(mut inner: W, passphrase: &str) -> io::Result<Self> {
+        let header = Header::generate()?;
+        let aad = header.to_bytes();
+        inner.write_all(&aad)?;
+        Ok(Self {
+            key: header.key(passphrase)?,
+            inner,
+            header,
+            aad,
+            counter: 0,
+            buf: Vec::with_capacity(CHUNK_LEN + TAG_LEN),
+        })
+    }
+
+    fn seal_chunk(&mut self, last: bool) -> io::Result<()> {
+        let nonce = self.header.nonce(self.counter, last);
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("backup is too large"))?;
+        self.key
+            .seal_in_place_append_tag(nonce, Aad::from(&self.aad), &mut self.buf)
+            .map_err(|_| io::Error::other("encryption failed"))?;
+        self.inner.write_all(&[u8::from(last)])?;
+        self.inner
+            .write_all(&(self.buf.len() as u32).to_be_bytes())?;
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// 残りを最終チャンクとして書き、内側の writer を返す。
+    pub fn finish(mut self) -> io::Result<W> {
+        self.seal_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(CHUNK_LEN - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == CHUNK_LEN {
+            self.seal_chunk(false)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// [`EncryptWriter`] で書いたものを復号しながら読む。パスフレーズ違い・改ざん・
+/// 途中切れはいずれも読み取りエラーになる。
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    header: Header,
+    aad: [u8; HEADER_LEN],
+    key: LessSafeKey,
+    counter: u32,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(mut inner: R, passphrase: &str) -> io::Result<Self> {
+        let mut aad = [0u8; HEADER_LEN];
+        inner.read_exact(&mut aad).map_err(truncated)?;
+        let header = Header::parse(&aad)?;
+        Ok(Self {
+            key: header.key(passphrase)?,
+            inner,
+            header,
+            aad,
+            counter: 0,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    fn open_chunk(&mut self) -> io::Result<()> {
+        let mut frame = [0u8; 5];
+        self.inner.read_exact(&mut frame).map_err(truncated)?;
+        let last = match frame[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid_data("corrupted backup")),
+        };
+        let len = u32::from_be_bytes([frame[1], frame[2], frame[3], frame[4]]) as usize;
+        if !(TAG_LEN..=CHUNK_LEN + TAG_LEN).contains(&len) {
+            return Err(invalid_data("corrupted backup"));
+        }
+        self.buf.resize(len, 0);
+        self.inner.read_exact(&mut self.buf).map_err(truncated)?;
+        let nonce = self.header.nonce(self.counter, last);
+        let plain_len = self
+            .key
+            .open_in_place(nonce, Aad::from(&self.aad), &mut self.buf)
+            .map_err(|_| invalid_data("wrong passphrase or corrupted backup"))?
+            .len();
+        self.buf.truncate(plain_len);
+        self.pos = 0;
+        self.counter = self.counter.wrapping_add(1);
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let n = out.len().min(self.buf.len() - self.pos);
+                out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            self.open_chunk()?;
+        }
+    }
+}
+
+fn truncated(e: io::Error) -> io::Error {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        invalid_data("backup is truncated")
+    } else {
+        e
+    }
+}
+
+fn write_entry(out: &mut impl Write, name: &str, len: u64, data: &mut impl Read) -> io::Result<()> {
+    out.write_all(&(name.len() as u16).to_be_bytes())?;
+    out.write_all(name.as_bytes())?;
+    out.write_all(&len.to_be_bytes())?;
+    let copied = io::copy(&mut data.take(len), out)?;
+    if copied != len {
+        return Err(io::Error::other(format!("{name} changed while backing up")));
+    }
+    Ok(())
+}
+
+fn end_entries(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(&0u16.to_be_bytes())
+}
+
+/// エントリを順に `on_entry` へ渡す。読み残した分は読み飛ばし、終端の後は
+/// ストリームの終わり (最終チャンクの認証) まで読み切る。
+fn read_entries<R: Read>(
+    input: &mut R,
+    mut on_entry: impl FnMut(&str, &mut io::Take<&mut R>) -> io::Result<()>,
+) -> io::Result<()> {
+    loop {
+        let mut name_len = [0u8; 2];
+        input.read_exact(&mut name_len).map_err(truncated)?;
+        let name_len = u16::from_be_bytes(name_len) as usize;
+        if name_len == 0 {
+            break;
+        }
+        let mut name = vec![0u8; name_len];
+        input.read_exact(&mut name).map_err(truncated)?;
+        let name = String::from_utf8(name).map_err(|_| invalid_data("corrupted backup"))?;
+        let mut len = [0u8; 8];
+        input.read_exact(&mut len).map_err(truncated)?;
+        let mut data = input.by_ref().take(u64::from_be_bytes(len));
+        on_entry(&name, &mut data)?;
+        io::copy(&mut data, &mut io::sink())?;
+        if data.limit() != 0 {
+            return Err(invalid_data("backup is truncated"));
+        }
+    }
+    io::copy(input, &mut io::sink())?;
+    Ok(())
+}
+
+/// 一時ファイル。失敗やジョブのキャンセル (abort) でも残らないよう drop で消す。
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// `db_path` をトークン列を空にした形で `dest` へ複製する。
+fn snapshot_db(db_path: &Path, dest: &Path) -> rusqlite::Result<()> {
+    let _ = fs::remove_file(dest);
+    let src = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    src.execute("VACUUM INTO ?1", [dest.to_string_lossy()])?;
+    drop(src);
+
+    let conn = Connection::open(dest)?;
+    let tables = conn
+        .prepare(
+            "SELECT m.name FROM sqlite_master m, pragma_table_info(m.name) p \
+             WHERE m.type = 'table' AND p.name = 'token'",
+        )?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for table in tables {
+        let table = table.replace('"', "\"\"");
+        conn.execute(&format!("UPDATE \"{table}\" SET token = ''"), [])?;
+    }
+    // 空き領域に残った元のトークンも消す
+    conn.execute_batch("VACUUM")
+}
+
+fn backup_timestamp(file_name: &str) -> Option<i64> {
+    file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_SUFFIX)?
+        .parse()
+        .ok()
+}
+
+/// `dir` のバックアップを新しいものから `keep` 個残して消し、消した数を返す。
+/// このモジュールの命名のファイルにしか触らない。
+fn prune(dir: &Path, keep: usize) -> io::Result<usize> {
+    let mut backups: Vec<(i64, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let timestamp = backup_timestamp(entry.file_name().to_str()?)?;
+            Some((timestamp, entry.path()))
+        })
+        .collect();
+    backups.sort_unstable_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+    Ok(backups
+        .into_iter()
+        .skip(keep)
+        .filter(|(_, path)| fs::remove_file(path).is_ok())
+        .count())
+}
+
+pub struct BackupOutcome {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub removed: usize,
+}
+
+fn write_backup(
+    dest: &Path,
+    db: &Path,
+    settings: &[u8],
+    backend_settings: Option<&[u8]>,
+    passphrase: &str,
+) -> io::Result<()> {
+    let file = BufWriter::new(File::create(dest)?);
+    let mut out = EncryptWriter::new(file, passphrase)?;
+    let mut db = File::open(db)?;
+    let db_len = db.metadata()?.len();
+    write_entry(&mut out, DB_ENTRY, db_len, &mut db)?;
+    write_entry(
+        &mut out,
+        SETTINGS_ENTRY,
+        settings.len() as u64,
+        &mut &settings[..],
+    )?;
+    if let Some(backend_settings) = backend_settings {
+        write_entry(
+            &mut out,
+            BACKEND_SETTINGS_ENTRY,
+            backend_settings.len() as u64,
+            &mut &backend_settings[..],
+        )?;
+    }
+    end_entries(&mut out)?;
+    out.finish()?
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()
+}
+
+/// `app_dir` の DB と設定を `dir` へ暗号化して書き出し、古いものを整理する。
+pub fn create(
+    app_dir: &Path,
+    dir: &Path,
+    passphrase: &str,
+    keep: usize,
+    now_ms: i64,
+) -> Result<BackupOutcome> {
+    fs::create_dir_all(dir).map_err(invalid)?;
+    let snapshot = TempFile(app_dir.join("notecli.db.backup-tmp"));
+    snapshot_db(&app_dir.join(DB_FILE), &snapshot.0)
+        .map_err(|e| invalid(format!("Failed to snapshot database: {e}")))?;
+    let settings = crate::settings_store::export_bundle(&app_dir.join(SETTINGS_DIR))?;
+    let settings = serde_json::to_vec(&settings).map_err(invalid)?;
+    let backend_settings = match fs::read(crate::settings::file_path(app_dir)) {
+        Ok(raw) => Some(raw),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(invalid(format!("Failed to read backend settings: {e}"))),
+    };
+
+    let path = dir.join(format!("{FILE_PREFIX}{now_ms}{FILE_SUFFIX}"));
+    let part = TempFile(path.with_extension("ndbak.part"));
+    write_backup(
+        &part.0,
+        &snapshot.0,
+        &settings,
+        backend_settings.as_deref(),
+        passphrase,
+    )
+    .map_err(|e| invalid(format!("Failed to write backup: {e}")))?;
+    fs::rename(&part.0, &path).map_err(invalid)?;
+
+    let bytes = fs::metadata(&path).map_err(invalid)?.len();
+    let removed = prune(dir, keep).map_err(invalid)?;
+    Ok(BackupOutcome {
+        path,
+        bytes,
+        removed,
+    })
+}
+
+/// キーチェーンのパスフレーズで [`create`] する (定期バックアップのジョブ用)。
+pub fn create_with_stored_passphrase(
+    app_dir: &Path,
+    dir: &Path,
+    keep: usize,
+    now_ms: i64,
+) -> Result<BackupOutcome> {
+    let passphrase = stored_passphrase().ok_or_else(|| invalid("Backup passphrase is not set"))?;
+    create(app_dir, dir, &passphrase, keep, now_ms)
+}
+
+fn is_sqlite(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; 16];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == b"SQLite format 3\0"),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// バックアップを復号して DB と設定を書き戻し、戻した設定ファイル数を返す。
+/// 全体の復号・検証が済むまで現在の DB / 設定には触らない。呼び出し側は
+/// この後アプリを再起動する (`import_db` と同じ)。
+pub fn restore(app_dir: &Path, src: &Path, passphrase: &str) -> Result<u32> {
+    let staged = TempFile(app_dir.join("notecli.db.restore-tmp"));
+    let mut settings = None;
+    let mut backend_settings = None;
+    let mut has_db = false;
+    let file = File::open(src).map_err(invalid)?;
+    let mut input = DecryptReader::new(BufReader::new(file), passphrase).map_err(invalid)?;
+    read_entries(&mut input, |name, data| {
+        match name {
+            DB_ENTRY => {
+                let mut out = File::create(&staged.0)?;
+                io::copy(data, &mut out)?;
+                out.sync_all()?;
+                has_db = true;
+            }
+            SETTINGS_ENTRY => {
+                let mut raw = Vec::new();
+                data.read_to_end(&mut raw)?;
+                settings = Some(raw);
+            }
+            BACKEND_SETTINGS_ENTRY => {
+                let mut raw = Vec::new();
+                data.read_to_end(&mut raw)?;
+                backend_settings = Some(raw);
+            }
+            // 新しい版で増えたエントリは読み飛ばす
+            _ => {}
+        }
+        Ok(())
+    })
+    .map_err(invalid)?;
+
+    if !has_db || !is_sqlite(&staged.0).map_err(invalid)? {
+        return Err(invalid("Backup does not contain a valid database"));
+    }
+    let bundle: BTreeMap<String, String> = match settings {
+        Some(raw) => serde_json::from_slice(&raw).map_err(invalid)?,
+        None => BTreeMap::new(),
+    };
+    let backend_settings = backend_settings
+        .map(|raw| SettingsFile::from_backup(&raw))
+        .transpose()?;
+
+    fs::copy(&staged.0, app_dir.join(DB_FILE))
+        .map_err(|e| invalid(format!("Failed to restore database: {e}")))?;
+    let _ = fs::remove_file(app_dir.join("notecli.db-wal"));
+    let _ = fs::remove_file(app_dir.join("notecli.db-shm"));
+    crate::settings_store::import_bundle(&app_dir.join(SETTINGS_DIR), &bundle)?;
+    if let Some(backend_settings) = &backend_settings {
+        crate::settings::restore_file(app_dir, backend_settings)?;
+    }
+    Ok(bundle.len() as u32 + u32::from(backend_settings.is_some()))
+}
+
+fn stored_passphrase() -> Option<Zeroizing<String>> {
+    notecli::keychain::get_token(KEYCHAIN_ID)
+        .ok()
+        .flatten()
+        .map(Zeroizing::new)
+}
+
+/// 定期バックアップに使うパスフレーズを OS キーチェーンに保存する。
+#[tauri::command]
+#[specta::specta]
+pub fn backup_set_passphrase(passphrase: String) -> Result<()> {
+    let passphrase = Zeroizing::new(passphrase);
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(invalid(format!(
+            "Passphrase must be at least {MIN_PASSPHRASE_CHARS} characters"
+        )));
+    }
+    notecli::keychain::store_token(KEYCHAIN_ID, &passphrase)
+}
+
+/// パスフレーズが保存済みか。
+#[tauri::command]
+#[specta::specta]
+pub fn backup_has_passphrase() -> bool {
+    stored_passphrase().is_some()
+}
+
+/// バックアップの保存先フォルダを選ぶ。キャンセル時は None。
+#[tauri::command]
+#[specta::specta]
+pub async fn backup_pick_dir(app: tauri::AppHandle) -> Result<Option<String>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let dir = app.dialog().file().blocking_pick_folder();
+    Ok(dir
+        .as_ref()
+        .and_then(|d| d.as_path())
+        .map(|p| p.to_string_lossy().into_owned()))
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupRestoreResult {
+    /// 書き戻した設定ファイルの数
+    pub settings_files: u32,
+}
+
+/// 選んだ `.ndbak` から復元する。`passphrase` が None ならキーチェーンの
+/// ものを使う。キャンセル時は None。成功したらフロントはアプリを再起動する。
+#[tauri::command]
+#[specta::specta]
+pub async fn backup_import(
+    app: tauri::AppHandle,
+    passphrase: Option<String>,
+) -> Result<Option<BackupRestoreResult>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let passphrase = match passphrase.filter(|p| !p.is_empty()) {
+        Some(p) => Zeroizing::new(p),
+        None => stored_passphrase().ok_or_else(|| invalid("Backup passphrase is not set"))?,
+    };
+    let app_dir = crate::app_dir::resolve_app_dir(&app).map_err(invalid)?;
+
+    let src = app
+        .dialog()
+        .file()
+        .add_filter("NoteDeck Backup", &["ndbak"])
+        .blocking_pick_file();
+    let Some(src) = src else {
+        return Ok(None); // user cancelled
+    };
+    let src = src
+        .as_path()
+        .ok_or_else(|| invalid("Invalid source path"))?
+        .to_path_buf();
+
+    let settings_files = tokio::task::spawn_blocking(move || restore(&app_dir, &src, &passphrase))
+        .await
+        .map_err(invalid)??;
+    Ok(Some(BackupRestoreResult { settings_files }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(plain: &[u8], passphrase: &str) -> Vec<u8> {
+        let mut out = EncryptWriter::new(Vec::new(), passphrase).unwrap();
+        out.write_all(plain).unwrap();
+        out.finish().unwrap()
+    }
+
+    fn decrypt(data: &[u8], passphrase: &str) -> io::Result<Vec<u8>> {
+        let mut plain = Vec::new();
+        DecryptReader::new(data, passphrase)?.read_to_end(&mut plain)?;
+        Ok(plain)
+    }
+
+    #[test]
+    fn roundtrips_across_chunks() {
+        let plain: Vec<u8> = (0..CHUNK_LEN * 2 + 123).map(|i| (i % 251) as u8).collect();
+        let data = encrypt(&plain, "correct horse");
+        assert_eq!(decrypt(&data, "correct horse").unwrap(), plain);
+        assert_eq!(decrypt(&encrypt(b"", "pw"), "pw").unwrap(), b"");
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase_tampering_and_truncation() {
+        let plain = vec![7u8; CHUNK_LEN + 10];
+        let data = encrypt(&plain, "correct horse");
+        assert!(decrypt(&data, "wrong horse").is_err());
+
+        let mut tampered = data.clone();
+        tampered[HEADER_LEN + 100] ^= 1;
+        assert!(decrypt(&tampered, "correct horse").is_err());
+
+        // 最終チャンクを丸ごと落としても、途中で切っても検出する
+        let first_chunk_end = HEADER_LEN + 5 + CHUNK_LEN + TAG_LEN;
+        assert!(decrypt(&data[..first_chunk_end], "correct horse").is_err());
+        assert!(decrypt(&data[..data.len() - 1], "correct horse").is_err());
+    }
+
+    #[test]
+    fn container_entries_roundtrip() {
+        let mut plain = Vec::new();
+        write_entry(&mut plain, "a", 3, &mut &b"abc"[..]).unwrap();
+        write_entry(&mut plain, "unknown", 2, &mut &b"zz"[..]).unwrap();
+        end_entries(&mut plain).unwrap();
+
+        let mut entries = Vec::new();
+        read_entries(&mut &plain[..], |name, data| {
+            if name == "a" {
+                let mut s = String::new();
+                data.read_to_string(&mut s)?;
+                entries.push(s);
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(entries, ["abc"]);
+        assert!(read_entries(&mut &plain[..plain.len() - 3], |_, _| Ok(())).is_err());
+    }
+
+    #[test]
+    fn backup_strips_tokens_and_restores() {
+        let app_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let db_path = app_dir.path().join(DB_FILE);
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE accounts (id TEXT, token TEXT NOT NULL);
+             INSERT INTO accounts VALUES ('a1', 'secret-token');",
+        )
+        .unwrap();
+        drop(conn);
+        let settings = app_dir.path().join(SETTINGS_DIR);
+        fs::create_dir_all(&settings).unwrap();
+        fs::write(settings.join("settings.json5"), "{ theme: 'dark' }").unwrap();
+
+        let outcome = create(app_dir.path(), backup_dir.path(), "passphrase", 3, 1).unwrap();
+        let raw = fs::read(&outcome.path).unwrap();
+        assert!(!raw.windows(12).any(|w| w == b"secret-token"));
+
+        fs::remove_file(&db_path).unwrap();
+        fs::remove_file(settings.join("settings.json5")).unwrap();
+        assert!(restore(app_dir.path(), &outcome.path, "wrong-passphrase").is_err());
+        assert!(!db_path.exists());
+
+        assert_eq!(
+            restore(app_dir.path(), &outcome.path, "passphrase").unwrap(),
+            1
+        );
+        let conn = Connection::open(&db_path).unwrap();
+        let token: String = conn
+            .query_row("SELECT token FROM accounts WHERE id = 'a1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(token, "");
+        assert_eq!(
+            fs::read_to_string(settings.join("settings.json5")).unwrap(),
+            "{ theme: 'dark' }"
+        );
+    }
+
+    #[test]
+    fn backend_settings_survive_restore_without_plugin_grants() {
+        let app_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Connection::open(app_dir.path().join(DB_FILE))
+            .unwrap()
+            .execute_batch("CREATE TABLE accounts (id TEXT, token TEXT NOT NULL);")
+            .unwrap();
+        let store = crate::settings::SettingsStore::load(app_dir.path());
+        store
+            .update(|s| {
+                s.cache.image_ttl_days = 3;
+                s.launch.start_in_tray = true;
+                s.plugins.insert("example".to_string(), Default::default());
+                Ok(())
+            })
+            .unwrap();
+
+        let outcome = create(app_dir.path(), backup_dir.path(), "passphrase", 3, 1).unwrap();
+        fs::remove_file(crate::settings::file_path(app_dir.path())).unwrap();
+        assert_eq!(
+            restore(app_dir.path(), &outcome.path, "passphrase").unwrap(),
+            1
+        );
+
+        let restored = crate::settings::SettingsStore::load(app_dir.path()).read(|s| s.clone());
+        assert_eq!(restored.cache.image_ttl_days, 3);
+        assert!(restored.launch.start_in_tray);
+        assert!(restored.plugins.is_empty());
+    }
+
+    #[test]
+    fn prune_keeps_newest_backups_only() {
+        let dir = tempfile::tempdir().unwrap();
+        for ts in [10, 30, 20] {
+            fs::write(
+                dir.path().join(format!("{FILE_PREFIX}{ts}{FILE_SUFFIX}")),
+                b"",
+            )
+            .unwrap();
+        }
+        fs::write(dir.path().join("unrelated.ndbak"), b"").unwrap();
+        assert_eq!(prune(dir.path(), 2).unwrap(), 1);
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "notedeck-backup-20.ndbak",
+                "notedeck-backup-30.ndbak",
+                "unrelated.ndbak"
+            ]
+        );
+    }
+}
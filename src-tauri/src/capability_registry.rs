@@ -0,0 +1,633 @@
+//! バックエンド側の capability registry。
+//!
+//! WebView を介さずに実行できる操作 (ウィンドウ操作 / クイック投稿 / ストリーム
+//! 切り替え / ジョブ投入など) を id・表示名・引数スキーマ・ハンドラの組で
+//! 1 か所に定義する。IPC (`capability_execute`)・HTTP
+//! (`/api/capabilities/{id}/execute`)・トレイ・グローバルショートカットは
+//! すべてここを引き、見つからない id だけを従来どおり query_bridge 経由で
+//! フロントの dispatcher に回す。
+//!
+//! フロントは起動時に `capability_list` を読んで自身の registry (コマンド
+//! パレット / AI / slash) にミラー登録するので、定義はこのファイルだけが持つ。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use futures_util::future::{BoxFuture, FutureExt};
+use notecli::error::NoteDeckError;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 引数 / 戻り値の型。フロントの `ParameterDef['type']` / `ReturnTypeDef['type']` と同じ語彙。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+    Void,
+}
+
+impl ValueType {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Boolean => value.is_boolean(),
+            Self::Object => value.is_object(),
+            Self::Array => value.is_array(),
+            Self::Void => value.is_null(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamDef {
+    pub r#type: ValueType,
+    pub description: String,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnDef {
+    pub r#type: ValueType,
+    pub description: Option<String>,
+}
+
+/// `capability_list` / `GET /api/capabilities` が返すメタデータ。
+/// フロントの `capabilities/list` と同じ形に `icon` / `external` / `palette` を足したもの。
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityInfo {
+    pub id: String,
+    /// AI tool 名と同じ sanitized 形式 (`.` → `_`)。
+    pub name: String,
+    pub label: String,
+    /// Tabler icon 名。
+    pub icon: String,
+    pub category: String,
+    pub description: String,
+    pub params: BTreeMap<String, ParamDef>,
+    pub returns: ReturnDef,
+    pub permissions: Vec<String>,
+    pub requires_confirmation: bool,
+    /// HTTP API (external principal) から実行できるか。
+    pub external: bool,
+    /// コマンドパレットに表示するか (フロント側に同等のコマンドがあるものは出さない)。
+    pub palette: bool,
+}
+
+/// 実行の要求元。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Caller {
+    /// IPC / トレイ / ショートカット (本人の操作)。
+    Local,
+    /// HTTP API (フロントの dispatcher と同じく external principal)。
+    External,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapabilityError {
+    Unknown(String),
+    PermissionDenied(Vec<String>),
+    InvalidParams(String),
+    Failed(String),
+}
+
+impl CapabilityError {
+    /// フロントの DispatchResult と同じコード。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unknown(_) => "unknown_capability",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::InvalidParams(_) => "preflight_failed",
+            Self::Failed(_) => "execute_failed",
+        }
+    }
+}
+
+impl fmt::Display for CapabilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(id) => write!(f, "Unknown capability: {id}"),
+            Self::PermissionDenied(required) if required.is_empty() => {
+                f.write_str("denied for external principal: local only")
+            }
+            Self::PermissionDenied(required) => write!(
+                f,
+                "denied for external principal: required [{}]",
+                required.join(", ")
+            ),
+            Self::InvalidParams(message) | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for CapabilityError {}
+
+type Handler = fn(AppHandle, Map<String, Value>) -> BoxFuture<'static, Result<Value, String>>;
+
+struct Param {
+    name: &'static str,
+    kind: ValueType,
+    description: &'static str,
+    optional: bool,
+}
+
+struct Capability {
+    id: &'static str,
+    label: &'static str,
+    icon: &'static str,
+    category: &'static str,
+    description: &'static str,
+    params: &'static [Param],
+    returns: ValueType,
+    permissions: &'static [&'static str],
+    external: bool,
+    palette: bool,
+    /// モバイルでは登録しない (ウィンドウ / トレイ前提の操作)。
+    desktop_only: bool,
+    handler: Handler,
+}
+
+impl Capability {
+    fn info(&self) -> CapabilityInfo {
+        CapabilityInfo {
+            id: self.id.to_string(),
+            name: self.id.replace('.', "_"),
+            label: self.label.to_string(),
+            icon: self.icon.to_string(),
+            category: self.category.to_string(),
+            description: self.description.to_string(),
+            params: self
+                .params
+                .iter()
+                .map(|p| {
+                    (
+                        p.name.to_string(),
+                        ParamDef {
+                            r#type: p.kind,
+                            description: p.description.to_string(),
+                            optional: p.optional,
+                        },
+                    )
+                })
+                .collect(),
+            returns: ReturnDef {
+                r#type: self.returns,
+                description: None,
+            },
+            permissions: self.permissions.iter().map(|p| p.to_string()).collect(),
+            requires_confirmation: false,
+            external: self.external,
+            palette: self.palette,
+        }
+    }
+
+    /// params を object に正規化し、宣言と照合する (null は引数なし扱い)。
+    fn check_params(&self, params: Value) -> Result<Map<String, Value>, CapabilityError> {
+        let params = match params {
+            Value::Null => Map::new(),
+            Value::Object(map) => map,
+            _ => {
+                return Err(CapabilityError::InvalidParams(
+                    "params must be an object".to_string(),
+                ))
+            }
+        };
+        for param in self.params {
+            match params.get(param.name) {
+                None | Some(Value::Null) if param.optional => {}
+                None | Some(Value::Null) => {
+                    return Err(CapabilityError::InvalidParams(format!(
+                        "missing param: {}",
+                        param.name
+                    )))
+                }
+                Some(value) if !param.kind.accepts(value) => {
+                    return Err(CapabilityError::InvalidParams(format!(
+                        "param {} must be {:?}",
+                        param.name, param.kind
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(params)
+    }
+
+    fn authorize(&self, caller: Caller) -> Result<(), CapabilityError> {
+        if caller == Caller::Local {
+            return Ok(());
+        }
+        if !self.external {
+            return Err(CapabilityError::PermissionDenied(Vec::new()));
+        }
+        let denied: Vec<String> = self
+            .permissions
+            .iter()
+            .filter(|k| !crate::permissions_gate::is_granted(k))
+            .map(|k| k.to_string())
+            .collect();
+        if denied.is_empty() {
+            Ok(())
+        } else {
+            Err(CapabilityError::PermissionDenied(denied))
+        }
+    }
+}
+
+const ACCOUNT_ID_PARAM: Param = Param {
+    name: "accountId",
+    kind: ValueType::String,
+    description: "対象アカウントの ID",
+    optional: false,
+};
+
+static CAPABILITIES: &[Capability] = &[
+    Capability {
+        id: "window.show",
+        label: "メインウィンドウを表示",
+        icon: "app-window",
+        category: "window",
+        description: "メインウィンドウを表示して前面に出す。",
+        params: &[],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: true,
+        palette: false,
+        desktop_only: true,
+        handler: window_show,
+    },
+    Capability {
+        id: "window.toggle",
+        label: "メインウィンドウの表示切替",
+        icon: "eye-off",
+        category: "window",
+        description: "メインウィンドウが表示中なら隠し、隠れていれば表示する (Boss Key)。",
+        params: &[],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: false,
+        palette: false,
+        desktop_only: true,
+        handler: window_toggle,
+    },
+    Capability {
+        id: "quickPost.open",
+        label: "クイック投稿ウィンドウを開く",
+        icon: "pencil",
+        category: "note",
+        description: "クイック投稿のミニウィンドウを開く。accountId を渡すとそのアカウントで開く。",
+        params: &[Param {
+            name: "accountId",
+            kind: ValueType::String,
+            description: "投稿に使うアカウントの ID (省略時はアクティブアカウント)",
+            optional: true,
+        }],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: true,
+        palette: true,
+        desktop_only: true,
+        handler: quick_post_open,
+    },
+    Capability {
+        id: "app.toggleOffline",
+        label: "オフラインモード切替",
+        icon: "wifi-off",
+        category: "general",
+        description: "オフラインモードを切り替える。",
+        params: &[],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: false,
+        palette: false,
+        desktop_only: false,
+        handler: toggle_offline,
+    },
+    Capability {
+        id: "app.toggleRealtime",
+        label: "リアルタイムモード切替",
+        icon: "bolt",
+        category: "general",
+        description: "リアルタイムモードを切り替える。",
+        params: &[],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: false,
+        palette: false,
+        desktop_only: false,
+        handler: toggle_realtime,
+    },
+    Capability {
+        id: "streams.toggleMode",
+        label: "ストリームの realtime / polling 切替",
+        icon: "antenna-bars-5",
+        category: "account",
+        description: "接続中のストリームの realtime / polling を反転する。未接続なら何もしない。",
+        params: &[ACCOUNT_ID_PARAM],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: false,
+        palette: false,
+        desktop_only: true,
+        handler: toggle_stream_mode,
+    },
+    Capability {
+        id: "imageCache.prune",
+        label: "画像キャッシュを整理",
+        icon: "photo-x",
+        category: "general",
+        description: "期限切れの画像キャッシュを消すバックグラウンドジョブを積み、ジョブを返す。",
+        params: &[],
+        returns: ValueType::Object,
+        permissions: &["performance.write"],
+        external: true,
+        palette: true,
+        desktop_only: false,
+        handler: prune_image_cache,
+    },
+    Capability {
+        id: "app.quit",
+        label: "NoteDeck を終了",
+        icon: "power",
+        category: "general",
+        description: "ウィンドウ位置を保存して NoteDeck を終了する。",
+        params: &[],
+        returns: ValueType::Void,
+        permissions: &[],
+        external: false,
+        palette: true,
+        desktop_only: true,
+        handler: app_quit,
+    },
+];
+
+fn available() -> impl Iterator<Item = &'static Capability> {
+    CAPABILITIES
+        .iter()
+        .filter(|c| !(cfg!(mobile) && c.desktop_only))
+}
+
+/// dotted (`window.show`) / sanitized (`window_show`) のどちらでも引ける。
+fn find(id: &str) -> Option<&'static Capability> {
+    available().find(|c| c.id == id || c.id.replace('.', "_") == id)
+}
+
+/// バックエンドに定義された capability か。
+pub fn contains(id: &str) -> bool {
+    find(id).is_some()
+}
+
+pub fn list() -> Vec<CapabilityInfo> {
+    available().map(Capability::info).collect()
+}
+
+/// capability を実行する。
+pub async fn execute(
+    app: &AppHandle,
+    id: &str,
+    params: Value,
+    caller: Caller,
+) -> Result<Value, CapabilityError> {
+    let capability = find(id).ok_or_else(|| CapabilityError::Unknown(id.to_string()))?;
+    capability.authorize(caller)?;
+    let params = capability.check_params(params)?;
+    (capability.handler)(app.clone(), params)
+        .await
+        .map_err(CapabilityError::Failed)
+}
+
+/// トレイ / ショートカットから結果を待たずに実行する。失敗はログに残す。
+#[cfg_attr(mobile, allow(dead_code))]
+pub fn spawn_local(app: &AppHandle, id: &'static str, params: Value) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = execute(&app, id, params, Caller::Local).await {
+            tracing::warn!("[capability] {id} failed: {e}");
+        }
+    });
+}
+
+fn done() -> BoxFuture<'static, Result<Value, String>> {
+    futures_util::future::ready(Ok(Value::Null)).boxed()
+}
+
+fn fail(message: impl Into<String>) -> BoxFuture<'static, Result<Value, String>> {
+    futures_util::future::ready(Err(message.into())).boxed()
+}
+
+fn window_show(app: AppHandle, _: Map<String, Value>) -> BoxFuture<'static, Result<Value, String>> {
+    match app.get_webview_window("main") {
+        Some(w) => {
+            let _ = w.show();
+            let _ = w.set_focus();
+            done()
+        }
+        None => fail("main window is closed"),
+    }
+}
+
+fn window_toggle(
+    app: AppHandle,
+    _: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    let Some(w) = app.get_webview_window("main") else {
+        return fail("main window is closed");
+    };
+    if w.is_visible().unwrap_or(false) {
+        #[cfg(not(mobile))]
+        crate::window_geometry::capture(&w);
+        let _ = w.hide();
+    } else {
+        let _ = w.show();
+        let _ = w.set_focus();
+    }
+    done()
+}
+
+fn quick_post_open(
+    app: AppHandle,
+    params: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    #[cfg(not(mobile))]
+    {
+        let account_id = params.get("accountId").and_then(Value::as_str);
+        match crate::quick_post::open(&app, account_id) {
+            Ok(()) => done(),
+            Err(e) => fail(format!("failed to open quick post: {e}")),
+        }
+    }
+    #[cfg(mobile)]
+    {
+        let _ = (app, params);
+        fail("quick post is desktop only")
+    }
+}
+
+fn toggle_offline(
+    app: AppHandle,
+    _: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    match app.emit("nd:toggle-offline-mode", ()) {
+        Ok(()) => done(),
+        Err(e) => fail(e.to_string()),
+    }
+}
+
+fn toggle_realtime(
+    app: AppHandle,
+    _: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    match app.emit("nd:toggle-realtime-mode", ()) {
+        Ok(()) => done(),
+        Err(e) => fail(e.to_string()),
+    }
+}
+
+fn toggle_stream_mode(
+    app: AppHandle,
+    params: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    let account_id = params
+        .get("accountId")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    async move {
+        #[cfg(not(mobile))]
+        {
+            let result = crate::tray::toggle_stream(&app, &account_id).await;
+            // 失敗時もトレイのチェックを実状態へ戻す
+            crate::tray::sync_stream(&app, &account_id);
+            result.map(|()| Value::Null).map_err(|e| e.to_string())
+        }
+        #[cfg(mobile)]
+        {
+            let _ = (app, account_id);
+            Err("stream toggle is desktop only".to_string())
+        }
+    }
+    .boxed()
+}
+
+fn prune_image_cache(
+    app: AppHandle,
+    _: Map<String, Value>,
+) -> BoxFuture<'static, Result<Value, String>> {
+    let result = crate::jobs::submit(&app, crate::jobs::JobSpec::PruneImageCache)
+        .map_err(|e| e.to_string())
+        .and_then(|job| serde_json::to_value(job).map_err(|e| e.to_string()));
+    futures_util::future::ready(result).boxed()
+}
+
+fn app_quit(app: AppHandle, _: Map<String, Value>) -> BoxFuture<'static, Result<Value, String>> {
+    #[cfg(not(mobile))]
+    if let Some(w) = app.get_webview_window("main") {
+        crate::window_geometry::capture(&w);
+    }
+    app.exit(0);
+    done()
+}
+
+/// バックエンドに定義された capability の一覧。
+#[tauri::command]
+#[specta::specta]
+pub fn capability_list() -> Vec<CapabilityInfo> {
+    list()
+}
+
+/// バックエンドの capability を実行する。権限はフロントの dispatcher が
+/// 照合済みとして扱う (Local)。
+#[tauri::command]
+#[specta::specta]
+pub async fn capability_execute(
+    app: AppHandle,
+    id: String,
+    params: Option<Value>,
+) -> Result<Value, NoteDeckError> {
+    execute(&app, &id, params.unwrap_or(Value::Null), Caller::Local)
+        .await
+        .map_err(|e| NoteDeckError::InvalidInput(format!("{}: {e}", e.code())))
+}
+
+/// HTTP の実行結果を DispatchResult (`{ok, result}` / `{ok, code, error}`) にする。
+pub fn dispatch_result(result: Result<Value, CapabilityError>) -> Value {
+    match result {
+        Ok(result) => json!({ "ok": true, "result": result }),
+        Err(e) => json!({ "ok": false, "code": e.code(), "error": e.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_unique_and_sanitized_lookup_works() {
+        let mut ids: Vec<_> = CAPABILITIES.iter().map(|c| c.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), CAPABILITIES.len());
+
+        assert!(contains("imageCache.prune"));
+        assert!(contains("imageCache_prune"));
+        assert!(!contains("notes.create"));
+    }
+
+    #[test]
+    fn info_mirrors_the_frontend_list_shape() {
+        let info = find("streams.toggleMode").unwrap().info();
+        let value = serde_json::to_value(&info).unwrap();
+        assert_eq!(value["name"], "streams_toggleMode");
+        assert_eq!(value["params"]["accountId"]["type"], "string");
+        assert_eq!(value["params"]["accountId"]["optional"], false);
+        assert_eq!(value["returns"]["type"], "void");
+        assert_eq!(value["requiresConfirmation"], false);
+    }
+
+    #[test]
+    fn params_are_checked_against_the_schema() {
+        let toggle = find("streams.toggleMode").unwrap();
+        assert!(toggle.check_params(json!({ "accountId": "a" })).is_ok());
+        assert!(matches!(
+            toggle.check_params(Value::Null),
+            Err(CapabilityError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            toggle.check_params(json!({ "accountId": 1 })),
+            Err(CapabilityError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            toggle.check_params(json!([])),
+            Err(CapabilityError::InvalidParams(_))
+        ));
+
+        let quick_post = find("quickPost.open").unwrap();
+        assert!(quick_post.check_params(Value::Null).is_ok());
+        assert!(quick_post
+            .check_params(json!({ "accountId": null }))
+            .is_ok());
+    }
+
+    #[test]
+    fn external_callers_are_limited() {
+        let quit = find("app.quit").unwrap();
+        assert!(quit.authorize(Caller::Local).is_ok());
+        assert_eq!(
+            quit.authorize(Caller::External),
+            Err(CapabilityError::PermissionDenied(Vec::new()))
+        );
+        assert!(find("window.show")
+            .unwrap()
+            .authorize(Caller::External)
+            .is_ok());
+    }
+}
@@ -23,8 +23,9 @@ pub async fn delete_account(
     id: String,
 ) -> Result<()> {
     let db = app_state.db().await;
-    account_service::delete(&db, &id)?;
+    account_service::delete(&app, &db, &id)?;
     export_account_list(&app, &db);
+    crate::tray::refresh_accounts(&app, &db);
     Ok(())
 }
 
@@ -37,8 +38,9 @@ pub async fn logout_account(
     id: String,
 ) -> Result<()> {
     let db = app_state.db().await;
-    account_service::logout(&db, &id)?;
+    account_service::logout(&app, &db, &id)?;
     export_account_list(&app, &db);
+    crate::tray::refresh_accounts(&app, &db);
     Ok(())
 }
 
@@ -183,6 +185,7 @@ pub async fn create_guest_account(
     let host = validate_host(&host)?;
     let account = account_service::create_guest(&db, host, software)?;
     export_account_list(&app, &db);
+    crate::tray::refresh_accounts(&app, &db);
     Ok(AccountPublic::new(&account, false))
 }
 
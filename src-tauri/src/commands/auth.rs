@@ -52,6 +52,8 @@ pub async fn auth_complete_and_save(
             .await?;
 
     export_account_list(&app, &db);
+    crate::tray::refresh_accounts(&app, &db);
+    crate::avatar_warm::spawn(&app, Some(vec![saved.id.clone()]));
 
     Ok(saved)
 }
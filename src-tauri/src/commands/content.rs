@@ -6,6 +6,8 @@ use notecli::error::NoteDeckError;
 use notecli::models::{GalleryPost, Page, ServerEmoji};
 
 use super::{AppState, get_credentials, get_credentials_or_anon, Result, typed_request, validate_host};
+use crate::emoji_cache::{EmojiCache, EmojiDiff};
+use crate::media_gate::MediaGate;
 
 // --- Server metadata ---
 
@@ -70,14 +72,91 @@ pub async fn api_update_user_setting(
     client.update_user_setting(&host, &token, &key, value).await
 }
 
+/// アカウントのサーバーの絵文字一覧。絵文字キャッシュを通し、返した一覧を
+/// 以後の差分同期 (`nd:emojis-updated`) の基準にする。
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_server_emojis(
     app_state: State<'_, AppState>,
+    emoji_cache: State<'_, EmojiCache>,
     account_id: String,
 ) -> Result<Vec<ServerEmoji>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.get_server_emojis(&host, &token).await
+    emoji_cache.list(&client, &host, &token).await
+}
+
+/// `api_resolve_emojis` が 1 回に受け付ける参照数の上限。
+const MAX_EMOJI_REFS: usize = 1000;
+
+/// `shortcode@host` (host 省略 / `.` は自ホスト) の一覧を絵文字キャッシュで
+/// 解決し、入力文字列 → URL の map を返す。未取得のホストはホストごとに 1 回
+/// だけ取りに行く。解決できなかった参照は含まれない。
+#[tauri::command]
+#[specta::specta]
+pub async fn api_resolve_emojis(
+    app_state: State<'_, AppState>,
+    emoji_cache: State<'_, EmojiCache>,
+    account_id: String,
+    refs: Vec<String>,
+) -> Result<HashMap<String, String>> {
+    if refs.len() > MAX_EMOJI_REFS {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Too many emoji refs (max {MAX_EMOJI_REFS})"
+        )));
+    }
+    let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
+    Ok(emoji_cache.resolve(&client, &host, &token, &refs).await)
+}
+
+/// `search_emojis` が返す件数の上限。
+const MAX_EMOJI_SEARCH_RESULTS: u32 = 200;
+
+/// アカウントのサーバーのカスタム絵文字を名前 / エイリアスで検索する
+/// (完全一致 → 前方一致 → 部分一致 → あいまい一致の順)。一覧は絵文字
+/// キャッシュのものを使い、未取得なら 1 回だけ取りに行く。
+#[tauri::command]
+#[specta::specta]
+pub async fn search_emojis(
+    app_state: State<'_, AppState>,
+    emoji_cache: State<'_, EmojiCache>,
+    account_id: String,
+    query: String,
+    limit: u32,
+) -> Result<Vec<ServerEmoji>> {
+    if limit > MAX_EMOJI_SEARCH_RESULTS {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Too many results requested (max {MAX_EMOJI_SEARCH_RESULTS})"
+        )));
+    }
+    let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
+    Ok(emoji_cache
+        .search(&client, &host, &token, &query, limit as usize)
+        .await)
+}
+
+/// アカウントのサーバーの絵文字一覧をすぐに取り直す。差分があれば
+/// `nd:emojis-updated` を emit して返す (差分が無い / 取得に失敗したら null)。
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_emojis(
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+    emoji_cache: State<'_, EmojiCache>,
+    account_id: String,
+) -> Result<Option<EmojiDiff>> {
+    let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
+    let diff = emoji_cache.refresh(&client, &host, &token).await;
+    if let Some(diff) = &diff {
+        crate::emoji_cache::emit_diff(&app, diff);
+    }
+    Ok(diff)
+}
+
+/// `nd:emojis-updated` の差分を当てたことを返す。以後の差分はこの版から取る。
+#[tauri::command]
+#[specta::specta]
+pub fn ack_emojis(emoji_cache: State<'_, EmojiCache>, host: String, version: u32) {
+    emoji_cache.ack(&host, version);
 }
 
 #[tauri::command]
@@ -396,12 +475,15 @@ pub async fn api_get_drive_files(
 #[tauri::command]
 #[specta::specta]
 pub async fn api_delete_drive_file(
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
     account_id: String,
     file_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.delete_drive_file(&host, &token, &file_id).await
+    client.delete_drive_file(&host, &token, &file_id).await?;
+    crate::upload_history::forget(&app, &account_id, &file_id);
+    Ok(())
 }
 
 // --- Drive: 整理（フォルダ CRUD・ファイル移動/リネーム） ---
@@ -459,11 +541,16 @@ pub async fn api_delete_drive_folder(
     Ok(())
 }
 
+/// ドライブファイルのキャプション (alt テキスト) の上限。Misskey の
+/// `DB_MAX_IMAGE_COMMENT_LENGTH` と同じ。
+const MAX_DRIVE_FILE_COMMENT_CHARS: usize = 512;
+
 /// drive/files/update。None のフィールドは送信されず変更されない。
 /// comment は空文字で null 送信 = alt テキストのクリア (#753)。
 #[tauri::command]
 #[specta::specta]
 pub async fn api_update_drive_file(
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
     account_id: String,
     file_id: String,
@@ -471,6 +558,14 @@ pub async fn api_update_drive_file(
     comment: Option<String>,
     is_sensitive: Option<bool>,
 ) -> Result<()> {
+    if comment
+        .as_ref()
+        .is_some_and(|c| c.chars().count() > MAX_DRIVE_FILE_COMMENT_CHARS)
+    {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Caption is too long (max {MAX_DRIVE_FILE_COMMENT_CHARS} characters)"
+        )));
+    }
     let (client, host, token) = app_state.authed(&account_id).await?;
     let mut params = serde_json::json!({ "fileId": file_id });
     let obj = params.as_object_mut().expect("params is an object");
@@ -488,9 +583,14 @@ pub async fn api_update_drive_file(
     if let Some(is_sensitive) = is_sensitive {
         obj.insert("isSensitive".into(), is_sensitive.into());
     }
-    client
+    let updated = client
         .request(&host, &token, "drive/files/update", params)
         .await?;
+    // 最近のアップロードに残っていれば新しい名前 / センシティブ指定に揃える
+    match super::upload::drive_file_from_response(updated) {
+        Ok(file) => crate::upload_history::refresh(&app, &account_id, &file),
+        Err(e) => tracing::warn!("[uploads] unexpected drive/files/update response: {e}"),
+    }
     Ok(())
 }
 
@@ -539,11 +639,13 @@ pub async fn api_update_flash(
 #[specta::specta]
 pub async fn api_get_note_raw(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     params: serde_json::Value,
 ) -> Result<serde_json::Value> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.request(&host, &token, "notes/show", params).await
+    let note = client.request(&host, &token, "notes/show", params).await?;
+    Ok(gate.gate(note))
 }
 
 #[tauri::command]
@@ -565,6 +667,7 @@ pub async fn api_get_drive_file(
 #[specta::specta]
 pub async fn api_request(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     endpoint: String,
     params: Option<serde_json::Value>,
@@ -587,14 +690,15 @@ pub async fn api_request(
     // (charts/*, meta, users/show 等) を呼び出せるようにする。
     // 認証必須エンドポイントはサーバーが 401 を返し上位でハンドリングされる。
     let (host, token) = get_credentials_or_anon(&db, &account_id)?;
-    client
+    let data = client
         .request(
             &host,
             &token,
             &endpoint,
             params.unwrap_or(serde_json::json!({})),
         )
-        .await
+        .await?;
+    Ok(gate.gate(data))
 }
 
 // --- Theme ---
@@ -3,10 +3,15 @@ use tauri::State;
 
 use notecli::error::NoteDeckError;
 
-use super::{get_credentials, AppState, Result};
+use super::{get_host, AppState, Result};
 
 // --- OGP Preview ---
 
+/// Fetch a URL preview. With an account it goes through that account's
+/// server (`/url?url=`, the summaly proxy the web client uses), so the linked
+/// site sees the server rather than this machine; see
+/// `OgpCache::get_ogp_via_proxy` for when it still fetches locally. Without an
+/// account: plugins → direct HTML parse.
 #[tauri::command]
 #[specta::specta]
 pub async fn fetch_ogp(
@@ -15,23 +20,20 @@ pub async fn fetch_ogp(
     url: String,
     account_id: Option<String>,
 ) -> Result<crate::ogp::OgpData> {
-    let db = app_state.db().await;
     if url.len() > 2048 {
         return Err(NoteDeckError::InvalidInput("URL too long".to_string()));
     }
-
-    // With server context: plugins → server → direct HTML parse
-    // Without: plugins → direct HTML parse
-    let result = if let Some(ref aid) = account_id {
-        if let Ok((host, token)) = get_credentials(&db, aid) {
-            ogp_cache.get_ogp_via_server(&url, &host, &token).await
-        } else {
-            ogp_cache.get_ogp(&url).await
+    let host = match account_id {
+        Some(ref aid) => {
+            let db = app_state.db().await;
+            Some(get_host(&db, aid)?)
         }
-    } else {
-        ogp_cache.get_ogp(&url).await
+        None => None,
+    };
+    let result = match host {
+        Some(host) => ogp_cache.get_ogp_via_proxy(&url, &host).await,
+        None => ogp_cache.get_ogp(&url).await,
     };
-
     result.map_err(|e| NoteDeckError::InvalidInput(format!("OGP: {e}")))
 }
 
@@ -6,6 +6,10 @@ use notecli::db::Database;
 use notecli::models::{ChatMessage, NormalizedNotification, TimelineOptions};
 
 use super::{get_credentials, AppState, Result};
+use crate::media_gate::MediaGate;
+use crate::upstream_rate::UpstreamRate;
+
+const TEST_NOTIFICATION_ENDPOINT: &str = "notifications/test-notification";
 
 /// REST レスポンスで取得した chat メッセージを fire-and-forget で DB に upsert する。
 /// `cache` フラグが false なら何もしない (`chat.cacheEnabled = false` 時の opt-out)。
@@ -44,26 +48,38 @@ fn cache_chat_response(
 #[specta::specta]
 pub async fn api_get_notifications(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     options: Option<TimelineOptions>,
 ) -> Result<Vec<NormalizedNotification>> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .get_notifications(&host, &token, &account_id, options.unwrap_or_default())
-        .await
+    let options = options.unwrap_or_default();
+    let notifications = rate
+        .read(&host, || {
+            client.get_notifications(&host, &token, &account_id, options.clone())
+        })
+        .await?;
+    Ok(gate.gate_all(notifications))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_notifications_grouped(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     options: Option<TimelineOptions>,
 ) -> Result<Vec<NormalizedNotification>> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .get_notifications_grouped(&host, &token, &account_id, options.unwrap_or_default())
-        .await
+    let options = options.unwrap_or_default();
+    let notifications = rate
+        .read(&host, || {
+            client.get_notifications_grouped(&host, &token, &account_id, options.clone())
+        })
+        .await?;
+    Ok(gate.gate_all(notifications))
 }
 
 #[tauri::command]
@@ -90,6 +106,32 @@ pub async fn api_mark_all_notifications_as_read(
         .await
 }
 
+/// サーバーにテスト通知 (`notifications/test-notification`) を送らせる。
+/// 通知はストリーミングで届くので、OS 通知や通知音まで実際の経路で確かめられる。
+/// エンドポイントの無いサーバー (古い Misskey / 一部フォーク) では false を返すので、
+/// 呼び出し側は `stream_simulate_notification` で代わりに再現する。
+#[tauri::command]
+#[specta::specta]
+pub async fn api_send_test_notification(
+    app_state: State<'_, AppState>,
+    account_id: String,
+) -> Result<bool> {
+    let (client, host, token) = app_state.authed(&account_id).await?;
+    let endpoints = client.get_endpoints(&host).await?;
+    if !endpoints.iter().any(|e| e == TEST_NOTIFICATION_ENDPOINT) {
+        return Ok(false);
+    }
+    client
+        .request(
+            &host,
+            &token,
+            TEST_NOTIFICATION_ENDPOINT,
+            serde_json::json!({}),
+        )
+        .await?;
+    Ok(true)
+}
+
 // --- Unread chat ---
 
 #[tauri::command]
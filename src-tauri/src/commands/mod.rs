@@ -17,6 +17,7 @@ mod messaging;
 mod settings;
 mod streaming;
 mod timeline;
+mod upload;
 mod user;
 mod utility;
 mod vault;
@@ -42,6 +43,7 @@ pub use messaging::*;
 pub use settings::*;
 pub use streaming::*;
 pub use timeline::*;
+pub use upload::*;
 pub use user::*;
 pub use utility::*;
 pub use vault::*;
@@ -366,7 +368,7 @@ pub fn cleanup_expired_credentials() {
 }
 
 /// Get host only from account_id (no token required).
-fn get_host(db: &Database, account_id: &str) -> Result<String> {
+pub(crate) fn get_host(db: &Database, account_id: &str) -> Result<String> {
     let account = db
         .get_account(account_id)?
         .ok_or_else(|| NoteDeckError::AccountNotFound(account_id.to_string()))?;
@@ -1,8 +1,15 @@
-use tauri::State;
+use std::sync::Arc;
+
+use tauri::{Manager, State};
 
 use notecli::db::Database;
+use notecli::error::NoteDeckError;
 use notecli::streaming::StreamingManager;
 
+use crate::power::ActiveStreams;
+use crate::query_runtime::QueryRuntime;
+use crate::streaming::TauriEmitter;
+
 use super::{get_credentials, AppState, Result};
 
 /// Ensure the streaming WebSocket is connected for the given account.
@@ -22,21 +29,30 @@ async fn ensure_stream_connected(
 #[tauri::command]
 #[specta::specta]
 pub async fn stream_connect(
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
     streaming: State<'_, StreamingManager>,
+    active: State<'_, ActiveStreams>,
     account_id: String,
 ) -> Result<()> {
     let db = app_state.db().await;
-    ensure_stream_connected(&db, &streaming, &account_id).await
+    ensure_stream_connected(&db, &streaming, &account_id).await?;
+    active.connected(&account_id);
+    crate::tray::sync_stream(&app, &account_id);
+    Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn stream_disconnect(
+    app: tauri::AppHandle,
     streaming: State<'_, StreamingManager>,
+    active: State<'_, ActiveStreams>,
     account_id: String,
 ) -> Result<()> {
     streaming.disconnect(&account_id).await;
+    active.disconnected(&account_id);
+    crate::tray::sync_stream(&app, &account_id);
     Ok(())
 }
 
@@ -45,8 +61,10 @@ pub async fn stream_disconnect(
 #[tauri::command]
 #[specta::specta]
 pub async fn stream_set_mode(
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
     streaming: State<'_, StreamingManager>,
+    active: State<'_, ActiveStreams>,
     account_id: String,
     mode: String,
     interval_ms: Option<u64>,
@@ -55,16 +73,28 @@ pub async fn stream_set_mode(
     let (host, token) = get_credentials(&db, &account_id)?;
     streaming
         .set_mode(&account_id, &host, &token, &mode, interval_ms)
-        .await
+        .await?;
+    active.set_mode(&account_id, &mode);
+    crate::tray::sync_stream(&app, &account_id);
+    Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn stream_sub_note(
+    app_state: State<'_, AppState>,
     streaming: State<'_, StreamingManager>,
+    runtime: State<'_, QueryRuntime>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
+    // capture を patch に畳むとき自分のリアクションを見分けるため
+    if !runtime.has_self_user_id(&account_id) {
+        let db = app_state.db().await;
+        if let Ok(Some(account)) = db.get_account(&account_id) {
+            runtime.set_self_user_id(account_id.clone(), account.user_id);
+        }
+    }
     streaming.sub_note(&account_id, &note_id).await
 }
 
@@ -77,3 +107,32 @@ pub async fn stream_unsub_note(
 ) -> Result<()> {
     streaming.unsub_note(&account_id, &note_id).await
 }
+
+/// 合成した通知をストリーミングで届いたときと同じ経路に流す (既定は `test`)。
+/// サーバーを介さずに OS 通知・自動化ルール・通知音・DND 設定を確かめるためのもの。
+#[tauri::command]
+#[specta::specta]
+pub async fn stream_simulate_notification(
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+    account_id: String,
+    notification_type: Option<String>,
+) -> Result<()> {
+    let db = app_state.db().await;
+    let account = db
+        .get_account(&account_id)?
+        .ok_or_else(|| NoteDeckError::AccountNotFound(account_id.clone()))?;
+    let notification = crate::streaming::simulated_notification(
+        &account_id,
+        &account.host,
+        &account.user_id,
+        &account.username,
+        notification_type.as_deref().unwrap_or("test"),
+        crate::jobs::now_ms(),
+    )?;
+    let emitter = app
+        .try_state::<Arc<TauriEmitter>>()
+        .ok_or_else(|| NoteDeckError::InvalidInput("streaming is not ready".to_string()))?;
+    emitter.simulate(notification);
+    Ok(())
+}
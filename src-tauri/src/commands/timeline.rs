@@ -11,9 +11,14 @@ use notecli::models::{
 };
 
 use super::{
-    extract_ogp_urls, get_credentials, get_credentials_or_anon, AppState, Result,
+    extract_ogp_urls, get_credentials, get_credentials_or_anon, get_host, AppState, Result,
     MAX_UPLOAD_BYTES,
 };
+use crate::host_queue::{HostQueue, Priority};
+use crate::media_gate::MediaGate;
+use crate::page_prefetch::PagePrefetcher;
+use crate::request_dedup::RequestDedup;
+use crate::upstream_rate::UpstreamRate;
 
 /// Maximum number of concurrent OGP prefetch requests per timeline load
 const MAX_OGP_CONCURRENT: usize = 20;
@@ -25,6 +30,7 @@ const MAX_OGP_CONCURRENT: usize = 20;
 pub async fn api_get_timeline(
     app: tauri::AppHandle,
     app_state: State<'_, AppState>,
+    dedup: State<'_, RequestDedup>,
     account_id: String,
     timeline_type: TimelineType,
     options: Option<TimelineOptions>,
@@ -32,36 +38,223 @@ pub async fn api_get_timeline(
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials_or_anon(&db, &account_id)?;
     let opts = options.unwrap_or_default();
-    let cache_key = if timeline_type.as_str() == "user-list" {
-        if let Some(ref list_id) = opts.list_id {
-            format!("user-list:{list_id}")
-        } else {
-            timeline_type.as_str().to_string()
+    let cache_key = timeline_cache_key(&timeline_type, opts.list_id.as_deref());
+    let dedup_key = timeline_dedup_key(&account_id, &cache_key, &opts);
+    // A page prefetched when the column neared its end is served without a round-trip
+    if let Some(notes) = dedup_key
+        .as_deref()
+        .and_then(|key| app.state::<PagePrefetcher>().take(key))
+    {
+        return Ok(app.state::<MediaGate>().gate_all(notes));
+    }
+    let gate_app = app.clone();
+    let fetch = move || async move {
+        // Reads are idempotent, so a 429 from the server is waited out and retried
+        let notes = app
+            .state::<UpstreamRate>()
+            .read(&host, || {
+                client.get_timeline(&host, &token, &account_id, timeline_type.clone(), opts.clone())
+            })
+            .await?;
+        crate::timeline_gaps::track_page(&app, &db, &account_id, &cache_key, &opts, &notes);
+        if let Err(e) = db.cache_notes(&notes, &cache_key) {
+            tracing::warn!("[cache] failed to cache timeline notes: {e}");
         }
-    } else {
-        timeline_type.as_str().to_string()
+
+        // Background OGP prefetch: extract URLs and spawn async task (non-blocking)
+        if !token.is_empty() {
+            spawn_ogp_prefetch(&app, &notes, host);
+        }
+        Ok::<_, NoteDeckError>(notes)
     };
-    let notes = client
-        .get_timeline(&host, &token, &account_id, timeline_type, opts)
-        .await?;
-    if let Err(e) = db.cache_notes(&notes, &cache_key) {
-        tracing::warn!("[cache] failed to cache timeline notes: {e}");
+
+    // Columns refreshing the same timeline together share one upstream call
+    let notes = match dedup_key {
+        Some(key) => dedup.timelines.run(key, fetch).await,
+        None => fetch().await,
+    }?;
+    Ok(gate_app.state::<MediaGate>().gate_all(notes))
+}
+
+/// Key under which identical timeline requests are coalesced. Filtered requests
+/// are rare and their filters are not keyed, so they get `None` and always go upstream.
+fn timeline_dedup_key(account_id: &str, cache_key: &str, opts: &TimelineOptions) -> Option<String> {
+    opts.filters.is_none().then(|| {
+        format!(
+            "{account_id}\0{cache_key}\0{:?}\0{:?}\0{:?}",
+            opts.limit, opts.since_id, opts.until_id
+        )
+    })
+}
+
+/// Fetch an older page (`untilId`) in the background so the column's next
+/// `api_get_timeline` for it returns immediately. Returns without waiting;
+/// the page also lands in the SQLite cache like any other fetch.
+#[tauri::command]
+#[specta::specta]
+pub async fn api_prefetch_timeline_page(
+    app: tauri::AppHandle,
+    account_id: String,
+    timeline_type: TimelineType,
+    options: TimelineOptions,
+) -> Result<()> {
+    if options.until_id.is_none() {
+        return Err(NoteDeckError::InvalidInput(
+            "Prefetch requires untilId".to_string(),
+        ));
+    }
+    let cache_key = timeline_cache_key(&timeline_type, options.list_id.as_deref());
+    let Some(key) = timeline_dedup_key(&account_id, &cache_key, &options) else {
+        return Ok(());
+    };
+    if app.state::<PagePrefetcher>().contains(&key) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn(async move {
+        let app_state = app.state::<AppState>();
+        let host = match get_host(&app_state.db().await, &account_id) {
+            Ok(host) => host,
+            Err(e) => {
+                tracing::debug!("[prefetch] timeline page skipped: {e}");
+                return;
+            }
+        };
+        // 先読みは投稿 / リアクションの枠を食わない
+        let result = app
+            .state::<HostQueue>()
+            .run(
+                &host,
+                Priority::Background,
+                api_get_timeline(
+                    app.clone(),
+                    app_state,
+                    app.state::<RequestDedup>(),
+                    account_id,
+                    timeline_type,
+                    Some(options),
+                ),
+            )
+            .await;
+        match result {
+            Ok(notes) => app.state::<PagePrefetcher>().store(key, notes),
+            Err(e) => tracing::debug!("[prefetch] timeline page failed: {e}"),
+        }
+    });
+    Ok(())
+}
+
+/// Upper bound on requests accepted by one bulk call.
+const MAX_BULK_TIMELINES: usize = 50;
+/// Concurrent timeline fetches per host within a bulk call.
+const BULK_PER_HOST_CONCURRENCY: usize = 4;
+
+/// One column's timeline fetch in `api_get_timelines_bulk`.
+#[derive(serde::Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineRequest {
+    /// Caller-chosen key the result is returned under
+    pub id: String,
+    pub account_id: String,
+    pub timeline_type: TimelineType,
+    pub options: Option<TimelineOptions>,
+}
+
+/// Per-request outcome; one failing column does not fail the whole batch.
+#[derive(serde::Serialize, specta::Type)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TimelineResult {
+    Ok { notes: Vec<NormalizedNote> },
+    Error { code: String, message: String },
+}
+
+impl From<Result<Vec<NormalizedNote>>> for TimelineResult {
+    fn from(result: Result<Vec<NormalizedNote>>) -> Self {
+        match result {
+            Ok(notes) => Self::Ok { notes },
+            Err(e) => {
+                // Same `{ code, message }` shape the invoke error path serializes
+                let value = serde_json::to_value(&e).unwrap_or_default();
+                let field = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+                Self::Error {
+                    code: field("code").unwrap_or_else(|| "UNKNOWN".to_string()),
+                    message: field("message").unwrap_or_else(|| e.to_string()),
+                }
+            }
+        }
     }
+}
 
-    // Background OGP prefetch: extract URLs and spawn async task (non-blocking)
-    if !token.is_empty() {
-        spawn_ogp_prefetch(&app, &notes, host, token);
+/// Fetch several columns' timelines in one invoke (startup / bulk refresh).
+///
+/// Requests run concurrently, at most `BULK_PER_HOST_CONCURRENCY` at a time per
+/// host so one server is not hit by every column at once. Each request goes
+/// through `api_get_timeline`, so caching, dedup and rate-limit retries apply.
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_timelines_bulk(
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+    dedup: State<'_, RequestDedup>,
+    requests: Vec<TimelineRequest>,
+) -> Result<HashMap<String, TimelineResult>> {
+    if requests.len() > MAX_BULK_TIMELINES {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Too many timeline requests (max {MAX_BULK_TIMELINES})"
+        )));
+    }
+    let db = app_state.db().await;
+    let mut by_host: HashMap<String, Vec<TimelineRequest>> = HashMap::new();
+    let mut results: HashMap<String, TimelineResult> = HashMap::new();
+    for request in requests {
+        match super::get_host(&db, &request.account_id) {
+            Ok(host) => by_host.entry(host).or_default().push(request),
+            Err(e) => {
+                results.insert(request.id, TimelineResult::from(Err(e)));
+            }
+        }
     }
 
-    Ok(notes)
+    let hosts = by_host.into_values().map(|requests| {
+        let (app, app_state, dedup) = (app.clone(), app_state.clone(), dedup.clone());
+        stream::iter(requests)
+            .map(move |request| {
+                let (app, app_state, dedup) = (app.clone(), app_state.clone(), dedup.clone());
+                async move {
+                    let result = api_get_timeline(
+                        app,
+                        app_state,
+                        dedup,
+                        request.account_id,
+                        request.timeline_type,
+                        request.options,
+                    )
+                    .await;
+                    (request.id, TimelineResult::from(result))
+                }
+            })
+            .buffer_unordered(BULK_PER_HOST_CONCURRENCY)
+            .collect::<Vec<_>>()
+    });
+    for host_results in futures_util::future::join_all(hosts).await {
+        results.extend(host_results);
+    }
+    Ok(results)
+}
+
+/// Cache key under which a timeline's notes are stored (`user-list:<id>` for lists).
+pub(crate) fn timeline_cache_key(timeline_type: &TimelineType, list_id: Option<&str>) -> String {
+    match list_id {
+        Some(list_id) if timeline_type.as_str() == "user-list" => format!("user-list:{list_id}"),
+        _ => timeline_type.as_str().to_string(),
+    }
 }
 
 /// Extract URLs from notes and spawn background OGP prefetch via Tauri events.
+/// Goes through the instance's preview proxy like `fetch_ogp` with an account.
 fn spawn_ogp_prefetch(
     app: &tauri::AppHandle,
     notes: &[NormalizedNote],
     host: String,
-    token: String,
 ) {
     let mut urls: Vec<String> = Vec::new();
     for note in notes {
@@ -87,11 +280,10 @@ fn spawn_ogp_prefetch(
         let hints: HashMap<String, crate::ogp::OgpData> = stream::iter(urls)
             .map(|url| {
                 let host = host.clone();
-                let token = token.clone();
                 let ogp = ogp_cache.clone();
                 async move {
                     let result: std::result::Result<crate::ogp::OgpData, _> =
-                        ogp.get_ogp_via_server(&url, &host, &token).await;
+                        ogp.get_ogp_via_proxy(&url, &host).await;
                     (url, result.ok())
                 }
             })
@@ -158,6 +350,8 @@ pub async fn api_update_antenna(
 #[specta::specta]
 pub async fn api_get_antenna_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     antenna_id: String,
     limit: Option<i64>,
@@ -166,27 +360,31 @@ pub async fn api_get_antenna_notes(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials(&db, &account_id)?;
-    let notes = client
-        .get_antenna_notes(
-            &host,
-            &token,
-            &account_id,
-            &antenna_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_antenna_notes(
+                &host,
+                &token,
+                &account_id,
+                &antenna_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+            )
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, &format!("antenna:{antenna_id}")) {
         tracing::warn!("[cache] failed to cache antenna notes: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_favorites(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     limit: Option<i64>,
     since_id: Option<String>,
@@ -194,40 +392,49 @@ pub async fn api_get_favorites(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials(&db, &account_id)?;
-    let notes = client
-        .get_favorites(
-            &host,
-            &token,
-            &account_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_favorites(
+                &host,
+                &token,
+                &account_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+            )
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, "favorites") {
         tracing::warn!("[cache] failed to cache favorites: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_featured_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     limit: Option<i64>,
 ) -> Result<Vec<NormalizedNote>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    let notes = client
-        .get_featured_notes(&host, &token, &account_id, limit.unwrap_or(30))
+    let limit = limit.unwrap_or(30);
+    let notes = rate
+        .read(&host, || {
+            client.get_featured_notes(&host, &token, &account_id, limit)
+        })
         .await?;
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_mentions(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     limit: Option<i64>,
     since_id: Option<String>,
@@ -236,16 +443,18 @@ pub async fn api_get_mentions(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials(&db, &account_id)?;
-    let notes = client
-        .get_mentions(
-            &host,
-            &token,
-            &account_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-            visibility.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_mentions(
+                &host,
+                &token,
+                &account_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+                visibility.as_deref(),
+            )
+        })
         .await?;
     // ダイレクト（specified）と通常メンションは別カラム・別キャッシュキー。
     // 同じキーに混ぜると read 側（cacheKey='specified' / 'mentions'）と不整合になる。
@@ -257,7 +466,7 @@ pub async fn api_get_mentions(
     if let Err(e) = db.cache_notes(&notes, cache_key) {
         tracing::warn!("[cache] failed to cache mentions: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 // --- Clips ---
@@ -276,6 +485,8 @@ pub async fn api_get_clips(
 #[specta::specta]
 pub async fn api_get_clip_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     clip_id: String,
     limit: Option<i64>,
@@ -284,21 +495,23 @@ pub async fn api_get_clip_notes(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials(&db, &account_id)?;
-    let notes = client
-        .get_clip_notes(
-            &host,
-            &token,
-            &account_id,
-            &clip_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_clip_notes(
+                &host,
+                &token,
+                &account_id,
+                &clip_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+            )
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, &format!("clip:{clip_id}")) {
         tracing::warn!("[cache] failed to cache clip notes: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 // --- Channels ---
@@ -328,6 +541,8 @@ pub async fn api_search_channels(
 #[specta::specta]
 pub async fn api_get_channel_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     channel_id: String,
     limit: Option<i64>,
@@ -336,21 +551,23 @@ pub async fn api_get_channel_notes(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials_or_anon(&db, &account_id)?;
-    let notes = client
-        .get_channel_notes(
-            &host,
-            &token,
-            &account_id,
-            &channel_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_channel_notes(
+                &host,
+                &token,
+                &account_id,
+                &channel_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+            )
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, &format!("channel:{channel_id}")) {
         tracing::warn!("[cache] failed to cache channel notes: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 // --- Roles ---
@@ -359,6 +576,8 @@ pub async fn api_get_channel_notes(
 #[specta::specta]
 pub async fn api_get_role_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     role_id: String,
     limit: Option<i64>,
@@ -367,21 +586,23 @@ pub async fn api_get_role_notes(
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials_or_anon(&db, &account_id)?;
-    let notes = client
-        .get_role_notes(
-            &host,
-            &token,
-            &account_id,
-            &role_id,
-            limit.unwrap_or(20),
-            since_id.as_deref(),
-            until_id.as_deref(),
-        )
+    let notes = rate
+        .read(&host, || {
+            client.get_role_notes(
+                &host,
+                &token,
+                &account_id,
+                &role_id,
+                limit.unwrap_or(20),
+                since_id.as_deref(),
+                until_id.as_deref(),
+            )
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, &format!("role:{role_id}")) {
         tracing::warn!("[cache] failed to cache role notes: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 // --- Notes ---
@@ -390,30 +611,61 @@ pub async fn api_get_role_notes(
 #[specta::specta]
 pub async fn api_get_note(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<NormalizedNote> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.get_note(&host, &token, &account_id, &note_id).await
+    let note = rate
+        .read(&host, || {
+            client.get_note(&host, &token, &account_id, &note_id)
+        })
+        .await?;
+    Ok(gate.gate(note))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_create_note(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     params: CreateNoteParams,
     channel_id: Option<String>,
 ) -> Result<NormalizedNote> {
-    let (client, host, token) = app_state.authed(&account_id).await?;
+    let host = get_host(&app_state.db().await, &account_id)?;
+    let note = queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(
+                &host,
+                create_note(&app_state, &account_id, &params, channel_id.as_deref()),
+            ),
+        )
+        .await?;
+    Ok(gate.gate(note))
+}
+
+/// Post a note. Shared by `api_create_note` and the scheduler's queued posts.
+pub(crate) async fn create_note(
+    app_state: &AppState,
+    account_id: &str,
+    params: &CreateNoteParams,
+    channel_id: Option<&str>,
+) -> Result<NormalizedNote> {
+    let (client, host, token) = app_state.authed(account_id).await?;
     // notecli の CreateNoteParams / CreateNotePoll は Option に
     // skip_serializing_if が付いていないため、struct をそのまま serde_json::json!
     // で包むと multiple: null / expiresAt: null 等が混入し Misskey 側で
     // INVALID_PARAM になる。安全のため body は常に手組みする。
-    let body = build_create_note_body(&params, channel_id.as_deref());
+    let body = build_create_note_body(params, channel_id);
     let data = client.request(&host, &token, "notes/create", body).await?;
     let raw: RawCreateNoteResponse = serde_json::from_value(data)?;
-    Ok(raw.created_note.normalize(&account_id, &host))
+    Ok(raw.created_note.normalize(account_id, &host))
 }
 
 fn build_create_note_body(
@@ -472,23 +724,39 @@ fn build_create_note_body(
 #[specta::specta]
 pub async fn api_update_note(
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     params: CreateNoteParams,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.update_note(&host, &token, &note_id, params).await
+    queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(&host, client.update_note(&host, &token, &note_id, params)),
+        )
+        .await
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_delete_note(
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.delete_note(&host, &token, &note_id).await
+    queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(&host, client.delete_note(&host, &token, &note_id)),
+        )
+        .await
 }
 
 // --- Reactions ---
@@ -497,13 +765,22 @@ pub async fn api_delete_note(
 #[specta::specta]
 pub async fn api_create_reaction(
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     reaction: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .create_reaction(&host, &token, &note_id, &reaction)
+    queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(
+                &host,
+                client.create_reaction(&host, &token, &note_id, &reaction),
+            ),
+        )
         .await
 }
 
@@ -511,31 +788,81 @@ pub async fn api_create_reaction(
 #[specta::specta]
 pub async fn api_delete_reaction(
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.delete_reaction(&host, &token, &note_id).await
+    queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(&host, client.delete_reaction(&host, &token, &note_id)),
+        )
+        .await
 }
 
-// --- Poll vote ---
+// --- Polls ---
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_vote_poll(
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     choice: u32,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.vote_poll(&host, &token, &note_id, choice).await
+    queue
+        .run(
+            &host,
+            Priority::Interactive,
+            rate.write(&host, client.vote_poll(&host, &token, &note_id, choice)),
+        )
+        .await
+}
+
+/// Open polls the account can still vote in (`notes/polls/recommendation`).
+/// The server leaves out expired polls, polls already voted in and the
+/// account's own notes. Paged by `offset`, not by note id.
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_poll_recommendations(
+    app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
+    account_id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    exclude_channels: Option<bool>,
+) -> Result<Vec<NormalizedNote>> {
+    let (client, host, token) = app_state.authed(&account_id).await?;
+    let body = serde_json::json!({
+        "limit": limit.unwrap_or(20).clamp(1, 100),
+        "offset": offset.unwrap_or(0),
+        "excludeChannels": exclude_channels.unwrap_or(false),
+    });
+    let data = rate
+        .read(&host, || {
+            client.request(&host, &token, "notes/polls/recommendation", body.clone())
+        })
+        .await?;
+    let raw: Vec<RawNote> = serde_json::from_value(data)?;
+    let notes: Vec<NormalizedNote> = raw
+        .into_iter()
+        .map(|n| n.normalize(&account_id, &host))
+        .collect();
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_note_reactions(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     reaction_type: Option<String>,
@@ -543,16 +870,18 @@ pub async fn api_get_note_reactions(
     until_id: Option<String>,
 ) -> Result<Vec<NormalizedNoteReaction>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client
-        .get_note_reactions(
+    let limit = limit.unwrap_or(11).clamp(1, 100);
+    rate.read(&host, || {
+        client.get_note_reactions(
             &host,
             &token,
             &note_id,
             reaction_type.as_deref(),
-            limit.unwrap_or(11).clamp(1, 100),
+            limit,
             until_id.as_deref(),
         )
-        .await
+    })
+    .await
 }
 
 // --- Favorites ---
@@ -561,22 +890,26 @@ pub async fn api_get_note_reactions(
 #[specta::specta]
 pub async fn api_create_favorite(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.create_favorite(&host, &token, &note_id).await
+    rate.write(&host, client.create_favorite(&host, &token, &note_id))
+        .await
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_delete_favorite(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.delete_favorite(&host, &token, &note_id).await
+    rate.write(&host, client.delete_favorite(&host, &token, &note_id))
+        .await
 }
 
 // --- Pin/Unpin ---
@@ -585,22 +918,26 @@ pub async fn api_delete_favorite(
 #[specta::specta]
 pub async fn api_pin_note(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.pin_note(&host, &token, &note_id).await
+    rate.write(&host, client.pin_note(&host, &token, &note_id))
+        .await
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_unpin_note(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.unpin_note(&host, &token, &note_id).await
+    rate.write(&host, client.unpin_note(&host, &token, &note_id))
+        .await
 }
 
 // --- Clip operations ---
@@ -609,28 +946,34 @@ pub async fn api_unpin_note(
 #[specta::specta]
 pub async fn api_add_note_to_clip(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     clip_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .add_note_to_clip(&host, &token, &clip_id, &note_id)
-        .await
+    rate.write(
+        &host,
+        client.add_note_to_clip(&host, &token, &clip_id, &note_id),
+    )
+    .await
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_remove_note_from_clip(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     clip_id: String,
     note_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .remove_note_from_clip(&host, &token, &clip_id, &note_id)
-        .await
+    rate.write(
+        &host,
+        client.remove_note_from_clip(&host, &token, &clip_id, &note_id),
+    )
+    .await
 }
 
 // --- Note thread ---
@@ -639,64 +982,75 @@ pub async fn api_remove_note_from_clip(
 #[specta::specta]
 pub async fn api_get_note_children(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     limit: Option<u32>,
 ) -> Result<Vec<NormalizedNote>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client
-        .get_note_children(
-            &host,
-            &token,
-            &account_id,
-            &note_id,
-            limit.unwrap_or(30).clamp(1, 100),
-        )
-        .await
+    let notes = rate
+        .read(&host, || {
+            client.get_note_children(
+                &host,
+                &token,
+                &account_id,
+                &note_id,
+                limit.unwrap_or(30).clamp(1, 100),
+            )
+        })
+        .await?;
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_note_renotes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     limit: Option<u32>,
 ) -> Result<Vec<NormalizedNote>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    let data = client
-        .request(
-            &host,
-            &token,
-            "notes/renotes",
-            serde_json::json!({ "noteId": note_id, "limit": limit.unwrap_or(30).clamp(1, 100) }),
-        )
+    let body = serde_json::json!({ "noteId": note_id, "limit": limit.unwrap_or(30).clamp(1, 100) });
+    let data = rate
+        .read(&host, || {
+            client.request(&host, &token, "notes/renotes", body.clone())
+        })
         .await?;
     let raw: Vec<RawNote> = serde_json::from_value(data)?;
-    Ok(raw
+    let notes = raw
         .into_iter()
         .map(|n| n.normalize(&account_id, &host))
-        .collect())
+        .collect();
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_note_conversation(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     note_id: String,
     limit: Option<u32>,
 ) -> Result<Vec<NormalizedNote>> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client
-        .get_note_conversation(
-            &host,
-            &token,
-            &account_id,
-            &note_id,
-            limit.unwrap_or(30).clamp(1, 100),
-        )
-        .await
+    let notes = rate
+        .read(&host, || {
+            client.get_note_conversation(
+                &host,
+                &token,
+                &account_id,
+                &note_id,
+                limit.unwrap_or(30).clamp(1, 100),
+            )
+        })
+        .await?;
+    Ok(gate.gate_all(notes))
 }
 
 // --- Search ---
@@ -705,6 +1059,8 @@ pub async fn api_get_note_conversation(
 #[specta::specta]
 pub async fn api_search_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     query: String,
     options: Option<SearchOptions>,
@@ -715,15 +1071,13 @@ pub async fn api_search_notes(
         ));
     }
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client
-        .search_notes(
-            &host,
-            &token,
-            &account_id,
-            &query,
-            options.unwrap_or_default(),
-        )
-        .await
+    let options = options.unwrap_or_default();
+    let notes = rate
+        .read(&host, || {
+            client.search_notes(&host, &token, &account_id, &query, options.clone())
+        })
+        .await?;
+    Ok(gate.gate_all(notes))
 }
 
 // --- Upload ---
@@ -731,7 +1085,11 @@ pub async fn api_search_notes(
 #[tauri::command]
 #[specta::specta]
 pub async fn api_upload_file(
+    app: tauri::AppHandle,
     app_state: State<'_, AppState>,
+    queue: State<'_, HostQueue>,
+    rate: State<'_, UpstreamRate>,
+    prep: State<'_, crate::upload_prep::UploadPrep>,
     account_id: String,
     file_name: String,
     file_data: Vec<u8>,
@@ -739,58 +1097,58 @@ pub async fn api_upload_file(
     is_sensitive: bool,
     folder_id: Option<String>,
 ) -> Result<NormalizedDriveFile> {
-    if file_data.len() > MAX_UPLOAD_BYTES {
+    // 加工対象の画像は縮小後のサイズで上限を判定する
+    let limit = if prep.applies_to(&content_type) {
+        crate::upload_prep::MAX_PREP_INPUT_BYTES
+    } else {
+        MAX_UPLOAD_BYTES
+    };
+    if file_data.len() > limit {
+        return Err(NoteDeckError::InvalidInput("File too large".to_string()));
+    }
+    let prepared = prep.prepare(file_data, &content_type, &file_name).await?;
+    if prepared.data.len() > MAX_UPLOAD_BYTES {
         return Err(NoteDeckError::InvalidInput("File too large".to_string()));
     }
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .upload_file(
-            &host,
-            &token,
-            &file_name,
-            file_data,
-            &content_type,
-            is_sensitive,
-            folder_id.as_deref(),
-        )
-        .await
+    let upload = client.upload_file(
+        &host,
+        &token,
+        &prepared.file_name,
+        prepared.data,
+        &prepared.content_type,
+        is_sensitive,
+        folder_id.as_deref(),
+    );
+    let file = queue
+        .run(&host, Priority::Interactive, rate.write(&host, upload))
+        .await?;
+    crate::upload_history::remember(&app, &account_id, &file);
+    Ok(file)
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_upload_file_from_path(
-    app_state: State<'_, AppState>,
+    app: tauri::AppHandle,
     account_id: String,
     file_path: String,
     is_sensitive: bool,
     folder_id: Option<String>,
 ) -> Result<NormalizedDriveFile> {
-    let path = std::path::Path::new(&file_path);
-    let file_data = std::fs::read(path)
-        .map_err(|e| NoteDeckError::InvalidInput(format!("Failed to read file: {e}")))?;
-    if file_data.len() > MAX_UPLOAD_BYTES {
-        return Err(NoteDeckError::InvalidInput("File too large".to_string()));
-    }
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("file")
-        .to_string();
-    let content_type = mime_guess::from_path(path)
-        .first_or_octet_stream()
-        .to_string();
-    let (client, host, token) = app_state.authed(&account_id).await?;
-    client
-        .upload_file(
-            &host,
-            &token,
-            &file_name,
-            file_data,
-            &content_type,
-            is_sensitive,
-            folder_id.as_deref(),
-        )
-        .await
+    // 全体をメモリに読まずディスクから流す (upload.rs)
+    let file = super::upload::upload_path(
+        &app,
+        crate::host_queue::Priority::Interactive,
+        &account_id,
+        std::path::Path::new(&file_path),
+        is_sensitive,
+        folder_id,
+        None,
+    )
+    .await?;
+    crate::upload_history::remember(&app, &account_id, &file);
+    Ok(file)
 }
 
 // --- Cache ---
@@ -799,22 +1157,25 @@ pub async fn api_upload_file_from_path(
 #[specta::specta]
 pub async fn api_get_cached_timeline(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     timeline_type: String,
     limit: Option<i64>,
 ) -> Result<Vec<NormalizedNote>> {
     let db = app_state.db().await;
-    db.get_cached_timeline(
+    let notes = db.get_cached_timeline(
         &account_id,
         &timeline_type,
         limit.unwrap_or(40).clamp(1, 200),
-    )
+    )?;
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_cached_timeline_before(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     timeline_type: String,
     before: String,
@@ -824,12 +1185,13 @@ pub async fn api_get_cached_timeline_before(
         return Err(NoteDeckError::InvalidInput("Invalid date".to_string()));
     }
     let db = app_state.db().await;
-    db.get_cached_timeline_before(
+    let notes = db.get_cached_timeline_before(
         &account_id,
         &timeline_type,
         &before,
         limit.unwrap_or(40).clamp(1, 200),
-    )
+    )?;
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
@@ -847,16 +1209,19 @@ pub async fn api_get_cache_date_range(
 #[specta::specta]
 pub async fn api_find_notes_by_uri(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     uri: String,
 ) -> Result<Vec<NormalizedNote>> {
     let db = app_state.db().await;
-    db.find_notes_by_uri(&uri)
+    let notes = db.find_notes_by_uri(&uri)?;
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_search_notes_local(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     query: String,
     limit: Option<i64>,
@@ -870,14 +1235,15 @@ pub async fn api_search_notes_local(
         ));
     }
     let db = app_state.db().await;
-    db.search_cached_notes_advanced(
+    let notes = db.search_cached_notes_advanced(
         &account_id,
         &query,
         limit.unwrap_or(30).clamp(1, 200),
         since_date.as_deref(),
         until_date.as_deref(),
         ascending.unwrap_or(false),
-    )
+    )?;
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
@@ -900,6 +1266,7 @@ const MAX_VERIFY_CONCURRENT: usize = 20;
 #[specta::specta]
 pub async fn api_verify_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     note_ids: Vec<String>,
 ) -> Result<HashMap<String, NormalizedNote>> {
@@ -930,5 +1297,5 @@ pub async fn api_verify_notes(
         .collect()
         .await;
 
-    Ok(verified)
+    Ok(gate.gate(verified))
 }
@@ -0,0 +1,203 @@
+//! ファイルパス指定のドライブアップロード。
+//!
+//! `api_upload_file` はフロントがファイル全体を `Vec<u8>` として IPC に載せる
+//! ため、D&D された大きな動画では JSON シリアライズ分も含めてメモリを食う。
+//! こちらは Rust 側でディスクから読みながら multipart body に流し込む
+//! (`tokio::fs::File` → `reqwest::Body`)。サイズ上限 (MAX_UPLOAD_BYTES) は
+//! 読み込み前に metadata で判定する。EXIF 除去など `UploadPrep` の加工対象の
+//! 画像だけは、加工のためにメモリへ読み込んでから送る。
+
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use notecli::error::NoteDeckError;
+use notecli::models::NormalizedDriveFile;
+
+use crate::host_queue::{HostQueue, Priority};
+use crate::upload_prep::{UploadPrep, MAX_PREP_INPUT_BYTES};
+use crate::upstream_rate::{rate_limited_error, UpstreamRate};
+
+use super::{AppState, Result, MAX_UPLOAD_BYTES};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 無通信がこれだけ続いたら打ち切る。回線が遅いと 50MB の送信自体は
+/// 数分かかるので、全体タイムアウトは掛けない。
+const READ_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// アップロード専用クライアント。`MisskeyClient::upload_file` はバイト列しか
+/// 受け取れず、共有 `reqwest::Client` は全体 10s タイムアウトなので、どちらも
+/// 大きなファイルの送信に使えない。同時実行数とレート制限は `upload_path` が
+/// `HostQueue` / `UpstreamRate` を通して他の呼び出しと揃える。
+static UPLOAD_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn upload_client() -> &'static reqwest::Client {
+    UPLOAD_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(READ_TIMEOUT)
+            .user_agent("notedeck-upload")
+            .build()
+            .expect("failed to build upload client")
+    })
+}
+
+fn check_upload_size(len: u64) -> Result<()> {
+    if len > MAX_UPLOAD_BYTES as u64 {
+        return Err(NoteDeckError::InvalidInput("File too large".to_string()));
+    }
+    Ok(())
+}
+
+/// `drive/files/create` の応答を `NormalizedDriveFile` にする。Misskey は
+/// 画像の幅・高さを `properties` 下に返すので、正規化モデルの位置へ持ち上げる。
+pub(super) fn drive_file_from_response(
+    mut value: serde_json::Value,
+) -> Result<NormalizedDriveFile> {
+    if let Some(obj) = value.as_object_mut() {
+        let props = obj.get("properties").cloned();
+        for key in ["width", "height"] {
+            if let Some(v) = props.as_ref().and_then(|p| p.get(key)) {
+                obj.insert(key.to_string(), v.clone());
+            }
+        }
+    }
+    serde_json::from_value(value)
+        .map_err(|e| NoteDeckError::InvalidInput(format!("Unexpected drive file response: {e}")))
+}
+
+/// ローカルファイルをディスクから直接ストリーミングしてドライブへ
+/// アップロードする。`name` 省略時はファイル名、Content-Type は拡張子から推定。
+///
+/// 投稿と同じくホストの同時実行枠 (`HostQueue`) を取り、上流が制限中なら
+/// 解除を待ってから 1 回だけ送る。429 で弾かれたら `UpstreamRate` に記録する。
+pub(crate) async fn upload_path(
+    app: &AppHandle,
+    priority: Priority,
+    account_id: &str,
+    path: &Path,
+    is_sensitive: bool,
+    folder_id: Option<String>,
+    name: Option<String>,
+) -> Result<NormalizedDriveFile> {
+    let meta = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| NoteDeckError::InvalidInput(format!("Failed to read file: {e}")))?;
+    if !meta.is_file() {
+        return Err(NoteDeckError::InvalidInput(
+            "Not a regular file".to_string(),
+        ));
+    }
+    let content_type = mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .to_string();
+    let prep = app.state::<UploadPrep>();
+    let prepare = prep.applies_to(&content_type);
+    if prepare {
+        // 縮小で上限内に収まる可能性があるので、加工後のサイズで判定する
+        if meta.len() > MAX_PREP_INPUT_BYTES as u64 {
+            return Err(NoteDeckError::InvalidInput("File too large".to_string()));
+        }
+    } else {
+        check_upload_size(meta.len())?;
+    }
+
+    let file_name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file")
+            .to_string()
+    });
+
+    let (_, host, token) = app.state::<AppState>().authed(account_id).await?;
+    let (part, file_name, content_type) = if prepare {
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| NoteDeckError::InvalidInput(format!("Failed to read file: {e}")))?;
+        let prepared = prep.prepare(data, &content_type, &file_name).await?;
+        check_upload_size(prepared.data.len() as u64)?;
+        (
+            reqwest::multipart::Part::bytes(prepared.data),
+            prepared.file_name,
+            prepared.content_type,
+        )
+    } else {
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| NoteDeckError::InvalidInput(format!("Failed to read file: {e}")))?;
+        (
+            reqwest::multipart::Part::stream_with_length(file, meta.len()),
+            file_name,
+            content_type,
+        )
+    };
+    let part = part
+        .file_name(file_name.clone())
+        .mime_str(&content_type)
+        .map_err(NoteDeckError::from)?;
+    let mut form = reqwest::multipart::Form::new()
+        .text("i", token)
+        .text("name", file_name)
+        .text("isSensitive", is_sensitive.to_string())
+        .part("file", part);
+    if let Some(folder_id) = folder_id {
+        form = form.text("folderId", folder_id);
+    }
+
+    let url = format!("https://{host}/api/drive/files/create");
+    let send = async {
+        let resp = upload_client()
+            .post(url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(NoteDeckError::from)?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited_error("drive/files/create"));
+        }
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            let detail: String = body.chars().take(200).collect();
+            return Err(NoteDeckError::InvalidInput(format!(
+                "Upload failed ({status}): {detail}"
+            )));
+        }
+        let value: serde_json::Value = resp.json().await.map_err(NoteDeckError::from)?;
+        drive_file_from_response(value)
+    };
+    let _slot = app.state::<HostQueue>().acquire(&host, priority).await;
+    app.state::<UpstreamRate>().write(&host, send).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_guard_matches_upload_limit() {
+        assert!(check_upload_size(MAX_UPLOAD_BYTES as u64).is_ok());
+        assert!(check_upload_size(MAX_UPLOAD_BYTES as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn lifts_image_dimensions_from_properties() {
+        let file = drive_file_from_response(serde_json::json!({
+            "id": "f1",
+            "name": "a.png",
+            "type": "image/png",
+            "url": "https://example.com/a.png",
+            "thumbnailUrl": null,
+            "size": 10,
+            "isSensitive": false,
+            "blurhash": null,
+            "properties": { "width": 640, "height": 480 },
+        }))
+        .unwrap();
+        assert_eq!(file.id, "f1");
+        assert_eq!(file.width, Some(640));
+        assert_eq!(file.height, Some(480));
+    }
+}
@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use tauri::State;
 
-use notecli::api::SearchUsersOptions;
+use notecli::api::{MisskeyClient, SearchUsersOptions};
 use notecli::error::NoteDeckError;
 use notecli::models::{
     Flash, GalleryPost, MutedWordsResult, NormalizedNote, NormalizedUser, NormalizedUserDetail,
@@ -8,6 +13,9 @@ use notecli::models::{
 };
 
 use super::{AppState, get_credentials_or_anon, Result, typed_request, validate_host};
+use crate::media_gate::MediaGate;
+use crate::request_dedup::RequestDedup;
+use crate::upstream_rate::UpstreamRate;
 
 // --- User profile ---
 
@@ -15,11 +23,90 @@ use super::{AppState, get_credentials_or_anon, Result, typed_request, validate_h
 #[specta::specta]
 pub async fn api_get_user(
     app_state: State<'_, AppState>,
+    dedup: State<'_, RequestDedup>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     user_id: String,
 ) -> Result<NormalizedUser> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.get_user(&host, &token, &user_id).await
+    dedup
+        .users
+        .run(format!("{account_id}\0{user_id}"), || {
+            rate.read(&host, || client.get_user(&host, &token, &user_id))
+        })
+        .await
+}
+
+/// `users/show` の `userIds` 一括取得の上限 (1 リクエストあたり)。
+const USERS_SHOW_BATCH: usize = 100;
+
+/// `users/show` を `userIds` で上限ごとに分けて引き、生の応答を連結して返す。
+/// `NormalizedUser` に無いフィールド (bannerUrl 等) を読む呼び出し側のため生のまま返す。
+pub(crate) async fn show_users_raw(
+    client: &MisskeyClient,
+    host: &str,
+    token: &str,
+    user_ids: &[String],
+) -> Result<Vec<serde_json::Value>> {
+    let mut users = Vec::with_capacity(user_ids.len());
+    for chunk in user_ids.chunks(USERS_SHOW_BATCH) {
+        let raw: Vec<serde_json::Value> = typed_request(
+            client,
+            host,
+            token,
+            "users/show",
+            serde_json::json!({ "userIds": chunk }),
+        )
+        .await?;
+        users.extend(raw);
+    }
+    Ok(users)
+}
+
+/// 複数ユーザーをまとめて取得し、id → ユーザーの map で返す。
+/// リアクション一覧 / フォロワー一覧のアバター解決を 1 件ずつ投げないための口。
+/// 存在しない (削除済み等) ユーザーは map に含まれない。
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_users(
+    app_state: State<'_, AppState>,
+    account_id: String,
+    user_ids: Vec<String>,
+) -> Result<HashMap<String, NormalizedUser>> {
+    let mut ids = user_ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
+    let mut users = HashMap::with_capacity(ids.len());
+    let mut undecodable = 0usize;
+    for value in show_users_raw(&client, &host, &token, &ids).await? {
+        match serde_json::from_value::<NormalizedUser>(value) {
+            Ok(user) => {
+                users.insert(user.id.clone(), user);
+            }
+            Err(e) => {
+                undecodable += 1;
+                tracing::debug!("[users] batch entry not decodable: {e}");
+            }
+        }
+    }
+    // フォーク差分等で一括応答を復元できなかったときだけ、欠けた分を個別に取り直す
+    // (単に欠けているのは削除済みユーザーなので取り直さない)
+    if undecodable == 0 {
+        return Ok(users);
+    }
+    for id in ids.iter().filter(|id| !users.contains_key(*id)) {
+        match client.get_user(&host, &token, id).await {
+            Ok(user) => {
+                users.insert(id.clone(), user);
+            }
+            Err(e) => tracing::debug!(user_id = %id, "[users] fallback fetch failed: {e}"),
+        }
+    }
+    Ok(users)
 }
 
 #[tauri::command]
@@ -39,51 +126,53 @@ pub async fn api_get_user_detail(
 #[specta::specta]
 pub async fn api_get_user_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     user_id: String,
     options: Option<TimelineOptions>,
 ) -> Result<Vec<NormalizedNote>> {
     let (db, client) = app_state.ready().await;
     let (host, token) = get_credentials_or_anon(&db, &account_id)?;
-    let notes = client
-        .get_user_notes(
-            &host,
-            &token,
-            &account_id,
-            &user_id,
-            options.unwrap_or_default(),
-        )
+    let options = options.unwrap_or_default();
+    let notes = rate
+        .read(&host, || {
+            client.get_user_notes(&host, &token, &account_id, &user_id, options.clone())
+        })
         .await?;
     if let Err(e) = db.cache_notes(&notes, &format!("user:{user_id}")) {
         tracing::warn!("[cache] failed to cache user notes: {e}");
     }
-    Ok(notes)
+    Ok(gate.gate_all(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_user_notes_filtered(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     params: serde_json::Value,
 ) -> Result<serde_json::Value> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
+    let notes = client
         .get_user_notes_filtered(&host, &token, params)
-        .await
+        .await?;
+    Ok(gate.gate(notes))
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_get_user_featured_notes(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     user_id: String,
     limit: Option<i64>,
     until_id: Option<String>,
 ) -> Result<serde_json::Value> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client
+    let notes = client
         .get_user_featured_notes(
             &host,
             &token,
@@ -91,7 +180,8 @@ pub async fn api_get_user_featured_notes(
             limit.unwrap_or(30).clamp(1, 100),
             until_id.as_deref(),
         )
-        .await
+        .await?;
+    Ok(gate.gate(notes))
 }
 
 #[tauri::command]
@@ -142,22 +232,26 @@ pub async fn api_get_self(
 #[specta::specta]
 pub async fn api_follow_user(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     user_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.follow_user(&host, &token, &user_id).await
+    rate.write(&host, client.follow_user(&host, &token, &user_id))
+        .await
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn api_unfollow_user(
     app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
     account_id: String,
     user_id: String,
 ) -> Result<()> {
     let (client, host, token) = app_state.authed(&account_id).await?;
-    client.unfollow_user(&host, &token, &user_id).await
+    rate.write(&host, client.unfollow_user(&host, &token, &user_id))
+        .await
 }
 
 #[tauri::command]
@@ -316,6 +410,44 @@ pub async fn api_get_user_relations(
     client.get_user_relations(&host, &token, &user_ids).await
 }
 
+/// One entry of `users/get-frequently-replied-users`.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FrequentlyRepliedUser {
+    pub user: NormalizedUser,
+    /// How often `user` is replied to, relative to the other entries
+    pub weight: f64,
+}
+
+/// Users that `user_id` replies to most, ordered by weight
+/// (`users/get-frequently-replied-users`). Defaults to the account's
+/// own user, which is what the composer's mention suggestions ask for.
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_frequently_replied_users(
+    app_state: State<'_, AppState>,
+    account_id: String,
+    user_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<FrequentlyRepliedUser>> {
+    let (db, client) = app_state.ready().await;
+    let (host, token) = get_credentials_or_anon(&db, &account_id)?;
+    let user_id = match user_id {
+        Some(id) => id,
+        None => db
+            .get_account(&account_id)?
+            .ok_or_else(|| NoteDeckError::AccountNotFound(account_id.clone()))?
+            .user_id,
+    };
+    typed_request(
+        &client,
+        &host,
+        &token,
+        "users/get-frequently-replied-users",
+        serde_json::json!({ "userId": user_id, "limit": limit.unwrap_or(10).clamp(1, 100) }),
+    )
+    .await
+}
+
 // --- Mute / Block ---
 
 #[tauri::command]
@@ -340,6 +472,124 @@ pub async fn api_unmute_user(
     client.unmute_user(&host, &token, &user_id).await
 }
 
+/// How the server lets a followed user's renotes be hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum RenoteMuteSupport {
+    /// `renote-mute/create` / `renote-mute/delete` (Misskey and most forks)
+    Endpoint,
+    /// `withRenotes` on `following/update`, for forks without renote-mute
+    FollowingUpdate,
+    Unsupported,
+}
+
+/// Detected support per host. Endpoint lists only change with a server
+/// upgrade, so this lives for the process lifetime. Only successful
+/// detections are stored; a failed lookup is retried on the next call.
+static RENOTE_MUTE_SUPPORT: LazyLock<Mutex<HashMap<String, RenoteMuteSupport>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn classify_renote_mute(
+    endpoints: &[String],
+    following_update_params: &[String],
+) -> RenoteMuteSupport {
+    if endpoints.iter().any(|e| e == "renote-mute/create") {
+        RenoteMuteSupport::Endpoint
+    } else if following_update_params.iter().any(|p| p == "withRenotes") {
+        RenoteMuteSupport::FollowingUpdate
+    } else {
+        RenoteMuteSupport::Unsupported
+    }
+}
+
+async fn renote_mute_support(client: &MisskeyClient, host: &str) -> Result<RenoteMuteSupport> {
+    if let Some(support) = RENOTE_MUTE_SUPPORT.lock().unwrap().get(host) {
+        return Ok(*support);
+    }
+    let endpoints = client.get_endpoints(host).await?;
+    let params = if endpoints.iter().any(|e| e == "following/update") {
+        client.get_endpoint_params(host, "following/update").await?
+    } else {
+        Vec::new()
+    };
+    let support = classify_renote_mute(&endpoints, &params);
+    RENOTE_MUTE_SUPPORT
+        .lock()
+        .unwrap()
+        .insert(host.to_string(), support);
+    Ok(support)
+}
+
+/// Which way (if any) the account's server supports muting renotes only.
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_renote_mute_support(
+    app_state: State<'_, AppState>,
+    account_id: String,
+) -> Result<RenoteMuteSupport> {
+    let (client, host, _token) = app_state.authed(&account_id).await?;
+    renote_mute_support(&client, &host).await
+}
+
+/// Mute or unmute a user's renotes through whichever route the server has:
+/// the renote-mute endpoints, or `following/update` (followed users only).
+#[tauri::command]
+#[specta::specta]
+pub async fn api_set_renote_mute(
+    app_state: State<'_, AppState>,
+    account_id: String,
+    user_id: String,
+    muted: bool,
+) -> Result<RenoteMuteSupport> {
+    let (client, host, token) = app_state.authed(&account_id).await?;
+    let support = renote_mute_support(&client, &host).await?;
+    match support {
+        RenoteMuteSupport::Endpoint if muted => {
+            client.renote_mute_user(&host, &token, &user_id).await?;
+        }
+        RenoteMuteSupport::Endpoint => {
+            client.unrenote_mute_user(&host, &token, &user_id).await?;
+        }
+        RenoteMuteSupport::FollowingUpdate => {
+            client
+                .request(
+                    &host,
+                    &token,
+                    "following/update",
+                    serde_json::json!({ "userId": user_id, "withRenotes": !muted }),
+                )
+                .await?;
+        }
+        RenoteMuteSupport::Unsupported => {
+            return Err(NoteDeckError::InvalidInput(format!(
+                "{host} does not support muting renotes"
+            )));
+        }
+    }
+    Ok(support)
+}
+
+/// Whether a followed user's renotes are hidden through `following/update`,
+/// as the server reports the follow's `withRenotes` on `users/show`. `None`
+/// when the server doesn't report it (not following, or an older fork).
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_following_renotes_muted(
+    app_state: State<'_, AppState>,
+    account_id: String,
+    user_id: String,
+) -> Result<Option<bool>> {
+    let (client, host, token) = app_state.authed(&account_id).await?;
+    let params = serde_json::json!({ "userId": user_id });
+    let user = client.request(&host, &token, "users/show", params).await?;
+    Ok(following_renotes_muted(&user))
+}
+
+fn following_renotes_muted(user: &serde_json::Value) -> Option<bool> {
+    let with_renotes = user.get("withRenotes")?.as_bool()?;
+    Some(!with_renotes)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn api_renote_mute_user(
@@ -528,11 +778,13 @@ pub async fn api_search_hashtags(
 #[specta::specta]
 pub async fn api_ap_show(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     uri: String,
 ) -> Result<serde_json::Value> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.ap_show(&host, &token, &uri).await
+    let object = client.ap_show(&host, &token, &uri).await?;
+    Ok(gate.gate(object))
 }
 
 // --- User-scoped raw endpoints (薄ラッパー) ---
@@ -544,11 +796,13 @@ pub async fn api_ap_show(
 #[specta::specta]
 pub async fn api_get_user_raw(
     app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
     account_id: String,
     params: serde_json::Value,
 ) -> Result<serde_json::Value> {
     let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
-    client.request(&host, &token, "users/show", params).await
+    let user = client.request(&host, &token, "users/show", params).await?;
+    Ok(gate.gate(user))
 }
 
 #[tauri::command]
@@ -595,3 +849,43 @@ pub async fn api_get_user_gallery_by(
     typed_request(&client, &host, &token, "users/gallery/posts", params).await
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn renote_mute_prefers_the_dedicated_endpoint() {
+        let endpoints = strings(&["following/update", "renote-mute/create"]);
+        assert_eq!(
+            classify_renote_mute(&endpoints, &strings(&["userId", "withRenotes"])),
+            RenoteMuteSupport::Endpoint
+        );
+    }
+
+    #[test]
+    fn renote_mute_falls_back_to_following_update() {
+        let endpoints = strings(&["following/update"]);
+        assert_eq!(
+            classify_renote_mute(&endpoints, &strings(&["userId", "notify", "withRenotes"])),
+            RenoteMuteSupport::FollowingUpdate
+        );
+        assert_eq!(
+            classify_renote_mute(&endpoints, &strings(&["userId", "notify", "withReplies"])),
+            RenoteMuteSupport::Unsupported
+        );
+    }
+
+    #[test]
+    fn following_renotes_muted_reads_with_renotes() {
+        let muted = serde_json::json!({ "id": "u1", "withRenotes": false });
+        assert_eq!(following_renotes_muted(&muted), Some(true));
+        let shown = serde_json::json!({ "id": "u1", "withRenotes": true });
+        assert_eq!(following_renotes_muted(&shown), Some(false));
+        let unknown = serde_json::json!({ "id": "u1" });
+        assert_eq!(following_renotes_muted(&unknown), None);
+    }
+}
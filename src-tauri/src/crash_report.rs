@@ -0,0 +1,433 @@
+//! クラッシュレポート (opt-in)。
+//!
+//! 有効にすると、Rust の panic (panic hook) とフロントの未捕捉エラー
+//! (`crash_report_capture`) を `app_dir/crash-reports/<ulid>.json` に 1 件 1 ファイルで
+//! 残す。issue に添付してもらう前提なので、ユーザーのデータが混ざりうる部分は
+//! 書き出さない。panic の payload とフロントのエラーメッセージは本文を捨てて
+//! 短いハッシュ (同じ原因のレポートを突き合わせる用) とエラー名だけにし、
+//! バックトレース / スタックはフレーム行だけを残したうえでトークン類を伏せる。
+//!
+//! 既定は無効で、設定はバックエンド設定 (`settings.rs`) の `crashReports`
+//! セクションに保存する。release ビルドは
+//! `panic = "abort"` なので、hook の中で同期的に書き切ってから既定の hook
+//! (stderr 出力) へ渡す。
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use notecli::error::NoteDeckError;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use tauri::State;
+
+use crate::settings::SettingsStore;
+
+const REPORTS_DIR: &str = "crash-reports";
+/// 残すレポートの上限。超えたら古いものから消す。
+const MAX_REPORTS: usize = 20;
+const MAX_BACKTRACE_CHARS: usize = 16_000;
+const REDACTED: &str = "[REDACTED]";
+
+/// バックエンド設定の `crashReports` セクション。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CrashReportConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum CrashKind {
+    /// Rust 側の panic
+    Panic,
+    /// フロントの未捕捉エラー / Promise rejection
+    Frontend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_ms: i64,
+    pub kind: CrashKind,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// panic したスレッド名 (フロントは None)
+    pub thread: Option<String>,
+    /// `file:line:column` (フロントは None)
+    pub location: Option<String>,
+    /// エラー名 (分かれば) とメッセージ本文のハッシュ。本文そのものは残さない
+    pub message: String,
+    /// Rust のバックトレース / JS のスタックのフレーム行
+    pub backtrace: Option<String>,
+}
+
+/// レポート id。同じミリ秒内でも新しい順に並ぶよう単調増加の ULID を使う。
+static IDS: Mutex<ulid::Generator> = Mutex::new(ulid::Generator::new());
+
+fn next_id() -> String {
+    let mut ids = IDS.lock().unwrap_or_else(|e| e.into_inner());
+    ids.generate()
+        .unwrap_or_else(|_| ulid::Ulid::new())
+        .to_string()
+}
+
+impl CrashReport {
+    fn new(kind: CrashKind, message: &str, backtrace: Option<&str>) -> Self {
+        Self {
+            id: next_id(),
+            created_ms: crate::jobs::now_ms(),
+            kind,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: None,
+            location: None,
+            message: summarize(message),
+            backtrace: backtrace.map(|b| truncate(&frames(b), MAX_BACKTRACE_CHARS)),
+        }
+    }
+}
+
+/// 先頭のエラー名 (`TypeError: ...`)。
+static ERROR_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z][A-Za-z]*(?:Error|Exception))(?::|$)").unwrap());
+
+/// メッセージ本文を `エラー名 sha256:<12 桁> (<文字数> chars)` にする。
+/// 本文にはノートや入力が紛れうるので、伏せ字ではなく丸ごと捨てる。
+fn summarize(message: &str) -> String {
+    let digest = Sha256::digest(message.as_bytes());
+    let hex: String = digest[..6].iter().map(|b| format!("{b:02x}")).collect();
+    let hash = format!("sha256:{hex} ({} chars)", message.chars().count());
+    match ERROR_NAME.captures(message) {
+        Some(caps) => format!("{} {hash}", &caps[1]),
+        None => hash,
+    }
+}
+
+/// バックトレース / スタックのフレーム行。Rust の `12: crate::f` と
+/// `at src/x.rs:1:2`、V8 の `at f (url:1:2)`、Firefox / Safari の `f@url:1:2`。
+static FRAME_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^\s*(?:",
+        r"\d+:\s+(?:0x[0-9a-f]+ - )?(?:<unknown>|__\S+|main|<?[A-Za-z_]\w*(?:::|<).*)",
+        r"|at .*(?::\d+:\d+\)?|\(<anonymous>\)|\(native\))",
+        r"|[\w$.<>/\[\]]*@\S+:\d+:\d+",
+        r")$",
+    ))
+    .unwrap()
+});
+
+/// フレーム行だけを残し (スタック先頭のメッセージの繰り返し等は落とす)、
+/// URL に紛れたトークン類を伏せる。
+fn frames(stack: &str) -> String {
+    let kept: Vec<&str> = stack
+        .lines()
+        .filter(|line| FRAME_LINE.is_match(line))
+        .collect();
+    redact(&kept.join("\n"))
+}
+
+/// `key: value` / `"key":"value"` / `key=value` の形で値を伏せるキー。
+/// 資格情報に加え、ノート本文が入りうるフィールドも対象にする。
+static SENSITIVE_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(token|access_?token|i|secret|password|passphrase|authorization|api_?key|text|cw|content|body|comment)("?\s*[:=]\s*)("(?:[^"\\]|\\.)*"|Some\("(?:[^"\\]|\\.)*"\)|[^\s,&}\])]+)"#,
+    )
+    .unwrap()
+});
+static BEARER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Bearer\s+[A-Za-z0-9._\-]+").unwrap());
+/// 長い文字列リテラル (Debug 出力に紛れたノート本文など)。
+static LONG_STRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""(?:[^"\\]|\\.){64,}""#).unwrap());
+/// 英数字が混ざった 16 文字以上の語 (Misskey のアクセストークン等)。
+static TOKEN_LIKE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[A-Za-z0-9]{16,}\b").unwrap());
+
+/// フレーム行から資格情報とノート本文らしき値を伏せる。
+fn redact(s: &str) -> String {
+    let s = BEARER.replace_all(s, format!("Bearer {REDACTED}"));
+    let s = SENSITIVE_FIELD.replace_all(&s, format!("${{1}}${{2}}{REDACTED}"));
+    let s = LONG_STRING.replace_all(&s, format!("\"{REDACTED}\""));
+    TOKEN_LIKE
+        .replace_all(&s, |caps: &Captures| {
+            let word = &caps[0];
+            let mixed = word.bytes().any(|b| b.is_ascii_digit())
+                && word.bytes().any(|b| b.is_ascii_alphabetic());
+            let out = if mixed { REDACTED } else { word };
+            out.to_string()
+        })
+        .into_owned()
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>")
+}
+
+pub struct CrashReporter {
+    settings: Arc<SettingsStore>,
+    dir: PathBuf,
+    /// 設定の写し。panic hook の中でロックを取らずに読む。
+    enabled: AtomicBool,
+}
+
+impl CrashReporter {
+    fn load(app_dir: &Path, settings: Arc<SettingsStore>) -> Self {
+        let enabled = settings.read(|s| s.crash_reports.enabled);
+        Self {
+            settings,
+            dir: app_dir.join(REPORTS_DIR),
+            enabled: AtomicBool::new(enabled),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), NoteDeckError> {
+        self.settings.update(|s| {
+            s.crash_reports = CrashReportConfig { enabled };
+            Ok(())
+        })?;
+        self.enabled.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 有効なら 1 件書き出し、上限を超えた古いレポートを消す。
+    fn record(&self, report: &CrashReport) -> std::io::Result<bool> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_vec_pretty(report)?;
+        std::fs::write(self.dir.join(format!("{}.json", report.id)), json)?;
+        for stale in self.report_paths().into_iter().skip(MAX_REPORTS) {
+            let _ = std::fs::remove_file(stale);
+        }
+        Ok(true)
+    }
+
+    /// レポートファイルを新しい順に返す (ULID 名なので名前の降順)。
+    fn report_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort_unstable();
+        paths.reverse();
+        paths
+    }
+
+    fn list(&self) -> Vec<CrashReport> {
+        self.report_paths()
+            .iter()
+            .filter_map(|path| std::fs::read(path).ok())
+            .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+            .collect()
+    }
+}
+
+/// panic hook を差し込み、コマンド用の reporter を返す。既存の hook
+/// (既定の stderr 出力) は書き出しの後にそのまま呼ぶ。
+pub fn install(app_dir: &Path, settings: Arc<SettingsStore>) -> Arc<CrashReporter> {
+    let reporter = Arc::new(CrashReporter::load(app_dir, settings));
+    let hook_reporter = reporter.clone();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if hook_reporter.is_enabled() {
+            let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+            let mut report = CrashReport::new(
+                CrashKind::Panic,
+                panic_message(info.payload()),
+                Some(&backtrace),
+            );
+            report.thread = std::thread::current().name().map(str::to_string);
+            report.location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            if let Err(e) = hook_reporter.record(&report) {
+                eprintln!("[crash-report] failed to write report: {e}");
+            }
+        }
+        previous(info);
+    }));
+    reporter
+}
+
+/// クラッシュレポートの記録が有効か。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_reports_get_enabled(reporter: State<'_, Arc<CrashReporter>>) -> bool {
+    reporter.is_enabled()
+}
+
+/// クラッシュレポートの記録を有効 / 無効にする。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_reports_set_enabled(
+    reporter: State<'_, Arc<CrashReporter>>,
+    enabled: bool,
+) -> Result<(), NoteDeckError> {
+    reporter.set_enabled(enabled)
+}
+
+/// 保存済みのレポートを新しい順に返す。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_reports_list(reporter: State<'_, Arc<CrashReporter>>) -> Vec<CrashReport> {
+    reporter.list()
+}
+
+/// 保存済みのレポートをすべて消し、消した件数を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_reports_clear(reporter: State<'_, Arc<CrashReporter>>) -> u32 {
+    reporter
+        .report_paths()
+        .iter()
+        .filter(|path| std::fs::remove_file(path).is_ok())
+        .count() as u32
+}
+
+/// レポートの保存先フォルダ (「フォルダを開く」用)。無ければ作る。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_reports_dir(reporter: State<'_, Arc<CrashReporter>>) -> Result<String, NoteDeckError> {
+    std::fs::create_dir_all(&reporter.dir)
+        .map_err(|e| NoteDeckError::InvalidInput(e.to_string()))?;
+    Ok(reporter.dir.to_string_lossy().into_owned())
+}
+
+/// フロントの未捕捉エラーを記録する。無効なら何もせず false。
+#[tauri::command]
+#[specta::specta]
+pub fn crash_report_capture(
+    reporter: State<'_, Arc<CrashReporter>>,
+    message: String,
+    stack: Option<String>,
+) -> Result<bool, NoteDeckError> {
+    let report = CrashReport::new(CrashKind::Frontend, &message, stack.as_deref());
+    reporter
+        .record(&report)
+        .map_err(|e| NoteDeckError::InvalidInput(format!("Failed to write crash report: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_credentials_and_note_text() {
+        assert_eq!(
+            redact(r#"request failed: {"i":"abcDEF123456ghiJKL","text":"secret note"}"#),
+            r#"request failed: {"i":[REDACTED],"text":[REDACTED]}"#
+        );
+        assert_eq!(
+            redact("retrying with Bearer abc.def-ghi"),
+            "retrying with Bearer [REDACTED]"
+        );
+        assert_eq!(
+            redact("Note { id: \"9abc\", text: Some(\"hello\"), cw: None }"),
+            "Note { id: \"9abc\", text: [REDACTED], cw: [REDACTED] }"
+        );
+        assert_eq!(
+            redact("https://misskey.example/api?i=Ab12Cd34Ef56Gh78&limit=10"),
+            "https://misskey.example/api?i=[REDACTED]&limit=10"
+        );
+    }
+
+    #[test]
+    fn redacts_long_literals_and_token_like_words() {
+        let long = format!("called unwrap on \"{}\"", "あ".repeat(80));
+        assert_eq!(redact(&long), "called unwrap on \"[REDACTED]\"");
+        assert_eq!(
+            redact("key 0123456789abcdefXYZ in streaming.rs:42"),
+            "key [REDACTED] in streaming.rs:42"
+        );
+        assert_eq!(
+            redact("index out of bounds: the len is 3"),
+            "index out of bounds: the len is 3"
+        );
+    }
+
+    #[test]
+    fn messages_keep_only_the_error_name_and_a_hash() {
+        let summary = summarize("TypeError: cannot read 'x' of note 今日のごはん");
+        assert!(summary.starts_with("TypeError sha256:"), "{summary}");
+        assert!(!summary.contains("ごはん"));
+        // 同じ本文は同じハッシュになる
+        assert_eq!(
+            summary,
+            summarize("TypeError: cannot read 'x' of note 今日のごはん")
+        );
+
+        let panic = summarize("short note text");
+        assert!(panic.starts_with("sha256:"), "{panic}");
+        assert!(panic.ends_with("(15 chars)"), "{panic}");
+    }
+
+    #[test]
+    fn stacks_keep_only_frame_lines() {
+        let js = "Error: hello from my note\nsecond line of the note\n    \
+                  at render (http://localhost:1420/src/a.ts:10:5)\n    \
+                  at new Promise (<anonymous>)\n\
+                  flush@http://localhost:1420/src/b.ts:3:7";
+        assert_eq!(
+            frames(js),
+            "    at render (http://localhost:1420/src/a.ts:10:5)\n    \
+             at new Promise (<anonymous>)\n\
+             flush@http://localhost:1420/src/b.ts:3:7"
+        );
+        let rust = "   0: std::backtrace::Backtrace::force_capture\n             \
+                    at /rustc/library/std/src/backtrace.rs:312:13\n   \
+                    1: notedeck_lib::streaming::run\nnote text that leaked";
+        assert_eq!(
+            frames(rust),
+            "   0: std::backtrace::Backtrace::force_capture\n             \
+             at /rustc/library/std/src/backtrace.rs:312:13\n   \
+             1: notedeck_lib::streaming::run"
+        );
+    }
+
+    #[test]
+    fn records_only_when_enabled_and_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Arc::new(SettingsStore::load(dir.path()));
+        let reporter = CrashReporter::load(dir.path(), settings);
+        let report = CrashReport::new(CrashKind::Frontend, "boom", None);
+        assert!(!reporter.record(&report).unwrap());
+        assert!(reporter.list().is_empty());
+
+        reporter.set_enabled(true).unwrap();
+        let reloaded = Arc::new(SettingsStore::load(dir.path()));
+        assert!(CrashReporter::load(dir.path(), reloaded).is_enabled());
+        for i in 0..MAX_REPORTS + 3 {
+            let report = CrashReport::new(CrashKind::Frontend, &format!("boom {i}"), None);
+            assert!(reporter.record(&report).unwrap());
+        }
+        let reports = reporter.list();
+        assert_eq!(reports.len(), MAX_REPORTS);
+        assert_eq!(
+            reports[0].message,
+            summarize(&format!("boom {}", MAX_REPORTS + 2))
+        );
+    }
+}
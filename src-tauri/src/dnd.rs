@@ -0,0 +1,299 @@
+//! OS の集中モード / 応答不可 (Do Not Disturb) 検知。
+//!
+//! DND 中は OS 通知 (トースト) を抑制する。stream-envelope は従来どおり
+//! 流れるので、アプリ内の未読カウントやバッジは影響を受けない。
+//! 設定で「DND を無視して常に通知」に切り替えられる (プレゼン中以外でも
+//! DND を常用しているユーザー向け)。
+//!
+//! プラットフォーム別の検知:
+//! - Windows: `SHQueryUserNotificationState` (プレゼンテーションモード /
+//!   全画面アプリ)。「応答不可」/ 集中モード (Focus Assist) はこの API に
+//!   現れず、公開 API もない (WNF / レジストリは非公開で版ごとに変わる) ので
+//!   検知しない。`QUNS_QUIET_TIME` は OS 導入直後の静かな時間で DND ではない
+//! - Linux: GNOME セッション (`XDG_CURRENT_DESKTOP`) では `gsettings` の
+//!   show-banners、それ以外は freedesktop 通知サーバーの `Inhibited`
+//!   プロパティ (KDE 等, busctl 経由)。GNOME 以外でも gsettings の値は
+//!   残っていることがあるので、セッションで判定する
+//! - macOS / モバイル: 公開 API がないため不明扱い (= 抑制しない)
+//!
+//! 外部コマンドを通知ごとに起動しないよう、判定結果は CACHE_TTL だけ再利用する。
+//! 期限切れのときも呼び出し元 (通知の送出経路) では待たず、直前の値を返して
+//! `spawn_blocking` で問い合わせ直す。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{Manager, State};
+
+/// OS 状態の再問い合わせ間隔。
+const CACHE_TTL: Duration = Duration::from_secs(15);
+
+/// DND の扱い (ユーザー設定)。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum DndMode {
+    /// OS の DND に従って OS 通知を抑制する
+    #[default]
+    FollowSystem,
+    /// DND 中でも OS 通知を出す
+    AlwaysNotify,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DndStatus {
+    pub mode: DndMode,
+    /// OS の DND 状態。検知できない環境では null。
+    pub os_active: Option<bool>,
+    /// 現在 OS 通知を抑制しているか。
+    pub suppressing: bool,
+}
+
+type DndCache = Arc<Mutex<Option<(Instant, Option<bool>)>>>;
+
+#[derive(Default)]
+pub struct DndState {
+    mode: Mutex<DndMode>,
+    cache: DndCache,
+    /// 問い合わせ中 (二重に外部コマンドを起動しない)
+    refreshing: Arc<AtomicBool>,
+}
+
+impl DndState {
+    pub fn mode(&self) -> DndMode {
+        *self.mode.lock().unwrap()
+    }
+
+    pub fn set_mode(&self, mode: DndMode) {
+        *self.mode.lock().unwrap() = mode;
+    }
+
+    /// キャッシュした OS の DND 状態を返す。期限切れなら直前の値を返しつつ
+    /// 問い合わせ直す (まだ一度も取れていなければ不明 = None)。
+    pub fn os_active(&self) -> Option<bool> {
+        let cached = *self.cache.lock().unwrap();
+        match cached {
+            Some((at, value)) if at.elapsed() < CACHE_TTL => value,
+            stale => {
+                self.refresh();
+                stale.and_then(|(_, value)| value)
+            }
+        }
+    }
+
+    /// OS の状態をブロッキング用スレッドで問い合わせてキャッシュを更新する。
+    /// 起動時にも呼んでおき、最初の通知から判定できるようにする。
+    pub fn refresh(&self) {
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let cache = self.cache.clone();
+        let refreshing = self.refreshing.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let value = query_os();
+            *cache.lock().unwrap() = Some((Instant::now(), value));
+            refreshing.store(false, Ordering::Release);
+        });
+    }
+
+    /// OS 通知を抑制すべきか。
+    pub fn should_suppress(&self) -> bool {
+        match self.mode() {
+            DndMode::AlwaysNotify => false,
+            DndMode::FollowSystem => self.os_active().unwrap_or(false),
+        }
+    }
+
+    fn status(&self) -> DndStatus {
+        let mode = self.mode();
+        let os_active = self.os_active();
+        DndStatus {
+            mode,
+            os_active,
+            suppressing: mode == DndMode::FollowSystem && os_active.unwrap_or(false),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_cached(&self, value: Option<bool>) {
+        *self.cache.lock().unwrap() = Some((Instant::now(), value));
+    }
+}
+
+/// streaming から呼ぶ: state 未登録 (ユニットテスト等) は抑制しない。
+pub fn should_suppress<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    app.try_state::<DndState>()
+        .is_some_and(|state| state.should_suppress())
+}
+
+#[cfg(target_os = "windows")]
+fn query_os() -> Option<bool> {
+    use windows::Win32::UI::Shell::SHQueryUserNotificationState;
+    // SAFETY: 引数なしの読み取り専用 Shell API
+    let state = unsafe { SHQueryUserNotificationState() }.ok()?;
+    Some(quns_suppresses(state.0))
+}
+
+/// `SHQueryUserNotificationState` の値 (`QUERY_USER_NOTIFICATION_STATE`) が
+/// 通知を控える状態か。全画面アプリ (`QUNS_BUSY` / `QUNS_RUNNING_D3D_FULL_SCREEN`)
+/// とプレゼンテーションモードだけを DND とみなす。
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn quns_suppresses(state: i32) -> bool {
+    const QUNS_BUSY: i32 = 2;
+    const QUNS_RUNNING_D3D_FULL_SCREEN: i32 = 3;
+    const QUNS_PRESENTATION_MODE: i32 = 4;
+    matches!(
+        state,
+        QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn query_os() -> Option<bool> {
+    use std::process::Command;
+
+    let run = |cmd: &str, args: &[&str]| -> Option<String> {
+        let out = Command::new(cmd).args(args).output().ok()?;
+        out.status
+            .success()
+            .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+    };
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    if is_gnome_session(&desktop) {
+        if let Some(out) = run(
+            "gsettings",
+            &["get", "org.gnome.desktop.notifications", "show-banners"],
+        ) {
+            if let Some(active) = parse_gsettings_show_banners(&out) {
+                return Some(active);
+            }
+        }
+    }
+    run(
+        "busctl",
+        &[
+            "--user",
+            "get-property",
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+            "Inhibited",
+        ],
+    )
+    .and_then(|out| parse_busctl_bool(&out))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn query_os() -> Option<bool> {
+    None
+}
+
+/// `XDG_CURRENT_DESKTOP` (":" 区切り、例: "ubuntu:GNOME") が GNOME か。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_gnome_session(desktop: &str) -> bool {
+    desktop
+        .split(':')
+        .any(|name| name.eq_ignore_ascii_case("GNOME"))
+}
+
+/// `gsettings get ... show-banners` の出力 ("true"/"false")。
+/// show-banners = false が GNOME の「通知を表示しない (DND)」。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_gsettings_show_banners(out: &str) -> Option<bool> {
+    match out.trim() {
+        "true" => Some(false),
+        "false" => Some(true),
+        _ => None,
+    }
+}
+
+/// `busctl get-property` の bool 出力 ("b true")。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_busctl_bool(out: &str) -> Option<bool> {
+    match out.trim().strip_prefix("b ")? {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// DND 状態と設定を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn dnd_get_status(state: State<'_, DndState>) -> DndStatus {
+    state.status()
+}
+
+/// DND の扱いを設定する (フロントの設定値を起動時・変更時に反映)。
+#[tauri::command]
+#[specta::specta]
+pub fn dnd_set_mode(state: State<'_, DndState>, mode: DndMode) -> DndStatus {
+    state.set_mode(mode);
+    state.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_system_suppresses_only_when_os_active() {
+        let state = DndState::default();
+        state.set_cached(Some(true));
+        assert!(state.should_suppress());
+
+        state.set_cached(Some(false));
+        assert!(!state.should_suppress());
+
+        // 検知できない環境では抑制しない
+        state.set_cached(None);
+        assert!(!state.should_suppress());
+    }
+
+    #[test]
+    fn always_notify_overrides_os_dnd() {
+        let state = DndState::default();
+        state.set_cached(Some(true));
+        state.set_mode(DndMode::AlwaysNotify);
+        assert!(!state.should_suppress());
+        let status = state.status();
+        assert_eq!(status.os_active, Some(true));
+        assert!(!status.suppressing);
+    }
+
+    #[test]
+    fn parses_linux_outputs() {
+        assert_eq!(parse_gsettings_show_banners("false\n"), Some(true));
+        assert_eq!(parse_gsettings_show_banners("true\n"), Some(false));
+        assert_eq!(parse_gsettings_show_banners("No such schema"), None);
+        assert_eq!(parse_busctl_bool("b true\n"), Some(true));
+        assert_eq!(parse_busctl_bool("b false"), Some(false));
+        assert_eq!(parse_busctl_bool("s x"), None);
+    }
+
+    #[test]
+    fn maps_windows_notification_states() {
+        // QUNS_BUSY / QUNS_RUNNING_D3D_FULL_SCREEN / QUNS_PRESENTATION_MODE
+        assert!(quns_suppresses(2));
+        assert!(quns_suppresses(3));
+        assert!(quns_suppresses(4));
+        // QUNS_NOT_PRESENT (ロック中) / QUNS_ACCEPTS_NOTIFICATIONS /
+        // QUNS_QUIET_TIME (導入直後) / QUNS_APP
+        assert!(!quns_suppresses(1));
+        assert!(!quns_suppresses(5));
+        assert!(!quns_suppresses(6));
+        assert!(!quns_suppresses(7));
+    }
+
+    #[test]
+    fn gsettings_is_trusted_only_on_gnome() {
+        assert!(is_gnome_session("GNOME"));
+        assert!(is_gnome_session("ubuntu:GNOME"));
+        assert!(!is_gnome_session("KDE"));
+        assert!(!is_gnome_session("X-Cinnamon"));
+        assert!(!is_gnome_session(""));
+    }
+}
@@ -0,0 +1,755 @@
+//! カスタム絵文字 shortcode → URL の解決キャッシュ。
+//!
+//! リアクションの多いノートを描画するとき、フロントが絵文字ごとに問い合わせる
+//! 代わりに `shortcode@host` の一覧を 1 回の IPC で解決する。ホストごとの
+//! 絵文字一覧をメモリに持ち、未取得のホストは 1 回だけ取りに行く (同じホストへの
+//! 同時要求は `Coalescer` で合流させる)。取得に失敗したホストは
+//! `FAILED_BACKOFF` の間は再取得しない。
+//!
+//! 同じ一覧をリアクションピッカー / `:` 補完の検索にも使う (`search`)。
+//! 数万件のカスタム絵文字でも JS 側で巨大な配列を舐めずに済むよう、名前と
+//! エイリアスを小文字化した検索キーを取得時に 1 度だけ作っておく。
+//!
+//! アカウントのホストと最近使ったホストは `SYNC_INTERVAL` ごとに取り直し、
+//! 差分 (追加 / 削除 / 改名 / 変更) だけを `nd:emojis-updated` で流す。差分の
+//! 基準はフロントが受け取り済みと返した (`ack_emojis`) 版の一覧なので、
+//! イベントを取りこぼしても次の差分にまとめて載る。フロントの emojis ストアは
+//! 差分を当てるだけで、一覧を丸ごと取り直さない。`STALE_AFTER` の間使われて
+//! いないアカウント以外のホストは同期のついでに追い出す。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use notecli::api::MisskeyClient;
+use notecli::error::NoteDeckError;
+use notecli::models::ServerEmoji;
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::request_dedup::Coalescer;
+
+/// 差分が見つかるたびに emit する (payload は [`EmojiDiff`])。
+pub const EMOJIS_UPDATED_EVENT: &str = "nd:emojis-updated";
+
+/// ホストの絵文字一覧を取り直すまでの期間。
+const HOST_TTL: Duration = Duration::from_secs(60 * 60);
+/// 取得に失敗したホストを再試行しない期間 (フロントの emojis ストアと同じ)。
+const FAILED_BACKOFF: Duration = Duration::from_secs(30);
+/// 差分同期の間隔。HOST_TTL より短くし、同期中のホストは期限切れにしない。
+const SYNC_INTERVAL: Duration = Duration::from_secs(20 * 60);
+/// この間に使われたホストだけを同期する (アカウントのホストは常に同期する)。
+const RECENT_USE: Duration = Duration::from_secs(60 * 60);
+/// この間使われていないホストは追い出す (アカウントのホストは残す)。
+const STALE_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// ホスト 1 つ分の絵文字一覧。
+struct HostEmojis {
+    /// shortcode → URL
+    urls: HashMap<String, String>,
+    emojis: Vec<ServerEmoji>,
+    /// `emojis` と同じ並びの検索キー (小文字化した名前 + エイリアス)
+    keys: Vec<SearchKeys>,
+}
+
+struct SearchKeys {
+    name: String,
+    aliases: Vec<String>,
+}
+
+impl HostEmojis {
+    fn new(emojis: Vec<ServerEmoji>) -> Self {
+        let urls = emojis
+            .iter()
+            .map(|e| (e.name.clone(), e.url.clone()))
+            .collect();
+        let keys = emojis
+            .iter()
+            .map(|e| SearchKeys {
+                name: e.name.to_lowercase(),
+                aliases: e
+                    .aliases
+                    .iter()
+                    .filter(|a| !a.is_empty())
+                    .map(|a| a.to_lowercase())
+                    .collect(),
+            })
+            .collect();
+        Self { urls, emojis, keys }
+    }
+}
+
+/// 名前だけが変わった絵文字 (画像 URL が同じ)。
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EmojiRename {
+    /// 変更前の shortcode
+    pub from: String,
+    /// 変更後の絵文字
+    pub emoji: ServerEmoji,
+}
+
+/// ホストの絵文字一覧を取り直したときの差分。
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EmojiDiff {
+    pub host: String,
+    /// 差分を当てた後の一覧の版。当てたら `ack_emojis` で返す
+    pub version: u32,
+    pub added: Vec<ServerEmoji>,
+    /// 削除された shortcode
+    pub removed: Vec<String>,
+    pub renamed: Vec<EmojiRename>,
+    /// 名前はそのままで URL / カテゴリ / エイリアスが変わった絵文字
+    pub updated: Vec<ServerEmoji>,
+}
+
+impl EmojiDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.updated.is_empty()
+    }
+}
+
+fn same_emoji(a: &ServerEmoji, b: &ServerEmoji) -> bool {
+    a.url == b.url && a.category == b.category && a.aliases == b.aliases
+}
+
+/// `old` → `new` の差分。消えた名前と増えた名前で URL が同じものは改名とみなす。
+fn diff(host: &str, old: &[ServerEmoji], new: &[ServerEmoji]) -> EmojiDiff {
+    let old_by_name: HashMap<&str, &ServerEmoji> =
+        old.iter().map(|e| (e.name.as_str(), e)).collect();
+    let new_names: HashSet<&str> = new.iter().map(|e| e.name.as_str()).collect();
+    let gone: Vec<&str> = old
+        .iter()
+        .map(|e| e.name.as_str())
+        .filter(|name| !new_names.contains(name))
+        .collect();
+    let mut gone_by_url: HashMap<&str, &str> = gone
+        .iter()
+        .map(|name| (old_by_name[name].url.as_str(), *name))
+        .collect();
+    let mut renamed_from = HashSet::new();
+
+    let mut out = EmojiDiff {
+        host: host.to_string(),
+        version: 0,
+        added: Vec::new(),
+        removed: Vec::new(),
+        renamed: Vec::new(),
+        updated: Vec::new(),
+    };
+    for emoji in new {
+        match old_by_name.get(emoji.name.as_str()) {
+            Some(prev) if !same_emoji(prev, emoji) => out.updated.push(emoji.clone()),
+            Some(_) => {}
+            None => match gone_by_url.remove(emoji.url.as_str()) {
+                Some(from) => {
+                    renamed_from.insert(from);
+                    out.renamed.push(EmojiRename {
+                        from: from.to_string(),
+                        emoji: emoji.clone(),
+                    });
+                }
+                None => out.added.push(emoji.clone()),
+            },
+        }
+    }
+    out.removed = gone
+        .into_iter()
+        .filter(|name| !renamed_from.contains(name))
+        .map(str::to_string)
+        .collect();
+    out
+}
+
+type Lookup = Arc<HostEmojis>;
+
+struct HostEntry {
+    fetched_at: Instant,
+    /// 最後に解決 / 検索 / 一覧に使った時刻
+    last_used: Instant,
+    lookup: Lookup,
+    /// 一覧が変わるたびに増える版
+    version: u32,
+    /// フロントが受け取り済みの一覧 (まだ無ければ None)。差分はここから取る
+    acked: Option<Lookup>,
+}
+
+impl HostEntry {
+    fn new(lookup: Lookup) -> Self {
+        let now = Instant::now();
+        Self {
+            fetched_at: now,
+            last_used: now,
+            lookup,
+            version: 0,
+            acked: None,
+        }
+    }
+
+    fn in_use(&self, within: Duration) -> bool {
+        self.last_used.elapsed() < within
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    hosts: HashMap<String, HostEntry>,
+    failed: HashMap<String, Instant>,
+}
+
+pub struct EmojiCache {
+    state: RwLock<CacheState>,
+    inflight: Coalescer<Lookup>,
+}
+
+impl Default for EmojiCache {
+    fn default() -> Self {
+        Self {
+            state: RwLock::new(CacheState::default()),
+            // 完了後は hosts に載るので合流だけでよい
+            inflight: Coalescer::new(Duration::ZERO),
+        }
+    }
+}
+
+/// `shortcode@host` を (shortcode, host) に分ける。host が無い / `.` なら
+/// `local_host`。前後の `:` は許容する (`:blobcat@misskey.io:`)。
+fn parse_ref<'a>(raw: &'a str, local_host: &'a str) -> Option<(&'a str, &'a str)> {
+    let raw = raw.trim().trim_matches(':');
+    let (name, host) = match raw.rsplit_once('@') {
+        Some((name, "" | ".")) => (name, local_host),
+        Some((name, host)) => (name, host),
+        None => (raw, local_host),
+    };
+    (!name.is_empty()).then_some((name, host))
+}
+
+impl EmojiCache {
+    fn cached(&self, host: &str) -> Option<Lookup> {
+        let mut state = self.state.write().ok()?;
+        let entry = state
+            .hosts
+            .get_mut(host)
+            .filter(|entry| entry.fetched_at.elapsed() < HOST_TTL)?;
+        entry.last_used = Instant::now();
+        Some(entry.lookup.clone())
+    }
+
+    fn backing_off(&self, host: &str) -> bool {
+        self.state
+            .read()
+            .ok()
+            .and_then(|state| state.failed.get(host).copied())
+            .is_some_and(|at| at.elapsed() < FAILED_BACKOFF)
+    }
+
+    /// `host` の shortcode → URL を返す。未取得 / 期限切れなら取得する。
+    /// 自ホストは `token` 付き、他ホストは匿名で `emojis` を呼ぶ。
+    async fn lookup(&self, client: &MisskeyClient, host: &str, token: &str) -> Option<Lookup> {
+        if let Some(lookup) = self.cached(host) {
+            return Some(lookup);
+        }
+        if self.backing_off(host) {
+            return None;
+        }
+        match self.fetch(client, host, token).await {
+            Ok(lookup) => Some(lookup),
+            Err(e) => {
+                tracing::debug!(%host, "[emoji] failed to fetch emojis: {e}");
+                None
+            }
+        }
+    }
+
+    /// `host` の一覧を取得して載せる。同じホストへの同時要求は合流させる。
+    async fn fetch(
+        &self,
+        client: &MisskeyClient,
+        host: &str,
+        token: &str,
+    ) -> Result<Lookup, NoteDeckError> {
+        let result = self
+            .inflight
+            .run(host.to_string(), || async {
+                let emojis = client.get_server_emojis(host, token).await?;
+                let lookup: Lookup = Arc::new(HostEmojis::new(emojis));
+                Ok::<_, NoteDeckError>(lookup)
+            })
+            .await;
+        if let Ok(mut state) = self.state.write() {
+            match &result {
+                Ok(lookup) => {
+                    state.failed.remove(host);
+                    let entry = state
+                        .hosts
+                        .entry(host.to_string())
+                        .or_insert_with(|| HostEntry::new(lookup.clone()));
+                    if !Arc::ptr_eq(&entry.lookup, lookup) {
+                        entry.lookup = lookup.clone();
+                        entry.version += 1;
+                    }
+                    entry.fetched_at = Instant::now();
+                    entry.last_used = Instant::now();
+                }
+                Err(_) => {
+                    state.failed.insert(host.to_string(), Instant::now());
+                }
+            }
+        }
+        result
+    }
+
+    /// `host` の絵文字一覧を返し、フロントが受け取り済みとして記録する。
+    /// 未取得 / 期限切れなら取得する (失敗はそのまま返す)。
+    pub async fn list(
+        &self,
+        client: &MisskeyClient,
+        host: &str,
+        token: &str,
+    ) -> Result<Vec<ServerEmoji>, NoteDeckError> {
+        let lookup = match self.cached(host) {
+            Some(lookup) => lookup,
+            None => self.fetch(client, host, token).await?,
+        };
+        if let Ok(mut state) = self.state.write() {
+            if let Some(entry) = state.hosts.get_mut(host) {
+                if Arc::ptr_eq(&entry.lookup, &lookup) {
+                    entry.acked = Some(lookup.clone());
+                }
+            }
+        }
+        Ok(lookup.emojis.clone())
+    }
+
+    /// フロントが `version` の差分を当てたことを記録する。その後に一覧が
+    /// 変わっていたら記録せず、次の差分も受け取り済みの版から取る。
+    pub fn ack(&self, host: &str, version: u32) {
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+        if let Some(entry) = state.hosts.get_mut(host) {
+            if entry.version == version {
+                entry.acked = Some(entry.lookup.clone());
+            }
+        }
+    }
+
+    /// 参照の一覧を解決し、入力文字列 → URL の map を返す。解決できなかった
+    /// 参照は含まれない。ホストごとの取得は 1 回にまとめる。
+    pub async fn resolve(
+        &self,
+        client: &MisskeyClient,
+        local_host: &str,
+        token: &str,
+        refs: &[String],
+    ) -> HashMap<String, String> {
+        let mut by_host: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+        for raw in refs {
+            if let Some((name, host)) = parse_ref(raw, local_host) {
+                by_host.entry(host).or_default().push((raw.as_str(), name));
+            }
+        }
+
+        // ホスト単位で並行に引く (初回は未取得ホストの数だけ待つので直列にしない)
+        let lookups = by_host.into_iter().map(|(host, wanted)| async move {
+            let host_token = if host == local_host {
+                token
+            } else if crate::commands::validate_host(host).is_ok() {
+                ""
+            } else {
+                return Vec::<(String, String)>::new();
+            };
+            let Some(lookup) = self.lookup(client, host, host_token).await else {
+                return Vec::new();
+            };
+            wanted
+                .into_iter()
+                .filter_map(|(raw, name)| Some((raw.to_string(), lookup.urls.get(name)?.clone())))
+                .collect::<Vec<_>>()
+        });
+        let mut resolved = HashMap::new();
+        for pairs in futures_util::future::join_all(lookups).await {
+            resolved.extend(pairs);
+        }
+        resolved
+    }
+}
+
+impl EmojiCache {
+    /// `within` の間に使われたホスト。
+    fn recent_hosts(&self, within: Duration) -> Vec<String> {
+        self.state
+            .read()
+            .map(|state| {
+                state
+                    .hosts
+                    .iter()
+                    .filter(|(_, entry)| entry.in_use(within))
+                    .map(|(host, _)| host.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// `keep` 以外で `within` の間使われていないホストと、期限の過ぎた
+    /// 失敗記録を捨てる。
+    fn evict_unused(&self, keep: &HashSet<String>, within: Duration) {
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+        state
+            .hosts
+            .retain(|host, entry| keep.contains(host) || entry.in_use(within));
+        state.failed.retain(|_, at| at.elapsed() < FAILED_BACKOFF);
+    }
+
+    /// `host` の一覧を取り直して差し替え、フロントが受け取り済みの一覧
+    /// (まだ無ければ手元の一覧) との差分を返す。未取得のホストは載せるだけ。
+    /// 差分が無い / 取得に失敗したときは None。
+    pub async fn refresh(
+        &self,
+        client: &MisskeyClient,
+        host: &str,
+        token: &str,
+    ) -> Option<EmojiDiff> {
+        match client.get_server_emojis(host, token).await {
+            Ok(emojis) => self.replace(host, emojis),
+            Err(e) => {
+                tracing::debug!(%host, "[emoji] failed to refresh emojis: {e}");
+                None
+            }
+        }
+    }
+
+    /// 取り直した一覧で差し替え、`refresh` の差分を返す。
+    fn replace(&self, host: &str, emojis: Vec<ServerEmoji>) -> Option<EmojiDiff> {
+        let mut state = self.state.write().ok()?;
+        state.failed.remove(host);
+        let Some(entry) = state.hosts.get_mut(host) else {
+            state.hosts.insert(
+                host.to_string(),
+                HostEntry::new(Arc::new(HostEmojis::new(emojis))),
+            );
+            return None;
+        };
+        let changed = diff(host, &entry.lookup.emojis, &emojis);
+        if !changed.is_empty() {
+            entry.lookup = Arc::new(HostEmojis::new(emojis));
+            entry.version += 1;
+        }
+        entry.fetched_at = Instant::now();
+        let mut changes = match &entry.acked {
+            Some(acked) => diff(host, &acked.emojis, &entry.lookup.emojis),
+            None => changed,
+        };
+        changes.version = entry.version;
+        (!changes.is_empty()).then_some(changes)
+    }
+}
+
+/// 差分をフロントへ流す。
+pub fn emit_diff(app: &AppHandle, diff: &EmojiDiff) {
+    tracing::debug!(
+        host = %diff.host,
+        added = diff.added.len(),
+        removed = diff.removed.len(),
+        renamed = diff.renamed.len(),
+        updated = diff.updated.len(),
+        "[emoji] emoji list changed"
+    );
+    let _ = app.emit(EMOJIS_UPDATED_EVENT, diff);
+}
+
+/// アカウントのホストと最近使ったホストを取り直し、差分を emit する。
+/// アカウントのホストはそのトークンで、他は匿名で取る。
+async fn sync_all(app: &AppHandle) {
+    let app_state = app.state::<crate::commands::AppState>();
+    let (db, client) = app_state.ready().await;
+    let cache = app.state::<EmojiCache>();
+
+    let mut hosts: HashMap<String, String> = HashMap::new();
+    for account in db.load_accounts().unwrap_or_default() {
+        let token = crate::commands::get_credentials(&db, &account.id)
+            .map(|(_, token)| token)
+            .unwrap_or_default();
+        let entry = hosts.entry(account.host).or_default();
+        if entry.is_empty() {
+            *entry = token;
+        }
+    }
+    cache.evict_unused(&hosts.keys().cloned().collect(), STALE_AFTER);
+    for host in cache.recent_hosts(RECENT_USE) {
+        hosts.entry(host).or_default();
+    }
+    let queue = app.state::<crate::host_queue::HostQueue>();
+    for (host, token) in hosts {
+        let refreshed = queue
+            .run(
+                &host,
+                crate::host_queue::Priority::Background,
+                cache.refresh(&client, &host, &token),
+            )
+            .await;
+        if let Some(diff) = refreshed {
+            emit_diff(app, &diff);
+        }
+    }
+}
+
+/// SYNC_INTERVAL ごとに差分同期する worker を起動する。
+pub fn spawn_sync(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SYNC_INTERVAL).await;
+            sync_all(&app).await;
+        }
+    });
+}
+
+/// 一致の強さ。小さいほど上位。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchTier {
+    Exact,
+    AliasExact,
+    Prefix,
+    AliasPrefix,
+    Contains,
+    AliasContains,
+    /// 文字が順に現れる (`bct` → `bocat`)。値は間に挟まった文字数
+    Fuzzy(usize),
+}
+
+/// `query` の文字が `key` に順に現れれば、その間に挟まった文字数を返す。
+fn subsequence_gaps(key: &str, query: &str) -> Option<usize> {
+    let mut chars = key.chars();
+    let mut gaps = 0;
+    let mut started = false;
+    for q in query.chars() {
+        loop {
+            let c = chars.next()?;
+            if c == q {
+                started = true;
+                break;
+            }
+            if started {
+                gaps += 1;
+            }
+        }
+    }
+    Some(gaps)
+}
+
+fn match_tier(keys: &SearchKeys, query: &str) -> Option<MatchTier> {
+    let aliases = || keys.aliases.iter();
+    if keys.name == query {
+        Some(MatchTier::Exact)
+    } else if aliases().any(|a| a == query) {
+        Some(MatchTier::AliasExact)
+    } else if keys.name.starts_with(query) {
+        Some(MatchTier::Prefix)
+    } else if aliases().any(|a| a.starts_with(query)) {
+        Some(MatchTier::AliasPrefix)
+    } else if keys.name.contains(query) {
+        Some(MatchTier::Contains)
+    } else if aliases().any(|a| a.contains(query)) {
+        Some(MatchTier::AliasContains)
+    } else {
+        subsequence_gaps(&keys.name, query).map(MatchTier::Fuzzy)
+    }
+}
+
+/// 一致の強い順 → 名前の短い順 → 名前順に最大 `limit` 件を返す。
+fn rank(host: &HostEmojis, query: &str, limit: usize) -> Vec<ServerEmoji> {
+    let query = query.trim().trim_matches(':').to_lowercase();
+    if query.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+    let mut hits: Vec<(MatchTier, usize)> = host
+        .keys
+        .iter()
+        .enumerate()
+        .filter_map(|(i, keys)| Some((match_tier(keys, &query)?, i)))
+        .collect();
+    let sort_key = |&(tier, i): &(MatchTier, usize)| (tier, host.keys[i].name.len(), i);
+    if hits.len() > limit {
+        hits.select_nth_unstable_by_key(limit - 1, sort_key);
+        hits.truncate(limit);
+    }
+    hits.sort_unstable_by_key(sort_key);
+    hits.into_iter()
+        .map(|(_, i)| host.emojis[i].clone())
+        .collect()
+}
+
+impl EmojiCache {
+    /// `host` のカスタム絵文字を名前 / エイリアスで検索する。未取得なら取得する。
+    pub async fn search(
+        &self,
+        client: &MisskeyClient,
+        host: &str,
+        token: &str,
+        query: &str,
+        limit: usize,
+    ) -> Vec<ServerEmoji> {
+        match self.lookup(client, host, token).await {
+            Some(lookup) => rank(&lookup, query, limit),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ref_splits_shortcode_and_host() {
+        let local = "misskey.example";
+        assert_eq!(parse_ref("blobcat", local), Some(("blobcat", local)));
+        assert_eq!(parse_ref("blobcat@.", local), Some(("blobcat", local)));
+        assert_eq!(
+            parse_ref(":blobcat@misskey.io:", local),
+            Some(("blobcat", "misskey.io"))
+        );
+        assert_eq!(parse_ref("::", local), None);
+        assert_eq!(parse_ref("@misskey.io", local), None);
+    }
+
+    fn emoji(name: &str, aliases: &[&str]) -> ServerEmoji {
+        ServerEmoji {
+            name: name.to_string(),
+            url: format!("https://misskey.example/emoji/{name}.webp"),
+            category: None,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn names(emojis: Vec<ServerEmoji>) -> Vec<String> {
+        emojis.into_iter().map(|e| e.name).collect()
+    }
+
+    #[test]
+    fn search_ranks_exact_prefix_contains_then_fuzzy() {
+        let host = HostEmojis::new(vec![
+            emoji("blobcat_happy", &[]),
+            emoji("nekomimi", &["cat_ears"]),
+            emoji("blobcat", &[]),
+            emoji("big_cat", &[]),
+            emoji("catto", &[]),
+            emoji("bocat", &[]),
+        ]);
+        assert_eq!(
+            names(rank(&host, ":Cat:", 10)),
+            [
+                "catto",
+                "nekomimi",
+                "bocat",
+                "blobcat",
+                "big_cat",
+                "blobcat_happy"
+            ]
+        );
+        assert_eq!(
+            names(rank(&host, "blobcat", 10)),
+            ["blobcat", "blobcat_happy"]
+        );
+        assert_eq!(
+            names(rank(&host, "bct", 10)),
+            ["bocat", "blobcat", "big_cat", "blobcat_happy"]
+        );
+    }
+
+    #[test]
+    fn search_respects_limit_and_empty_query() {
+        let host = HostEmojis::new((0..50).map(|i| emoji(&format!("cat{i:02}"), &[])).collect());
+        assert_eq!(names(rank(&host, "cat", 3)), ["cat00", "cat01", "cat02"]);
+        assert!(rank(&host, "  ", 10).is_empty());
+        assert!(rank(&host, "dog", 10).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_renamed_and_updated() {
+        let old = vec![
+            emoji("keep", &[]),
+            emoji("gone", &[]),
+            emoji("old_name", &[]),
+            emoji("tagged", &[]),
+        ];
+        let mut renamed = emoji("new_name", &[]);
+        renamed.url = old[2].url.clone();
+        let new = vec![
+            emoji("keep", &[]),
+            renamed,
+            emoji("tagged", &["tag"]),
+            emoji("fresh", &[]),
+        ];
+        let changes = diff("misskey.example", &old, &new);
+        assert_eq!(changes.removed, ["gone"]);
+        assert_eq!(changes.renamed.len(), 1);
+        assert_eq!(changes.renamed[0].from, "old_name");
+        assert_eq!(changes.renamed[0].emoji.name, "new_name");
+        assert_eq!(names(changes.added), ["fresh"]);
+        assert_eq!(names(changes.updated), ["tagged"]);
+    }
+
+    #[test]
+    fn diff_of_same_list_is_empty() {
+        let emojis = vec![emoji("blobcat", &["cat"]), emoji("nekomimi", &[])];
+        assert!(diff("misskey.example", &emojis, &emojis).is_empty());
+    }
+
+    #[test]
+    fn fuzzy_gaps_count_skipped_characters() {
+        assert_eq!(subsequence_gaps("blobcat", "bct"), Some(4));
+        assert_eq!(subsequence_gaps("bocat", "bct"), Some(2));
+        assert_eq!(subsequence_gaps("cat", "dog"), None);
+    }
+
+    #[test]
+    fn refresh_diffs_from_the_acknowledged_version() {
+        let cache = EmojiCache::default();
+        let initial = cache.replace("a.example", vec![emoji("one", &[])]);
+        assert!(initial.is_none());
+        cache.ack("a.example", 0);
+
+        let first = cache
+            .replace("a.example", vec![emoji("one", &[]), emoji("two", &[])])
+            .unwrap();
+        assert_eq!(names(first.added), ["two"]);
+        // 当てたと返ってこなければ、次の差分にも前回の分が載る
+        let second = cache
+            .replace(
+                "a.example",
+                vec![emoji("one", &[]), emoji("two", &[]), emoji("three", &[])],
+            )
+            .unwrap();
+        assert_eq!(names(second.added), ["two", "three"]);
+
+        // 古い版の ack は無視する
+        cache.ack("a.example", first.version);
+        let list = vec![emoji("one", &[]), emoji("two", &[]), emoji("three", &[])];
+        assert!(cache.replace("a.example", list.clone()).is_some());
+        cache.ack("a.example", second.version);
+        assert!(cache.replace("a.example", list).is_none());
+    }
+
+    #[test]
+    fn evicts_unused_hosts_but_keeps_account_hosts() {
+        let cache = EmojiCache::default();
+        cache.replace("other.example", Vec::new());
+        cache.replace("account.example", Vec::new());
+        assert_eq!(cache.recent_hosts(RECENT_USE).len(), 2);
+        assert!(cache.recent_hosts(Duration::ZERO).is_empty());
+
+        let keep = HashSet::from(["account.example".to_string()]);
+        cache.evict_unused(&keep, RECENT_USE);
+        assert_eq!(cache.recent_hosts(RECENT_USE).len(), 2);
+        cache.evict_unused(&keep, Duration::ZERO);
+        assert_eq!(cache.recent_hosts(RECENT_USE), ["account.example"]);
+    }
+}
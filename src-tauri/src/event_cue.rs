@@ -0,0 +1,206 @@
+//! 通知ごとの音とプライオリティ (cue)。
+//!
+//! ストリーミングで届いた通知 (バックグラウンド同期で取り直したものを含む) を
+//! 種類で分類し、鳴らす音のタグと優先度を付けた [`StreamCue`] を出す。種類ごとの
+//! 既定は [`classify`] の表で、自動化ルール (`automation.rs`) の `cue` アクションに
+//! 合う通知 (特定ユーザーからのメンション等) はその音 / 優先度で上書きする。WebView は
+//! typed イベント `stream-cue` で、外部ツールは HTTP API の SSE (`/api/cues`)
+//! で受け取るので、どちらも通知を自分で分類し直さずに音を鳴らし分けられる。
+//!
+//! 同じ通知が複数の購読から届いても cue は 1 回だけ出す。メンションはメイン
+//! チャネルの `mention` とメンション通知の両方で届くため、cue は通知からだけ作る。
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use notecli::models::NormalizedNotification;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_specta::Event;
+use tokio::sync::broadcast;
+
+/// SSE 購読者向けのバッファ。遅れた購読者は古い cue を取りこぼす。
+const CHANNEL_CAPACITY: usize = 64;
+/// 出した cue の通知 ID を覚えておく件数。超えたら忘れる (streaming.rs と同じ)。
+const DEDUP_MAX_IDS: usize = 500;
+
+/// 鳴らす音の種類。どのファイルを鳴らすかは受け手が決める。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum CueSound {
+    Mention,
+    Reply,
+    Quote,
+    Reaction,
+    Renote,
+    Follow,
+    /// フォローリクエストの受信 / 承認
+    FollowRequest,
+    /// 投票の終了
+    Poll,
+    Other,
+}
+
+/// 通知の優先度。`high` は自分宛ての会話 (メンション / 返信 / 引用)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum CuePriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// 通知 1 件に付ける音と優先度 (イベント名 "stream-cue")。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamCue {
+    pub account_id: String,
+    pub notification_id: String,
+    /// Misskey の通知 type そのまま (`mention` / `reaction` / `follow` など)
+    pub notification_type: String,
+    pub note_id: Option<String>,
+    pub user_id: Option<String>,
+    pub sound: CueSound,
+    pub priority: CuePriority,
+    /// OS の集中モード / DND で OS 通知を抑制している間は true。鳴らすかどうかは受け手が決める
+    pub quiet: bool,
+}
+
+/// 通知 type から既定の音と優先度を決める。
+pub fn classify(notification_type: &str) -> (CueSound, CuePriority) {
+    match notification_type {
+        "mention" => (CueSound::Mention, CuePriority::High),
+        "reply" => (CueSound::Reply, CuePriority::High),
+        "quote" => (CueSound::Quote, CuePriority::High),
+        "reaction" | "reactionGrouped" => (CueSound::Reaction, CuePriority::Low),
+        "renote" | "renoteGrouped" => (CueSound::Renote, CuePriority::Low),
+        "follow" => (CueSound::Follow, CuePriority::Normal),
+        "receiveFollowRequest" | "followRequestAccepted" => {
+            (CueSound::FollowRequest, CuePriority::Normal)
+        }
+        "pollEnded" => (CueSound::Poll, CuePriority::Normal),
+        _ => (CueSound::Other, CuePriority::Normal),
+    }
+}
+
+fn cue_for(notification: &NormalizedNotification, quiet: bool) -> StreamCue {
+    let (sound, priority) = classify(&notification.notification_type);
+    StreamCue {
+        account_id: notification.account_id.clone(),
+        notification_id: notification.id.clone(),
+        notification_type: notification.notification_type.clone(),
+        note_id: notification.note.as_ref().map(|n| n.id.clone()),
+        user_id: notification.user.as_ref().map(|u| u.id.clone()),
+        sound,
+        priority,
+        quiet,
+    }
+}
+
+pub struct CueBus {
+    tx: broadcast::Sender<StreamCue>,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl Default for CueBus {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl CueBus {
+    /// SSE 用に cue を購読する。
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamCue> {
+        self.tx.subscribe()
+    }
+
+    /// 初めて見る通知なら true。
+    fn first_seen(&self, notification: &NormalizedNotification) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        if seen.len() >= DEDUP_MAX_IDS {
+            seen.clear();
+        }
+        seen.insert(format!("{}:{}", notification.account_id, notification.id))
+    }
+}
+
+/// 届いた通知の cue を WebView と SSE 購読者へ出す。
+pub fn on_notification<R: Runtime>(app: &AppHandle<R>, notification: &NormalizedNotification) {
+    let Some(bus) = app.try_state::<CueBus>() else {
+        return;
+    };
+    if !bus.first_seen(notification) {
+        return;
+    }
+    let mut cue = cue_for(notification, crate::dnd::should_suppress(app));
+    if let Some(engine) = app.try_state::<crate::automation::AutomationEngine>() {
+        let (sound, priority) = engine.cue_override(notification);
+        cue.sound = sound.unwrap_or(cue.sound);
+        cue.priority = priority.unwrap_or(cue.priority);
+    }
+    // SSE 購読者がいなければ Err になるだけ
+    let _ = bus.tx.send(cue.clone());
+    if let Err(e) = cue.emit(app) {
+        tracing::warn!("[cue] emit failed: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn notification(id: &str, notif_type: &str) -> NormalizedNotification {
+        serde_json::from_value(json!({
+            "id": id,
+            "_accountId": "acct-1",
+            "_serverHost": "misskey.example",
+            "createdAt": "2026-01-01T00:00:00.000Z",
+            "type": notif_type,
+            "user": { "id": "u1", "username": "alice" },
+        }))
+        .expect("test notification fixture should deserialize")
+    }
+
+    #[test]
+    fn classifies_conversation_above_reactions() {
+        assert_eq!(classify("reply"), (CueSound::Reply, CuePriority::High));
+        assert_eq!(classify("reaction"), (CueSound::Reaction, CuePriority::Low));
+        assert_eq!(classify("follow"), (CueSound::Follow, CuePriority::Normal));
+        assert_eq!(
+            classify("receiveFollowRequest"),
+            (CueSound::FollowRequest, CuePriority::Normal)
+        );
+        assert_eq!(
+            classify("someFutureType"),
+            (CueSound::Other, CuePriority::Normal)
+        );
+    }
+
+    #[test]
+    fn cue_carries_notification_context() {
+        let cue = cue_for(&notification("n1", "mention"), true);
+        assert_eq!(cue.account_id, "acct-1");
+        assert_eq!(cue.notification_id, "n1");
+        assert_eq!(cue.user_id.as_deref(), Some("u1"));
+        assert_eq!(cue.sound, CueSound::Mention);
+        assert_eq!(cue.priority, CuePriority::High);
+        assert!(cue.quiet);
+        assert_eq!(
+            serde_json::to_value(&cue).unwrap()["sound"],
+            json!("mention")
+        );
+    }
+
+    #[test]
+    fn each_notification_is_cued_once() {
+        let bus = CueBus::default();
+        assert!(bus.first_seen(&notification("n1", "follow")));
+        assert!(!bus.first_seen(&notification("n1", "follow")));
+        assert!(bus.first_seen(&notification("n2", "follow")));
+    }
+}
@@ -0,0 +1,245 @@
+//! 上流サーバーへのリクエストのホスト単位の同時実行数と優先度。
+//!
+//! notecli の `MisskeyClient` はホストを区別せずにリクエストを投げるので、
+//! 遅いサーバーでは定期同期やページ先読みが接続を埋め、投稿やリアクションが
+//! その後ろで待たされる。ここではホストごとに同時実行数を `MAX_PER_HOST` に
+//! 抑え、バックグラウンドの処理は `INTERACTIVE_RESERVED` 枠を残した数までしか
+//! 走らせない。空いた枠は待っているユーザー操作から先に渡す。
+//!
+//! 通すのは優先度を付けたい呼び出しだけで、カラムの通常の読み込みは
+//! ここを通らない (upstream_rate.rs の再試行と同じく呼び出し側で包む)。
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+
+use tokio::sync::oneshot;
+
+/// ホストごとの同時実行数の上限。
+const MAX_PER_HOST: usize = 6;
+/// バックグラウンドの処理が使えない、ユーザー操作のための枠の数。
+const INTERACTIVE_RESERVED: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// 投稿 / リアクション等、ユーザーが結果を待っている操作
+    Interactive,
+    /// 定期同期 / 先読み等、遅れても困らない処理
+    Background,
+}
+
+#[derive(Default)]
+struct HostSlots {
+    running: usize,
+    interactive: VecDeque<oneshot::Sender<()>>,
+    background: VecDeque<oneshot::Sender<()>>,
+}
+
+pub struct HostQueue {
+    hosts: Mutex<HashMap<String, HostSlots>>,
+    max_per_host: usize,
+    reserved: usize,
+}
+
+impl Default for HostQueue {
+    fn default() -> Self {
+        Self::with_limits(MAX_PER_HOST, INTERACTIVE_RESERVED)
+    }
+}
+
+/// 実行中の枠。drop で次の待ち手に渡す。
+pub struct Slot<'a> {
+    queue: &'a HostQueue,
+    host: String,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.queue.release(&self.host);
+    }
+}
+
+/// 枠を待っている間の後始末。待ちが取り消されたとき、既に枠を渡されて
+/// いたら返す。
+struct Waiting<'a> {
+    queue: &'a HostQueue,
+    host: String,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            rx.close();
+            if rx.try_recv().is_ok() {
+                self.queue.release(&self.host);
+            }
+        }
+    }
+}
+
+impl HostQueue {
+    fn with_limits(max_per_host: usize, reserved: usize) -> Self {
+        Self {
+            hosts: Mutex::new(HashMap::new()),
+            max_per_host,
+            reserved: reserved.min(max_per_host.saturating_sub(1)),
+        }
+    }
+
+    fn limit(&self, priority: Priority) -> usize {
+        match priority {
+            Priority::Interactive => self.max_per_host,
+            Priority::Background => self.max_per_host - self.reserved,
+        }
+    }
+
+    /// `host` の枠を 1 つ取る。空きが無ければ優先度順に待つ。
+    pub async fn acquire(&self, host: &str, priority: Priority) -> Slot<'_> {
+        let rx = {
+            let mut hosts = self.hosts.lock().unwrap();
+            let slots = hosts.entry(host.to_string()).or_default();
+            if slots.running < self.limit(priority) {
+                slots.running += 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                match priority {
+                    Priority::Interactive => slots.interactive.push_back(tx),
+                    Priority::Background => slots.background.push_back(tx),
+                }
+                Some(rx)
+            }
+        };
+        if let Some(rx) = rx {
+            let mut waiting = Waiting {
+                queue: self,
+                host: host.to_string(),
+                rx: Some(rx),
+            };
+            // release が枠を数えたまま渡すので、受け取れた時点で実行してよい
+            if let Some(rx) = waiting.rx.as_mut() {
+                let _ = rx.await;
+            }
+            waiting.rx = None;
+        }
+        Slot {
+            queue: self,
+            host: host.to_string(),
+        }
+    }
+
+    /// `host` の枠を取って `fut` を実行する。
+    pub async fn run<T>(&self, host: &str, priority: Priority, fut: impl Future<Output = T>) -> T {
+        let _slot = self.acquire(host, priority).await;
+        fut.await
+    }
+
+    /// 枠を 1 つ返し、入れるだけ待ち手を入れる (ユーザー操作が先)。
+    fn release(&self, host: &str) {
+        let mut hosts = self.hosts.lock().unwrap();
+        let Some(slots) = hosts.get_mut(host) else {
+            return;
+        };
+        slots.running = slots.running.saturating_sub(1);
+        loop {
+            let next = if slots.running < self.limit(Priority::Interactive)
+                && !slots.interactive.is_empty()
+            {
+                slots.interactive.pop_front()
+            } else if slots.running < self.limit(Priority::Background) {
+                slots.background.pop_front()
+            } else {
+                None
+            };
+            let Some(tx) = next else {
+                break;
+            };
+            // 取り消された待ち手 (受け手が drop 済み) は飛ばす
+            if tx.send(()).is_ok() {
+                slots.running += 1;
+            }
+        }
+        if slots.running == 0 && slots.interactive.is_empty() && slots.background.is_empty() {
+            hosts.remove(host);
+        }
+    }
+
+    #[cfg(test)]
+    fn running(&self, host: &str) -> usize {
+        self.hosts
+            .lock()
+            .unwrap()
+            .get(host)
+            .map_or(0, |slots| slots.running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn background_leaves_room_for_interactive() {
+        let queue = HostQueue::with_limits(3, 1);
+        let _a = queue.acquire("a.example", Priority::Background).await;
+        let _b = queue.acquire("a.example", Priority::Background).await;
+        let third = tokio::time::timeout(
+            Duration::from_millis(20),
+            queue.acquire("a.example", Priority::Background),
+        )
+        .await;
+        assert!(third.is_err(), "background must not take the reserved slot");
+        let _c = queue.acquire("a.example", Priority::Interactive).await;
+        assert_eq!(queue.running("a.example"), 3);
+        // 他のホストには影響しない
+        let _d = queue.acquire("b.example", Priority::Background).await;
+    }
+
+    #[tokio::test]
+    async fn freed_slot_goes_to_interactive_first() {
+        let queue = Arc::new(HostQueue::with_limits(1, 0));
+        let held = queue.acquire("a.example", Priority::Background).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let spawn = |priority, label: &'static str| {
+            let queue = queue.clone();
+            let order = order.clone();
+            tokio::spawn(async move {
+                queue
+                    .run("a.example", priority, async {
+                        order.lock().unwrap().push(label);
+                    })
+                    .await;
+            })
+        };
+        let background = spawn(Priority::Background, "background");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let interactive = spawn(Priority::Interactive, "interactive");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(held);
+        background.await.unwrap();
+        interactive.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), ["interactive", "background"]);
+        assert_eq!(queue.running("a.example"), 0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_does_not_leak_a_slot() {
+        let queue = HostQueue::with_limits(1, 0);
+        let held = queue.acquire("a.example", Priority::Interactive).await;
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(10),
+            queue.acquire("a.example", Priority::Interactive),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        drop(held);
+        assert_eq!(queue.running("a.example"), 0);
+        let _again = queue.acquire("a.example", Priority::Interactive).await;
+        assert_eq!(queue.running("a.example"), 1);
+    }
+}
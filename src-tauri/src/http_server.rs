@@ -3,14 +3,20 @@ use axum::{
     extract::{Path, Query, Request, State},
     http::{header::AUTHORIZATION, StatusCode},
     middleware::{self, Next},
-    response::{IntoResponse, Response},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json, Router,
 };
+use futures_util::Stream;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tauri::AppHandle;
+use tokio_stream::wrappers::BroadcastStream;
 use tower_http::cors::CorsLayer;
 use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
 use utoipa_axum::{router::OpenApiRouter, routes};
@@ -80,6 +86,11 @@ struct ApiErrorResponse {
 
 const PORT: u16 = 19820;
 
+/// ローカル HTTP API のベース URL (外部プラグインに環境変数で渡す)。
+pub(crate) fn api_base_url() -> String {
+    format!("http://127.0.0.1:{PORT}")
+}
+
 // --- NoteDeck-specific state (for deck, commands, proxy routes) ---
 
 #[derive(Clone)]
@@ -107,6 +118,27 @@ impl ApiError {
     }
 }
 
+impl From<query_bridge::QueryError> for ApiError {
+    fn from(e: query_bridge::QueryError) -> Self {
+        Self {
+            status: query_error_status(&e),
+            code: e.code().to_ascii_uppercase(),
+            message: e.to_string(),
+        }
+    }
+}
+
+/// query_bridge の失敗を HTTP status に写像する。
+fn query_error_status(e: &query_bridge::QueryError) -> StatusCode {
+    use query_bridge::QueryError;
+    match e {
+        QueryError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        QueryError::Interrupted | QueryError::WindowClosed => StatusCode::SERVICE_UNAVAILABLE,
+        QueryError::Frontend { code, .. } if code == "unknown_query" => StatusCode::NOT_FOUND,
+        QueryError::Emit(_) | QueryError::Frontend { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let body = json!({ "error": self.code, "message": self.message });
@@ -172,6 +204,7 @@ pub struct ServeConfig {
     pub event_bus: Arc<EventBus>,
     pub api_token: String,
     pub api_token_store: Arc<crate::api_tokens::ApiTokenStore>,
+    pub plugin_host: Arc<crate::plugin_host::PluginHost>,
     pub token_path: String,
     pub image_cache: Arc<ImageCache>,
     pub perf: crate::perf_config::SharedPerfConfig,
@@ -181,6 +214,7 @@ pub struct ServeConfig {
 #[derive(Clone)]
 struct TokenBridgeState {
     store: Arc<crate::api_tokens::ApiTokenStore>,
+    plugin_host: Arc<crate::plugin_host::PluginHost>,
     api_token: String,
 }
 
@@ -190,7 +224,8 @@ struct TokenBridgeState {
 /// トークンに書き換えて下流に流す。これで notecli コアルート (accounts /
 /// timeline / events 等) と NoteDeck 固有ルートの両方の既存認証がそのまま
 /// 通る (notecli 側の変更不要)。無効・無関係な Bearer はそのまま下流の
-/// 認証で 401 になる。
+/// 認証で 401 になる。外部プラグインのトークンも同じく詰め替え、
+/// 承認スコープを marker に載せる。
 async fn persistent_token_middleware(
     State(state): State<TokenBridgeState>,
     mut req: Request,
@@ -202,7 +237,8 @@ async fn persistent_token_middleware(
         .and_then(|v| v.to_str().ok())
         .and_then(|v| v.strip_prefix("Bearer "));
     if let Some(token) = presented {
-        if state.store.verify(token) {
+        let plugin = state.plugin_host.verify(token);
+        if plugin.is_some() || state.store.verify(token) {
             let bridged = format!("Bearer {}", state.api_token);
             if let Ok(value) = axum::http::HeaderValue::from_str(&bridged) {
                 req.headers_mut().insert(AUTHORIZATION, value);
@@ -212,6 +248,9 @@ async fn persistent_token_middleware(
             // (notecli CLI 等) は local trust として免除
             req.extensions_mut()
                 .insert(crate::permissions_gate::ExternalTokenMarker);
+            if let Some(plugin) = plugin {
+                req.extensions_mut().insert(plugin);
+            }
         }
     }
     next.run(req).await
@@ -298,6 +337,7 @@ pub async fn serve(config: ServeConfig, ready_tx: tokio::sync::oneshot::Sender<(
         .layer(middleware::from_fn_with_state(
             TokenBridgeState {
                 store: config.api_token_store.clone(),
+                plugin_host: config.plugin_host.clone(),
                 api_token: config.api_token.clone(),
             },
             persistent_token_middleware,
@@ -384,11 +424,7 @@ async fn deck_auth_middleware(
 async fn get_deck_columns(State(state): State<DeckState>) -> Result<Json<Value>, ApiError> {
     let data = query_bridge::query_frontend(&state.app_handle, "deck/columns", json!({}))
         .await
-        .map_err(|e| ApiError {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            code: "QUERY_FAILED".to_string(),
-            message: e,
-        })?;
+        .map_err(ApiError::from)?;
     Ok(Json(data))
 }
 
@@ -402,11 +438,7 @@ async fn get_deck_columns(State(state): State<DeckState>) -> Result<Json<Value>,
 async fn get_deck_active(State(state): State<DeckState>) -> Result<Json<Value>, ApiError> {
     let data = query_bridge::query_frontend(&state.app_handle, "deck/active", json!({}))
         .await
-        .map_err(|e| ApiError {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            code: "QUERY_FAILED".to_string(),
-            message: e,
-        })?;
+        .map_err(ApiError::from)?;
     Ok(Json(data))
 }
 
@@ -420,22 +452,46 @@ async fn get_deck_active(State(state): State<DeckState>) -> Result<Json<Value>,
 async fn list_commands(State(state): State<DeckState>) -> Result<Json<Value>, ApiError> {
     let data = query_bridge::query_frontend(&state.app_handle, "commands/list", json!({}))
         .await
-        .map_err(|e| ApiError {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            code: "QUERY_FAILED".to_string(),
-            message: e,
-        })?;
+        .map_err(ApiError::from)?;
     Ok(Json(data))
 }
 
+#[utoipa::path(get, path = "/api/cues", tag = "events",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Server-sent event stream (`text/event-stream`) of notification cues. Each `cue` event carries a JSON `StreamCue` (`accountId` / `notificationId` / `notificationType` / `noteId` / `userId` / `sound` / `priority` / `quiet`) so clients can play distinct sounds without classifying notifications themselves."),
+        (status = 401, description = "Unauthorized", body = ApiErrorResponse),
+    )
+)]
+async fn sse_cues(
+    State(state): State<DeckState>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, ApiError> {
+    use tauri::Manager;
+    use tokio_stream::StreamExt;
+    let bus = state
+        .app_handle
+        .try_state::<crate::event_cue::CueBus>()
+        .ok_or_else(|| ApiError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            code: "CUES_UNAVAILABLE".to_string(),
+            message: "Notification cues are not available".to_string(),
+        })?;
+    // 遅れて取りこぼした分 (Lagged) は飛ばす
+    let stream = BroadcastStream::new(bus.subscribe()).filter_map(|cue| {
+        let cue = cue.ok()?;
+        SseEvent::default()
+            .event("cue")
+            .json_data(&cue)
+            .ok()
+            .map(Ok)
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
 // --- Capability API (#709: 外部アプリ向け操作面) ---
 // カラム追加/削除・コマンド実行の旧ルートは #711 で削除した。外部からの操作は
 // すべて POST /api/capabilities/{id}/execute (= 権限判定を通る dispatcher) を使う。
 
-/// Capability 実行はユーザー確認ダイアログ待ちを挟みうるので、
-/// query_bridge 既定の 5 秒ではなく長めのタイムアウトを使う。
-const CAPABILITY_EXECUTE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
-
 #[utoipa::path(get, path = "/api/capabilities", tag = "capabilities",
     security(("bearer_auth" = [])),
     responses(
@@ -444,14 +500,35 @@ const CAPABILITY_EXECUTE_TIMEOUT: std::time::Duration = std::time::Duration::fro
     )
 )]
 async fn list_capabilities(State(state): State<DeckState>) -> Result<Json<Value>, ApiError> {
-    let data = query_bridge::query_frontend(&state.app_handle, "capabilities/list", json!({}))
-        .await
-        .map_err(|e| ApiError {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            code: "QUERY_FAILED".to_string(),
-            message: e,
-        })?;
-    Ok(Json(data))
+    use crate::capability_registry;
+
+    // バックエンド定義分はフロント不在でも返せる。フロントのミラー登録分は
+    // Rust 側のメタデータで置き換える (定義元を 1 つにする)
+    let backend = capability_registry::list();
+    let frontend = match query_bridge::query_frontend(
+        &state.app_handle,
+        "capabilities/list",
+        json!({}),
+    )
+    .await
+    {
+        Ok(Value::Array(items)) => items,
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            tracing::debug!("[http] frontend capabilities unavailable: {e}");
+            Vec::new()
+        }
+    };
+    let mut merged: Vec<Value> = frontend
+        .into_iter()
+        .filter(|cap| {
+            !cap.get("id")
+                .and_then(Value::as_str)
+                .is_some_and(capability_registry::contains)
+        })
+        .collect();
+    merged.extend(backend.into_iter().filter_map(|info| serde_json::to_value(info).ok()));
+    Ok(Json(Value::Array(merged)))
 }
 
 #[utoipa::path(post, path = "/api/capabilities/{capability_id}/execute", tag = "capabilities",
@@ -472,24 +549,47 @@ async fn execute_capability(
     Path(capability_id): Path<String>,
     body: Option<Json<Value>>,
 ) -> (StatusCode, Json<Value>) {
+    use crate::capability_registry::{self, Caller};
+
     let params = body.map(|Json(v)| v).unwrap_or(Value::Null);
-    let data = match query_bridge::query_frontend_with_timeout(
+    // バックエンド定義の capability は WebView を経由せずに実行する。
+    // principal はフロントの dispatcher と同じく external 扱い
+    let data = if capability_registry::contains(&capability_id) {
+        let result =
+            capability_registry::execute(&state.app_handle, &capability_id, params, Caller::External)
+                .await;
+        capability_registry::dispatch_result(result)
+    } else {
+        match forward_capability(&state, &capability_id, params).await {
+            Ok(data) => data,
+            Err(response) => return response,
+        }
+    };
+    dispatch_response(data)
+}
+
+/// フロントの dispatcher に capability 実行を依頼する。
+async fn forward_capability(
+    state: &DeckState,
+    capability_id: &str,
+    params: Value,
+) -> Result<Value, (StatusCode, Json<Value>)> {
+    // タイムアウト (確認ダイアログ待ち込み) と再送しない方針は query_bridge 側の既定
+    query_bridge::query_frontend(
         &state.app_handle,
         "capabilities/execute",
         json!({ "capabilityId": capability_id, "params": params }),
-        CAPABILITY_EXECUTE_TIMEOUT,
     )
     .await
-    {
-        Ok(data) => data,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "ok": false, "code": "query_failed", "error": e })),
-            );
-        }
-    };
+    .map_err(|e| {
+        (
+            query_error_status(&e),
+            Json(json!({ "ok": false, "code": e.code(), "error": e.to_string() })),
+        )
+    })
+}
 
+fn dispatch_response(data: Value) -> (StatusCode, Json<Value>) {
     // DispatchResult (`{ok, result}` / `{ok, code, error}`) を HTTP status に写像する。
     // body はそのまま返す (外部クライアントは code で機械判別できる)。
     match data.get("ok").and_then(Value::as_bool) {
@@ -504,8 +604,8 @@ async fn execute_capability(
             };
             (status, Json(data))
         }
-        // frontend が DispatchResult 以外 (handleQuery の error envelope 等) を
-        // 返した場合は構造化エラーに正規化する。
+        // frontend が DispatchResult 以外を返した場合は構造化エラーに正規化する
+        // (ハンドラの throw は query_bridge の Frontend エラーとして上で返る)。
         None => {
             let error = data
                 .get("error")
@@ -570,7 +670,7 @@ async fn get_health(
             }
             Err(e) => {
                 map.insert("frontendReady".into(), Value::Bool(false));
-                map.insert("frontendError".into(), Value::String(e));
+                map.insert("frontendError".into(), Value::String(e.to_string()));
                 map.insert("streams".into(), Value::Null);
             }
         }
@@ -588,6 +688,7 @@ fn deck_openapi_router() -> OpenApiRouter<DeckState> {
         .routes(routes!(get_deck_columns))
         .routes(routes!(get_deck_active))
         .routes(routes!(list_commands))
+        .routes(routes!(sse_cues))
         .routes(routes!(list_capabilities))
         .routes(routes!(execute_capability))
         .routes(routes!(get_health))
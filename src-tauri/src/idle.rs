@@ -0,0 +1,311 @@
+//! システムのアイドル (離席) 検知。
+//!
+//! OS の「最後の入力からの経過時間」を定期的に問い合わせ、フロントが設定した
+//! 閾値を跨いだとき (離席中に判定を無効化したときも含む) だけ
+//! `nd:idle-changed` を emit する。離席中の扱い
+//! (ストリーミングをポーリングへ落とす等) はフロント側 (realtimeMode ストア)
+//! が決める。Rust はアイドル時間の計測と閾値判定のみ。
+//!
+//! プラットフォーム別の取得:
+//! - Windows: `GetLastInputInfo`
+//! - macOS: `ioreg -c IOHIDSystem` の HIDIdleTime (ns)
+//! - Linux: GNOME は Mutter IdleMonitor (busctl 経由)、それ以外は `xprintidle`
+//! - モバイル: 取得しない (= 離席判定しない)
+
+use std::sync::Mutex;
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+/// フロント (realtimeMode ストア) が listen するイベント名。
+#[cfg_attr(mobile, allow(dead_code))]
+pub const IDLE_CHANGED_EVENT: &str = "nd:idle-changed";
+
+/// OS への問い合わせ間隔。閾値は分単位なので数秒の遅れは問題にならない。
+#[cfg_attr(mobile, allow(dead_code))]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 閾値の上限/下限 (分)。
+const MIN_THRESHOLD_MINUTES: u32 = 1;
+const MAX_THRESHOLD_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleStatus {
+    /// 最後の入力からの経過秒数。取得できない環境では null。
+    pub idle_seconds: Option<u64>,
+    /// 離席判定の閾値 (分)。null は離席判定を無効化している。
+    pub threshold_minutes: Option<u32>,
+    /// 閾値を超えて離席中と判定しているか。
+    pub away: bool,
+}
+
+#[derive(Default)]
+pub struct IdleMonitor {
+    threshold_minutes: Mutex<Option<u32>>,
+    away: Mutex<bool>,
+}
+
+impl IdleMonitor {
+    fn threshold_minutes(&self) -> Option<u32> {
+        *self.threshold_minutes.lock().unwrap()
+    }
+
+    /// 閾値を設定する。無効化 (None) したら離席状態も解除し、離席中だった
+    /// ときは解除後の状態を返す。
+    fn set_threshold(&self, minutes: Option<u32>) -> Option<IdleStatus> {
+        *self.threshold_minutes.lock().unwrap() = minutes;
+        if minutes.is_some() {
+            return None;
+        }
+        let mut away = self.away.lock().unwrap();
+        if !*away {
+            return None;
+        }
+        *away = false;
+        Some(IdleStatus {
+            idle_seconds: None,
+            threshold_minutes: None,
+            away: false,
+        })
+    }
+
+    /// 計測値を反映し、離席状態が変わったときだけ新しい状態を返す。
+    fn observe(&self, idle_seconds: Option<u64>) -> Option<IdleStatus> {
+        let threshold_minutes = self.threshold_minutes();
+        let away = is_away(idle_seconds, threshold_minutes);
+        let mut current = self.away.lock().unwrap();
+        if *current == away {
+            return None;
+        }
+        *current = away;
+        Some(IdleStatus {
+            idle_seconds,
+            threshold_minutes,
+            away,
+        })
+    }
+
+    fn status(&self, idle_seconds: Option<u64>) -> IdleStatus {
+        IdleStatus {
+            idle_seconds,
+            threshold_minutes: self.threshold_minutes(),
+            away: *self.away.lock().unwrap(),
+        }
+    }
+}
+
+/// 閾値を超えているか。計測できない / 無効化中は離席扱いしない。
+fn is_away(idle_seconds: Option<u64>, threshold_minutes: Option<u32>) -> bool {
+    match (idle_seconds, threshold_minutes) {
+        (Some(idle), Some(minutes)) => idle >= u64::from(minutes) * 60,
+        _ => false,
+    }
+}
+
+/// 離席状態の変化をフロントに伝える。
+fn emit_changed(app: &tauri::AppHandle, status: &IdleStatus) {
+    use tauri::Emitter;
+
+    tracing::debug!(away = status.away, "[idle] state changed");
+    if let Err(e) = app.emit(IDLE_CHANGED_EVENT, status) {
+        tracing::warn!("[idle] emit failed: {e}");
+    }
+}
+
+/// 閾値の監視 task を起動する。setup から 1 度だけ呼ぶ。
+#[cfg(not(mobile))]
+pub fn spawn_watcher(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(monitor) = app.try_state::<IdleMonitor>() else {
+                continue;
+            };
+            // 無効化中は外部コマンドを起動しない
+            if monitor.threshold_minutes().is_none() {
+                continue;
+            }
+            let idle = tauri::async_runtime::spawn_blocking(query_idle_seconds)
+                .await
+                .ok()
+                .flatten();
+            if let Some(status) = monitor.observe(idle) {
+                emit_changed(&app, &status);
+            }
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+fn query_idle_seconds() -> Option<u64> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: cbSize を設定した LASTINPUTINFO を渡す読み取り専用 API
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // GetTickCount は約 49.7 日で wrap するため wrapping_sub で差を取る
+    let now = unsafe { GetTickCount() };
+    Some(u64::from(now.wrapping_sub(info.dwTime)) / 1000)
+}
+
+#[cfg(target_os = "macos")]
+fn query_idle_seconds() -> Option<u64> {
+    let out = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    parse_ioreg_idle(&String::from_utf8_lossy(&out.stdout))
+}
+
+#[cfg(target_os = "linux")]
+fn query_idle_seconds() -> Option<u64> {
+    use std::process::Command;
+
+    let run = |cmd: &str, args: &[&str]| -> Option<String> {
+        let out = Command::new(cmd).args(args).output().ok()?;
+        out.status
+            .success()
+            .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+    };
+    // GNOME (Wayland でも使える)
+    if let Some(ms) = run(
+        "busctl",
+        &[
+            "--user",
+            "call",
+            "org.gnome.Mutter.IdleMonitor",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "org.gnome.Mutter.IdleMonitor",
+            "GetIdletime",
+        ],
+    )
+    .and_then(|out| parse_busctl_u64(&out))
+    {
+        return Some(ms / 1000);
+    }
+    // X11 (xprintidle はミリ秒を出力する)
+    run("xprintidle", &[]).and_then(|out| out.trim().parse::<u64>().ok().map(|ms| ms / 1000))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn query_idle_seconds() -> Option<u64> {
+    None
+}
+
+/// `ioreg` 出力中の `"HIDIdleTime" = <ns>` を秒にする。
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg_idle(out: &str) -> Option<u64> {
+    out.lines().find_map(|line| {
+        let (_, value) = line.split_once("\"HIDIdleTime\" = ")?;
+        value
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|ns| ns / 1_000_000_000)
+    })
+}
+
+/// `busctl call` の u64 出力 ("t 12345")。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_busctl_u64(out: &str) -> Option<u64> {
+    out.trim().strip_prefix("t ")?.parse().ok()
+}
+
+/// 現在のアイドル時間と離席状態を返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn idle_get_status(monitor: State<'_, IdleMonitor>) -> Result<IdleStatus, NoteDeckError> {
+    let idle = tauri::async_runtime::spawn_blocking(query_idle_seconds)
+        .await
+        .ok()
+        .flatten();
+    Ok(monitor.status(idle))
+}
+
+/// 離席判定の閾値 (分) を設定する。null で無効化。フロントの設定値を
+/// 起動時・変更時に反映する。
+#[tauri::command]
+#[specta::specta]
+pub fn idle_configure(
+    app: tauri::AppHandle,
+    monitor: State<'_, IdleMonitor>,
+    threshold_minutes: Option<u32>,
+) -> Result<(), NoteDeckError> {
+    if let Some(minutes) = threshold_minutes {
+        if !(MIN_THRESHOLD_MINUTES..=MAX_THRESHOLD_MINUTES).contains(&minutes) {
+            return Err(NoteDeckError::InvalidInput(format!(
+                "idle threshold must be {MIN_THRESHOLD_MINUTES}..={MAX_THRESHOLD_MINUTES} minutes (got {minutes})"
+            )));
+        }
+    }
+    // 離席中に無効化されたら、フロントがポーリングのまま残らないよう解除を伝える
+    if let Some(status) = monitor.set_threshold(threshold_minutes) {
+        emit_changed(&app, &status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn away_only_past_threshold() {
+        assert!(!is_away(Some(599), Some(10)));
+        assert!(is_away(Some(600), Some(10)));
+        // 無効化中 / 計測不能は離席扱いしない
+        assert!(!is_away(Some(10_000), None));
+        assert!(!is_away(None, Some(1)));
+    }
+
+    #[test]
+    fn observe_reports_transitions_only() {
+        let monitor = IdleMonitor::default();
+        monitor.set_threshold(Some(1));
+
+        assert_eq!(monitor.observe(Some(10)), None);
+        let away = monitor.observe(Some(60)).unwrap();
+        assert!(away.away);
+        assert_eq!(away.threshold_minutes, Some(1));
+        // 離席中に計測が続いても再通知しない
+        assert_eq!(monitor.observe(Some(120)), None);
+        assert!(!monitor.observe(Some(0)).unwrap().away);
+    }
+
+    #[test]
+    fn disabling_clears_away() {
+        let monitor = IdleMonitor::default();
+        assert_eq!(monitor.set_threshold(Some(1)), None);
+        monitor.observe(Some(60));
+        let cleared = monitor.set_threshold(None).unwrap();
+        assert!(!cleared.away);
+        assert!(!monitor.status(None).away);
+        // 離席していなければ解除の通知は出さない
+        assert_eq!(monitor.set_threshold(None), None);
+    }
+
+    #[test]
+    fn parses_platform_outputs() {
+        let ioreg = r#"    | |   "HIDIdleTime" = 12500000000
+    | |   "HIDKeyboardModifierMappingPairs" = ()"#;
+        assert_eq!(parse_ioreg_idle(ioreg), Some(12));
+        assert_eq!(parse_ioreg_idle("nothing"), None);
+        assert_eq!(parse_busctl_u64("t 4200\n"), Some(4200));
+        assert_eq!(parse_busctl_u64("s x"), None);
+    }
+}
@@ -79,7 +79,7 @@ impl ImageCache {
     }
 
     pub fn with_client(app_dir: &Path, http_client: reqwest::Client, perf: SharedPerfConfig) -> Self {
-        let cache_dir = app_dir.join("image_cache");
+        let cache_dir = cache_dir(app_dir);
         std::fs::create_dir_all(&cache_dir).ok();
         let max_total = DEFAULT_MEMORY_CACHE_MAX_TOTAL;
         let max_item = DEFAULT_MEMORY_CACHE_MAX_ITEM;
@@ -512,6 +512,52 @@ impl ImageCache {
     }
 }
 
+/// ディスクキャッシュの置き場所。
+pub fn cache_dir(app_dir: &Path) -> PathBuf {
+    app_dir.join("image_cache")
+}
+
+/// [`prune_expired`] の結果。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PruneStats {
+    pub scanned: u64,
+    pub removed: u64,
+    pub freed_bytes: u64,
+}
+
+/// TTL を過ぎた `.dat` とその `.meta` をディスクから削除する (blocking)。
+/// 読み出し側 (check_cache) は期限切れを無視するだけで消さないので、
+/// 放っておくと溜まり続ける。`on_progress(done, total)` はファイルごとに呼ぶ。
+pub fn prune_expired(
+    cache_dir: &Path,
+    ttl: Duration,
+    mut on_progress: impl FnMut(u64, u64),
+) -> std::io::Result<PruneStats> {
+    let data_files: Vec<PathBuf> = std::fs::read_dir(cache_dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "dat"))
+        .collect();
+    let total = data_files.len() as u64;
+    let now = SystemTime::now();
+    let mut stats = PruneStats::default();
+    for data_path in data_files {
+        stats.scanned += 1;
+        if let Ok(meta) = data_path.metadata() {
+            let expired = meta
+                .modified()
+                .map(|m| now.duration_since(m).unwrap_or_default() > ttl)
+                .unwrap_or(false);
+            if expired && std::fs::remove_file(&data_path).is_ok() {
+                let _ = std::fs::remove_file(data_path.with_extension("meta"));
+                stats.removed += 1;
+                stats.freed_bytes += meta.len();
+            }
+        }
+        on_progress(stats.scanned, total);
+    }
+    Ok(stats)
+}
+
 pub fn hex_hash(input: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(input.as_bytes());
@@ -522,6 +568,34 @@ pub fn hex_hash(input: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn prune_removes_only_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["old", "new"] {
+            std::fs::write(dir.path().join(format!("{name}.dat")), b"data").unwrap();
+            std::fs::write(dir.path().join(format!("{name}.meta")), b"image/png").unwrap();
+        }
+        let old = std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("old.dat"))
+            .unwrap();
+        old.set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+
+        let mut calls = Vec::new();
+        let stats = prune_expired(dir.path(), Duration::from_secs(60), |done, total| {
+            calls.push((done, total))
+        })
+        .unwrap();
+        assert_eq!(stats.scanned, 2);
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.freed_bytes, 4);
+        assert_eq!(calls.last(), Some(&(2, 2)));
+        assert!(!dir.path().join("old.dat").exists());
+        assert!(!dir.path().join("old.meta").exists());
+        assert!(dir.path().join("new.dat").exists());
+    }
+
     #[test]
     fn hex_hash_deterministic() {
         let a = hex_hash("https://example.com/image.png");
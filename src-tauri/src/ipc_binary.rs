@@ -0,0 +1,85 @@
+//! 大きな応答を raw バイト列で返す IPC コマンド。
+//!
+//! 通常のコマンドは戻り値が Tauri の JSON 応答として WebView に渡り、ノートの
+//! 多いカラムでは巨大な文字列の受け渡しと JS 側の変換が二重に走る。ここの
+//! `*_bin` コマンドは同じ結果を `serde_json::to_vec` で 1 回だけバイト列にし、
+//! `tauri::ipc::Response` (JS 側は `ArrayBuffer`) で返す。フロントは
+//! `utils/binaryIpc.ts` で `TextDecoder` + `JSON.parse` する。
+//!
+//! `ipc::Response` は specta の型を持たないため、これらは tauri-specta の
+//! builder に載せず `lib.rs` で invoke handler を振り分けて登録する
+//! (bindings.ts には出ない)。JSON 版のコマンドはそのまま残す。
+
+use notecli::models::{TimelineOptions, TimelineType};
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::State;
+
+use crate::commands::{self, AppState, Result};
+use crate::media_gate::MediaGate;
+use crate::request_dedup::RequestDedup;
+
+/// このモジュールのコマンド名。invoke handler の振り分けに使う。
+pub const COMMANDS: &[&str] = &[
+    "api_get_timeline_bin",
+    "api_get_cached_timeline_bin",
+    "api_get_cached_timeline_before_bin",
+];
+
+fn encode<T: Serialize>(value: &T) -> Result<Response> {
+    Ok(Response::new(serde_json::to_vec(value)?))
+}
+
+/// `api_get_timeline` の raw 版。
+#[tauri::command]
+pub async fn api_get_timeline_bin(
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+    dedup: State<'_, RequestDedup>,
+    account_id: String,
+    timeline_type: TimelineType,
+    options: Option<TimelineOptions>,
+) -> Result<Response> {
+    let notes =
+        commands::api_get_timeline(app, app_state, dedup, account_id, timeline_type, options)
+            .await?;
+    encode(&notes)
+}
+
+/// `api_get_cached_timeline` の raw 版。
+#[tauri::command]
+pub async fn api_get_cached_timeline_bin(
+    app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    account_id: String,
+    timeline_type: String,
+    limit: Option<i64>,
+) -> Result<Response> {
+    let notes =
+        commands::api_get_cached_timeline(app_state, gate, account_id, timeline_type, limit)
+            .await?;
+    encode(&notes)
+}
+
+/// `api_get_cached_timeline_before` の raw 版。
+#[tauri::command]
+pub async fn api_get_cached_timeline_before_bin(
+    app_state: State<'_, AppState>,
+    gate: State<'_, MediaGate>,
+    account_id: String,
+    timeline_type: String,
+    before: String,
+    limit: Option<i64>,
+) -> Result<Response> {
+    let notes = commands::api_get_cached_timeline_before(
+        app_state,
+        gate,
+        account_id,
+        timeline_type,
+        before,
+        limit,
+    )
+    .await?;
+    encode(&notes)
+}
+
@@ -0,0 +1,800 @@
+//! バックグラウンドジョブ (ファイルアップロード / 動画の圧縮アップロード /
+//! DB エクスポート / フォローの一括インポート / 画像キャッシュの整理)。
+//!
+//! `job_enqueue` はジョブを積んで即座に返り、進捗と完了は `nd:job-updated`
+//! (Job 全体) で通知する。ジョブは `app-state.db` (app_db.rs) に保存し、
+//! 終了やクラッシュで中断したものは次回起動時に続きから再開する。ただしやり直すと重複する
+//! もの (アップロード) は再開せず失敗扱いにする。
+//!
+//! 同時実行は MAX_CONCURRENT 件まで。キャンセルは実行中のタスクを abort する。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+
+use crate::app_db::{self, SharedConn};
+use crate::settings::SettingsStore;
+
+/// ジョブの状態が変わるたびに emit する (payload は [`Job`])。
+pub const JOB_UPDATED_EVENT: &str = "nd:job-updated";
+
+/// `app-state.db` の `runtime_state` 表でのキー。
+const STATE_KEY: &str = "jobs";
+
+const MAX_CONCURRENT: usize = 2;
+/// 保持する終了済みジョブの数 (古いものから捨てる)。
+const MAX_FINISHED: usize = 50;
+/// 進捗だけの更新はこの間隔でしか保存しない。
+const PROGRESS_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+/// フォローのインポートで 1 件ごとに空ける間隔 (レート制限対策)。
+const FOLLOW_IMPORT_INTERVAL: Duration = Duration::from_secs(1);
+/// 画像キャッシュ整理の進捗はこの件数ごとに通知する。
+const PRUNE_PROGRESS_STEP: u64 = 100;
+/// `ExportDb` の書き出し先 (アプリデータ下)。
+const EXPORT_DIR: &str = "exports";
+
+/// ジョブの内容。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum JobSpec {
+    /// ローカルファイルをドライブへアップロードする。
+    #[serde(rename_all = "camelCase")]
+    Upload {
+        account_id: String,
+        path: String,
+        is_sensitive: bool,
+        folder_id: Option<String>,
+        name: Option<String>,
+    },
+    /// 動画を ffmpeg で圧縮してからドライブへアップロードする
+    /// (`video_compress.rs`)。進捗は変換済みの再生時間 (ms)。
+    #[serde(rename_all = "camelCase")]
+    CompressUpload {
+        account_id: String,
+        path: String,
+        is_sensitive: bool,
+        folder_id: Option<String>,
+        name: Option<String>,
+    },
+    /// notecli.db をアプリデータ下の `exports/` へコピーする。書き出し先は
+    /// 選ばせない (ユーザーが選ぶ場合は save dialog 付きの `export_db`)。
+    ExportDb,
+    /// DB と設定を暗号化して `dir` へ書き出し、新しいものから `keep` 個残す
+    /// (`backup.rs`)。パスフレーズはキーチェーンから読む。
+    #[serde(rename_all = "camelCase")]
+    Backup { dir: String, keep: u32 },
+    /// Misskey のフォローエクスポート (1 行 1 アカウント) を読んでフォローする。
+    #[serde(rename_all = "camelCase")]
+    ImportFollows { account_id: String, path: String },
+    /// 期限切れの画像キャッシュをディスクから消す。
+    PruneImageCache,
+}
+
+impl JobSpec {
+    /// 中断したジョブを次回起動時に再開してよいか。
+    fn resumable(&self) -> bool {
+        !matches!(self, Self::Upload { .. } | Self::CompressUpload { .. })
+    }
+
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        let fields: Vec<&String> = match self {
+            Self::Upload {
+                account_id, path, ..
+            }
+            | Self::CompressUpload {
+                account_id, path, ..
+            } => vec![account_id, path],
+            Self::Backup { dir, .. } => vec![dir],
+            Self::ImportFollows { account_id, path } => vec![account_id, path],
+            Self::ExportDb | Self::PruneImageCache => Vec::new(),
+        };
+        if fields.iter().any(|f| f.trim().is_empty()) {
+            return Err(NoteDeckError::InvalidInput(
+                "job fields must not be empty".to_string(),
+            ));
+        }
+        if matches!(self, Self::Backup { keep: 0, .. }) {
+            return Err(NoteDeckError::InvalidInput(
+                "keep must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub done: u64,
+    /// 総量が分からないうちは None。
+    pub total: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: String,
+    pub spec: JobSpec,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    /// 成功時の結果 (アップロードしたファイル、件数など)。
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+pub struct JobQueue {
+    conn: SharedConn,
+    jobs: Mutex<Vec<Job>>,
+    /// 実行中タスク (キャンセル時に abort する)。
+    running: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+    last_saved: Mutex<Instant>,
+    wake: Notify,
+}
+
+/// 前回の実行中に終わったジョブを起動時の状態に戻す。
+fn recover(jobs: &mut [Job]) {
+    for job in jobs.iter_mut().filter(|j| j.status == JobStatus::Running) {
+        if job.spec.resumable() {
+            job.status = JobStatus::Queued;
+        } else {
+            job.status = JobStatus::Failed;
+            job.error = Some("interrupted by restart".to_string());
+        }
+    }
+}
+
+/// 終了済みジョブを MAX_FINISHED 件まで減らす (古いものから)。
+fn trim_finished(jobs: &mut Vec<Job>) {
+    let finished = jobs.iter().filter(|j| j.status.is_finished()).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED);
+    jobs.retain(|j| {
+        if excess > 0 && j.status.is_finished() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+impl JobQueue {
+    /// `app-state.db` からジョブを読み込む。まだ無ければ以前
+    /// `settings.json` に保存していた分を引き継いで保存し直す。
+    pub fn load(conn: SharedConn, settings: &SettingsStore) -> Self {
+        let stored = app_db::load_state(&conn, STATE_KEY).unwrap_or_else(|e| {
+            tracing::warn!("[jobs] failed to load jobs: {e}");
+            None
+        });
+        let migrate = stored.is_none();
+        let mut jobs: Vec<Job> = stored
+            .or_else(|| settings.take_runtime_state(STATE_KEY))
+            .unwrap_or_default();
+        recover(&mut jobs);
+        let queue = Self {
+            conn,
+            jobs: Mutex::new(jobs),
+            running: Mutex::new(HashMap::new()),
+            last_saved: Mutex::new(Instant::now()),
+            wake: Notify::new(),
+        };
+        if migrate && !queue.list().is_empty() {
+            queue.save();
+        }
+        queue
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs.lock().unwrap().clone()
+    }
+
+    fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|j| j.id == id)
+            .cloned()
+    }
+
+    fn save(&self) {
+        match app_db::save_state(&self.conn, STATE_KEY, &self.list()) {
+            Ok(()) => *self.last_saved.lock().unwrap() = Instant::now(),
+            Err(e) => tracing::warn!("[jobs] failed to save jobs: {e}"),
+        }
+    }
+
+    /// id のジョブを書き換え、書き換え後の値を返す。
+    fn update(&self, id: &str, f: impl FnOnce(&mut Job)) -> Option<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.iter_mut().find(|j| j.id == id)?;
+        f(job);
+        job.updated_at_ms = now_ms();
+        Some(job.clone())
+    }
+
+    fn enqueue(&self, spec: JobSpec) -> Result<Job, NoteDeckError> {
+        spec.validate()?;
+        let now = now_ms();
+        let job = Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            spec,
+            status: JobStatus::Queued,
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            created_at_ms: now,
+            updated_at_ms: now,
+        };
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(job.clone());
+            trim_finished(&mut jobs);
+        }
+        self.save();
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    /// 空きがあれば次の待機ジョブを Running にして返す。
+    fn start_next(&self) -> Option<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let running = jobs
+            .iter()
+            .filter(|j| j.status == JobStatus::Running)
+            .count();
+        if running >= MAX_CONCURRENT {
+            return None;
+        }
+        let job = jobs.iter_mut().find(|j| j.status == JobStatus::Queued)?;
+        job.status = JobStatus::Running;
+        job.updated_at_ms = now_ms();
+        Some(job.clone())
+    }
+
+    fn set_progress(&self, id: &str, done: u64, total: Option<u64>) -> Option<Job> {
+        let job = self.update(id, |j| j.progress = JobProgress { done, total })?;
+        if self.last_saved.lock().unwrap().elapsed() >= PROGRESS_SAVE_INTERVAL {
+            self.save();
+        }
+        Some(job)
+    }
+
+    /// 実行を終えたジョブを記録する。先にキャンセルされていれば何もしない。
+    fn finish(&self, id: &str, outcome: Result<Value, String>) -> Option<Job> {
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs
+                .iter_mut()
+                .find(|j| j.id == id && j.status == JobStatus::Running)?;
+            match outcome {
+                Ok(result) => {
+                    job.status = JobStatus::Succeeded;
+                    job.result = Some(result);
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e);
+                }
+            }
+            job.updated_at_ms = now_ms();
+            job.clone()
+        };
+        self.running.lock().unwrap().remove(id);
+        self.save();
+        self.wake.notify_one();
+        Some(job)
+    }
+
+    fn cancel(&self, id: &str) -> Result<Job, NoteDeckError> {
+        let mut running = self.running.lock().unwrap();
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let job = jobs
+                .iter_mut()
+                .find(|j| j.id == id)
+                .ok_or_else(|| NoteDeckError::InvalidInput(format!("job not found: {id}")))?;
+            if job.status.is_finished() {
+                return Err(NoteDeckError::InvalidInput(format!(
+                    "job already finished: {id}"
+                )));
+            }
+            job.status = JobStatus::Cancelled;
+            job.updated_at_ms = now_ms();
+            job.clone()
+        };
+        if let Some(handle) = running.remove(id) {
+            handle.abort();
+        }
+        drop(running);
+        self.save();
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    fn clear_finished(&self) -> Vec<Job> {
+        let jobs = {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.retain(|j| !j.status.is_finished());
+            jobs.clone()
+        };
+        self.save();
+        jobs
+    }
+}
+
+fn emit(app: &AppHandle, job: &Job) {
+    let _ = app.emit(JOB_UPDATED_EVENT, job);
+}
+
+/// 実行中のジョブから進捗を報告する窓口。
+#[derive(Clone)]
+struct JobContext {
+    app: AppHandle,
+    id: String,
+}
+
+impl JobContext {
+    fn progress(&self, done: u64, total: Option<u64>) {
+        if let Some(job) = self
+            .app
+            .state::<JobQueue>()
+            .set_progress(&self.id, done, total)
+        {
+            emit(&self.app, &job);
+        }
+    }
+}
+
+/// 待機ジョブを拾って実行する常駐ワーカー。
+pub fn spawn_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let queue = app.state::<JobQueue>();
+        loop {
+            while let Some(job) = queue.start_next() {
+                queue.save();
+                emit(&app, &job);
+                let ctx = JobContext {
+                    app: app.clone(),
+                    id: job.id.clone(),
+                };
+                let handle = tauri::async_runtime::spawn(async move {
+                    let outcome = run(&ctx, &job.spec).await;
+                    if let Err(e) = &outcome {
+                        tracing::warn!("[jobs] {} failed: {e}", ctx.id);
+                    }
+                    if let Some(job) = ctx.app.state::<JobQueue>().finish(&ctx.id, outcome) {
+                        emit(&ctx.app, &job);
+                    }
+                });
+                // 登録前に終わっていた (finish 済み) なら handle は不要
+                let mut running = queue.running.lock().unwrap();
+                if queue
+                    .get(&job.id)
+                    .is_some_and(|j| j.status == JobStatus::Running)
+                {
+                    running.insert(job.id, handle);
+                }
+            }
+            queue.wake.notified().await;
+        }
+    });
+}
+
+async fn run(ctx: &JobContext, spec: &JobSpec) -> Result<Value, String> {
+    match spec {
+        JobSpec::Upload {
+            account_id,
+            path,
+            is_sensitive,
+            folder_id,
+            name,
+        } => {
+            ctx.progress(0, Some(1));
+            let file = crate::commands::upload_path(
+                &ctx.app,
+                crate::host_queue::Priority::Background,
+                account_id,
+                Path::new(path),
+                *is_sensitive,
+                folder_id.clone(),
+                name.clone(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            crate::upload_history::remember(&ctx.app, account_id, &file);
+            ctx.progress(1, Some(1));
+            serde_json::to_value(file).map_err(|e| e.to_string())
+        }
+        JobSpec::CompressUpload {
+            account_id,
+            path,
+            is_sensitive,
+            folder_id,
+            name,
+        } => {
+            let input = Path::new(path);
+            let output = crate::video_compress::TempOutput::new(&ctx.id);
+            crate::video_compress::compress(input, output.path(), |done, total| {
+                ctx.progress(done, total)
+            })
+            .await?;
+            let file = crate::commands::upload_path(
+                &ctx.app,
+                crate::host_queue::Priority::Background,
+                account_id,
+                output.path(),
+                *is_sensitive,
+                folder_id.clone(),
+                Some(crate::video_compress::output_name(input, name.as_deref())),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            crate::upload_history::remember(&ctx.app, account_id, &file);
+            serde_json::to_value(file).map_err(|e| e.to_string())
+        }
+        JobSpec::ExportDb => {
+            let app_dir = crate::app_dir::resolve_app_dir(&ctx.app).map_err(|e| e.to_string())?;
+            let src = app_dir.join("notecli.db");
+            let dest = export_path(&app_dir, now_ms());
+            let path = dest.to_string_lossy().into_owned();
+            let bytes = tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(dest.parent().unwrap_or(&dest))?;
+                std::fs::copy(src, dest)
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to export database: {e}"))?;
+            Ok(json!({ "path": path, "bytes": bytes }))
+        }
+        JobSpec::Backup { dir, keep } => {
+            let app_dir = crate::app_dir::resolve_app_dir(&ctx.app).map_err(|e| e.to_string())?;
+            let dir = PathBuf::from(dir);
+            let keep = *keep as usize;
+            let outcome = tokio::task::spawn_blocking(move || {
+                crate::backup::create_with_stored_passphrase(&app_dir, &dir, keep, now_ms())
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+            Ok(json!({
+                "path": outcome.path.to_string_lossy(),
+                "bytes": outcome.bytes,
+                "removed": outcome.removed,
+            }))
+        }
+        JobSpec::ImportFollows { account_id, path } => {
+            import_follows(ctx, account_id, Path::new(path)).await
+        }
+        JobSpec::PruneImageCache => {
+            let app_dir = crate::app_dir::resolve_app_dir(&ctx.app).map_err(|e| e.to_string())?;
+            let ttl_days = ctx
+                .app
+                .state::<crate::perf_config::SharedPerfConfig>()
+                .read()
+                .await
+                .image_cache_ttl_days;
+            let ttl = Duration::from_secs(ttl_days * 24 * 60 * 60);
+            let cache_dir = crate::image_cache::cache_dir(&app_dir);
+            let progress_ctx = ctx.clone();
+            let stats = tokio::task::spawn_blocking(move || {
+                crate::image_cache::prune_expired(&cache_dir, ttl, |done, total| {
+                    if done % PRUNE_PROGRESS_STEP == 0 || done == total {
+                        progress_ctx.progress(done, Some(total));
+                    }
+                })
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to prune image cache: {e}"))?;
+            Ok(json!({
+                "scanned": stats.scanned,
+                "removed": stats.removed,
+                "freedBytes": stats.freed_bytes,
+            }))
+        }
+    }
+}
+
+/// フォローエクスポートの 1 行 (`@user@host` / `user@host` / `user`) を
+/// (username, host) にする。自サーバーのユーザーは host = None。
+fn parse_acct(line: &str, self_host: &str) -> Option<(String, Option<String>)> {
+    // CSV で列が増えても先頭列だけ見る
+    let acct = line.split(',').next()?.trim().trim_start_matches('@');
+    if acct.is_empty() || acct.starts_with('#') {
+        return None;
+    }
+    let (username, host) = match acct.split_once('@') {
+        Some((user, host)) => (user, Some(host)),
+        None => (acct, None),
+    };
+    if username.is_empty() || host.is_some_and(str::is_empty) {
+        return None;
+    }
+    let host = host
+        .filter(|h| !h.eq_ignore_ascii_case(self_host))
+        .map(|h| h.to_ascii_lowercase());
+    Some((username.to_string(), host))
+}
+
+/// 1 件ずつ users/show で解決してフォローする。再開時は progress.done 件目から続ける。
+/// 見つからない / 既にフォロー済みのアカウントは飛ばす。
+async fn import_follows(ctx: &JobContext, account_id: &str, path: &Path) -> Result<Value, String> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read file: {e}"))?;
+    let app_state = ctx.app.state::<crate::commands::AppState>();
+    let (client, host, token) = app_state
+        .authed(account_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let accts: Vec<_> = text.lines().filter_map(|l| parse_acct(l, &host)).collect();
+    let total = accts.len() as u64;
+    let start = ctx
+        .app
+        .state::<JobQueue>()
+        .get(&ctx.id)
+        .map_or(0, |j| j.progress.done)
+        .min(total);
+
+    let (mut followed, mut skipped, mut failed) = (0u64, 0u64, 0u64);
+    ctx.progress(start, Some(total));
+    for (i, (username, user_host)) in accts.iter().enumerate().skip(start as usize) {
+        let user = client
+            .request(
+                &host,
+                &token,
+                "users/show",
+                json!({ "username": username, "host": user_host }),
+            )
+            .await;
+        match user {
+            Ok(user) if user.get("isFollowing").and_then(Value::as_bool) == Some(true) => {
+                skipped += 1;
+            }
+            Ok(user) => match user.get("id").and_then(Value::as_str) {
+                Some(user_id) => match client.follow_user(&host, &token, user_id).await {
+                    Ok(()) => followed += 1,
+                    Err(e) => {
+                        tracing::debug!("[jobs] follow {username} failed: {e}");
+                        failed += 1;
+                    }
+                },
+                None => failed += 1,
+            },
+            Err(e) => {
+                tracing::debug!("[jobs] resolve {username} failed: {e}");
+                failed += 1;
+            }
+        }
+        ctx.progress(i as u64 + 1, Some(total));
+        tokio::time::sleep(FOLLOW_IMPORT_INTERVAL).await;
+    }
+    Ok(json!({
+        "total": total,
+        "followed": followed,
+        "skipped": skipped,
+        "failed": failed,
+    }))
+}
+
+/// `ExportDb` の書き出し先 (`app_dir/exports/notecli-<ms>.db`)。
+fn export_path(app_dir: &Path, at_ms: i64) -> PathBuf {
+    app_dir
+        .join(EXPORT_DIR)
+        .join(format!("notecli-{at_ms}.db"))
+}
+
+pub(crate) fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// ジョブを登録する。実行はバックグラウンドで行い、状態は `nd:job-updated` で届く。
+#[tauri::command]
+#[specta::specta]
+pub fn job_enqueue(
+    app: AppHandle,
+    queue: State<'_, JobQueue>,
+    spec: JobSpec,
+) -> Result<Job, NoteDeckError> {
+    let job = queue.enqueue(spec)?;
+    emit(&app, &job);
+    Ok(job)
+}
+
+/// バックエンド内 (スケジューラ等) からジョブを登録する。
+pub(crate) fn submit(app: &AppHandle, spec: JobSpec) -> Result<Job, NoteDeckError> {
+    job_enqueue(app.clone(), app.state::<JobQueue>(), spec)
+}
+
+/// 保持しているジョブ (待機中 / 実行中 / 終了済み) を登録順に返す。
+#[tauri::command]
+#[specta::specta]
+pub fn job_list(queue: State<'_, JobQueue>) -> Vec<Job> {
+    queue.list()
+}
+
+/// 待機中 / 実行中のジョブをキャンセルする。
+#[tauri::command]
+#[specta::specta]
+pub fn job_cancel(
+    app: AppHandle,
+    queue: State<'_, JobQueue>,
+    id: String,
+) -> Result<Job, NoteDeckError> {
+    let job = queue.cancel(&id)?;
+    emit(&app, &job);
+    Ok(job)
+}
+
+/// 終了済みのジョブを一覧から消し、残りを返す。
+#[tauri::command]
+#[specta::specta]
+pub fn job_clear_finished(queue: State<'_, JobQueue>) -> Vec<Job> {
+    queue.clear_finished()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> (tempfile::TempDir, JobQueue) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = app_db::open_in_memory().unwrap();
+        let queue = JobQueue::load(conn, &SettingsStore::load(dir.path()));
+        (dir, queue)
+    }
+
+    fn export() -> JobSpec {
+        JobSpec::ExportDb
+    }
+
+    #[test]
+    fn starts_at_most_max_concurrent() {
+        let (_dir, queue) = queue();
+        for _ in 0..3 {
+            queue.enqueue(export()).unwrap();
+        }
+        assert!(queue.start_next().is_some());
+        assert!(queue.start_next().is_some());
+        assert!(queue.start_next().is_none());
+
+        let first = queue.list()[0].id.clone();
+        queue.finish(&first, Ok(Value::Null)).unwrap();
+        assert_eq!(queue.list()[0].status, JobStatus::Succeeded);
+        assert!(queue.start_next().is_some());
+    }
+
+    #[test]
+    fn cancelled_job_is_not_overwritten_by_finish() {
+        let (_dir, queue) = queue();
+        let job = queue.enqueue(export()).unwrap();
+        queue.start_next().unwrap();
+        assert_eq!(queue.cancel(&job.id).unwrap().status, JobStatus::Cancelled);
+        assert!(queue.finish(&job.id, Ok(Value::Null)).is_none());
+        assert_eq!(queue.get(&job.id).unwrap().status, JobStatus::Cancelled);
+        // 終了済みは再キャンセルできない
+        assert!(queue.cancel(&job.id).is_err());
+    }
+
+    #[test]
+    fn restart_resumes_or_fails_interrupted_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = app_db::open_in_memory().unwrap();
+        {
+            let queue = JobQueue::load(conn.clone(), &SettingsStore::load(dir.path()));
+            queue
+                .enqueue(JobSpec::ImportFollows {
+                    account_id: "acc".into(),
+                    path: "/tmp/following.csv".into(),
+                })
+                .unwrap();
+            queue
+                .enqueue(JobSpec::Upload {
+                    account_id: "acc".into(),
+                    path: "/tmp/video.mp4".into(),
+                    is_sensitive: false,
+                    folder_id: None,
+                    name: None,
+                })
+                .unwrap();
+            let import = queue.start_next().unwrap();
+            queue.start_next().unwrap();
+            queue.set_progress(&import.id, 3, Some(10));
+            queue.save();
+        }
+
+        let jobs = JobQueue::load(conn, &SettingsStore::load(dir.path())).list();
+        assert_eq!(jobs[0].status, JobStatus::Queued);
+        assert_eq!(jobs[0].progress.done, 3);
+        assert_eq!(jobs[1].status, JobStatus::Failed);
+        assert!(jobs[1].error.is_some());
+    }
+
+    #[test]
+    fn keeps_a_bounded_number_of_finished_jobs() {
+        let (_dir, queue) = queue();
+        for _ in 0..MAX_FINISHED + 5 {
+            let job = queue.enqueue(JobSpec::PruneImageCache).unwrap();
+            queue.cancel(&job.id).unwrap();
+        }
+        let pending = queue.enqueue(JobSpec::PruneImageCache).unwrap();
+        let jobs = queue.list();
+        assert_eq!(jobs.len(), MAX_FINISHED + 1);
+        assert_eq!(jobs.last().unwrap().id, pending.id);
+
+        assert_eq!(queue.clear_finished().len(), 1);
+    }
+
+    #[test]
+    fn rejects_empty_fields() {
+        let (_dir, queue) = queue();
+        assert!(queue.enqueue(JobSpec::ImportFollows {
+            account_id: "acc".into(),
+            path: " ".into(),
+        })
+        .is_err());
+        let backup = |keep| JobSpec::Backup {
+            dir: "/tmp/backups".to_string(),
+            keep,
+        };
+        assert!(queue.enqueue(backup(0)).is_err());
+        assert!(queue.enqueue(backup(3)).is_ok());
+    }
+
+    #[test]
+    fn exports_stay_under_the_app_dir() {
+        let app_dir = Path::new("/data/notedeck");
+        assert_eq!(
+            export_path(app_dir, 42),
+            app_dir.join("exports").join("notecli-42.db")
+        );
+    }
+
+    #[test]
+    fn parses_follow_export_lines() {
+        assert_eq!(
+            parse_acct("@alice@Example.COM", "misskey.io"),
+            Some(("alice".into(), Some("example.com".into())))
+        );
+        assert_eq!(
+            parse_acct("bob@misskey.io,true", "misskey.io"),
+            Some(("bob".into(), None))
+        );
+        assert_eq!(
+            parse_acct("carol", "misskey.io"),
+            Some(("carol".into(), None))
+        );
+        assert_eq!(parse_acct("", "misskey.io"), None);
+        assert_eq!(parse_acct("# comment", "misskey.io"), None);
+        assert_eq!(parse_acct("dave@", "misskey.io"), None);
+    }
+}
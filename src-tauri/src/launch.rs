@@ -0,0 +1,284 @@
+//! 起動時の挙動 (OS 起動時の自動起動 / 最小化で起動 / トレイに格納して起動)。
+//!
+//! 自動起動の登録は tauri-plugin-autostart が OS 側 (レジストリ / LaunchAgent /
+//! XDG autostart) に持つので、ここでは登録の有無をそのまま読み書きする。
+//! 残りの 2 つはバックエンド設定 (`settings.rs`) の `launch` セクションに
+//! 保存し、自動起動で立ち上がったとき
+//! (AUTOSTART_ARG 付き) だけ適用する。手動起動では常にウィンドウを出す。
+//!
+//! メインウィンドウは `visible: false` で生成され、フロントのマウント後に
+//! `launch_reveal_window` で表示する。初回だけ起動時の設定に従って最小化 /
+//! 非表示にし、2 回目以降 (リロード等) は普通に表示する。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::settings::SettingsStore;
+
+/// 自動起動の登録時に渡す引数。
+pub const AUTOSTART_ARG: &str = "--autostart";
+/// 旧バージョンが自動起動に登録していた引数。登録済みの環境向けに同じ扱いにする。
+const LEGACY_AUTOSTART_ARG: &str = "--minimized";
+
+/// バックエンド設定の `launch` セクション。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LaunchConfig {
+    pub start_minimized: bool,
+    pub start_in_tray: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchSettings {
+    /// OS へのログイン時に自動起動する。
+    pub autostart: bool,
+    /// 自動起動時にウィンドウを最小化した状態で起動する。
+    pub start_minimized: bool,
+    /// 自動起動時にウィンドウを出さずトレイに格納して起動する
+    /// (start_minimized より優先。トレイが使えない環境では最小化になる)。
+    pub start_in_tray: bool,
+}
+
+/// 起動直後のメインウィンドウの状態。
+#[cfg_attr(mobile, allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InitialWindow {
+    Shown,
+    Minimized,
+    Hidden,
+}
+
+#[cfg_attr(mobile, allow(dead_code))]
+fn initial_window(config: &LaunchConfig, autostarted: bool, has_tray: bool) -> InitialWindow {
+    if !autostarted {
+        return InitialWindow::Shown;
+    }
+    if config.start_in_tray && has_tray {
+        InitialWindow::Hidden
+    } else if config.start_minimized || config.start_in_tray {
+        InitialWindow::Minimized
+    } else {
+        InitialWindow::Shown
+    }
+}
+
+fn is_autostart_launch(mut args: impl Iterator<Item = String>) -> bool {
+    args.any(|a| a == AUTOSTART_ARG || a == LEGACY_AUTOSTART_ARG)
+}
+
+pub struct LaunchState {
+    settings: Arc<SettingsStore>,
+    autostarted: bool,
+    /// 起動時の状態をまだ適用していない。
+    pending: AtomicBool,
+}
+
+impl LaunchState {
+    pub fn new(settings: Arc<SettingsStore>) -> Self {
+        Self {
+            settings,
+            autostarted: is_autostart_launch(std::env::args()),
+            pending: AtomicBool::new(true),
+        }
+    }
+
+    fn config(&self) -> LaunchConfig {
+        self.settings.read(|s| s.launch)
+    }
+
+    fn save(&self, config: LaunchConfig) -> Result<(), NoteDeckError> {
+        self.settings
+            .update(|s| {
+                s.launch = config;
+                Ok(())
+            })
+            .map(|_| ())
+    }
+
+    /// 初回だけ起動時の状態を返し、以降は Shown。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn take_initial(&self, has_tray: bool) -> InitialWindow {
+        if !self.pending.swap(false, Ordering::SeqCst) {
+            return InitialWindow::Shown;
+        }
+        initial_window(&self.config(), self.autostarted, has_tray)
+    }
+}
+
+#[cfg(not(mobile))]
+fn autostart_enabled(app: &tauri::AppHandle) -> Result<bool, NoteDeckError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| NoteDeckError::InvalidInput(format!("autostart: {e}")))
+}
+
+#[cfg(mobile)]
+fn autostart_enabled(_app: &tauri::AppHandle) -> Result<bool, NoteDeckError> {
+    Ok(false)
+}
+
+/// 現在の起動設定を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn launch_get_settings(
+    app: tauri::AppHandle,
+    state: State<'_, LaunchState>,
+) -> Result<LaunchSettings, NoteDeckError> {
+    let config = state.config();
+    Ok(LaunchSettings {
+        autostart: autostart_enabled(&app)?,
+        start_minimized: config.start_minimized,
+        start_in_tray: config.start_in_tray,
+    })
+}
+
+/// 起動設定を更新し、反映後の値を返す。自動起動は OS への登録 / 解除を行う。
+#[tauri::command]
+#[specta::specta]
+pub fn launch_set_settings(
+    app: tauri::AppHandle,
+    state: State<'_, LaunchState>,
+    settings: LaunchSettings,
+) -> Result<LaunchSettings, NoteDeckError> {
+    #[cfg(not(mobile))]
+    if settings.autostart != autostart_enabled(&app)? {
+        use tauri_plugin_autostart::ManagerExt;
+
+        let autolaunch = app.autolaunch();
+        let result = if settings.autostart {
+            autolaunch.enable()
+        } else {
+            autolaunch.disable()
+        };
+        result.map_err(|e| NoteDeckError::InvalidInput(format!("autostart: {e}")))?;
+    }
+    #[cfg(mobile)]
+    if settings.autostart {
+        return Err(NoteDeckError::InvalidInput(
+            "autostart is not available on mobile".to_string(),
+        ));
+    }
+
+    state.save(LaunchConfig {
+        start_minimized: settings.start_minimized,
+        start_in_tray: settings.start_in_tray,
+    })?;
+    launch_get_settings(app, state)
+}
+
+/// 呼び出し元のウィンドウを表示する。メインウィンドウの初回だけは起動設定に
+/// 従って最小化 / トレイ格納のままにする。
+#[tauri::command]
+#[specta::specta]
+pub fn launch_reveal_window(
+    window: tauri::WebviewWindow,
+    state: State<'_, LaunchState>,
+) -> Result<(), NoteDeckError> {
+    let to_err = |e: tauri::Error| NoteDeckError::InvalidInput(format!("window: {e}"));
+
+    #[cfg(not(mobile))]
+    {
+        use tauri::Manager;
+
+        if window.label() != "main" {
+            return window.show().map_err(to_err);
+        }
+        let has_tray = window
+            .app_handle()
+            .tray_by_id(crate::tray::TRAY_ID)
+            .is_some();
+        match state.take_initial(has_tray) {
+            InitialWindow::Shown => window.show().map_err(to_err),
+            InitialWindow::Minimized => {
+                // 先に show すると最小化までの間に一瞬表示される
+                window.minimize().map_err(to_err)?;
+                window.show().map_err(to_err)
+            }
+            InitialWindow::Hidden => Ok(()),
+        }
+    }
+    #[cfg(mobile)]
+    {
+        let _ = state;
+        window.show().map_err(to_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(start_minimized: bool, start_in_tray: bool) -> LaunchConfig {
+        LaunchConfig {
+            start_minimized,
+            start_in_tray,
+        }
+    }
+
+    #[test]
+    fn manual_launch_always_shows() {
+        assert_eq!(
+            initial_window(&config(true, true), false, true),
+            InitialWindow::Shown
+        );
+    }
+
+    #[test]
+    fn autostart_applies_options() {
+        assert_eq!(
+            initial_window(&config(false, false), true, true),
+            InitialWindow::Shown
+        );
+        assert_eq!(
+            initial_window(&config(true, false), true, true),
+            InitialWindow::Minimized
+        );
+        assert_eq!(
+            initial_window(&config(true, true), true, true),
+            InitialWindow::Hidden
+        );
+        // トレイが無ければ格納できないので最小化にとどめる
+        assert_eq!(
+            initial_window(&config(false, true), true, false),
+            InitialWindow::Minimized
+        );
+    }
+
+    #[test]
+    fn detects_autostart_args() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_autostart_launch(
+            args(&["notedeck", "--autostart"]).into_iter()
+        ));
+        assert!(is_autostart_launch(
+            args(&["notedeck", "--minimized"]).into_iter()
+        ));
+        assert!(!is_autostart_launch(
+            args(&["notedeck", "notedeck://misskey.io/notifications"]).into_iter()
+        ));
+    }
+
+    #[test]
+    fn initial_state_is_applied_once() {
+        let dir = std::env::temp_dir().join(format!("notedeck-launch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut state = LaunchState::load(&dir);
+        state.autostarted = true;
+        state.save(config(false, true)).unwrap();
+        assert_eq!(state.take_initial(true), InitialWindow::Hidden);
+        assert_eq!(state.take_initial(true), InitialWindow::Shown);
+
+        // 保存した設定は次回起動時に読み直される
+        let reloaded = LaunchState::load(&dir);
+        assert_eq!(*reloaded.config.lock().unwrap(), config(false, true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -4,11 +4,7 @@ use tauri::Manager;
 #[cfg(not(mobile))]
 use std::sync::atomic::{AtomicBool, Ordering};
 #[cfg(not(mobile))]
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter,
-};
+use tauri::Emitter;
 #[cfg(not(mobile))]
 use tauri_plugin_autostart::MacosLauncher;
 #[cfg(not(mobile))]
@@ -17,27 +13,67 @@ use tauri_plugin_global_shortcut::GlobalShortcutExt;
 mod account_service;
 mod ai_chat_service;
 mod api_tokens;
+mod app_db;
 mod app_dir;
 mod auth_service;
+mod automation;
+mod avatar_warm;
+mod background_sync;
+mod backup;
+mod capability_registry;
 mod commands;
+mod crash_report;
+mod dnd;
+mod emoji_cache;
+mod event_cue;
+mod host_queue;
 #[cfg(target_os = "windows")]
 mod hwheel_hook;
+mod idle;
+mod launch;
 /// Public so the `gen-openapi` binary and the OpenAPI snapshot test can call
 /// [`http_server::build_openapi`].
 pub mod http_server;
 mod image_cache;
+mod ipc_binary;
+mod jobs;
+mod media_gate;
+mod merged_timeline;
+mod mfm;
 mod migrations;
+mod network;
+mod note_patch;
+mod note_share;
 mod ogp;
+mod page_prefetch;
 mod os_notify;
 mod perf_config;
 mod permissions_gate;
+mod plugin_host;
+mod power;
+mod qr;
 mod query_bridge;
 mod query_runtime;
+mod quick_post;
+mod settings;
 mod settings_store;
 mod rate_limit;
+mod request_dedup;
+mod scheduler;
 mod streaming;
+mod system_theme;
+mod timeline_gaps;
+mod translation;
+mod tray;
+mod upload_history;
+mod upload_prep;
+mod upstream_rate;
 mod vault;
+mod video_compress;
 mod win_chrome;
+#[cfg(not(mobile))]
+mod window_geometry;
+mod window_manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -120,32 +156,14 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
 
     #[cfg(not(mobile))]
     {
-        // ウィンドウ位置・サイズの永続化 (#643)。
-        // - 対象は main のみ: PiP/デッキ派生ウィンドウはラベルが動的生成
-        //   (pip-<ts>-<n>) で復元先が二度とマッチせず、state ファイルに
-        //   ゴミが溜まり続けるため除外
-        // - VISIBLE は保存しない: close→トレイ hide→Quit で visible=false が
-        //   保存されると次回起動が不可視になるため
-        // - DECORATIONS も除外 (decorations:false 固定、win_chrome が管理)
-        use tauri_plugin_window_state::StateFlags;
+        // ウィンドウ位置・サイズの永続化 (#643) は window_geometry (SQLite) が担う
         builder = builder
-            .plugin(
-                tauri_plugin_window_state::Builder::default()
-                    .with_state_flags(
-                        StateFlags::SIZE
-                            | StateFlags::POSITION
-                            | StateFlags::MAXIMIZED
-                            | StateFlags::FULLSCREEN,
-                    )
-                    .with_filter(|label| label == "main")
-                    .build(),
-            )
             .plugin(tauri_plugin_updater::Builder::new().build())
             .plugin(tauri_plugin_process::init())
             .plugin(tauri_plugin_global_shortcut::Builder::new().build())
             .plugin(tauri_plugin_autostart::init(
                 MacosLauncher::LaunchAgent,
-                Some(vec!["--minimized"]),
+                Some(vec![launch::AUTOSTART_ARG]),
             ));
     }
 
@@ -159,7 +177,30 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("Failed to export typescript bindings: {e}");
     }
 
-    builder = builder.invoke_handler(specta_builder.invoke_handler());
+    // raw バイト列を返すコマンド (ipc_binary.rs) は specta の型を持たないので
+    // 別の handler に振り分ける
+    let specta_handler = specta_builder.invoke_handler();
+    let binary_handler = tauri::generate_handler![
+        ipc_binary::api_get_timeline_bin,
+        ipc_binary::api_get_cached_timeline_bin,
+        ipc_binary::api_get_cached_timeline_before_bin,
+    ];
+    builder = builder.invoke_handler(move |invoke| {
+        // クイック投稿ミニウィンドウは投稿フォームのコマンドだけ (quick_post.rs)
+        if !quick_post::allows(invoke.message.webview().label(), invoke.message.command()) {
+            let message = format!(
+                "{} is not allowed in the quick post window",
+                invoke.message.command()
+            );
+            invoke.resolver.reject(message);
+            return true;
+        }
+        if ipc_binary::COMMANDS.contains(&invoke.message.command()) {
+            binary_handler(invoke)
+        } else {
+            specta_handler(invoke)
+        }
+    });
 
     #[cfg(not(mobile))]
     let has_tray = Arc::new(AtomicBool::new(false));
@@ -194,6 +235,12 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         let app_dir = app_dir::resolve_app_dir(app)?;
         std::fs::create_dir_all(&app_dir)?;
 
+        // バックエンド設定 (settings.json)。各機能のセクションもここから読むので最初に開く
+        let backend_settings = std::sync::Arc::new(settings::SettingsStore::load(&app_dir));
+
+        // クラッシュレポート (opt-in)。以降の panic を拾えるよう app_dir の直後に差し込む
+        app.manage(crash_report::install(&app_dir, backend_settings.clone()));
+
         // Initialize platform keychain + filesystem migrations (both lightweight)
         if let Err(e) = notecli::keychain::init_store() {
             tracing::warn!("keychain unavailable ({e})");
@@ -203,10 +250,28 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         // AppState: empty wrapper — commands await until Phase 2 fills it
         let app_state = commands::AppState::new();
         app.manage(app_state);
+        // 同一タイムライン / ユーザー取得の合流 (複数カラムの同時リフレッシュ)
+        app.manage(request_dedup::RequestDedup::default());
+        app.manage(emoji_cache::EmojiCache::default());
+        emoji_cache::spawn_sync(app.handle().clone());
+        app.manage(page_prefetch::PagePrefetcher::default());
+        // 上流サーバーの 429 状態 (読み取りの自動再試行 + UI のリフレッシュ抑制)
+        app.manage(upstream_rate::UpstreamRate::default());
+        // 上流ホストごとの同時実行数 (バックグラウンド処理がユーザー操作を塞がないように)
+        app.manage(host_queue::HostQueue::default());
+        app.manage(translation::TranslationCache::default());
+        // アップロード前の EXIF 除去等 (設定は settings.json、変更はフロントから)
+        app.manage(upload_prep::UploadPrep::new(backend_settings.clone()));
 
-        // Performance config: starts with defaults, updated dynamically via Tauri command
+        // Performance config: starts from settings.json, updated dynamically via Tauri command
+        let initial_perf = backend_settings.get().perf_config();
+        let stream_limits = initial_perf.stream_limits();
+        app.manage(media_gate::MediaGate::new(
+            backend_settings.get().media.hide_sensitive,
+        ));
+        app.manage(backend_settings.clone());
         let shared_perf: perf_config::SharedPerfConfig =
-            std::sync::Arc::new(tokio::sync::RwLock::new(perf_config::PerformanceConfig::default()));
+            std::sync::Arc::new(tokio::sync::RwLock::new(initial_perf));
         let shared_perf_bg = shared_perf.clone();
         app.manage(shared_perf);
 
@@ -234,7 +299,13 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
 
         // Query runtime: stream events から Read Model を materialize し、
         // pending を貯めて 16ms 間隔で query-delta event をバッチ emit する。
-        app.manage(query_runtime::QueryRuntime::default());
+        let queries = query_runtime::QueryRuntime::default();
+        queries.set_stream_limits(stream_limits);
+        app.manage(queries);
+        app.manage(merged_timeline::MergedTimelines::default());
+
+        // HTTP API → フロントの query bridge (応答待ちレジストリ + listener)
+        query_bridge::init(app.handle());
         // 常駐 flusher: notify_one を受けて DELTA_FLUSH_WINDOW スリープ後に
         // drain_pending() を emit。
         let flusher_app = app.app_handle().clone();
@@ -261,6 +332,105 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         let api_token_store = std::sync::Arc::new(api_tokens::ApiTokenStore::load(&app_dir));
         app.manage(api_token_store.clone());
 
+        // 外部プラグイン (起動は HTTP API の受付開始後)
+        let plugin_host = std::sync::Arc::new(plugin_host::PluginHost::load(
+            &app_dir,
+            backend_settings.clone(),
+        ));
+        app.manage(plugin_host.clone());
+
+        // アプリ側の小さな状態 (ジオメトリ / gap / アップロード履歴 / ジョブ /
+        // 予約投稿) の DB。ジオメトリを表示前に復元するため Phase 1 で開く
+        // (notecli.db は Phase 2 まで開けない)
+        let app_conn = match app_db::open(&app_dir) {
+            Ok(conn) => {
+                // メインウィンドウのジオメトリ (#643)
+                #[cfg(not(mobile))]
+                match window_geometry::GeometryStore::new(conn.clone()) {
+                    Ok(store) => {
+                        if let Some(w) = app.get_webview_window("main") {
+                            window_geometry::restore(&w, &store);
+                        }
+                        app.manage(store);
+                    }
+                    Err(e) => tracing::warn!("window geometry store unavailable: {e}"),
+                }
+
+                // タイムラインキャッシュの gap 記録
+                match timeline_gaps::GapStore::new(conn.clone()) {
+                    Ok(store) => {
+                        app.manage(store);
+                    }
+                    Err(e) => tracing::warn!("timeline gap store unavailable: {e}"),
+                }
+
+                // 自分がアップロードしたファイルの記録 (投稿フォームの「最近のアップロード」)
+                match upload_history::UploadHistory::new(conn.clone()) {
+                    Ok(store) => {
+                        app.manage(store);
+                    }
+                    Err(e) => tracing::warn!("upload history unavailable: {e}"),
+                }
+                conn
+            }
+            Err(e) => {
+                // ジョブと定期タスクの worker は常に動くので、保存できなくても
+                // メモリ上の DB で続ける
+                tracing::warn!("app state db unavailable: {e}");
+                app_db::open_in_memory()?
+            }
+        };
+
+        // OS の DND 検知 (設定はフロントが dnd_set_mode で反映する)
+        let dnd_state = dnd::DndState::default();
+        dnd_state.refresh();
+        app.manage(dnd_state);
+
+        // 通知ごとの音 / 優先度 (WebView の stream-cue と HTTP API の /api/cues)
+        app.manage(event_cue::CueBus::default());
+
+        // アイドル (離席) 検知。閾値はフロントが idle_configure で反映する
+        app.manage(idle::IdleMonitor::default());
+        #[cfg(not(mobile))]
+        idle::spawn_watcher(app.handle().clone());
+
+        // OS のライト / ダーク・アクセントカラー (変化時に nd:system-appearance-changed)
+        app.manage(system_theme::SystemThemeState::default());
+        #[cfg(not(mobile))]
+        system_theme::spawn_watcher(app.handle().clone());
+
+        // スリープ復帰でストリームを作り直す (stream_* コマンドが対象を記録)
+        app.manage(power::ActiveStreams::default());
+        #[cfg(not(mobile))]
+        power::spawn_watcher(app.handle().clone());
+
+        // 回線断 / 復旧の検知 (オフライン中はストリームを退避)
+        app.manage(network::NetworkMonitor::default());
+        #[cfg(not(mobile))]
+        network::spawn_watcher(app.handle().clone());
+
+        // 切り離しウィンドウの記録 (小さな JSON なので Phase 1 で読む)
+        app.manage(window_manager::WindowManager::load(&app_dir));
+
+        // 起動時の挙動 (最小化 / トレイ格納)。フロントの launch_reveal_window が参照する
+        app.manage(launch::LaunchState::new(backend_settings.clone()));
+
+        // バックグラウンドジョブ (前回中断したものは worker が再開する)
+        app.manage(jobs::JobQueue::load(app_conn.clone(), &backend_settings));
+        jobs::spawn_worker(app.handle().clone());
+
+        // 自動化ルール (streaming の TauriEmitter がイベントごとに照合する)
+        app.manage(automation::AutomationEngine::load(backend_settings.clone()));
+
+        // 定期タスク / 予約投稿 (前回閉じている間に過ぎたものは worker が追いつく)
+        app.manage(scheduler::Scheduler::load(app_conn, &backend_settings));
+        scheduler::spawn_worker(app.handle().clone());
+
+        // トレイ格納中のバックグラウンド同期 (ウィンドウが非表示の間だけ動く)
+        app.manage(background_sync::BackgroundSync::new(backend_settings));
+        #[cfg(not(mobile))]
+        background_sync::spawn_worker(app.handle().clone());
+
         // ══════════════════════════════════════════════════════════
         // Phase 2: Heavy init in background thread (two-stage)
         //
@@ -317,6 +487,8 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             // ため、後に置くと State 未登録で "state not managed" の即時エラー
             // になる race がある (query 購読は初回失敗すると再試行されない)。
             let emitter = std::sync::Arc::new(streaming::TauriEmitter::new(app_handle.clone()));
+            // バックグラウンド同期も同じ emitter で OS 通知を出す (dedup を共有)
+            app_handle.manage(emitter.clone());
             app_handle.manage(notecli::streaming::StreamingManager::new(
                 emitter,
                 event_bus.clone(),
@@ -337,6 +509,8 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             let image_cache = std::sync::Arc::new(
                 image_cache::ImageCache::with_client(&app_dir_bg, shared_http, shared_perf_bg.clone()),
             );
+            // アバター温め (avatar_warm.rs) からも使う
+            app_handle.manage(image_cache.clone());
 
             // Start HTTP API server (attach routes to pre-bound listener)
             // Wait for the server to be ready before signalling the frontend,
@@ -344,6 +518,7 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             if let Some(server) = bound_server {
                 let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<()>();
                 let serve_app_handle = app_handle.clone();
+                let serve_plugin_host = plugin_host.clone();
                 tauri::async_runtime::spawn(async move {
                     http_server::serve(http_server::ServeConfig {
                         server,
@@ -353,6 +528,7 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                         event_bus,
                         api_token,
                         api_token_store,
+                        plugin_host: serve_plugin_host,
                         token_path: token_path_str,
                         image_cache,
                         perf: shared_perf_bg,
@@ -361,8 +537,12 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                 });
                 // Block until routes are built and server is about to accept
                 tauri::async_runtime::block_on(async { ready_rx.await.ok() });
+                plugin_host.start_enabled();
             }
             let _ = tauri::Emitter::emit(&app_handle, "nd:backend-ready", ());
+
+            // 保存済みアカウントのアバター / バナーを先読みする
+            avatar_warm::spawn(&app_handle, None);
         });
 
         // Periodic credential cache cleanup (every 5 minutes)
@@ -401,6 +581,16 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                 if let Some(url) = urls.first() {
                     let url_str = url.as_str().to_string();
                     tracing::info!("[deep-link] received: {url_str}");
+                    // Windows の通知 toast クリック (#754): アプリ起動中は
+                    // 第 2 インスタンス → single-instance 転送でここに届く。
+                    // クリック/Open は NotificationClicked 経路へ、Reply/React は
+                    // ウィンドウをフォーカスせずに実行する (他 OS でこのスキームが
+                    // 届くことはない — 届いても遷移させず無視)
+                    if url.scheme() == os_notify::NOTIFICATION_PROTOCOL {
+                        #[cfg(target_os = "windows")]
+                        os_notify::handle_protocol_url(&deep_link_handle, &url_str);
+                        return;
+                    }
                     // show/set_focus はモバイルの WebviewWindow に存在しない
                     // (Android は intent で既に前面化されている)
                     #[cfg(desktop)]
@@ -408,20 +598,6 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                         let _ = w.show();
                         let _ = w.set_focus();
                     }
-                    // Windows の通知 toast クリック (#754): アプリ起動中は
-                    // 第 2 インスタンス → single-instance 転送でここに届く。
-                    // 復元して既存の NotificationClicked 経路に流す (他 OS で
-                    // このスキームが届くことはない — 届いても遷移させず無視)
-                    if url.scheme() == os_notify::NOTIFICATION_PROTOCOL {
-                        #[cfg(target_os = "windows")]
-                        if let Some(ctx) = os_notify::decode_protocol_url(&url_str) {
-                            use tauri_specta::Event;
-                            if let Err(e) = ctx.emit(&deep_link_handle) {
-                                tracing::warn!("[notification] click emit failed: {e}");
-                            }
-                        }
-                        return;
-                    }
                     let _ = tauri::Emitter::emit(&deep_link_handle, "nd:deep-link", &url_str);
                 }
             });
@@ -462,17 +638,15 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                     if event.state != ShortcutState::Pressed {
                         return;
                     }
-                    if let Some(w) = app.get_webview_window("main") {
-                        if w.is_visible().unwrap_or(false) {
-                            let _ = w.hide();
-                        } else {
-                            let _ = w.show();
-                            let _ = w.set_focus();
-                        }
-                    }
+                    capability_registry::spawn_local(
+                        app,
+                        "window.toggle",
+                        serde_json::Value::Null,
+                    );
                 })?;
 
-            // Quick Note: Ctrl+Alt+N — show window + emit event for post mode
+            // Quick Note: Ctrl+Alt+N — デッキを操作中ならデッキ内の post モード、
+            // それ以外 (非表示/背面) はデッキを復元せずクイック投稿ミニウィンドウ
             let quick_note = GShortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyN);
             app.global_shortcut()
                 .on_shortcut(quick_note, |app: &tauri::AppHandle, _, event| {
@@ -480,75 +654,29 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
                         return;
                     }
                     if let Some(w) = app.get_webview_window("main") {
-                        let _ = w.show();
-                        let _ = w.set_focus();
-                        let _ = w.emit("nd:quick-note", ());
+                        let active = w.is_visible().unwrap_or(false)
+                            && w.is_focused().unwrap_or(false);
+                        if active {
+                            let _ = w.emit("nd:quick-note", ());
+                            return;
+                        }
                     }
+                    capability_registry::spawn_local(
+                        app,
+                        "quickPost.open",
+                        serde_json::Value::Null,
+                    );
                 })?;
         }
 
         // System tray (desktop only)
         #[cfg(not(mobile))]
-        {
-            let show_i = MenuItem::with_id(app, "show", "Show NoteDeck", true, None::<&str>)?;
-            let offline_i = MenuItem::with_id(app, "offline", "Offline Mode", true, None::<&str>)?;
-            let realtime_i = MenuItem::with_id(app, "realtime", "Realtime Mode", true, None::<&str>)?;
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &offline_i, &realtime_i, &quit_i])?;
-
-            let icon = app
-                .default_window_icon()
-                .ok_or("Default window icon not found")?
-                .clone();
-
-            match TrayIconBuilder::with_id("main")
-                .icon(icon)
-                .tooltip("NoteDeck")
-                .menu(&menu)
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "show" => {
-                        if let Some(w) = app.get_webview_window("main") {
-                            let _ = w.show();
-                            let _ = w.set_focus();
-                        }
-                    }
-                    "offline" => {
-                        let _ = app.emit("nd:toggle-offline-mode", ());
-                    }
-                    "realtime" => {
-                        let _ = app.emit("nd:toggle-realtime-mode", ());
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        if let Some(w) = app.get_webview_window("main") {
-                            if w.is_visible().unwrap_or(false) {
-                                let _ = w.hide();
-                            } else {
-                                let _ = w.show();
-                                let _ = w.set_focus();
-                            }
-                        }
-                    }
-                })
-                .build(app)
-            {
-                Ok(_) => {
-                    has_tray_for_setup.store(true, Ordering::Relaxed);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to create tray icon (continuing without it): {e}");
-                }
+        match tray::init(app) {
+            Ok(()) => {
+                has_tray_for_setup.store(true, Ordering::Relaxed);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to create tray icon (continuing without it): {e}");
             }
         }
 
@@ -580,6 +708,13 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        // 前回開いていた切り離しウィンドウ (ノート / プロフィール) を復元
+        #[cfg(not(mobile))]
+        window_manager::restore_all(
+            app.handle(),
+            &app.state::<window_manager::WindowManager>(),
+        );
+
         Ok(())
     });
 
@@ -588,7 +723,33 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
     {
         let has_tray = has_tray.clone();
         builder = builder.on_window_event(move |window, event| {
+            if let tauri::WindowEvent::ThemeChanged(_) = event {
+                if window.label() == "main" {
+                    system_theme::refresh(window.app_handle());
+                }
+                return;
+            }
+            if let tauri::WindowEvent::Destroyed = event {
+                if window.label() == "main" {
+                    query_bridge::cancel_all(window.app_handle());
+                }
+                return;
+            }
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // 切り離しウィンドウはユーザーが閉じたら記録から外して破棄する
+                // (アプリ終了時は CloseRequested が来ないので記録が残り、次回復元)
+                if window_manager::is_detached_label(window.label()) {
+                    let manager = window.state::<window_manager::WindowManager>();
+                    if let Err(e) = manager.forget(window.label()) {
+                        tracing::warn!("[window] failed to forget detached window: {e}");
+                    }
+                    return;
+                }
+                if window.label() == "main" {
+                    if let Some(w) = window.get_webview_window("main") {
+                        window_geometry::capture(&w);
+                    }
+                }
                 if has_tray.load(Ordering::Relaxed) {
                     api.prevent_close();
                     let _ = window.hide();
@@ -597,7 +758,15 @@ fn run_inner() -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
-    builder.run(tauri::generate_context!())?;
+    let app = builder.build(tauri::generate_context!())?;
+    app.run(|handle, event| {
+        // 外部プラグインのプロセスを残さない
+        if let tauri::RunEvent::Exit = event {
+            handle
+                .state::<std::sync::Arc<plugin_host::PluginHost>>()
+                .stop_all();
+        }
+    });
 
     Ok(())
 }
@@ -632,6 +801,8 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::api_get_user_policies,
             commands::api_update_user_setting,
             commands::api_get_timeline,
+            commands::api_get_timelines_bulk,
+            commands::api_prefetch_timeline_page,
             commands::api_get_user_lists,
             commands::api_get_antennas,
             commands::api_get_antenna,
@@ -651,6 +822,7 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::api_create_reaction,
             commands::api_delete_reaction,
             commands::api_vote_poll,
+            commands::api_get_poll_recommendations,
             commands::api_get_note_reactions,
             commands::api_update_note,
             commands::api_upload_file,
@@ -667,9 +839,14 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::api_reject_follow_request,
             commands::api_cancel_follow_request,
             commands::api_get_user,
+            commands::api_get_users,
             commands::api_get_user_detail,
             commands::api_get_user_notes,
             commands::api_get_server_emojis,
+            commands::api_resolve_emojis,
+            commands::search_emojis,
+            commands::sync_emojis,
+            commands::ack_emojis,
             commands::api_get_pinned_reactions,
             commands::api_get_notifications,
             commands::api_get_notifications_grouped,
@@ -683,12 +860,17 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::api_verify_notes,
             commands::api_get_cached_timeline_before,
             commands::api_get_cache_date_range,
+            timeline_gaps::timeline_gaps,
+            timeline_gaps::fill_gap,
             commands::api_search_notes_local,
             commands::api_find_notes_by_uri,
             commands::api_pin_note,
             commands::api_unpin_note,
             commands::api_mute_user,
             commands::api_unmute_user,
+            commands::api_get_renote_mute_support,
+            commands::api_set_renote_mute,
+            commands::api_get_following_renotes_muted,
             commands::api_renote_mute_user,
             commands::api_unrenote_mute_user,
             commands::api_get_muted_users,
@@ -704,8 +886,10 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::api_get_following,
             commands::api_get_followers,
             commands::api_get_user_relations,
+            commands::api_get_frequently_replied_users,
             commands::api_get_unread_notification_count,
             commands::api_mark_all_notifications_as_read,
+            commands::api_send_test_notification,
             commands::api_get_unread_chat,
             commands::api_get_self,
             commands::api_get_drive_folders,
@@ -806,6 +990,7 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             commands::stream_set_mode,
             commands::stream_sub_note,
             commands::stream_unsub_note,
+            commands::stream_simulate_notification,
             commands::fetch_ogp,
             commands::fetch_server_meta,
             commands::fetch_image_base64,
@@ -878,18 +1063,78 @@ pub fn build_specta_builder() -> tauri_specta::Builder<tauri::Wry> {
             query_runtime::query_close,
             query_runtime::query_get_snapshot,
             query_runtime::query_get_read_model_snapshot,
+            merged_timeline::api_get_merged_timeline,
+            merged_timeline::merged_timeline_open,
+            merged_timeline::merged_timeline_close,
             perf_config::update_performance_config,
             perf_config::get_performance_config,
+            settings::settings_get,
+            settings::settings_set_section,
+            settings::settings_reset,
             permissions_gate::permissions_sync,
             permissions_gate::permissions_lockdown,
+            quick_post::quick_post_open,
+            quick_post::quick_post_hide,
+            window_manager::window_open_detached,
+            window_manager::window_list_detached,
+            window_manager::window_close_detached,
+            dnd::dnd_get_status,
+            dnd::dnd_set_mode,
+            idle::idle_get_status,
+            idle::idle_configure,
+            upload_history::recent_uploads,
+            upload_history::forget_recent_upload,
+            system_theme::system_appearance,
+            network::network_get_status,
+            network::network_check_now,
+            launch::launch_get_settings,
+            launch::launch_set_settings,
+            launch::launch_reveal_window,
+            background_sync::background_sync_get_settings,
+            background_sync::background_sync_set_settings,
+            media_gate::reveal_file,
+            jobs::job_enqueue,
+            jobs::job_list,
+            jobs::job_cancel,
+            jobs::job_clear_finished,
+            plugin_host::plugin_list,
+            plugin_host::plugin_set_enabled,
+            automation::automation_list,
+            automation::automation_save,
+            automation::automation_delete,
+            scheduler::schedule_list,
+            scheduler::schedule_save,
+            scheduler::schedule_delete,
+            scheduler::schedule_run_now,
+            capability_registry::capability_list,
+            capability_registry::capability_execute,
+            upstream_rate::api_get_rate_limit_state,
+            translation::translate_text,
+            qr::generate_qr,
+            upload_prep::upload_prep_configure,
+            video_compress::video_compress_check,
+            avatar_warm::warm_user_images,
+            note_share::format_note_for_share,
+            backup::backup_set_passphrase,
+            backup::backup_has_passphrase,
+            backup::backup_pick_dir,
+            backup::backup_import,
+            crash_report::crash_reports_get_enabled,
+            crash_report::crash_reports_set_enabled,
+            crash_report::crash_reports_list,
+            crash_report::crash_reports_clear,
+            crash_report::crash_reports_dir,
+            crash_report::crash_report_capture,
         ])
         .events(tauri_specta::collect_events![
             query_runtime::QueryDelta,
             query_runtime::NoteCaptureBatch,
+            merged_timeline::MergedTimelineDelta,
             streaming::StreamEnvelope,
             streaming::StreamStatus,
             streaming::StreamChatMessageReacted,
             streaming::StreamChatMessageUnreacted,
+            event_cue::StreamCue,
             os_notify::NotificationClicked,
         ])
 }
@@ -0,0 +1,280 @@
+//! センシティブ指定のメディアを WebView に渡さないゲート。
+//!
+//! バックエンド設定の `media.hideSensitive` が有効な間、フロントへ返すノート /
+//! 通知の添付ファイルのうち `isSensitive` のものは `url` を空にして
+//! `thumbnailUrl` を外す (blurhash は残るのでプレースホルダは出せる)。本物の
+//! URL はここで覚えておき、ユーザーが明示的に表示したときだけ `reveal_file`
+//! で渡す。
+//!
+//! 型に依存せず JSON として辿るので、返信 / リノート先 (notecli では生 JSON)
+//! の添付も同じように隠れる。キャッシュ DB には元のノートをそのまま保存する。
+//!
+//! 通す経路: ノート / 通知を返すすべてのコマンド (タイムライン・アンテナ・
+//! クリップ・検索・スレッド・キャッシュの読み出し等。ノートを含みうる生 JSON
+//! のコマンドも含む)、バイナリ版タイムライン、ストリーミングのノート /
+//! メンション / 通知。コマンドの漏れは `tests/media_gate_coverage.rs` が
+//! `#[tauri::command]` を走査して検出する。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use notecli::error::NoteDeckError;
+use notecli::streaming::StreamEvent;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use tauri::State;
+
+/// `reveal_file` 用に覚えておく URL の件数。超えたら古いものから忘れる
+/// (忘れたファイルはノートを読み直せばまた表示できる)。
+const MAX_HIDDEN: usize = 5000;
+
+/// 隠した添付ファイルの本来の URL。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealedFile {
+    pub url: String,
+    pub thumbnail_url: Option<String>,
+}
+
+#[derive(Default)]
+struct HiddenFiles {
+    by_id: HashMap<String, RevealedFile>,
+    order: VecDeque<String>,
+}
+
+impl HiddenFiles {
+    fn remember(&mut self, id: String, file: RevealedFile) {
+        if self.by_id.insert(id.clone(), file).is_none() {
+            self.order.push_back(id);
+        }
+        while self.order.len() > MAX_HIDDEN {
+            if let Some(oldest) = self.order.pop_front() {
+                self.by_id.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// 添付ファイル 1 件 (`files` 配列の要素) がセンシティブなら URL を外す。
+fn hide_file(file: &mut Value) -> Option<(String, RevealedFile)> {
+    let obj = file.as_object_mut()?;
+    if obj.get("isSensitive") != Some(&Value::Bool(true)) {
+        return None;
+    }
+    let id = obj.get("id")?.as_str()?.to_string();
+    let url = obj.get("url")?.as_str()?.to_string();
+    if url.is_empty() {
+        return None;
+    }
+    let thumbnail_url = obj
+        .get("thumbnailUrl")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    obj.insert("url".into(), Value::String(String::new()));
+    if obj.contains_key("thumbnailUrl") {
+        obj.insert("thumbnailUrl".into(), Value::Null);
+    }
+    Some((id, RevealedFile { url, thumbnail_url }))
+}
+
+/// `value` の中の `files` 配列を辿ってセンシティブな添付の URL を外し、
+/// 外したものを `hidden` に積む。
+fn hide_sensitive(value: &mut Value, hidden: &mut Vec<(String, RevealedFile)>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, child) in obj.iter_mut() {
+                if key == "files" {
+                    if let Value::Array(files) = child {
+                        hidden.extend(files.iter_mut().filter_map(hide_file));
+                        continue;
+                    }
+                }
+                hide_sensitive(child, hidden);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                hide_sensitive(item, hidden);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub struct MediaGate {
+    enabled: AtomicBool,
+    hidden: Mutex<HiddenFiles>,
+}
+
+impl MediaGate {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            hidden: Mutex::new(HiddenFiles::default()),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// センシティブな添付があれば隠した値を返す。無ければ None (元の値を使う)。
+    fn gated<T: Serialize + DeserializeOwned>(&self, item: &T) -> Option<T> {
+        let mut value = serde_json::to_value(item).ok()?;
+        let mut hidden = Vec::new();
+        hide_sensitive(&mut value, &mut hidden);
+        if hidden.is_empty() {
+            return None;
+        }
+        let mut files = self.hidden.lock().unwrap();
+        for (id, file) in hidden {
+            files.remember(id, file);
+        }
+        drop(files);
+        match serde_json::from_value(value) {
+            Ok(gated) => Some(gated),
+            Err(e) => {
+                tracing::warn!("[media-gate] failed to rebuild gated value: {e}");
+                None
+            }
+        }
+    }
+
+    /// 有効ならセンシティブな添付の URL を外す。
+    pub fn gate<T: Serialize + DeserializeOwned>(&self, item: T) -> T {
+        if !self.enabled() {
+            return item;
+        }
+        self.gated(&item).unwrap_or(item)
+    }
+
+    pub fn gate_all<T: Serialize + DeserializeOwned>(&self, items: Vec<T>) -> Vec<T> {
+        if !self.enabled() {
+            return items;
+        }
+        items.into_iter().map(|item| self.gate(item)).collect()
+    }
+
+    fn gate_shared<T: Serialize + DeserializeOwned>(&self, item: &Arc<T>) -> Arc<T> {
+        self.gated(item.as_ref())
+            .map(Arc::new)
+            .unwrap_or_else(|| Arc::clone(item))
+    }
+
+    /// ストリーミングのノート / メンション / 通知をゲートに通す。QueryRuntime
+    /// へ取り込む前に通すので、クエリの差分にも本物の URL は載らない。
+    pub fn gate_event(&self, mut event: StreamEvent) -> StreamEvent {
+        if !self.enabled() {
+            return event;
+        }
+        match &mut event {
+            StreamEvent::Note(e) => e.note = self.gate_shared(&e.note),
+            StreamEvent::Mention(e) => e.note = self.gate_shared(&e.note),
+            StreamEvent::Notification(e) => {
+                if let Some(gated) = self.gated(&e.notification) {
+                    e.notification = gated;
+                }
+            }
+            _ => {}
+        }
+        event
+    }
+
+    fn reveal(&self, file_id: &str) -> Option<RevealedFile> {
+        self.hidden.lock().unwrap().by_id.get(file_id).cloned()
+    }
+}
+
+/// ゲートで隠した添付ファイルの本来の URL を返す。ユーザーがセンシティブな
+/// メディアを明示的に表示したときだけ呼ぶ。
+#[tauri::command]
+#[specta::specta]
+pub fn reveal_file(
+    gate: State<'_, MediaGate>,
+    file_id: String,
+) -> Result<RevealedFile, NoteDeckError> {
+    gate.reveal(&file_id)
+        .ok_or_else(|| NoteDeckError::InvalidInput(format!("Unknown hidden file: {file_id}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note() -> Value {
+        json!({
+            "id": "n1",
+            "files": [
+                { "id": "f1", "url": "https://x.example/f1.webp", "thumbnailUrl": "https://x.example/t1.webp", "isSensitive": true, "blurhash": "LKO2" },
+                { "id": "f2", "url": "https://x.example/f2.webp", "thumbnailUrl": null, "isSensitive": false }
+            ],
+            "renote": {
+                "id": "n0",
+                "files": [{ "id": "f0", "url": "https://x.example/f0.webp", "isSensitive": true }]
+            }
+        })
+    }
+
+    #[test]
+    fn hides_sensitive_files_including_nested_renotes() {
+        let mut value = note();
+        let mut hidden = Vec::new();
+        hide_sensitive(&mut value, &mut hidden);
+
+        let ids: Vec<_> = hidden.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["f1", "f0"]);
+        assert_eq!(value["files"][0]["url"], "");
+        assert_eq!(value["files"][0]["thumbnailUrl"], Value::Null);
+        assert_eq!(value["files"][0]["blurhash"], "LKO2");
+        assert_eq!(value["files"][1]["url"], "https://x.example/f2.webp");
+        assert_eq!(value["renote"]["files"][0]["url"], "");
+        assert!(value["renote"]["files"][0].get("thumbnailUrl").is_none());
+    }
+
+    #[test]
+    fn gate_remembers_urls_for_reveal() {
+        let gate = MediaGate::new(true);
+        let gated = gate.gate(note());
+        assert_eq!(gated["files"][0]["url"], "");
+        assert_eq!(
+            gate.reveal("f1"),
+            Some(RevealedFile {
+                url: "https://x.example/f1.webp".into(),
+                thumbnail_url: Some("https://x.example/t1.webp".into()),
+            })
+        );
+        assert_eq!(gate.reveal("f2"), None);
+    }
+
+    #[test]
+    fn disabled_gate_passes_values_through() {
+        let gate = MediaGate::new(false);
+        assert_eq!(gate.gate(note()), note());
+        assert_eq!(gate.reveal("f1"), None);
+    }
+
+    #[test]
+    fn forgets_oldest_urls_past_the_cap() {
+        let mut files = HiddenFiles::default();
+        for i in 0..=MAX_HIDDEN {
+            files.remember(
+                format!("f{i}"),
+                RevealedFile {
+                    url: format!("https://x.example/{i}"),
+                    thumbnail_url: None,
+                },
+            );
+        }
+        assert_eq!(files.by_id.len(), MAX_HIDDEN);
+        assert!(!files.by_id.contains_key("f0"));
+        assert!(files.by_id.contains_key(&format!("f{MAX_HIDDEN}")));
+    }
+}
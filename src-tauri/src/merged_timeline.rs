@@ -0,0 +1,456 @@
+//! 複数の (アカウント, タイムライン) を 1 本にまとめる merged タイムライン。
+//!
+//! 複数サーバーにアカウントを持つと、同じノートが連合経由で各サーバーに
+//! 別 id のコピーとして届く。ここでは ActivityPub の `uri` (ローカルノートは
+//! `https://{host}/notes/{id}`) を同一性の鍵にして 1 件に畳み、`createdAt` の
+//! 新しい順に並べる。
+//!
+//! - ページ取得 (`api_get_merged_timeline`): ソースごとに `api_get_timeline`
+//!   を並行に呼んで統合する。`untilId` はサーバーごとに意味が違うので、
+//!   カーソルはソースごとの `untilId` の列として往復させる。
+//! - ストリーミング (`merged_timeline_open`): ソースごとに通常の timeline
+//!   query を開き、flusher が drain した `QueryDelta` のうち配下 query の
+//!   挿入を重複除去して `MergedTimelineDelta` として emit する。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use notecli::error::NoteDeckError;
+use notecli::models::{NormalizedNote, TimelineOptions, TimelineType};
+use notecli::streaming::StreamingManager;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+use tauri_specta::Event;
+
+use crate::commands::{api_get_timeline, AppState, Result};
+use crate::query_runtime::{
+    query_close, query_subscribe_timeline, QueryDelta, QueryItem, QueryRuntime,
+};
+use crate::request_dedup::RequestDedup;
+
+/// 1 feed が覚えておく既出ノートの鍵の数。stream の重複は数分以内に
+/// 揃って届くので、直近分だけあれば足りる。
+const MAX_SEEN_KEYS: usize = 1000;
+const MAX_SOURCES: usize = 16;
+const DEFAULT_PAGE_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedSource {
+    pub account_id: String,
+    pub timeline_type: TimelineType,
+    pub list_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedTimelinePage {
+    /// 重複除去済み、新しい順
+    pub notes: Vec<NormalizedNote>,
+    /// 次ページ用のソースごとの `untilId` (`sources` と同じ並び)
+    pub cursors: Vec<Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedTimelineSnapshot {
+    pub merged_id: String,
+    /// 配下の timeline query (ソースと同じ並び)
+    pub query_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedTimelineDelta {
+    pub merged_id: String,
+    /// 既出のコピーを除いた新着 (届いた順)
+    pub inserts: Vec<Arc<NormalizedNote>>,
+    /// 配下 query で削除されたノート id (どのサーバーのコピーかは問わない)
+    pub deletes: Vec<String>,
+    /// 配下 query のどれかで stream バッファが溢れた (`QueryDelta::gap`)
+    pub gap: bool,
+}
+
+/// 連合コピーを同一視する鍵。
+pub fn note_key(note: &NormalizedNote) -> String {
+    match note.uri.as_deref() {
+        Some(uri) if !uri.is_empty() => uri.to_string(),
+        _ => format!("https://{}/notes/{}", note.server_host, note.id),
+    }
+}
+
+/// ソースごとのページを 1 本に統合する。同時刻はソースの並び順を優先し、
+/// 同じノートは先に現れたコピーを残す。カーソルは採用範囲 (最後に採用した
+/// ノートの時刻以降) で消費したソース内最古の id に進める。
+pub fn merge_pages(
+    pages: Vec<Vec<NormalizedNote>>,
+    cursors: &[Option<String>],
+    limit: usize,
+) -> MergedTimelinePage {
+    let mut next_cursors: Vec<Option<String>> = (0..pages.len())
+        .map(|i| cursors.get(i).cloned().flatten())
+        .collect();
+    let mut all: Vec<(usize, NormalizedNote)> = pages
+        .into_iter()
+        .enumerate()
+        .flat_map(|(source, notes)| notes.into_iter().map(move |note| (source, note)))
+        .collect();
+    // ISO 8601 (UTC, ミリ秒固定) なので文字列比較で時系列になる。stable sort
+    all.sort_by(|(_, a), (_, b)| b.created_at.cmp(&a.created_at));
+
+    let mut seen = HashSet::new();
+    let mut notes = Vec::new();
+    let mut cutoff: Option<String> = None;
+    for (source, note) in all {
+        if let Some(cutoff) = &cutoff {
+            if note.created_at < *cutoff {
+                break;
+            }
+        }
+        next_cursors[source] = Some(note.id.clone());
+        if !seen.insert(note_key(&note)) {
+            continue;
+        }
+        notes.push(note);
+        if notes.len() == limit {
+            cutoff = notes.last().map(|n| n.created_at.clone());
+        }
+    }
+    MergedTimelinePage {
+        notes,
+        cursors: next_cursors,
+    }
+}
+
+#[derive(Debug, Default)]
+struct SeenKeys {
+    order: VecDeque<String>,
+    set: HashSet<String>,
+}
+
+impl SeenKeys {
+    /// 初見なら記録して true。
+    fn insert(&mut self, key: String) -> bool {
+        if self.set.contains(&key) {
+            return false;
+        }
+        if self.order.len() >= MAX_SEEN_KEYS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+        self.set.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+
+#[derive(Debug)]
+struct Feed {
+    query_ids: Vec<String>,
+    seen: SeenKeys,
+}
+
+#[derive(Default)]
+pub struct MergedTimelines {
+    feeds: Mutex<HashMap<String, Feed>>,
+}
+
+impl MergedTimelines {
+    fn register(&self, query_ids: Vec<String>) -> Result<String> {
+        let merged_id = format!("m:{}", uuid::Uuid::new_v4());
+        self.lock()?.insert(
+            merged_id.clone(),
+            Feed {
+                query_ids,
+                seen: SeenKeys::default(),
+            },
+        );
+        Ok(merged_id)
+    }
+
+    fn unregister(&self, merged_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .lock()?
+            .remove(merged_id)
+            .map(|feed| feed.query_ids)
+            .unwrap_or_default())
+    }
+
+    /// ページ取得で表示したノートを既出にする (後から届く別サーバーの
+    /// コピーを stream 側で落とすため)。
+    fn mark_seen(&self, merged_id: &str, notes: &[NormalizedNote]) {
+        let Ok(mut feeds) = self.lock() else {
+            return;
+        };
+        if let Some(feed) = feeds.get_mut(merged_id) {
+            for note in notes {
+                feed.seen.insert(note_key(note));
+            }
+        }
+    }
+
+    /// drain した query delta を merged feed ごとに振り分ける。
+    pub fn route(&self, deltas: &[QueryDelta]) -> Vec<MergedTimelineDelta> {
+        let Ok(mut feeds) = self.lock() else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        for (merged_id, feed) in feeds.iter_mut() {
+            let mut inserts = Vec::new();
+            let mut deletes = Vec::new();
+            let mut gap = false;
+            for delta in deltas
+                .iter()
+                .filter(|d| feed.query_ids.contains(&d.query_id))
+            {
+                for item in &delta.inserts {
+                    if let QueryItem::Note(note) = item {
+                        if feed.seen.insert(note_key(note)) {
+                            inserts.push(note.clone());
+                        }
+                    }
+                }
+                deletes.extend(delta.deletes.iter().cloned());
+                gap |= delta.gap;
+            }
+            if !inserts.is_empty() || !deletes.is_empty() || gap {
+                out.push(MergedTimelineDelta {
+                    merged_id: merged_id.clone(),
+                    inserts,
+                    deletes,
+                    gap,
+                });
+            }
+        }
+        out
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Feed>>> {
+        self.feeds
+            .lock()
+            .map_err(|_| NoteDeckError::InvalidInput("merged timeline lock poisoned".into()))
+    }
+}
+
+fn validate_sources(sources: &[MergedSource]) -> Result<()> {
+    if sources.is_empty() || sources.len() > MAX_SOURCES {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Merged timeline needs 1-{MAX_SOURCES} sources"
+        )));
+    }
+    Ok(())
+}
+
+/// 複数ソースのタイムラインを 1 ページ分統合して返す。`cursors` は前回の
+/// 応答の `cursors` (初回は空)。`merged_id` を渡すと結果をその feed の
+/// 既出扱いにする。
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_merged_timeline(
+    app: AppHandle,
+    merged: State<'_, MergedTimelines>,
+    sources: Vec<MergedSource>,
+    cursors: Vec<Option<String>>,
+    limit: Option<u32>,
+    merged_id: Option<String>,
+) -> Result<MergedTimelinePage> {
+    validate_sources(&sources)?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, 100);
+    let fetches = sources.iter().enumerate().map(|(i, source)| {
+        let app = app.clone();
+        let options = TimelineOptions {
+            limit: Some(limit.into()),
+            until_id: cursors.get(i).cloned().flatten(),
+            list_id: source.list_id.clone(),
+            ..TimelineOptions::default()
+        };
+        async move {
+            api_get_timeline(
+                app.clone(),
+                app.state::<AppState>(),
+                app.state::<RequestDedup>(),
+                source.account_id.clone(),
+                source.timeline_type.clone(),
+                Some(options),
+            )
+            .await
+        }
+    });
+    let results = futures_util::future::join_all(fetches).await;
+
+    // 1 サーバーが落ちていても残りで続ける。全滅なら最初のエラーを返す
+    if results.iter().all(|r| r.is_err()) {
+        if let Some(Err(e)) = results.into_iter().next() {
+            return Err(e);
+        }
+        return Ok(MergedTimelinePage {
+            notes: Vec::new(),
+            cursors,
+        });
+    }
+    let pages = results
+        .into_iter()
+        .zip(&sources)
+        .map(|(result, source)| {
+            result.unwrap_or_else(|e| {
+                tracing::warn!(account_id = %source.account_id, "[merged-timeline] source failed: {e}");
+                Vec::new()
+            })
+        })
+        .collect();
+    let page = merge_pages(pages, &cursors, limit as usize);
+    if let Some(merged_id) = merged_id.as_deref() {
+        merged.mark_seen(merged_id, &page.notes);
+    }
+    Ok(page)
+}
+
+/// ソースごとの timeline query を開いて merged feed を作る。以降の新着は
+/// `MergedTimelineDelta` で届く。
+#[tauri::command]
+#[specta::specta]
+pub async fn merged_timeline_open(
+    app_state: State<'_, AppState>,
+    streaming: State<'_, StreamingManager>,
+    runtime: State<'_, QueryRuntime>,
+    merged: State<'_, MergedTimelines>,
+    sources: Vec<MergedSource>,
+) -> Result<MergedTimelineSnapshot> {
+    validate_sources(&sources)?;
+    let mut query_ids = Vec::with_capacity(sources.len());
+    for source in sources {
+        let opened = query_subscribe_timeline(
+            app_state.clone(),
+            streaming.clone(),
+            runtime.clone(),
+            source.account_id,
+            source.timeline_type,
+            source.list_id,
+        )
+        .await;
+        match opened {
+            Ok(snapshot) => query_ids.push(snapshot.query_id),
+            Err(e) => {
+                // 途中まで開いた query を戻す
+                for query_id in query_ids {
+                    let _ = query_close(streaming.clone(), runtime.clone(), query_id).await;
+                }
+                return Err(e);
+            }
+        }
+    }
+    let merged_id = merged.register(query_ids.clone())?;
+    Ok(MergedTimelineSnapshot {
+        merged_id,
+        query_ids,
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn merged_timeline_close(
+    streaming: State<'_, StreamingManager>,
+    runtime: State<'_, QueryRuntime>,
+    merged: State<'_, MergedTimelines>,
+    merged_id: String,
+) -> Result<()> {
+    for query_id in merged.unregister(&merged_id)? {
+        query_close(streaming.clone(), runtime.clone(), query_id).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note(id: &str, host: &str, created_at: &str, uri: Option<&str>) -> NormalizedNote {
+        serde_json::from_value(json!({
+            "id": id,
+            "_accountId": "acct-1",
+            "_serverHost": host,
+            "createdAt": created_at,
+            "user": { "id": "u1", "username": "alice" },
+            "visibility": "public",
+            "renoteCount": 0,
+            "repliesCount": 0,
+            "uri": uri,
+        }))
+        .expect("test note fixture should deserialize")
+    }
+
+    #[test]
+    fn federated_copies_collapse_to_first_source() {
+        let origin = note("a1", "a.example", "2026-01-01T00:00:02.000Z", None);
+        let copy = note(
+            "b1",
+            "b.example",
+            "2026-01-01T00:00:02.000Z",
+            Some("https://a.example/notes/a1"),
+        );
+        let other = note("b2", "b.example", "2026-01-01T00:00:01.000Z", None);
+        let page = merge_pages(vec![vec![origin], vec![copy, other]], &[], 10);
+        let ids: Vec<_> = page.notes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a1", "b2"]);
+        assert_eq!(page.cursors, vec![Some("a1".into()), Some("b2".into())]);
+    }
+
+    #[test]
+    fn cursors_stop_at_the_last_merged_note() {
+        let a = vec![
+            note("a3", "a.example", "2026-01-01T00:00:06.000Z", None),
+            note("a2", "a.example", "2026-01-01T00:00:04.000Z", None),
+            note("a1", "a.example", "2026-01-01T00:00:02.000Z", None),
+        ];
+        let b = vec![
+            note("b2", "b.example", "2026-01-01T00:00:05.000Z", None),
+            note("b1", "b.example", "2026-01-01T00:00:01.000Z", None),
+        ];
+        let page = merge_pages(vec![a, b], &[None, Some("b3".into())], 3);
+        let ids: Vec<_> = page.notes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a3", "b2", "a2"]);
+        assert_eq!(page.cursors, vec![Some("a2".into()), Some("b2".into())]);
+    }
+
+    #[test]
+    fn route_dedupes_stream_copies_per_feed() {
+        let merged = MergedTimelines::default();
+        let id = merged
+            .register(vec!["q:a".into(), "q:b".into()])
+            .expect("register");
+        let delta = |query_id: &str, note: NormalizedNote| QueryDelta {
+            query_id: query_id.into(),
+            revision: 1,
+            inserts: vec![QueryItem::Note(Arc::new(note))],
+            deletes: Vec::new(),
+            updates: Vec::new(),
+            gap: false,
+        };
+        let out = merged.route(&[
+            delta(
+                "q:a",
+                note("a1", "a.example", "2026-01-01T00:00:00.000Z", None),
+            ),
+            delta(
+                "q:b",
+                note(
+                    "b1",
+                    "b.example",
+                    "2026-01-01T00:00:00.000Z",
+                    Some("https://a.example/notes/a1"),
+                ),
+            ),
+            delta(
+                "q:other",
+                note("c1", "c.example", "2026-01-01T00:00:00.000Z", None),
+            ),
+        ]);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].merged_id, id);
+        assert_eq!(out[0].inserts.len(), 1);
+        assert_eq!(out[0].inserts[0].id, "a1");
+    }
+}
@@ -0,0 +1,199 @@
+//! MFM からプレーンテキスト / Markdown を取り出す。
+//!
+//! トレイのメニュー項目やノートの共有 (`note_share.rs`) など、MFM を描画
+//! できない場所向けの簡易変換で、フロントの mfmParser のような完全な構文
+//! 解析はしない。
+//! - `$[fn.args 本文]` → 本文 (入れ子可)
+//! - `[ラベル](url)` / `?[ラベル](url)` → ラベル (Markdown ではリンクのまま)
+//! - `**` `~~` と `<small>` `<center>` `<plain>` などのタグは除去
+//!   (Markdown では `**` `~~` を残し、`<b>` `<i>` `<s>` を記号に置き換える)
+//! - `to_plain_text` は改行を含む連続空白を 1 つの空白にまとめる
+//!
+//! `:emoji:` とインラインコードはそのまま残す。
+
+/// 除去するインラインタグ。
+const TAGS: &[&str] = &[
+    "<plain>",
+    "</plain>",
+    "<small>",
+    "</small>",
+    "<center>",
+    "</center>",
+    "<i>",
+    "</i>",
+    "<b>",
+    "</b>",
+    "<s>",
+    "</s>",
+];
+
+/// 除去する強調記号。
+const MARKERS: &[&str] = &["**", "~~"];
+
+/// Markdown で記号に置き換えるタグ。
+const MARKDOWN_TAGS: &[(&str, &str)] = &[
+    ("<b>", "**"),
+    ("</b>", "**"),
+    ("<i>", "*"),
+    ("</i>", "*"),
+    ("<s>", "~~"),
+    ("</s>", "~~"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Markdown,
+}
+
+/// `[label](https://...)` を読み、(ラベル, URL, 残り) を返す。
+fn parse_link(s: &str) -> Option<(&str, &str, &str)> {
+    let s = s.strip_prefix('?').unwrap_or(s).strip_prefix('[')?;
+    let label_end = s.find("](")?;
+    let label = &s[..label_end];
+    if label.contains(['\n', '[', ']']) {
+        return None;
+    }
+    let url = &s[label_end + 2..];
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return None;
+    }
+    let url_end = url.find([')', '\n', ' '])?;
+    if !url[url_end..].starts_with(')') {
+        return None;
+    }
+    Some((label, &url[..url_end], &url[url_end + 1..]))
+}
+
+fn convert(text: &str, style: Style) -> String {
+    let mut out = String::with_capacity(text.len());
+    // 閉じていない `$[` の数。対応する `]` を捨てる
+    let mut fn_depth = 0usize;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("$[") {
+            // 関数名と引数 (`x2` / `fg.color=f00`) を読み飛ばす
+            let name_end = after.find(char::is_whitespace).unwrap_or(after.len());
+            let body = &after[name_end..];
+            rest = body
+                .strip_prefix(|c: char| c.is_whitespace())
+                .unwrap_or(body);
+            fn_depth += 1;
+            continue;
+        }
+        if c == ']' && fn_depth > 0 {
+            fn_depth -= 1;
+            rest = &rest[1..];
+            continue;
+        }
+        if c == '[' || c == '?' {
+            if let Some((label, url, after)) = parse_link(rest) {
+                match style {
+                    Style::Plain => out.push_str(label),
+                    Style::Markdown => out.push_str(&format!("[{label}]({url})")),
+                }
+                rest = after;
+                continue;
+            }
+        }
+        if style == Style::Markdown {
+            if let Some((tag, marker)) = MARKDOWN_TAGS.iter().find(|(t, _)| rest.starts_with(*t)) {
+                out.push_str(marker);
+                rest = &rest[tag.len()..];
+                continue;
+            }
+            if let Some(token) = MARKERS.iter().find(|t| rest.starts_with(**t)) {
+                out.push_str(token);
+                rest = &rest[token.len()..];
+                continue;
+            }
+        }
+        if let Some(token) = TAGS.iter().chain(MARKERS).find(|t| rest.starts_with(**t)) {
+            rest = &rest[token.len()..];
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// 各行の末尾の空白と、前後の空行を落とす。
+fn trim_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// MFM テキストを 1 行のプレーンテキストにする。
+pub fn to_plain_text(text: &str) -> String {
+    convert(text, Style::Plain)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// MFM テキストを改行を保ったプレーンテキストにする。
+pub fn to_plain_lines(text: &str) -> String {
+    trim_lines(&convert(text, Style::Plain))
+}
+
+/// MFM テキストを Markdown にする。
+pub fn to_markdown(text: &str) -> String {
+    trim_lines(&convert(text, Style::Markdown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_functions_and_decorations() {
+        assert_eq!(to_plain_text("$[x2 **大きい**] 文字"), "大きい 文字");
+        assert_eq!(
+            to_plain_text("$[fg.color=f00 $[spin 回る]]と<small>小さい</small>"),
+            "回ると小さい"
+        );
+        assert_eq!(
+            to_plain_text("~~取り消し~~ :blobcat:"),
+            "取り消し :blobcat:"
+        );
+    }
+
+    #[test]
+    fn keeps_link_labels() {
+        assert_eq!(
+            to_plain_text("詳細は[こちら](https://example.com/a)へ"),
+            "詳細はこちらへ"
+        );
+        assert_eq!(to_plain_text("?[隠し](https://example.com)"), "隠し");
+        // URL でなければリンクではない
+        assert_eq!(to_plain_text("[a](b)"), "[a](b)");
+    }
+
+    #[test]
+    fn plain_lines_keep_line_breaks() {
+        assert_eq!(
+            to_plain_lines("$[x2 一行目]  \n\n<small>二行目</small>\n"),
+            "一行目\n\n二行目"
+        );
+    }
+
+    #[test]
+    fn markdown_keeps_links_and_emphasis() {
+        assert_eq!(
+            to_markdown("<b>太字</b>と<i>斜体</i>と~~取り消し~~"),
+            "**太字**と*斜体*と~~取り消し~~"
+        );
+        assert_eq!(
+            to_markdown("?[詳細](https://example.com/a) $[spin 回る]"),
+            "[詳細](https://example.com/a) 回る"
+        );
+        assert_eq!(to_markdown("<center>中央</center>"), "中央");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        assert_eq!(to_plain_text("  一行目\n\n二行目\t "), "一行目 二行目");
+        assert_eq!(to_plain_text(""), "");
+    }
+}
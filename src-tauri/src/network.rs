@@ -0,0 +1,259 @@
+//! ネットワーク接続状態の監視。
+//!
+//! オフライン中も notecli の再接続ループは WebSocket の接続を試み続ける。
+//! 回線断を検知したら realtime のストリームを長間隔 polling に退避させて
+//! (power::park_streams) 再接続の嵐を止め、復旧したら作り直して
+//! (power::reestablish_streams) `nd:network-changed` でフロントに catch-up を促す。
+//!
+//! 判定は 2 段階:
+//! 1. 経路: UDP socket を connect (パケットは送らない) して既定経路の送信元
+//!    アドレスを得る。経路が無ければ即オフライン。送信元が変わったら
+//!    インターフェース切替 (Wi-Fi 乗り換え等) とみなして 2 を行う
+//! 2. 到達性: 登録済みアカウントのサーバーに `api/ping` を投げる。キャプティブ
+//!    ポータル配下では TLS が通らないので失敗する。外部の疎通確認サービスは
+//!    使わない (PRIVACY.md の通信先を増やさない)
+//!
+//! オンライン中は 1 だけを定期確認し、2 は経路変化時とオフライン中のみ行う。
+
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+/// フロント (useDeckResume) が listen するイベント名。
+#[cfg_attr(mobile, allow(dead_code))]
+pub const NETWORK_CHANGED_EVENT: &str = "nd:network-changed";
+
+/// オンライン中 / オフライン中の確認間隔。
+#[cfg_attr(mobile, allow(dead_code))]
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+#[cfg_attr(mobile, allow(dead_code))]
+const OFFLINE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// 到達性確認 1 件あたりのタイムアウト。
+#[cfg_attr(mobile, allow(dead_code))]
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 到達性確認に使うサーバー数の上限。
+#[cfg_attr(mobile, allow(dead_code))]
+const MAX_PROBE_HOSTS: usize = 3;
+
+/// 経路確認の宛先 (TEST-NET-1)。UDP の connect は経路表を引くだけで
+/// パケットを送らないので、実在しないアドレスでよい。
+#[cfg_attr(mobile, allow(dead_code))]
+const ROUTE_PROBE_ADDR: &str = "192.0.2.1:9";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStatus {
+    /// オンラインか。起動直後の未確認時は null。
+    pub online: Option<bool>,
+}
+
+#[derive(Default)]
+pub struct NetworkMonitor {
+    online: Mutex<Option<bool>>,
+    route: Mutex<Option<IpAddr>>,
+}
+
+impl NetworkMonitor {
+    fn status(&self) -> NetworkStatus {
+        NetworkStatus {
+            online: *self.online.lock().unwrap(),
+        }
+    }
+
+    /// 経路を記録し、到達性確認が必要か返す (経路変化 / オフライン中 / 未確認)。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn needs_probe(&self, route: Option<IpAddr>) -> bool {
+        let mut prev = self.route.lock().unwrap();
+        let changed = *prev != route;
+        *prev = route;
+        changed || *self.online.lock().unwrap() != Some(true)
+    }
+
+    /// 判定結果を反映し、状態が変わったときだけ新しい値を返す。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn apply(&self, online: bool) -> Option<bool> {
+        let mut current = self.online.lock().unwrap();
+        let prev = current.replace(online);
+        // 起動直後の初回確認でオンラインなら通知しない (何も変わっていない)
+        match prev {
+            Some(p) if p == online => None,
+            None if online => None,
+            _ => Some(online),
+        }
+    }
+}
+
+/// 経路と到達性確認の結果から判定する。経路が無ければオフライン。
+/// 確認できるサーバーが無い (未ログイン) 場合は経路の有無だけで判断する。
+#[cfg_attr(mobile, allow(dead_code))]
+fn evaluate(route: Option<IpAddr>, probe: Option<bool>) -> bool {
+    route.is_some() && probe.unwrap_or(true)
+}
+
+/// 既定経路の送信元アドレス。経路が無ければ None。
+#[cfg_attr(mobile, allow(dead_code))]
+fn default_route() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect(ROUTE_PROBE_ADDR).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+/// 登録済みサーバーへの到達性。どれか 1 つに届けばオンライン。
+/// 確認できるサーバーが無ければ None。
+#[cfg(not(mobile))]
+async fn probe_servers(app: &tauri::AppHandle) -> Option<bool> {
+    use tauri::Manager;
+
+    let app_state = app.try_state::<crate::commands::AppState>()?;
+    let http = app.try_state::<reqwest::Client>()?;
+    let db = app_state.db().await;
+    let mut hosts: Vec<String> = db
+        .load_accounts()
+        .ok()?
+        .into_iter()
+        .map(|a| a.host)
+        .collect();
+    hosts.sort();
+    hosts.dedup();
+    hosts.truncate(MAX_PROBE_HOSTS);
+    if hosts.is_empty() {
+        return None;
+    }
+    for host in hosts {
+        let reachable = http
+            .post(format!("https://{host}/api/ping"))
+            .json(&serde_json::json!({}))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .is_ok_and(|resp| resp.status().is_success());
+        if reachable {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
+/// 1 回分の確認。force なら経路が変わっていなくても到達性を確認する。
+#[cfg(not(mobile))]
+async fn check(app: &tauri::AppHandle, force: bool) {
+    use tauri::{Emitter, Manager};
+
+    let Some(monitor) = app.try_state::<NetworkMonitor>() else {
+        return;
+    };
+    let route = default_route();
+    let probe = if route.is_some() && (monitor.needs_probe(route) || force) {
+        probe_servers(app).await
+    } else {
+        None
+    };
+    let online = evaluate(route, probe);
+    let Some(online) = monitor.apply(online) else {
+        return;
+    };
+
+    tracing::info!(online, "[network] connectivity changed");
+    if online {
+        crate::power::reestablish_streams(app).await;
+    } else {
+        crate::power::park_streams(app).await;
+    }
+    if let Err(e) = app.emit(NETWORK_CHANGED_EVENT, monitor.status()) {
+        tracing::warn!("[network] emit failed: {e}");
+    }
+}
+
+/// 接続状態の監視 task を起動する。setup から 1 度だけ呼ぶ。
+#[cfg(not(mobile))]
+pub fn spawn_watcher(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            check(&app, false).await;
+            let online = app
+                .try_state::<NetworkMonitor>()
+                .and_then(|m| m.status().online)
+                .unwrap_or(true);
+            let wait = if online {
+                ONLINE_CHECK_INTERVAL
+            } else {
+                OFFLINE_CHECK_INTERVAL
+            };
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+/// 現在の接続状態を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn network_get_status(monitor: State<'_, NetworkMonitor>) -> NetworkStatus {
+    monitor.status()
+}
+
+/// 到達性をすぐに確認する (フロントからの手動再確認用)。
+#[tauri::command]
+#[specta::specta]
+pub async fn network_check_now(
+    app: tauri::AppHandle,
+    monitor: State<'_, NetworkMonitor>,
+) -> Result<NetworkStatus, notecli::error::NoteDeckError> {
+    #[cfg(not(mobile))]
+    check(&app, true).await;
+    #[cfg(mobile)]
+    let _ = app;
+    Ok(monitor.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn evaluate_requires_route_and_reachability() {
+        assert!(!evaluate(None, None));
+        assert!(!evaluate(ip("192.168.1.2"), Some(false)));
+        assert!(evaluate(ip("192.168.1.2"), Some(true)));
+        // 確認できるサーバーが無ければ経路だけで判断
+        assert!(evaluate(ip("192.168.1.2"), None));
+    }
+
+    #[test]
+    fn apply_reports_transitions_only() {
+        let monitor = NetworkMonitor::default();
+        // 起動直後のオンライン確認は変化扱いしない
+        assert_eq!(monitor.apply(true), None);
+        assert_eq!(monitor.apply(true), None);
+        assert_eq!(monitor.apply(false), Some(false));
+        assert_eq!(monitor.apply(false), None);
+        assert_eq!(monitor.apply(true), Some(true));
+
+        // 起動直後からオフラインなら通知する
+        let monitor = NetworkMonitor::default();
+        assert_eq!(monitor.apply(false), Some(false));
+    }
+
+    #[test]
+    fn probes_on_route_change_or_while_offline() {
+        let monitor = NetworkMonitor::default();
+        // 未確認
+        assert!(monitor.needs_probe(ip("10.0.0.2")));
+        monitor.apply(true);
+        assert!(!monitor.needs_probe(ip("10.0.0.2")));
+        // Wi-Fi 乗り換えで送信元が変わった
+        assert!(monitor.needs_probe(ip("192.168.0.5")));
+        monitor.apply(false);
+        assert!(monitor.needs_probe(ip("192.168.0.5")));
+    }
+}
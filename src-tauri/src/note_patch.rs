@@ -0,0 +1,315 @@
+//! Note Capture 更新のノート単位パッチへの畳み込み。
+//!
+//! subNote 経由の `reacted` / `unreacted` / `pollVoted` を 1 件ずつ流すと、
+//! 人気ノートではフロントがイベントごとにノートを複製して GC が嵩む。
+//! flusher の 1 window (DELTA_FLUSH_WINDOW) に溜まった更新をノートごとに
+//! 集計し、リアクション数の増減・自分のリアクション・投票数の増減だけを持つ
+//! `NotePatch` にして 1 件で渡す。
+//!
+//! 同じ更新が channel の auto-capture と subNote の両経路から届くため、
+//! `UpdateSigs` で短い窓の重複を ingest 時点で落とす (JS 側
+//! `createUpdateDeduper` と同じ規則: ノートごとに直前の sig と比較)。
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use notecli::models::NoteUpdateBody;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::query_runtime::NoteCapture;
+
+/// 重複とみなす窓 (JS 側 noteStore の deduper と同じ)。
+const DEDUP_WINDOW: Duration = Duration::from_millis(1500);
+/// この件数を超えたら期限切れの sig を掃除する。
+const SIG_PRUNE_THRESHOLD: usize = 1024;
+
+/// 自分のリアクションの最終状態。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MyReactionChange {
+    Set { reaction: String },
+    Cleared,
+}
+
+/// 1 ノート分の集計済み更新。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NotePatch {
+    pub account_id: String,
+    pub note_id: String,
+    /// 他ユーザーのリアクションによる件数の増減 (差し引き 0 のキーは含めない)
+    #[serde(default)]
+    pub reactions: BTreeMap<String, i32>,
+    /// shortcode (コロン無し) → 絵文字 URL
+    #[serde(default)]
+    pub reaction_emojis: BTreeMap<String, String>,
+    /// 自分のリアクションが変わったときだけ Some
+    #[serde(default)]
+    pub my_reaction: Option<MyReactionChange>,
+    /// choice index → 増えた票数 (自分の票を含む)
+    #[serde(default)]
+    pub poll_votes: BTreeMap<u32, u32>,
+    /// 自分が投票した choice
+    #[serde(default)]
+    pub my_poll_choices: Vec<u32>,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl NotePatch {
+    fn is_empty(&self) -> bool {
+        !self.deleted
+            && self.reactions.is_empty()
+            && self.reaction_emojis.is_empty()
+            && self.my_reaction.is_none()
+            && self.poll_votes.is_empty()
+            && self.my_poll_choices.is_empty()
+    }
+}
+
+fn is_self(user_id: Option<&String>, self_user_id: Option<&String>) -> bool {
+    matches!((user_id, self_user_id), (Some(a), Some(b)) if a == b)
+}
+
+/// `ReactionEmoji` は `{ name, url }` と bare string の揺れがあるので
+/// ワイヤ形から URL を拾う。
+fn emoji_url<T: Serialize>(emoji: &T) -> Option<String> {
+    let value = serde_json::to_value(emoji).ok()?;
+    value
+        .get("url")
+        .and_then(|url| url.as_str())
+        .or_else(|| value.as_str())
+        .map(str::to_string)
+}
+
+/// 溜まった capture をノート単位のパッチに畳む。初出順を保つ。
+/// `self_user_ids` は account_id → 自分の user id。
+pub fn fold(captures: Vec<NoteCapture>, self_user_ids: &HashMap<String, String>) -> Vec<NotePatch> {
+    let mut order: Vec<(String, String)> = Vec::new();
+    let mut patches: HashMap<(String, String), NotePatch> = HashMap::new();
+
+    for capture in captures {
+        let key = (capture.account_id, capture.note_id);
+        let me = self_user_ids.get(&key.0);
+        let patch = patches.entry(key.clone()).or_insert_with(|| {
+            order.push(key.clone());
+            NotePatch {
+                account_id: key.0.clone(),
+                note_id: key.1.clone(),
+                ..NotePatch::default()
+            }
+        });
+        match capture.update {
+            NoteUpdateBody::Reacted(body) => {
+                if let Some(url) = body.emoji.as_ref().and_then(emoji_url) {
+                    let shortcode = body.reaction.trim_matches(':').to_string();
+                    patch.reaction_emojis.insert(shortcode, url);
+                }
+                if is_self(body.user_id.as_ref(), me) {
+                    patch.my_reaction = Some(MyReactionChange::Set {
+                        reaction: body.reaction,
+                    });
+                } else {
+                    *patch.reactions.entry(body.reaction).or_default() += 1;
+                }
+            }
+            NoteUpdateBody::Unreacted(body) => {
+                if is_self(body.user_id.as_ref(), me) {
+                    patch.my_reaction = Some(MyReactionChange::Cleared);
+                } else {
+                    *patch.reactions.entry(body.reaction).or_default() -= 1;
+                }
+            }
+            NoteUpdateBody::PollVoted(body) => {
+                let Some(choice) = u32::try_from(body.choice).ok() else {
+                    continue;
+                };
+                *patch.poll_votes.entry(choice).or_default() += 1;
+                if is_self(body.user_id.as_ref(), me) && !patch.my_poll_choices.contains(&choice) {
+                    patch.my_poll_choices.push(choice);
+                }
+            }
+            NoteUpdateBody::Deleted(_) => patch.deleted = true,
+        }
+    }
+
+    order
+        .into_iter()
+        .filter_map(|key| patches.remove(&key))
+        .map(|mut patch| {
+            patch.reactions.retain(|_, delta| *delta != 0);
+            patch
+        })
+        .filter(|patch| !patch.is_empty())
+        .collect()
+}
+
+/// JS 側 `noteUpdateSig` と同じ組み立て (type × userId × reaction × choice)。
+/// 削除は冪等なので dedup 対象外 (None)。
+fn update_sig(update: &NoteUpdateBody) -> Option<String> {
+    match update {
+        NoteUpdateBody::Reacted(b) => Some(format!(
+            "reacted{}{}",
+            b.user_id.as_deref().unwrap_or(""),
+            b.reaction
+        )),
+        NoteUpdateBody::Unreacted(b) => Some(format!(
+            "unreacted{}{}",
+            b.user_id.as_deref().unwrap_or(""),
+            b.reaction
+        )),
+        NoteUpdateBody::PollVoted(b) => Some(format!(
+            "pollVoted{}{}",
+            b.user_id.as_deref().unwrap_or(""),
+            b.choice
+        )),
+        NoteUpdateBody::Deleted(_) => None,
+    }
+}
+
+/// ノートごとの直前の更新 sig。channel と capture の二重配信を弾く。
+#[derive(Debug, Default)]
+pub struct UpdateSigs {
+    recent: HashMap<String, (String, Instant)>,
+}
+
+impl UpdateSigs {
+    /// 初めて見る更新なら記録して true、窓内の重複なら false。
+    pub fn accept(&mut self, note_id: &str, update: &NoteUpdateBody) -> bool {
+        let Some(sig) = update_sig(update) else {
+            return true;
+        };
+        let now = Instant::now();
+        if self.recent.len() > SIG_PRUNE_THRESHOLD {
+            self.recent
+                .retain(|_, (_, at)| now.duration_since(*at) < DEDUP_WINDOW);
+        }
+        let duplicate = self
+            .recent
+            .get(note_id)
+            .is_some_and(|(last, at)| *last == sig && now.duration_since(*at) < DEDUP_WINDOW);
+        if duplicate {
+            return false;
+        }
+        self.recent.insert(note_id.to_string(), (sig, now));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notecli::models::{NotePollVotedBody, NoteReactedBody, NoteUnreactedBody};
+
+    fn capture(note_id: &str, update: NoteUpdateBody) -> NoteCapture {
+        NoteCapture {
+            account_id: "acct-1".into(),
+            note_id: note_id.into(),
+            update,
+        }
+    }
+
+    fn reacted(user: &str, reaction: &str) -> NoteUpdateBody {
+        NoteUpdateBody::Reacted(NoteReactedBody {
+            reaction: reaction.into(),
+            emoji: None,
+            user_id: Some(user.into()),
+        })
+    }
+
+    fn unreacted(user: &str, reaction: &str) -> NoteUpdateBody {
+        NoteUpdateBody::Unreacted(NoteUnreactedBody {
+            reaction: reaction.into(),
+            user_id: Some(user.into()),
+        })
+    }
+
+    fn self_ids() -> HashMap<String, String> {
+        HashMap::from([("acct-1".to_string(), "me".to_string())])
+    }
+
+    #[test]
+    fn reactions_are_summed_per_note() {
+        let patches = fold(
+            vec![
+                capture("n1", reacted("u1", ":+1:")),
+                capture("n2", reacted("u1", ":+1:")),
+                capture("n1", reacted("u2", ":+1:")),
+                capture("n1", reacted("u3", "❤")),
+                capture("n1", unreacted("u3", "❤")),
+            ],
+            &self_ids(),
+        );
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].note_id, "n1");
+        assert_eq!(
+            patches[0].reactions,
+            BTreeMap::from([(":+1:".to_string(), 2)])
+        );
+        assert_eq!(patches[1].note_id, "n2");
+    }
+
+    #[test]
+    fn own_reactions_only_move_my_reaction() {
+        let patches = fold(
+            vec![
+                capture("n1", reacted("me", ":+1:")),
+                capture("n1", unreacted("me", ":+1:")),
+                capture("n1", reacted("me", "❤")),
+            ],
+            &self_ids(),
+        );
+        assert_eq!(patches.len(), 1);
+        assert!(patches[0].reactions.is_empty());
+        assert_eq!(
+            patches[0].my_reaction,
+            Some(MyReactionChange::Set {
+                reaction: "❤".into()
+            })
+        );
+    }
+
+    #[test]
+    fn cancelled_out_updates_produce_no_patch() {
+        let patches = fold(
+            vec![
+                capture("n1", reacted("u1", ":+1:")),
+                capture("n1", unreacted("u1", ":+1:")),
+            ],
+            &self_ids(),
+        );
+        assert!(patches.is_empty());
+    }
+
+    #[test]
+    fn poll_votes_are_counted_and_own_choices_recorded() {
+        let vote = |user: &str, choice| {
+            NoteUpdateBody::PollVoted(NotePollVotedBody {
+                choice,
+                user_id: Some(user.into()),
+            })
+        };
+        let patches = fold(
+            vec![
+                capture("n1", vote("u1", 0)),
+                capture("n1", vote("u2", 0)),
+                capture("n1", vote("me", 1)),
+            ],
+            &self_ids(),
+        );
+        assert_eq!(patches[0].poll_votes, BTreeMap::from([(0, 2), (1, 1)]));
+        assert_eq!(patches[0].my_poll_choices, vec![1]);
+    }
+
+    #[test]
+    fn duplicate_updates_within_window_are_rejected() {
+        let mut sigs = UpdateSigs::default();
+        assert!(sigs.accept("n1", &reacted("u1", ":+1:")));
+        assert!(!sigs.accept("n1", &reacted("u1", ":+1:")));
+        // 別ノート・別 sig は通る
+        assert!(sigs.accept("n2", &reacted("u1", ":+1:")));
+        assert!(sigs.accept("n1", &unreacted("u1", ":+1:")));
+        assert!(sigs.accept("n1", &reacted("u1", ":+1:")));
+    }
+}
@@ -0,0 +1,188 @@
+//! ノートの共有用テキスト (「コピー」系のメニュー)。
+//!
+//! Markdown / プレーンテキスト / リンク付き引用の 3 形式を Rust 側でそろえて
+//! 作る。本文の MFM は `mfm.rs` で変換し、ノートは `notes/show` の生 JSON
+//! から読む (リモートノートの url / uri もそのまま使える)。
+
+use notecli::error::NoteDeckError;
+use serde::Deserialize;
+use serde_json::Value;
+use specta::Type;
+use tauri::State;
+
+use crate::commands::{AppState, Result};
+
+#[derive(Debug, Clone, Copy, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum NoteShareFormat {
+    /// 本文を Markdown に変換し、添付と元ノートへのリンクを付ける
+    Markdown,
+    /// 本文 (と CW) をプレーンテキストにしたもの
+    Plain,
+    /// 本文を `> ` で引用し、投稿者と元ノートへのリンクを付ける
+    QuoteWithLink,
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key)?.as_str().filter(|s| !s.is_empty())
+}
+
+/// `@user@host` (ローカルユーザーは `host` を補う)。
+fn acct(note: &Value, host: &str) -> String {
+    let user = &note["user"];
+    let username = str_field(user, "username").unwrap_or("?");
+    let user_host = str_field(user, "host").unwrap_or(host);
+    format!("@{username}@{user_host}")
+}
+
+/// 元ノートの URL。フロントの `noteWebUrl` と同じ優先順。
+fn note_url(note: &Value, host: &str) -> String {
+    str_field(note, "url")
+        .or_else(|| str_field(note, "uri"))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let id = str_field(note, "id").unwrap_or_default();
+            format!("https://{host}/notes/{id}")
+        })
+}
+
+/// CW があれば CW → 空行 → 本文、なければ本文。
+fn body(note: &Value, convert: fn(&str) -> String) -> String {
+    let text = str_field(note, "text").map(convert).unwrap_or_default();
+    match str_field(note, "cw").map(convert) {
+        Some(cw) if text.is_empty() => cw,
+        Some(cw) => format!("{cw}\n\n{text}"),
+        None => text,
+    }
+}
+
+fn files(note: &Value) -> impl Iterator<Item = (&str, &str, bool)> + '_ {
+    note.get("files")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|file| {
+            let url = str_field(file, "url")?;
+            let name = str_field(file, "name").unwrap_or("file");
+            let is_image = str_field(file, "type").is_some_and(|t| t.starts_with("image/"));
+            Some((name, url, is_image))
+        })
+}
+
+fn format_note(note: &Value, host: &str, format: NoteShareFormat) -> String {
+    let url = note_url(note, host);
+    match format {
+        NoteShareFormat::Plain => body(note, crate::mfm::to_plain_lines),
+        NoteShareFormat::Markdown => {
+            let mut parts = vec![body(note, crate::mfm::to_markdown)];
+            let attachments: Vec<String> = files(note)
+                .map(|(name, url, is_image)| {
+                    let bang = if is_image { "!" } else { "" };
+                    format!("{bang}[{name}]({url})")
+                })
+                .collect();
+            if !attachments.is_empty() {
+                parts.push(attachments.join("\n"));
+            }
+            parts.push(format!("— [{}]({url})", acct(note, host)));
+            parts.retain(|p| !p.is_empty());
+            parts.join("\n\n")
+        }
+        NoteShareFormat::QuoteWithLink => {
+            let quoted = body(note, crate::mfm::to_plain_lines)
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {line}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let author = match str_field(&note["user"], "name") {
+                Some(name) => format!("{} ({})", crate::mfm::to_plain_text(name), acct(note, host)),
+                None => acct(note, host),
+            };
+            let credit = format!("— {author}\n{url}");
+            if quoted.is_empty() {
+                credit
+            } else {
+                format!("{quoted}\n\n{credit}")
+            }
+        }
+    }
+}
+
+/// ノートを共有用のテキストにする (「Markdown でコピー」「引用としてコピー」など)。
+#[tauri::command]
+#[specta::specta]
+pub async fn format_note_for_share(
+    app_state: State<'_, AppState>,
+    account_id: String,
+    note_id: String,
+    format: NoteShareFormat,
+) -> Result<String> {
+    if note_id.is_empty() {
+        return Err(NoteDeckError::InvalidInput("note_id is empty".to_string()));
+    }
+    let (client, host, token) = app_state.authed_or_anon(&account_id).await?;
+    let note = client
+        .request(
+            &host,
+            &token,
+            "notes/show",
+            serde_json::json!({ "noteId": note_id }),
+        )
+        .await?;
+    Ok(format_note(&note, &host, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note() -> Value {
+        serde_json::json!({
+            "id": "9abc",
+            "text": "**こんにちは** $[x2 世界]\n[詳細](https://example.com/a)",
+            "cw": null,
+            "user": { "username": "alice", "host": null, "name": "Alice :blobcat:" },
+            "files": [
+                { "name": "cat.png", "url": "https://files.example/cat.png", "type": "image/png" },
+                { "name": "doc.pdf", "url": "https://files.example/doc.pdf", "type": "application/pdf" }
+            ],
+        })
+    }
+
+    #[test]
+    fn plain_keeps_lines_and_drops_markup() {
+        assert_eq!(
+            format_note(&note(), "misskey.example", NoteShareFormat::Plain),
+            "こんにちは 世界\n詳細"
+        );
+    }
+
+    #[test]
+    fn markdown_has_attachments_and_source_link() {
+        assert_eq!(
+            format_note(&note(), "misskey.example", NoteShareFormat::Markdown),
+            "**こんにちは** 世界\n[詳細](https://example.com/a)\n\n\
+             ![cat.png](https://files.example/cat.png)\n[doc.pdf](https://files.example/doc.pdf)\n\n\
+             — [@alice@misskey.example](https://misskey.example/notes/9abc)"
+        );
+    }
+
+    #[test]
+    fn quote_prefixes_lines_and_prefers_remote_url() {
+        let mut note = note();
+        note["cw"] = "ネタバレ".into();
+        note["user"]["host"] = "remote.example".into();
+        note["url"] = "https://remote.example/notes/xyz".into();
+        assert_eq!(
+            format_note(&note, "misskey.example", NoteShareFormat::QuoteWithLink),
+            "> ネタバレ\n>\n> こんにちは 世界\n> 詳細\n\n\
+             — Alice :blobcat: (@alice@remote.example)\nhttps://remote.example/notes/xyz"
+        );
+    }
+}
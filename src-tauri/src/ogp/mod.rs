@@ -4,7 +4,7 @@ pub mod plugins;
 use lru::LruCache;
 use notecli::db::SummaryRow;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,6 +16,8 @@ use crate::perf_config::SharedPerfConfig;
 
 const DEFAULT_MAX_ENTRIES: usize = 64;
 const MAX_HTML_SIZE: usize = 2 * 1024 * 1024;
+/// `lang` for the instance's preview proxy when the OS locale is unknown.
+const DEFAULT_PROXY_LANG: &str = "ja-JP";
 
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
 pub struct Player {
@@ -126,6 +128,10 @@ pub struct OgpCache {
     db: Arc<notecli::db::Database>,
     loaded: Arc<std::sync::atomic::AtomicBool>,
     perf: SharedPerfConfig,
+    /// Hosts known not to expose the `/url?url=` preview proxy.
+    no_proxy_hosts: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// `lang` passed to the preview proxy (the OS locale, e.g. `en-US`).
+    proxy_lang: Arc<str>,
 }
 
 impl OgpCache {
@@ -146,6 +152,10 @@ impl OgpCache {
             db,
             loaded: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             perf,
+            no_proxy_hosts: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            proxy_lang: tauri_plugin_os::locale()
+                .unwrap_or_else(|| DEFAULT_PROXY_LANG.to_string())
+                .into(),
         }
     }
 
@@ -177,23 +187,20 @@ impl OgpCache {
 
     /// Fetch without server context (plugins → direct HTML parse).
     pub async fn get_ogp(&self, url: &str) -> Result<SummaryData, String> {
-        self.cached_or_fetch(url, |this| Box::pin(this.resolve(url.to_string(), None)))
+        self.cached_or_fetch(url, |this| Box::pin(this.resolve(url.to_string())))
             .await
     }
 
-    /// Fetch with server context.
-    /// Priority: plugins → server `/api/url` → direct HTML parse.
-    pub async fn get_ogp_via_server(
-        &self,
-        url: &str,
-        host: &str,
-        token: &str,
-    ) -> Result<SummaryData, String> {
+    /// Fetch through the instance's `/url?url=` preview proxy (summaly), the
+    /// same route the web client uses, so the linked site sees the server
+    /// instead of this machine. Only instances without the proxy fall back to
+    /// the local fetcher (plugins → direct HTML parse), which does expose this
+    /// machine's IP; when the proxy is there but fails, the preview fails too.
+    pub async fn get_ogp_via_proxy(&self, url: &str, host: &str) -> Result<SummaryData, String> {
         let host = host.to_string();
-        let token = token.to_string();
         let url_owned = url.to_string();
         self.cached_or_fetch(url, |this| {
-            Box::pin(this.resolve(url_owned, Some((host, token))))
+            Box::pin(this.resolve_via_proxy(url_owned, host))
         })
         .await
     }
@@ -290,58 +297,49 @@ impl OgpCache {
         self.persist_to_disk(url, data);
     }
 
-    async fn fetch_from_server(
-        &self,
-        url: &str,
-        host: &str,
-        token: &str,
-    ) -> Result<SummaryData, String> {
-        let body = serde_json::json!({ "i": token, "url": url });
-        let resp = self
+    /// GET `https://{host}/url?url=...` (the instance's summaly proxy).
+    async fn fetch_from_proxy(&self, url: &str, host: &str) -> ProxyOutcome {
+        let resp = match self
             .http_client
-            .post(format!("https://{host}/api/url"))
-            .json(&body)
+            .get(format!("https://{host}/url"))
+            .query(&[("url", url), ("lang", &*self.proxy_lang)])
             .send()
             .await
-            .map_err(|e| format!("Server OGP fetch failed: {e}"))?;
-
-        if !resp.status().is_success() {
-            return Err(format!("Server OGP HTTP {}", resp.status()));
+        {
+            Ok(resp) => resp,
+            Err(e) => return ProxyOutcome::Failed(format!("Proxy OGP fetch failed: {e}")),
+        };
+        let is_json = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.contains("json"));
+        if let Some(outcome) = classify_proxy_response(resp.status().as_u16(), is_json) {
+            return outcome;
         }
+        match resp.json::<ServerUrlResponse>().await {
+            Ok(server_data) => match server_data.into_summary(url) {
+                Ok(data) => ProxyOutcome::Ok(Box::new(data)),
+                Err(e) => ProxyOutcome::Failed(e),
+            },
+            Err(e) => ProxyOutcome::Failed(format!("Proxy OGP parse failed: {e}")),
+        }
+    }
 
-        let server_data: ServerUrlResponse = resp
-            .json()
-            .await
-            .map_err(|e| format!("Server OGP parse failed: {e}"))?;
-
-        let player = server_data.player.and_then(|p| {
-            let player_url = p.url.filter(|u| !u.is_empty())?;
-            Some(Player {
-                url: player_url,
-                width: p.width,
-                height: p.height,
-                allow: p.allow.unwrap_or_default(),
-            })
-        });
-
-        // Treat response without a title as a failure so we fall through
-        // to the direct HTML parser (e.g. note.com, zenn.dev).
-        let title = server_data
-            .title
-            .filter(|t| !t.is_empty())
-            .ok_or("Server returned no title")?;
-
-        Ok(SummaryData {
-            title: Some(title),
-            description: server_data.description,
-            icon: server_data.icon,
-            sitename: server_data.sitename,
-            thumbnail: server_data.thumbnail,
-            medias: Vec::new(),
-            player,
-            url: server_data.url.unwrap_or_else(|| url.to_string()),
-            sensitive: server_data.sensitive.unwrap_or(false),
-        })
+    async fn resolve_via_proxy(&self, url: String, host: String) -> Result<SummaryData, String> {
+        let known_missing = self.no_proxy_hosts.lock().unwrap().contains(&host);
+        if !known_missing {
+            match self.fetch_from_proxy(&url, &host).await {
+                ProxyOutcome::Ok(data) => return Ok(*data),
+                ProxyOutcome::Unsupported => {
+                    tracing::info!("[ogp] {host} has no URL preview proxy, using local fetcher");
+                    self.no_proxy_hosts.lock().unwrap().insert(host);
+                }
+                // Don't fetch locally: that would reveal this machine to the site
+                ProxyOutcome::Failed(e) => return Err(e),
+            }
+        }
+        self.resolve(url).await
     }
 
     /// Player URLs that are known to be broken (e.g. Cloudflare challenge).
@@ -361,18 +359,12 @@ impl OgpCache {
         }
     }
 
-    /// Resolve URL summary with priority: plugins → server → direct HTML parse.
+    /// Resolve URL summary locally with priority: plugins → direct HTML parse.
     ///
     /// Plugins are tried first because they are only registered when they produce
-    /// better results than the server (e.g. richer oEmbed data).
-    /// The server's `/api/url` endpoint is used next — it is fast and already
-    /// cached server-side, so we prefer it over a direct HTTP fetch.
-    async fn resolve(
-        &self,
-        url: String,
-        server: Option<(String, String)>,
-    ) -> Result<SummaryData, String> {
-        // 1. Plugins (only registered when they beat the server)
+    /// better results than a generic parse (e.g. richer oEmbed data).
+    async fn resolve(&self, url: String) -> Result<SummaryData, String> {
+        // 1. Plugins (only registered when they beat the generic parser)
         if let Ok(parsed) = url::Url::parse(&url) {
             for plugin in plugins::all() {
                 if plugin.test(&parsed) {
@@ -387,14 +379,7 @@ impl OgpCache {
             }
         }
 
-        // 2. Server summary (fast, already cached on server)
-        if let Some((host, token)) = server {
-            if let Ok(data) = self.fetch_from_server(&url, &host, &token).await {
-                return Ok(data);
-            }
-        }
-
-        // 3. Direct HTML fetch + parse (fallback)
+        // 2. Direct HTML fetch + parse (fallback)
         self.fetch_and_parse(&url).await
     }
 
@@ -662,9 +647,42 @@ mod tests {
         OgpCache::sanitize_player(&mut data);
         assert!(data.player.is_some());
     }
+
+    #[test]
+    fn classify_proxy_response_detects_missing_proxy() {
+        assert!(classify_proxy_response(200, true).is_none());
+        assert!(matches!(classify_proxy_response(404, false), Some(ProxyOutcome::Unsupported)));
+        // SPA fallback serving index.html for unknown paths
+        assert!(matches!(classify_proxy_response(200, false), Some(ProxyOutcome::Unsupported)));
+        // summaly failures on a server that does have the proxy
+        assert!(matches!(classify_proxy_response(422, true), Some(ProxyOutcome::Failed(_))));
+        // previews refused for now; not a reason to stop using the proxy
+        assert!(matches!(classify_proxy_response(403, true), Some(ProxyOutcome::Failed(_))));
+        assert!(matches!(classify_proxy_response(502, false), Some(ProxyOutcome::Failed(_))));
+    }
+
+    #[test]
+    fn server_response_into_summary() {
+        let resp: ServerUrlResponse = serde_json::from_value(serde_json::json!({
+            "title": "Title",
+            "sitename": "Example",
+            "player": { "url": "", "width": 640, "height": 360 },
+            "sensitive": true
+        }))
+        .unwrap();
+        let data = resp.into_summary("https://example.com/a").unwrap();
+        assert_eq!(data.title.as_deref(), Some("Title"));
+        assert_eq!(data.url, "https://example.com/a");
+        assert!(data.player.is_none());
+        assert!(data.sensitive);
+
+        let untitled: ServerUrlResponse =
+            serde_json::from_value(serde_json::json!({ "title": "" })).unwrap();
+        assert!(untitled.into_summary("https://example.com/b").is_err());
+    }
 }
 
-/// Response from Misskey's POST /api/url endpoint (summaly format)
+/// Response from Misskey's `/url?url=` proxy (summaly format)
 #[derive(Debug, Deserialize)]
 struct ServerUrlResponse {
     title: Option<String>,
@@ -684,3 +702,59 @@ struct ServerPlayer {
     height: Option<u32>,
     allow: Option<Vec<String>>,
 }
+
+impl ServerUrlResponse {
+    fn into_summary(self, url: &str) -> Result<SummaryData, String> {
+        let player = self.player.and_then(|p| {
+            let player_url = p.url.filter(|u| !u.is_empty())?;
+            Some(Player {
+                url: player_url,
+                width: p.width,
+                height: p.height,
+                allow: p.allow.unwrap_or_default(),
+            })
+        });
+
+        // A summary without a title (e.g. note.com, zenn.dev) has nothing to
+        // show, so treat it as a failed preview.
+        let title = self
+            .title
+            .filter(|t| !t.is_empty())
+            .ok_or("Server returned no title")?;
+
+        Ok(SummaryData {
+            title: Some(title),
+            description: self.description,
+            icon: self.icon,
+            sitename: self.sitename,
+            thumbnail: self.thumbnail,
+            medias: Vec::new(),
+            player,
+            url: self.url.unwrap_or_else(|| url.to_string()),
+            sensitive: self.sensitive.unwrap_or(false),
+        })
+    }
+}
+
+/// Result of asking the instance's `/url?url=` proxy.
+enum ProxyOutcome {
+    Ok(Box<SummaryData>),
+    /// The instance has no proxy; remember it and use the local fetcher.
+    Unsupported,
+    /// The proxy is there but didn't give a summary (including 403 when the
+    /// server refuses previews, which may change); no preview this time.
+    Failed(String),
+}
+
+/// Classify a proxy response by status and content type. `None` means the
+/// body should be parsed as a summary.
+fn classify_proxy_response(status: u16, is_json: bool) -> Option<ProxyOutcome> {
+    match status {
+        // 404 / 405: no such route (non-Misskey software)
+        404 | 405 => Some(ProxyOutcome::Unsupported),
+        200..=299 if is_json => None,
+        // An HTML page (e.g. SPA fallback) means the route isn't the proxy
+        200..=299 => Some(ProxyOutcome::Unsupported),
+        _ => Some(ProxyOutcome::Failed(format!("Proxy OGP HTTP {status}"))),
+    }
+}
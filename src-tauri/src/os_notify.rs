@@ -9,7 +9,12 @@
 //!   従来の plugin 経路を維持 (クリック遷移なし)。署名導入時に解禁する
 //! - Android: plugin の `extra` にコンテキストを積み、JS 側 onAction が遷移する
 //!   (このモジュールは使わない — streaming.rs 参照)
+//!
+//! メンション系・リアクション通知には Reply / React / Open のアクションボタンを
+//! 付ける。Reply / React はメインウィンドウをフォーカスせずバックエンドから
+//! 直接投稿する (user-notify 経路のみ)。
 
+use notecli::models::CreateNoteParams;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tauri_specta::Event;
@@ -48,6 +53,102 @@ pub struct NotifyMedia {
     pub image_url: Option<String>,
 }
 
+/// アクションボタン付き通知のカテゴリ ID。公開範囲が specified のノートは
+/// 宛先を引き継げないため Reply なしのカテゴリを使う。
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+pub const ACTION_CATEGORY: &str = "notedeck-note";
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+pub const ACTION_CATEGORY_NO_REPLY: &str = "notedeck-note-noreply";
+
+/// React ボタンで送るリアクション。
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+pub const QUICK_REACTION: &str = "👍";
+
+/// 通知のアクションボタン。識別子はカテゴリ登録と応答の両方で使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+pub enum NotificationAction {
+    Reply,
+    React,
+    Open,
+}
+
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+impl NotificationAction {
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Reply => "reply",
+            Self::React => "react",
+            Self::Open => "open",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "reply" => Some(Self::Reply),
+            "react" => Some(Self::React),
+            "open" => Some(Self::Open),
+            _ => None,
+        }
+    }
+}
+
+/// アクションボタンを付けるための情報。対象ノートは context の noteId。
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+pub struct NotifyActions {
+    /// 返信の公開範囲 (元ノートに揃える)。None なら Reply ボタンを出さない。
+    pub reply_visibility: Option<String>,
+}
+
+impl NotifyActions {
+    /// 元ノートの公開範囲からアクション情報を作る。specified は宛先
+    /// (visibleUserIds) を通知から復元できないため返信不可にする。
+    pub fn for_note_visibility(visibility: &str) -> Self {
+        let reply_visibility = match visibility {
+            "public" | "home" | "followers" => Some(visibility.to_string()),
+            _ => None,
+        };
+        Self { reply_visibility }
+    }
+
+    #[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+    fn category(&self) -> &'static str {
+        if self.reply_visibility.is_some() {
+            ACTION_CATEGORY
+        } else {
+            ACTION_CATEGORY_NO_REPLY
+        }
+    }
+}
+
+/// Reply アクションの投稿内容。空文字は投稿しない。
+#[cfg_attr(any(target_os = "macos", target_os = "android"), allow(dead_code))]
+fn quick_reply_params(
+    note_id: &str,
+    text: &str,
+    visibility: &str,
+) -> Result<CreateNoteParams, notecli::error::NoteDeckError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(notecli::error::NoteDeckError::InvalidInput(
+            "reply text is empty".to_string(),
+        ));
+    }
+    Ok(CreateNoteParams {
+        text: Some(text.to_string()),
+        cw: None,
+        visibility: Some(visibility.to_string()),
+        local_only: None,
+        mode_flags: None,
+        reply_id: Some(note_id.to_string()),
+        renote_id: None,
+        file_ids: None,
+        poll: None,
+        scheduled_at: None,
+    })
+}
+
 #[cfg(any(target_os = "linux", target_os = "windows"))]
 mod desktop {
     use std::collections::HashMap;
@@ -55,9 +156,15 @@ mod desktop {
 
     use tauri::Manager;
     use tauri_specta::Event;
-    use user_notify::{NotificationManager, NotificationResponseAction};
+    use user_notify::{
+        NotificationCategory, NotificationCategoryAction, NotificationManager,
+        NotificationResponse, NotificationResponseAction,
+    };
 
-    use super::NotificationClicked;
+    use super::{NotificationAction, NotificationClicked, NotifyActions};
+    use crate::commands::{create_note, AppState};
+    use crate::host_queue::{HostQueue, Priority};
+    use crate::upstream_rate::UpstreamRate;
 
     static MANAGER: OnceLock<Arc<dyn NotificationManager>> = OnceLock::new();
 
@@ -75,29 +182,8 @@ mod desktop {
         );
         let handle = app.clone();
         let register_result = manager.register(
-            Box::new(move |response| {
-                // Dismiss (スワイプ/閉じる) では遷移しない
-                if !matches!(response.action, NotificationResponseAction::Default) {
-                    return;
-                }
-                if let Some(w) = handle.get_webview_window("main") {
-                    let _ = w.show();
-                    let _ = w.unminimize();
-                    let _ = w.set_focus();
-                }
-                let info = &response.user_info;
-                if let Some(account_id) = info.get("accountId") {
-                    let event = NotificationClicked {
-                        account_id: account_id.clone(),
-                        note_id: info.get("noteId").cloned(),
-                        user_id: info.get("userId").cloned(),
-                    };
-                    if let Err(e) = event.emit(&handle) {
-                        tracing::warn!("[notification] click emit failed: {e}");
-                    }
-                }
-            }),
-            vec![],
+            Box::new(move |response| dispatch(&handle, &response)),
+            categories(),
         );
         if let Err(e) = register_result {
             tracing::warn!("[notification] click handler registration failed: {e:?}");
@@ -105,13 +191,169 @@ mod desktop {
         let _ = MANAGER.set(manager);
     }
 
+    /// アクションボタンのカテゴリ。Reply はテキスト入力付き (インライン返信)。
+    fn categories() -> Vec<NotificationCategory> {
+        let react = || NotificationCategoryAction::Action {
+            identifier: NotificationAction::React.id().to_string(),
+            title: super::QUICK_REACTION.to_string(),
+        };
+        let open = || NotificationCategoryAction::Action {
+            identifier: NotificationAction::Open.id().to_string(),
+            title: "開く".to_string(),
+        };
+        vec![
+            NotificationCategory {
+                identifier: super::ACTION_CATEGORY.to_string(),
+                actions: vec![
+                    NotificationCategoryAction::TextInputAction {
+                        identifier: NotificationAction::Reply.id().to_string(),
+                        title: "返信".to_string(),
+                        input_button_title: "送信".to_string(),
+                        input_placeholder: "返信を入力".to_string(),
+                    },
+                    react(),
+                    open(),
+                ],
+            },
+            NotificationCategory {
+                identifier: super::ACTION_CATEGORY_NO_REPLY.to_string(),
+                actions: vec![react(), open()],
+            },
+        ]
+    }
+
+    /// 通知の応答を振り分ける。クリック (Default) と Open はメインウィンドウを
+    /// フォーカスして遷移、Reply / React はフォーカスせずバックグラウンドで投稿。
+    /// テキスト入力をサポートしない通知サーバー (一部 Linux DE) で Reply が
+    /// 本文なしで届いた場合は Open 扱いにしてアプリ側で返信させる。
+    pub(super) fn dispatch<R: tauri::Runtime>(
+        app: &tauri::AppHandle<R>,
+        response: &NotificationResponse,
+    ) {
+        let mut action = match &response.action {
+            NotificationResponseAction::Default => NotificationAction::Open,
+            // Dismiss (スワイプ/閉じる) では遷移しない
+            NotificationResponseAction::Dismiss => return,
+            NotificationResponseAction::Other(id) => match NotificationAction::from_id(id) {
+                Some(action) => action,
+                None => return,
+            },
+        };
+        let text = response
+            .user_text
+            .clone()
+            .filter(|t| !t.trim().is_empty());
+        if action == NotificationAction::Reply && text.is_none() {
+            action = NotificationAction::Open;
+        }
+
+        let info = &response.user_info;
+        let context = info.get("accountId").map(|account_id| NotificationClicked {
+            account_id: account_id.clone(),
+            note_id: info.get("noteId").cloned(),
+            user_id: info.get("userId").cloned(),
+        });
+
+        if action == NotificationAction::Open {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.unminimize();
+                let _ = w.set_focus();
+            }
+            if let Some(event) = context {
+                if let Err(e) = event.emit(app) {
+                    tracing::warn!("[notification] click emit failed: {e}");
+                }
+            }
+            return;
+        }
+
+        let Some(ctx) = context else {
+            return;
+        };
+        let visibility = info.get("visibility").cloned();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = perform_quick_action(&app, action, &ctx, visibility, text).await {
+                tracing::warn!("[notification] {} action failed: {e}", action.id());
+                show(
+                    "通知アクションに失敗しました",
+                    Some(&e.to_string()),
+                    Some(&ctx),
+                    None,
+                    None,
+                );
+            }
+        });
+    }
+
+    async fn perform_quick_action<R: tauri::Runtime>(
+        app: &tauri::AppHandle<R>,
+        action: NotificationAction,
+        ctx: &NotificationClicked,
+        visibility: Option<String>,
+        text: Option<String>,
+    ) -> Result<(), notecli::error::NoteDeckError> {
+        use notecli::error::NoteDeckError;
+
+        let note_id = ctx
+            .note_id
+            .as_deref()
+            .ok_or_else(|| NoteDeckError::InvalidInput("notification has no note".into()))?;
+        let state = app.state::<AppState>();
+        let (client, host, token) = state.authed(&ctx.account_id).await?;
+        match action {
+            NotificationAction::Reply => {
+                let visibility = visibility.ok_or_else(|| {
+                    NoteDeckError::InvalidInput("note does not accept quick replies".into())
+                })?;
+                let params =
+                    super::quick_reply_params(note_id, text.as_deref().unwrap_or(""), &visibility)?;
+                // 投稿フォームからの送信 (api_create_note) と同じ経路で送る
+                let send = create_note(&state, &ctx.account_id, &params, None);
+                app.state::<HostQueue>()
+                    .run(
+                        &host,
+                        Priority::Interactive,
+                        app.state::<UpstreamRate>().write(&host, send),
+                    )
+                    .await?;
+            }
+            NotificationAction::React => {
+                let send = client.create_reaction(&host, &token, note_id, super::QUICK_REACTION);
+                app.state::<HostQueue>()
+                    .run(
+                        &host,
+                        Priority::Interactive,
+                        app.state::<UpstreamRate>().write(&host, send),
+                    )
+                    .await?;
+            }
+            NotificationAction::Open => {}
+        }
+        Ok(())
+    }
+
+    /// Windows: toast の protocol 起動 URL を decode して dispatch する。
+    /// アクションボタンも protocol 起動で届くため、Reply / React はここから
+    /// フォーカスなしで実行される。
+    #[cfg(target_os = "windows")]
+    pub fn handle_protocol_url<R: tauri::Runtime>(app: &tauri::AppHandle<R>, url: &str) {
+        match user_notify::windows::decode_deeplink(url) {
+            Ok(response) => dispatch(app, &response),
+            Err(e) => tracing::warn!("[notification] protocol url decode failed: {e:?}"),
+        }
+    }
+
     /// OS 通知を表示する。context があればクリック時の遷移ペイロードとして
     /// user_info に積み、media があればアバター/絵文字画像を添付する。
+    /// actions があればアクションボタンのカテゴリを設定する。
     pub fn show(
         title: &str,
         body: Option<&str>,
         context: Option<&NotificationClicked>,
         media: Option<&super::NotifyMedia>,
+        actions: Option<&NotifyActions>,
     ) {
         // 未初期化 (ユニットテスト等) は no-op
         let Some(manager) = MANAGER.get() else {
@@ -130,6 +372,13 @@ mod desktop {
             if let Some(user_id) = &ctx.user_id {
                 info.insert("userId".to_string(), user_id.clone());
             }
+            // アクションは対象ノートが必要 (context なしの要約通知には付かない)
+            if let Some(actions) = actions.filter(|_| ctx.note_id.is_some()) {
+                if let Some(visibility) = &actions.reply_visibility {
+                    info.insert("visibility".to_string(), visibility.clone());
+                }
+                builder = builder.set_category_id(actions.category());
+            }
             builder = builder.set_user_info(info);
         }
         // Linux の send_notification は内部でブロッキングの notify-rust
@@ -196,6 +445,8 @@ mod desktop {
 
 #[cfg(any(target_os = "linux", target_os = "windows"))]
 pub use desktop::{init, show};
+#[cfg(target_os = "windows")]
+pub use desktop::handle_protocol_url;
 
 /// Windows: toast の protocol 起動 URL (NOTIFICATION_PROTOCOL://) を
 /// 遷移コンテキストに復元する (#754)。Default action 以外 (dismiss) や
@@ -209,7 +460,15 @@ pub fn decode_protocol_url(url: &str) -> Option<NotificationClicked> {
             return None;
         }
     };
-    if !matches!(resp.action, user_notify::NotificationResponseAction::Default) {
+    // cold start ではアクションを実行せず、Open 相当 (クリック/開く) のみ遷移する
+    let is_open = match &resp.action {
+        user_notify::NotificationResponseAction::Default => true,
+        user_notify::NotificationResponseAction::Other(id) => {
+            NotificationAction::from_id(id) == Some(NotificationAction::Open)
+        }
+        _ => false,
+    };
+    if !is_open {
         return None;
     }
     Some(NotificationClicked {
@@ -218,3 +477,41 @@ pub fn decode_protocol_url(url: &str) -> Option<NotificationClicked> {
         user_id: resp.user_info.get("userId").cloned(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_ids_round_trip() {
+        for action in [
+            NotificationAction::Reply,
+            NotificationAction::React,
+            NotificationAction::Open,
+        ] {
+            assert_eq!(NotificationAction::from_id(action.id()), Some(action));
+        }
+        assert_eq!(NotificationAction::from_id("__default__"), None);
+    }
+
+    #[test]
+    fn specified_notes_get_no_reply_category() {
+        let public = NotifyActions::for_note_visibility("public");
+        assert_eq!(public.reply_visibility.as_deref(), Some("public"));
+        assert_eq!(public.category(), ACTION_CATEGORY);
+
+        let specified = NotifyActions::for_note_visibility("specified");
+        assert!(specified.reply_visibility.is_none());
+        assert_eq!(specified.category(), ACTION_CATEGORY_NO_REPLY);
+    }
+
+    #[test]
+    fn quick_reply_params_keep_visibility_and_reject_empty() {
+        let params = quick_reply_params("note-1", "  thanks!  ", "followers").unwrap();
+        assert_eq!(params.reply_id.as_deref(), Some("note-1"));
+        assert_eq!(params.text.as_deref(), Some("thanks!"));
+        assert_eq!(params.visibility.as_deref(), Some("followers"));
+
+        assert!(quick_reply_params("note-1", "   ", "public").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! タイムラインの次ページの先読み置き場。
+//!
+//! カラムが末尾に近づくと、フロントは次に要求するはずのページ (`untilId` =
+//! 表示中の最古ノート) を `api_prefetch_timeline_page` でバックグラウンド取得
+//! させる。取得結果は通常の取得と同じく SQLite キャッシュに書かれ、ここにも
+//! `api_get_timeline` の合流キー単位で置かれる。実際の `loadMore` が同じキーで
+//! 来たら上流に行かずここから返す (取得中なら `RequestDedup` 側で合流する)。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use notecli::models::NormalizedNote;
+
+/// 先読みしたページを使わずに捨てるまでの期間。
+const PAGE_TTL: Duration = Duration::from_secs(120);
+/// 保持するページ数の上限 (カラム数より十分多ければよい)。
+const MAX_PAGES: usize = 64;
+
+struct Page {
+    stored_at: Instant,
+    /// 格納順。上限超過時に最古を捨てるのに使う
+    seq: u64,
+    notes: Vec<NormalizedNote>,
+}
+
+#[derive(Default)]
+struct Pages {
+    by_key: HashMap<String, Page>,
+    next_seq: u64,
+}
+
+#[derive(Default)]
+pub struct PagePrefetcher {
+    pages: Mutex<Pages>,
+}
+
+impl PagePrefetcher {
+    pub fn store(&self, key: String, notes: Vec<NormalizedNote>) {
+        let Ok(mut pages) = self.pages.lock() else {
+            return;
+        };
+        pages
+            .by_key
+            .retain(|_, page| page.stored_at.elapsed() < PAGE_TTL);
+        if pages.by_key.len() >= MAX_PAGES {
+            let oldest = pages
+                .by_key
+                .iter()
+                .min_by_key(|(_, page)| page.seq)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                pages.by_key.remove(&oldest);
+            }
+        }
+        let seq = pages.next_seq;
+        pages.next_seq += 1;
+        pages.by_key.insert(
+            key,
+            Page {
+                stored_at: Instant::now(),
+                seq,
+                notes,
+            },
+        );
+    }
+
+    /// 先読み済みページを取り出す (1 回限り)。期限切れなら None。
+    pub fn take(&self, key: &str) -> Option<Vec<NormalizedNote>> {
+        let page = self.pages.lock().ok()?.by_key.remove(key)?;
+        (page.stored_at.elapsed() < PAGE_TTL).then_some(page.notes)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.pages.lock().is_ok_and(|pages| {
+            pages
+                .by_key
+                .get(key)
+                .is_some_and(|page| page.stored_at.elapsed() < PAGE_TTL)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_taken_once() {
+        let prefetcher = PagePrefetcher::default();
+        prefetcher.store("k".into(), Vec::new());
+        assert!(prefetcher.contains("k"));
+        assert_eq!(prefetcher.take("k").map(|notes| notes.len()), Some(0));
+        assert!(prefetcher.take("k").is_none());
+        assert!(!prefetcher.contains("k"));
+    }
+
+    #[test]
+    fn oldest_page_is_evicted_at_capacity() {
+        let prefetcher = PagePrefetcher::default();
+        for i in 0..=MAX_PAGES {
+            prefetcher.store(format!("k{i}"), Vec::new());
+        }
+        assert!(!prefetcher.contains("k0"));
+        assert!(prefetcher.contains(&format!("k{MAX_PAGES}")));
+    }
+}
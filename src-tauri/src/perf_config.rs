@@ -2,6 +2,21 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::query_runtime::{QueryRuntime, StreamLimits};
+use crate::settings::{BackendSettings, SettingsStore};
+
+/// What to drop when a streaming buffer hits its cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamDropPolicy {
+    /// Keep the newest events and flag the delta with a gap marker so the
+    /// consumer refetches the missed range.
+    #[default]
+    DropOldest,
+    /// Keep what is already buffered and discard new arrivals (also flagged).
+    DropNewest,
+}
+
 /// Performance configuration shared across the application.
 /// All fields are dynamically updatable at runtime via Tauri commands.
 #[derive(Debug, Clone, Serialize, Deserialize, specta::Type)]
@@ -14,6 +29,21 @@ pub struct PerformanceConfig {
     pub circuit_breaker_threshold: u32,
     pub circuit_breaker_duration: u64,
     pub image_cache_ttl_days: u64,
+    /// Max inserts / updates buffered per query between delta flushes.
+    pub stream_pending_max: usize,
+    /// Max note capture updates buffered between flushes.
+    pub stream_captures_max: usize,
+    pub stream_drop_policy: StreamDropPolicy,
+}
+
+impl PerformanceConfig {
+    pub fn stream_limits(&self) -> StreamLimits {
+        StreamLimits {
+            pending_max: self.stream_pending_max.max(1),
+            captures_max: self.stream_captures_max.max(1),
+            drop_policy: self.stream_drop_policy,
+        }
+    }
 }
 
 impl Default for PerformanceConfig {
@@ -27,6 +57,9 @@ impl Default for PerformanceConfig {
             circuit_breaker_threshold: 5,
             circuit_breaker_duration: 60,
             image_cache_ttl_days: 7,
+            stream_pending_max: 500,
+            stream_captures_max: 2000,
+            stream_drop_policy: StreamDropPolicy::DropOldest,
         }
     }
 }
@@ -34,15 +67,25 @@ impl Default for PerformanceConfig {
 pub type SharedPerfConfig = Arc<RwLock<PerformanceConfig>>;
 
 /// Tauri command: update performance config at runtime.
+/// Persisted to `settings.json` (see `settings.rs`, which also offers
+/// per-section updates).
 #[tauri::command]
 #[specta::specta]
 pub async fn update_performance_config(
+    app: tauri::AppHandle,
     config: PerformanceConfig,
     state: tauri::State<'_, SharedPerfConfig>,
+    runtime: tauri::State<'_, QueryRuntime>,
+    settings: tauri::State<'_, Arc<SettingsStore>>,
 ) -> Result<(), String> {
-    let mut current = state.write().await;
-    *current = config;
-    Ok(())
+    // PerformanceConfig に無いセクション (メディア / 機能ごとの設定) は今の値を引き継ぐ
+    crate::settings::commit(&app, &settings, &state, &runtime, |current| {
+        current.set_perf_sections(BackendSettings::from_perf_config(&config));
+        Ok(())
+    })
+    .await
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
 /// Tauri command: get current performance config.
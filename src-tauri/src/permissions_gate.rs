@@ -20,7 +20,7 @@
 //!   数秒であり、「未設定のあいだ開いている」時間帯を作らない。
 
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 use axum::extract::Request;
 use axum::http::{Method, StatusCode};
@@ -35,6 +35,29 @@ use serde_json::json;
 #[derive(Clone, Copy, Debug)]
 pub struct ExternalTokenMarker;
 
+/// 外部プラグインの API トークンで認証されたリクエストに付く marker。
+/// ExternalTokenMarker と併せて付き、external プロファイルに加えて
+/// ここの承認スコープでも gate する (両方を満たすものだけ通す)。
+#[derive(Clone, Debug)]
+pub struct PluginTokenMarker {
+    pub plugin_id: Arc<str>,
+    pub scopes: Arc<[String]>,
+}
+
+/// プラグイン manifest が宣言できるスコープ。route_rule の対応表のキーと、
+/// dispatcher ルートに使う `capabilities.execute`。
+pub const PLUGIN_SCOPES: &[&str] = &[
+    "notes.read",
+    "notes.write",
+    "notes.react",
+    "notifications",
+    "account.read",
+    "deck.read",
+    "drive.read",
+    "clips.read",
+    "capabilities.execute",
+];
+
 /// external principal の Misskey コンテンツ read 下限 (#712 §5.3)。
 /// フロント側 `EXTERNAL_READ_FLOOR` と同じ意味論的選択 — 「トークンを発行して
 /// 渡す行為そのものが Misskey コンテンツ read への同意」。sync 前でもこの
@@ -82,7 +105,8 @@ pub fn reset_external_granted_for_test() {
     *EXTERNAL_GRANTED.write().unwrap() = None;
 }
 
-fn is_granted(key: &str) -> bool {
+/// external principal にこのキーが granted か (capability registry からも引く)。
+pub(crate) fn is_granted(key: &str) -> bool {
     // floor キーは sync 状態に関わらず常時許可 (resolveFor 側でも ON に clamp
     // されるので表示と一致する)
     if EXTERNAL_READ_FLOOR.contains(&key) {
@@ -109,7 +133,7 @@ pub enum RouteRule {
     Deny,
 }
 
-/// per-route 対応表 (#712 §5.3)。openapi.json の全 25 ルートを網羅する。
+/// per-route 対応表 (#712 §5.3)。openapi.json の全 26 ルートを網羅する。
 /// 対応表に無いパスは Deny (deny-by-default)。
 pub fn route_rule(method: &Method, path: &str) -> RouteRule {
     let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
@@ -163,6 +187,8 @@ pub fn route_rule(method: &Method, path: &str) -> RouteRule {
             "/api/events" => {
                 return RouteRule::Keys(&["notes.read", "notifications"]);
             }
+            // 通知の cue (種類・送信者 ID) だけの SSE
+            "/api/cues" => return RouteRule::Keys(&["notifications"]),
             _ => {}
         }
     }
@@ -193,6 +219,38 @@ pub fn route_rule(method: &Method, path: &str) -> RouteRule {
     RouteRule::Deny
 }
 
+/// プラグイントークンで呼ぶのに必要なスコープ。None は恒久拒否。
+/// 免除ルートのうち dispatcher に届くもの (capability 実行) は
+/// `capabilities.execute` を要求する。
+fn plugin_required_scopes(method: &Method, path: &str) -> Option<&'static [&'static str]> {
+    match route_rule(method, path) {
+        RouteRule::Exempt if method == Method::POST && path.ends_with("/execute") => {
+            Some(&["capabilities.execute"])
+        }
+        RouteRule::Exempt => Some(&[]),
+        RouteRule::Keys(keys) => Some(keys),
+        RouteRule::Deny => None,
+    }
+}
+
+fn plugin_forbidden(plugin_id: &str, required: &[&str]) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "ok": false,
+            "code": "permission_denied",
+            "principal": "plugin",
+            "plugin": plugin_id,
+            "required": required,
+            "error": format!(
+                "denied for plugin {plugin_id}: required scopes [{}]",
+                required.join(", ")
+            ),
+        })),
+    )
+        .into_response()
+}
+
 fn forbidden(required: &[&str]) -> Response {
     (
         StatusCode::FORBIDDEN,
@@ -216,6 +274,21 @@ pub async fn external_gate_middleware(req: Request, next: Next) -> Response {
     if req.extensions().get::<ExternalTokenMarker>().is_none() {
         return next.run(req).await;
     }
+    if let Some(plugin) = req.extensions().get::<PluginTokenMarker>() {
+        match plugin_required_scopes(req.method(), req.uri().path()) {
+            Some(keys) => {
+                let missing: Vec<&str> = keys
+                    .iter()
+                    .filter(|k| !plugin.scopes.iter().any(|s| s == *k))
+                    .copied()
+                    .collect();
+                if !missing.is_empty() {
+                    return plugin_forbidden(&plugin.plugin_id, &missing);
+                }
+            }
+            None => return plugin_forbidden(&plugin.plugin_id, &[]),
+        }
+    }
     match route_rule(req.method(), req.uri().path()) {
         RouteRule::Exempt => next.run(req).await,
         RouteRule::Keys(keys) => {
@@ -302,6 +375,10 @@ mod tests {
             route_rule(&Method::GET, "/api/deck/columns"),
             RouteRule::Keys(&["deck.read"])
         );
+        assert_eq!(
+            route_rule(&Method::GET, "/api/cues"),
+            RouteRule::Keys(&["notifications"])
+        );
     }
 
     #[test]
@@ -331,6 +408,35 @@ mod tests {
         assert_eq!(route_rule(&Method::GET, "/api/unknown"), RouteRule::Deny);
     }
 
+    #[test]
+    fn plugin_scopes_follow_route_rules() {
+        assert_eq!(
+            plugin_required_scopes(&Method::GET, "/api/misskey.io/timeline/home"),
+            Some(&["notes.read"][..])
+        );
+        assert_eq!(
+            plugin_required_scopes(&Method::POST, "/api/capabilities/notes.create/execute"),
+            Some(&["capabilities.execute"][..])
+        );
+        assert_eq!(
+            plugin_required_scopes(&Method::GET, "/api/capabilities"),
+            Some(&[][..])
+        );
+        assert_eq!(plugin_required_scopes(&Method::GET, "/api/unknown"), None);
+        // 対応表のキーはすべて manifest で宣言できる
+        for (method, path) in [
+            (Method::POST, "/api/misskey.io/note"),
+            (Method::POST, "/api/misskey.io/notes/x/reactions"),
+            (Method::GET, "/api/misskey.io/notifications"),
+            (Method::GET, "/api/misskey.io/users/x"),
+            (Method::GET, "/api/deck/columns"),
+        ] {
+            for key in plugin_required_scopes(&method, path).unwrap() {
+                assert!(PLUGIN_SCOPES.contains(key), "{key}");
+            }
+        }
+    }
+
     #[test]
     fn floor_keys_are_granted_even_before_first_sync() {
         let _guard = lock_state();
@@ -0,0 +1,489 @@
+//! 外部プラグイン (ローカル HTTP API を使う別プロセス)。
+//!
+//! `app_dir/plugins/<dir>/plugin.json` の manifest で起動コマンドと必要な
+//! スコープ (permissions_gate::PLUGIN_SCOPES) を宣言する。フロントの AiScript
+//! プラグイン (stores/plugins) とは別物。
+//!
+//! - 有効化はユーザーの承認で、その時点の宣言スコープをバックエンド設定の
+//!   `plugins` セクションに記録する。manifest が後からスコープを増やしたら再承認まで起動しない。
+//! - 起動ごとに使い捨ての API トークン (`ndpl_`) を発行し、環境変数
+//!   `NOTEDECK_API_TOKEN` で渡す。メモリ上にハッシュだけを持ち、停止で失効する。
+//!   マスタートークン (`api-token` ファイル) は渡さない。
+//! - プラグイン由来のリクエストは external プロファイルに加えて承認スコープでも
+//!   gate される (permissions_gate::external_gate_middleware)。
+//! - 標準出力 / 標準エラーは `plugin.log` に書き出す。アプリ終了時に全停止する。
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use tauri::State;
+
+use crate::permissions_gate::{PluginTokenMarker, PLUGIN_SCOPES};
+use crate::settings::SettingsStore;
+
+const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+const LOG_FILE: &str = "plugin.log";
+/// プラグイン用トークンの接頭辞 (永続トークンの `ndp_` と区別する)。
+const TOKEN_PREFIX: &str = "ndpl_";
+
+/// `plugin.json`。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginManifest {
+    id: String,
+    name: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// 実行ファイル。区切り文字を含む相対パスはプラグインのディレクトリ基準、
+    /// それ以外 (`node` 等) は PATH から探す。
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+impl PluginManifest {
+    fn validate(&self) -> Result<(), String> {
+        let id_ok = !self.id.is_empty()
+            && self.id.len() <= 64
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c));
+        if !id_ok {
+            return Err(format!("invalid plugin id: {:?}", self.id));
+        }
+        if self.name.trim().is_empty() || self.command.trim().is_empty() {
+            return Err("name and command are required".to_string());
+        }
+        if let Some(scope) = self
+            .scopes
+            .iter()
+            .find(|s| !PLUGIN_SCOPES.contains(&s.as_str()))
+        {
+            return Err(format!("unknown scope: {scope}"));
+        }
+        Ok(())
+    }
+}
+
+/// バックエンド設定の `plugins` セクションのエントリ (プラグイン ID ごと)。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PluginState {
+    enabled: bool,
+    /// 有効化時にユーザーが承認したスコープ。
+    granted_scopes: Vec<String>,
+}
+
+/// 承認済みスコープで宣言スコープを賄えるか。
+fn covers(granted: &[String], requested: &[String]) -> bool {
+    requested.iter().all(|s| granted.contains(s))
+}
+
+/// フロントに見せるプラグインの状態。
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// manifest が要求するスコープ。
+    pub scopes: Vec<String>,
+    /// 有効化時に承認したスコープ。
+    pub granted_scopes: Vec<String>,
+    pub enabled: bool,
+    /// 要求スコープが承認時より増えている。再度有効化するまで起動しない。
+    pub needs_approval: bool,
+    pub running: bool,
+    pub pid: Option<u32>,
+    /// 直近の起動失敗 / 異常終了の内容。
+    pub last_error: Option<String>,
+}
+
+struct RunningPlugin {
+    child: Child,
+    token_hash: String,
+}
+
+pub struct PluginHost {
+    plugins_dir: PathBuf,
+    settings: Arc<SettingsStore>,
+    running: Mutex<HashMap<String, RunningPlugin>>,
+    /// 発行中トークンのハッシュ → 承認スコープ。
+    tokens: RwLock<HashMap<String, PluginTokenMarker>>,
+    errors: Mutex<HashMap<String, String>>,
+    /// HTTP API が受付を始めたか。始まる前の有効化は記録だけして後で起動する。
+    api_ready: AtomicBool,
+}
+
+impl PluginHost {
+    /// 有効 / 承認スコープはバックエンド設定の `plugins` セクションから読む。
+    /// 記録の無いプラグインは無効として扱う。
+    pub fn load(app_dir: &Path, settings: Arc<SettingsStore>) -> Self {
+        Self {
+            plugins_dir: app_dir.join(PLUGINS_DIR),
+            settings,
+            running: Mutex::new(HashMap::new()),
+            tokens: RwLock::new(HashMap::new()),
+            errors: Mutex::new(HashMap::new()),
+            api_ready: AtomicBool::new(false),
+        }
+    }
+
+    /// `plugins/*/plugin.json` を読む。壊れた manifest は warn して飛ばす。
+    fn scan(&self) -> Vec<(PathBuf, PluginManifest)> {
+        let Ok(entries) = std::fs::read_dir(&self.plugins_dir) else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        let mut plugins: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|dir| dir.is_dir())
+            .filter_map(|dir| {
+                let path = dir.join(MANIFEST_FILE);
+                let text = std::fs::read_to_string(&path).ok()?;
+                let manifest = serde_json::from_str::<PluginManifest>(&text)
+                    .map_err(|e| e.to_string())
+                    .and_then(|m| m.validate().map(|()| m));
+                match manifest {
+                    Ok(m) => Some((dir, m)),
+                    Err(e) => {
+                        tracing::warn!("[plugins] {}: {e}", path.display());
+                        None
+                    }
+                }
+            })
+            .collect();
+        plugins.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+        plugins.retain(|(dir, m)| {
+            let first = seen.insert(m.id.clone());
+            if !first {
+                tracing::warn!("[plugins] duplicate id {} in {}", m.id, dir.display());
+            }
+            first
+        });
+        plugins
+    }
+
+    fn find(&self, id: &str) -> Result<(PathBuf, PluginManifest), NoteDeckError> {
+        self.scan()
+            .into_iter()
+            .find(|(_, m)| m.id == id)
+            .ok_or_else(|| NoteDeckError::InvalidInput(format!("plugin not found: {id}")))
+    }
+
+    fn state(&self, id: &str) -> Option<PluginState> {
+        self.settings.read(|s| s.plugins.get(id).cloned())
+    }
+
+    /// 終了したプロセスを片付け、トークンを失効させる。
+    fn reap(&self) {
+        let mut running = self.running.lock().unwrap();
+        let exited: Vec<(String, String)> = running
+            .iter_mut()
+            .filter_map(|(id, p)| match p.child.try_wait() {
+                Ok(Some(status)) => Some((id.clone(), format!("exited: {status}"))),
+                Ok(None) => None,
+                Err(e) => Some((id.clone(), e.to_string())),
+            })
+            .collect();
+        for (id, reason) in exited {
+            if let Some(p) = running.remove(&id) {
+                self.tokens.write().unwrap().remove(&p.token_hash);
+            }
+            tracing::info!("[plugins] {id} {reason}");
+            self.errors.lock().unwrap().insert(id, reason);
+        }
+    }
+
+    fn info(&self, manifest: &PluginManifest) -> PluginInfo {
+        let state = self.state(&manifest.id).unwrap_or_default();
+        let pid = self
+            .running
+            .lock()
+            .unwrap()
+            .get(&manifest.id)
+            .map(|p| p.child.id());
+        PluginInfo {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            scopes: manifest.scopes.clone(),
+            needs_approval: state.enabled && !covers(&state.granted_scopes, &manifest.scopes),
+            granted_scopes: state.granted_scopes,
+            enabled: state.enabled,
+            running: pid.is_some(),
+            pid,
+            last_error: self.errors.lock().unwrap().get(&manifest.id).cloned(),
+        }
+    }
+
+    pub fn list(&self) -> Vec<PluginInfo> {
+        self.reap();
+        self.scan().iter().map(|(_, m)| self.info(m)).collect()
+    }
+
+    /// 有効化 (= 現在の宣言スコープを承認) / 無効化する。
+    pub fn set_enabled(&self, id: &str, enabled: bool) -> Result<PluginInfo, NoteDeckError> {
+        let (dir, manifest) = self.find(id)?;
+        self.settings.update(|s| {
+            let state = s.plugins.entry(id.to_string()).or_default();
+            state.enabled = enabled;
+            if enabled {
+                state.granted_scopes = manifest.scopes.clone();
+            }
+            Ok(())
+        })?;
+        self.stop(id);
+        self.errors.lock().unwrap().remove(id);
+        if enabled && self.api_ready.load(Ordering::SeqCst) {
+            self.start(&dir, &manifest);
+        }
+        Ok(self.info(&manifest))
+    }
+
+    /// HTTP API の受付開始後に呼ぶ。有効なプラグインをまとめて起動する。
+    pub fn start_enabled(&self) {
+        self.api_ready.store(true, Ordering::SeqCst);
+        for (dir, manifest) in self.scan() {
+            let state = self.state(&manifest.id);
+            if state.is_some_and(|s| s.enabled) {
+                self.start(&dir, &manifest);
+            }
+        }
+    }
+
+    /// 承認スコープでトークンを発行して起動する。失敗は last_error に残す。
+    fn start(&self, dir: &Path, manifest: &PluginManifest) {
+        let granted = self
+            .state(&manifest.id)
+            .map(|s| s.granted_scopes)
+            .unwrap_or_default();
+        if !covers(&granted, &manifest.scopes) {
+            self.errors.lock().unwrap().insert(
+                manifest.id.clone(),
+                "requested scopes changed; enable again to approve".to_string(),
+            );
+            return;
+        }
+        if self.running.lock().unwrap().contains_key(&manifest.id) {
+            return;
+        }
+
+        let token = new_token();
+        let token_hash = hash_hex(&token);
+        match spawn_process(dir, manifest, &token) {
+            Ok(child) => {
+                tracing::info!("[plugins] started {} (pid {})", manifest.id, child.id());
+                self.tokens.write().unwrap().insert(
+                    token_hash.clone(),
+                    PluginTokenMarker {
+                        plugin_id: Arc::from(manifest.id.as_str()),
+                        scopes: Arc::from(manifest.scopes.clone()),
+                    },
+                );
+                self.running
+                    .lock()
+                    .unwrap()
+                    .insert(manifest.id.clone(), RunningPlugin { child, token_hash });
+            }
+            Err(e) => {
+                tracing::warn!("[plugins] failed to start {}: {e}", manifest.id);
+                self.errors
+                    .lock()
+                    .unwrap()
+                    .insert(manifest.id.clone(), format!("failed to start: {e}"));
+            }
+        }
+    }
+
+    /// プロセスを止めてトークンを失効させる。
+    fn stop(&self, id: &str) {
+        let Some(mut plugin) = self.running.lock().unwrap().remove(id) else {
+            return;
+        };
+        self.tokens.write().unwrap().remove(&plugin.token_hash);
+        let _ = plugin.child.kill();
+        let _ = plugin.child.wait();
+        tracing::info!("[plugins] stopped {id}");
+    }
+
+    pub fn stop_all(&self) {
+        let ids: Vec<String> = self.running.lock().unwrap().keys().cloned().collect();
+        for id in ids {
+            self.stop(&id);
+        }
+    }
+
+    /// 提示されたトークンが発行中のプラグイントークンなら、その承認内容を返す。
+    pub fn verify(&self, presented: &str) -> Option<PluginTokenMarker> {
+        if !presented.starts_with(TOKEN_PREFIX) {
+            return None;
+        }
+        // ハッシュ同士の比較 (HashMap 引き) なので raw の比較時間は漏れない
+        self.tokens
+            .read()
+            .unwrap()
+            .get(&hash_hex(presented))
+            .cloned()
+    }
+}
+
+fn spawn_process(dir: &Path, manifest: &PluginManifest, token: &str) -> std::io::Result<Child> {
+    let program = if manifest.command.contains(['/', '\\']) {
+        dir.join(&manifest.command)
+    } else {
+        PathBuf::from(&manifest.command)
+    };
+    let log = std::fs::File::create(dir.join(LOG_FILE))?;
+    let mut command = Command::new(program);
+    command
+        .args(&manifest.args)
+        .current_dir(dir)
+        .env("NOTEDECK_API_URL", crate::http_server::api_base_url())
+        .env("NOTEDECK_API_TOKEN", token)
+        .env("NOTEDECK_PLUGIN_ID", &manifest.id)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // コンソールウィンドウを出さない (CREATE_NO_WINDOW)
+        command.creation_flags(0x0800_0000);
+    }
+    command.spawn()
+}
+
+fn new_token() -> String {
+    let raw: String = rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("{TOKEN_PREFIX}{raw}")
+}
+
+fn hash_hex(raw: &str) -> String {
+    let digest = Sha256::digest(raw.as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// インストール済みの外部プラグインを返す。
+#[tauri::command]
+#[specta::specta]
+pub fn plugin_list(host: State<'_, Arc<PluginHost>>) -> Vec<PluginInfo> {
+    host.list()
+}
+
+/// 外部プラグインを有効化 / 無効化する。有効化は manifest が宣言する
+/// スコープの承認を兼ね、HTTP API が動いていればすぐに起動する。
+#[tauri::command]
+#[specta::specta]
+pub fn plugin_set_enabled(
+    host: State<'_, Arc<PluginHost>>,
+    id: String,
+    enabled: bool,
+) -> Result<PluginInfo, NoteDeckError> {
+    host.set_enabled(&id, enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(app_dir: &Path) -> Arc<SettingsStore> {
+        Arc::new(SettingsStore::load(app_dir))
+    }
+
+    fn write_manifest(app_dir: &Path, dir: &str, json: &str) {
+        let dir = app_dir.join(PLUGINS_DIR).join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), json).unwrap();
+    }
+
+    #[test]
+    fn scans_valid_manifests_only() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "good",
+            r#"{"id":"com.example.good","name":"Good","command":"node","args":["index.js"],"scopes":["notes.read"]}"#,
+        );
+        write_manifest(
+            dir.path(),
+            "bad-scope",
+            r#"{"id":"bad","name":"Bad","command":"node","scopes":["everything"]}"#,
+        );
+        write_manifest(
+            dir.path(),
+            "bad-id",
+            r#"{"id":"Bad Id","name":"Bad","command":"node"}"#,
+        );
+        write_manifest(dir.path(), "broken", "not json");
+
+        let host = PluginHost::load(dir.path(), settings(dir.path()));
+        let plugins = host.list();
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0].id, "com.example.good");
+        assert!(!plugins[0].enabled);
+        assert!(!plugins[0].running);
+    }
+
+    #[test]
+    fn enabling_records_granted_scopes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "p",
+            r#"{"id":"p","name":"P","command":"node","scopes":["notes.read"]}"#,
+        );
+        let host = PluginHost::load(dir.path(), settings(dir.path()));
+        // API 受付前なので起動はしない
+        let info = host.set_enabled("p", true).unwrap();
+        assert!(info.enabled);
+        assert!(!info.running);
+        assert_eq!(info.granted_scopes, vec!["notes.read".to_string()]);
+
+        // manifest がスコープを増やしたら再承認が必要
+        write_manifest(
+            dir.path(),
+            "p",
+            r#"{"id":"p","name":"P","command":"node","scopes":["notes.read","notes.write"]}"#,
+        );
+        let reloaded = PluginHost::load(dir.path(), settings(dir.path()));
+        assert!(reloaded.list()[0].needs_approval);
+
+        assert!(host.set_enabled("missing", true).is_err());
+    }
+
+    #[test]
+    fn only_issued_tokens_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let host = PluginHost::load(dir.path(), settings(dir.path()));
+        let token = new_token();
+        host.tokens.write().unwrap().insert(
+            hash_hex(&token),
+            PluginTokenMarker {
+                plugin_id: Arc::from("p"),
+                scopes: Arc::from(vec!["notes.read".to_string()]),
+            },
+        );
+        assert_eq!(host.verify(&token).unwrap().plugin_id.as_ref(), "p");
+        assert!(host.verify(&new_token()).is_none());
+        assert!(host.verify("ndp_persistent").is_none());
+    }
+}
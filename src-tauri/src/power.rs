@@ -0,0 +1,343 @@
+//! OS のスリープ / 復帰に合わせたストリーム再接続。
+//!
+//! スリープ中に WebSocket は相手側で切られているが、復帰直後の socket は
+//! 生きているように見え、notecli の ping (30s) が失敗するまで再接続しない。
+//! 復帰を検知したらバックエンドで能動的に socket を作り直し、
+//! `nd:system-resumed` でフロントに catch-up (deckResumeSignal) を促す。
+//!
+//! 復帰の検知は 2 系統 (どちらが先に来ても RESUME_COOLDOWN 内の重複は捨てる):
+//! - Linux: logind の `PrepareForSleep(false)` シグナル (`gdbus monitor`)
+//! - 全プラットフォーム: 壁時計ジャンプ。スリープ中は tokio の interval が
+//!   止まる (monotonic clock が進まない) ので、tick 間の壁時計の経過が
+//!   間隔を大きく超えたら復帰とみなす
+//!
+//! 再接続対象は stream_connect / stream_set_mode で記録したアカウントのうち
+//! realtime のもの (ActiveStreams)。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+
+/// フロント (useDeckResume) が listen するイベント名。
+#[cfg_attr(mobile, allow(dead_code))]
+pub const RESUMED_EVENT: &str = "nd:system-resumed";
+
+/// 壁時計ジャンプ検知の tick 間隔。
+#[cfg_attr(mobile, allow(dead_code))]
+const CLOCK_TICK: Duration = Duration::from_secs(5);
+/// tick 間隔をこれ以上超えたらスリープ復帰とみなす。
+#[cfg_attr(mobile, allow(dead_code))]
+const CLOCK_JUMP_THRESHOLD: Duration = Duration::from_secs(20);
+/// logind シグナルと壁時計ジャンプの二重発火をまとめる窓。
+#[cfg_attr(mobile, allow(dead_code))]
+const RESUME_COOLDOWN: Duration = Duration::from_secs(30);
+/// polling を経由させる際の (実際には使われない) ポーリング間隔。
+#[cfg_attr(mobile, allow(dead_code))]
+const TRANSIENT_POLL_INTERVAL_MS: u64 = 60_000;
+/// オフライン退避中のポーリング間隔。復旧は network.rs が検知するので長くてよい。
+#[cfg_attr(mobile, allow(dead_code))]
+const PARKED_POLL_INTERVAL_MS: u64 = 10 * 60_000;
+
+#[cfg_attr(mobile, allow(dead_code))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemResumed {
+    /// 推定スリープ時間 (秒)。logind 経由など不明な場合は null。
+    pub slept_seconds: Option<u64>,
+}
+
+/// 接続中ストリームのモード記録。
+#[derive(Default)]
+pub struct ActiveStreams {
+    /// account_id → realtime か
+    modes: Mutex<HashMap<String, bool>>,
+    last_resume: Mutex<Option<Instant>>,
+}
+
+impl ActiveStreams {
+    /// stream_connect 時。既にモードが記録済みなら維持する。
+    pub fn connected(&self, account_id: &str) {
+        self.modes
+            .lock()
+            .unwrap()
+            .entry(account_id.to_string())
+            .or_insert(true);
+    }
+
+    pub fn disconnected(&self, account_id: &str) {
+        self.modes.lock().unwrap().remove(account_id);
+    }
+
+    pub fn set_mode(&self, account_id: &str, mode: &str) {
+        self.modes
+            .lock()
+            .unwrap()
+            .insert(account_id.to_string(), mode != "polling");
+    }
+
+    /// 接続中なら realtime か (true) polling か (false)。未接続は None。
+    #[cfg_attr(mobile, allow(dead_code))]
+    pub fn stream_mode(&self, account_id: &str) -> Option<bool> {
+        self.modes.lock().unwrap().get(account_id).copied()
+    }
+
+    /// 再接続対象 (realtime) のアカウント。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn realtime_accounts(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .modes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, realtime)| **realtime)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// 重複検知を捨てる。処理すべき復帰なら true。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn begin_resume(&self, now: Instant) -> bool {
+        let mut last = self.last_resume.lock().unwrap();
+        if last.is_some_and(|at| now.duration_since(at) < RESUME_COOLDOWN) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+/// tick 間の壁時計の経過から、スリープしていた時間を推定する。
+#[cfg_attr(mobile, allow(dead_code))]
+fn detect_clock_jump(prev: SystemTime, now: SystemTime) -> Option<Duration> {
+    let elapsed = now.duration_since(prev).ok()?;
+    let gap = elapsed.checked_sub(CLOCK_TICK)?;
+    (gap >= CLOCK_JUMP_THRESHOLD).then_some(gap)
+}
+
+/// `gdbus monitor` の 1 行から PrepareForSleep の引数を取り出す。
+/// true = スリープ開始、false = 復帰。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_prepare_for_sleep(line: &str) -> Option<bool> {
+    let (_, args) = line.split_once("PrepareForSleep")?;
+    match args.trim() {
+        "(true,)" => Some(true),
+        "(false,)" => Some(false),
+        _ => None,
+    }
+}
+
+/// realtime のストリームを購読を保ったまま作り直す。network.rs の回線復旧
+/// からも呼ぶ。
+#[cfg(not(mobile))]
+pub(crate) async fn reestablish_streams(app: &tauri::AppHandle) {
+    for_each_realtime(app, |streaming, account_id, host, token| async move {
+        // notecli の set_mode は購読を保ったまま接続を作り直す。いったん
+        // polling を経由させて、死んでいる WebSocket を確実に破棄する
+        streaming
+            .set_mode(
+                &account_id,
+                &host,
+                &token,
+                "polling",
+                Some(TRANSIENT_POLL_INTERVAL_MS),
+            )
+            .await?;
+        streaming
+            .set_mode(&account_id, &host, &token, "realtime", None)
+            .await
+    })
+    .await;
+}
+
+/// オフライン中は realtime のストリームを長間隔 polling に退避させ、
+/// notecli の再接続ループが失敗を繰り返さないようにする (購読は保たれる)。
+/// 復旧時に reestablish_streams で realtime へ戻す。
+#[cfg(not(mobile))]
+pub(crate) async fn park_streams(app: &tauri::AppHandle) {
+    for_each_realtime(app, |streaming, account_id, host, token| async move {
+        streaming
+            .set_mode(
+                &account_id,
+                &host,
+                &token,
+                "polling",
+                Some(PARKED_POLL_INTERVAL_MS),
+            )
+            .await
+    })
+    .await;
+}
+
+#[cfg(not(mobile))]
+async fn for_each_realtime<'a, F, Fut>(app: &'a tauri::AppHandle, op: F)
+where
+    F: Fn(&'a notecli::streaming::StreamingManager, String, String, String) -> Fut,
+    Fut: std::future::Future<Output = Result<(), notecli::error::NoteDeckError>>,
+{
+    use tauri::Manager;
+
+    let (Some(active), Some(app_state), Some(streaming)) = (
+        app.try_state::<ActiveStreams>(),
+        app.try_state::<crate::commands::AppState>(),
+        app.try_state::<notecli::streaming::StreamingManager>(),
+    ) else {
+        return;
+    };
+    let streaming = streaming.inner();
+    let db = app_state.db().await;
+    for account_id in active.realtime_accounts() {
+        let Ok((host, token)) = crate::commands::get_credentials(&db, &account_id) else {
+            continue;
+        };
+        if let Err(e) = op(streaming, account_id.clone(), host, token).await {
+            tracing::warn!(%account_id, "[power] stream mode switch failed: {e}");
+        }
+    }
+}
+
+/// 復帰処理: realtime のストリームを作り直し、フロントへ通知する。
+#[cfg(not(mobile))]
+async fn handle_resume(app: &tauri::AppHandle, slept: Option<Duration>) {
+    use tauri::{Emitter, Manager};
+
+    let Some(active) = app.try_state::<ActiveStreams>() else {
+        return;
+    };
+    if !active.begin_resume(Instant::now()) {
+        return;
+    }
+    tracing::info!(slept_secs = ?slept.map(|d| d.as_secs()), "[power] resumed from sleep");
+    reestablish_streams(app).await;
+
+    let payload = SystemResumed {
+        slept_seconds: slept.map(|d| d.as_secs()),
+    };
+    if let Err(e) = app.emit(RESUMED_EVENT, &payload) {
+        tracing::warn!("[power] emit failed: {e}");
+    }
+}
+
+/// スリープ復帰の監視を開始する。setup から 1 度だけ呼ぶ。
+#[cfg(not(mobile))]
+pub fn spawn_watcher(app: tauri::AppHandle) {
+    let clock_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(CLOCK_TICK);
+        let mut prev = SystemTime::now();
+        loop {
+            ticker.tick().await;
+            let now = SystemTime::now();
+            if let Some(slept) = detect_clock_jump(prev, now) {
+                handle_resume(&clock_app, Some(slept)).await;
+            }
+            prev = now;
+        }
+    });
+
+    #[cfg(target_os = "linux")]
+    spawn_logind_listener(app);
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
+
+/// logind の PrepareForSleep を購読する。gdbus が無い環境では何もしない
+/// (壁時計ジャンプ検知だけで動く)。
+#[cfg(target_os = "linux")]
+fn spawn_logind_listener(app: tauri::AppHandle) {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    let spawned = Command::new("gdbus")
+        .args([
+            "monitor",
+            "--system",
+            "--dest",
+            "org.freedesktop.login1",
+            "--object-path",
+            "/org/freedesktop/login1",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!("[power] gdbus unavailable, using clock-jump detection only: {e}");
+            return;
+        }
+    };
+    let Some(stdout) = child.stdout.take() else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if parse_prepare_for_sleep(&line) == Some(false) {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    handle_resume(&app, None).await;
+                });
+            }
+        }
+        let _ = child.wait();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_jump_detects_sleep_only() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        assert_eq!(detect_clock_jump(t0, t0 + CLOCK_TICK), None);
+        assert_eq!(detect_clock_jump(t0, t0 + Duration::from_secs(15)), None);
+        assert_eq!(
+            detect_clock_jump(t0, t0 + Duration::from_secs(605)),
+            Some(Duration::from_secs(600))
+        );
+        // 壁時計の巻き戻し (NTP 補正) は無視
+        assert_eq!(detect_clock_jump(t0, t0 - Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn parses_logind_signal() {
+        let line =
+            "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForSleep (false,)";
+        assert_eq!(parse_prepare_for_sleep(line), Some(false));
+        assert_eq!(
+            parse_prepare_for_sleep(&line.replace("false", "true")),
+            Some(true)
+        );
+        assert_eq!(parse_prepare_for_sleep("PrepareForShutdown (true,)"), None);
+    }
+
+    #[test]
+    fn tracks_realtime_accounts() {
+        let active = ActiveStreams::default();
+        active.connected("a");
+        active.connected("b");
+        active.set_mode("b", "polling");
+        // 再 connect でモードを上書きしない
+        active.connected("b");
+        active.connected("c");
+        active.disconnected("c");
+        assert_eq!(active.realtime_accounts(), vec!["a".to_string()]);
+        assert_eq!(active.stream_mode("a"), Some(true));
+        assert_eq!(active.stream_mode("b"), Some(false));
+        assert_eq!(active.stream_mode("c"), None);
+    }
+
+    #[test]
+    fn resume_is_debounced() {
+        let active = ActiveStreams::default();
+        let now = Instant::now();
+        assert!(active.begin_resume(now));
+        assert!(!active.begin_resume(now + Duration::from_secs(5)));
+        assert!(active.begin_resume(now + RESUME_COOLDOWN));
+    }
+}
@@ -0,0 +1,91 @@
+//! QR コードの生成。
+//!
+//! MiAuth の認証 URL をスマホで読み取ってログインする用途など、装飾の要らない
+//! QR を Rust 側で作って画像バイト列で返す。プロフィール共有の装飾付き QR は
+//! フロントの `qr-code-styling` で描く (`UserProfileQrCode.vue`)。
+
+use std::io::Cursor;
+
+use notecli::error::NoteDeckError;
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use serde::Deserialize;
+use specta::Type;
+
+use crate::commands::Result;
+
+/// 埋め込めるデータの上限 (バイト)。URL 用途には十分。
+const MAX_DATA_BYTES: usize = 2048;
+const DEFAULT_SIZE: u32 = 256;
+const MIN_SIZE: u32 = 64;
+const MAX_SIZE: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum QrFormat {
+    Png,
+    Svg,
+}
+
+fn encode(data: &str, format: QrFormat, size: u32) -> Result<Vec<u8>> {
+    if data.is_empty() || data.len() > MAX_DATA_BYTES {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "QR data must be 1..={MAX_DATA_BYTES} bytes"
+        )));
+    }
+    let code = QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)
+        .map_err(|e| NoteDeckError::InvalidInput(format!("QR encode failed: {e}")))?;
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    match format {
+        QrFormat::Svg => Ok(code
+            .render::<svg::Color>()
+            .min_dimensions(size, size)
+            .build()
+            .into_bytes()),
+        QrFormat::Png => {
+            let image = code
+                .render::<image::Luma<u8>>()
+                .min_dimensions(size, size)
+                .build();
+            let mut png = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(|e| NoteDeckError::InvalidInput(format!("PNG encode failed: {e}")))?;
+            Ok(png)
+        }
+    }
+}
+
+/// `data` を QR コード画像 (PNG / SVG) にする。`size` は一辺の最小ピクセル数。
+#[tauri::command]
+#[specta::specta]
+pub fn generate_qr(data: String, format: QrFormat, size: Option<u32>) -> Result<Vec<u8>> {
+    encode(&data, format, size.unwrap_or(DEFAULT_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_has_signature_and_requested_size() {
+        let png = encode("https://misskey.example/miauth/abc", QrFormat::Png, 200).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let image = image::load_from_memory(&png).unwrap();
+        assert!(image.width() >= 200);
+        assert_eq!(image.width(), image.height());
+    }
+
+    #[test]
+    fn svg_is_rendered_as_text() {
+        let svg = encode("https://misskey.example/@alice", QrFormat::Svg, 0).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("<svg"));
+    }
+
+    #[test]
+    fn empty_and_oversized_data_are_rejected() {
+        assert!(encode("", QrFormat::Svg, 256).is_err());
+        assert!(encode(&"a".repeat(MAX_DATA_BYTES + 1), QrFormat::Svg, 256).is_err());
+    }
+}
@@ -1,55 +1,340 @@
+//! Bridges HTTP API requests to the frontend (Pinia stores) via Tauri events.
+//!
+//! Flow:
+//!   HTTP handler → query_frontend() → emit "nd:query-request" `{ id, type, params }`
+//!   → Frontend (apiBridge) handles & emits "nd:query-response"
+//!     `{ id, ok: true, data }` / `{ id, ok: false, code, message }`
+//!   → 常駐 listener が [`PendingQueries`] から id の待ち手を引いて渡す → HTTP response
+//!
+//! - タイムアウトは query type ごと ([`QueryOptions::for_query`])。
+//! - WebView がリロードされると処理中の query は応答されずに消えるので、
+//!   フロントが listener を張り直した合図 (`nd:query-bridge-ready`) で
+//!   待機中の query を 1 回だけ再送する (副作用のある query は再送しない)。
+//! - メインウィンドウが破棄されたら待機中の query をすべて打ち切る。
+
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use serde::Deserialize;
 use serde_json::Value;
-use tauri::{AppHandle, Emitter, Listener};
-
-/// Bridges HTTP API requests to the frontend (Pinia stores) via Tauri events.
-///
-/// Flow:
-///   HTTP handler → query_frontend() → emit "nd:query-request"
-///   → Frontend handles & emits "nd:query-response-{id}"
-///   → query_frontend() receives via oneshot channel → HTTP response
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tokio::sync::oneshot;
+
+const REQUEST_EVENT: &str = "nd:query-request";
+const RESPONSE_EVENT: &str = "nd:query-response";
+/// フロントが `nd:query-request` の listener を張った (起動 / リロード後)。
+const READY_EVENT: &str = "nd:query-bridge-ready";
+
+/// 既定のタイムアウト。store を読むだけの query はこれで足りる。
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Capability 実行はユーザー確認ダイアログ待ちを挟みうるので長めにする。
+const CAPABILITY_EXECUTE_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// 期限内に応答が無かった。
+    Timeout,
+    /// 応答前に WebView がリロードされた (再送しない query / 再送済み)。
+    Interrupted,
+    /// メインウィンドウが無い / 閉じられた。
+    WindowClosed,
+    /// `nd:query-request` を emit できなかった。
+    Emit(String),
+    /// フロントがエラーを返した (`unknown_query` / `handler_failed` 等)。
+    Frontend { code: String, message: String },
+}
+
+impl QueryError {
+    /// HTTP 応答などに載せる機械可読なコード。
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Timeout => "query_timeout",
+            Self::Interrupted => "query_interrupted",
+            Self::WindowClosed => "window_closed",
+            Self::Emit(_) => "query_failed",
+            Self::Frontend { code, .. } => code,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => f.write_str("Query timed out"),
+            Self::Interrupted => f.write_str("Frontend reloaded before responding"),
+            Self::WindowClosed => f.write_str("Main window is closed"),
+            Self::Emit(e) => write!(f, "Failed to send query: {e}"),
+            Self::Frontend { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryOptions {
+    pub timeout: Duration,
+    /// WebView のリロードで応答が失われたとき 1 回だけ再送する。
+    pub retry_on_reload: bool,
+}
+
+impl QueryOptions {
+    /// query type ごとの既定値。
+    pub fn for_query(query_type: &str) -> Self {
+        match query_type {
+            // 実行済みかどうか分からないまま再送すると二重実行になりうる
+            "capabilities/execute" => Self {
+                timeout: CAPABILITY_EXECUTE_TIMEOUT,
+                retry_on_reload: false,
+            },
+            _ => Self {
+                timeout: DEFAULT_TIMEOUT,
+                retry_on_reload: true,
+            },
+        }
+    }
+}
+
+/// `nd:query-response` の payload。
+#[derive(Debug, Deserialize)]
+struct RawResponse {
+    id: String,
+    ok: bool,
+    #[serde(default)]
+    data: Value,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl RawResponse {
+    fn into_result(self) -> (String, Result<Value, QueryError>) {
+        let result = if self.ok {
+            Ok(self.data)
+        } else {
+            Err(QueryError::Frontend {
+                code: self.code.unwrap_or_else(|| "query_failed".to_string()),
+                message: self
+                    .message
+                    .unwrap_or_else(|| "Frontend returned an error".to_string()),
+            })
+        };
+        (self.id, result)
+    }
+}
+
+#[derive(Debug)]
+enum Signal {
+    Response(Result<Value, QueryError>),
+    Reloaded,
+    Cancelled,
+}
+
+/// 応答待ちの query (id → 待ち手)。
+#[derive(Default)]
+pub struct PendingQueries {
+    pending: Mutex<HashMap<String, oneshot::Sender<Signal>>>,
+}
+
+/// 登録した待ち手を drop 時に外す (タイムアウト / HTTP 切断で future が捨てられた場合)。
+struct PendingGuard<'a> {
+    registry: &'a PendingQueries,
+    id: String,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.registry.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl PendingQueries {
+    fn register(&self, id: String) -> (PendingGuard<'_>, oneshot::Receiver<Signal>) {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+        (PendingGuard { registry: self, id }, rx)
+    }
+
+    fn resolve(&self, id: &str, result: Result<Value, QueryError>) {
+        if let Some(tx) = self.pending.lock().unwrap().remove(id) {
+            let _ = tx.send(Signal::Response(result));
+        }
+    }
+
+    fn signal_all(&self, make: fn() -> Signal) {
+        for (_, tx) in self.pending.lock().unwrap().drain() {
+            let _ = tx.send(make());
+        }
+    }
+
+    /// 待機中の query の数。
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+/// レジストリを manage し、応答 / リロード通知の listener を張る。
+pub fn init(app: &AppHandle) {
+    app.manage(PendingQueries::default());
+
+    let handle = app.clone();
+    app.listen(RESPONSE_EVENT, move |event| {
+        match serde_json::from_str::<RawResponse>(event.payload()) {
+            Ok(raw) => {
+                let (id, result) = raw.into_result();
+                handle.state::<PendingQueries>().resolve(&id, result);
+            }
+            Err(e) => tracing::warn!("[query_bridge] malformed response: {e}"),
+        }
+    });
+
+    let handle = app.clone();
+    app.listen(READY_EVENT, move |_| {
+        handle
+            .state::<PendingQueries>()
+            .signal_all(|| Signal::Reloaded);
+    });
+}
+
+/// メインウィンドウの破棄時に呼ぶ。待機中の query を [`QueryError::WindowClosed`] で終える。
+#[cfg_attr(mobile, allow(dead_code))]
+pub fn cancel_all(app: &AppHandle) {
+    if let Some(registry) = app.try_state::<PendingQueries>() {
+        registry.signal_all(|| Signal::Cancelled);
+    }
+}
+
+/// 既定のオプション ([`QueryOptions::for_query`]) で query を投げる。
 pub async fn query_frontend(
     app: &AppHandle,
     query_type: &str,
     params: Value,
-) -> Result<Value, String> {
-    query_frontend_with_timeout(app, query_type, params, Duration::from_secs(5)).await
+) -> Result<Value, QueryError> {
+    query_frontend_with(app, query_type, params, QueryOptions::for_query(query_type)).await
 }
 
-/// [`query_frontend`] のタイムアウト指定版。capability 実行のように
-/// ユーザー確認ダイアログ待ちを挟みうる query で使う。
-pub async fn query_frontend_with_timeout(
+/// [`query_frontend`] のオプション指定版。
+pub async fn query_frontend_with(
     app: &AppHandle,
     query_type: &str,
     params: Value,
-    timeout: Duration,
-) -> Result<Value, String> {
-    let id = uuid::Uuid::new_v4().to_string();
-    let (tx, rx) = tokio::sync::oneshot::channel::<Value>();
-    let tx = Mutex::new(Some(tx));
-
-    let response_event = format!("nd:query-response-{id}");
-    let _listener = app.once(response_event, move |event| {
-        if let Some(tx) = tx.lock().unwrap().take() {
-            let data = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
-            let _ = tx.send(data);
+    options: QueryOptions,
+) -> Result<Value, QueryError> {
+    if app.get_webview_window("main").is_none() {
+        return Err(QueryError::WindowClosed);
+    }
+    let registry = app
+        .try_state::<PendingQueries>()
+        .ok_or(QueryError::WindowClosed)?;
+    let deadline = tokio::time::Instant::now() + options.timeout;
+    let mut retries = u8::from(options.retry_on_reload);
+
+    loop {
+        // 再送のたびに id を変え、リロード前の応答が紛れ込まないようにする
+        let id = uuid::Uuid::new_v4().to_string();
+        let (_guard, rx) = registry.register(id.clone());
+
+        app.emit(
+            REQUEST_EVENT,
+            serde_json::json!({
+                "id": id,
+                "type": query_type,
+                "params": params,
+            }),
+        )
+        .map_err(|e| QueryError::Emit(e.to_string()))?;
+
+        match tokio::time::timeout_at(deadline, rx).await {
+            Err(_) => return Err(QueryError::Timeout),
+            Ok(Ok(Signal::Response(result))) => return result,
+            Ok(Ok(Signal::Reloaded)) if retries > 0 => {
+                retries -= 1;
+                tracing::debug!("[query_bridge] frontend reloaded, retrying {query_type}");
+            }
+            Ok(Ok(Signal::Reloaded)) => return Err(QueryError::Interrupted),
+            Ok(Ok(Signal::Cancelled)) | Ok(Err(_)) => return Err(QueryError::WindowClosed),
         }
-    });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> (String, Result<Value, QueryError>) {
+        serde_json::from_str::<RawResponse>(json)
+            .unwrap()
+            .into_result()
+    }
+
+    #[test]
+    fn parses_response_envelope() {
+        let (id, result) = parse(r#"{"id":"a","ok":true,"data":[1,2]}"#);
+        assert_eq!(id, "a");
+        assert_eq!(result, Ok(serde_json::json!([1, 2])));
+
+        let (_, result) = parse(
+            r#"{"id":"b","ok":false,"code":"unknown_query","message":"Unknown query type: x"}"#,
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.code(), "unknown_query");
+        assert_eq!(err.to_string(), "Unknown query type: x");
+
+        // data 省略 (ハンドラが undefined を返した) は null
+        let (_, result) = parse(r#"{"id":"c","ok":true}"#);
+        assert_eq!(result, Ok(Value::Null));
+    }
+
+    #[test]
+    fn per_query_options() {
+        let execute = QueryOptions::for_query("capabilities/execute");
+        assert_eq!(execute.timeout, CAPABILITY_EXECUTE_TIMEOUT);
+        assert!(!execute.retry_on_reload);
+
+        let read = QueryOptions::for_query("deck/columns");
+        assert_eq!(read.timeout, DEFAULT_TIMEOUT);
+        assert!(read.retry_on_reload);
+    }
+
+    #[test]
+    fn resolves_only_the_matching_query() {
+        let registry = PendingQueries::default();
+        let (_a, mut rx_a) = registry.register("a".into());
+        let (_b, mut rx_b) = registry.register("b".into());
+
+        registry.resolve("a", Ok(Value::Bool(true)));
+        registry.resolve("unknown", Ok(Value::Null));
+        assert!(matches!(
+            rx_a.try_recv(),
+            Ok(Signal::Response(Ok(Value::Bool(true))))
+        ));
+        assert!(rx_b.try_recv().is_err());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn reload_and_cancel_signal_every_pending_query() {
+        let registry = PendingQueries::default();
+        let (_a, mut rx_a) = registry.register("a".into());
+        let (_b, mut rx_b) = registry.register("b".into());
+        registry.signal_all(|| Signal::Reloaded);
+        assert!(matches!(rx_a.try_recv(), Ok(Signal::Reloaded)));
+        assert!(matches!(rx_b.try_recv(), Ok(Signal::Reloaded)));
+        assert_eq!(registry.len(), 0);
+
+        let (_c, mut rx_c) = registry.register("c".into());
+        registry.signal_all(|| Signal::Cancelled);
+        assert!(matches!(rx_c.try_recv(), Ok(Signal::Cancelled)));
+    }
 
-    app.emit(
-        "nd:query-request",
-        serde_json::json!({
-            "id": id,
-            "type": query_type,
-            "params": params,
-        }),
-    )
-    .map_err(|e| e.to_string())?;
-
-    tokio::time::timeout(timeout, rx)
-        .await
-        .map_err(|_| "Query timed out".to_string())?
-        .map_err(|_| "Channel closed".to_string())
+    #[test]
+    fn dropping_the_guard_unregisters() {
+        let registry = PendingQueries::default();
+        let (guard, _rx) = registry.register("a".into());
+        assert_eq!(registry.len(), 1);
+        drop(guard);
+        assert_eq!(registry.len(), 0);
+    }
 }
@@ -15,6 +15,9 @@ use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 
 use crate::commands::{get_credentials, AppState};
+use crate::merged_timeline::MergedTimelines;
+use crate::note_patch::{self, NotePatch, UpdateSigs};
+use crate::perf_config::{PerformanceConfig, StreamDropPolicy};
 
 const MAX_READ_MODEL_ITEMS: usize = 200;
 /// `Warm` 状態が継続したらこの時間で `Suspended` に escalate する。
@@ -23,6 +26,9 @@ const WARM_GRACE: Duration = Duration::from_millis(8000);
 /// 高頻度 stream event を 1 フレーム分まとめて 1 個の query-delta event に
 /// 集約するための debounce 窓。連続イベント時に IPC 数を桁で減らせる。
 const DELTA_FLUSH_WINDOW: Duration = Duration::from_millis(16);
+/// 同時に開ける query の上限。カラム数より十分大きく、subscription map が
+/// 際限なく伸びないようにするための安全弁。
+const MAX_QUERIES: usize = 512;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(tag = "kind", rename_all = "camelCase")]
@@ -123,6 +129,10 @@ pub struct QueryDelta {
     /// from `stream-note-updated`. Items in the read model are not rewritten —
     /// consumers apply these to their own per-note state.
     pub updates: Vec<NoteUpdate>,
+    /// The flush window overflowed `StreamLimits` and events were dropped.
+    /// Consumers should refetch the latest page instead of trusting the delta
+    /// to be contiguous.
+    pub gap: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -145,10 +155,11 @@ pub struct NoteCapture {
     pub update: NoteUpdateBody,
 }
 
+/// flush window 内の capture をノート単位に畳んだもの (`note_patch::fold`)。
 #[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
 #[serde(rename_all = "camelCase")]
 pub struct NoteCaptureBatch {
-    pub captures: Vec<NoteCapture>,
+    pub patches: Vec<NotePatch>,
 }
 
 #[derive(Debug)]
@@ -175,9 +186,42 @@ struct QueryEntry {
 
 #[derive(Debug, Default)]
 struct PendingDelta {
-    inserts: Vec<QueryItem>,
+    inserts: VecDeque<QueryItem>,
     deletes: Vec<String>,
-    updates: Vec<NoteUpdate>,
+    updates: VecDeque<NoteUpdate>,
+    gap: bool,
+}
+
+/// flush 窓あたりに溜める stream 変更の上限 (`PerformanceConfig` から同期)。
+/// 連合のバースト (数千ノート/秒) でも flusher が追いつくまでのメモリを
+/// 一定に抑える。溢れた分は `drop_policy` に従って捨て、delta に gap を立てる。
+#[derive(Debug, Clone, Copy)]
+pub struct StreamLimits {
+    pub pending_max: usize,
+    pub captures_max: usize,
+    pub drop_policy: StreamDropPolicy,
+}
+
+impl Default for StreamLimits {
+    fn default() -> Self {
+        PerformanceConfig::default().stream_limits()
+    }
+}
+
+/// 上限付きで積む。何か捨てたら true。
+fn push_bounded<T>(buf: &mut VecDeque<T>, item: T, limits: &StreamLimits, max: usize) -> bool {
+    if buf.len() < max {
+        buf.push_back(item);
+        return false;
+    }
+    match limits.drop_policy {
+        StreamDropPolicy::DropOldest => {
+            buf.pop_front();
+            buf.push_back(item);
+        }
+        StreamDropPolicy::DropNewest => {}
+    }
+    true
 }
 
 #[derive(Default)]
@@ -194,7 +238,15 @@ struct QueryRuntimeInner {
     /// Pending per-note capture updates (subNote 経由)。channel-bound な
     /// query_id を持たないので、QueryEntry とは別に flat な Vec で管理し、
     /// flusher が 1 つの NoteCaptureBatch にまとめて emit する。
-    pending_captures: Vec<NoteCapture>,
+    pending_captures: VecDeque<NoteCapture>,
+    /// 上限超過で捨てた capture の数 (drain 時にログへ出してリセット)
+    dropped_captures: usize,
+    limits: StreamLimits,
+    /// channel / capture 両経路の重複 noteUpdated を弾く直近 sig。
+    update_sigs: UpdateSigs,
+    /// account_id → 自分の user id。capture を patch に畳むとき自分の
+    /// リアクション / 投票を見分けるのに使う (stream_sub_note で登録)。
+    self_user_ids: HashMap<String, String>,
 }
 
 #[derive(Default)]
@@ -227,14 +279,23 @@ pub async fn run_delta_flusher(app: AppHandle) {
         let Some(runtime) = app.try_state::<QueryRuntime>() else {
             return;
         };
-        for delta in runtime.drain_pending() {
+        let deltas = runtime.drain_pending();
+        // merged タイムラインは配下 query の delta から組み立てる
+        if let Some(merged) = app.try_state::<MergedTimelines>() {
+            for merged_delta in merged.route(&deltas) {
+                if let Err(e) = merged_delta.emit(&app) {
+                    tracing::warn!("[merged-timeline-delta] emit failed: {e}");
+                }
+            }
+        }
+        for delta in deltas {
             if let Err(e) = delta.emit(&app) {
                 tracing::warn!("[query-delta] emit failed: {e}");
             }
         }
-        let captures = runtime.drain_captures();
-        if !captures.is_empty() {
-            if let Err(e) = (NoteCaptureBatch { captures }).emit(&app) {
+        let patches = runtime.drain_note_patches();
+        if !patches.is_empty() {
+            if let Err(e) = (NoteCaptureBatch { patches }).emit(&app) {
                 tracing::warn!("[note-capture-batch] emit failed: {e}");
             }
         }
@@ -257,6 +318,11 @@ impl QueryRuntime {
             return Ok(snapshot(entry));
         }
 
+        if inner.entries.len() >= MAX_QUERIES {
+            return Err(runtime_error(format!(
+                "too many open queries (max {MAX_QUERIES})"
+            )));
+        }
         let query_id = format!("q:{}", uuid::Uuid::new_v4());
         let entry = QueryEntry {
             query_id: query_id.clone(),
@@ -326,6 +392,25 @@ impl QueryRuntime {
         Ok(Some((account_id(&entry.key).to_string(), subscription_id)))
     }
 
+    /// キーで開いている query の stream subscription。開いていなければ None。
+    pub fn stream_subscription_for_key(&self, key: &QueryKey) -> Option<String> {
+        let canonical_key = canonicalize_key(key).ok()?;
+        let inner = self.lock().ok()?;
+        let query_id = inner.ids_by_key.get(&canonical_key)?;
+        inner.entries.get(query_id)?.source_subscription_id.clone()
+    }
+
+    /// subscription が紐づく query の正規化キー。automation が「どの列に
+    /// 流れたノートか」を照合するのに使う。
+    pub fn canonical_key_for_subscription(&self, subscription_id: &str) -> Option<String> {
+        let inner = self.lock().ok()?;
+        let query_id = inner.query_ids_by_subscription.get(subscription_id)?;
+        inner
+            .entries
+            .get(query_id)
+            .map(|entry| entry.canonical_key.clone())
+    }
+
     pub fn set_runtime_state(
         &self,
         query_id: &str,
@@ -444,20 +529,36 @@ impl QueryRuntime {
             let Ok(mut inner) = self.inner.lock() else {
                 return false;
             };
-            inner.pending_captures.push(NoteCapture {
+            if !inner
+                .update_sigs
+                .accept(&capture.note_id, &capture.update)
+            {
+                return false;
+            }
+            let inner = &mut *inner;
+            let capture = NoteCapture {
                 account_id: capture.account_id.clone(),
                 note_id: capture.note_id.clone(),
                 update: capture.update.clone(),
-            });
+            };
+            if push_bounded(
+                &mut inner.pending_captures,
+                capture,
+                &inner.limits,
+                inner.limits.captures_max,
+            ) {
+                inner.dropped_captures += 1;
+            }
             return true;
         }
 
         let Some(change) = StreamChange::from_event(event) else {
             return false;
         };
-        let Ok(mut inner) = self.inner.lock() else {
+        let Ok(mut guard) = self.inner.lock() else {
             return false;
         };
+        let inner = &mut *guard;
         let Some(query_id) = inner
             .query_ids_by_subscription
             .get(change.subscription_id)
@@ -468,7 +569,16 @@ impl QueryRuntime {
         let Some(entry) = inner.entries.get_mut(&query_id) else {
             return false;
         };
-        if change.apply(entry) {
+        // Suspended の query が受け取らなかった更新は記録しない (同じ更新が
+        // capture 経由で届いたときに重複扱いしないため)
+        if let StreamChangeKind::Update(update) = &change.kind {
+            if entry.runtime_state != QueryRuntimeState::Suspended
+                && !inner.update_sigs.accept(&update.note_id, &update.update)
+            {
+                return false;
+            }
+        }
+        if change.apply(entry, &inner.limits) {
             inner.pending_query_ids.insert(query_id);
             true
         } else {
@@ -482,7 +592,41 @@ impl QueryRuntime {
         let Ok(mut inner) = self.inner.lock() else {
             return Vec::new();
         };
-        std::mem::take(&mut inner.pending_captures)
+        let dropped = std::mem::take(&mut inner.dropped_captures);
+        if dropped > 0 {
+            tracing::warn!(dropped, "[query-runtime] note capture buffer overflowed");
+        }
+        inner.pending_captures.drain(..).collect()
+    }
+
+    pub fn set_stream_limits(&self, limits: StreamLimits) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.limits = limits;
+        }
+    }
+
+    /// Drain pending captures folded into per-note patches.
+    pub fn drain_note_patches(&self) -> Vec<NotePatch> {
+        let captures = self.drain_captures();
+        if captures.is_empty() {
+            return Vec::new();
+        }
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        note_patch::fold(captures, &inner.self_user_ids)
+    }
+
+    pub fn has_self_user_id(&self, account_id: &str) -> bool {
+        self.inner
+            .lock()
+            .is_ok_and(|inner| inner.self_user_ids.contains_key(account_id))
+    }
+
+    pub fn set_self_user_id(&self, account_id: String, user_id: String) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.self_user_ids.insert(account_id, user_id);
+        }
     }
 
     /// Drain pending deltas across all queries with pending changes.
@@ -503,9 +647,10 @@ impl QueryRuntime {
             out.push(QueryDelta {
                 query_id: entry.query_id.clone(),
                 revision: entry.revision,
-                inserts: pending.inserts,
+                inserts: pending.inserts.into(),
                 deletes: pending.deletes,
-                updates: pending.updates,
+                updates: pending.updates.into(),
+                gap: pending.gap,
             });
         }
         out
@@ -578,7 +723,7 @@ impl<'a> StreamChange<'a> {
     /// recent_ids / id_set / revision を即時更新しつつ、emit するための変更を
     /// `entry.pending` に積む。返り値は「flusher を起こすべきか」のフラグ
     /// (= 何かが pending に入ったか)。
-    fn apply(self, entry: &mut QueryEntry) -> bool {
+    fn apply(self, entry: &mut QueryEntry, limits: &StreamLimits) -> bool {
         // Suspended カラムには何も書き込まない (set_runtime_state(Suspended)
         // で recent_ids/pending を空にしてあるため)。WebSocket subscription
         // 自体も suspend されているはずだが、レース対策として gate しておく。
@@ -601,11 +746,10 @@ impl<'a> StreamChange<'a> {
                     }
                 }
                 entry.revision = entry.revision.saturating_add(1);
-                entry
-                    .pending
-                    .get_or_insert_with(PendingDelta::default)
-                    .inserts
-                    .push(item);
+                let pending = entry.pending.get_or_insert_with(PendingDelta::default);
+                if push_bounded(&mut pending.inserts, item, limits, limits.pending_max) {
+                    pending.gap = true;
+                }
                 true
             }
             StreamChangeKind::Delete(id) => {
@@ -623,11 +767,10 @@ impl<'a> StreamChange<'a> {
             }
             StreamChangeKind::Update(update) => {
                 entry.revision = entry.revision.saturating_add(1);
-                entry
-                    .pending
-                    .get_or_insert_with(PendingDelta::default)
-                    .updates
-                    .push(update);
+                let pending = entry.pending.get_or_insert_with(PendingDelta::default);
+                if push_bounded(&mut pending.updates, update, limits, limits.pending_max) {
+                    pending.gap = true;
+                }
                 true
             }
         }
@@ -963,7 +1106,7 @@ fn snapshot(entry: &QueryEntry) -> QuerySnapshot {
     }
 }
 
-fn canonicalize_key(key: &QueryKey) -> Result<String, NoteDeckError> {
+pub(crate) fn canonicalize_key(key: &QueryKey) -> Result<String, NoteDeckError> {
     serde_json::to_string(key).map_err(|e| runtime_error(format!("invalid query key: {e}")))
 }
 
@@ -971,7 +1114,7 @@ fn runtime_error(message: impl Into<String>) -> NoteDeckError {
     NoteDeckError::InvalidInput(message.into())
 }
 
-fn account_id(key: &QueryKey) -> &str {
+pub(crate) fn account_id(key: &QueryKey) -> &str {
     match key {
         QueryKey::Timeline { account_id, .. }
         | QueryKey::Antenna { account_id, .. }
@@ -1164,6 +1307,44 @@ mod tests {
         assert!(drained[0].deletes.is_empty());
     }
 
+    /// channel 経由で受けた更新と同じものが capture 経由で届いても二重に
+    /// 積まない。capture は自分の user id を見て patch に畳まれる。
+    #[test]
+    fn capture_duplicate_of_channel_update_is_dropped() {
+        use notecli::streaming::StreamNoteCaptureEvent;
+
+        let capture = |user: &str| {
+            StreamEvent::NoteCaptureUpdated(Box::new(StreamNoteCaptureEvent {
+                account_id: "acct-1".into(),
+                note_id: "n1".into(),
+                update: NoteUpdateBody::Reacted(NoteReactedBody {
+                    reaction: ":+1:".into(),
+                    emoji: None,
+                    user_id: Some(user.into()),
+                }),
+            }))
+        };
+        let rt = QueryRuntime::default();
+        rt.set_self_user_id("acct-1".into(), "me".into());
+        let s = open_home(&rt, "acct-1");
+        rt.attach_stream_subscription(&s.query_id, "sub-A".into())
+            .unwrap();
+
+        assert!(rt.ingest_stream_event(&reaction_event("sub-A", "n1")));
+        assert!(!rt.ingest_stream_event(&capture("u1")));
+        assert!(rt.ingest_stream_event(&capture("me")));
+
+        let patches = rt.drain_note_patches();
+        assert_eq!(patches.len(), 1);
+        assert!(patches[0].reactions.is_empty());
+        assert_eq!(
+            patches[0].my_reaction,
+            Some(crate::note_patch::MyReactionChange::Set {
+                reaction: ":+1:".into()
+            })
+        );
+    }
+
     /// T7: MAX_READ_MODEL_ITEMS + 1 件 insert で recent_ids は MAX に切り詰められる。
     #[test]
     fn truncate_at_max() {
@@ -1204,6 +1385,54 @@ mod tests {
         assert_eq!(third.len(), 1);
     }
 
+    /// flush 窓の上限を超えたら drop_policy に従って捨て、gap を立てる。
+    #[test]
+    fn pending_overflow_drops_by_policy_and_marks_gap() {
+        let rt = QueryRuntime::default();
+        let s = open_home(&rt, "acct-1");
+        rt.attach_stream_subscription(&s.query_id, "sub-A".into())
+            .unwrap();
+        let limits = |drop_policy| StreamLimits {
+            pending_max: 2,
+            captures_max: 2,
+            drop_policy,
+        };
+        let insert_ids = |delta: &QueryDelta| -> Vec<String> {
+            delta.inserts.iter().map(|i| i.id().to_string()).collect()
+        };
+
+        rt.set_stream_limits(limits(StreamDropPolicy::DropOldest));
+        for id in ["n1", "n2", "n3"] {
+            rt.ingest_stream_event(&note_event("sub-A", id));
+        }
+        let deltas = rt.drain_pending();
+        assert_eq!(insert_ids(&deltas[0]), vec!["n2", "n3"]);
+        assert!(deltas[0].gap);
+
+        rt.set_stream_limits(limits(StreamDropPolicy::DropNewest));
+        for id in ["n4", "n5", "n6"] {
+            rt.ingest_stream_event(&note_event("sub-A", id));
+        }
+        let deltas = rt.drain_pending();
+        assert_eq!(insert_ids(&deltas[0]), vec!["n4", "n5"]);
+        assert!(deltas[0].gap);
+
+        // 上限内なら gap は立たない
+        rt.ingest_stream_event(&note_event("sub-A", "n7"));
+        assert!(!rt.drain_pending()[0].gap);
+    }
+
+    #[test]
+    fn open_refuses_beyond_max_queries() {
+        let rt = QueryRuntime::default();
+        for i in 0..MAX_QUERIES {
+            open_home(&rt, &format!("acct-{i}"));
+        }
+        assert!(rt.open(home_key("acct-overflow")).is_err());
+        // 既存 key の再 open は refcount を増やすだけなので通る
+        assert!(rt.open(home_key("acct-0")).is_ok());
+    }
+
     /// T9: read_model_snapshot は limit を尊重し、None なら MAX_READ_MODEL_ITEMS まで返す。
     #[test]
     fn read_model_snapshot_respects_limit() {
@@ -0,0 +1,242 @@
+//! クイック投稿ミニウィンドウ。
+//!
+//! デッキ全体を復元せずに投稿できるよう、投稿フォームだけを載せた小さな
+//! webview window をバックエンドが管理する。初回は on demand で生成し、
+//! 閉じても破棄せず hide して次回の表示を即時にする (close-to-tray の
+//! on_window_event が hide に変換する)。位置はホットキー押下時 / トレイ
+//! クリック時のカーソル付近 (モニター内にクランプ)。
+//!
+//! フロント側は `/quick-post` ルート (MkPostForm の inline 表示)。メイン
+//! ウィンドウの capability (default / desktop) からは外し、
+//! `capabilities/quick-post.json` で投稿フォームに要る権限だけを渡す。
+//! アプリのコマンドは capability では絞れない (app manifest を持たない) ので、
+//! `ALLOWED_COMMANDS` 以外は lib.rs の invoke_handler が [`allows`] で拒否する。
+
+use notecli::error::NoteDeckError;
+
+/// ミニウィンドウの label。`capabilities/quick-post.json` の windows と同じ。
+pub const QUICK_POST_LABEL: &str = "quick-post";
+
+/// ミニウィンドウから呼べるアプリのコマンド。
+const ALLOWED_COMMANDS: &[&str] = &[
+    // アカウント一覧 / 表示設定の読み取り
+    "load_accounts",
+    "read_notedeck_json",
+    "settings_get",
+    // 絵文字の補完と表示
+    "api_get_server_emojis",
+    "api_resolve_emojis",
+    "ack_emojis",
+    // 文字数上限などのポリシー
+    "api_get_self",
+    "api_get_user_policies",
+    "api_get_meta_detail",
+    // 下書きの自動保存
+    "api_get_drafts",
+    "api_create_draft",
+    "api_update_draft",
+    "api_delete_draft",
+    // 投稿 / 添付
+    "api_create_note",
+    "api_upload_file",
+    "api_upload_file_from_path",
+    // 投稿後 / キャンセルで閉じる
+    "quick_post_hide",
+];
+
+/// `window` から `command` を呼んでよいか。ミニウィンドウ以外は制限しない。
+pub fn allows(window: &str, command: &str) -> bool {
+    window != QUICK_POST_LABEL || ALLOWED_COMMANDS.contains(&command)
+}
+
+/// 表示中のウィンドウへ対象アカウントの切り替えを伝えるイベント。
+#[cfg_attr(mobile, allow(dead_code))]
+const OPEN_EVENT: &str = "nd:quick-post-open";
+
+/// ミニウィンドウの論理サイズ。
+#[cfg_attr(mobile, allow(dead_code))]
+const WINDOW_WIDTH: f64 = 420.0;
+#[cfg_attr(mobile, allow(dead_code))]
+const WINDOW_HEIGHT: f64 = 280.0;
+
+/// カーソルとウィンドウの間隔 / モニター端からの最小余白 (物理px)。
+#[cfg_attr(mobile, allow(dead_code))]
+const CURSOR_GAP: i32 = 16;
+#[cfg_attr(mobile, allow(dead_code))]
+const EDGE_MARGIN: i32 = 8;
+
+/// カーソル付近の配置位置 (物理px, ウィンドウ左上) を求める。
+/// 基本はカーソルの下に水平中央揃えで置き、下に収まらなければ上に出す
+/// (タスクバー下端のトレイから開いた場合)。最後にモニター内へクランプする。
+#[cfg_attr(mobile, allow(dead_code))]
+fn place_near_cursor(
+    cursor: (i32, i32),
+    window: (i32, i32),
+    monitor_origin: (i32, i32),
+    monitor_size: (i32, i32),
+) -> (i32, i32) {
+    let (cx, cy) = cursor;
+    let (w, h) = window;
+    let (mx, my) = monitor_origin;
+    let (mw, mh) = monitor_size;
+
+    let min_x = mx + EDGE_MARGIN;
+    let max_x = (mx + mw - w - EDGE_MARGIN).max(min_x);
+    let min_y = my + EDGE_MARGIN;
+    let max_y = (my + mh - h - EDGE_MARGIN).max(min_y);
+
+    let x = (cx - w / 2).clamp(min_x, max_x);
+    let below = cy + CURSOR_GAP;
+    let y = if below + h <= my + mh - EDGE_MARGIN {
+        below
+    } else {
+        cy - CURSOR_GAP - h
+    };
+    (x, y.clamp(min_y, max_y))
+}
+
+#[cfg(not(mobile))]
+fn route(account_id: Option<&str>) -> String {
+    match account_id {
+        Some(id) => format!("quick-post?account={}", urlencoding_component(id)),
+        None => "quick-post".to_string(),
+    }
+}
+
+/// account_id はサーバー発行の英数字 ID だが、念のためクエリ用にエスケープする。
+#[cfg(not(mobile))]
+fn urlencoding_component(s: &str) -> String {
+    url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+/// ミニウィンドウを開く (なければ生成、あれば再表示)。account_id を渡すと
+/// そのアカウントでフォームを開く (None はフロント側のアクティブアカウント)。
+#[cfg(not(mobile))]
+pub fn open<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    account_id: Option<&str>,
+) -> tauri::Result<()> {
+    use tauri::{Emitter, Manager};
+
+    let window = match app.get_webview_window(QUICK_POST_LABEL) {
+        Some(window) => {
+            if let Err(e) = window.emit(OPEN_EVENT, account_id) {
+                tracing::warn!("[quick-post] open emit failed: {e}");
+            }
+            window
+        }
+        None => tauri::WebviewWindowBuilder::new(
+            app,
+            QUICK_POST_LABEL,
+            tauri::WebviewUrl::App(route(account_id).into()),
+        )
+        .title("クイック投稿")
+        .inner_size(WINDOW_WIDTH, WINDOW_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()?,
+    };
+
+    // 位置決めは物理px同士で行う (#721 と同じくスケール誤報告を避ける)
+    if let Ok(cursor) = app.cursor_position() {
+        if let Ok(Some(monitor)) = app.monitor_from_point(cursor.x, cursor.y) {
+            let scale = monitor.scale_factor();
+            let size = (
+                (WINDOW_WIDTH * scale) as i32,
+                (WINDOW_HEIGHT * scale) as i32,
+            );
+            let origin = monitor.position();
+            let extent = monitor.size();
+            let (x, y) = place_near_cursor(
+                (cursor.x as i32, cursor.y as i32),
+                size,
+                (origin.x, origin.y),
+                (extent.width as i32, extent.height as i32),
+            );
+            let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+        }
+    }
+    window.show()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+/// ミニウィンドウを開く。モバイルはマルチウィンドウを持たないため未対応。
+#[tauri::command]
+#[specta::specta]
+pub fn quick_post_open(
+    app: tauri::AppHandle,
+    account_id: Option<String>,
+) -> Result<(), NoteDeckError> {
+    #[cfg(not(mobile))]
+    {
+        open(&app, account_id.as_deref())
+            .map_err(|e| NoteDeckError::InvalidInput(format!("quick post window: {e}")))
+    }
+    #[cfg(mobile)]
+    {
+        let _ = (app, account_id);
+        Err(NoteDeckError::InvalidInput(
+            "quick post window is not available on mobile".to_string(),
+        ))
+    }
+}
+
+/// 投稿完了 / キャンセル時にミニウィンドウを隠す (破棄はしない)。
+#[tauri::command]
+#[specta::specta]
+pub fn quick_post_hide(app: tauri::AppHandle) {
+    #[cfg(not(mobile))]
+    {
+        use tauri::Manager;
+        if let Some(window) = app.get_webview_window(QUICK_POST_LABEL) {
+            let _ = window.hide();
+        }
+    }
+    #[cfg(mobile)]
+    let _ = app;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: ((i32, i32), (i32, i32)) = ((0, 0), (1920, 1080));
+
+    #[test]
+    fn only_composer_commands_are_allowed_in_the_mini_window() {
+        assert!(allows(QUICK_POST_LABEL, "api_create_note"));
+        assert!(allows(QUICK_POST_LABEL, "quick_post_hide"));
+        assert!(!allows(QUICK_POST_LABEL, "api_delete_note"));
+        assert!(!allows(QUICK_POST_LABEL, "backup_import"));
+        assert!(allows("main", "backup_import"));
+    }
+
+    #[test]
+    fn places_below_cursor_centered() {
+        let (x, y) = place_near_cursor((960, 300), (420, 280), MONITOR.0, MONITOR.1);
+        assert_eq!(x, 960 - 210);
+        assert_eq!(y, 300 + CURSOR_GAP);
+    }
+
+    /// タスクバー下端のトレイから開いた場合はカーソルの上に出る。
+    #[test]
+    fn flips_above_when_no_room_below() {
+        let (_, y) = place_near_cursor((1800, 1070), (420, 280), MONITOR.0, MONITOR.1);
+        assert_eq!(y, 1070 - CURSOR_GAP - 280);
+    }
+
+    #[test]
+    fn clamps_into_monitor_bounds() {
+        let (x, _) = place_near_cursor((1910, 300), (420, 280), MONITOR.0, MONITOR.1);
+        assert_eq!(x, 1920 - 420 - EDGE_MARGIN);
+
+        // セカンダリモニター (負座標) の左端
+        let (x, y) = place_near_cursor((-1900, 5), (420, 280), (-1920, 0), (1920, 1080));
+        assert_eq!(x, -1920 + EDGE_MARGIN);
+        assert_eq!(y, 5 + CURSOR_GAP);
+    }
+}
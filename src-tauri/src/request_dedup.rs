@@ -0,0 +1,205 @@
+//! 同一リクエストの合流と短 TTL メモ化。
+//!
+//! 複数カラムが同じタイムライン / ユーザーをほぼ同時に読むと、同じ Misskey
+//! API が並んで飛ぶ。`ImageCache` の inflight dedup と同じく、実行中の
+//! 同一キーには後続を相乗りさせ、完了後 `RECENT_TTL` の間は結果を使い回す。
+//!
+//! 失敗は共有しない: 先行リクエストが失敗 (または中断) したら、待っていた側は
+//! 自分で取りに行く。エラー型を Clone にできない / 失敗をキャッシュしたくない
+//! ため。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use notecli::models::{NormalizedNote, NormalizedUser};
+use tokio::sync::{watch, Mutex};
+
+/// 完了した結果を使い回す期間。
+const RECENT_TTL: Duration = Duration::from_secs(1);
+
+/// 先行リクエストの結果。`Some(None)` は失敗 (待機側は自前で取得する)。
+type Slot<T> = Option<Option<T>>;
+
+struct CoalescerState<T> {
+    inflight: HashMap<String, watch::Receiver<Slot<T>>>,
+    recent: HashMap<String, (Instant, T)>,
+}
+
+/// キー単位で実行中のリクエストを合流させる。
+pub struct Coalescer<T> {
+    ttl: Duration,
+    state: Mutex<CoalescerState<T>>,
+}
+
+impl<T: Clone> Coalescer<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CoalescerState {
+                inflight: HashMap::new(),
+                recent: HashMap::new(),
+            }),
+        }
+    }
+
+    /// `key` の結果を返す。同じキーが実行中ならその完了を待ち、直近に
+    /// 成功していればその結果を返す。どちらでもなければ `fetch` を実行する。
+    pub async fn run<E, F, Fut>(&self, key: String, fetch: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        let ttl = self.ttl;
+        state
+            .recent
+            .retain(|_, (at, _)| now.duration_since(*at) < ttl);
+        if let Some((_, value)) = state.recent.get(&key) {
+            return Ok(value.clone());
+        }
+
+        // 送信側が結果を送らずに drop された (呼び出しが中断された) 枠は捨てる
+        let waiting = state
+            .inflight
+            .get(&key)
+            .filter(|rx| rx.has_changed().is_ok())
+            .cloned();
+        if let Some(mut rx) = waiting {
+            drop(state);
+            if let Ok(slot) = rx.wait_for(Option::is_some).await {
+                if let Some(Some(value)) = slot.as_ref() {
+                    return Ok(value.clone());
+                }
+            }
+            return fetch().await;
+        }
+
+        let (tx, rx) = watch::channel(None);
+        state.inflight.insert(key.clone(), rx);
+        drop(state);
+
+        let result = fetch().await;
+
+        let mut state = self.state.lock().await;
+        state.inflight.remove(&key);
+        if let Ok(value) = &result {
+            state.recent.insert(key, (Instant::now(), value.clone()));
+        }
+        drop(state);
+        let _ = tx.send(Some(result.as_ref().ok().cloned()));
+        result
+    }
+}
+
+/// IPC コマンドが共有する合流器。
+pub struct RequestDedup {
+    pub timelines: Coalescer<Vec<NormalizedNote>>,
+    pub users: Coalescer<NormalizedUser>,
+}
+
+impl Default for RequestDedup {
+    fn default() -> Self {
+        Self {
+            timelines: Coalescer::new(RECENT_TTL),
+            users: Coalescer::new(RECENT_TTL),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn concurrent_calls_share_one_fetch() {
+        let coalescer = Arc::new(Coalescer::<u32>::new(Duration::from_secs(1)));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (release_tx, release_rx) = watch::channel(false);
+
+        let first = {
+            let coalescer = coalescer.clone();
+            let calls = calls.clone();
+            let mut release_rx = release_rx.clone();
+            tokio::spawn(async move {
+                coalescer
+                    .run("k".to_string(), || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        let _ = release_rx.wait_for(|v| *v).await;
+                        Ok::<_, String>(7)
+                    })
+                    .await
+            })
+        };
+        // 先行リクエストが inflight に載るのを待つ
+        while calls.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let second = {
+            let coalescer = coalescer.clone();
+            let calls = calls.clone();
+            tokio::spawn(async move {
+                coalescer
+                    .run("k".to_string(), || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        Ok::<_, String>(0)
+                    })
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+        release_tx.send(true).unwrap();
+
+        assert_eq!(first.await.unwrap(), Ok(7));
+        assert_eq!(second.await.unwrap(), Ok(7));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn recent_results_are_reused_until_ttl() {
+        let coalescer = Coalescer::<u32>::new(Duration::from_millis(50));
+        let first = coalescer
+            .run("k".to_string(), || async { Ok::<_, String>(1) })
+            .await;
+        let cached = coalescer
+            .run("k".to_string(), || async { Ok::<_, String>(2) })
+            .await;
+        assert_eq!((first, cached), (Ok(1), Ok(1)));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let refreshed = coalescer
+            .run("k".to_string(), || async { Ok::<_, String>(3) })
+            .await;
+        assert_eq!(refreshed, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_cached() {
+        let coalescer = Coalescer::<u32>::new(Duration::from_secs(1));
+        let failed = coalescer
+            .run("k".to_string(), || async {
+                Err::<u32, _>("boom".to_string())
+            })
+            .await;
+        assert_eq!(failed, Err("boom".to_string()));
+        let retried = coalescer
+            .run("k".to_string(), || async { Ok::<_, String>(5) })
+            .await;
+        assert_eq!(retried, Ok(5));
+    }
+
+    #[tokio::test]
+    async fn different_keys_do_not_share() {
+        let coalescer = Coalescer::<u32>::new(Duration::from_secs(1));
+        let a = coalescer
+            .run("a".to_string(), || async { Ok::<_, String>(1) })
+            .await;
+        let b = coalescer
+            .run("b".to_string(), || async { Ok::<_, String>(2) })
+            .await;
+        assert_eq!((a, b), (Ok(1), Ok(2)));
+    }
+}
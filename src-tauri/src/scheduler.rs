@@ -0,0 +1,578 @@
+//! 定期タスク / 予約実行のスケジューラ。
+//!
+//! - タイムラインの定期同期 (オフライン用キャッシュを温める)
+//! - 画像キャッシュの整理 / DB バックアップ / 暗号化バックアップ (jobs.rs の
+//!   ジョブとして積む)
+//! - 予約投稿 (サーバー側の予約投稿に対応していないサーバー向け。アプリが
+//!   起動している間だけ送信される)
+//!
+//! タスクは `app-state.db` (app_db.rs) に保存する。アプリを閉じていて実行時刻を
+//! 過ぎた繰り返しタスクは起動後に 1 回だけ実行し、次の時刻へ進める。予約投稿は
+//! MAX_POST_DELAY_MS 以上遅れたら投稿せず失敗として残す。
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notecli::error::NoteDeckError;
+use notecli::models::{CreateNoteParams, TimelineOptions, TimelineType};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+
+use crate::app_db::{self, SharedConn};
+use crate::jobs::now_ms;
+use crate::settings::SettingsStore;
+
+/// タスクの状態 (次回時刻 / 実行結果) が変わるたびに emit する (payload は [`ScheduledTask`])。
+pub const SCHEDULE_UPDATED_EVENT: &str = "nd:schedule-updated";
+
+/// `app-state.db` の `runtime_state` 表でのキー。
+const STATE_KEY: &str = "schedule";
+
+/// 繰り返しの最短間隔 (分)。
+const MIN_INTERVAL_MINUTES: u32 = 5;
+/// 予約投稿を遅れて送ってよい上限。これより遅れたら投稿しない。
+const MAX_POST_DELAY_MS: i64 = 60 * 60 * 1000;
+/// 次回時刻までの待機の上限。スリープ復帰などで時計が飛んでも取りこぼさない。
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Schedule {
+    /// 指定時刻 (UNIX ms) に 1 回だけ。
+    #[serde(rename_all = "camelCase")]
+    Once { at_ms: i64 },
+    /// `anchor_ms` を起点に `minutes` 分ごと。毎日 3:00 なら anchor = 3:00、
+    /// minutes = 1440 (夏時間の切り替えでは 1 時間ずれる)。
+    #[serde(rename_all = "camelCase")]
+    Every { minutes: u32, anchor_ms: i64 },
+}
+
+impl Schedule {
+    /// `now_ms` より後の最初の実行時刻。`Once` で過ぎていれば None。
+    fn next_after(self, now_ms: i64) -> Option<i64> {
+        match self {
+            Self::Once { at_ms } => (at_ms > now_ms).then_some(at_ms),
+            Self::Every { minutes, anchor_ms } => {
+                let interval = i64::from(minutes.max(1)) * 60 * 1000;
+                if now_ms < anchor_ms {
+                    return Some(anchor_ms);
+                }
+                let elapsed = (now_ms - anchor_ms) / interval + 1;
+                Some(anchor_ms + elapsed * interval)
+            }
+        }
+    }
+
+    fn validate(self) -> Result<(), NoteDeckError> {
+        match self {
+            Self::Every { minutes, .. } if minutes < MIN_INTERVAL_MINUTES => Err(invalid(format!(
+                "interval must be at least {MIN_INTERVAL_MINUTES} minutes"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ScheduledAction {
+    /// タイムラインを取得してキャッシュに保存する。
+    #[serde(rename_all = "camelCase")]
+    SyncTimeline {
+        account_id: String,
+        timeline_type: TimelineType,
+        list_id: Option<String>,
+    },
+    /// 期限切れの画像キャッシュを消すジョブを積む。
+    PruneImageCache,
+    /// アプリデータ下の `exports/` に DB を書き出すジョブを積む
+    /// (`JobSpec::ExportDb`)。
+    ExportBackup,
+    /// `dir` に暗号化バックアップを書き出し、新しいものから `keep` 個残すジョブを積む
+    /// (`backup.rs`)。
+    #[serde(rename_all = "camelCase")]
+    EncryptedBackup { dir: String, keep: u32 },
+    /// ノートを投稿する (`Once` のみ)。
+    #[serde(rename_all = "camelCase")]
+    Post {
+        account_id: String,
+        params: CreateNoteParams,
+        channel_id: Option<String>,
+    },
+}
+
+impl ScheduledAction {
+    fn validate(&self, schedule: Schedule) -> Result<(), NoteDeckError> {
+        match self {
+            Self::SyncTimeline { account_id, .. } if account_id.trim().is_empty() => {
+                Err(invalid("accountId must not be empty"))
+            }
+            Self::EncryptedBackup { dir, .. } if dir.trim().is_empty() => {
+                Err(invalid("backup dir must not be empty"))
+            }
+            Self::EncryptedBackup { keep: 0, .. } => Err(invalid("keep must be at least 1")),
+            Self::Post { account_id, .. } if account_id.trim().is_empty() => {
+                Err(invalid("accountId must not be empty"))
+            }
+            Self::Post { .. } if !matches!(schedule, Schedule::Once { .. }) => {
+                Err(invalid("scheduled posts must use a once schedule"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    /// 新規作成時は空文字で渡すと採番する。
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub schedule: Schedule,
+    pub action: ScheduledAction,
+    /// 次回の実行時刻。無効化中 / 実行済みの `Once` は None。
+    pub next_run_ms: Option<i64>,
+    pub last_run_ms: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl ScheduledTask {
+    fn reschedule(&mut self, now_ms: i64) {
+        self.next_run_ms = if self.enabled {
+            self.schedule.next_after(now_ms)
+        } else {
+            None
+        };
+    }
+}
+
+/// 起動時、アプリを閉じている間に過ぎた実行時刻を直す。
+/// 繰り返しは 1 回だけ追いつき実行し、遅れすぎた予約投稿は失敗にする。
+fn recover(tasks: &mut [ScheduledTask], now_ms: i64) {
+    for task in tasks.iter_mut() {
+        let Some(next) = task.next_run_ms else {
+            continue;
+        };
+        if next > now_ms {
+            continue;
+        }
+        if matches!(task.action, ScheduledAction::Post { .. }) && now_ms - next > MAX_POST_DELAY_MS
+        {
+            task.enabled = false;
+            task.next_run_ms = None;
+            task.last_error = Some("missed: NoteDeck was not running at the scheduled time".into());
+        } else {
+            task.next_run_ms = Some(now_ms);
+        }
+    }
+}
+
+pub struct Scheduler {
+    conn: SharedConn,
+    tasks: Mutex<Vec<ScheduledTask>>,
+    wake: Notify,
+}
+
+impl Scheduler {
+    /// `app-state.db` からタスクを読み込む。まだ無ければ以前
+    /// `settings.json` に保存していた分を引き継いで保存し直す。
+    pub fn load(conn: SharedConn, settings: &SettingsStore) -> Self {
+        let stored = app_db::load_state(&conn, STATE_KEY).unwrap_or_else(|e| {
+            tracing::warn!("[scheduler] failed to load tasks: {e}");
+            None
+        });
+        let migrate = stored.is_none();
+        let mut tasks: Vec<ScheduledTask> = stored
+            .or_else(|| settings.take_runtime_state(STATE_KEY))
+            .unwrap_or_default();
+        recover(&mut tasks, now_ms());
+        let scheduler = Self {
+            conn,
+            tasks: Mutex::new(tasks),
+            wake: Notify::new(),
+        };
+        if migrate && !scheduler.list().is_empty() {
+            scheduler.save();
+        }
+        scheduler
+    }
+
+    pub fn list(&self) -> Vec<ScheduledTask> {
+        self.tasks.lock().unwrap().clone()
+    }
+
+    fn save(&self) {
+        if let Err(e) = app_db::save_state(&self.conn, STATE_KEY, &self.list()) {
+            tracing::warn!("[scheduler] failed to save tasks: {e}");
+        }
+    }
+
+    /// タスクを追加 (id が空 / 未登録) または置き換え、次回時刻を計算し直す。
+    fn upsert(&self, mut task: ScheduledTask) -> Result<ScheduledTask, NoteDeckError> {
+        if task.name.trim().is_empty() {
+            return Err(invalid("task name must not be empty"));
+        }
+        task.schedule.validate()?;
+        task.action.validate(task.schedule)?;
+        if task.id.trim().is_empty() {
+            task.id = uuid::Uuid::new_v4().to_string();
+        }
+        task.reschedule(now_ms());
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            match tasks.iter_mut().find(|t| t.id == task.id) {
+                Some(existing) => {
+                    task.last_run_ms = existing.last_run_ms;
+                    task.last_error = existing.last_error.take();
+                    *existing = task.clone();
+                }
+                None => tasks.push(task.clone()),
+            }
+        }
+        self.save();
+        self.wake.notify_one();
+        Ok(task)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), NoteDeckError> {
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            let before = tasks.len();
+            tasks.retain(|t| t.id != id);
+            if tasks.len() == before {
+                return Err(invalid(format!("scheduled task not found: {id}")));
+            }
+        }
+        self.save();
+        Ok(())
+    }
+
+    /// 次の実行を今にする (無効なタスクも 1 回だけ実行する)。
+    fn run_now(&self, id: &str) -> Result<ScheduledTask, NoteDeckError> {
+        let task = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == id)
+                .ok_or_else(|| invalid(format!("scheduled task not found: {id}")))?;
+            task.next_run_ms = Some(now_ms());
+            task.clone()
+        };
+        self.wake.notify_one();
+        Ok(task)
+    }
+
+    /// 実行時刻を迎えたタスクを取り出す。取り出した時点で次回時刻へ進める
+    /// (`Once` は無効化) ので、実行が長引いても二重には取り出さない。
+    fn take_due(&self, now_ms: i64) -> Vec<ScheduledTask> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut due = Vec::new();
+        for task in tasks
+            .iter_mut()
+            .filter(|t| t.next_run_ms.is_some_and(|next| next <= now_ms))
+        {
+            due.push(task.clone());
+            if matches!(task.schedule, Schedule::Once { .. }) {
+                task.enabled = false;
+            }
+            task.reschedule(now_ms);
+        }
+        due
+    }
+
+    fn record(
+        &self,
+        id: &str,
+        ran_at_ms: i64,
+        outcome: Result<(), String>,
+    ) -> Option<ScheduledTask> {
+        let task = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let task = tasks.iter_mut().find(|t| t.id == id)?;
+            task.last_run_ms = Some(ran_at_ms);
+            task.last_error = outcome.err();
+            task.clone()
+        };
+        self.save();
+        Some(task)
+    }
+
+    /// 次に起きるまでの時間 (MAX_SLEEP 以下)。
+    fn sleep_duration(&self, now_ms: i64) -> Duration {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|t| t.next_run_ms)
+            .min()
+            .map(|next| Duration::from_millis(next.saturating_sub(now_ms).max(0) as u64))
+            .map_or(MAX_SLEEP, |d| d.min(MAX_SLEEP))
+    }
+}
+
+fn emit(app: &AppHandle, task: &ScheduledTask) {
+    let _ = app.emit(SCHEDULE_UPDATED_EVENT, task);
+}
+
+/// 実行時刻を迎えたタスクを実行する常駐ワーカー。
+pub fn spawn_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let scheduler = app.state::<Scheduler>();
+        loop {
+            let now = now_ms();
+            let due = scheduler.take_due(now);
+            if !due.is_empty() {
+                scheduler.save();
+            }
+            for task in due {
+                let outcome = run(&app, &task.action).await;
+                if let Err(e) = &outcome {
+                    tracing::warn!("[scheduler] {} ({}) failed: {e}", task.id, task.name);
+                }
+                if let Some(task) = scheduler.record(&task.id, now, outcome) {
+                    emit(&app, &task);
+                }
+            }
+            let sleep = scheduler.sleep_duration(now_ms());
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = scheduler.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// タイムラインの最新ページを取得してキャッシュに保存する
+/// (background_sync.rs のトレイ格納中の同期からも使う)。
+pub(crate) async fn sync_timeline(
+    app: &AppHandle,
+    account_id: &str,
+    timeline_type: &TimelineType,
+    list_id: Option<&str>,
+) -> Result<(), String> {
+    let app_state = app.state::<crate::commands::AppState>();
+    let (db, client) = app_state.ready().await;
+    let (host, token) =
+        crate::commands::get_credentials(&db, account_id).map_err(|e| e.to_string())?;
+    let cache_key = crate::commands::timeline_cache_key(timeline_type, list_id);
+    let options = TimelineOptions {
+        list_id: list_id.map(str::to_string),
+        ..Default::default()
+    };
+    let notes = app
+        .state::<crate::host_queue::HostQueue>()
+        .run(
+            &host,
+            crate::host_queue::Priority::Background,
+            client.get_timeline(&host, &token, account_id, timeline_type.clone(), options.clone()),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    crate::timeline_gaps::track_page(app, &db, account_id, &cache_key, &options, &notes);
+    db.cache_notes(&notes, &cache_key)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to cache notes: {e}"))
+}
+
+async fn run(app: &AppHandle, action: &ScheduledAction) -> Result<(), String> {
+    match action {
+        ScheduledAction::SyncTimeline {
+            account_id,
+            timeline_type,
+            list_id,
+        } => sync_timeline(app, account_id, timeline_type, list_id.as_deref()).await,
+        ScheduledAction::PruneImageCache => {
+            crate::jobs::submit(app, crate::jobs::JobSpec::PruneImageCache)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        ScheduledAction::ExportBackup => crate::jobs::submit(app, crate::jobs::JobSpec::ExportDb)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        ScheduledAction::EncryptedBackup { dir, keep } => {
+            let spec = crate::jobs::JobSpec::Backup {
+                dir: dir.clone(),
+                keep: *keep,
+            };
+            crate::jobs::submit(app, spec)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        ScheduledAction::Post {
+            account_id,
+            params,
+            channel_id,
+        } => {
+            let app_state = app.state::<crate::commands::AppState>();
+            crate::commands::create_note(&app_state, account_id, params, channel_id.as_deref())
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> NoteDeckError {
+    NoteDeckError::InvalidInput(message.into())
+}
+
+/// 登録済みのタスクを登録順に返す。
+#[tauri::command]
+#[specta::specta]
+pub fn schedule_list(scheduler: State<'_, Scheduler>) -> Vec<ScheduledTask> {
+    scheduler.list()
+}
+
+/// タスクを追加または更新して保存し、保存後のタスク (採番済み id / 次回時刻) を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn schedule_save(
+    scheduler: State<'_, Scheduler>,
+    task: ScheduledTask,
+) -> Result<ScheduledTask, NoteDeckError> {
+    scheduler.upsert(task)
+}
+
+/// タスクを削除する。
+#[tauri::command]
+#[specta::specta]
+pub fn schedule_delete(scheduler: State<'_, Scheduler>, id: String) -> Result<(), NoteDeckError> {
+    scheduler.delete(&id)
+}
+
+/// タスクを今すぐ 1 回実行する。結果は `nd:schedule-updated` で届く。
+#[tauri::command]
+#[specta::specta]
+pub fn schedule_run_now(
+    scheduler: State<'_, Scheduler>,
+    id: String,
+) -> Result<ScheduledTask, NoteDeckError> {
+    scheduler.run_now(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60 * 1000;
+
+    fn task(schedule: Schedule) -> ScheduledTask {
+        ScheduledTask {
+            id: String::new(),
+            name: "task".into(),
+            enabled: true,
+            schedule,
+            action: ScheduledAction::PruneImageCache,
+            next_run_ms: None,
+            last_run_ms: None,
+            last_error: None,
+        }
+    }
+
+    fn post(at_ms: i64) -> ScheduledTask {
+        let params = serde_json::from_value(serde_json::json!({ "text": "hello" })).unwrap();
+        ScheduledTask {
+            action: ScheduledAction::Post {
+                account_id: "acct-1".into(),
+                params,
+                channel_id: None,
+            },
+            ..task(Schedule::Once { at_ms })
+        }
+    }
+
+    #[test]
+    fn every_runs_on_the_anchor_grid() {
+        let every = Schedule::Every {
+            minutes: 60,
+            anchor_ms: 1_000,
+        };
+        assert_eq!(every.next_after(0), Some(1_000));
+        assert_eq!(every.next_after(1_000), Some(1_000 + 60 * MINUTE));
+        assert_eq!(
+            every.next_after(1_000 + 150 * MINUTE),
+            Some(1_000 + 180 * MINUTE)
+        );
+
+        let once = Schedule::Once { at_ms: 5_000 };
+        assert_eq!(once.next_after(4_999), Some(5_000));
+        assert_eq!(once.next_after(5_000), None);
+    }
+
+    #[test]
+    fn validates_interval_and_post_schedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = app_db::open(dir.path()).unwrap();
+        let scheduler = Scheduler::load(conn.clone(), &SettingsStore::load(dir.path()));
+        let too_often = task(Schedule::Every {
+            minutes: 1,
+            anchor_ms: 0,
+        });
+        assert!(scheduler.upsert(too_often).is_err());
+
+        let mut repeating_post = post(now_ms() + MINUTE);
+        repeating_post.schedule = Schedule::Every {
+            minutes: 60,
+            anchor_ms: 0,
+        };
+        assert!(scheduler.upsert(repeating_post).is_err());
+
+        let saved = scheduler.upsert(post(now_ms() + MINUTE)).unwrap();
+        assert!(!saved.id.is_empty());
+        assert!(saved.next_run_ms.is_some());
+        let reloaded = Scheduler::load(conn, &SettingsStore::load(dir.path()));
+        assert_eq!(reloaded.list().len(), 1);
+    }
+
+    #[test]
+    fn due_tasks_are_taken_once_and_rescheduled() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = app_db::open(dir.path()).unwrap();
+        let scheduler = Scheduler::load(conn.clone(), &SettingsStore::load(dir.path()));
+        let now = now_ms();
+        let every = scheduler
+            .upsert(task(Schedule::Every {
+                minutes: 60,
+                anchor_ms: now - 30 * MINUTE,
+            }))
+            .unwrap();
+        let once = scheduler.upsert(post(now + MINUTE)).unwrap();
+
+        assert!(scheduler.take_due(now).is_empty());
+        let due = scheduler.take_due(now + 30 * MINUTE);
+        assert_eq!(due.len(), 2);
+        assert!(scheduler.take_due(now + 30 * MINUTE).is_empty());
+
+        let tasks = scheduler.list();
+        let every = tasks.iter().find(|t| t.id == every.id).unwrap();
+        assert_eq!(every.next_run_ms, Some(now + 90 * MINUTE));
+        let once = tasks.iter().find(|t| t.id == once.id).unwrap();
+        assert!(!once.enabled);
+        assert_eq!(once.next_run_ms, None);
+    }
+
+    #[test]
+    fn recover_catches_up_but_drops_stale_posts() {
+        let now = 10 * MAX_POST_DELAY_MS;
+        let mut missed_every = task(Schedule::Every {
+            minutes: 60,
+            anchor_ms: 0,
+        });
+        missed_every.next_run_ms = Some(now - 5 * MAX_POST_DELAY_MS);
+        let mut late_post = post(now - MINUTE);
+        late_post.next_run_ms = Some(now - MINUTE);
+        let mut stale_post = post(now - 2 * MAX_POST_DELAY_MS);
+        stale_post.next_run_ms = Some(now - 2 * MAX_POST_DELAY_MS);
+
+        let mut tasks = vec![missed_every, late_post, stale_post];
+        recover(&mut tasks, now);
+        assert_eq!(tasks[0].next_run_ms, Some(now));
+        assert_eq!(tasks[1].next_run_ms, Some(now));
+        assert_eq!(tasks[2].next_run_ms, None);
+        assert!(!tasks[2].enabled);
+        assert!(tasks[2].last_error.is_some());
+    }
+}
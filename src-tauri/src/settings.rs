@@ -0,0 +1,844 @@
+//! バックエンド設定 (`app_dir/settings.json`)。Rust 側が保存する設定はすべてここ。
+//!
+//! ネットワーク / キャッシュ / ストリーミング / HTTP サーバー / メディアの
+//! 5 セクションを型付きで持ち、範囲を検証してから保存する。起動時に読み込んで
+//! `SharedPerfConfig` と `QueryRuntime` のストリーム上限へ反映するので、
+//! フロントが同期する前 (起動直後の画像プロキシや定期ジョブ) から保存済みの
+//! 値で動く。変更は `nd:backend-settings-changed` で全ウィンドウへ届く。
+//! フロントの性能設定はこの 5 セクションの値を `performance.json5` に
+//! 持たず、ここから読んで `settings_set_section` で書く。
+//!
+//! 起動設定・自動化ルール・クラッシュレポート・プラグイン・バックグラウンド
+//! 同期・アップロード前加工も機能ごとのセクションとして同じファイルに持つ。
+//! 各機能は自分のコマンドで検証してから `SettingsStore::update` で書き込む。
+//! これらはフロントへ送らない (`settings_get` と変更イベントは 5 セクションだけ)。
+//! 以前の個別ファイル (`launch.json` 等) は初回起動時に取り込んで消す。
+//!
+//! ジョブと予約投稿は設定ではなく実行時の状態なので、ここには置かず
+//! `app-state.db` (app_db.rs) に保存する。以前ここに保存していた分は
+//! `take_runtime_state` で一度だけ引き渡す。
+//!
+//! フロントの表示設定 (`notedeck/settings.json5`) とは別のファイル。
+//! 実行時の参照先は従来どおり `PerformanceConfig` で、ここはその永続化と
+//! 検証を受け持つ。
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use notecli::error::NoteDeckError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::automation::AutomationRule;
+use crate::background_sync::BackgroundSyncSettings;
+use crate::crash_report::CrashReportConfig;
+use crate::launch::LaunchConfig;
+use crate::media_gate::MediaGate;
+use crate::perf_config::{PerformanceConfig, SharedPerfConfig, StreamDropPolicy};
+use crate::plugin_host::PluginState;
+use crate::query_runtime::QueryRuntime;
+use crate::upload_prep::UploadPrepConfig;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 以前は別ファイルに保存していたセクション (キー, `app_dir` 直下のファイル名)。
+/// `settings.json` にそのキーが無ければ中身を取り込み、保存できたら消す。
+const LEGACY_FILES: &[(&str, &str)] = &[
+    ("launch", "launch.json"),
+    ("crashReports", "crash_reports.json"),
+    ("backgroundSync", "background-sync.json"),
+    ("plugins", "plugins-state.json"),
+    ("automation", "automation.json"),
+    ("schedule", "schedule.json"),
+    ("jobs", "jobs.json"),
+];
+
+/// 以前このファイルに持っていた実行時の状態のキー (`take_runtime_state` で引き渡す)。
+const RUNTIME_STATE_KEYS: &[&str] = &["schedule", "jobs"];
+
+/// 設定が変わったときにフロントへ送るイベント名 (payload は変更後の [`BackendSettings`])。
+pub const SETTINGS_CHANGED_EVENT: &str = "nd:backend-settings-changed";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    /// 画像などの同時取得数
+    pub max_concurrent_fetches: u32,
+    /// 同じホストへの取得がこの回数続けて失敗したら一時停止する
+    pub circuit_breaker_threshold: u32,
+    /// 一時停止する秒数
+    pub circuit_breaker_secs: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheSettings {
+    /// 画像メモリキャッシュの合計上限 (MB)
+    pub memory_max_mb: u32,
+    /// メモリに載せる画像 1 枚あたりの上限 (KB)
+    pub memory_item_max_kb: u32,
+    /// OGP キャッシュの件数上限
+    pub ogp_entries_max: u32,
+    /// ディスクの画像キャッシュを残す日数
+    pub image_ttl_days: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamingSettings {
+    /// クエリごとに flush 間で溜める追加 / 更新の上限
+    pub pending_max: u32,
+    /// flush 間で溜めるノートキャプチャ更新の上限
+    pub captures_max: u32,
+    /// 上限に達したときに捨てる側
+    pub drop_policy: StreamDropPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpServerSettings {
+    /// ローカル HTTP API から上流サーバー 1 つへ送る 1 分あたりのリクエスト上限
+    pub max_requests_per_window: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaSettings {
+    /// センシティブ指定の添付の URL を WebView に渡さない (`media_gate.rs`)
+    pub hide_sensitive: bool,
+}
+
+/// フロントへ渡す設定 (`settings_get` / 変更イベント)。型付きの 5 セクションだけ。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackendSettings {
+    pub network: NetworkSettings,
+    pub cache: CacheSettings,
+    pub streaming: StreamingSettings,
+    pub http_server: HttpServerSettings,
+    pub media: MediaSettings,
+}
+
+/// `settings.json` の全体。欠けたセクション / 項目は既定値で埋める。
+///
+/// `launch` 以降は機能ごとのセクション。各機能のコマンドが読み書きするので
+/// フロントへは送らない。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SettingsFile {
+    pub network: NetworkSettings,
+    pub cache: CacheSettings,
+    pub streaming: StreamingSettings,
+    pub http_server: HttpServerSettings,
+    pub media: MediaSettings,
+    /// 起動時の挙動 (`launch.rs`)
+    pub launch: LaunchConfig,
+    /// クラッシュレポートの記録 (`crash_report.rs`)
+    pub crash_reports: CrashReportConfig,
+    /// トレイ格納中の同期 (`background_sync.rs`)
+    pub background_sync: BackgroundSyncSettings,
+    /// アップロード前の加工 (`upload_prep.rs`)
+    pub upload_prep: UploadPrepConfig,
+    /// プラグイン ID → 有効 / 承認スコープ (`plugin_host.rs`)
+    pub plugins: HashMap<String, PluginState>,
+    /// 自動化ルール (`automation.rs`)
+    pub automation: Vec<AutomationRule>,
+}
+
+// 機能ごとのセクションの型は PartialEq を揃えていないので、
+// 保存するときと同じ JSON 表現で比べる。
+impl PartialEq for SettingsFile {
+    fn eq(&self, other: &Self) -> bool {
+        serde_json::to_value(self).ok() == serde_json::to_value(other).ok()
+    }
+}
+
+/// 1 セクション分の更新。
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "section", content = "values", rename_all = "camelCase")]
+pub enum SettingsSection {
+    Network(NetworkSettings),
+    Cache(CacheSettings),
+    Streaming(StreamingSettings),
+    HttpServer(HttpServerSettings),
+    Media(MediaSettings),
+}
+
+// 既定値は PerformanceConfig::default() に合わせる (単位だけ変換)。
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        let perf = PerformanceConfig::default();
+        Self {
+            max_concurrent_fetches: perf.max_concurrent_fetches as u32,
+            circuit_breaker_threshold: perf.circuit_breaker_threshold,
+            circuit_breaker_secs: perf.circuit_breaker_duration as u32,
+        }
+    }
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        let perf = PerformanceConfig::default();
+        Self {
+            memory_max_mb: (perf.memory_cache_max_total / (1024 * 1024)) as u32,
+            memory_item_max_kb: (perf.memory_cache_max_item / 1024) as u32,
+            ogp_entries_max: perf.rust_ogp_cache_max as u32,
+            image_ttl_days: perf.image_cache_ttl_days as u32,
+        }
+    }
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        let perf = PerformanceConfig::default();
+        Self {
+            pending_max: perf.stream_pending_max as u32,
+            captures_max: perf.stream_captures_max as u32,
+            drop_policy: perf.stream_drop_policy,
+        }
+    }
+}
+
+impl Default for HttpServerSettings {
+    fn default() -> Self {
+        Self {
+            max_requests_per_window: PerformanceConfig::default().max_requests_per_window as u32,
+        }
+    }
+}
+
+fn check(field: &str, value: u32, range: RangeInclusive<u32>) -> Result<(), NoteDeckError> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(NoteDeckError::InvalidInput(format!(
+            "{field} must be between {} and {} (got {value})",
+            range.start(),
+            range.end()
+        )))
+    }
+}
+
+impl NetworkSettings {
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        check(
+            "network.maxConcurrentFetches",
+            self.max_concurrent_fetches,
+            1..=100,
+        )?;
+        check(
+            "network.circuitBreakerThreshold",
+            self.circuit_breaker_threshold,
+            1..=20,
+        )?;
+        check(
+            "network.circuitBreakerSecs",
+            self.circuit_breaker_secs,
+            1..=3600,
+        )
+    }
+}
+
+impl CacheSettings {
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        check("cache.memoryMaxMb", self.memory_max_mb, 1..=512)?;
+        check("cache.memoryItemMaxKb", self.memory_item_max_kb, 1..=8192)?;
+        check("cache.ogpEntriesMax", self.ogp_entries_max, 1..=10_000)?;
+        check("cache.imageTtlDays", self.image_ttl_days, 1..=365)?;
+        if u64::from(self.memory_item_max_kb) > u64::from(self.memory_max_mb) * 1024 {
+            return Err(NoteDeckError::InvalidInput(
+                "cache.memoryItemMaxKb must not exceed cache.memoryMaxMb".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl StreamingSettings {
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        check("streaming.pendingMax", self.pending_max, 1..=100_000)?;
+        check("streaming.capturesMax", self.captures_max, 1..=100_000)
+    }
+}
+
+impl HttpServerSettings {
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        check(
+            "httpServer.maxRequestsPerWindow",
+            self.max_requests_per_window,
+            1..=10_000,
+        )
+    }
+}
+
+/// `obj` から 1 セクションを取り出す。壊れていたら警告してそのセクションだけ既定値にする。
+fn take_section<T: DeserializeOwned + Default>(obj: &mut Map<String, Value>, key: &str) -> T {
+    match obj.remove(key) {
+        Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+            tracing::warn!("invalid backend settings section {key}, using defaults: {e}");
+            T::default()
+        }),
+        None => T::default(),
+    }
+}
+
+/// `take_section` に加えて範囲を検証し、範囲外ならそのセクションだけ既定値にする。
+fn take_checked<T: DeserializeOwned + Default>(
+    obj: &mut Map<String, Value>,
+    key: &str,
+    validate: impl FnOnce(&T) -> Result<(), NoteDeckError>,
+) -> T {
+    let value = take_section(obj, key);
+    match validate(&value) {
+        Ok(()) => value,
+        Err(e) => {
+            tracing::warn!("invalid backend settings section {key}, using defaults: {e}");
+            T::default()
+        }
+    }
+}
+
+impl SettingsFile {
+    pub fn validate(&self) -> Result<(), NoteDeckError> {
+        self.network.validate()?;
+        self.cache.validate()?;
+        self.streaming.validate()?;
+        self.http_server.validate()?;
+        self.background_sync.validate()
+    }
+
+    /// 読み込んだファイルの中身から組み立てる。セクション単位で読むので、
+    /// 1 つのセクションが壊れていても他 (プラグインや自動化ルール) は失わない。
+    fn from_object(mut obj: Map<String, Value>) -> Self {
+        Self {
+            network: take_checked(&mut obj, "network", NetworkSettings::validate),
+            cache: take_checked(&mut obj, "cache", CacheSettings::validate),
+            streaming: take_checked(&mut obj, "streaming", StreamingSettings::validate),
+            http_server: take_checked(&mut obj, "httpServer", HttpServerSettings::validate),
+            media: take_section(&mut obj, "media"),
+            launch: take_section(&mut obj, "launch"),
+            crash_reports: take_section(&mut obj, "crashReports"),
+            background_sync: take_checked(
+                &mut obj,
+                "backgroundSync",
+                BackgroundSyncSettings::validate,
+            ),
+            upload_prep: take_section(&mut obj, "uploadPrep"),
+            plugins: take_section(&mut obj, "plugins"),
+            automation: take_section(&mut obj, "automation"),
+        }
+    }
+
+    /// バックアップ (backup.rs) に入れた `settings.json` の中身から組み立てる。
+    /// プラグインの有効化と承認スコープは戻さず、復元先で改めて承認してもらう
+    /// (別の端末から持ち込んだ承認で実行ファイルを起動しないように)。
+    pub(crate) fn from_backup(raw: &[u8]) -> Result<Self, NoteDeckError> {
+        match serde_json::from_slice(raw) {
+            Ok(Value::Object(obj)) => {
+                let mut settings = Self::from_object(obj);
+                settings.plugins.clear();
+                Ok(settings)
+            }
+            Ok(_) => Err(NoteDeckError::InvalidInput(
+                "backend settings in the backup are not an object".to_string(),
+            )),
+            Err(e) => Err(NoteDeckError::InvalidInput(e.to_string())),
+        }
+    }
+
+    /// フロントへ渡す 5 セクション。
+    pub fn backend(&self) -> BackendSettings {
+        BackendSettings {
+            network: self.network.clone(),
+            cache: self.cache.clone(),
+            streaming: self.streaming.clone(),
+            http_server: self.http_server.clone(),
+            media: self.media.clone(),
+        }
+    }
+
+    /// フロントの性能設定から持ってくる 4 セクションを差し替える。
+    pub(crate) fn set_perf_sections(&mut self, from: BackendSettings) {
+        self.network = from.network;
+        self.cache = from.cache;
+        self.streaming = from.streaming;
+        self.http_server = from.http_server;
+    }
+
+    /// 検証してからセクションを差し替えた設定を返す。
+    fn with_section(&self, section: SettingsSection) -> Result<Self, NoteDeckError> {
+        let mut next = self.clone();
+        match section {
+            SettingsSection::Network(s) => {
+                s.validate()?;
+                next.network = s;
+            }
+            SettingsSection::Cache(s) => {
+                s.validate()?;
+                next.cache = s;
+            }
+            SettingsSection::Streaming(s) => {
+                s.validate()?;
+                next.streaming = s;
+            }
+            SettingsSection::HttpServer(s) => {
+                s.validate()?;
+                next.http_server = s;
+            }
+            SettingsSection::Media(s) => next.media = s,
+        }
+        Ok(next)
+    }
+}
+
+impl BackendSettings {
+    /// 実行時に各モジュールが参照する `PerformanceConfig` に写す。
+    pub fn perf_config(&self) -> PerformanceConfig {
+        PerformanceConfig {
+            memory_cache_max_total: self.cache.memory_max_mb as usize * 1024 * 1024,
+            memory_cache_max_item: self.cache.memory_item_max_kb as usize * 1024,
+            max_concurrent_fetches: self.network.max_concurrent_fetches as usize,
+            rust_ogp_cache_max: self.cache.ogp_entries_max as usize,
+            max_requests_per_window: self.http_server.max_requests_per_window as usize,
+            circuit_breaker_threshold: self.network.circuit_breaker_threshold,
+            circuit_breaker_duration: u64::from(self.network.circuit_breaker_secs),
+            image_cache_ttl_days: u64::from(self.cache.image_ttl_days),
+            stream_pending_max: self.streaming.pending_max as usize,
+            stream_captures_max: self.streaming.captures_max as usize,
+            stream_drop_policy: self.streaming.drop_policy,
+        }
+    }
+
+    /// `PerformanceConfig` から戻す (旧 `update_performance_config` 経由の更新用)。
+    /// `PerformanceConfig` に無いメディアのセクションは既定値になる。
+    pub fn from_perf_config(perf: &PerformanceConfig) -> Self {
+        let clamp = |v: u64| v.min(u64::from(u32::MAX)) as u32;
+        Self {
+            network: NetworkSettings {
+                max_concurrent_fetches: clamp(perf.max_concurrent_fetches as u64),
+                circuit_breaker_threshold: perf.circuit_breaker_threshold,
+                circuit_breaker_secs: clamp(perf.circuit_breaker_duration),
+            },
+            cache: CacheSettings {
+                memory_max_mb: clamp((perf.memory_cache_max_total / (1024 * 1024)) as u64),
+                memory_item_max_kb: clamp((perf.memory_cache_max_item / 1024) as u64),
+                ogp_entries_max: clamp(perf.rust_ogp_cache_max as u64),
+                image_ttl_days: clamp(perf.image_cache_ttl_days),
+            },
+            streaming: StreamingSettings {
+                pending_max: clamp(perf.stream_pending_max as u64),
+                captures_max: clamp(perf.stream_captures_max as u64),
+                drop_policy: perf.stream_drop_policy,
+            },
+            http_server: HttpServerSettings {
+                max_requests_per_window: clamp(perf.max_requests_per_window as u64),
+            },
+            media: MediaSettings::default(),
+        }
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    current: Mutex<SettingsFile>,
+    /// 以前このファイルにあった実行時の状態 (キー → 値)。引き渡したら消える。
+    runtime_state: Mutex<Map<String, Value>>,
+    /// `commit` の保存から反映までを直列にする (反映の順序が保存と逆にならないように)。
+    commit_lock: tokio::sync::Mutex<()>,
+}
+
+/// 以前の個別ファイルのうち `settings.json` にまだ無いセクションを取り込み、
+/// 取り込んだファイルのパスを返す。
+fn migrate_legacy(app_dir: &Path, obj: &mut Map<String, Value>) -> Vec<PathBuf> {
+    let mut migrated = Vec::new();
+    for (key, file) in LEGACY_FILES {
+        let path = app_dir.join(file);
+        if obj.contains_key(*key) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        match serde_json::from_str::<Value>(&content) {
+            Ok(value) => {
+                obj.insert((*key).to_string(), value);
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "dropping unreadable legacy settings: {e}")
+            }
+        }
+        migrated.push(path);
+    }
+    migrated
+}
+
+fn write_file(path: &Path, settings: &SettingsFile) -> Result<(), NoteDeckError> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| NoteDeckError::InvalidInput(e.to_string()))?;
+    crate::settings_store::atomic_write(path, &json, None)
+}
+
+/// `app_dir/settings.json` のパス (バックアップ用)。
+pub(crate) fn file_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(SETTINGS_FILE)
+}
+
+/// バックアップから戻した設定を `app_dir/settings.json` に書く。
+/// 読み込み済みの `SettingsStore` には反映しない (復元後は再起動する)。
+pub(crate) fn restore_file(app_dir: &Path, settings: &SettingsFile) -> Result<(), NoteDeckError> {
+    write_file(&file_path(app_dir), settings)
+}
+
+impl SettingsStore {
+    /// `app_dir/settings.json` を読む。無い場合は既定値、壊れている / 範囲外の
+    /// セクションは警告してそのセクションだけ既定値で起動する (ファイルは次の
+    /// 保存まで触らない)。以前の個別ファイルがあれば取り込んで保存し直す。
+    pub fn load(app_dir: &Path) -> Self {
+        let path = file_path(app_dir);
+        let mut obj = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<Value>(&content) {
+                Ok(Value::Object(obj)) => obj,
+                Ok(_) | Err(_) => {
+                    tracing::warn!(path = %path.display(), "invalid backend settings, using defaults");
+                    Map::new()
+                }
+            },
+            Err(_) => Map::new(),
+        };
+        let migrated = migrate_legacy(app_dir, &mut obj);
+        let runtime_state: Map<String, Value> = RUNTIME_STATE_KEYS
+            .iter()
+            .filter_map(|key| Some(((*key).to_string(), obj.remove(*key)?)))
+            .collect();
+        let settings = SettingsFile::from_object(obj);
+        if !migrated.is_empty() || !runtime_state.is_empty() {
+            match write_file(&path, &settings) {
+                Ok(()) => {
+                    for legacy in migrated {
+                        let _ = std::fs::remove_file(legacy);
+                    }
+                }
+                Err(e) => tracing::warn!("failed to save migrated backend settings: {e}"),
+            }
+        }
+        Self {
+            path,
+            current: Mutex::new(settings),
+            runtime_state: Mutex::new(runtime_state),
+            commit_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// フロントへ渡す 5 セクション。
+    pub fn get(&self) -> BackendSettings {
+        self.current.lock().unwrap().backend()
+    }
+
+    /// 一部だけ読む (自動化ルール等のセクションを毎回複製しないように)。
+    pub fn read<T>(&self, f: impl FnOnce(&SettingsFile) -> T) -> T {
+        f(&self.current.lock().unwrap())
+    }
+
+    /// 以前このファイルに保存していた実行時の状態 (`jobs` / `schedule`) を
+    /// 一度だけ取り出す。ファイルからは読み込み時に取り除いてある。
+    pub fn take_runtime_state<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.runtime_state.lock().unwrap().remove(key)?;
+        serde_json::from_value(value)
+            .map_err(|e| tracing::warn!("dropping unreadable legacy {key}: {e}"))
+            .ok()
+    }
+
+    /// 今の値を `f` で書き換え、検証して保存する。読み出しから保存までを
+    /// 1 つのロックの中で行うので、同時に別のセクションを更新しても互いの
+    /// 変更を消さない。保存に失敗したらメモリ上の値も変えない。
+    /// 更新後の全体と、値が変わったかを返す。
+    pub fn update(
+        &self,
+        f: impl FnOnce(&mut SettingsFile) -> Result<(), NoteDeckError>,
+    ) -> Result<(SettingsFile, bool), NoteDeckError> {
+        let mut current = self.current.lock().unwrap();
+        let mut next = current.clone();
+        f(&mut next)?;
+        if next == *current {
+            return Ok((next, false));
+        }
+        next.validate()?;
+        write_file(&self.path, &next)?;
+        *current = next.clone();
+        Ok((next, true))
+    }
+}
+
+/// 設定を実行時の状態 (`SharedPerfConfig` / ストリーム上限) に反映する。
+/// メディアの設定は `commit` が `MediaGate` へ渡す。
+pub async fn apply(settings: &BackendSettings, perf: &SharedPerfConfig, runtime: &QueryRuntime) {
+    let config = settings.perf_config();
+    runtime.set_stream_limits(config.stream_limits());
+    *perf.write().await = config;
+}
+
+/// `f` で書き換えて保存し、反映して変更を通知する。値が変わっていなければ何もしない。
+/// 返り値と通知はフロントへ渡す 5 セクションだけ。
+pub(crate) async fn commit(
+    app: &AppHandle,
+    store: &SettingsStore,
+    perf: &SharedPerfConfig,
+    runtime: &QueryRuntime,
+    f: impl FnOnce(&mut SettingsFile) -> Result<(), NoteDeckError>,
+) -> Result<BackendSettings, NoteDeckError> {
+    let _serial = store.commit_lock.lock().await;
+    let (file, changed) = store.update(f)?;
+    let settings = file.backend();
+    if !changed {
+        return Ok(settings);
+    }
+    apply(&settings, perf, runtime).await;
+    if let Some(gate) = app.try_state::<MediaGate>() {
+        gate.set_enabled(settings.media.hide_sensitive);
+    }
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+    Ok(settings)
+}
+
+/// 現在のバックエンド設定を返す。
+#[tauri::command]
+#[specta::specta]
+pub fn settings_get(store: State<'_, Arc<SettingsStore>>) -> BackendSettings {
+    store.get()
+}
+
+/// 1 セクションを検証して保存し、すぐに反映する。変更後の 5 セクションを返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_section(
+    app: AppHandle,
+    store: State<'_, Arc<SettingsStore>>,
+    perf: State<'_, SharedPerfConfig>,
+    runtime: State<'_, QueryRuntime>,
+    section: SettingsSection,
+) -> Result<BackendSettings, NoteDeckError> {
+    commit(&app, &store, &perf, &runtime, |settings| {
+        *settings = settings.with_section(section)?;
+        Ok(())
+    })
+    .await
+}
+
+/// 性能設定とメディアの 5 セクションを既定値に戻す (機能ごとのセクションはそのまま)。
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_reset(
+    app: AppHandle,
+    store: State<'_, Arc<SettingsStore>>,
+    perf: State<'_, SharedPerfConfig>,
+    runtime: State<'_, QueryRuntime>,
+) -> Result<BackendSettings, NoteDeckError> {
+    commit(&app, &store, &perf, &runtime, |settings| {
+        settings.set_perf_sections(BackendSettings::default());
+        settings.media = MediaSettings::default();
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_performance_config() {
+        assert!(SettingsFile::default().validate().is_ok());
+        let settings = BackendSettings::default();
+        let perf = settings.perf_config();
+        let defaults = PerformanceConfig::default();
+        assert_eq!(perf.memory_cache_max_total, defaults.memory_cache_max_total);
+        assert_eq!(perf.memory_cache_max_item, defaults.memory_cache_max_item);
+        assert_eq!(
+            perf.max_requests_per_window,
+            defaults.max_requests_per_window
+        );
+        assert_eq!(perf.stream_captures_max, defaults.stream_captures_max);
+        assert_eq!(BackendSettings::from_perf_config(&defaults), settings);
+    }
+
+    #[test]
+    fn partial_file_is_filled_with_defaults() {
+        let settings: SettingsFile =
+            serde_json::from_str(r#"{ "cache": { "imageTtlDays": 3 } }"#).unwrap();
+        assert_eq!(settings.cache.image_ttl_days, 3);
+        assert_eq!(
+            settings.cache.memory_max_mb,
+            CacheSettings::default().memory_max_mb
+        );
+        assert_eq!(settings.network, NetworkSettings::default());
+    }
+
+    #[test]
+    fn out_of_range_section_is_rejected() {
+        let current = SettingsFile::default();
+        let err = current
+            .with_section(SettingsSection::Network(NetworkSettings {
+                max_concurrent_fetches: 0,
+                ..Default::default()
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("network.maxConcurrentFetches"));
+
+        let too_big_item = SettingsSection::Cache(CacheSettings {
+            memory_max_mb: 1,
+            memory_item_max_kb: 2048,
+            ..Default::default()
+        });
+        assert!(current.with_section(too_big_item).is_err());
+    }
+
+    #[test]
+    fn set_section_persists_and_reloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::load(dir.path());
+        let section: SettingsSection = serde_json::from_value(serde_json::json!({
+            "section": "streaming",
+            "values": { "pendingMax": 800, "capturesMax": 3000, "dropPolicy": "dropNewest" },
+        }))
+        .unwrap();
+        store
+            .update(|settings| {
+                *settings = settings.with_section(section)?;
+                Ok(())
+            })
+            .unwrap();
+
+        let reloaded = SettingsStore::load(dir.path()).get();
+        assert_eq!(reloaded.streaming.pending_max, 800);
+        assert_eq!(reloaded.streaming.drop_policy, StreamDropPolicy::DropNewest);
+        assert_eq!(reloaded.network, NetworkSettings::default());
+    }
+
+    #[test]
+    fn legacy_files_are_migrated_once() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("launch.json"), r#"{ "startInTray": true }"#).unwrap();
+        std::fs::write(
+            dir.path().join("background-sync.json"),
+            r#"{ "enabled": true, "intervalMinutes": 10 }"#,
+        )
+        .unwrap();
+
+        let settings = SettingsStore::load(dir.path()).read(|s| s.clone());
+        assert!(settings.launch.start_in_tray);
+        assert!(settings.background_sync.enabled);
+        assert_eq!(settings.background_sync.interval_minutes, 10);
+        assert!(!dir.path().join("launch.json").exists());
+        assert!(!dir.path().join("background-sync.json").exists());
+
+        // 取り込んだ後は settings.json から読む
+        assert_eq!(
+            SettingsStore::load(dir.path()).read(|s| s.clone()),
+            settings
+        );
+    }
+
+    #[test]
+    fn runtime_state_is_handed_over_and_removed_from_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(SETTINGS_FILE),
+            r#"{ "launch": { "startInTray": true }, "jobs": [{ "id": "j1" }] }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("schedule.json"), r#"[{ "id": "t1" }]"#).unwrap();
+
+        let store = SettingsStore::load(dir.path());
+        let jobs: Vec<Value> = store.take_runtime_state("jobs").unwrap();
+        assert_eq!(jobs[0]["id"], "j1");
+        assert!(store.take_runtime_state::<Vec<Value>>("jobs").is_none());
+        assert!(store.take_runtime_state::<Vec<Value>>("schedule").is_some());
+
+        let content = std::fs::read_to_string(dir.path().join(SETTINGS_FILE)).unwrap();
+        let file: Value = serde_json::from_str(&content).unwrap();
+        assert!(file.get("jobs").is_none());
+        assert!(file.get("schedule").is_none());
+        assert_eq!(file["launch"]["startInTray"], true);
+        assert!(!dir.path().join("schedule.json").exists());
+    }
+
+    #[test]
+    fn backend_view_has_only_the_typed_sections() {
+        let mut settings = SettingsFile::default();
+        settings.launch.start_in_tray = true;
+        settings.media.hide_sensitive = true;
+        let value = serde_json::to_value(settings.backend()).unwrap();
+        let mut keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["cache", "httpServer", "media", "network", "streaming"]
+        );
+        assert_eq!(value["media"]["hideSensitive"], true);
+    }
+
+    #[test]
+    fn broken_section_keeps_the_others() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(SETTINGS_FILE),
+            r#"{ "network": { "maxConcurrentFetches": 0 }, "launch": { "startMinimized": true } }"#,
+        )
+        .unwrap();
+        let settings = SettingsStore::load(dir.path()).read(|s| s.clone());
+        assert_eq!(settings.network, NetworkSettings::default());
+        assert!(settings.launch.start_minimized);
+    }
+
+    #[test]
+    fn update_only_touches_its_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::load(dir.path());
+        store
+            .update(|settings| {
+                settings.launch.start_in_tray = true;
+                Ok(())
+            })
+            .unwrap();
+        let (settings, changed) = store
+            .update(|settings| {
+                settings.media.hide_sensitive = true;
+                Ok(())
+            })
+            .unwrap();
+        assert!(changed);
+        assert!(settings.launch.start_in_tray);
+
+        let (_, changed) = store.update(|_| Ok(())).unwrap();
+        assert!(!changed);
+        assert!(store
+            .update(|settings| {
+                settings.background_sync.interval_minutes = 0;
+                Ok(())
+            })
+            .is_err());
+        assert_eq!(
+            SettingsStore::load(dir.path()).read(|s| s.clone()),
+            settings
+        );
+    }
+
+    #[test]
+    fn invalid_file_falls_back_to_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(SETTINGS_FILE),
+            r#"{ "httpServer": { "maxRequestsPerWindow": 0 } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SettingsStore::load(dir.path()).get(),
+            BackendSettings::default()
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use notecli::error::NoteDeckError;
 use notecli::models::NormalizedNotification;
 use notecli::streaming::FrontendEmitter;
 use serde::{Deserialize, Serialize};
@@ -11,7 +12,7 @@ use tauri::{AppHandle, Manager};
 use tauri_plugin_notification::NotificationExt;
 use tauri_specta::Event;
 
-use crate::os_notify::{NotificationClicked, NotifyMedia};
+use crate::os_notify::{NotificationClicked, NotifyActions, NotifyMedia};
 
 // #781: specta 契約に載せる typed イベント。notecli の型を newtype で包む
 // (serde/specta とも透過なのでワイヤ形・TS 型は中身そのもの)。
@@ -35,6 +36,24 @@ pub struct StreamChatMessageUnreacted(pub notecli::streaming::StreamChatMessageU
 #[cfg(target_os = "android")]
 const NOTIFICATION_CHANNEL_ID: &str = "notedeck_notifications";
 
+/// 再現した通知 ([`TauriEmitter::simulate`]) を載せる subscription ID。
+/// 通知カラムの購読が無いときに使う (どの query にも入らない)。
+const SIMULATED_SUBSCRIPTION_ID: &str = "simulated";
+
+/// 通知の再現で作れる type ([`simulated_notification`])。
+pub(crate) const SIMULATED_NOTIFICATION_TYPES: &[&str] = &[
+    "test",
+    "mention",
+    "reply",
+    "quote",
+    "reaction",
+    "renote",
+    "follow",
+    "receiveFollowRequest",
+    "followRequestAccepted",
+    "pollEnded",
+];
+
 /// Maximum number of notification IDs to keep for deduplication.
 /// When exceeded, the set is cleared to prevent unbounded growth.
 const DEDUP_MAX_IDS: usize = 500;
@@ -55,6 +74,8 @@ struct PendingOsNotification {
     context: Option<NotificationClicked>,
     /// アバター/絵文字画像 (#754)。要約通知になると失われる。
     media: Option<NotifyMedia>,
+    /// Reply / React / Open ボタン。要約通知になると失われる。
+    actions: Option<NotifyActions>,
 }
 
 /// send_native_notification の判定結果。表示 (副作用) と分離してテスト可能にする。
@@ -67,6 +88,7 @@ enum OsNotifPlan {
         body: Option<String>,
         context: Option<NotificationClicked>,
         media: Option<NotifyMedia>,
+        actions: Option<NotifyActions>,
     },
     /// バーストとしてバッファ済み。spawn_flusher が true なら flush タスクを起動する
     #[cfg_attr(target_os = "android", allow(dead_code))]
@@ -85,6 +107,7 @@ fn summarize_group(items: &[PendingOsNotification]) -> Option<PendingOsNotificat
             body: single.body.clone(),
             context: single.context.clone(),
             media: single.media.clone(),
+            actions: single.actions.clone(),
         }),
         _ => {
             let mut names: Vec<&str> = Vec::new();
@@ -103,30 +126,32 @@ fn summarize_group(items: &[PendingOsNotification]) -> Option<PendingOsNotificat
                 body: Some(body),
                 context: None,
                 media: None,
+                actions: None,
             })
         }
     }
 }
 
-fn show_os_notification<R: tauri::Runtime>(
+pub(crate) fn show_os_notification<R: tauri::Runtime>(
     app: &AppHandle<R>,
     title: &str,
     body: Option<&str>,
     context: Option<&NotificationClicked>,
     media: Option<&NotifyMedia>,
+    actions: Option<&NotifyActions>,
 ) {
-    // Linux / Windows: user-notify 経由 (クリック遷移 + 画像添付, #754)
+    // Linux / Windows: user-notify 経由 (クリック遷移 + 画像添付 + アクションボタン, #754)
     #[cfg(any(target_os = "linux", target_os = "windows"))]
     {
         let _ = app;
-        crate::os_notify::show(title, body, context, media);
+        crate::os_notify::show(title, body, context, media, actions);
     }
 
     // macOS: user-notify は署名済み bundle 必須のため plugin 経路を維持
     // (クリック遷移・画像は署名導入までブロック, #754)
     #[cfg(target_os = "macos")]
     {
-        let _ = (context, media);
+        let _ = (context, media, actions);
         let mut builder = app.notification().builder().title(title);
         if let Some(body) = body {
             builder = builder.body(body);
@@ -140,7 +165,7 @@ fn show_os_notification<R: tauri::Runtime>(
     // (plugin builder は動的画像を添付できないため media 未対応)
     #[cfg(target_os = "android")]
     {
-        let _ = media;
+        let _ = (media, actions);
         let mut builder = app
             .notification()
             .builder()
@@ -207,6 +232,7 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                 body,
                 context,
                 media,
+                actions,
             } => {
                 show_os_notification(
                     &self.app,
@@ -214,6 +240,7 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                     body.as_deref(),
                     context.as_ref(),
                     media.as_ref(),
+                    actions.as_ref(),
                 );
             }
             OsNotifPlan::Buffer { spawn_flusher } => {
@@ -231,6 +258,7 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                                 summary.body.as_deref(),
                                 summary.context.as_ref(),
                                 summary.media.as_ref(),
+                                summary.actions.as_ref(),
                             );
                         }
                     });
@@ -241,6 +269,55 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
         }
     }
 
+    /// ストリーム外で取得した通知 (background_sync.rs) を、ストリームで届いた
+    /// ときと同じ経路で出す (OS 通知・トレイ・cue)。dedup セットを共有するので
+    /// 二重には出ない。
+    #[cfg_attr(mobile, allow(dead_code))]
+    pub(crate) fn notify_fetched(&self, notification: &NormalizedNotification) {
+        self.send_native_notification(notification);
+        self.record_recent(notification);
+        crate::event_cue::on_notification(&self.app, notification);
+    }
+
+    /// 合成した通知をストリームで届いたものとして流す (OS 通知・自動化ルール・
+    /// cue・DND 抑制まで同じ経路)。通知カラムを購読していればその subscription
+    /// に載せるので、カラムにも並ぶ。
+    pub(crate) fn simulate(&self, notification: NormalizedNotification) {
+        let key = crate::query_runtime::QueryKey::Notifications {
+            account_id: notification.account_id.clone(),
+        };
+        let subscription_id = self
+            .app
+            .try_state::<crate::query_runtime::QueryRuntime>()
+            .and_then(|runtime| runtime.stream_subscription_for_key(&key))
+            .unwrap_or_else(|| SIMULATED_SUBSCRIPTION_ID.to_string());
+        self.emit(notecli::streaming::StreamEvent::Notification(Box::new(
+            notecli::streaming::StreamNotificationEvent {
+                account_id: notification.account_id.clone(),
+                subscription_id,
+                notification,
+            },
+        )));
+    }
+
+    /// トレイの「最近の通知」に積む (トレイの無いモバイルでは state が無い)。
+    fn record_recent(&self, notification: &NormalizedNotification) {
+        let Some(recent) = self.app.try_state::<crate::tray::RecentNotifications>() else {
+            return;
+        };
+        let Some((title, body)) = describe_notification(notification) else {
+            return;
+        };
+        let text = notification.note.as_ref().and_then(|n| n.text.as_deref());
+        recent.push(
+            &notification.account_id,
+            crate::tray::RecentNotification {
+                id: notification.id.clone(),
+                label: crate::tray::recent_label(&title, body.as_deref(), text),
+            },
+        );
+    }
+
     /// OS 通知の表示可否・形態を判定する。表示副作用は持たない (テスト用に分離)。
     fn plan_os_notification(&self, notification: &NormalizedNotification) -> OsNotifPlan {
         // Deduplicate by notification ID — multiple subscriptions for the same
@@ -256,50 +333,8 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
         }
 
         let notif_type = notification.notification_type.as_str();
-
-        // アクター系: 送信元ユーザーを title に。user が欠落したら "誰か" で従来挙動を維持。
-        let actor_name = || {
-            notification
-                .user
-                .as_ref()
-                .and_then(|u| u.name.as_deref().or(Some(u.username.as_str())))
-                .unwrap_or("誰か")
-                .to_string()
-        };
-
-        // Misskey 本家 (packages/sw/src/scripts/create-notification.ts) に合わせ、
-        // アクター系 (title = user) と自己/システム通知 (title = 固定ラベル) を分ける。
-        let (title, body_opt): (String, Option<String>) = match notif_type {
-            "reaction" => {
-                let body = notification
-                    .reaction
-                    .as_deref()
-                    .map(|r| format!("リアクション {r}"))
-                    .unwrap_or_else(|| "リアクション".to_string());
-                (actor_name(), Some(body))
-            }
-            "reply" => (actor_name(), Some("リプライ".to_string())),
-            "renote" => (actor_name(), Some("リノート".to_string())),
-            "quote" => (actor_name(), Some("引用".to_string())),
-            "mention" => (actor_name(), Some("メンション".to_string())),
-            "follow" => (actor_name(), Some("フォロー".to_string())),
-            "followRequestAccepted" => (actor_name(), Some("フォローリクエスト承認".to_string())),
-            "receiveFollowRequest" => (actor_name(), Some("フォローリクエスト".to_string())),
-
-            // user フィールドを持たない自己/システム通知
-            "achievementEarned" => {
-                let body = notification
-                    .achievement
-                    .as_deref()
-                    .map(|a| achievement_label(a).to_string());
-                ("実績獲得".to_string(), body)
-            }
-            "login" => ("ログイン検知".to_string(), None),
-            "pollEnded" => ("投票終了".to_string(), None),
-            "app" => ("通知".to_string(), None),
-            "test" => ("テスト通知".to_string(), Some("テスト通知".to_string())),
-
-            _ => return OsNotifPlan::Suppress,
+        let Some((title, body_opt)) = describe_notification(notification) else {
+            return OsNotifPlan::Suppress;
         };
 
         // クリック時の遷移コンテキスト (#754)。note があればノート詳細、
@@ -334,6 +369,14 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
             })
         };
 
+        // アクションボタン: 返信・リアクションの対象になるノートを伴う通知のみ。
+        // 公開範囲は返信に引き継ぐ (specified は Reply なし)。
+        let actions = notification
+            .note
+            .as_ref()
+            .filter(|_| matches!(notif_type, "mention" | "reply" | "quote" | "reaction"))
+            .map(|note| NotifyActions::for_note_visibility(&note.visibility));
+
         // Android は webview が凍結されうるため常に即時表示 (グルーピングは
         // channel 経由で OS が行う)
         #[cfg(target_os = "android")]
@@ -343,6 +386,7 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                 body: body_opt,
                 context,
                 media,
+                actions,
             }
         }
 
@@ -357,6 +401,11 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
             if focused {
                 return OsNotifPlan::Suppress;
             }
+            // OS の集中モード / DND 中は OS 通知を出さない (envelope は流れる
+            // ので未読カウントは変わらない)。設定で無効化できる
+            if crate::dnd::should_suppress(&self.app) {
+                return OsNotifPlan::Suppress;
+            }
 
             // バースト集約 (#750): 直近の通知から GROUP_WINDOW 内ならバッファし、
             // flusher が窓の終わりに要約 1 件へまとめる。窓外の 1 件目は即時表示。
@@ -370,6 +419,7 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                     body: body_opt,
                     context,
                     media,
+                    actions,
                 };
             }
             let mut pending = self.pending_group.lock().unwrap();
@@ -379,12 +429,140 @@ impl<R: tauri::Runtime> TauriEmitter<R> {
                 body: body_opt,
                 context,
                 media,
+                actions,
             });
             OsNotifPlan::Buffer { spawn_flusher }
         }
     }
 }
 
+/// 通知の (title, body)。OS 通知とトレイの最近の通知で共有する。
+/// 表示対象外の type は None。
+fn describe_notification(
+    notification: &NormalizedNotification,
+) -> Option<(String, Option<String>)> {
+    let notif_type = notification.notification_type.as_str();
+
+    // アクター系: 送信元ユーザーを title に。user が欠落したら "誰か" で従来挙動を維持。
+    let actor_name = || {
+        notification
+            .user
+            .as_ref()
+            .and_then(|u| u.name.as_deref().or(Some(u.username.as_str())))
+            .unwrap_or("誰か")
+            .to_string()
+    };
+
+    // Misskey 本家 (packages/sw/src/scripts/create-notification.ts) に合わせ、
+    // アクター系 (title = user) と自己/システム通知 (title = 固定ラベル) を分ける。
+    Some(match notif_type {
+        "reaction" => {
+            let body = notification
+                .reaction
+                .as_deref()
+                .map(|r| format!("リアクション {r}"))
+                .unwrap_or_else(|| "リアクション".to_string());
+            (actor_name(), Some(body))
+        }
+        "reply" => (actor_name(), Some("リプライ".to_string())),
+        "renote" => (actor_name(), Some("リノート".to_string())),
+        "quote" => (actor_name(), Some("引用".to_string())),
+        "mention" => (actor_name(), Some("メンション".to_string())),
+        "follow" => (actor_name(), Some("フォロー".to_string())),
+        "followRequestAccepted" => (actor_name(), Some("フォローリクエスト承認".to_string())),
+        "receiveFollowRequest" => (actor_name(), Some("フォローリクエスト".to_string())),
+
+        // user フィールドを持たない自己/システム通知
+        "achievementEarned" => {
+            let body = notification
+                .achievement
+                .as_deref()
+                .map(|a| achievement_label(a).to_string());
+            ("実績獲得".to_string(), body)
+        }
+        "login" => ("ログイン検知".to_string(), None),
+        "pollEnded" => ("投票終了".to_string(), None),
+        "app" => ("通知".to_string(), None),
+        "test" => ("テスト通知".to_string(), Some("テスト通知".to_string())),
+
+        _ => return None,
+    })
+}
+
+/// 動作確認用の合成通知を作る。送信元は自分自身で、ノートを伴う type には
+/// 合成ノートを付ける。ID は `simulated-` で始まるのでサーバーの通知と衝突しない。
+pub(crate) fn simulated_notification(
+    account_id: &str,
+    host: &str,
+    user_id: &str,
+    username: &str,
+    notification_type: &str,
+    now_ms: i64,
+) -> Result<NormalizedNotification, NoteDeckError> {
+    if !SIMULATED_NOTIFICATION_TYPES.contains(&notification_type) {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "cannot simulate a {notification_type} notification"
+        )));
+    }
+    let created_at = iso8601_utc(now_ms);
+    let user = serde_json::json!({ "id": user_id, "username": username, "host": null });
+    let mut value = serde_json::json!({
+        "id": format!("simulated-{now_ms}"),
+        "_accountId": account_id,
+        "_serverHost": host,
+        "createdAt": created_at,
+        "type": notification_type,
+    });
+    // test / pollEnded は送信元ユーザーを持たない
+    if !matches!(notification_type, "test" | "pollEnded") {
+        value["user"] = user.clone();
+    }
+    if matches!(
+        notification_type,
+        "mention" | "reply" | "quote" | "reaction" | "renote" | "pollEnded"
+    ) {
+        value["note"] = serde_json::json!({
+            "id": format!("simulated-note-{now_ms}"),
+            "_accountId": account_id,
+            "_serverHost": host,
+            "createdAt": created_at,
+            "text": "通知のテスト",
+            "user": user,
+            "visibility": "public",
+            "renoteCount": 0,
+            "repliesCount": 0,
+        });
+    }
+    if notification_type == "reaction" {
+        value["reaction"] = serde_json::json!("👍");
+    }
+    serde_json::from_value(value)
+        .map_err(|e| NoteDeckError::InvalidInput(format!("simulated notification: {e}")))
+}
+
+/// UNIX ミリ秒を Misskey と同じ `2026-01-01T00:00:00.000Z` 形式にする。
+fn iso8601_utc(ms: i64) -> String {
+    let secs = ms.div_euclid(1000);
+    let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // days → 暦日 (Howard Hinnant の civil_from_days)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        ms.rem_euclid(1000)
+    )
+}
+
 fn achievement_label(name: &str) -> &str {
     match name {
         "notes1" => "はじめてのノート",
@@ -473,6 +651,12 @@ impl<R: tauri::Runtime> FrontendEmitter for TauriEmitter<R> {
     fn emit(&self, event: notecli::streaming::StreamEvent) {
         use notecli::streaming::StreamEvent as E;
 
+        // センシティブな添付の URL はどの経路にも載せない
+        let event = match self.app.try_state::<crate::media_gate::MediaGate>() {
+            Some(gate) => gate.gate_event(event),
+            None => event,
+        };
+
         if let Some(runtime) = self.app.try_state::<crate::query_runtime::QueryRuntime>() {
             if runtime.ingest_stream_event(&event) {
                 // 常駐 flusher が DELTA_FLUSH_WINDOW 後に drain して emit する。
@@ -480,6 +664,8 @@ impl<R: tauri::Runtime> FrontendEmitter for TauriEmitter<R> {
             }
         }
 
+        crate::automation::on_stream_event(&self.app, &event);
+
         // stream-note-capture-updated は QueryRuntime が NoteCaptureBatch に
         // まとめて emit するので、個別 stream-event は抑止 (IPC 削減)。
         // StreamInspector は元から ALL_KINDS に capture を含まないので影響なし。
@@ -492,6 +678,8 @@ impl<R: tauri::Runtime> FrontendEmitter for TauriEmitter<R> {
         let dedicated = match &event {
             E::Notification(e) => {
                 self.send_native_notification(&e.notification);
+                self.record_recent(&e.notification);
+                crate::event_cue::on_notification(&self.app, &e.notification);
                 None
             }
             E::Status(e) => StreamStatus((**e).clone()).emit(&self.app).err(),
@@ -812,6 +1000,7 @@ mod tests {
                 icon_url: Some(format!("https://misskey.example/avatar-of-{title}.webp")),
                 image_url: None,
             }),
+            actions: Some(NotifyActions::for_note_visibility("public")),
         }
     }
 
@@ -863,6 +1052,24 @@ mod tests {
         assert!(emitter.pending_group.lock().unwrap().is_empty());
     }
 
+    /// OS が DND 中なら OS 通知は抑制し、設定で常に通知に切り替えられる。
+    #[test]
+    fn os_dnd_suppresses_unless_overridden() {
+        let app = mock_app();
+        let dnd = crate::dnd::DndState::default();
+        dnd.set_cached(Some(true));
+        app.manage(dnd);
+        let emitter = TauriEmitter::new(app.handle().clone());
+
+        let plan = emitter.plan_os_notification(&test_notification_with_user("d1", "reply", "alice"));
+        assert!(matches!(plan, OsNotifPlan::Suppress));
+
+        app.state::<crate::dnd::DndState>()
+            .set_mode(crate::dnd::DndMode::AlwaysNotify);
+        let plan = emitter.plan_os_notification(&test_notification_with_user("d2", "reply", "alice"));
+        assert!(matches!(plan, OsNotifPlan::ShowNow { .. }));
+    }
+
     /// バッファ 1 件の flush は元の title/body/context をそのまま使う (要約しない)。
     #[test]
     fn summarize_group_single_keeps_original() {
@@ -942,11 +1149,16 @@ mod tests {
         .expect("fixture should deserialize");
 
         match emitter.plan_os_notification(&notification) {
-            OsNotifPlan::ShowNow { context, .. } => {
+            OsNotifPlan::ShowNow {
+                context, actions, ..
+            } => {
                 let ctx = context.expect("actor notification should carry context");
                 assert_eq!(ctx.account_id, "acct-1");
                 assert_eq!(ctx.note_id.as_deref(), Some("note-1"));
                 assert_eq!(ctx.user_id.as_deref(), Some("u1"));
+                // note 付きのリアクション通知には Reply/React/Open が付く
+                let actions = actions.expect("reaction on a note should carry actions");
+                assert_eq!(actions.reply_visibility.as_deref(), Some("public"));
             }
             _ => panic!("first notification should be ShowNow"),
         }
@@ -1023,4 +1235,51 @@ mod tests {
             "unknownFutureBadge"
         );
     }
+
+    #[test]
+    fn simulated_notification_matches_type_shape() {
+        let build = |t| simulated_notification("acct-1", "misskey.example", "u1", "me", t, 0);
+
+        let test = build("test").expect("test should simulate");
+        assert!(test.id.starts_with("simulated-"));
+        assert_eq!(test.created_at, "1970-01-01T00:00:00.000Z");
+        assert!(test.user.is_none() && test.note.is_none());
+
+        let reaction = build("reaction").expect("reaction should simulate");
+        assert_eq!(reaction.user.as_ref().map(|u| u.id.as_str()), Some("u1"));
+        assert!(reaction.note.is_some());
+        assert_eq!(reaction.reaction.as_deref(), Some("👍"));
+
+        assert!(build("chatRoomInvitationReceived").is_err());
+        assert_eq!(iso8601_utc(1_709_210_096_789), "2024-02-29T12:34:56.789Z");
+    }
+
+    /// 再現した通知は通知カラムの購読 (main チャネル) に載り、delta になる。
+    #[test]
+    fn simulated_notification_reaches_open_notifications_query() {
+        let app = mock_app();
+        app.manage(QueryRuntime::default());
+        let snap = {
+            let rt = app.state::<QueryRuntime>();
+            let snap = rt
+                .open(QueryKey::Notifications {
+                    account_id: "acct-1".into(),
+                })
+                .expect("open should succeed");
+            rt.attach_stream_subscription(&snap.query_id, "sub-main".into())
+                .expect("attach should succeed");
+            snap
+        };
+
+        let emitter = TauriEmitter::new(app.handle().clone());
+        let notification =
+            simulated_notification("acct-1", "misskey.example", "u1", "me", "follow", 1)
+                .expect("follow should simulate");
+        emitter.simulate(notification);
+
+        let deltas = app.state::<QueryRuntime>().drain_pending();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].query_id, snap.query_id);
+        assert_eq!(deltas[0].inserts.len(), 1);
+    }
 }
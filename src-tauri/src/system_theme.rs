@@ -0,0 +1,241 @@
+//! OS のライト / ダーク設定とアクセントカラー。
+//!
+//! WebView の `prefers-color-scheme` は Linux (WebKitGTK) で OS 設定に追従
+//! しないことがあり、アクセントカラーは Web から取得する手段がない。
+//! バックエンドで OS から直接読み、変化したときだけ
+//! `nd:system-appearance-changed` を emit する。
+//!
+//! - ライト / ダーク: メインウィンドウの `WindowEvent::ThemeChanged` を契機に
+//!   `Window::theme()` を読む
+//! - アクセント: 変更通知を受ける API がプラットフォームごとに異なるため、
+//!   ACCENT_POLL_INTERVAL ごとに問い合わせて差分があれば emit する
+//!   - Windows: `DwmGetColorizationColor`
+//!   - macOS: `defaults read -g AppleAccentColor`
+//!   - Linux: GNOME 47+ の `org.gnome.desktop.interface accent-color`
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// フロント (theme ストア) が listen するイベント名。
+#[cfg_attr(mobile, allow(dead_code))]
+pub const APPEARANCE_CHANGED_EVENT: &str = "nd:system-appearance-changed";
+
+/// アクセントカラーの問い合わせ間隔。
+#[cfg_attr(mobile, allow(dead_code))]
+const ACCENT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemAppearance {
+    /// OS のライト / ダーク設定。取得できない環境では null。
+    pub color_scheme: Option<ColorScheme>,
+    /// OS のアクセントカラー (`#rrggbb`)。取得できない環境では null。
+    pub accent_color: Option<String>,
+}
+
+/// 最後に emit した値。同じ値の再通知を抑える。
+#[derive(Default)]
+pub struct SystemThemeState {
+    last: Mutex<Option<SystemAppearance>>,
+}
+
+impl SystemThemeState {
+    /// 前回から変化していれば記録して true。
+    fn update(&self, next: &SystemAppearance) -> bool {
+        let mut last = self.last.lock().unwrap();
+        if last.as_ref() == Some(next) {
+            return false;
+        }
+        *last = Some(next.clone());
+        true
+    }
+}
+
+/// 現在の OS 設定を読む。
+pub fn query<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> SystemAppearance {
+    SystemAppearance {
+        color_scheme: query_color_scheme(app),
+        accent_color: query_accent_color(),
+    }
+}
+
+#[cfg(not(mobile))]
+fn query_color_scheme<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<ColorScheme> {
+    use tauri::Manager;
+
+    let window = app.get_webview_window("main")?;
+    match window.theme().ok()? {
+        tauri::Theme::Dark => Some(ColorScheme::Dark),
+        tauri::Theme::Light => Some(ColorScheme::Light),
+        _ => None,
+    }
+}
+
+#[cfg(mobile)]
+fn query_color_scheme<R: tauri::Runtime>(_app: &tauri::AppHandle<R>) -> Option<ColorScheme> {
+    None
+}
+
+/// 変化があればフロントへ通知する。ThemeChanged と accent の定期確認から呼ぶ。
+#[cfg_attr(mobile, allow(dead_code))]
+pub fn refresh<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    use tauri::{Emitter, Manager};
+
+    let Some(state) = app.try_state::<SystemThemeState>() else {
+        return;
+    };
+    let appearance = query(app);
+    if state.update(&appearance) {
+        if let Err(e) = app.emit(APPEARANCE_CHANGED_EVENT, &appearance) {
+            tracing::warn!("[system-theme] emit failed: {e}");
+        }
+    }
+}
+
+/// アクセントカラーの監視 task を起動する。setup から 1 度だけ呼ぶ。
+#[cfg(not(mobile))]
+pub fn spawn_watcher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(ACCENT_POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let app = app.clone();
+            // 外部コマンドを起動するプラットフォームがあるので blocking 側で
+            let _ = tauri::async_runtime::spawn_blocking(move || refresh(&app)).await;
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+fn query_accent_color() -> Option<String> {
+    use windows::Win32::Graphics::Dwm::DwmGetColorizationColor;
+
+    let mut color = 0u32;
+    let mut opaque = windows::core::BOOL::default();
+    // SAFETY: 出力先を渡すだけの読み取り専用 API
+    unsafe { DwmGetColorizationColor(&mut color, &mut opaque) }.ok()?;
+    Some(argb_to_hex(color))
+}
+
+#[cfg(target_os = "macos")]
+fn query_accent_color() -> Option<String> {
+    let out = std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleAccentColor"])
+        .output()
+        .ok()?;
+    // 既定 (青) のときはキー自体が存在せず非 0 終了になる
+    let value = out
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().parse::<i32>().ok())
+        .flatten();
+    macos_accent_hex(value).map(str::to_string)
+}
+
+#[cfg(target_os = "linux")]
+fn query_accent_color() -> Option<String> {
+    let out = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "accent-color"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    gnome_accent_hex(&String::from_utf8_lossy(&out.stdout)).map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn query_accent_color() -> Option<String> {
+    None
+}
+
+/// DWM の 0xAARRGGBB を `#rrggbb` にする (alpha は捨てる)。
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn argb_to_hex(argb: u32) -> String {
+    format!("#{:06x}", argb & 0x00ff_ffff)
+}
+
+/// `AppleAccentColor` の値 (未設定 = 青) をシステムカラーにする。
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn macos_accent_hex(value: Option<i32>) -> Option<&'static str> {
+    Some(match value {
+        None | Some(4) => "#007aff",
+        Some(-1) => "#8c8c8c",
+        Some(0) => "#ff5257",
+        Some(1) => "#f7821b",
+        Some(2) => "#ffc600",
+        Some(3) => "#62ba46",
+        Some(5) => "#a550a7",
+        Some(6) => "#f74f9e",
+        Some(_) => return None,
+    })
+}
+
+/// `gsettings get ... accent-color` の出力 ("'blue'") を GNOME のパレットにする。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn gnome_accent_hex(out: &str) -> Option<&'static str> {
+    Some(match out.trim().trim_matches('\'') {
+        "blue" => "#3584e4",
+        "teal" => "#2190a4",
+        "green" => "#3a944a",
+        "yellow" => "#c88800",
+        "orange" => "#ed5b00",
+        "red" => "#e62d42",
+        "pink" => "#d56199",
+        "purple" => "#9141ac",
+        "slate" => "#6f8396",
+        _ => return None,
+    })
+}
+
+/// 現在の OS のライト / ダーク設定とアクセントカラーを返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn system_appearance(app: tauri::AppHandle) -> SystemAppearance {
+    tauri::async_runtime::spawn_blocking(move || query(&app))
+        .await
+        .unwrap_or(SystemAppearance {
+            color_scheme: None,
+            accent_color: None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_reports_changes_only() {
+        let state = SystemThemeState::default();
+        let dark = SystemAppearance {
+            color_scheme: Some(ColorScheme::Dark),
+            accent_color: Some("#3584e4".into()),
+        };
+        assert!(state.update(&dark));
+        assert!(!state.update(&dark.clone()));
+        let light = SystemAppearance {
+            color_scheme: Some(ColorScheme::Light),
+            ..dark
+        };
+        assert!(state.update(&light));
+    }
+
+    #[test]
+    fn converts_platform_accents() {
+        assert_eq!(argb_to_hex(0xc40078d4), "#0078d4");
+        assert_eq!(macos_accent_hex(None), Some("#007aff"));
+        assert_eq!(macos_accent_hex(Some(0)), Some("#ff5257"));
+        assert_eq!(macos_accent_hex(Some(42)), None);
+        assert_eq!(gnome_accent_hex("'teal'\n"), Some("#2190a4"));
+        assert_eq!(gnome_accent_hex("No such key"), None);
+    }
+}
@@ -0,0 +1,425 @@
+//! タイムラインキャッシュの欠け (gap) の記録と穴埋め。
+//!
+//! アプリを閉じている間に流れたノートが多いと、再開時に取った最新ページと
+//! キャッシュ済みの最新ノートの間が繋がらない。そのままだとオフライン表示で
+//! 離れた時間帯が隙間なく並んでしまうので、最新ページをキャッシュする前に
+//! 「取得ページの最古ノート」と「キャッシュ済みの最新ノート」の間を gap として
+//! `app-state.db` (app_db.rs) に記録する。
+//!
+//! gap は `fill_gap` (sinceId / untilId で間を取る) か、通常の遡り読み込み
+//! (untilId が gap の新しい側と一致するページ) で縮み、繋がったら消える。
+//! notecli.db のスキーマは notecli の持ち物なので、gap はアプリ側の DB に置く。
+
+use notecli::db::Database;
+use notecli::error::NoteDeckError;
+use notecli::models::{NormalizedNote, TimelineOptions, TimelineType};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager, State};
+
+use crate::app_db::SharedConn;
+use crate::commands::{timeline_cache_key, AppState, Result};
+use crate::request_dedup::RequestDedup;
+
+/// `fill_gap` 1 回で取るノート数。
+const FILL_PAGE: i64 = 40;
+
+/// キャッシュ上で隣り合っているが、間のノートが欠けている 2 ノート。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineGap {
+    /// gap の新しい側 (直後) のノート
+    pub newer_id: String,
+    pub newer_at: String,
+    /// gap の古い側 (直前) のノート
+    pub older_id: String,
+    pub older_at: String,
+}
+
+/// gap 判定に使うノートの id と作成日時。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NoteRef<'a> {
+    id: &'a str,
+    created_at: &'a str,
+}
+
+impl<'a> From<&'a NormalizedNote> for NoteRef<'a> {
+    fn from(note: &'a NormalizedNote) -> Self {
+        Self {
+            id: &note.id,
+            created_at: &note.created_at,
+        }
+    }
+}
+
+/// 新しい順のページの最古ノート (作成日時で比べる。ID 形式はサーバー依存)。
+fn oldest<'a>(page: &[NoteRef<'a>]) -> Option<NoteRef<'a>> {
+    page.iter()
+        .copied()
+        .min_by(|a, b| a.created_at.cmp(b.created_at))
+}
+
+/// 最新ページ `page` とキャッシュ済みの最新ノート `cached` の間に gap があるか。
+/// ページが `limit` 件に満たなければ (= それ以上ノートが無い) gap は無い。
+fn detect(page: &[NoteRef], limit: Option<i64>, cached: Option<NoteRef>) -> Option<TimelineGap> {
+    let cached = cached?;
+    if limit.is_some_and(|l| (page.len() as i64) < l) || page.iter().any(|n| n.id == cached.id) {
+        return None;
+    }
+    let oldest = oldest(page)?;
+    (oldest.created_at > cached.created_at).then(|| TimelineGap {
+        newer_id: oldest.id.to_string(),
+        newer_at: oldest.created_at.to_string(),
+        older_id: cached.id.to_string(),
+        older_at: cached.created_at.to_string(),
+    })
+}
+
+/// gap の新しい側から遡って取った `page` で gap がどうなるか。
+/// 繋がったら None、まだ残るなら縮めた gap を返す。
+fn shrink(gap: &TimelineGap, page: &[NoteRef], limit: Option<i64>) -> Option<TimelineGap> {
+    let reached = page
+        .iter()
+        .any(|n| n.id == gap.older_id || n.created_at <= gap.older_at.as_str());
+    if reached || limit.is_some_and(|l| (page.len() as i64) < l) {
+        return None;
+    }
+    oldest(page).map(|oldest| TimelineGap {
+        newer_id: oldest.id.to_string(),
+        newer_at: oldest.created_at.to_string(),
+        ..gap.clone()
+    })
+}
+
+pub struct GapStore {
+    conn: SharedConn,
+}
+
+impl GapStore {
+    /// 共有の app-state.db (app_db.rs) に表を作って使う。
+    pub fn new(conn: SharedConn) -> rusqlite::Result<Self> {
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(crate::app_db::open_in_memory()?)
+    }
+
+    fn init(conn: SharedConn) -> rusqlite::Result<Self> {
+        conn.lock().unwrap().execute_batch(
+            "CREATE TABLE IF NOT EXISTS timeline_gaps (
+                account_id TEXT NOT NULL,
+                timeline   TEXT NOT NULL,
+                newer_id   TEXT NOT NULL,
+                newer_at   TEXT NOT NULL,
+                older_id   TEXT NOT NULL,
+                older_at   TEXT NOT NULL,
+                PRIMARY KEY (account_id, timeline, newer_id)
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    fn record(&self, account_id: &str, timeline: &str, gap: &TimelineGap) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO timeline_gaps
+                (account_id, timeline, newer_id, newer_at, older_id, older_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                account_id,
+                timeline,
+                gap.newer_id,
+                gap.newer_at,
+                gap.older_id,
+                gap.older_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// タイムラインの gap を新しい順に返す。
+    pub fn list(&self, account_id: &str, timeline: &str) -> rusqlite::Result<Vec<TimelineGap>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT newer_id, newer_at, older_id, older_at FROM timeline_gaps
+             WHERE account_id = ?1 AND timeline = ?2 ORDER BY newer_at DESC",
+        )?;
+        let rows = stmt.query_map(params![account_id, timeline], |row| {
+            Ok(TimelineGap {
+                newer_id: row.get(0)?,
+                newer_at: row.get(1)?,
+                older_id: row.get(2)?,
+                older_at: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// `note_id` が新しい側か古い側に接している gap。
+    fn find(
+        &self,
+        account_id: &str,
+        timeline: &str,
+        note_id: &str,
+    ) -> rusqlite::Result<Option<TimelineGap>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT newer_id, newer_at, older_id, older_at FROM timeline_gaps
+             WHERE account_id = ?1 AND timeline = ?2 AND (newer_id = ?3 OR older_id = ?3)
+             ORDER BY newer_at DESC LIMIT 1",
+            params![account_id, timeline, note_id],
+            |row| {
+                Ok(TimelineGap {
+                    newer_id: row.get(0)?,
+                    newer_at: row.get(1)?,
+                    older_id: row.get(2)?,
+                    older_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// `gap` を `next` に置き換える (None なら消す)。
+    fn replace(
+        &self,
+        account_id: &str,
+        timeline: &str,
+        gap: &TimelineGap,
+        next: Option<&TimelineGap>,
+    ) -> rusqlite::Result<()> {
+        {
+            let conn = self.conn.lock().unwrap();
+            conn.execute(
+                "DELETE FROM timeline_gaps WHERE account_id = ?1 AND timeline = ?2 AND newer_id = ?3",
+                params![account_id, timeline, gap.newer_id],
+            )?;
+        }
+        match next {
+            Some(next) => self.record(account_id, timeline, next),
+            None => Ok(()),
+        }
+    }
+
+    /// 最新ページ (sinceId / untilId なし) をキャッシュする直前に呼ぶ。
+    /// `cached_newest` はキャッシュ済みの最新ノート。
+    pub fn on_latest_page(
+        &self,
+        account_id: &str,
+        timeline: &str,
+        page: &[NormalizedNote],
+        limit: Option<i64>,
+        cached_newest: Option<&NormalizedNote>,
+    ) {
+        let page: Vec<NoteRef> = page.iter().map(NoteRef::from).collect();
+        let Some(gap) = detect(&page, limit, cached_newest.map(NoteRef::from)) else {
+            return;
+        };
+        tracing::debug!(
+            account_id,
+            timeline,
+            ?gap,
+            "[gaps] timeline cache gap detected"
+        );
+        if let Err(e) = self.record(account_id, timeline, &gap) {
+            tracing::warn!("[gaps] failed to record gap: {e}");
+        }
+    }
+
+    /// 遡り読み込み (untilId のみ) の結果で、`until_id` に接する gap を縮める。
+    pub fn on_older_page(
+        &self,
+        account_id: &str,
+        timeline: &str,
+        until_id: &str,
+        page: &[NormalizedNote],
+        limit: Option<i64>,
+    ) {
+        let result = self
+            .find(account_id, timeline, until_id)
+            .and_then(|gap| match gap {
+                Some(gap) if gap.newer_id == until_id => {
+                    let page: Vec<NoteRef> = page.iter().map(NoteRef::from).collect();
+                    let next = shrink(&gap, &page, limit);
+                    self.replace(account_id, timeline, &gap, next.as_ref())
+                }
+                _ => Ok(()),
+            });
+        if let Err(e) = result {
+            tracing::warn!("[gaps] failed to update gap: {e}");
+        }
+    }
+}
+
+/// `api_get_timeline` が取ったページをキャッシュする直前に呼ぶ。最新ページなら
+/// gap を検出し、遡りページなら接している gap を縮める。フィルタ付きの取得は
+/// 件数が当てにならないので見ない。
+pub(crate) fn track_page(
+    app: &AppHandle,
+    db: &Database,
+    account_id: &str,
+    timeline: &str,
+    options: &TimelineOptions,
+    page: &[NormalizedNote],
+) {
+    let Some(gaps) = app.try_state::<GapStore>() else {
+        return;
+    };
+    if options.filters.is_some() {
+        return;
+    }
+    match (&options.since_id, &options.until_id) {
+        (None, None) => {
+            let newest = db
+                .get_cached_timeline(account_id, timeline, 1)
+                .unwrap_or_default();
+            gaps.on_latest_page(account_id, timeline, page, options.limit, newest.first());
+        }
+        (None, Some(until_id)) => {
+            gaps.on_older_page(account_id, timeline, until_id, page, options.limit);
+        }
+        _ => {}
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> NoteDeckError {
+    NoteDeckError::InvalidInput(format!("timeline gap store: {e}"))
+}
+
+fn store(app: &AppHandle) -> Result<tauri::State<'_, GapStore>> {
+    app.try_state::<GapStore>()
+        .ok_or_else(|| NoteDeckError::InvalidInput("timeline gap store unavailable".to_string()))
+}
+
+/// タイムラインのキャッシュ上の gap を新しい順に返す。オフライン表示で
+/// `newerId` と `olderId` の間に「読み込めていない区間」を出すのに使う。
+#[tauri::command]
+#[specta::specta]
+pub fn timeline_gaps(
+    app: AppHandle,
+    account_id: String,
+    timeline_type: TimelineType,
+    list_id: Option<String>,
+) -> Result<Vec<TimelineGap>> {
+    let timeline = timeline_cache_key(&timeline_type, list_id.as_deref());
+    store(&app)?.list(&account_id, &timeline).map_err(db_error)
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FillGapResult {
+    /// 取れた間のノート (新しい順、キャッシュ済み)
+    pub notes: Vec<NormalizedNote>,
+    /// まだ残っている gap (繋がったら null)
+    pub remaining: Option<TimelineGap>,
+}
+
+/// `around_id` (gap の新しい側か古い側のノート) に接する gap の間を 1 ページ
+/// 取ってキャッシュする。取り切れなければ縮めた gap を `remaining` で返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn fill_gap(
+    app: AppHandle,
+    app_state: State<'_, AppState>,
+    dedup: State<'_, RequestDedup>,
+    account_id: String,
+    timeline_type: TimelineType,
+    list_id: Option<String>,
+    around_id: String,
+) -> Result<FillGapResult> {
+    let timeline = timeline_cache_key(&timeline_type, list_id.as_deref());
+    let gap = store(&app)?
+        .find(&account_id, &timeline, &around_id)
+        .map_err(db_error)?
+        .ok_or_else(|| NoteDeckError::InvalidInput(format!("No gap next to {around_id}")))?;
+
+    // sinceId と untilId を両方渡すと、その間を新しい順に返す
+    let options = TimelineOptions {
+        limit: Some(FILL_PAGE),
+        since_id: Some(gap.older_id.clone()),
+        until_id: Some(gap.newer_id.clone()),
+        list_id,
+        ..TimelineOptions::default()
+    };
+    let notes = crate::commands::api_get_timeline(
+        app.clone(),
+        app_state,
+        dedup,
+        account_id.clone(),
+        timeline_type,
+        Some(options),
+    )
+    .await?;
+
+    let page: Vec<NoteRef> = notes.iter().map(NoteRef::from).collect();
+    let remaining = shrink(&gap, &page, Some(FILL_PAGE));
+    store(&app)?
+        .replace(&account_id, &timeline, &gap, remaining.as_ref())
+        .map_err(db_error)?;
+    Ok(FillGapResult { notes, remaining })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note<'a>(id: &'a str, created_at: &'a str) -> NoteRef<'a> {
+        NoteRef { id, created_at }
+    }
+
+    fn gap() -> TimelineGap {
+        TimelineGap {
+            newer_id: "n3".to_string(),
+            newer_at: "2026-01-02T03:00:00.000Z".to_string(),
+            older_id: "c1".to_string(),
+            older_at: "2026-01-01T00:00:00.000Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn full_page_newer_than_cache_is_a_gap() {
+        let page = [
+            note("n5", "2026-01-02T05:00:00.000Z"),
+            note("n4", "2026-01-02T04:00:00.000Z"),
+            note("n3", "2026-01-02T03:00:00.000Z"),
+        ];
+        let cached = note("c1", "2026-01-01T00:00:00.000Z");
+        assert_eq!(detect(&page, Some(3), Some(cached)), Some(gap()));
+        // 足りないページ / 既知ノートと重なるページ / キャッシュ無しは gap ではない
+        assert_eq!(detect(&page, Some(10), Some(cached)), None);
+        let overlapping = [page[0], note("c1", "2026-01-01T00:00:00.000Z")];
+        assert_eq!(detect(&overlapping, Some(2), Some(cached)), None);
+        assert_eq!(detect(&page, Some(3), None), None);
+    }
+
+    #[test]
+    fn older_pages_shrink_then_close_the_gap() {
+        let page = [
+            note("n2", "2026-01-02T02:00:00.000Z"),
+            note("n1", "2026-01-02T01:00:00.000Z"),
+        ];
+        let shrunk = shrink(&gap(), &page, Some(2)).unwrap();
+        assert_eq!(shrunk.newer_id, "n1");
+        assert_eq!(shrunk.older_id, "c1");
+
+        assert_eq!(shrink(&shrunk, &page[..1], Some(2)), None);
+        let reached = [note("c1", "2026-01-01T00:00:00.000Z")];
+        assert_eq!(shrink(&shrunk, &reached, Some(1)), None);
+    }
+
+    #[test]
+    fn store_finds_gap_from_either_side() {
+        let store = GapStore::open_in_memory().unwrap();
+        store.record("acc", "home", &gap()).unwrap();
+        assert_eq!(store.list("acc", "home").unwrap(), [gap()]);
+        assert_eq!(store.find("acc", "home", "c1").unwrap(), Some(gap()));
+        assert_eq!(store.find("acc", "home", "n3").unwrap(), Some(gap()));
+        assert_eq!(store.find("acc", "local", "n3").unwrap(), None);
+
+        store.replace("acc", "home", &gap(), None).unwrap();
+        assert!(store.list("acc", "home").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+//! ノート本文の翻訳。
+//!
+//! サーバーの `notes/translate` は管理者が DeepL 等を設定していないと空を返す
+//! (あるいはエンドポイント自体が無い)。その場合に備え、ユーザーが vault に
+//! 登録した DeepL / LibreTranslate の接続で直接翻訳できるようにする。
+//! どのプロバイダをどの順で試すかはアカウントごとの設定としてフロントが持ち、
+//! `translate_text` は先頭から順に試して最初に成功した結果を返す。
+//! 結果は本文と翻訳先言語をキーにメモリ上へキャッシュする。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::commands::{AppState, Result};
+use crate::vault::fetch::{vault_fetch, VaultFetchRequest};
+
+/// キャッシュに置く翻訳の上限。
+const MAX_CACHED: usize = 500;
+/// キャッシュした翻訳を使い回す期間。
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// 外部プロバイダへ送る本文の上限 (文字数)。
+const MAX_TEXT_CHARS: usize = 10_000;
+const PROVIDER_TIMEOUT_MS: u64 = 20_000;
+
+/// 翻訳に使うプロバイダ。`connection_id` は vault の接続 ID。
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TranslationProvider {
+    /// サーバーの `notes/translate` (`note_id` が必要)
+    Server,
+    #[serde(rename_all = "camelCase")]
+    Deepl { connection_id: String },
+    #[serde(rename_all = "camelCase")]
+    LibreTranslate { connection_id: String },
+}
+
+impl TranslationProvider {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Server => "server",
+            Self::Deepl { .. } => "deepl",
+            Self::LibreTranslate { .. } => "libreTranslate",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslateRequest {
+    pub account_id: String,
+    /// サーバー翻訳に使うノート ID。無ければ `server` は飛ばす
+    pub note_id: Option<String>,
+    pub text: String,
+    /// 翻訳先の言語 (BCP 47。例: `ja`, `en-US`)
+    pub target_lang: String,
+    /// 試す順。空ならサーバー翻訳のみ
+    #[serde(default)]
+    pub providers: Vec<TranslationProvider>,
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Translation {
+    pub text: String,
+    /// 検出された原文の言語 (プロバイダが返した場合)
+    pub source_lang: Option<String>,
+    /// 翻訳したプロバイダ (`server` / `deepl` / `libreTranslate`)
+    pub provider: String,
+    /// キャッシュから返したか
+    pub cached: bool,
+}
+
+struct CachedTranslation {
+    stored_at: Instant,
+    translation: Translation,
+}
+
+/// 翻訳結果のキャッシュ。プロバイダが違っても同じ本文・言語なら使い回す。
+#[derive(Default)]
+pub struct TranslationCache {
+    entries: Mutex<HashMap<String, CachedTranslation>>,
+}
+
+fn cache_key(target_lang: &str, text: &str) -> String {
+    format!("{}\0{text}", target_lang.to_ascii_lowercase())
+}
+
+impl TranslationCache {
+    fn get(&self, key: &str) -> Option<Translation> {
+        let entries = self.entries.lock().ok()?;
+        let entry = entries.get(key)?;
+        (entry.stored_at.elapsed() < CACHE_TTL).then(|| Translation {
+            cached: true,
+            ..entry.translation.clone()
+        })
+    }
+
+    fn put(&self, key: String, translation: Translation) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, entry| entry.stored_at.elapsed() < CACHE_TTL);
+        if entries.len() >= MAX_CACHED {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedTranslation {
+                stored_at: Instant::now(),
+                translation,
+            },
+        );
+    }
+}
+
+/// DeepL の `target_lang`。英語・ポルトガル語だけ地域付きを受け付ける。
+fn deepl_target(lang: &str) -> String {
+    let upper = lang.replace('_', "-").to_ascii_uppercase();
+    let primary = upper.split('-').next().unwrap_or_default();
+    match upper.as_str() {
+        "EN-GB" | "EN-US" | "PT-BR" | "PT-PT" => upper,
+        _ => primary.to_string(),
+    }
+}
+
+/// LibreTranslate は主言語の小文字コードのみ受け付ける。
+fn libre_target(lang: &str) -> String {
+    lang.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn non_empty(text: Option<&str>) -> Option<String> {
+    text.filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+/// `notes/translate` の応答。翻訳が未設定のサーバーは 204 (空) を返す。
+fn parse_server(value: &serde_json::Value) -> Option<(String, Option<String>)> {
+    let text = non_empty(value.get("text")?.as_str())?;
+    let source = non_empty(value.get("sourceLang").and_then(|v| v.as_str()));
+    Some((text, source))
+}
+
+/// DeepL `v2/translate` の応答。
+fn parse_deepl(value: &serde_json::Value) -> Option<(String, Option<String>)> {
+    let first = value.get("translations")?.get(0)?;
+    let text = non_empty(first.get("text")?.as_str())?;
+    let source = non_empty(
+        first
+            .get("detected_source_language")
+            .and_then(|v| v.as_str()),
+    )
+    .map(|lang| lang.to_ascii_lowercase());
+    Some((text, source))
+}
+
+/// LibreTranslate `translate` の応答。
+fn parse_libre(value: &serde_json::Value) -> Option<(String, Option<String>)> {
+    let text = non_empty(value.get("translatedText")?.as_str())?;
+    let source = non_empty(
+        value
+            .get("detectedLanguage")
+            .and_then(|d| d.get("language"))
+            .and_then(|v| v.as_str()),
+    );
+    Some((text, source))
+}
+
+/// vault 接続に JSON を POST し、2xx の応答を JSON として返す。
+async fn post_json(
+    app: &tauri::AppHandle,
+    connection_id: &str,
+    path: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let response = vault_fetch(
+        app,
+        connection_id,
+        VaultFetchRequest {
+            path: path.to_string(),
+            method: Some("POST".into()),
+            headers: Some(HashMap::from([(
+                "Content-Type".to_string(),
+                "application/json".to_string(),
+            )])),
+            body: Some(body.to_string()),
+            timeout_ms: Some(PROVIDER_TIMEOUT_MS),
+            slot: None,
+        },
+    )
+    .await
+    .map_err(|e| NoteDeckError::InvalidInput(e.to_string()))?;
+    if !(200..300).contains(&response.status) {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "translation provider returned HTTP {}",
+            response.status
+        )));
+    }
+    serde_json::from_str(&response.body)
+        .map_err(|e| NoteDeckError::InvalidInput(format!("invalid translation response: {e}")))
+}
+
+async fn translate_with(
+    app: &tauri::AppHandle,
+    app_state: &AppState,
+    request: &TranslateRequest,
+    provider: &TranslationProvider,
+) -> Result<Option<(String, Option<String>)>> {
+    match provider {
+        TranslationProvider::Server => {
+            let Some(note_id) = &request.note_id else {
+                return Ok(None);
+            };
+            let (client, host, token) = app_state.authed(&request.account_id).await?;
+            let value = client
+                .request(
+                    &host,
+                    &token,
+                    "notes/translate",
+                    serde_json::json!({ "noteId": note_id, "targetLang": request.target_lang }),
+                )
+                .await?;
+            Ok(parse_server(&value))
+        }
+        TranslationProvider::Deepl { connection_id } => {
+            let body = serde_json::json!({
+                "text": [request.text],
+                "target_lang": deepl_target(&request.target_lang),
+            });
+            let value = post_json(app, connection_id, "v2/translate", body).await?;
+            Ok(parse_deepl(&value))
+        }
+        TranslationProvider::LibreTranslate { connection_id } => {
+            let body = serde_json::json!({
+                "q": request.text,
+                "source": "auto",
+                "target": libre_target(&request.target_lang),
+                "format": "text",
+            });
+            let value = post_json(app, connection_id, "translate", body).await?;
+            Ok(parse_libre(&value))
+        }
+    }
+}
+
+/// 本文を翻訳する。`providers` を順に試し、失敗・未設定なら次へフォールバックする。
+#[tauri::command]
+#[specta::specta]
+pub async fn translate_text(
+    app: tauri::AppHandle,
+    app_state: State<'_, AppState>,
+    cache: State<'_, TranslationCache>,
+    request: TranslateRequest,
+) -> Result<Translation> {
+    if request.text.trim().is_empty() {
+        return Err(NoteDeckError::InvalidInput("Nothing to translate".into()));
+    }
+    if request.text.chars().count() > MAX_TEXT_CHARS {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "Text too long to translate (max {MAX_TEXT_CHARS} chars)"
+        )));
+    }
+    let key = cache_key(&request.target_lang, &request.text);
+    if let Some(hit) = cache.get(&key) {
+        return Ok(hit);
+    }
+
+    let providers = if request.providers.is_empty() {
+        vec![TranslationProvider::Server]
+    } else {
+        request.providers.clone()
+    };
+    let mut last_error = None;
+    for provider in &providers {
+        match translate_with(&app, &app_state, &request, provider).await {
+            Ok(Some((text, source_lang))) => {
+                let translation = Translation {
+                    text,
+                    source_lang,
+                    provider: provider.name().to_string(),
+                    cached: false,
+                };
+                cache.put(key, translation.clone());
+                return Ok(translation);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::debug!(provider = provider.name(), error = %e, "[translation] provider failed");
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        NoteDeckError::InvalidInput("No translation provider is available".into())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn target_languages_are_mapped_per_provider() {
+        assert_eq!(deepl_target("ja"), "JA");
+        assert_eq!(deepl_target("ja-JP"), "JA");
+        assert_eq!(deepl_target("en-us"), "EN-US");
+        assert_eq!(deepl_target("pt_BR"), "PT-BR");
+        assert_eq!(libre_target("zh-TW"), "zh");
+        assert_eq!(libre_target("EN"), "en");
+    }
+
+    #[test]
+    fn provider_responses_are_parsed() {
+        assert_eq!(
+            parse_server(&json!({ "sourceLang": "en", "text": "こんにちは" })),
+            Some(("こんにちは".into(), Some("en".into())))
+        );
+        assert_eq!(parse_server(&json!(null)), None);
+        assert_eq!(
+            parse_deepl(&json!({
+                "translations": [{ "detected_source_language": "EN", "text": "やあ" }]
+            })),
+            Some(("やあ".into(), Some("en".into())))
+        );
+        assert_eq!(
+            parse_libre(&json!({
+                "translatedText": "hello",
+                "detectedLanguage": { "confidence": 90, "language": "ja" }
+            })),
+            Some(("hello".into(), Some("ja".into())))
+        );
+        assert_eq!(parse_libre(&json!({ "translatedText": "" })), None);
+    }
+
+    #[test]
+    fn provider_preference_deserializes() {
+        let providers: Vec<TranslationProvider> = serde_json::from_value(json!([
+            { "kind": "server" },
+            { "kind": "deepl", "connectionId": "c1" },
+            { "kind": "libreTranslate", "connectionId": "c2" },
+        ]))
+        .unwrap();
+        assert!(matches!(providers[0], TranslationProvider::Server));
+        assert!(
+            matches!(&providers[1], TranslationProvider::Deepl { connection_id } if connection_id == "c1")
+        );
+        assert_eq!(providers[2].name(), "libreTranslate");
+    }
+
+    #[test]
+    fn cache_hits_are_marked_and_ignore_target_case() {
+        let cache = TranslationCache::default();
+        cache.put(
+            cache_key("en-US", "やあ"),
+            Translation {
+                text: "hi".into(),
+                source_lang: Some("ja".into()),
+                provider: "deepl".into(),
+                cached: false,
+            },
+        );
+        let hit = cache.get(&cache_key("EN-us", "やあ")).unwrap();
+        assert!(hit.cached);
+        assert_eq!(hit.text, "hi");
+        assert!(cache.get(&cache_key("ja", "やあ")).is_none());
+    }
+}
@@ -0,0 +1,573 @@
+//! システムトレイとそのメニュー。
+//!
+//! 固定項目 (表示 / クイック投稿 / オフライン / リアルタイム / 終了) に加え、
+//! 登録済みアカウントごとのサブメニューを並べる:
+//! - 通知を開く: メインウィンドウを表示して `nd:tray-open-notifications`
+//! - このアカウントで投稿: クイック投稿ミニウィンドウ (quick_post::open)
+//! - ストリーミング: realtime / polling をそのアカウントだけ切り替える
+//! - 最近の通知 (RECENT_PER_ACCOUNT 件): クリックで通知カラムへ
+//!
+//! メニューはアカウントの追加・削除・ログアウトのたびに作り直す
+//! (refresh_accounts)。最近の通知はストリーミングの emitter が
+//! RecentNotifications に積み、まとめて作り直す。ストリーミングの
+//! チェック状態は stream_* コマンドから sync_stream で個別に更新し、
+//! 接続のたびに作り直すことはしない。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// トレイアイコンの ID。
+#[cfg_attr(mobile, allow(dead_code))]
+pub const TRAY_ID: &str = "main";
+
+/// 通知カラムを開く対象アカウントをフロントへ伝えるイベント。
+#[cfg_attr(mobile, allow(dead_code))]
+const OPEN_NOTIFICATIONS_EVENT: &str = "nd:tray-open-notifications";
+
+/// トレイから polling に切り替えたときの間隔 (パフォーマンス設定の既定値と同じ)。
+#[cfg_attr(mobile, allow(dead_code))]
+const TRAY_POLL_INTERVAL_MS: u64 = 30_000;
+
+/// アカウントごとに保持する最近の通知の件数。
+const RECENT_PER_ACCOUNT: usize = 5;
+/// 最近の通知のメニュー項目の最大文字数。
+const RECENT_LABEL_MAX_CHARS: usize = 48;
+/// 通知のバーストで何度も作り直さないよう、最初の通知からこれだけ待って
+/// まとめて反映する。
+#[cfg_attr(mobile, allow(dead_code))]
+const RECENT_REBUILD_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// メニュー項目 ID とその操作。アカウント別の項目は `acct:<操作>:<id>`。
+#[cfg_attr(mobile, allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TrayAction {
+    Show,
+    QuickPost,
+    Offline,
+    Realtime,
+    Quit,
+    Notifications(String),
+    ComposeAs(String),
+    ToggleStream(String),
+    /// (account_id, notification_id)
+    RecentNotification(String, String),
+}
+
+#[cfg_attr(mobile, allow(dead_code))]
+impl TrayAction {
+    fn parse(id: &str) -> Option<Self> {
+        Some(match id {
+            "show" => Self::Show,
+            "quick_post" => Self::QuickPost,
+            "offline" => Self::Offline,
+            "realtime" => Self::Realtime,
+            "quit" => Self::Quit,
+            _ => {
+                let (action, account_id) = id.strip_prefix("acct:")?.split_once(':')?;
+                if account_id.is_empty() {
+                    return None;
+                }
+                let account_id = account_id.to_string();
+                match action {
+                    "notifications" => Self::Notifications(account_id),
+                    "compose" => Self::ComposeAs(account_id),
+                    "stream" => Self::ToggleStream(account_id),
+                    "recent" => {
+                        let (account_id, notification_id) = account_id.split_once(':')?;
+                        if account_id.is_empty() || notification_id.is_empty() {
+                            return None;
+                        }
+                        Self::RecentNotification(
+                            account_id.to_string(),
+                            notification_id.to_string(),
+                        )
+                    }
+                    _ => return None,
+                }
+            }
+        })
+    }
+
+    fn id(&self) -> String {
+        match self {
+            Self::Show => "show".to_string(),
+            Self::QuickPost => "quick_post".to_string(),
+            Self::Offline => "offline".to_string(),
+            Self::Realtime => "realtime".to_string(),
+            Self::Quit => "quit".to_string(),
+            Self::Notifications(id) => format!("acct:notifications:{id}"),
+            Self::ComposeAs(id) => format!("acct:compose:{id}"),
+            Self::ToggleStream(id) => format!("acct:stream:{id}"),
+            Self::RecentNotification(id, notification_id) => {
+                format!("acct:recent:{id}:{notification_id}")
+            }
+        }
+    }
+}
+
+/// サブメニューの見出し。表示名があれば併記する。
+#[cfg_attr(mobile, allow(dead_code))]
+fn account_label(username: &str, host: &str, display_name: Option<&str>) -> String {
+    let acct = format!("@{username}@{host}");
+    match display_name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => format!("{name} ({acct})"),
+        None => acct,
+    }
+}
+
+/// 最近の通知のメニュー項目名。`<title> <body>: <本文>` を MFM を除いて
+/// 1 行にし、RECENT_LABEL_MAX_CHARS で切る。
+pub fn recent_label(title: &str, body: Option<&str>, text: Option<&str>) -> String {
+    let mut label = match body {
+        Some(body) => format!("{title} {body}"),
+        None => title.to_string(),
+    };
+    let text = text.map(crate::mfm::to_plain_text).unwrap_or_default();
+    if !text.is_empty() {
+        label.push_str(": ");
+        label.push_str(&text);
+    }
+    if label.chars().count() > RECENT_LABEL_MAX_CHARS {
+        label = label.chars().take(RECENT_LABEL_MAX_CHARS - 1).collect();
+        label.push('…');
+    }
+    label
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentNotification {
+    pub id: String,
+    pub label: String,
+}
+
+/// アカウントごとの最近の通知。ストリーミングの emitter が積む。
+#[derive(Default)]
+pub struct RecentNotifications {
+    /// account_id → 新しい順
+    by_account: Mutex<HashMap<String, VecDeque<RecentNotification>>>,
+    changed: Arc<tokio::sync::Notify>,
+}
+
+impl RecentNotifications {
+    /// 先頭に積み、古いものを捨てる。同じ通知 (複数購読からの重複) は無視する。
+    pub fn push(&self, account_id: &str, notification: RecentNotification) {
+        let mut by_account = self.by_account.lock().unwrap();
+        let recent = by_account.entry(account_id.to_string()).or_default();
+        if recent.iter().any(|n| n.id == notification.id) {
+            return;
+        }
+        recent.push_front(notification);
+        recent.truncate(RECENT_PER_ACCOUNT);
+        self.changed.notify_one();
+    }
+
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn list(&self, account_id: &str) -> Vec<RecentNotification> {
+        self.by_account
+            .lock()
+            .unwrap()
+            .get(account_id)
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// メニューの作り直しに使うアカウント一覧と、アカウント別ストリーミング
+/// 項目のハンドル (チェック状態の更新用)。
+#[cfg(not(mobile))]
+#[derive(Default)]
+pub struct TrayMenuState {
+    accounts: Mutex<Vec<notecli::models::AccountPublic>>,
+    stream_items: Mutex<HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>>,
+}
+
+#[cfg(not(mobile))]
+fn build_menu(
+    app: &tauri::AppHandle,
+    accounts: &[notecli::models::AccountPublic],
+) -> tauri::Result<(
+    tauri::menu::Menu<tauri::Wry>,
+    HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>,
+)> {
+    use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+    use tauri::Manager;
+
+    let item = |action: TrayAction, label: &str| {
+        MenuItem::with_id(app, action.id(), label, true, None::<&str>)
+    };
+    let show_i = item(TrayAction::Show, "Show NoteDeck")?;
+    let quick_post_i = item(TrayAction::QuickPost, "Quick Post")?;
+    let offline_i = item(TrayAction::Offline, "Offline Mode")?;
+    let realtime_i = item(TrayAction::Realtime, "Realtime Mode")?;
+    let quit_i = item(TrayAction::Quit, "Quit")?;
+
+    let active = app.try_state::<crate::power::ActiveStreams>();
+    let recent = app.try_state::<RecentNotifications>();
+    let mut stream_items = HashMap::new();
+    let mut account_menus = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let id = &account.id;
+        let mode = active.as_ref().and_then(|a| a.stream_mode(id));
+        let notifications_i = MenuItem::with_id(
+            app,
+            TrayAction::Notifications(id.clone()).id(),
+            "Open Notifications",
+            true,
+            None::<&str>,
+        )?;
+        let compose_i = MenuItem::with_id(
+            app,
+            TrayAction::ComposeAs(id.clone()).id(),
+            "Compose as This Account",
+            account.has_token,
+            None::<&str>,
+        )?;
+        let stream_i = CheckMenuItem::with_id(
+            app,
+            TrayAction::ToggleStream(id.clone()).id(),
+            "Realtime Streaming",
+            mode.is_some(),
+            mode == Some(true),
+            None::<&str>,
+        )?;
+        let recent_items = recent
+            .as_ref()
+            .map(|r| r.list(id))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|n| {
+                MenuItem::with_id(
+                    app,
+                    TrayAction::RecentNotification(id.clone(), n.id).id(),
+                    n.label,
+                    true,
+                    None::<&str>,
+                )
+            })
+            .collect::<tauri::Result<Vec<_>>>()?;
+        let recent_separator = PredefinedMenuItem::separator(app)?;
+        let mut entries: Vec<&dyn IsMenuItem<tauri::Wry>> =
+            vec![&notifications_i, &compose_i, &stream_i];
+        if !recent_items.is_empty() {
+            entries.push(&recent_separator);
+            for item in &recent_items {
+                entries.push(item);
+            }
+        }
+        let submenu = Submenu::with_items(
+            app,
+            account_label(
+                &account.username,
+                &account.host,
+                account.display_name.as_deref(),
+            ),
+            true,
+            &entries,
+        )?;
+        stream_items.insert(id.clone(), stream_i);
+        account_menus.push(submenu);
+    }
+
+    let separator = PredefinedMenuItem::separator(app)?;
+    let mode_separator = PredefinedMenuItem::separator(app)?;
+    let mut items: Vec<&dyn IsMenuItem<tauri::Wry>> = vec![&show_i, &quick_post_i];
+    if !account_menus.is_empty() {
+        items.push(&separator);
+        for submenu in &account_menus {
+            items.push(submenu);
+        }
+    }
+    items.push(&mode_separator);
+    items.push(&offline_i);
+    items.push(&realtime_i);
+    items.push(&quit_i);
+    Ok((Menu::with_items(app, &items)?, stream_items))
+}
+
+/// トレイアイコンを作る。setup 時点では DB が未準備なので、アカウント
+/// 項目は準備を待ってから足す。
+#[cfg(not(mobile))]
+pub fn init(app: &tauri::App) -> tauri::Result<()> {
+    use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+    use tauri::Manager;
+
+    let (menu, _) = build_menu(app.handle(), &[])?;
+    let icon = app
+        .default_window_icon()
+        .cloned()
+        .ok_or_else(|| tauri::Error::AssetNotFound("default window icon".into()))?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon)
+        .tooltip("NoteDeck")
+        .menu(&menu)
+        .on_menu_event(|app, event| {
+            if let Some(action) = TrayAction::parse(event.id.as_ref()) {
+                handle_action(app, action);
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                if let Some(w) = app.get_webview_window("main") {
+                    if w.is_visible().unwrap_or(false) {
+                        crate::window_geometry::capture(&w);
+                        let _ = w.hide();
+                    } else {
+                        let _ = w.show();
+                        let _ = w.set_focus();
+                    }
+                }
+            }
+        })
+        .build(app)?;
+    app.manage(TrayMenuState::default());
+    let recent = RecentNotifications::default();
+    let changed = Arc::clone(&recent.changed);
+    app.manage(recent);
+
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(app_state) = handle.try_state::<crate::commands::AppState>() else {
+            return;
+        };
+        let db = app_state.db().await;
+        refresh_accounts(&handle, &db);
+    });
+
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            changed.notified().await;
+            tokio::time::sleep(RECENT_REBUILD_DELAY).await;
+            rebuild(&handle);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(mobile))]
+fn show_main(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    if let Some(w) = app.get_webview_window("main") {
+        let _ = w.show();
+        let _ = w.set_focus();
+    }
+}
+
+/// メニュー項目をバックエンドの capability registry 経由で実行する。
+/// 通知を開く系だけは表示とイベント送出の順序が要るので直接扱う。
+#[cfg(not(mobile))]
+fn handle_action(app: &tauri::AppHandle, action: TrayAction) {
+    use crate::capability_registry::spawn_local;
+    use serde_json::{json, Value};
+    use tauri::Emitter;
+
+    match action {
+        TrayAction::Show => spawn_local(app, "window.show", Value::Null),
+        TrayAction::QuickPost => spawn_local(app, "quickPost.open", Value::Null),
+        TrayAction::Offline => spawn_local(app, "app.toggleOffline", Value::Null),
+        TrayAction::Realtime => spawn_local(app, "app.toggleRealtime", Value::Null),
+        TrayAction::Quit => spawn_local(app, "app.quit", Value::Null),
+        TrayAction::Notifications(account_id) | TrayAction::RecentNotification(account_id, _) => {
+            show_main(app);
+            let _ = app.emit(OPEN_NOTIFICATIONS_EVENT, account_id);
+        }
+        TrayAction::ComposeAs(account_id) => spawn_local(
+            app,
+            "quickPost.open",
+            json!({ "accountId": account_id }),
+        ),
+        TrayAction::ToggleStream(account_id) => spawn_local(
+            app,
+            "streams.toggleMode",
+            json!({ "accountId": account_id }),
+        ),
+    }
+}
+
+/// 接続中ストリームの realtime / polling を反転する。未接続なら何もしない
+/// (項目自体が無効化されている)。
+#[cfg(not(mobile))]
+pub(crate) async fn toggle_stream(
+    app: &tauri::AppHandle,
+    account_id: &str,
+) -> Result<(), notecli::error::NoteDeckError> {
+    use tauri::Manager;
+
+    let (Some(active), Some(app_state), Some(streaming)) = (
+        app.try_state::<crate::power::ActiveStreams>(),
+        app.try_state::<crate::commands::AppState>(),
+        app.try_state::<notecli::streaming::StreamingManager>(),
+    ) else {
+        return Ok(());
+    };
+    let Some(realtime) = active.stream_mode(account_id) else {
+        return Ok(());
+    };
+    let (mode, interval_ms) = if realtime {
+        ("polling", Some(TRAY_POLL_INTERVAL_MS))
+    } else {
+        ("realtime", None)
+    };
+    let db = app_state.db().await;
+    let (host, token) = crate::commands::get_credentials(&db, account_id)?;
+    streaming
+        .set_mode(account_id, &host, &token, mode, interval_ms)
+        .await?;
+    active.set_mode(account_id, mode);
+    Ok(())
+}
+
+/// アカウント一覧を読み直してメニューを作り直す。アカウントの追加・
+/// 削除・ログアウト時と起動時 (DB 準備後) に呼ぶ。
+#[cfg(not(mobile))]
+pub fn refresh_accounts(app: &tauri::AppHandle, db: &notecli::db::Database) {
+    use tauri::Manager;
+
+    let Some(state) = app.try_state::<TrayMenuState>() else {
+        return;
+    };
+    match crate::account_service::list_public(db) {
+        Ok(accounts) => *state.accounts.lock().unwrap() = accounts,
+        Err(e) => {
+            tracing::warn!("[tray] failed to load accounts: {e}");
+            return;
+        }
+    }
+    rebuild(app);
+}
+
+#[cfg(mobile)]
+pub fn refresh_accounts(_app: &tauri::AppHandle, _db: &notecli::db::Database) {}
+
+/// 記録済みのアカウント一覧でメニューを作り直す。
+#[cfg(not(mobile))]
+fn rebuild(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let (Some(tray), Some(state)) = (app.tray_by_id(TRAY_ID), app.try_state::<TrayMenuState>())
+    else {
+        return;
+    };
+    let accounts = state.accounts.lock().unwrap().clone();
+    match build_menu(app, &accounts) {
+        Ok((menu, stream_items)) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                tracing::warn!("[tray] failed to set menu: {e}");
+                return;
+            }
+            *state.stream_items.lock().unwrap() = stream_items;
+        }
+        Err(e) => tracing::warn!("[tray] failed to build menu: {e}"),
+    }
+}
+
+/// ストリーミング項目のチェック / 有効状態を ActiveStreams に合わせる。
+#[cfg(not(mobile))]
+pub fn sync_stream(app: &tauri::AppHandle, account_id: &str) {
+    use tauri::Manager;
+
+    let (Some(state), Some(active)) = (
+        app.try_state::<TrayMenuState>(),
+        app.try_state::<crate::power::ActiveStreams>(),
+    ) else {
+        return;
+    };
+    let items = state.stream_items.lock().unwrap();
+    let Some(item) = items.get(account_id) else {
+        return;
+    };
+    let mode = active.stream_mode(account_id);
+    let _ = item.set_enabled(mode.is_some());
+    let _ = item.set_checked(mode == Some(true));
+}
+
+#[cfg(mobile)]
+pub fn sync_stream(_app: &tauri::AppHandle, _account_id: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_ids_round_trip() {
+        let actions = [
+            TrayAction::Show,
+            TrayAction::QuickPost,
+            TrayAction::Offline,
+            TrayAction::Realtime,
+            TrayAction::Quit,
+            TrayAction::Notifications("9abc".into()),
+            TrayAction::ComposeAs("9abc".into()),
+            TrayAction::ToggleStream("9abc".into()),
+            TrayAction::RecentNotification("9abc".into(), "9xyz".into()),
+        ];
+        for action in actions {
+            assert_eq!(TrayAction::parse(&action.id()), Some(action));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_menu_ids() {
+        assert_eq!(TrayAction::parse("acct:notifications:"), None);
+        assert_eq!(TrayAction::parse("acct:delete:9abc"), None);
+        assert_eq!(TrayAction::parse("acct:9abc"), None);
+        assert_eq!(TrayAction::parse("acct:recent:9abc"), None);
+        assert_eq!(TrayAction::parse("acct:recent:9abc:"), None);
+        assert_eq!(TrayAction::parse("settings"), None);
+    }
+
+    #[test]
+    fn labels_accounts() {
+        assert_eq!(
+            account_label("alice", "misskey.io", Some("Alice")),
+            "Alice (@alice@misskey.io)"
+        );
+        assert_eq!(
+            account_label("alice", "misskey.io", Some("  ")),
+            "@alice@misskey.io"
+        );
+        assert_eq!(
+            account_label("bob", "example.com", None),
+            "@bob@example.com"
+        );
+    }
+
+    #[test]
+    fn recent_keeps_latest_per_account() {
+        let recent = RecentNotifications::default();
+        let n = |id: &str| RecentNotification {
+            id: id.to_string(),
+            label: format!("label {id}"),
+        };
+        for i in 0..7 {
+            recent.push("a", n(&i.to_string()));
+        }
+        // 複数購読からの重複は積まない
+        recent.push("a", n("6"));
+        recent.push("b", n("x"));
+        let ids: Vec<String> = recent.list("a").into_iter().map(|n| n.id).collect();
+        assert_eq!(ids, ["6", "5", "4", "3", "2"]);
+        assert_eq!(recent.list("b").len(), 1);
+        assert!(recent.list("c").is_empty());
+    }
+
+    #[test]
+    fn recent_labels_are_plain_and_short() {
+        assert_eq!(
+            recent_label("Alice", Some("リプライ"), Some("$[x2 **やあ**]\nまた")),
+            "Alice リプライ: やあ また"
+        );
+        assert_eq!(recent_label("ログイン検知", None, None), "ログイン検知");
+        let long = recent_label("Bob", Some("メンション"), Some(&"あ".repeat(100)));
+        assert_eq!(long.chars().count(), RECENT_LABEL_MAX_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
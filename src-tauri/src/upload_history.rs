@@ -0,0 +1,273 @@
+//! 自分がアップロードしたドライブファイルの記録。
+//!
+//! 投稿フォームから最近のアップロードをすぐ添付し直せるように、アップロードに
+//! 成功したファイルのメタデータ (id / 名前 / 種類 / サムネイル / センシティブ
+//! 指定など、`NormalizedDriveFile` そのもの) をアカウントごとに
+//! `app-state.db` (app_db.rs) に残す。ドライブ全体を読み込まずに済むよう、
+//! ここではサーバーに問い合わせない。ドライブで名前やセンシティブ指定を
+//! 変えたファイルは記録も書き換え、削除したファイルやログアウトした
+//! アカウントの記録は消す。
+
+use notecli::error::NoteDeckError;
+use notecli::models::NormalizedDriveFile;
+use rusqlite::params;
+use tauri::{AppHandle, Manager};
+
+use crate::app_db::SharedConn;
+use crate::commands::Result;
+
+/// アカウントごとに残す件数。超えたら古いものから消す。
+const MAX_PER_ACCOUNT: i64 = 100;
+/// `recent_uploads` の既定件数。
+const DEFAULT_LIMIT: i64 = 20;
+
+pub struct UploadHistory {
+    conn: SharedConn,
+}
+
+impl UploadHistory {
+    /// 共有の app-state.db (app_db.rs) に表を作って使う。
+    pub fn new(conn: SharedConn) -> rusqlite::Result<Self> {
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(crate::app_db::open_in_memory()?)
+    }
+
+    fn init(conn: SharedConn) -> rusqlite::Result<Self> {
+        conn.lock().unwrap().execute_batch(
+            "CREATE TABLE IF NOT EXISTS recent_uploads (
+                account_id  TEXT NOT NULL,
+                file_id     TEXT NOT NULL,
+                uploaded_at INTEGER NOT NULL,
+                file        TEXT NOT NULL,
+                PRIMARY KEY (account_id, file_id)
+            );
+            CREATE INDEX IF NOT EXISTS recent_uploads_by_time
+                ON recent_uploads (account_id, uploaded_at DESC);",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// アップロードしたファイルを記録し、上限を超えた古い記録を消す。
+    fn record(
+        &self,
+        account_id: &str,
+        file: &NormalizedDriveFile,
+        uploaded_at: i64,
+    ) -> rusqlite::Result<()> {
+        let json = serde_json::to_string(file)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO recent_uploads (account_id, file_id, uploaded_at, file)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![account_id, file.id, uploaded_at, json],
+        )?;
+        conn.execute(
+            "DELETE FROM recent_uploads WHERE account_id = ?1 AND file_id NOT IN (
+                SELECT file_id FROM recent_uploads WHERE account_id = ?1
+                ORDER BY uploaded_at DESC LIMIT ?2
+            )",
+            params![account_id, MAX_PER_ACCOUNT],
+        )?;
+        Ok(())
+    }
+
+    /// 最近アップロードしたファイルを新しい順に返す。読めない記録は飛ばす。
+    fn recent(&self, account_id: &str, limit: i64) -> rusqlite::Result<Vec<NormalizedDriveFile>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT file FROM recent_uploads WHERE account_id = ?1
+             ORDER BY uploaded_at DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![account_id, limit], |row| row.get::<_, String>(0))?;
+        let mut files = Vec::new();
+        for json in rows {
+            match serde_json::from_str(&json?) {
+                Ok(file) => files.push(file),
+                Err(e) => tracing::warn!("[uploads] skipping unreadable record: {e}"),
+            }
+        }
+        Ok(files)
+    }
+
+    /// 記録済みのファイルを最新のメタデータで置き換える。並び順は変えず、
+    /// 記録に無いファイルは足さない。
+    fn update(&self, account_id: &str, file: &NormalizedDriveFile) -> rusqlite::Result<()> {
+        let json = serde_json::to_string(file)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE recent_uploads SET file = ?3 WHERE account_id = ?1 AND file_id = ?2",
+            params![account_id, file.id, json],
+        )?;
+        Ok(())
+    }
+
+    fn forget(&self, account_id: &str, file_id: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM recent_uploads WHERE account_id = ?1 AND file_id = ?2",
+            params![account_id, file_id],
+        )?;
+        Ok(())
+    }
+
+    fn forget_account(&self, account_id: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM recent_uploads WHERE account_id = ?1",
+            params![account_id],
+        )?;
+        Ok(())
+    }
+}
+
+/// アップロードに成功したファイルを記録する。記録に失敗してもアップロード
+/// 自体は成功なので、ログだけ残す。
+pub(crate) fn remember(app: &AppHandle, account_id: &str, file: &NormalizedDriveFile) {
+    let Some(history) = app.try_state::<UploadHistory>() else {
+        return;
+    };
+    if let Err(e) = history.record(account_id, file, crate::jobs::now_ms()) {
+        tracing::warn!("[uploads] failed to record upload: {e}");
+    }
+}
+
+/// ドライブから削除したファイルを記録から消す。
+pub(crate) fn forget(app: &AppHandle, account_id: &str, file_id: &str) {
+    let Some(history) = app.try_state::<UploadHistory>() else {
+        return;
+    };
+    if let Err(e) = history.forget(account_id, file_id) {
+        tracing::warn!("[uploads] failed to forget upload: {e}");
+    }
+}
+
+/// ドライブで更新したファイル (名前 / センシティブ指定など) を記録に反映する。
+pub(crate) fn refresh(app: &AppHandle, account_id: &str, file: &NormalizedDriveFile) {
+    let Some(history) = app.try_state::<UploadHistory>() else {
+        return;
+    };
+    if let Err(e) = history.update(account_id, file) {
+        tracing::warn!("[uploads] failed to update upload: {e}");
+    }
+}
+
+/// アカウントの記録をすべて消す (アカウント削除 / ログアウト時)。
+pub(crate) fn forget_account(app: &AppHandle, account_id: &str) {
+    let Some(history) = app.try_state::<UploadHistory>() else {
+        return;
+    };
+    if let Err(e) = history.forget_account(account_id) {
+        tracing::warn!("[uploads] failed to forget account uploads: {e}");
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> NoteDeckError {
+    NoteDeckError::InvalidInput(format!("upload history: {e}"))
+}
+
+fn store(app: &AppHandle) -> Result<tauri::State<'_, UploadHistory>> {
+    app.try_state::<UploadHistory>()
+        .ok_or_else(|| NoteDeckError::InvalidInput("upload history unavailable".to_string()))
+}
+
+/// 最近アップロードしたファイルを新しい順に返す (既定 20 件、最大 100 件)。
+/// 投稿フォームの「最近のアップロード」から添付し直すのに使う。
+#[tauri::command]
+#[specta::specta]
+pub fn recent_uploads(
+    app: AppHandle,
+    account_id: String,
+    limit: Option<i64>,
+) -> Result<Vec<NormalizedDriveFile>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_PER_ACCOUNT);
+    store(&app)?.recent(&account_id, limit).map_err(db_error)
+}
+
+/// 最近のアップロードから 1 件外す (ドライブのファイルはそのまま)。
+#[tauri::command]
+#[specta::specta]
+pub fn forget_recent_upload(app: AppHandle, account_id: String, file_id: String) -> Result<()> {
+    store(&app)?.forget(&account_id, &file_id).map_err(db_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(id: &str) -> NormalizedDriveFile {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("{id}.png"),
+            "type": "image/png",
+            "url": format!("https://x.example/{id}.png"),
+            "thumbnailUrl": format!("https://x.example/{id}.webp"),
+            "isSensitive": false,
+        }))
+        .unwrap()
+    }
+
+    fn ids(files: &[NormalizedDriveFile]) -> Vec<&str> {
+        files.iter().map(|f| f.id.as_str()).collect()
+    }
+
+    #[test]
+    fn recent_lists_newest_first_per_account() {
+        let history = UploadHistory::open_in_memory().unwrap();
+        history.record("acc1", &file("a"), 1).unwrap();
+        history.record("acc1", &file("b"), 2).unwrap();
+        history.record("acc2", &file("c"), 3).unwrap();
+        // 同じファイルを記録し直すと先頭に来る
+        history.record("acc1", &file("a"), 4).unwrap();
+
+        let recent = history.recent("acc1", 10).unwrap();
+        assert_eq!(ids(&recent), ["a", "b"]);
+        assert_eq!(recent[1].name, "b.png");
+        assert_eq!(ids(&history.recent("acc1", 1).unwrap()), ["a"]);
+        assert_eq!(ids(&history.recent("acc2", 10).unwrap()), ["c"]);
+
+        history.forget("acc1", "a").unwrap();
+        assert_eq!(ids(&history.recent("acc1", 10).unwrap()), ["b"]);
+    }
+
+    #[test]
+    fn update_rewrites_known_files_and_account_purge_clears_rows() {
+        let history = UploadHistory::open_in_memory().unwrap();
+        history.record("acc1", &file("a"), 1).unwrap();
+        history.record("acc1", &file("b"), 2).unwrap();
+        history.record("acc2", &file("c"), 3).unwrap();
+
+        let mut renamed = file("a");
+        renamed.name = "renamed.png".to_string();
+        renamed.is_sensitive = true;
+        history.update("acc1", &renamed).unwrap();
+        // 記録に無いファイルは足さない
+        history.update("acc1", &file("z")).unwrap();
+
+        let recent = history.recent("acc1", 10).unwrap();
+        assert_eq!(ids(&recent), ["b", "a"]);
+        assert_eq!(recent[1].name, "renamed.png");
+        assert!(recent[1].is_sensitive);
+
+        history.forget_account("acc1").unwrap();
+        assert!(history.recent("acc1", 10).unwrap().is_empty());
+        assert_eq!(ids(&history.recent("acc2", 10).unwrap()), ["c"]);
+    }
+
+    #[test]
+    fn record_prunes_past_the_cap() {
+        let history = UploadHistory::open_in_memory().unwrap();
+        for i in 0..=MAX_PER_ACCOUNT {
+            history.record("acc1", &file(&format!("f{i}")), i).unwrap();
+        }
+        let recent = history.recent("acc1", MAX_PER_ACCOUNT + 1).unwrap();
+        assert_eq!(recent.len() as i64, MAX_PER_ACCOUNT);
+        assert_eq!(recent[0].id, format!("f{MAX_PER_ACCOUNT}"));
+        assert!(recent.iter().all(|f| f.id != "f0"));
+    }
+}
@@ -0,0 +1,705 @@
+//! ドライブへのアップロード前にファイルへ施す加工。
+//!
+//! 写真の EXIF には撮影位置 (GPS) や機種・撮影日時が入っており、そのまま
+//! アップロードすると意図せず公開してしまう。`strip_metadata` が有効なら
+//! JPEG / PNG / WebP のメタデータ領域を取り除いてから `drive/files/create` に
+//! 送る。画素データは再エンコードせずコンテナ上のチャンク単位で落とすので
+//! 画質は変わらない。向き (Orientation) だけは消すと写真が横倒しになるため、
+//! 元が 1 (正立) 以外なら Orientation 1 項目だけの EXIF を作り直して残す。
+//! 構造を解析できない (途中で切れている等) ファイルは、メタデータが残って
+//! いるかもしれないので元のまま送らずエラーにする。
+//!
+//! スマホの写真はそのままだとドライブ容量やサーバーのサイズ上限を圧迫するので、
+//! 長辺が `max_dimension` を超える画像は縮小して再エンコードする。HEIC / HEIF
+//! は `heic` feature 付きビルド (libheif が必要) なら JPEG に変換する。
+//! 再エンコードした画像はメタデータを持たず、向きは画素に焼き込む。
+//! アニメーション PNG / WebP は 1 フレーム目しか残らなくなるので縮小しない。
+//!
+//! 設定はバックエンド設定 (`settings.rs`) の `uploadPrep` セクションに保存し、
+//! フロントの `upload.*` 設定値が変わったときに `upload_prep_configure` で
+//! 書き換える。保存してあるので、フロントが起動する前に再開したジョブの
+//! アップロードにも同じ設定が効く。
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::commands::Result;
+use crate::settings::SettingsStore;
+
+/// このビルドが HEIC をデコードできるか。
+const HEIC_SUPPORTED: bool = cfg!(feature = "heic");
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+/// 加工対象の画像として受け付ける元ファイルの上限。アップロード上限
+/// (`MAX_UPLOAD_BYTES`) は加工後のサイズで判定する。
+pub const MAX_PREP_INPUT_BYTES: usize = 200 * 1024 * 1024;
+
+type PrepResult<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadPrepConfig {
+    /// EXIF / GPS 等のメタデータを取り除く (JPEG / PNG / WebP)
+    pub strip_metadata: bool,
+    /// 長辺がこれ (px) を超える画像を縮小する。null で縮小しない
+    pub max_dimension: Option<u32>,
+    /// 再エンコード時の JPEG 品質 (1〜100)
+    pub jpeg_quality: u8,
+    /// HEIC / HEIF を JPEG に変換する (`heic` feature 付きビルドのみ)
+    pub convert_heic: bool,
+}
+
+impl Default for UploadPrepConfig {
+    fn default() -> Self {
+        Self {
+            strip_metadata: true,
+            max_dimension: None,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+            convert_heic: true,
+        }
+    }
+}
+
+/// 加工後のファイル。再エンコードで形式が変わると MIME とファイル名も変わる。
+#[derive(Debug)]
+pub struct Prepared {
+    pub data: Vec<u8>,
+    pub content_type: String,
+    pub file_name: String,
+}
+
+pub struct UploadPrep {
+    settings: Arc<SettingsStore>,
+}
+
+impl UploadPrep {
+    pub fn new(settings: Arc<SettingsStore>) -> Self {
+        Self { settings }
+    }
+
+    pub fn config(&self) -> UploadPrepConfig {
+        self.settings.read(|s| s.upload_prep.clone())
+    }
+
+    pub fn set_config(&self, config: UploadPrepConfig) -> Result<()> {
+        self.settings
+            .update(|s| {
+                s.upload_prep = config;
+                Ok(())
+            })
+            .map(|_| ())
+    }
+
+    /// `content_type` のファイルを加工するか。パス指定のアップロードは、
+    /// 加工しないならディスクからそのままストリーミングする。
+    pub fn applies_to(&self, content_type: &str) -> bool {
+        self.config().applies_to(content_type)
+    }
+
+    /// アップロードするファイルを加工して返す。デコードは重いので
+    /// blocking スレッドで行う。
+    pub async fn prepare(
+        &self,
+        data: Vec<u8>,
+        content_type: &str,
+        file_name: &str,
+    ) -> Result<Prepared> {
+        let config = self.config();
+        let content_type = content_type.to_string();
+        let file_name = file_name.to_string();
+        tokio::task::spawn_blocking(move || config.prepare(data, content_type, file_name))
+            .await
+            .map_err(|e| NoteDeckError::InvalidInput(format!("Upload preparation failed: {e}")))?
+    }
+}
+
+impl UploadPrepConfig {
+    fn applies_to(&self, content_type: &str) -> bool {
+        match container_of(content_type) {
+            Some(Container::Heic) => self.convert_heic && HEIC_SUPPORTED,
+            Some(_) => self.strip_metadata || self.max_dimension.is_some(),
+            None => false,
+        }
+    }
+
+    /// 対象外のファイルは元のまま返す。メタデータを取り除く設定で画像を
+    /// 解析できなければ、元のまま送らずにエラーにする。
+    fn prepare(&self, data: Vec<u8>, content_type: String, file_name: String) -> Result<Prepared> {
+        let Some(container) = container_of(&content_type) else {
+            return Ok(Prepared {
+                data,
+                content_type,
+                file_name,
+            });
+        };
+        match self.reencode(&data, container) {
+            Ok(Some((data, format))) => {
+                return Ok(Prepared {
+                    data,
+                    content_type: format.to_mime_type().to_string(),
+                    file_name: with_extension(&file_name, format.extensions_str()[0]),
+                });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(%content_type, error = %e, "[upload-prep] re-encode failed, uploading original");
+            }
+        }
+        let data = if self.strip_metadata && container != Container::Heic {
+            strip_metadata(&data, container).ok_or_else(|| {
+                NoteDeckError::InvalidInput(format!(
+                    "Could not remove metadata from {file_name}: the image is damaged or unsupported"
+                ))
+            })?
+        } else {
+            data
+        };
+        Ok(Prepared {
+            data,
+            content_type,
+            file_name,
+        })
+    }
+
+    /// 縮小・HEIC 変換が必要なら再エンコードした画像と形式を返す。不要なら None。
+    fn reencode(
+        &self,
+        data: &[u8],
+        container: Container,
+    ) -> PrepResult<Option<(Vec<u8>, ImageFormat)>> {
+        let image = if container == Container::Heic {
+            if !self.convert_heic || !HEIC_SUPPORTED {
+                return Ok(None);
+            }
+            decode_heic(data)?
+        } else {
+            let Some(max) = self.max_dimension else {
+                return Ok(None);
+            };
+            if is_animated(data, container) {
+                return Ok(None);
+            }
+            let (width, height) = ImageReader::new(Cursor::new(data))
+                .with_guessed_format()?
+                .into_dimensions()?;
+            if width.max(height) <= max {
+                return Ok(None);
+            }
+            let mut decoder = ImageReader::new(Cursor::new(data))
+                .with_guessed_format()?
+                .into_decoder()?;
+            let orientation = decoder.orientation()?;
+            let mut image = DynamicImage::from_decoder(decoder)?;
+            image.apply_orientation(orientation);
+            image
+        };
+        let image = match self.max_dimension {
+            Some(max) if image.width().max(image.height()) > max => {
+                image.resize(max, max, FilterType::Lanczos3)
+            }
+            _ => image,
+        };
+        // スクリーンショット (PNG) と透過のある画像は PNG、写真は JPEG にする
+        let format = match container {
+            Container::Png => ImageFormat::Png,
+            Container::Webp if image.color().has_alpha() => ImageFormat::Png,
+            _ => ImageFormat::Jpeg,
+        };
+        let mut out = Vec::new();
+        if format == ImageFormat::Jpeg {
+            let encoder = JpegEncoder::new_with_quality(&mut out, self.jpeg_quality.clamp(1, 100));
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+        } else {
+            image.write_to(&mut Cursor::new(&mut out), format)?;
+        }
+        Ok(Some((out, format)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Jpeg,
+    Png,
+    Webp,
+    Heic,
+}
+
+fn container_of(content_type: &str) -> Option<Container> {
+    match content_type.to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some(Container::Jpeg),
+        "image/png" | "image/apng" => Some(Container::Png),
+        "image/webp" => Some(Container::Webp),
+        "image/heic" | "image/heif" => Some(Container::Heic),
+        _ => None,
+    }
+}
+
+/// 拡張子を `ext` に付け替える (`IMG_0001.HEIC` → `IMG_0001.jpg`)。
+fn with_extension(file_name: &str, ext: &str) -> String {
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+    format!("{stem}.{ext}")
+}
+
+/// APNG (`acTL` チャンク) / アニメーション WebP (VP8X の bit 1) か。
+fn is_animated(data: &[u8], container: Container) -> bool {
+    match container {
+        Container::Png => {
+            let mut pos = PNG_SIGNATURE.len();
+            while let Some(len_bytes) = data.get(pos..pos + 4) {
+                let len = u32::from_be_bytes(len_bytes.try_into().unwrap()) as usize;
+                match data.get(pos + 4..pos + 8) {
+                    Some(b"acTL") => return true,
+                    Some(b"IDAT") | None => return false,
+                    _ => pos += 12 + len,
+                }
+            }
+            false
+        }
+        Container::Webp => {
+            data.get(12..16) == Some(b"VP8X".as_slice())
+                && data.get(20).is_some_and(|f| f & 0x02 != 0)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(feature = "heic")]
+fn decode_heic(data: &[u8]) -> PrepResult<DynamicImage> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let lib = LibHeif::new();
+    let context = HeifContext::read_from_bytes(data)?;
+    let handle = context.primary_image_handle()?;
+    // libheif は irot / imir (向き) を適用済みの画素を返す
+    let image = lib.decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
+    let plane = image
+        .planes()
+        .interleaved
+        .ok_or("HEIC image has no interleaved plane")?;
+    let row_len = plane.width as usize * 3;
+    let mut rgb = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        rgb.extend_from_slice(row.get(..row_len).ok_or("HEIC row is too short")?);
+    }
+    let buffer = image::RgbImage::from_raw(plane.width, plane.height, rgb)
+        .ok_or("HEIC plane size mismatch")?;
+    Ok(DynamicImage::ImageRgb8(buffer))
+}
+
+#[cfg(not(feature = "heic"))]
+fn decode_heic(_data: &[u8]) -> PrepResult<DynamicImage> {
+    Err("built without HEIC support".into())
+}
+
+fn strip_metadata(data: &[u8], container: Container) -> Option<Vec<u8>> {
+    let orientation = orientation(data).filter(|&o| o != 1);
+    match container {
+        Container::Jpeg => strip_jpeg(data, orientation),
+        Container::Png => strip_png(data, orientation),
+        Container::Webp => strip_webp(data, orientation),
+        Container::Heic => None,
+    }
+}
+
+/// EXIF の Orientation (1〜8)。EXIF が無ければ None。
+fn orientation(data: &[u8]) -> Option<u16> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()?;
+    let field = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?;
+    let value = field.value.get_uint(0)?;
+    (1..=8).contains(&value).then_some(value as u16)
+}
+
+/// Orientation 1 項目だけの TIFF (EXIF 本体)。
+fn orientation_tiff(orientation: u16) -> Vec<u8> {
+    let mut tiff = Vec::with_capacity(26);
+    tiff.extend_from_slice(b"MM\0\x2A");
+    tiff.extend_from_slice(&8u32.to_be_bytes());
+    tiff.extend_from_slice(&1u16.to_be_bytes());
+    // tag=Orientation, type=SHORT, count=1, value (4 バイト枠に左詰め)
+    tiff.extend_from_slice(&0x0112u16.to_be_bytes());
+    tiff.extend_from_slice(&3u16.to_be_bytes());
+    tiff.extend_from_slice(&1u32.to_be_bytes());
+    tiff.extend_from_slice(&orientation.to_be_bytes());
+    tiff.extend_from_slice(&[0, 0]);
+    tiff.extend_from_slice(&0u32.to_be_bytes());
+    tiff
+}
+
+/// JPEG: APP1 (EXIF / XMP)・APP13 (IPTC)・COM を落とす。SOS 以降は素通し。
+fn strip_jpeg(data: &[u8], orientation: Option<u16>) -> Option<Vec<u8>> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pending_orientation = orientation;
+    let mut pos = 2;
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(pos + 1)?;
+        if marker == 0xFF {
+            // フィルバイト
+            pos += 1;
+            continue;
+        }
+        // JFIF では APP0 が先頭に来る必要があるので、作り直す EXIF はその直後に置く
+        if marker != 0xE0 {
+            if let Some(orientation) = pending_orientation.take() {
+                let mut payload = b"Exif\0\0".to_vec();
+                payload.extend_from_slice(&orientation_tiff(orientation));
+                out.extend_from_slice(&[0xFF, 0xE1]);
+                out.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+                out.extend_from_slice(&payload);
+            }
+        }
+        match marker {
+            // SOS / EOI 以降は画像データ
+            0xDA | 0xD9 => {
+                out.extend_from_slice(&data[pos..]);
+                return Some(out);
+            }
+            // 長さを持たないマーカー
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+            }
+            _ => {
+                let len = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+                let end = pos + 2 + len;
+                if len < 2 || end > data.len() {
+                    return None;
+                }
+                if !matches!(marker, 0xE1 | 0xED | 0xFE) {
+                    out.extend_from_slice(&data[pos..end]);
+                }
+                pos = end;
+            }
+        }
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn push_png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], payload: &[u8]) {
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(payload);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// PNG: eXIf と テキスト系 (tEXt / zTXt / iTXt)・tIME チャンクを落とす。
+fn strip_png(data: &[u8], orientation: Option<u16>) -> Option<Vec<u8>> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pending_orientation = orientation;
+    let mut pos = PNG_SIGNATURE.len();
+    while pos < data.len() {
+        let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let kind: [u8; 4] = data.get(pos + 4..pos + 8)?.try_into().ok()?;
+        let end = pos.checked_add(12)?.checked_add(len)?;
+        if end > data.len() {
+            return None;
+        }
+        match &kind {
+            b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME" => {}
+            _ => {
+                // eXIf は IDAT より前に置く決まり
+                if &kind == b"IDAT" {
+                    if let Some(orientation) = pending_orientation.take() {
+                        push_png_chunk(&mut out, b"eXIf", &orientation_tiff(orientation));
+                    }
+                }
+                out.extend_from_slice(&data[pos..end]);
+            }
+        }
+        pos = end;
+        if &kind == b"IEND" {
+            return Some(out);
+        }
+    }
+    None
+}
+
+/// WebP: EXIF / XMP チャンクを落とし、VP8X のフラグを合わせる。
+fn strip_webp(data: &[u8], orientation: Option<u16>) -> Option<Vec<u8>> {
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..12]);
+    let mut vp8x_flags_at = None;
+    let mut pos = 12;
+    while pos < data.len() {
+        let kind: [u8; 4] = data.get(pos..pos + 4)?.try_into().ok()?;
+        let len = u32::from_le_bytes(data.get(pos + 4..pos + 8)?.try_into().ok()?) as usize;
+        let end = pos.checked_add(8)?.checked_add(len + (len & 1))?;
+        if end > data.len() {
+            return None;
+        }
+        match &kind {
+            b"EXIF" | b"XMP " => {}
+            _ => {
+                if &kind == b"VP8X" {
+                    vp8x_flags_at = Some(out.len() + 8);
+                }
+                out.extend_from_slice(&data[pos..end]);
+            }
+        }
+        pos = end;
+    }
+    // EXIF は VP8X 拡張形式でしか置けないので、無い単純形式では向きを諦める
+    let orientation = orientation.filter(|_| vp8x_flags_at.is_some());
+    if let Some(flags_at) = vp8x_flags_at {
+        // bit 3 = EXIF, bit 2 = XMP
+        out[flags_at] &= !0x0C;
+        if orientation.is_some() {
+            out[flags_at] |= 0x08;
+        }
+    }
+    if let Some(orientation) = orientation {
+        let tiff = orientation_tiff(orientation);
+        out.extend_from_slice(b"EXIF");
+        out.extend_from_slice(&(tiff.len() as u32).to_le_bytes());
+        out.extend_from_slice(&tiff);
+    }
+    let riff_len = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Some(out)
+}
+
+/// アップロード前加工の設定を保存して反映する (フロントの設定値が変わったときに呼ぶ)。
+#[tauri::command]
+#[specta::specta]
+pub fn upload_prep_configure(state: State<'_, UploadPrep>, config: UploadPrepConfig) -> Result<()> {
+    state.set_config(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exif_app1(orientation: u16) -> Vec<u8> {
+        let mut payload = b"Exif\0\0".to_vec();
+        payload.extend_from_slice(&orientation_tiff(orientation));
+        let mut seg = vec![0xFF, 0xE1];
+        seg.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        seg.extend_from_slice(&payload);
+        seg
+    }
+
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut seg = vec![0xFF, marker];
+        seg.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        seg.extend_from_slice(payload);
+        seg
+    }
+
+    fn sample_jpeg(orientation: u16) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        jpeg.extend(exif_app1(orientation));
+        jpeg.extend(segment(0xFE, b"secret comment"));
+        jpeg.extend(segment(0xDB, &[0; 65]));
+        jpeg.extend(segment(0xDA, &[0; 10]));
+        jpeg.extend_from_slice(&[1, 2, 3, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn jpeg_metadata_is_removed_but_orientation_kept() {
+        let jpeg = sample_jpeg(6);
+        let out = strip_metadata(&jpeg, Container::Jpeg).unwrap();
+        assert!(!out.windows(14).any(|w| w == b"secret comment"));
+        assert_eq!(orientation(&out), Some(6));
+        // APP0 が先頭のまま
+        assert_eq!(&out[2..4], &[0xFF, 0xE0]);
+        assert!(out.ends_with(&[1, 2, 3, 0xFF, 0xD9]));
+    }
+
+    #[test]
+    fn upright_jpeg_has_no_exif_left() {
+        let out = strip_metadata(&sample_jpeg(1), Container::Jpeg).unwrap();
+        assert_eq!(orientation(&out), None);
+        assert!(!out.windows(4).any(|w| w == b"Exif"));
+    }
+
+    /// PNG に `kind` チャンクがあるか。
+    fn contains_chunk(png: &[u8], kind: &[u8; 4]) -> bool {
+        let mut pos = PNG_SIGNATURE.len();
+        while let Some(len_bytes) = png.get(pos..pos + 4) {
+            let len = u32::from_be_bytes(len_bytes.try_into().unwrap()) as usize;
+            if png.get(pos + 4..pos + 8) == Some(kind.as_slice()) {
+                return true;
+            }
+            pos += 12 + len;
+        }
+        false
+    }
+
+    #[test]
+    fn png_text_chunks_are_removed() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(2, 2)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        // IHDR (8 + 13 + 4 = 25 バイト) の直後に tEXt を差し込む
+        let at = PNG_SIGNATURE.len() + 25;
+        let mut text = Vec::new();
+        push_png_chunk(&mut text, b"tEXt", b"Location\0Tokyo");
+        png.splice(at..at, text);
+
+        let out = strip_metadata(&png, Container::Png).unwrap();
+        assert!(!contains_chunk(&out, b"tEXt"));
+        assert!(image::load_from_memory(&out).is_ok());
+    }
+
+    #[test]
+    fn webp_exif_chunk_and_flag_are_removed() {
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        webp.extend_from_slice(b"VP8X");
+        webp.extend_from_slice(&10u32.to_le_bytes());
+        webp.extend_from_slice(&[0x08, 0, 0, 0, 1, 0, 0, 1, 0, 0]);
+        let tiff = orientation_tiff(1);
+        webp.extend_from_slice(b"EXIF");
+        webp.extend_from_slice(&(tiff.len() as u32).to_le_bytes());
+        webp.extend_from_slice(&tiff);
+        let riff_len = (webp.len() - 8) as u32;
+        webp[4..8].copy_from_slice(&riff_len.to_le_bytes());
+
+        let out = strip_metadata(&webp, Container::Webp).unwrap();
+        assert_eq!(out.len(), 12 + 18);
+        assert_eq!(out[20] & 0x08, 0);
+        assert_eq!(u32::from_le_bytes(out[4..8].try_into().unwrap()), 22);
+    }
+
+    fn prepare(config: &UploadPrepConfig, data: Vec<u8>, content_type: &str) -> Prepared {
+        config
+            .prepare(data, content_type.to_string(), "photo.bin".to_string())
+            .unwrap()
+    }
+
+    fn encoded(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        image.write_to(&mut Cursor::new(&mut out), format).unwrap();
+        out
+    }
+
+    #[test]
+    fn disabled_or_unsupported_uploads_pass_through() {
+        let mut config = UploadPrepConfig::default();
+        let jpeg = sample_jpeg(1);
+        assert_ne!(prepare(&config, jpeg.clone(), "image/jpeg").data, jpeg);
+        assert_eq!(
+            prepare(&config, b"GIF89a".to_vec(), "image/gif").data,
+            b"GIF89a"
+        );
+        assert!(!config.applies_to("video/mp4"));
+        assert_eq!(config.applies_to("image/heic"), HEIC_SUPPORTED);
+
+        config.strip_metadata = false;
+        assert_eq!(prepare(&config, jpeg.clone(), "image/jpeg").data, jpeg);
+        assert!(!config.applies_to("image/jpeg"));
+    }
+
+    #[test]
+    fn damaged_image_with_exif_is_not_uploaded() {
+        let jpeg = sample_jpeg(6);
+        // APP1 (EXIF) の後、DQT の途中で切れた JPEG
+        let dqt = jpeg.windows(2).position(|w| w == [0xFF, 0xDB]).unwrap();
+        let truncated = jpeg[..dqt + 10].to_vec();
+        assert!(truncated.windows(4).any(|w| w == b"Exif"));
+
+        let config = UploadPrepConfig::default();
+        let result = config.prepare(truncated.clone(), "image/jpeg".into(), "photo.jpg".into());
+        assert!(result.is_err());
+
+        // 取り除かない設定ならそのまま送る
+        let config = UploadPrepConfig {
+            strip_metadata: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            prepare(&config, truncated.clone(), "image/jpeg").data,
+            truncated
+        );
+    }
+
+    #[test]
+    fn large_photos_are_downscaled_as_jpeg() {
+        let config = UploadPrepConfig {
+            max_dimension: Some(100),
+            ..Default::default()
+        };
+        let jpeg = encoded(DynamicImage::new_rgb8(400, 200), ImageFormat::Jpeg);
+        let out = prepare(&config, jpeg, "image/jpeg");
+        assert_eq!(out.content_type, "image/jpeg");
+        assert_eq!(out.file_name, "photo.jpg");
+        let image = image::load_from_memory(&out.data).unwrap();
+        assert_eq!((image.width(), image.height()), (100, 50));
+    }
+
+    #[test]
+    fn transparent_webp_is_downscaled_as_png() {
+        let config = UploadPrepConfig {
+            max_dimension: Some(64),
+            ..Default::default()
+        };
+        let webp = encoded(DynamicImage::new_rgba8(128, 128), ImageFormat::WebP);
+        let out = prepare(&config, webp, "image/webp");
+        assert_eq!(out.content_type, "image/png");
+        assert_eq!(out.file_name, "photo.png");
+        assert_eq!(image::load_from_memory(&out.data).unwrap().width(), 64);
+    }
+
+    #[test]
+    fn small_images_keep_their_format() {
+        let config = UploadPrepConfig {
+            max_dimension: Some(1000),
+            ..Default::default()
+        };
+        let png = encoded(DynamicImage::new_rgb8(10, 10), ImageFormat::Png);
+        let out = prepare(&config, png, "image/png");
+        assert_eq!(out.content_type, "image/png");
+        assert_eq!(out.file_name, "photo.bin");
+    }
+
+    #[test]
+    fn extension_is_replaced() {
+        assert_eq!(with_extension("IMG_0001.HEIC", "jpg"), "IMG_0001.jpg");
+        assert_eq!(with_extension("a.b.webp", "png"), "a.b.png");
+        assert_eq!(with_extension("noext", "jpg"), "noext.jpg");
+    }
+}
@@ -0,0 +1,284 @@
+//! 上流 Misskey サーバーのレート制限 (`RATE_LIMIT_EXCEEDED`) の追跡。
+//!
+//! `rate_limit.rs` は HTTP API 経由でこのアプリに来るリクエストを絞る側で、
+//! こちらはアプリから上流サーバーへのリクエストが 429 で弾かれた状態を持つ。
+//! notecli の `MisskeyClient` はレスポンスヘッダを返さないので `Retry-After` は
+//! 読めず、エラーコードで検知してホストごとに指数バックオフで解除予定時刻を
+//! 見積もる。冪等な読み取り (`read`) はその時刻まで待ってから再試行し、
+//! 書き込み (`write`) は待つだけで再試行しない。フロントは
+//! `api_get_rate_limit_state` でリフレッシュ操作を抑える。
+//!
+//! 通すのはタイムライン・通知・検索・ノート単体/スレッド・ユーザーノートなどの
+//! 読み取りと、投稿・編集・削除・リアクション・お気に入り・ピン留め・クリップ・
+//! 投票・フォロー・アップロードの書き込み。リスト/アンテナ/チャンネル一覧や
+//! チャット、管理系など残りのコマンドはまだ素通しで、そこで 429 を受けても
+//! バックオフには数えない。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use notecli::error::NoteDeckError;
+use serde::Serialize;
+use specta::Type;
+use tauri::State;
+
+use crate::commands::{get_host, AppState, Result};
+
+/// 初回検知時の待ち時間。連続で弾かれるたびに倍にする。
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// 読み取りが解除を待つ上限。これより先なら待たずに上流へ投げる。
+const MAX_QUEUE_WAIT: Duration = Duration::from_secs(10);
+/// 読み取りを自動で再試行する回数。
+const MAX_RETRIES: u32 = 2;
+
+struct HostRate {
+    until: Instant,
+    strikes: u32,
+}
+
+pub struct UpstreamRate {
+    hosts: Mutex<HashMap<String, HostRate>>,
+    base_backoff: Duration,
+}
+
+impl Default for UpstreamRate {
+    fn default() -> Self {
+        Self::with_base_backoff(BASE_BACKOFF)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitState {
+    pub limited: bool,
+    /// 解除予定までの残り (制限中でなければ 0)
+    pub retry_after_ms: u64,
+}
+
+/// Misskey の 429 応答のエラーコード。
+const RATE_LIMIT_CODE: &str = "RATE_LIMIT_EXCEEDED";
+
+/// フロントに届く API エラーの message (`endpoint: CODE: detail`) から
+/// Misskey のエラーコードを取り出す。フロントの `AppError.displayCode` と同じく
+/// 2 番目の区切りだけを見るので、detail 側 (サーバーのメッセージやノート本文) に
+/// 同じ文字列があっても拾わない。
+fn misskey_error_code(err: &NoteDeckError) -> Option<String> {
+    let value = serde_json::to_value(err).ok()?;
+    let code = value.get("message")?.as_str()?.split(':').nth(1)?.trim();
+    let is_code = !code.is_empty() && code.bytes().all(|b| b.is_ascii_uppercase() || b == b'_');
+    is_code.then(|| code.to_string())
+}
+
+/// Misskey の 429 応答は `RATE_LIMIT_EXCEEDED` コード付きの API エラーになる。
+pub fn is_rate_limited(err: &NoteDeckError) -> bool {
+    misskey_error_code(err).as_deref() == Some(RATE_LIMIT_CODE)
+}
+
+/// MisskeyClient を通さない送信 (upload.rs) が 429 を受けたときのエラー。
+/// API エラーと同じ `endpoint: CODE: detail` の形にする。
+pub(crate) fn rate_limited_error(endpoint: &str) -> NoteDeckError {
+    NoteDeckError::InvalidInput(format!(
+        "{endpoint}: {RATE_LIMIT_CODE}: Rate limit exceeded"
+    ))
+}
+
+impl UpstreamRate {
+    fn with_base_backoff(base_backoff: Duration) -> Self {
+        Self {
+            hosts: Mutex::new(HashMap::new()),
+            base_backoff,
+        }
+    }
+
+    /// `host` が制限中なら解除予定までの残り時間。
+    pub fn remaining(&self, host: &str) -> Option<Duration> {
+        let hosts = self.hosts.lock().ok()?;
+        let rest = hosts
+            .get(host)?
+            .until
+            .checked_duration_since(Instant::now())?;
+        (!rest.is_zero()).then_some(rest)
+    }
+
+    /// 制限を記録し、次に試してよいまでの待ち時間を返す。
+    fn limited(&self, host: &str) -> Duration {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return self.base_backoff;
+        };
+        let entry = hosts.entry(host.to_string()).or_insert(HostRate {
+            until: Instant::now(),
+            strikes: 0,
+        });
+        let backoff = self
+            .base_backoff
+            .saturating_mul(1 << entry.strikes.min(4))
+            .min(MAX_BACKOFF);
+        entry.strikes += 1;
+        entry.until = Instant::now() + backoff;
+        backoff
+    }
+
+    fn succeeded(&self, host: &str) {
+        if let Ok(mut hosts) = self.hosts.lock() {
+            hosts.remove(host);
+        }
+    }
+
+    pub fn state(&self, host: &str) -> RateLimitState {
+        let rest = self.remaining(host).unwrap_or_default();
+        RateLimitState {
+            limited: !rest.is_zero(),
+            retry_after_ms: rest.as_millis() as u64,
+        }
+    }
+
+    /// 冪等な読み取りを実行する。制限中なら解除まで待ち、
+    /// `RATE_LIMIT_EXCEEDED` で弾かれたらバックオフして再試行する。
+    /// 書き込み系はここを通さず `write` を使う (二重投稿を避けるため)。
+    pub async fn read<T, F, Fut>(&self, host: &str, mut fetch: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Some(wait) = self.remaining(host) {
+                tokio::time::sleep(wait.min(MAX_QUEUE_WAIT)).await;
+            }
+            match fetch().await {
+                Ok(value) => {
+                    self.succeeded(host);
+                    return Ok(value);
+                }
+                Err(e) if is_rate_limited(&e) => {
+                    let backoff = self.limited(host);
+                    tracing::debug!(%host, ?backoff, "[upstream-rate] rate limited");
+                    if attempt >= MAX_RETRIES || backoff > MAX_QUEUE_WAIT {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 書き込みを 1 回だけ実行する。制限中なら解除まで待ってから投げ、
+    /// 弾かれたら記録だけして再試行はしない。
+    pub async fn write<T>(&self, host: &str, send: impl Future<Output = Result<T>>) -> Result<T> {
+        if let Some(wait) = self.remaining(host) {
+            tokio::time::sleep(wait.min(MAX_QUEUE_WAIT)).await;
+        }
+        let result = send.await;
+        match &result {
+            Ok(_) => self.succeeded(host),
+            Err(e) if is_rate_limited(e) => {
+                let backoff = self.limited(host);
+                tracing::debug!(%host, ?backoff, "[upstream-rate] write rate limited");
+            }
+            Err(_) => {}
+        }
+        result
+    }
+}
+
+/// アカウントのサーバーが上流のレート制限中かを返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn api_get_rate_limit_state(
+    app_state: State<'_, AppState>,
+    rate: State<'_, UpstreamRate>,
+    account_id: String,
+) -> Result<RateLimitState> {
+    let db = app_state.db().await;
+    let host = get_host(&db, &account_id)?;
+    Ok(rate.state(&host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn rate_limited() -> NoteDeckError {
+        rate_limited_error("notes/create")
+    }
+
+    #[test]
+    fn detects_only_the_structured_code() {
+        assert!(is_rate_limited(&rate_limited()));
+        assert!(is_rate_limited(&NoteDeckError::InvalidInput(
+            "notes/timeline: RATE_LIMIT_EXCEEDED: Rate limit exceeded".into()
+        )));
+        // detail 側に同じ文字列があっても別のエラー
+        assert!(!is_rate_limited(&NoteDeckError::InvalidInput(
+            "notes/create: CONTAINS_PROHIBITED_WORDS: RATE_LIMIT_EXCEEDED".into()
+        )));
+        assert!(!is_rate_limited(&NoteDeckError::InvalidInput(
+            "Upload failed (500): RATE_LIMIT_EXCEEDED in body".into()
+        )));
+    }
+
+    #[test]
+    fn backoff_doubles_and_resets_on_success() {
+        let rate = UpstreamRate::default();
+        assert_eq!(rate.limited("a.example"), BASE_BACKOFF);
+        assert_eq!(rate.limited("a.example"), BASE_BACKOFF * 2);
+        assert!(rate.state("a.example").limited);
+        assert!(!rate.state("b.example").limited);
+
+        rate.succeeded("a.example");
+        assert!(!rate.state("a.example").limited);
+        assert_eq!(rate.limited("a.example"), BASE_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn read_retries_after_rate_limit() {
+        let rate = UpstreamRate::with_base_backoff(Duration::from_millis(10));
+        let calls = AtomicU32::new(0);
+        let result = rate
+            .read("a.example", || async {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(rate_limited())
+                } else {
+                    Ok(7)
+                }
+            })
+            .await;
+        assert_eq!(result.ok(), Some(7));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(!rate.state("a.example").limited);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let rate = UpstreamRate::default();
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = rate
+            .read("a.example", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(NoteDeckError::InvalidInput("boom".into()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn write_records_rate_limit_without_retrying() {
+        let rate = UpstreamRate::default();
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = rate
+            .write("a.example", async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(rate_limited())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(rate.state("a.example").limited);
+    }
+}
@@ -0,0 +1,256 @@
+//! アップロード前の動画圧縮 (ffmpeg)。
+//!
+//! 多くのサーバーは 50MB を超えるファイルを受け付けないので、大きな動画は
+//! ffmpeg で H.264 / AAC の MP4 (長辺 1280px) に変換してから送れるようにする。
+//! ffmpeg は同梱せず、PATH (と GUI 起動で PATH に載らない Homebrew 等の定番
+//! の場所) から探す。見つからなければ圧縮経路は提示しない。
+//!
+//! 変換とアップロードは `jobs.rs` の `CompressUpload` ジョブとして実行し、
+//! 進捗 (変換済みの再生時間 ms / 総再生時間 ms) は `nd:job-updated` で届く。
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use specta::Type;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+/// 変換後の長辺の上限 (px)。
+const MAX_DIMENSION: u32 = 1280;
+/// x264 の画質 (大きいほど小さく粗い)。
+const CRF: u32 = 28;
+const AUDIO_BITRATE: &str = "128k";
+
+#[cfg(windows)]
+const FFMPEG: &str = "ffmpeg.exe";
+#[cfg(not(windows))]
+const FFMPEG: &str = "ffmpeg";
+#[cfg(windows)]
+const FFPROBE: &str = "ffprobe.exe";
+#[cfg(not(windows))]
+const FFPROBE: &str = "ffprobe";
+
+/// PATH に無くても探す場所 (macOS の GUI アプリは shell の PATH を継がない)。
+const EXTRA_DIRS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"];
+
+fn find_binary(name: &str) -> Option<PathBuf> {
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(EXTRA_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// 見つかった ffmpeg。起動中に入れ直されることは想定しない。
+fn ffmpeg() -> Option<&'static Path> {
+    static FFMPEG_PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+    FFMPEG_PATH.get_or_init(|| find_binary(FFMPEG)).as_deref()
+}
+
+/// ffprobe は ffmpeg と同じ場所を優先する。無ければ総再生時間は不明扱い。
+fn ffprobe() -> Option<PathBuf> {
+    let sibling = ffmpeg()?.with_file_name(FFPROBE);
+    if sibling.is_file() {
+        Some(sibling)
+    } else {
+        find_binary(FFPROBE)
+    }
+}
+
+fn is_video(path: &Path) -> bool {
+    mime_guess::from_path(path)
+        .first()
+        .is_some_and(|mime| mime.type_() == mime_guess::mime::VIDEO)
+}
+
+/// 長辺を MAX_DIMENSION に収める scale フィルタ (小さい動画は拡大しない)。
+fn scale_filter() -> String {
+    format!(
+        "scale='if(gte(iw,ih),min({MAX_DIMENSION},iw),-2)':'if(gte(iw,ih),-2,min({MAX_DIMENSION},ih))'"
+    )
+}
+
+/// `-progress` 出力の 1 行から変換済みの再生時間 (ms) を読む。
+fn parse_progress_ms(line: &str) -> Option<u64> {
+    let (key, value) = line.split_once('=')?;
+    // out_time_ms は歴史的経緯でマイクロ秒
+    matches!(key, "out_time_us" | "out_time_ms")
+        .then(|| value.trim().parse::<u64>().ok())
+        .flatten()
+        .map(|us| us / 1000)
+}
+
+fn command(program: &Path) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(program);
+    // コンソールウィンドウを出さない (CREATE_NO_WINDOW)
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x0800_0000);
+    command
+}
+
+async fn duration_ms(input: &Path) -> Option<u64> {
+    let output = command(&ffprobe()?)
+        .args(["-v", "error", "-show_entries", "format=duration"])
+        .args(["-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(input)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let seconds: f64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    (seconds.is_finite() && seconds > 0.0).then(|| (seconds * 1000.0) as u64)
+}
+
+/// 変換先の一時ファイル。ジョブのキャンセル (abort) でも消えるよう drop で削除する。
+pub struct TempOutput(PathBuf);
+
+impl TempOutput {
+    pub fn new(job_id: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("notedeck-compress-{job_id}.mp4")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempOutput {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// `input` を MP4 に変換して `output` に書く。`on_progress(done_ms, total_ms)`
+/// は ffmpeg の進捗報告ごとに呼ばれる。
+pub async fn compress(
+    input: &Path,
+    output: &Path,
+    on_progress: impl Fn(u64, Option<u64>),
+) -> Result<(), String> {
+    let ffmpeg = ffmpeg().ok_or("ffmpeg was not found")?;
+    let total = duration_ms(input).await;
+    let mut child = command(ffmpeg)
+        .args(["-y", "-nostdin", "-loglevel", "error", "-nostats"])
+        .arg("-i")
+        .arg(input)
+        .args(["-vf", &scale_filter()])
+        .args([
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            &CRF.to_string(),
+        ])
+        .args(["-pix_fmt", "yuv420p"])
+        .args(["-c:a", "aac", "-b:a", AUDIO_BITRATE])
+        .args(["-movflags", "+faststart", "-progress", "pipe:1"])
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // ジョブがキャンセルされたら ffmpeg も止める
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start ffmpeg: {e}"))?;
+
+    let stdout = child.stdout.take().ok_or("ffmpeg stdout unavailable")?;
+    let mut lines = BufReader::new(stdout).lines();
+    on_progress(0, total);
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(done) = parse_progress_ms(&line) {
+            on_progress(total.map_or(done, |t| done.min(t)), total);
+        }
+    }
+
+    let result = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("ffmpeg failed: {e}"))?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let detail: String = stderr.trim().chars().take(300).collect();
+        return Err(format!("ffmpeg exited with {}: {detail}", result.status));
+    }
+    Ok(())
+}
+
+/// 圧縮後のファイル名 (`clip.mov` → `clip.mp4`)。
+pub fn output_name(input: &Path, name: Option<&str>) -> String {
+    let base = name
+        .filter(|n| !n.trim().is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            input
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "video".to_string());
+    let stem = base
+        .rsplit_once('.')
+        .map_or(base.as_str(), |(stem, _)| stem);
+    format!("{stem}.mp4")
+}
+
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoCompressCheck {
+    /// ffmpeg が見つかったか
+    pub available: bool,
+    /// 動画で、かつ閾値を超えていて圧縮を勧めるか
+    pub recommended: bool,
+}
+
+/// `path` の動画をアップロード前に圧縮すべきかを返す。
+#[tauri::command]
+#[specta::specta]
+pub async fn video_compress_check(path: String, threshold_bytes: u64) -> VideoCompressCheck {
+    let available = ffmpeg().is_some();
+    let path = Path::new(&path);
+    let size = tokio::fs::metadata(path).await.map_or(0, |m| m.len());
+    VideoCompressCheck {
+        available,
+        recommended: available && is_video(path) && size > threshold_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_lines_are_parsed_as_ms() {
+        assert_eq!(parse_progress_ms("out_time_us=1500000"), Some(1500));
+        assert_eq!(parse_progress_ms("out_time_ms=2000000"), Some(2000));
+        assert_eq!(parse_progress_ms("out_time=00:00:01.500000"), None);
+        assert_eq!(parse_progress_ms("out_time_us=N/A"), None);
+        assert_eq!(parse_progress_ms("progress=continue"), None);
+    }
+
+    #[test]
+    fn output_is_named_mp4() {
+        assert_eq!(output_name(Path::new("/tmp/clip.mov"), None), "clip.mp4");
+        assert_eq!(
+            output_name(Path::new("/tmp/clip.mov"), Some("旅行.MOV")),
+            "旅行.mp4"
+        );
+        assert_eq!(output_name(Path::new("/"), None), "video.mp4");
+    }
+
+    #[test]
+    fn only_videos_are_compressed() {
+        assert!(is_video(Path::new("a.mp4")));
+        assert!(is_video(Path::new("a.MOV")));
+        assert!(!is_video(Path::new("a.png")));
+    }
+}
@@ -0,0 +1,408 @@
+//! メインウィンドウの位置・サイズ・最大化状態・モニターの永続化。
+//!
+//! 旧実装 (#643) は tauri-plugin-window-state の JSON ファイルだったが、
+//! 保存先モニターが外された場合に画面外へ復元されるケースがあったため
+//! SQLite に移し、復元時にモニター構成と突き合わせる。notecli.db は Phase 2
+//! でしか開けないため、ウィンドウ表示前 (Phase 1) に同期で読める
+//! `app-state.db` (app_db.rs) に置いている。
+//!
+//! 保存は close (→トレイ hide) / hide / 終了の直前に行う。座標は物理px
+//! (#721 と同じくスケール誤報告の影響を避ける)。最大化中は通常時の
+//! 位置・サイズが取れないため、前回保存値を維持して maximized だけ更新する。
+//! 一度も保存していないまま最大化で閉じた場合は、モニターの作業領域を
+//! 通常時の位置・サイズとして保存する。
+
+use rusqlite::{params, OptionalExtension};
+
+use crate::app_db::SharedConn;
+
+/// 保存するジオメトリ (物理px)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    /// 保存時にウィンドウがあったモニター名 (取れない環境では None)。
+    pub monitor: Option<String>,
+}
+
+/// 復元判定用のモニター矩形 (物理px)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorRect {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorRect {
+    fn contains(&self, px: i32, py: i32) -> bool {
+        px >= self.x
+            && py >= self.y
+            && px < self.x + self.width as i32
+            && py < self.y + self.height as i32
+    }
+}
+
+/// タイトルバー付近がこの px 四方以上モニター内に見えていれば「掴める」とみなす。
+const MIN_VISIBLE: i32 = 48;
+
+pub struct GeometryStore {
+    conn: SharedConn,
+}
+
+impl GeometryStore {
+    /// 共有の app-state.db (app_db.rs) に表を作って使う。
+    pub fn new(conn: SharedConn) -> rusqlite::Result<Self> {
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(crate::app_db::open_in_memory()?)
+    }
+
+    fn init(conn: SharedConn) -> rusqlite::Result<Self> {
+        conn.lock().unwrap().execute_batch(
+            "CREATE TABLE IF NOT EXISTS window_geometry (
+                label      TEXT PRIMARY KEY,
+                x          INTEGER NOT NULL,
+                y          INTEGER NOT NULL,
+                width      INTEGER NOT NULL,
+                height     INTEGER NOT NULL,
+                maximized  INTEGER NOT NULL,
+                monitor    TEXT,
+                updated_at INTEGER NOT NULL
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn load(&self, label: &str) -> rusqlite::Result<Option<WindowGeometry>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT x, y, width, height, maximized, monitor
+             FROM window_geometry WHERE label = ?1",
+            params![label],
+            |row| {
+                Ok(WindowGeometry {
+                    x: row.get(0)?,
+                    y: row.get(1)?,
+                    width: row.get(2)?,
+                    height: row.get(3)?,
+                    maximized: row.get(4)?,
+                    monitor: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    pub fn save(&self, label: &str, geometry: &WindowGeometry) -> rusqlite::Result<()> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO window_geometry
+                (label, x, y, width, height, maximized, monitor, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(label) DO UPDATE SET
+                x = excluded.x, y = excluded.y,
+                width = excluded.width, height = excluded.height,
+                maximized = excluded.maximized, monitor = excluded.monitor,
+                updated_at = excluded.updated_at",
+            params![
+                label,
+                geometry.x,
+                geometry.y,
+                geometry.width,
+                geometry.height,
+                geometry.maximized,
+                geometry.monitor,
+                now
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// 保存ジオメトリを現在のモニター構成に合わせて補正する。
+///
+/// - 保存時のモニターが残っていて、ウィンドウ左上付近が見えていればそのまま
+/// - モニター名が変わっていても (ドライバ更新等) 位置がどこかのモニター上に
+///   見えていればそのまま
+/// - どこにも見えない (モニターが外された) 場合は、同名モニター → primary の
+///   順にフォールバックし、サイズをモニター内に収めて中央に置く
+///
+/// monitors が空 (取得失敗) なら補正しない。
+pub fn resolve_placement(
+    saved: &WindowGeometry,
+    monitors: &[MonitorRect],
+    primary: Option<&MonitorRect>,
+) -> WindowGeometry {
+    if monitors.is_empty() {
+        return saved.clone();
+    }
+    // 左上から MIN_VISIBLE 内側の点 (= タイトルバーの掴める位置) が見えているか
+    let grab = (saved.x + MIN_VISIBLE, saved.y + MIN_VISIBLE);
+    if let Some(on) = monitors.iter().find(|m| m.contains(grab.0, grab.1)) {
+        return WindowGeometry {
+            monitor: on.name.clone().or_else(|| saved.monitor.clone()),
+            ..saved.clone()
+        };
+    }
+
+    let target = saved
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name.as_ref() == Some(name)))
+        .or(primary)
+        .unwrap_or(&monitors[0]);
+    let width = saved.width.min(target.width);
+    let height = saved.height.min(target.height);
+    WindowGeometry {
+        x: target.x + (target.width - width) as i32 / 2,
+        y: target.y + (target.height - height) as i32 / 2,
+        width,
+        height,
+        maximized: saved.maximized,
+        monitor: target.name.clone(),
+    }
+}
+
+/// 通常時の位置・サイズを一度も保存していないまま最大化で閉じたときの保存値。
+/// 最大化を解除したときの戻り先はモニターの作業領域いっぱいにする。
+fn maximized_within(work_area: &MonitorRect) -> WindowGeometry {
+    WindowGeometry {
+        x: work_area.x,
+        y: work_area.y,
+        width: work_area.width,
+        height: work_area.height,
+        maximized: true,
+        monitor: work_area.name.clone(),
+    }
+}
+
+/// タスクバー・Dock 等を除いたモニターの作業領域。
+fn work_area_rect(monitor: &tauri::Monitor) -> MonitorRect {
+    let area = monitor.work_area();
+    MonitorRect {
+        name: monitor.name().cloned(),
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+fn monitor_rect(monitor: &tauri::Monitor) -> MonitorRect {
+    let pos = monitor.position();
+    let size = monitor.size();
+    MonitorRect {
+        name: monitor.name().cloned(),
+        x: pos.x,
+        y: pos.y,
+        width: size.width,
+        height: size.height,
+    }
+}
+
+/// 現在のジオメトリを保存する。hide / close / 終了の直前に呼ぶ。
+/// 非表示・最小化中は正しい値が取れないため保存しない。
+pub fn capture<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
+    use tauri::Manager;
+
+    let Some(store) = window.try_state::<GeometryStore>() else {
+        return;
+    };
+    if !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let label = window.label();
+    let maximized = window.is_maximized().unwrap_or(false);
+    let current = window.current_monitor().ok().flatten();
+    let monitor = current.as_ref().and_then(|m| m.name().cloned());
+
+    let geometry = if maximized {
+        // 最大化中の outer_* は画面全体なので、通常時の値は前回保存分を使う
+        match store.load(label) {
+            Ok(Some(prev)) => WindowGeometry {
+                maximized: true,
+                monitor,
+                ..prev
+            },
+            Ok(None) => match current.as_ref() {
+                Some(m) => maximized_within(&work_area_rect(m)),
+                None => return,
+            },
+            Err(e) => {
+                tracing::warn!(%label, "[window] failed to load geometry: {e}");
+                return;
+            }
+        }
+    } else {
+        let (Ok(pos), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+            return;
+        };
+        WindowGeometry {
+            x: pos.x,
+            y: pos.y,
+            width: size.width,
+            height: size.height,
+            maximized: false,
+            monitor,
+        }
+    };
+    if let Err(e) = store.save(label, &geometry) {
+        tracing::warn!(%label, "[window] failed to save geometry: {e}");
+    }
+}
+
+/// 保存ジオメトリを復元する。setup (ウィンドウ表示前) から呼ぶ。
+pub fn restore<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>, store: &GeometryStore) {
+    let saved = match store.load(window.label()) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("[window] failed to load geometry: {e}");
+            return;
+        }
+    };
+    let monitors: Vec<MonitorRect> = window
+        .available_monitors()
+        .map(|ms| ms.iter().map(monitor_rect).collect())
+        .unwrap_or_default();
+    let primary = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| monitor_rect(&m));
+    let placed = resolve_placement(&saved, &monitors, primary.as_ref());
+
+    let _ = window.set_size(tauri::PhysicalSize::new(placed.width, placed.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(placed.x, placed.y));
+    if placed.maximized {
+        let _ = window.maximize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorRect {
+        MonitorRect {
+            name: Some(name.into()),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn geometry(x: i32, y: i32, monitor: &str) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width: 1200,
+            height: 800,
+            maximized: false,
+            monitor: Some(monitor.into()),
+        }
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let store = GeometryStore::open_in_memory().unwrap();
+        assert_eq!(store.load("main").unwrap(), None);
+
+        let g = geometry(100, 50, "DP-1");
+        store.save("main", &g).unwrap();
+        assert_eq!(store.load("main").unwrap(), Some(g));
+
+        // upsert で上書きされる
+        let moved = WindowGeometry {
+            maximized: true,
+            ..geometry(-1800, 20, "HDMI-1")
+        };
+        store.save("main", &moved).unwrap();
+        assert_eq!(store.load("main").unwrap(), Some(moved));
+    }
+
+    #[test]
+    fn persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = || GeometryStore::new(crate::app_db::open(dir.path()).unwrap()).unwrap();
+        open().save("main", &geometry(10, 20, "DP-1")).unwrap();
+        let reopened = open();
+        assert_eq!(reopened.load("main").unwrap().unwrap().x, 10);
+    }
+
+    #[test]
+    fn visible_position_is_kept() {
+        let monitors = [monitor("DP-1", 0, 0, 1920, 1080)];
+        let saved = geometry(100, 50, "DP-1");
+        assert_eq!(resolve_placement(&saved, &monitors, None), saved);
+    }
+
+    /// 右側のセカンダリモニターが外された → primary の中央に寄せる
+    #[test]
+    fn missing_monitor_falls_back_to_primary_centered() {
+        let primary = monitor("DP-1", 0, 0, 1920, 1080);
+        let saved = geometry(2200, 100, "HDMI-1");
+        let placed = resolve_placement(&saved, std::slice::from_ref(&primary), Some(&primary));
+        assert_eq!((placed.x, placed.y), (360, 140));
+        assert_eq!((placed.width, placed.height), (1200, 800));
+        assert_eq!(placed.monitor.as_deref(), Some("DP-1"));
+    }
+
+    /// 低解像度モニターへのフォールバックではサイズも収める
+    #[test]
+    fn fallback_clamps_size_to_monitor() {
+        let small = monitor("eDP-1", 0, 0, 1024, 768);
+        let saved = geometry(3000, 3000, "gone");
+        let placed = resolve_placement(&saved, std::slice::from_ref(&small), None);
+        assert_eq!((placed.width, placed.height), (1024, 768));
+        assert_eq!((placed.x, placed.y), (0, 0));
+    }
+
+    /// 同名モニターが別座標に移動していたらそちらへ
+    #[test]
+    fn rearranged_monitor_is_followed_by_name() {
+        let monitors = [
+            monitor("DP-1", 0, 0, 1920, 1080),
+            monitor("HDMI-1", -1920, 0, 1920, 1080),
+        ];
+        let saved = geometry(2000, 100, "HDMI-1");
+        let placed = resolve_placement(&saved, &monitors, Some(&monitors[0]));
+        assert_eq!(placed.monitor.as_deref(), Some("HDMI-1"));
+        assert_eq!(placed.x, -1920 + 360);
+    }
+
+    /// 初回から最大化のまま閉じても最大化が復元され、解除時の戻り先は作業領域
+    #[test]
+    fn first_maximized_save_uses_work_area() {
+        let store = GeometryStore::open_in_memory().unwrap();
+        let work_area = monitor("DP-1", 0, 25, 1920, 1055);
+        store.save("main", &maximized_within(&work_area)).unwrap();
+
+        let saved = store.load("main").unwrap().unwrap();
+        assert!(saved.maximized);
+        assert_eq!((saved.x, saved.y), (0, 25));
+        assert_eq!((saved.width, saved.height), (1920, 1055));
+
+        let monitors = [monitor("DP-1", 0, 0, 1920, 1080)];
+        assert_eq!(resolve_placement(&saved, &monitors, None), saved);
+    }
+
+    #[test]
+    fn no_monitor_info_leaves_geometry_untouched() {
+        let saved = geometry(5000, 5000, "DP-1");
+        assert_eq!(resolve_placement(&saved, &[], None), saved);
+    }
+}
@@ -0,0 +1,342 @@
+//! 切り離しウィンドウ (ノートスレッド / ユーザープロフィール単体)。
+//!
+//! スレッドを別モニターで追えるよう、既存の `/note/:accountId/:noteId` と
+//! `/user/:accountId/:userId` ルートを secondary webview window で開く。
+//! 開いているウィンドウはバックエンドが `detached-windows.json` に記録し、
+//! 次回起動時に復元する。ユーザーが閉じたウィンドウ (CloseRequested) は
+//! 記録から外すが、アプリ終了で閉じたものは残る (= 次回復元される)。
+//!
+//! label は view から決定的に導出するため、同じノート / ユーザーを
+//! 再度開くと新規生成せず既存ウィンドウを前面に出す。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use notecli::error::NoteDeckError;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+const WINDOWS_FILE: &str = "detached-windows.json";
+/// 切り離しウィンドウの label 接頭辞。capabilities の windows にも登録している。
+pub const LABEL_PREFIX: &str = "detached-";
+
+/// 切り離しウィンドウに表示する内容。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DetachedView {
+    #[serde(rename_all = "camelCase")]
+    Note { account_id: String, note_id: String },
+    #[serde(rename_all = "camelCase")]
+    User { account_id: String, user_id: String },
+}
+
+impl DetachedView {
+    /// view から決定的に label を作る。ID をそのまま入れると label の許容
+    /// 文字 (英数 / `-` / `/` / `:` / `_`) を外れうるためハッシュで畳む。
+    pub fn label(&self) -> String {
+        let (kind, key) = match self {
+            Self::Note {
+                account_id,
+                note_id,
+            } => ("note", format!("{account_id}/{note_id}")),
+            Self::User {
+                account_id,
+                user_id,
+            } => ("user", format!("{account_id}/{user_id}")),
+        };
+        let hash = crate::image_cache::hex_hash(&key);
+        format!("{LABEL_PREFIX}{kind}-{}", &hash[..16])
+    }
+
+    /// フロントのルート (tauri::WebviewUrl::App に渡す相対パス)。
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn route(&self) -> String {
+        let enc = |s: &str| -> String {
+            url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+        };
+        match self {
+            Self::Note {
+                account_id,
+                note_id,
+            } => format!("note/{}/{}", enc(account_id), enc(note_id)),
+            Self::User {
+                account_id,
+                user_id,
+            } => format!("user/{}/{}", enc(account_id), enc(user_id)),
+        }
+    }
+
+    #[cfg_attr(mobile, allow(dead_code))]
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Note { .. } => "ノート",
+            Self::User { .. } => "プロフィール",
+        }
+    }
+
+    fn validate(&self) -> Result<(), NoteDeckError> {
+        let ids = match self {
+            Self::Note {
+                account_id,
+                note_id,
+            } => [account_id, note_id],
+            Self::User {
+                account_id,
+                user_id,
+            } => [account_id, user_id],
+        };
+        if ids.iter().any(|id| id.trim().is_empty()) {
+            return Err(NoteDeckError::InvalidInput(
+                "detached window ids must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 記録中の切り離しウィンドウ。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DetachedWindow {
+    pub label: String,
+    pub view: DetachedView,
+}
+
+pub struct WindowManager {
+    path: PathBuf,
+    windows: Mutex<Vec<DetachedWindow>>,
+}
+
+impl WindowManager {
+    /// `app_dir/detached-windows.json` を読み込む。無ければ空で開始。
+    /// 壊れたファイルは warn を出して空扱い (次の保存で上書きされる)。
+    pub fn load(app_dir: &Path) -> Self {
+        let path = app_dir.join(WINDOWS_FILE);
+        let windows = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(%e, "detached-windows.json is corrupt; starting empty");
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            path,
+            windows: Mutex::new(windows),
+        }
+    }
+
+    pub fn list(&self) -> Vec<DetachedWindow> {
+        self.windows.lock().unwrap().clone()
+    }
+
+    /// view を記録する。既に同じ label があれば何もしない。
+    pub fn track(&self, view: DetachedView) -> std::io::Result<DetachedWindow> {
+        let entry = DetachedWindow {
+            label: view.label(),
+            view,
+        };
+        let mut windows = self.windows.lock().unwrap();
+        if !windows.iter().any(|w| w.label == entry.label) {
+            windows.push(entry.clone());
+            self.save(&windows)?;
+        }
+        Ok(entry)
+    }
+
+    /// 記録から外す。存在したら true。
+    pub fn forget(&self, label: &str) -> std::io::Result<bool> {
+        let mut windows = self.windows.lock().unwrap();
+        let before = windows.len();
+        windows.retain(|w| w.label != label);
+        let removed = windows.len() != before;
+        if removed {
+            self.save(&windows)?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self, windows: &[DetachedWindow]) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(windows).expect("serialize detached windows");
+        std::fs::write(&self.path, json)
+    }
+}
+
+/// label が切り離しウィンドウのものか。
+pub fn is_detached_label(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// ウィンドウを開く (既存なら前面化)。記録は呼び出し側が行う。
+#[cfg(not(mobile))]
+fn show_window<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    entry: &DetachedWindow,
+) -> tauri::Result<()> {
+    use tauri::Manager;
+
+    if let Some(window) = app.get_webview_window(&entry.label) {
+        window.unminimize()?;
+        window.show()?;
+        return window.set_focus();
+    }
+    // visible: false で生成し、フロント (App.vue onMounted) が show する。
+    // decorations はメインと同じく TitleBar 側で描く
+    tauri::WebviewWindowBuilder::new(
+        app,
+        &entry.label,
+        tauri::WebviewUrl::App(entry.view.route().into()),
+    )
+    .title(entry.view.title())
+    .inner_size(480.0, 720.0)
+    .min_inner_size(320.0, 400.0)
+    .decorations(false)
+    .visible(false)
+    .build()?;
+    Ok(())
+}
+
+/// 前回終了時に開いていた切り離しウィンドウを復元する。setup から呼ぶ。
+#[cfg(not(mobile))]
+pub fn restore_all<R: tauri::Runtime>(app: &tauri::AppHandle<R>, manager: &WindowManager) {
+    for entry in manager.list() {
+        if let Err(e) = show_window(app, &entry) {
+            tracing::warn!(label = %entry.label, "[window] restore failed: {e}");
+        }
+    }
+}
+
+fn io_err(e: std::io::Error) -> NoteDeckError {
+    NoteDeckError::InvalidInput(format!("failed to persist detached windows: {e}"))
+}
+
+/// ノートスレッド / ユーザープロフィールを別ウィンドウで開き、label を返す。
+/// 同じ対象が既に開いていれば前面に出す。
+#[tauri::command]
+#[specta::specta]
+pub fn window_open_detached(
+    app: tauri::AppHandle,
+    manager: State<'_, WindowManager>,
+    view: DetachedView,
+) -> Result<String, NoteDeckError> {
+    view.validate()?;
+    #[cfg(not(mobile))]
+    {
+        let entry = DetachedWindow {
+            label: view.label(),
+            view,
+        };
+        show_window(&app, &entry)
+            .map_err(|e| NoteDeckError::InvalidInput(format!("detached window: {e}")))?;
+        manager.track(entry.view).map_err(io_err)?;
+        Ok(entry.label)
+    }
+    #[cfg(mobile)]
+    {
+        let _ = (app, manager);
+        Err(NoteDeckError::InvalidInput(
+            "detached windows are not available on mobile".to_string(),
+        ))
+    }
+}
+
+/// 記録中の切り離しウィンドウ一覧。
+#[tauri::command]
+#[specta::specta]
+pub fn window_list_detached(manager: State<'_, WindowManager>) -> Vec<DetachedWindow> {
+    manager.list()
+}
+
+/// 切り離しウィンドウを閉じて記録から外す。
+#[tauri::command]
+#[specta::specta]
+pub fn window_close_detached(
+    app: tauri::AppHandle,
+    manager: State<'_, WindowManager>,
+    label: String,
+) -> Result<bool, NoteDeckError> {
+    if !is_detached_label(&label) {
+        return Err(NoteDeckError::InvalidInput(format!(
+            "not a detached window: {label}"
+        )));
+    }
+    let removed = manager.forget(&label).map_err(io_err)?;
+    #[cfg(not(mobile))]
+    {
+        use tauri::Manager;
+        if let Some(window) = app.get_webview_window(&label) {
+            let _ = window.destroy();
+        }
+    }
+    #[cfg(mobile)]
+    let _ = app;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(account: &str, id: &str) -> DetachedView {
+        DetachedView::Note {
+            account_id: account.into(),
+            note_id: id.into(),
+        }
+    }
+
+    #[test]
+    fn label_is_deterministic_and_label_safe() {
+        let a = note("acct-1", "9abc");
+        assert_eq!(a.label(), note("acct-1", "9abc").label());
+        assert_ne!(a.label(), note("acct-1", "9abd").label());
+        assert!(a.label().starts_with("detached-note-"));
+        assert!(a
+            .label()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        assert!(is_detached_label(&a.label()));
+        assert!(!is_detached_label("main"));
+    }
+
+    #[test]
+    fn route_matches_frontend_paths() {
+        assert_eq!(note("a", "n").route(), "note/a/n");
+        let user = DetachedView::User {
+            account_id: "a".into(),
+            user_id: "u/x".into(),
+        };
+        assert_eq!(user.route(), "user/a/u%2Fx");
+    }
+
+    #[test]
+    fn track_forget_roundtrip_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = WindowManager::load(dir.path());
+        let entry = manager.track(note("acct-1", "n1")).unwrap();
+        // 同じ view の二重登録はしない
+        manager.track(note("acct-1", "n1")).unwrap();
+        manager.track(note("acct-2", "n2")).unwrap();
+
+        let reloaded = WindowManager::load(dir.path());
+        assert_eq!(reloaded.list().len(), 2);
+
+        assert!(manager.forget(&entry.label).unwrap());
+        assert!(!manager.forget(&entry.label).unwrap());
+        let remaining = WindowManager::load(dir.path()).list();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].view, note("acct-2", "n2"));
+    }
+
+    #[test]
+    fn empty_ids_are_rejected() {
+        assert!(note("acct-1", " ").validate().is_err());
+        assert!(note("acct-1", "n1").validate().is_ok());
+    }
+
+    #[test]
+    fn corrupt_file_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(WINDOWS_FILE), "not json").unwrap();
+        assert!(WindowManager::load(dir.path()).list().is_empty());
+    }
+}
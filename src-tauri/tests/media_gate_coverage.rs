@@ -0,0 +1,110 @@
+//! Guards that every command handing notes or notifications to the WebView
+//! goes through `MediaGate`. Commands are found by scanning `src/` for
+//! `#[tauri::command]`, so a new note-returning command without the gate fails
+//! here instead of quietly leaking sensitive media URLs.
+//!
+//! Typed commands are detected from their return type. Commands returning raw
+//! JSON that can contain notes are listed in `RAW_NOTE_COMMANDS`.
+
+use std::fs;
+use std::path::Path;
+
+/// Raw-JSON commands whose responses can carry notes (and their `files`).
+const RAW_NOTE_COMMANDS: &[&str] = &[
+    "api_get_note_raw",
+    "api_request",
+    "api_get_user_notes_filtered",
+    "api_get_user_featured_notes",
+    "api_ap_show",
+    "api_get_user_raw",
+];
+
+struct Command {
+    name: String,
+    file: String,
+    return_type: String,
+    source: String,
+}
+
+fn collect_sources(dir: &Path, out: &mut Vec<(String, String)>) {
+    for entry in fs::read_dir(dir).expect("read src dir") {
+        let path = entry.expect("dir entry").path();
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            let source = fs::read_to_string(&path).expect("read source");
+            out.push((path.display().to_string(), source));
+        }
+    }
+}
+
+fn commands() -> Vec<Command> {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut files = Vec::new();
+    collect_sources(&src, &mut files);
+
+    let mut commands = Vec::new();
+    for (file, source) in files {
+        for chunk in source.split("#[tauri::command]").skip(1) {
+            let Some(fn_start) = chunk.find("fn ") else {
+                continue;
+            };
+            let rest = &chunk[fn_start + 3..];
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            let body_start = rest.find('{').unwrap_or(rest.len());
+            let return_type = rest[..body_start]
+                .rsplit_once("->")
+                .map(|(_, ret)| ret.trim().to_string())
+                .unwrap_or_default();
+            let end = rest.find("\n}\n").map_or(rest.len(), |i| i + 3);
+            commands.push(Command {
+                name,
+                file: file.clone(),
+                return_type,
+                source: rest[..end].to_string(),
+            });
+        }
+    }
+    commands
+}
+
+fn returns_notes(command: &Command) -> bool {
+    let ret = command.return_type.replace("NormalizedNoteReaction", "");
+    ret.contains("NormalizedNote")
+        || ret.contains("NormalizedNotification")
+        || RAW_NOTE_COMMANDS.contains(&command.name.as_str())
+}
+
+#[test]
+fn note_returning_commands_are_gated() {
+    let commands = commands();
+    let note_commands: Vec<_> = commands.iter().filter(|c| returns_notes(c)).collect();
+    assert!(
+        note_commands.len() > RAW_NOTE_COMMANDS.len(),
+        "command scan found too few note-returning commands",
+    );
+
+    let ungated: Vec<_> = note_commands
+        .iter()
+        .filter(|c| !c.source.contains("MediaGate"))
+        .map(|c| format!("{} ({})", c.name, c.file))
+        .collect();
+    assert!(
+        ungated.is_empty(),
+        "note-returning commands must pass their result through MediaGate: {ungated:?}",
+    );
+}
+
+#[test]
+fn raw_note_commands_exist() {
+    let commands = commands();
+    for name in RAW_NOTE_COMMANDS {
+        assert!(
+            commands.iter().any(|c| c.name == *name),
+            "{name} is listed in RAW_NOTE_COMMANDS but no such command exists",
+        );
+    }
+}
@@ -1,3 +1,5 @@
+import type { Job } from '@/bindings'
+import { listenTauri } from '@/utils/tauriEvents'
 import { commands } from '@/utils/tauriInvoke'
 import type { DriveApi, NormalizedDriveFile } from '../../types'
 import { type MisskeyApiContext, unwrapAny } from './context'
@@ -32,6 +34,41 @@ export function normalizeDriveFile(raw: DriveFileRaw): NormalizedDriveFile {
   }
 }
 
+/**
+ * ジョブを積み、終わるまで `nd:job-updated` を追う。enqueue より先に listen
+ * しておき、ID が分かる前に届いた更新も取りこぼさない。
+ */
+async function runJob(
+  enqueue: () => Promise<Job>,
+  onProgress?: (job: Job) => void,
+): Promise<Job> {
+  const latest = new Map<string, Job>()
+  let jobId: string | null = null
+  let settle: ((job: Job) => void) | null = null
+  const done = new Promise<Job>((resolve) => {
+    settle = resolve
+  })
+  const handle = (job: Job) => {
+    if (job.status === 'queued' || job.status === 'running') {
+      onProgress?.(job)
+    } else {
+      settle?.(job)
+    }
+  }
+  const unlisten = await listenTauri('nd:job-updated', (job) => {
+    if (jobId === null) latest.set(job.id, job)
+    else if (job.id === jobId) handle(job)
+  })
+  try {
+    const job = await enqueue()
+    jobId = job.id
+    handle(latest.get(job.id) ?? job)
+    return await done
+  } finally {
+    unlisten()
+  }
+}
+
 export function createDriveApi(ctx: MisskeyApiContext): DriveApi {
   return {
     async uploadFile(
@@ -60,6 +97,7 @@ export function createDriveApi(ctx: MisskeyApiContext): DriveApi {
       folderId: string | null = null,
     ): Promise<NormalizedDriveFile> {
       ctx.requireAuth()
+      // Rust 側がディスクから直接ストリーミングする (IPC にバイト列を載せない)
       return unwrapAny(
         await commands.apiUploadFileFromPath(
           ctx.accountId,
@@ -69,5 +107,45 @@ export function createDriveApi(ctx: MisskeyApiContext): DriveApi {
         ),
       )
     },
+
+    async shouldCompressVideo(
+      filePath: string,
+      thresholdBytes: number,
+    ): Promise<boolean> {
+      const check = await commands.videoCompressCheck(filePath, thresholdBytes)
+      return check.recommended
+    },
+
+    async uploadVideoCompressed(
+      filePath: string,
+      onProgress?: (ratio: number | null) => void,
+      isSensitive = false,
+      folderId: string | null = null,
+    ): Promise<NormalizedDriveFile> {
+      ctx.requireAuth()
+      // 変換とアップロードは Rust のジョブ (jobs.rs の compressUpload) で行う
+      const job = await runJob(
+        async () =>
+          unwrapAny(
+            await commands.jobEnqueue({
+              kind: 'compressUpload',
+              accountId: ctx.accountId,
+              path: filePath,
+              isSensitive,
+              folderId,
+              name: null,
+            }),
+          ),
+        ({ progress }) =>
+          onProgress?.(
+            progress.total ? Math.min(progress.done / progress.total, 1) : null,
+          ),
+      )
+      if (job.status === 'cancelled') throw new Error('Upload was cancelled')
+      if (job.status !== 'succeeded' || !job.result) {
+        throw new Error(job.error ?? 'Video compression failed')
+      }
+      return job.result as unknown as NormalizedDriveFile
+    },
   }
 }
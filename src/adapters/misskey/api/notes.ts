@@ -1,4 +1,6 @@
-import { commands } from '@/utils/tauriInvoke'
+import type { TimelineOptions as BindingTimelineOptions } from '@/bindings'
+import { commands, unwrap } from '@/utils/tauriInvoke'
+import { getTimelineBatched } from '@/utils/timelineBatch'
 import type {
   CreateNoteParams,
   NormalizedNote,
@@ -11,21 +13,43 @@ import type {
 } from '../../types'
 import { type MisskeyApiContext, unwrapAny } from './context'
 
+function toTimelineOptions(options: TimelineOptions): BindingTimelineOptions {
+  return {
+    limit: options.limit ?? 20,
+    sinceId: options.sinceId ?? null,
+    untilId: options.untilId ?? null,
+    filters: (options.filters ?? null) as never,
+    listId: options.listId ?? null,
+  }
+}
+
 export function createNotesApi(ctx: MisskeyApiContext): NotesApi {
   return {
     async getTimeline(
       type: TimelineType,
       options: TimelineOptions = {},
     ): Promise<NormalizedNote[]> {
-      // OGP prefetch is handled asynchronously on the Rust side via Tauri events
-      return unwrapAny(
-        await commands.apiGetTimeline(ctx.accountId, type, {
-          limit: options.limit ?? 20,
-          sinceId: options.sinceId ?? null,
-          untilId: options.untilId ?? null,
-          filters: (options.filters ?? null) as never,
-          listId: options.listId ?? null,
-        }),
+      // OGP prefetch is handled asynchronously on the Rust side via Tauri events.
+      // Columns loading together (startup) share one bulk invoke; a lone
+      // request uses raw-bytes IPC to skip the JSON response round-trip
+      return (await getTimelineBatched(
+        ctx.accountId,
+        type,
+        toTimelineOptions(options),
+      )) as unknown as NormalizedNote[]
+    },
+
+    async prefetchTimeline(
+      type: TimelineType,
+      options: TimelineOptions,
+    ): Promise<void> {
+      // Same options as getTimeline so the later fetch hits the prefetched page
+      unwrap(
+        await commands.apiPrefetchTimelinePage(
+          ctx.accountId,
+          type,
+          toTimelineOptions(options),
+        ),
       )
     },
 
@@ -201,6 +225,23 @@ export function createNotesApi(ctx: MisskeyApiContext): NotesApi {
       )
     },
 
+    async getPollRecommendations(
+      options: {
+        limit?: number
+        offset?: number
+        excludeChannels?: boolean
+      } = {},
+    ): Promise<NormalizedNote[]> {
+      return unwrapAny(
+        await commands.apiGetPollRecommendations(
+          ctx.accountId,
+          options.limit ?? 20,
+          options.offset ?? 0,
+          options.excludeChannels ?? null,
+        ),
+      )
+    },
+
     async getRoleNotes(
       roleId: string,
       options: PaginationOptions = {},
@@ -21,6 +21,11 @@ export function createServerContentApi(
       return unwrapAny(await commands.apiGetServerEmojis(ctx.accountId))
     },
 
+    async resolveEmojis(refs: string[]): Promise<Record<string, string>> {
+      if (refs.length === 0) return {}
+      return unwrapAny(await commands.apiResolveEmojis(ctx.accountId, refs))
+    },
+
     async getPinnedReactions(): Promise<string[]> {
       return unwrapAny(await commands.apiGetPinnedReactions(ctx.accountId))
     },
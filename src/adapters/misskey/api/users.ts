@@ -7,6 +7,7 @@ import type {
   NormalizedUserDetail,
   NotesApi,
   PaginationOptions,
+  RenoteMuteSupport,
   UserNotesOptions,
   UserRelation,
   UsersApi,
@@ -85,6 +86,11 @@ export function createUsersApi(
       return unwrapAny(await commands.apiGetUser(ctx.accountId, userId))
     },
 
+    async getUsers(userIds: string[]): Promise<Record<string, NormalizedUser>> {
+      if (userIds.length === 0) return {}
+      return unwrapAny(await commands.apiGetUsers(ctx.accountId, userIds))
+    },
+
     async getUserDetail(userId: string): Promise<NormalizedUserDetail> {
       return unwrapAny(await commands.apiGetUserDetail(ctx.accountId, userId))
     },
@@ -207,14 +213,26 @@ export function createUsersApi(
       return unwrapAny(await commands.apiGetRenoteMutedUsers(ctx.accountId))
     },
 
+    async getRenoteMuteSupport(): Promise<RenoteMuteSupport> {
+      ctx.requireAuth()
+      return unwrapAny(await commands.apiGetRenoteMuteSupport(ctx.accountId))
+    },
+
+    async getFollowingRenotesMuted(userId: string): Promise<boolean | null> {
+      ctx.requireAuth()
+      return unwrapAny(
+        await commands.apiGetFollowingRenotesMuted(ctx.accountId, userId),
+      )
+    },
+
     async renoteMuteUser(userId: string): Promise<void> {
       ctx.requireAuth()
-      unwrapAny(await commands.apiRenoteMuteUser(ctx.accountId, userId))
+      unwrapAny(await commands.apiSetRenoteMute(ctx.accountId, userId, true))
     },
 
     async unrenoteMuteUser(userId: string): Promise<void> {
       ctx.requireAuth()
-      unwrapAny(await commands.apiUnrenoteMuteUser(ctx.accountId, userId))
+      unwrapAny(await commands.apiSetRenoteMute(ctx.accountId, userId, false))
     },
 
     async blockUser(userId: string): Promise<void> {
@@ -3,11 +3,12 @@ import type {
   ManagedChannelSubscription,
   NormalizedNote,
   NormalizedNotification,
+  NotePatchEvent,
   NoteUpdateEvent,
   SubscriptionRuntimeState,
 } from '@/adapters/types'
 import type {
-  NoteCapture,
+  NotePatch,
   NoteUpdate,
   QueryItem,
   QuerySnapshot,
@@ -25,6 +26,8 @@ export interface CreateQuerySubscriptionOptions {
   onDelete?: (id: string) => void
   /** Called for every partial note update (reaction / pollVoted etc.) in delta.updates. */
   onUpdate?: (event: NoteUpdateEvent) => void
+  /** Called when Rust dropped events from this delta (buffer cap); refetch the latest page. */
+  onGap?: () => void
 }
 
 /**
@@ -109,6 +112,7 @@ export function createQuerySubscription(
         for (const u of delta.updates)
           opts.onUpdate(toNoteUpdateEvent(u.noteId, u))
       }
+      if (delta.gap) opts.onGap?.()
       lastRevision = delta.revision
     })
 
@@ -153,15 +157,15 @@ export function createQuerySubscription(
 }
 
 /**
- * QueryDelta / NoteCapture の typed update を adapter の NoteUpdateEvent へ写す
+ * QueryDelta の typed update を adapter の NoteUpdateEvent へ写す
  * (#781)。switch の網羅性は戻り値型で TS が検査するので、updateType の variant が
  * 増えたらここがコンパイルエラーになる。
  * (NoteUpdateBody は bindings 側で flatten により inline 化され named export
- * されないため、それを含む NoteUpdate | NoteCapture を受ける)
+ * されないため、それを含む NoteUpdate を受ける)
  */
 export function toNoteUpdateEvent(
   noteId: string,
-  u: NoteUpdate | NoteCapture,
+  u: NoteUpdate,
 ): NoteUpdateEvent {
   switch (u.updateType) {
     case 'reacted':
@@ -195,6 +199,41 @@ export function toNoteUpdateEvent(
   }
 }
 
+/**
+ * NoteCaptureBatch の patch を adapter の NotePatchEvent へ写す。
+ * bindings の optional / null を空の既定値と undefined に揃える。
+ */
+export function toNotePatchEvent(p: NotePatch): NotePatchEvent {
+  const pollVotes: Record<number, number> = {}
+  for (const [choice, votes] of Object.entries(p.pollVotes ?? {})) {
+    if (votes != null) pollVotes[Number(choice)] = votes
+  }
+  return {
+    noteId: p.noteId,
+    deleted: p.deleted ?? false,
+    reactions: compact(p.reactions),
+    reactionEmojis: compact(p.reactionEmojis),
+    myReaction:
+      p.myReaction == null
+        ? undefined
+        : p.myReaction.kind === 'set'
+          ? p.myReaction.reaction
+          : null,
+    pollVotes,
+    myPollChoices: p.myPollChoices ?? [],
+  }
+}
+
+function compact<T>(
+  map: Partial<{ [key in string]: T }> | undefined,
+): Record<string, T> {
+  const out: Record<string, T> = {}
+  for (const [key, value] of Object.entries(map ?? {})) {
+    if (value !== undefined) out[key] = value
+  }
+  return out
+}
+
 /**
  * QueryItem (bindings) を adapter 正準型へ落とす境界ヘルパ (#781)。
  * bindings 側の NormalizedNote は specta の再帰制限で reply/renote が
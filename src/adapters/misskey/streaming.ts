@@ -2,12 +2,12 @@ import { events } from '@/bindings'
 import { recordStreamHealth, removeStreamHealth } from '@/core/streamHealth'
 import { commands, unwrap } from '@/utils/tauriInvoke'
 import type {
-  NoteUpdateEvent,
+  NotePatchEvent,
   RawStreamEvent,
   StreamAdapter,
   StreamConnectionState,
 } from '../types'
-import { toNoteUpdateEvent } from './query'
+import { toNotePatchEvent } from './query'
 
 export class MisskeyStream implements StreamAdapter {
   private accountId: string
@@ -28,7 +28,7 @@ export class MisskeyStream implements StreamAdapter {
   /** Per-note capture handlers (subNote / unsubNote). */
   private noteCaptureHandlers = new Map<
     string,
-    (event: NoteUpdateEvent) => void
+    (patch: NotePatchEvent) => void
   >()
   /** Raw event observers (StreamInspector). */
   private rawEventHandlers = new Set<(event: RawStreamEvent) => void>()
@@ -133,17 +133,15 @@ export class MisskeyStream implements StreamAdapter {
         console.error('[stream] failed to listen stream-status:', e),
       )
 
-    // Rust 側 flusher が DELTA_FLUSH_WINDOW (16ms) でまとめた capture batch を購読。
-    // 個別 stream-note-capture-updated は Rust 側で抑止されているので、ここが
-    // 唯一の note capture 配信経路になる。
+    // Rust 側 flusher が DELTA_FLUSH_WINDOW (16ms) でノート単位の patch に
+    // 畳んだ capture batch を購読。個別 stream-note-capture-updated は Rust 側で
+    // 抑止されているので、ここが唯一の note capture 配信経路になる。
     events.noteCaptureBatch
       .listen((event) => {
         if (gen !== this._listenerGeneration) return
-        for (const c of event.payload.captures) {
-          if (c.accountId !== this.accountId) continue
-          this.noteCaptureHandlers.get(c.noteId)?.(
-            toNoteUpdateEvent(c.noteId, c),
-          )
+        for (const p of event.payload.patches) {
+          if (p.accountId !== this.accountId) continue
+          this.noteCaptureHandlers.get(p.noteId)?.(toNotePatchEvent(p))
         }
       })
       .then((fn) => {
@@ -181,7 +179,7 @@ export class MisskeyStream implements StreamAdapter {
     })
   }
 
-  subNote(noteId: string, handler: (event: NoteUpdateEvent) => void): void {
+  subNote(noteId: string, handler: (patch: NotePatchEvent) => void): void {
     this.noteCaptureHandlers.set(noteId, handler)
     commands.streamSubNote(this.accountId, noteId).catch((e) => {
       console.warn('[stream] subNote failed:', e)
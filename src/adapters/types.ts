@@ -363,6 +363,25 @@ export interface NoteUpdateEvent {
   }
 }
 
+/**
+ * Note Capture の更新をノート単位に集計したもの (Rust 側 `note_patch::fold`)。
+ * 件数はすべて差分で、自分のリアクションは `myReaction` にだけ載る。
+ */
+export interface NotePatchEvent {
+  noteId: string
+  deleted: boolean
+  /** 他ユーザーのリアクション件数の増減 */
+  reactions: Record<string, number>
+  /** shortcode (コロン無し) → 絵文字 URL */
+  reactionEmojis: Record<string, string>
+  /** 自分のリアクションの新しい値。undefined は変化なし、null は取り消し */
+  myReaction?: string | null
+  /** choice index → 増えた票数 */
+  pollVotes: Record<number, number>
+  /** 自分が投票した choice index */
+  myPollChoices: number[]
+}
+
 export interface ReactionInfo {
   user: NormalizedUser
   reaction: string
@@ -457,9 +476,13 @@ import type { Flash, GalleryPost, Page } from '@/bindings'
 export type { Flash, GalleryPost, Page }
 
 // ワードミュート（#610）。mutedWords / hardMutedWords は notecli が `i` から取得。
-import type { MutedWord, MutedWordsResult } from '@/bindings'
+import type {
+  MutedWord,
+  MutedWordsResult,
+  RenoteMuteSupport,
+} from '@/bindings'
 
-export type { MutedWord, MutedWordsResult }
+export type { MutedWord, MutedWordsResult, RenoteMuteSupport }
 
 /** Misskey Pages の取得対象 endpoint (Rust 側で allowlist チェック)。 */
 export type PagesEndpoint = 'pages/featured' | 'i/pages' | 'i/page-likes'
@@ -505,6 +528,8 @@ export interface NotesApi {
     type: TimelineType,
     options?: TimelineOptions,
   ): Promise<NormalizedNote[]>
+  /** 次ページ (`untilId` 必須) をバックグラウンドで先読みさせる。結果は後の getTimeline が使う */
+  prefetchTimeline?(type: TimelineType, options: TimelineOptions): Promise<void>
   getNote(noteId: string): Promise<NormalizedNote>
   createNote(params: CreateNoteParams): Promise<NormalizedNote>
   updateNote(noteId: string, params: CreateNoteParams): Promise<void>
@@ -540,6 +565,12 @@ export interface NotesApi {
   ): Promise<NormalizedNote[]>
   getFavorites(options?: PaginationOptions): Promise<NormalizedNote[]>
   getFeaturedNotes(options?: { limit?: number }): Promise<NormalizedNote[]>
+  /** 自分がまだ投票できる受付中のアンケート (offset でページング) */
+  getPollRecommendations(options?: {
+    limit?: number
+    offset?: number
+    excludeChannels?: boolean
+  }): Promise<NormalizedNote[]>
   getRoleNotes(
     roleId: string,
     options?: PaginationOptions,
@@ -549,6 +580,8 @@ export interface NotesApi {
 /** ユーザーの取得・フォロー関係・ミュート/ブロック/通報 (users/* + following/*) */
 export interface UsersApi {
   getUser(userId: string): Promise<NormalizedUser>
+  /** 複数ユーザーを一括取得する (id → ユーザー。存在しない id は含まれない) */
+  getUsers(userIds: string[]): Promise<Record<string, NormalizedUser>>
   getUserDetail(userId: string): Promise<NormalizedUserDetail>
   lookupUser(username: string, host?: string | null): Promise<NormalizedUser>
   getUserNotes(
@@ -590,6 +623,17 @@ export interface UsersApi {
   getMutedWords(): Promise<MutedWordsResult>
   /** 自分が renote mute 中のユーザー ID 一覧（#614: 起動時の renote mute store hydrate 用）。 */
   getRenoteMutedUsers(): Promise<string[]>
+  /**
+   * リノートだけのミュートの対応方式。renote-mute API が無いフォークでは
+   * following/update の withRenotes (フォロー中のみ) で代替する。
+   */
+  getRenoteMuteSupport(): Promise<RenoteMuteSupport>
+  /**
+   * following/update 方式のサーバーで、フォロー中ユーザーのリノートを
+   * 隠しているか (withRenotes の逆)。サーバーが返さなければ null。
+   */
+  getFollowingRenotesMuted(userId: string): Promise<boolean | null>
+  /** 対応方式に合わせて renote-mute API か following/update で設定する */
   renoteMuteUser(userId: string): Promise<void>
   unrenoteMuteUser(userId: string): Promise<void>
   blockUser(userId: string): Promise<void>
@@ -666,6 +710,18 @@ export interface DriveApi {
     isSensitive?: boolean,
     folderId?: string | null,
   ): Promise<NormalizedDriveFile>
+  /** 閾値を超える動画で、ffmpeg による事前圧縮を勧めるか (デスクトップのみ) */
+  shouldCompressVideo?(filePath: string, thresholdBytes: number): Promise<boolean>
+  /**
+   * 動画を圧縮してからアップロードする。`onProgress` は変換の進捗 (0〜1、
+   * 総再生時間が分からなければ null)。
+   */
+  uploadVideoCompressed?(
+    filePath: string,
+    onProgress?: (ratio: number | null) => void,
+    isSensitive?: boolean,
+    folderId?: string | null,
+  ): Promise<NormalizedDriveFile>
 }
 
 /** アクティビティチャート (charts/*) */
@@ -714,6 +770,8 @@ export interface ChartsApi {
 /** サーバー提供コンテンツ (絵文字 / お知らせ / Pages / Gallery / Flash / 連合) */
 export interface ServerContentApi {
   getServerEmojis(): Promise<ServerEmoji[]>
+  /** `shortcode@host` の一覧を一括解決する (参照 → URL。解決できないものは含まれない) */
+  resolveEmojis(refs: string[]): Promise<Record<string, string>>
   getPinnedReactions(): Promise<string[]>
   getAnnouncements(options?: {
     limit?: number
@@ -807,7 +865,7 @@ export interface StreamAdapter {
   /** Clean up local listeners/handlers without killing the shared WebSocket connection. */
   cleanup(): void
   /** Subscribe to per-note updates (Misskey Note Capture). */
-  subNote(noteId: string, handler: (event: NoteUpdateEvent) => void): void
+  subNote(noteId: string, handler: (patch: NotePatchEvent) => void): void
   unsubNote(noteId: string): void
   readonly state: StreamConnectionState
   on(
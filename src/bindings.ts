@@ -222,6 +222,34 @@ async apiGetTimeline(accountId: string, timelineType: TimelineType, options: Tim
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Fetch several columns' timelines in one invoke (startup / bulk refresh).
+ * 
+ * Requests run concurrently, at most `BULK_PER_HOST_CONCURRENCY` at a time per
+ * host so one server is not hit by every column at once. Each request goes
+ * through `api_get_timeline`, so caching, dedup and rate-limit retries apply.
+ */
+async apiGetTimelinesBulk(requests: TimelineRequest[]) : Promise<Result<Partial<{ [key in string]: TimelineResult }>, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_timelines_bulk", { requests }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fetch the next timeline page (`options.untilId` required) in the background
+ * so the matching `api_get_timeline` call can return it without waiting
+ * on the server. Filtered requests are ignored.
+ */
+async apiPrefetchTimelinePage(accountId: string, timelineType: TimelineType, options: TimelineOptions) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_prefetch_timeline_page", { accountId, timelineType, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiGetUserLists(accountId: string) : Promise<Result<UserList[], { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_user_lists", { accountId }) };
@@ -380,6 +408,19 @@ async apiVotePoll(accountId: string, noteId: string, choice: number) : Promise<R
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Open polls the account can still vote in (`notes/polls/recommendation`).
+ * The server leaves out expired polls, polls already voted in and the
+ * account's own notes. Paged by `offset`, not by note id.
+ */
+async apiGetPollRecommendations(accountId: string, limit: number | null, offset: number | null, excludeChannels: boolean | null) : Promise<Result<NormalizedNote[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_poll_recommendations", { accountId, limit, offset, excludeChannels }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiGetNoteReactions(accountId: string, noteId: string, reactionType: string | null, limit: number | null, untilId: string | null) : Promise<Result<NormalizedNoteReaction[], { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_note_reactions", { accountId, noteId, reactionType, limit, untilId }) };
@@ -518,6 +559,19 @@ async apiGetUser(accountId: string, userId: string) : Promise<Result<NormalizedU
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * 複数ユーザーをまとめて取得し、id → ユーザーの map で返す。
+ * リアクション一覧 / フォロワー一覧のアバター解決を 1 件ずつ投げないための口。
+ * 存在しない (削除済み等) ユーザーは map に含まれない。
+ */
+async apiGetUsers(accountId: string, userIds: string[]) : Promise<Result<Partial<{ [key in string]: NormalizedUser }>, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_users", { accountId, userIds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiGetUserDetail(accountId: string, userId: string) : Promise<Result<NormalizedUserDetail, { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_user_detail", { accountId, userId }) };
@@ -534,6 +588,10 @@ async apiGetUserNotes(accountId: string, userId: string, options: TimelineOption
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * アカウントのサーバーの絵文字一覧。絵文字キャッシュを通し、返した一覧を
+ * 以後の差分同期 (`nd:emojis-updated`) の基準にする。
+ */
 async apiGetServerEmojis(accountId: string) : Promise<Result<ServerEmoji[], { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_server_emojis", { accountId }) };
@@ -542,6 +600,50 @@ async apiGetServerEmojis(accountId: string) : Promise<Result<ServerEmoji[], { co
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * `shortcode@host` (host 省略 / `.` は自ホスト) の一覧を絵文字キャッシュで
+ * 解決し、入力文字列 → URL の map を返す。未取得のホストはホストごとに 1 回
+ * だけ取りに行く。解決できなかった参照は含まれない。
+ */
+async apiResolveEmojis(accountId: string, refs: string[]) : Promise<Result<Partial<{ [key in string]: string }>, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_resolve_emojis", { accountId, refs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * アカウントのサーバーのカスタム絵文字を名前 / エイリアスで検索する
+ * (完全一致 → 前方一致 → 部分一致 → あいまい一致の順)。一覧は絵文字
+ * キャッシュのものを使い、未取得なら 1 回だけ取りに行く。
+ */
+async searchEmojis(accountId: string, query: string, limit: number) : Promise<Result<ServerEmoji[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_emojis", { accountId, query, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * アカウントのサーバーの絵文字一覧をすぐに取り直す。差分があれば
+ * `nd:emojis-updated` を emit して返す (差分が無い / 取得に失敗したら null)。
+ */
+async syncEmojis(accountId: string) : Promise<Result<EmojiDiff | null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("sync_emojis", { accountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * `nd:emojis-updated` の差分を当てたことを返す。以後の差分はこの版から取る。
+ */
+async ackEmojis(host: string, version: number) : Promise<void> {
+    await TAURI_INVOKE("ack_emojis", { host, version });
+},
 async apiGetPinnedReactions(accountId: string) : Promise<Result<string[], { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_pinned_reactions", { accountId }) };
@@ -651,6 +753,30 @@ async apiGetCacheDateRange(accountId: string, timelineType: string) : Promise<Re
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * タイムラインのキャッシュ上の gap を新しい順に返す。オフライン表示で
+ * `newerId` と `olderId` の間に「読み込めていない区間」を出すのに使う。
+ */
+async timelineGaps(accountId: string, timelineType: TimelineType, listId: string | null) : Promise<Result<TimelineGap[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("timeline_gaps", { accountId, timelineType, listId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * `around_id` (gap の新しい側か古い側のノート) に接する gap の間を 1 ページ
+ * 取ってキャッシュする。取り切れなければ縮めた gap を `remaining` で返す。
+ */
+async fillGap(accountId: string, timelineType: TimelineType, listId: string | null, aroundId: string) : Promise<Result<FillGapResult, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fill_gap", { accountId, timelineType, listId, aroundId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiSearchNotesLocal(accountId: string, query: string, limit: number | null, sinceDate: string | null, untilDate: string | null, ascending: boolean | null) : Promise<Result<NormalizedNote[], { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_search_notes_local", { accountId, query, limit, sinceDate, untilDate, ascending }) };
@@ -699,6 +825,42 @@ async apiUnmuteUser(accountId: string, userId: string) : Promise<Result<null, {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Which way (if any) the account's server supports muting renotes only.
+ */
+async apiGetRenoteMuteSupport(accountId: string) : Promise<Result<RenoteMuteSupport, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_renote_mute_support", { accountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Mute or unmute a user's renotes through whichever route the server has:
+ * the renote-mute endpoints, or `following/update` (followed users only).
+ */
+async apiSetRenoteMute(accountId: string, userId: string, muted: boolean) : Promise<Result<RenoteMuteSupport, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_set_renote_mute", { accountId, userId, muted }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether a followed user's renotes are hidden through `following/update`,
+ * as the server reports the follow's `withRenotes` on `users/show`. `None`
+ * when the server doesn't report it (not following, or an older fork).
+ */
+async apiGetFollowingRenotesMuted(accountId: string, userId: string) : Promise<Result<boolean | null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_following_renotes_muted", { accountId, userId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiRenoteMuteUser(accountId: string, userId: string) : Promise<Result<null, { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_renote_mute_user", { accountId, userId }) };
@@ -829,6 +991,19 @@ async apiGetUserRelations(accountId: string, userIds: string[]) : Promise<Result
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Users that `user_id` replies to most, ordered by weight
+ * (`users/get-frequently-replied-users`). Defaults to the account's
+ * own user, which is what the composer's mention suggestions ask for.
+ */
+async apiGetFrequentlyRepliedUsers(accountId: string, userId: string | null, limit: number | null) : Promise<Result<FrequentlyRepliedUser[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_frequently_replied_users", { accountId, userId, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiGetUnreadNotificationCount(accountId: string) : Promise<Result<number, { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_unread_notification_count", { accountId }) };
@@ -845,6 +1020,20 @@ async apiMarkAllNotificationsAsRead(accountId: string) : Promise<Result<null, {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * サーバーにテスト通知 (`notifications/test-notification`) を送らせる。
+ * 通知はストリーミングで届くので、OS 通知や通知音まで実際の経路で確かめられる。
+ * エンドポイントの無いサーバー (古い Misskey / 一部フォーク) では false を返すので、
+ * 呼び出し側は `stream_simulate_notification` で代わりに再現する。
+ */
+async apiSendTestNotification(accountId: string) : Promise<Result<boolean, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_send_test_notification", { accountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async apiGetUnreadChat(accountId: string) : Promise<Result<boolean, { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("api_get_unread_chat", { accountId }) };
@@ -1634,6 +1823,25 @@ async streamUnsubNote(accountId: string, noteId: string) : Promise<Result<null,
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * 合成した通知をストリーミングで届いたときと同じ経路に流す (既定は `test`)。
+ * サーバーを介さずに OS 通知・自動化ルール・通知音・DND 設定を確かめるためのもの。
+ */
+async streamSimulateNotification(accountId: string, notificationType: string | null) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stream_simulate_notification", { accountId, notificationType }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Fetch a URL preview. With an account it goes through that account's
+ * server (`/url?url=`, the summaly proxy the web client uses), so the linked
+ * site sees the server rather than this machine; see
+ * `OgpCache::get_ogp_via_proxy` for when it still fetches locally. Without an
+ * account: plugins → direct HTML parse.
+ */
 async fetchOgp(url: string, accountId: string | null) : Promise<Result<SummaryData, { code: string; message: string }>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("fetch_ogp", { url, accountId }) };
@@ -2278,8 +2486,43 @@ async queryGetReadModelSnapshot(queryId: string, limit: number | null) : Promise
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * 複数ソースのタイムラインを 1 ページ分統合して返す。`cursors` は前回の
+ * 応答の `cursors` (初回は空)。`merged_id` を渡すと結果をその feed の
+ * 既出扱いにする。
+ */
+async apiGetMergedTimeline(sources: MergedSource[], cursors: (string | null)[], limit: number | null, mergedId: string | null) : Promise<Result<MergedTimelinePage, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_merged_timeline", { sources, cursors, limit, mergedId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * ソースごとの timeline query を開いて merged feed を作る。以降の新着は
+ * `MergedTimelineDelta` で届く。
+ */
+async mergedTimelineOpen(sources: MergedSource[]) : Promise<Result<MergedTimelineSnapshot, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("merged_timeline_open", { sources }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async mergedTimelineClose(mergedId: string) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("merged_timeline_close", { mergedId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Tauri command: update performance config at runtime.
+ * Persisted to `settings.json` (see `settings.rs`, which also offers
+ * per-section updates).
  */
 async updatePerformanceConfig(config: PerformanceConfig) : Promise<Result<null, string>> {
     try {
@@ -2300,6 +2543,34 @@ async getPerformanceConfig() : Promise<Result<PerformanceConfig, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * 現在のバックエンド設定を返す。
+ */
+async settingsGet() : Promise<BackendSettings> {
+    return await TAURI_INVOKE("settings_get");
+},
+/**
+ * 1 セクションを検証して保存し、すぐに反映する。変更後の全体を返す。
+ */
+async settingsSetSection(section: SettingsSection) : Promise<Result<BackendSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("settings_set_section", { section }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 性能設定とメディアの 5 セクションを既定値に戻す (機能ごとのセクションはそのまま)。
+ */
+async settingsReset() : Promise<Result<BackendSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("settings_reset") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * フロントの `resolveFor('external')` の結果を受け取る (#712 §4.2)。
  * `reloadPermissionsConfig()` / 権限保存が必ずこれを伴う。
@@ -2329,81 +2600,581 @@ async permissionsLockdown() : Promise<Result<null, string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
-}
-}
-
-/** user-defined events **/
-
-
-export const events = __makeEvents__<{
-noteCaptureBatch: NoteCaptureBatch,
-notificationClicked: NotificationClicked,
-queryDelta: QueryDelta,
-streamChatMessageReacted: StreamChatMessageReacted,
-streamChatMessageUnreacted: StreamChatMessageUnreacted,
-streamEnvelope: StreamEnvelope,
-streamStatus: StreamStatus
-}>({
-noteCaptureBatch: "note-capture-batch",
-notificationClicked: "notification-clicked",
-queryDelta: "query-delta",
-streamChatMessageReacted: "stream-chat-message-reacted",
-streamChatMessageUnreacted: "stream-chat-message-unreacted",
-streamEnvelope: "stream-envelope",
-streamStatus: "stream-status"
-})
-
-/** user-defined constants **/
-
-
-
-/** user-defined types **/
-
+},
 /**
- * Token を含まない、フロントエンド向け Account 構造体
+ * ミニウィンドウを開く。モバイルはマルチウィンドウを持たないため未対応。
  */
-export type AccountPublic = { id: string; host: string; userId: string; username: string; displayName: string | null; avatarUrl: string | null; software: string; hasToken: boolean }
+async quickPostOpen(accountId: string | null) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("quick_post_open", { accountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * `charts/active-users`
+ * 投稿完了 / キャンセル時にミニウィンドウを隠す (破棄はしない)。
  */
-export type ActiveUsersChart = { readWrite: number[]; read: number[]; write: number[]; registeredWithinWeek: number[]; registeredWithinMonth: number[]; registeredWithinYear: number[]; registeredOutsideWeek: number[]; registeredOutsideMonth: number[]; registeredOutsideYear: number[] }
-export type AiChatMessage = { role: AiChatRole; content: string; 
+async quickPostHide() : Promise<void> {
+    await TAURI_INVOKE("quick_post_hide");
+},
 /**
- * AI が呼び出した tool の id (Anthropic `toolu_...` / OpenAI `call_...`)。
- * 同一 message が tool_use を含む assistant ターンであることを示す。
- * content と併用された場合は「テキスト + tool_use」の混在 message として
- * provider に投げる。
+ * ノートスレッド / ユーザープロフィールを別ウィンドウで開き、label を返す。
+ * 同じ対象が既に開いていれば前面に出す。
  */
-tool_use_id?: string | null; 
+async windowOpenDetached(view: DetachedView) : Promise<Result<string, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("window_open_detached", { view }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * tool_use の name (capability id)。tool_use_id とセット。
+ * 記録中の切り離しウィンドウ一覧。
  */
-tool_use_name?: string | null; 
+async windowListDetached() : Promise<DetachedWindow[]> {
+    return await TAURI_INVOKE("window_list_detached");
+},
 /**
- * tool_use の入力 JSON。tool_use_id とセット。
+ * 切り離しウィンドウを閉じて記録から外す。
  */
-tool_use_input?: JsonValue | null; 
+async windowCloseDetached(label: string) : Promise<Result<boolean, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("window_close_detached", { label }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * tool_result メッセージのときに、対応する tool_use の id を指す。
- * 設定されている場合 content は実行結果テキスト、role は user 想定。
+ * DND 状態と設定を返す。
  */
-tool_result_for?: string | null }
-export type AiChatRequest = { stream_id: string; 
+async dndGetStatus() : Promise<DndStatus> {
+    return await TAURI_INVOKE("dnd_get_status");
+},
 /**
- * 使用する Vault 接続 (#564 後続)。endpoint / 認証 / protocol は
- * この接続から Rust 側で解決する。secret はフロントには渡らない。
+ * DND の扱いを設定する (フロントの設定値を起動時・変更時に反映)。
  */
-connection_id: string; model: string; messages: AiChatMessage[]; system: string | null; max_tokens: number | null; 
+async dndSetMode(mode: DndMode) : Promise<DndStatus> {
+    return await TAURI_INVOKE("dnd_set_mode", { mode });
+},
 /**
- * Provider 形式 (Anthropic or OpenAI) の生 tool definition 配列。
- * フロントが provider に応じて事前変換した形で渡す。空 / None なら
- * tool calling は無効 (= 既存挙動と同じ)。
+ * 現在のアイドル時間と離席状態を返す。
  */
-tools: JsonValue | null }
-export type AiChatRole = "system" | "user" | "assistant"
-export type Antenna = { id: string; name: string; 
+async idleGetStatus() : Promise<Result<IdleStatus, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("idle_get_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 'home' | 'all' | 'users' | 'list' | 'users_blacklist'
+ * 離席判定の閾値 (分) を設定する。null で無効化。フロントの設定値を
+ * 起動時・変更時に反映する。
+ */
+async idleConfigure(thresholdMinutes: number | null) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("idle_configure", { thresholdMinutes }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 最近アップロードしたファイルを新しい順に返す (既定 20 件、最大 100 件)。
+ * 投稿フォームの「最近のアップロード」から添付し直すのに使う。
+ */
+async recentUploads(accountId: string, limit: number | null) : Promise<Result<NormalizedDriveFile[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recent_uploads", { accountId, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 最近のアップロードから 1 件外す (ドライブのファイルはそのまま)。
+ */
+async forgetRecentUpload(accountId: string, fileId: string) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("forget_recent_upload", { accountId, fileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 現在の OS のライト / ダーク設定とアクセントカラーを返す。
+ */
+async systemAppearance() : Promise<SystemAppearance> {
+    return await TAURI_INVOKE("system_appearance");
+},
+/**
+ * 現在の接続状態を返す。
+ */
+async networkGetStatus() : Promise<NetworkStatus> {
+    return await TAURI_INVOKE("network_get_status");
+},
+/**
+ * 到達性をすぐに確認する (フロントからの手動再確認用)。
+ */
+async networkCheckNow() : Promise<Result<NetworkStatus, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("network_check_now") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 現在の起動設定を返す。
+ */
+async launchGetSettings() : Promise<Result<LaunchSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("launch_get_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 起動設定を更新し、反映後の値を返す。自動起動は OS への登録 / 解除を行う。
+ */
+async launchSetSettings(settings: LaunchSettings) : Promise<Result<LaunchSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("launch_set_settings", { settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 呼び出し元のウィンドウを表示する。メインウィンドウの初回だけは起動設定に
+ * 従って最小化 / トレイ格納のままにする。
+ */
+async launchRevealWindow() : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("launch_reveal_window") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 現在のバックグラウンド同期の設定を返す。
+ */
+async backgroundSyncGetSettings() : Promise<Result<BackgroundSyncSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("background_sync_get_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * バックグラウンド同期の設定を保存し、反映後の値を返す。
+ */
+async backgroundSyncSetSettings(settings: BackgroundSyncSettings) : Promise<Result<BackgroundSyncSettings, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("background_sync_set_settings", { settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * ゲートで隠した添付ファイルの本来の URL を返す。ユーザーがセンシティブな
+ * メディアを明示的に表示したときだけ呼ぶ。
+ */
+async revealFile(fileId: string) : Promise<Result<RevealedFile, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reveal_file", { fileId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * ジョブを登録する。実行はバックグラウンドで行い、状態は `nd:job-updated` で届く。
+ */
+async jobEnqueue(spec: JobSpec) : Promise<Result<Job, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("job_enqueue", { spec }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 保持しているジョブ (待機中 / 実行中 / 終了済み) を登録順に返す。
+ */
+async jobList() : Promise<Job[]> {
+    return await TAURI_INVOKE("job_list");
+},
+/**
+ * 待機中 / 実行中のジョブをキャンセルする。
+ */
+async jobCancel(id: string) : Promise<Result<Job, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("job_cancel", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 終了済みのジョブを一覧から消し、残りを返す。
+ */
+async jobClearFinished() : Promise<Job[]> {
+    return await TAURI_INVOKE("job_clear_finished");
+},
+/**
+ * インストール済みの外部プラグインを返す。
+ */
+async pluginList() : Promise<PluginInfo[]> {
+    return await TAURI_INVOKE("plugin_list");
+},
+/**
+ * 外部プラグインを有効化 / 無効化する。有効化は manifest が宣言する
+ * スコープの承認を兼ね、HTTP API が動いていればすぐに起動する。
+ */
+async pluginSetEnabled(id: string, enabled: boolean) : Promise<Result<PluginInfo, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin_set_enabled", { id, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 登録済みの自動化ルールを登録順に返す。
+ */
+async automationList() : Promise<AutomationRule[]> {
+    return await TAURI_INVOKE("automation_list");
+},
+/**
+ * ルールを追加または更新して保存し、保存後のルール (採番済み id) を返す。
+ */
+async automationSave(rule: AutomationRule) : Promise<Result<AutomationRule, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("automation_save", { rule }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 自動化ルールを削除する。
+ */
+async automationDelete(id: string) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("automation_delete", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 登録済みのタスクを登録順に返す。
+ */
+async scheduleList() : Promise<ScheduledTask[]> {
+    return await TAURI_INVOKE("schedule_list");
+},
+/**
+ * タスクを追加または更新して保存し、保存後のタスク (採番済み id / 次回時刻) を返す。
+ */
+async scheduleSave(task: ScheduledTask) : Promise<Result<ScheduledTask, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("schedule_save", { task }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * タスクを削除する。
+ */
+async scheduleDelete(id: string) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("schedule_delete", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * タスクを今すぐ 1 回実行する。結果は `nd:schedule-updated` で届く。
+ */
+async scheduleRunNow(id: string) : Promise<Result<ScheduledTask, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("schedule_run_now", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * バックエンドに定義された capability の一覧。
+ */
+async capabilityList() : Promise<CapabilityInfo[]> {
+    return await TAURI_INVOKE("capability_list");
+},
+/**
+ * バックエンドの capability を実行する。権限はフロントの dispatcher が
+ * 照合済みとして扱う (Local)。
+ */
+async capabilityExecute(id: string, params: JsonValue | null) : Promise<Result<JsonValue, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("capability_execute", { id, params }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * アカウントのサーバーが上流のレート制限中かを返す。
+ */
+async apiGetRateLimitState(accountId: string) : Promise<Result<RateLimitState, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("api_get_rate_limit_state", { accountId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 本文を翻訳する。`providers` を順に試し、失敗・未設定なら次へフォールバックする。
+ */
+async translateText(request: TranslateRequest) : Promise<Result<Translation, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("translate_text", { request }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * `data` を QR コード画像 (PNG / SVG) にする。`size` は一辺の最小ピクセル数。
+ */
+async generateQr(data: string, format: QrFormat, size: number | null) : Promise<Result<number[], { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("generate_qr", { data, format, size }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * アップロード前加工の設定を保存して反映する (フロントの設定値が変わったときに呼ぶ)。
+ */
+async uploadPrepConfigure(config: UploadPrepConfig) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("upload_prep_configure", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * `path` の動画をアップロード前に圧縮すべきかを返す。
+ */
+async videoCompressCheck(path: string, thresholdBytes: number) : Promise<VideoCompressCheck> {
+    return await TAURI_INVOKE("video_compress_check", { path, thresholdBytes });
+},
+/**
+ * ピン留めユーザー (ユーザーカラム等) のアバター・バナーを先読みする。
+ * キャッシュに載った枚数を返す。
+ */
+async warmUserImages(accountId: string, userIds: string[]) : Promise<Result<number, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("warm_user_images", { accountId, userIds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * ノートを共有用のテキストにする (「Markdown でコピー」「引用としてコピー」など)。
+ */
+async formatNoteForShare(accountId: string, noteId: string, format: NoteShareFormat) : Promise<Result<string, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("format_note_for_share", { accountId, noteId, format }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 定期バックアップに使うパスフレーズを OS キーチェーンに保存する。
+ */
+async backupSetPassphrase(passphrase: string) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("backup_set_passphrase", { passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * パスフレーズが保存済みか。
+ */
+async backupHasPassphrase() : Promise<boolean> {
+    return await TAURI_INVOKE("backup_has_passphrase");
+},
+/**
+ * バックアップの保存先フォルダを選ぶ。キャンセル時は None。
+ */
+async backupPickDir() : Promise<Result<string | null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("backup_pick_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 選んだ `.ndbak` から復元する。`passphrase` が None ならキーチェーンの
+ * ものを使う。キャンセル時は None。成功したらフロントはアプリを再起動する。
+ */
+async backupImport(passphrase: string | null) : Promise<Result<BackupRestoreResult | null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("backup_import", { passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * クラッシュレポートの記録が有効か。
+ */
+async crashReportsGetEnabled() : Promise<boolean> {
+    return await TAURI_INVOKE("crash_reports_get_enabled");
+},
+/**
+ * クラッシュレポートの記録を有効 / 無効にする。
+ */
+async crashReportsSetEnabled(enabled: boolean) : Promise<Result<null, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("crash_reports_set_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 保存済みのレポートを新しい順に返す。
+ */
+async crashReportsList() : Promise<CrashReport[]> {
+    return await TAURI_INVOKE("crash_reports_list");
+},
+/**
+ * 保存済みのレポートをすべて消し、消した件数を返す。
+ */
+async crashReportsClear() : Promise<number> {
+    return await TAURI_INVOKE("crash_reports_clear");
+},
+/**
+ * レポートの保存先フォルダ (「フォルダを開く」用)。無ければ作る。
+ */
+async crashReportsDir() : Promise<Result<string, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("crash_reports_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * フロントの未捕捉エラーを記録する。無効なら何もせず false。
+ */
+async crashReportCapture(message: string, stack: string | null) : Promise<Result<boolean, { code: string; message: string }>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("crash_report_capture", { message, stack }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+export const events = __makeEvents__<{
+mergedTimelineDelta: MergedTimelineDelta,
+noteCaptureBatch: NoteCaptureBatch,
+notificationClicked: NotificationClicked,
+queryDelta: QueryDelta,
+streamChatMessageReacted: StreamChatMessageReacted,
+streamChatMessageUnreacted: StreamChatMessageUnreacted,
+streamCue: StreamCue,
+streamEnvelope: StreamEnvelope,
+streamStatus: StreamStatus
+}>({
+mergedTimelineDelta: "merged-timeline-delta",
+noteCaptureBatch: "note-capture-batch",
+notificationClicked: "notification-clicked",
+queryDelta: "query-delta",
+streamChatMessageReacted: "stream-chat-message-reacted",
+streamChatMessageUnreacted: "stream-chat-message-unreacted",
+streamCue: "stream-cue",
+streamEnvelope: "stream-envelope",
+streamStatus: "stream-status"
+})
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+/**
+ * Token を含まない、フロントエンド向け Account 構造体
+ */
+export type AccountPublic = { id: string; host: string; userId: string; username: string; displayName: string | null; avatarUrl: string | null; software: string; hasToken: boolean }
+/**
+ * `charts/active-users`
+ */
+export type ActiveUsersChart = { readWrite: number[]; read: number[]; write: number[]; registeredWithinWeek: number[]; registeredWithinMonth: number[]; registeredWithinYear: number[]; registeredOutsideWeek: number[]; registeredOutsideMonth: number[]; registeredOutsideYear: number[] }
+export type AiChatMessage = { role: AiChatRole; content: string; 
+/**
+ * AI が呼び出した tool の id (Anthropic `toolu_...` / OpenAI `call_...`)。
+ * 同一 message が tool_use を含む assistant ターンであることを示す。
+ * content と併用された場合は「テキスト + tool_use」の混在 message として
+ * provider に投げる。
+ */
+tool_use_id?: string | null; 
+/**
+ * tool_use の name (capability id)。tool_use_id とセット。
+ */
+tool_use_name?: string | null; 
+/**
+ * tool_use の入力 JSON。tool_use_id とセット。
+ */
+tool_use_input?: JsonValue | null; 
+/**
+ * tool_result メッセージのときに、対応する tool_use の id を指す。
+ * 設定されている場合 content は実行結果テキスト、role は user 想定。
+ */
+tool_result_for?: string | null }
+export type AiChatRequest = { stream_id: string; 
+/**
+ * 使用する Vault 接続 (#564 後続)。endpoint / 認証 / protocol は
+ * この接続から Rust 側で解決する。secret はフロントには渡らない。
+ */
+connection_id: string; model: string; messages: AiChatMessage[]; system: string | null; max_tokens: number | null; 
+/**
+ * Provider 形式 (Anthropic or OpenAI) の生 tool definition 配列。
+ * フロントが provider に応じて事前変換した形で渡す。空 / None なら
+ * tool calling は無効 (= 既存挙動と同じ)。
+ */
+tools: JsonValue | null }
+export type AiChatRole = "system" | "user" | "assistant"
+export type Antenna = { id: string; name: string; 
+/**
+ * 'home' | 'all' | 'users' | 'list' | 'users_blacklist'
  */
 src?: string; userListId?: string | null; 
 /**
@@ -2411,38 +3182,148 @@ src?: string; userListId?: string | null;
  */
 users?: string[]; keywords?: string[][]; excludeKeywords?: string[][]; caseSensitive?: boolean; localOnly?: boolean; excludeBots?: boolean; withReplies?: boolean; withFile?: boolean; notify?: boolean }
 /**
- * `charts/ap-request` (ActivityPub の配送成功/失敗/受信数)
+ * `charts/ap-request` (ActivityPub の配送成功/失敗/受信数)
+ */
+export type ApRequestChart = { deliverSucceeded: number[]; deliverFailed: number[]; inboxReceived: number[] }
+/**
+ * フロントに見せるメタデータ (ハッシュは含めない)。
+ */
+export type ApiTokenMeta = { id: string; name: string; createdAtMs: number }
+export type AuthSession = { sessionId: string; url: string; host: string }
+/**
+ * 認証方式。Rust 側で secret を注入する際の形を判別共用体で表現する。
+ * 
+ * v2 で `oauth2` variant を追加する余地を残すため `#[serde(tag = "kind")]`。
+ */
+export type AuthType = 
+/**
+ * `Authorization: Bearer <secret>`
+ */
+{ kind: "bearer" } | 
+/**
+ * `<name>: <secret>`
+ */
+{ kind: "header"; name: string } | 
+/**
+ * URL クエリパラメータ `?<param>=<secret>`
+ */
+{ kind: "query"; param: string } | 
+/**
+ * `Authorization: Basic base64(<username>:<secret>)`
+ */
+{ kind: "basic"; username: string }
+export type AutomationAction = 
+/**
+ * OS 通知を出す。`title` 省略時はルール名。
+ */
+{ kind: "notify"; title: string | null } | 
+/**
+ * 対象ノートにリアクションする (`:emoji:` またはユニコード絵文字)。
+ */
+{ kind: "react"; reaction: string } | 
+/**
+ * 対象ノートをクリップに追加する。
+ */
+{ kind: "addToClip"; clipId: string } | 
+/**
+ * ルール・ノート・通知を JSON で POST する。https か、http はループバックのみ。
+ */
+{ kind: "webhook"; url: string } | 
+/**
+ * 通知の音 / 優先度を決める (省略した方は通知の種類による既定のまま)。
+ * 通知トリガー (メンション / 投票終了) のルールでだけ使える。
+ */
+{ kind: "cue"; sound: CueSound | null; priority: CuePriority | null }
+export type AutomationFired = { ruleId: string; accountId: string; 
+/**
+ * 対象ノート (通知トリガーでノートを伴わない場合は None)。
+ */
+noteId: string | null; notificationId: string | null; 
+/**
+ * 失敗したアクション (`"react: ..."` 形式)。全成功なら空。
+ */
+errors: string[] }
+export type AutomationRule = { 
+/**
+ * 新規作成時は空文字で渡すと採番する。
+ */
+id: string; name: string; enabled: boolean; trigger: AutomationTrigger; actions: AutomationAction[] }
+export type AutomationTrigger = 
+/**
+ * ノート本文 (CW 含む) にキーワードのいずれかが含まれる (大文字小文字を無視)。
+ * `source` を指定するとその列 (query) に流れたノートだけを対象にする。
+ */
+{ kind: "noteKeyword"; accountId: string; source: QueryKey | null; keywords: string[] } | 
+/**
+ * 指定ユーザーからのメンション / リプライ。`user` は `alice` (同じサーバー)
+ * か `alice@example.com`。
+ */
+{ kind: "mentionFrom"; accountId: string; user: string } | 
+/**
+ * 投票した (または自分が作った) 投票が終了した。
+ */
+{ kind: "pollEnded"; accountId: string }
+export type AvatarDecoration = { id: string; url: string; angle?: number | null; flipH?: boolean | null; offsetX?: number | null; offsetY?: number | null }
+/**
+ * `settings.json` の全体。欠けたセクション / 項目は既定値で埋める。
+ * 
+ * `launch` 以降は機能ごとのセクション。各機能のコマンドが読み書きするので
+ * フロントの型には出さない。
+ */
+export type BackendSettings = { network: NetworkSettings; cache: CacheSettings; streaming: StreamingSettings; httpServer: HttpServerSettings; media: MediaSettings }
+export type BackgroundSyncSettings = { 
+/**
+ * トレイ格納中に同期する。
+ */
+enabled: boolean; 
+/**
+ * 同期の間隔 (分)。
+ */
+intervalMinutes: number }
+export type BackupRestoreResult = { 
+/**
+ * 書き戻した設定ファイルの数
+ */
+settingsFiles: number }
+export type CacheSettings = { 
+/**
+ * 画像メモリキャッシュの合計上限 (MB)
+ */
+memoryMaxMb: number; 
+/**
+ * メモリに載せる画像 1 枚あたりの上限 (KB)
+ */
+memoryItemMaxKb: number; 
+/**
+ * OGP キャッシュの件数上限
  */
-export type ApRequestChart = { deliverSucceeded: number[]; deliverFailed: number[]; inboxReceived: number[] }
+ogpEntriesMax: number; 
 /**
- * フロントに見せるメタデータ (ハッシュは含めない)。
+ * ディスクの画像キャッシュを残す日数
  */
-export type ApiTokenMeta = { id: string; name: string; createdAtMs: number }
-export type AuthSession = { sessionId: string; url: string; host: string }
+imageTtlDays: number }
+export type CacheStats = { noteCount: number; dbSizeBytes: number }
 /**
- * 認証方式。Rust 側で secret を注入する際の形を判別共用体で表現する。
- * 
- * v2 で `oauth2` variant を追加する余地を残すため `#[serde(tag = "kind")]`。
+ * `capability_list` / `GET /api/capabilities` が返すメタデータ。
+ * フロントの `capabilities/list` と同じ形に `icon` / `external` / `palette` を足したもの。
  */
-export type AuthType = 
+export type CapabilityInfo = { id: string; 
 /**
- * `Authorization: Bearer <secret>`
+ * AI tool 名と同じ sanitized 形式 (`.` → `_`)。
  */
-{ kind: "bearer" } | 
+name: string; label: string; 
 /**
- * `<name>: <secret>`
+ * Tabler icon 名。
  */
-{ kind: "header"; name: string } | 
+icon: string; category: string; description: string; params: Partial<{ [key in string]: ParamDef }>; returns: ReturnDef; permissions: string[]; requiresConfirmation: boolean; 
 /**
- * URL クエリパラメータ `?<param>=<secret>`
+ * HTTP API (external principal) から実行できるか。
  */
-{ kind: "query"; param: string } | 
+external: boolean; 
 /**
- * `Authorization: Basic base64(<username>:<secret>)`
+ * コマンドパレットに表示するか (フロント側に同等のコマンドがあるものは出さない)。
  */
-{ kind: "basic"; username: string }
-export type AvatarDecoration = { id: string; url: string; angle?: number | null; flipH?: boolean | null; offsetX?: number | null; offsetY?: number | null }
-export type CacheStats = { noteCount: number; dbSizeBytes: number }
+palette: boolean }
 export type Channel = { id: string; name: string; color?: string | null }
 export type ChatCacheStats = { messageCount: number; bytes: number }
 /**
@@ -2491,6 +3372,7 @@ isFavorited?: boolean | null;
  * `notesCount` は一部エンドポイントのみ返る。
  */
 notesCount?: number | null }
+export type ColorScheme = "light" | "dark"
 /**
  * 接続メタデータ。secret 本体は含まない (OS キーチェーンに別管理)。
  * 
@@ -2596,6 +3478,32 @@ origin?: ConnectionOrigin | null;
  * `origin = External` の詳細 (`ai-provider` 等)。
  */
 externalSource?: string | null }
+export type CrashKind = 
+/**
+ * Rust 側の panic
+ */
+"panic" | 
+/**
+ * フロントの未捕捉エラー / Promise rejection
+ */
+"frontend"
+export type CrashReport = { id: string; createdMs: number; kind: CrashKind; appVersion: string; os: string; arch: string; 
+/**
+ * panic したスレッド名 (フロントは None)
+ */
+thread: string | null; 
+/**
+ * `file:line:column` (フロントは None)
+ */
+location: string | null; 
+/**
+ * エラー名 (分かれば) とメッセージ本文のハッシュ。本文そのものは残さない
+ */
+message: string; 
+/**
+ * Rust のバックトレース / JS のスタックのフレーム行
+ */
+backtrace: string | null }
 export type CreateNoteParams = { text: string | null; cw: string | null; visibility: string | null; localOnly: boolean | null; modeFlags: Partial<{ [key in string]: boolean }> | null; replyId: string | null; renoteId: string | null; fileIds: string[] | null; poll: CreateNotePoll | null; scheduledAt: string | null }
 export type CreateNotePoll = { choices: string[]; multiple: boolean | null; expiresAt: number | null }
 export type CreatedApiToken = { meta: ApiTokenMeta; 
@@ -2605,6 +3513,71 @@ export type CreatedApiToken = { meta: ApiTokenMeta;
  */
 token: string }
 export type CreatedDriveFolder = { id: string; name: string; parentId?: string | null }
+/**
+ * 通知の優先度。`high` は自分宛ての会話 (メンション / 返信 / 引用)。
+ */
+export type CuePriority = "low" | "normal" | "high"
+/**
+ * 鳴らす音の種類。どのファイルを鳴らすかは受け手が決める。
+ */
+export type CueSound = "mention" | "reply" | "quote" | "reaction" | "renote" | "follow" | 
+/**
+ * フォローリクエストの受信 / 承認
+ */
+"followRequest" | 
+/**
+ * 投票の終了
+ */
+"poll" | "other"
+/**
+ * 切り離しウィンドウに表示する内容。
+ */
+export type DetachedView = { kind: "note"; accountId: string; noteId: string } | { kind: "user"; accountId: string; userId: string }
+/**
+ * 記録中の切り離しウィンドウ。
+ */
+export type DetachedWindow = { label: string; view: DetachedView }
+/**
+ * DND の扱い (ユーザー設定)。
+ */
+export type DndMode = "followSystem" | "alwaysNotify"
+export type DndStatus = { mode: DndMode; 
+/**
+ * OS の DND 状態。検知できない環境では null。
+ */
+osActive: boolean | null; 
+/**
+ * 現在 OS 通知を抑制しているか。
+ */
+suppressing: boolean }
+/**
+ * ホストの絵文字一覧を取り直したときの差分。
+ */
+export type EmojiDiff = { host: string; 
+/**
+ * 差分を当てた後の一覧の版。当てたら `ack_emojis` で返す
+ */
+version: number; added: ServerEmoji[]; 
+/**
+ * 削除された shortcode
+ */
+removed: string[]; renamed: EmojiRename[]; 
+/**
+ * 名前はそのままで URL / カテゴリ / エイリアスが変わった絵文字
+ */
+updated: ServerEmoji[] }
+/**
+ * 名前だけが変わった絵文字 (画像 URL が同じ)。
+ */
+export type EmojiRename = { 
+/**
+ * 変更前の shortcode
+ */
+from: string; 
+/**
+ * 変更後の絵文字
+ */
+emoji: ServerEmoji }
 /**
  * `notes_cache` の eviction policy。 デフォルトは「ほぼ永続保存」 — notedeck の
  * 「過去ノートを一瞬でローカル検索」という UX を尊重し、 暴走防止の hard cap
@@ -2641,6 +3614,15 @@ pub: number[]; pubsub: number[]; subActive: number[]; pubActive: number[] }
  * `show-instance` でのみ返るフィールドは Option にする。
  */
 export type FederationInstance = { id: string; host: string; usersCount: number; notesCount: number; followingCount: number; followersCount: number; isNotResponding: boolean; isSuspended: boolean; isBlocked: boolean | null; isSilenced: boolean | null; isMediaSilenced: boolean | null; suspensionState: string | null; moderationNote: string | null; softwareName: string | null; softwareVersion: string | null; openRegistrations: boolean | null; name: string | null; description: string | null; maintainerName: string | null; maintainerEmail: string | null; iconUrl: string | null; faviconUrl: string | null; themeColor: string | null; firstRetrievedAt: string; infoUpdatedAt: string | null; latestRequestSentAt: string | null; latestRequestReceivedAt: string | null; latestStatus: number | null }
+export type FillGapResult = { 
+/**
+ * 取れた間のノート (新しい順、キャッシュ済み)
+ */
+notes: NormalizedNote[]; 
+/**
+ * まだ残っている gap (繋がったら null)
+ */
+remaining: TimelineGap | null }
 /**
  * `users/flashs` / `flash/show` の 1 件分。本家
  * packages/backend/src/models/Flash.ts。
@@ -2652,6 +3634,11 @@ export type FollowChartSection = { followings: FollowChartGroup; followers: Foll
  * `users/gallery/posts` / `gallery/posts/show` の 1 件分。本家
  * packages/backend/src/models/GalleryPost.ts。
  */
+export type FrequentlyRepliedUser = { user: NormalizedUser; 
+/**
+ * How often `user` is replied to, relative to the other entries
+ */
+weight: number }
 export type GalleryPost = { id: string; createdAt: string; updatedAt: string; title: string; description: string | null; userId: string; user?: NormalizedUser | null; files: NormalizedDriveFile[]; isSensitive?: boolean; likedCount?: number; isLiked?: boolean | null }
 export type HealthReport = { 
 /**
@@ -2672,7 +3659,114 @@ heartbeatIntervalMinutes: number | null;
 logDir: string | null }
 export type HttpFetchRequest = { url: string; method: string | null; headers: Partial<{ [key in string]: string }> | null; body: string | null; timeoutMs: number | null }
 export type HttpFetchResponse = { status: number; headers: Partial<{ [key in string]: string }>; body: string }
+export type HttpServerSettings = { 
+/**
+ * ローカル HTTP API から上流サーバー 1 つへ送る 1 分あたりのリクエスト上限
+ */
+maxRequestsPerWindow: number }
+export type IdleStatus = { 
+/**
+ * 最後の入力からの経過秒数。取得できない環境では null。
+ */
+idleSeconds: number | null; 
+/**
+ * 離席判定の閾値 (分)。null は離席判定を無効化している。
+ */
+thresholdMinutes: number | null; 
+/**
+ * 閾値を超えて離席中と判定しているか。
+ */
+away: boolean }
+export type Job = { id: string; spec: JobSpec; status: JobStatus; progress: JobProgress; 
+/**
+ * 成功時の結果 (アップロードしたファイル、件数など)。
+ */
+result: JsonValue | null; error: string | null; createdAtMs: number; updatedAtMs: number }
+export type JobProgress = { done: number; 
+/**
+ * 総量が分からないうちは None。
+ */
+total: number | null }
+/**
+ * ジョブの内容。
+ */
+export type JobSpec = 
+/**
+ * ローカルファイルをドライブへアップロードする。
+ */
+{ kind: "upload"; accountId: string; path: string; isSensitive: boolean; folderId: string | null; name: string | null } | 
+/**
+ * 動画を ffmpeg で圧縮してからドライブへアップロードする
+ * (`video_compress.rs`)。進捗は変換済みの再生時間 (ms)。
+ */
+{ kind: "compressUpload"; accountId: string; path: string; isSensitive: boolean; folderId: string | null; name: string | null } | 
+/**
+ * notecli.db をアプリデータ下の `exports/` へコピーする。書き出し先は
+ * 選ばせない (ユーザーが選ぶ場合は save dialog 付きの `export_db`)。
+ */
+{ kind: "exportDb" } | 
+/**
+ * DB と設定を暗号化して `dir` へ書き出し、新しいものから `keep` 個残す
+ * (`backup.rs`)。パスフレーズはキーチェーンから読む。
+ */
+{ kind: "backup"; dir: string; keep: number } | 
+/**
+ * Misskey のフォローエクスポート (1 行 1 アカウント) を読んでフォローする。
+ */
+{ kind: "importFollows"; accountId: string; path: string } | 
+/**
+ * 期限切れの画像キャッシュをディスクから消す。
+ */
+{ kind: "pruneImageCache" }
+export type JobStatus = "queued" | "running" | "succeeded" | "failed" | "cancelled"
 export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+export type LaunchSettings = { 
+/**
+ * OS へのログイン時に自動起動する。
+ */
+autostart: boolean; 
+/**
+ * 自動起動時にウィンドウを最小化した状態で起動する。
+ */
+startMinimized: boolean; 
+/**
+ * 自動起動時にウィンドウを出さずトレイに格納して起動する
+ * (start_minimized より優先。トレイが使えない環境では最小化になる)。
+ */
+startInTray: boolean }
+export type MediaSettings = { 
+/**
+ * センシティブ指定の添付の URL を WebView に渡さない (`media_gate.rs`)
+ */
+hideSensitive: boolean }
+export type MergedSource = { accountId: string; timelineType: TimelineType; listId: string | null }
+export type MergedTimelineDelta = { mergedId: string; 
+/**
+ * 既出のコピーを除いた新着 (届いた順)
+ */
+inserts: NormalizedNote[]; 
+/**
+ * 配下 query で削除されたノート id (どのサーバーのコピーかは問わない)
+ */
+deletes: string[]; 
+/**
+ * 配下 query のどれかで stream バッファが溢れた (`QueryDelta::gap`)
+ */
+gap: boolean }
+export type MergedTimelinePage = { 
+/**
+ * 重複除去済み、新しい順
+ */
+notes: NormalizedNote[]; 
+/**
+ * 次ページ用のソースごとの `untilId` (`sources` と同じ並び)
+ */
+cursors: (string | null)[] }
+export type MergedTimelineSnapshot = { mergedId: string; 
+/**
+ * 配下の timeline query (ソースと同じ並び)
+ */
+queryIds: string[] }
 /**
  * Misskey の `mutedWords` / `hardMutedWords` の 1 要素。
  * 文字列配列なら AND 語群（全語含むとマッチ）、文字列なら `/regex/flags` 形式の正規表現。
@@ -2687,6 +3781,28 @@ export type MutedWordsResult = { mutedWords: MutedWord[]; hardMutedWords: MutedW
  * インスタンスミュート（#613）。ミュート対象ホスト名の配列。同じ `i` から取得。
  */
 mutedInstances: string[] }
+/**
+ * 自分のリアクションの最終状態。
+ */
+export type MyReactionChange = { kind: "set"; reaction: string } | { kind: "cleared" }
+export type NetworkSettings = { 
+/**
+ * 画像などの同時取得数
+ */
+maxConcurrentFetches: number; 
+/**
+ * 同じホストへの取得がこの回数続けて失敗したら一時停止する
+ */
+circuitBreakerThreshold: number; 
+/**
+ * 一時停止する秒数
+ */
+circuitBreakerSecs: number }
+export type NetworkStatus = { 
+/**
+ * オンラインか。起動直後の未確認時は null。
+ */
+online: boolean | null }
 export type NormalizedDriveFile = { id: string; name: string; type: string; url: string; thumbnailUrl: string | null; size?: number; isSensitive?: boolean; 
 /**
  * 画像の幅 (px)。フロントの aspect-ratio 予約 (レイアウトシフト防止) 用
@@ -2756,8 +3872,10 @@ pinnedNotes?: NormalizedNote[] }
  * Per-note capture (`subNote`) update. account_id まで付けて mixed-account batch
  * でも JS 側で正しく fan-out できるようにする。
  */
-export type NoteCapture = ({ updateType: "reacted"; body: NoteReactedBody } | { updateType: "unreacted"; body: NoteUnreactedBody } | { updateType: "pollVoted"; body: NotePollVotedBody } | { updateType: "deleted"; body: NoteDeletedBody }) & { accountId: string; noteId: string }
-export type NoteCaptureBatch = { captures: NoteCapture[] }
+/**
+ * flush window 内の capture をノート単位に畳んだもの (`note_patch::fold`)。
+ */
+export type NoteCaptureBatch = { patches: NotePatch[] }
 export type NoteDeletedBody = { deletedAt?: string | null }
 export type NoteDraft = { id: string; createdAt: string; text: string | null; cw: string | null; visibility: string; localOnly?: boolean; fileIds?: string[]; hashtag?: string | null; replyId?: string | null; renoteId?: string | null; channelId?: string | null; poll?: NoteDraftPoll | null; scheduledAt?: number | null; isActuallyScheduled?: boolean }
 /**
@@ -2767,6 +3885,30 @@ export type NoteDraft = { id: string; createdAt: string; text: string | null; cw
  * (notedeck 側でラッパーを剥がして直接 NoteDraft を渡す)。
  */
 export type NoteDraftPoll = { choices: string[]; multiple?: boolean | null; expiresAt?: number | null }
+/**
+ * 1 ノート分の集計済み更新。
+ */
+export type NotePatch = { accountId: string; noteId: string; 
+/**
+ * 他ユーザーのリアクションによる件数の増減 (差し引き 0 のキーは含めない)
+ */
+reactions?: Partial<{ [key in string]: number }>; 
+/**
+ * shortcode (コロン無し) → 絵文字 URL
+ */
+reactionEmojis?: Partial<{ [key in string]: string }>; 
+/**
+ * 自分のリアクションが変わったときだけ Some
+ */
+myReaction?: MyReactionChange | null; 
+/**
+ * choice index → 増えた票数 (自分の票を含む)
+ */
+pollVotes?: Partial<{ [key in number]: number }>; 
+/**
+ * 自分が投票した choice
+ */
+myPollChoices?: number[]; deleted?: boolean }
 export type NotePollVotedBody = { choice: number; userId?: string | null }
 export type NoteReactedBody = { reaction: string; 
 /**
@@ -2774,6 +3916,19 @@ export type NoteReactedBody = { reaction: string;
  * フォークが bare string を送る揺れもここで吸収する。
  */
 emoji?: ReactionEmoji | null; userId?: string | null }
+export type NoteShareFormat = 
+/**
+ * 本文を Markdown に変換し、添付と元ノートへのリンクを付ける
+ */
+"markdown" | 
+/**
+ * 本文 (と CW) をプレーンテキストにしたもの
+ */
+"plain" | 
+/**
+ * 本文を `> ` で引用し、投稿者と元ノートへのリンクを付ける
+ */
+"quoteWithLink"
 export type NoteUnreactedBody = { reaction: string; userId?: string | null }
 export type NoteUpdate = 
 /**
@@ -2796,12 +3951,41 @@ export type Page = { id: string; createdAt: string; updatedAt: string; title: st
  * `content` / `variables` はブロック構造で複雑。生 JSON で運ぶ。
  */
 content?: JsonValue | null; variables?: JsonValue | null; script?: string | null; alignCenter?: boolean; hideTitleWhenPinned?: boolean; font?: string | null; eyeCatchingImageId?: string | null; eyeCatchingImage?: NormalizedDriveFile | null; likedCount?: number | null; isLiked?: boolean | null }
+export type ParamDef = { type: ValueType; description: string; optional: boolean }
 /**
  * Performance configuration shared across the application.
  * All fields are dynamically updatable at runtime via Tauri commands.
  */
-export type PerformanceConfig = { memory_cache_max_total: number; memory_cache_max_item: number; max_concurrent_fetches: number; rust_ogp_cache_max: number; max_requests_per_window: number; circuit_breaker_threshold: number; circuit_breaker_duration: number; image_cache_ttl_days: number }
+export type PerformanceConfig = { memory_cache_max_total: number; memory_cache_max_item: number; max_concurrent_fetches: number; rust_ogp_cache_max: number; max_requests_per_window: number; circuit_breaker_threshold: number; circuit_breaker_duration: number; image_cache_ttl_days: number; 
+/**
+ * Max inserts / updates buffered per query between delta flushes.
+ */
+stream_pending_max: number; 
+/**
+ * Max note capture updates buffered between flushes.
+ */
+stream_captures_max: number; stream_drop_policy: StreamDropPolicy }
 export type Player = { url: string; width: number | null; height: number | null; allow?: string[] }
+/**
+ * フロントに見せるプラグインの状態。
+ */
+export type PluginInfo = { id: string; name: string; version: string; description: string | null; 
+/**
+ * manifest が要求するスコープ。
+ */
+scopes: string[]; 
+/**
+ * 有効化時に承認したスコープ。
+ */
+grantedScopes: string[]; enabled: boolean; 
+/**
+ * 要求スコープが承認時より増えている。再度有効化するまで起動しない。
+ */
+needsApproval: boolean; running: boolean; pid: number | null; 
+/**
+ * 直近の起動失敗 / 異常終了の内容。
+ */
+lastError: string | null }
 /**
  * 接続を開示する先の principal クラス (#712 §6.1)。
  * principal そのものより粗いクラス — 接続ごとに全 principal 分のトグルを
@@ -2822,13 +4006,20 @@ export type PrincipalClass =
  */
 "external"
 export type PvChartGroup = { user: number[]; visitor: number[] }
+export type QrFormat = "png" | "svg"
 export type QueryDelta = { queryId: string; revision: number; inserts: QueryItem[]; deletes: string[]; 
 /**
  * Partial note updates (reaction add/remove, poll vote, etc.) routed
  * from `stream-note-updated`. Items in the read model are not rewritten —
  * consumers apply these to their own per-note state.
  */
-updates: NoteUpdate[] }
+updates: NoteUpdate[]; 
+/**
+ * The flush window overflowed `StreamLimits` and events were dropped.
+ * Consumers should refetch the latest page instead of trusting the delta
+ * to be contiguous.
+ */
+gap: boolean }
 /**
  * Read-model item flowing through a query delta (#781). Internally tagged so
  * the frontend receives a discriminated union; every variant carries `id`.
@@ -2844,9 +4035,73 @@ export type QueryReadModelSnapshot = { queryId: string; revision: number;
 itemIds: string[] }
 export type QueryRuntimeState = "live" | "warm" | "suspended"
 export type QuerySnapshot = { queryId: string; key: QueryKey; runtimeState: QueryRuntimeState; subscriberCount: number; revision: number; sourceSubscriptionId: string | null }
+export type RateLimitState = { limited: boolean; 
+/**
+ * 解除予定までの残り (制限中でなければ 0)
+ */
+retryAfterMs: number }
 export type ReactionEmoji = { name: string; url: string } | string
 export type ReactionInfo = { user: NormalizedUser; reaction: string }
+/**
+ * How the server lets a followed user's renotes be hidden.
+ */
+export type RenoteMuteSupport = 
+/**
+ * `renote-mute/create` / `renote-mute/delete` (Misskey and most forks)
+ */
+"endpoint" | 
+/**
+ * `withRenotes` on `following/update`, for forks without renote-mute
+ */
+"followingUpdate" | "unsupported"
 export type Report = { ok: boolean; checks: Check[] }
+export type ReturnDef = { type: ValueType; description: string | null }
+/**
+ * 隠した添付ファイルの本来の URL。
+ */
+export type RevealedFile = { url: string; thumbnailUrl: string | null }
+export type Schedule = 
+/**
+ * 指定時刻 (UNIX ms) に 1 回だけ。
+ */
+{ kind: "once"; atMs: number } | 
+/**
+ * `anchor_ms` を起点に `minutes` 分ごと。毎日 3:00 なら anchor = 3:00、
+ * minutes = 1440 (夏時間の切り替えでは 1 時間ずれる)。
+ */
+{ kind: "every"; minutes: number; anchorMs: number }
+export type ScheduledAction = 
+/**
+ * タイムラインを取得してキャッシュに保存する。
+ */
+{ kind: "syncTimeline"; accountId: string; timelineType: TimelineType; listId: string | null } | 
+/**
+ * 期限切れの画像キャッシュを消すジョブを積む。
+ */
+{ kind: "pruneImageCache" } | 
+/**
+ * アプリデータ下の `exports/` に DB を書き出すジョブを積む
+ * (`JobSpec::ExportDb`)。
+ */
+{ kind: "exportBackup" } | 
+/**
+ * `dir` に暗号化バックアップを書き出し、新しいものから `keep` 個残すジョブを積む
+ * (`backup.rs`)。
+ */
+{ kind: "encryptedBackup"; dir: string; keep: number } | 
+/**
+ * ノートを投稿する (`Once` のみ)。
+ */
+{ kind: "post"; accountId: string; params: CreateNoteParams; channelId: string | null }
+export type ScheduledTask = { 
+/**
+ * 新規作成時は空文字で渡すと採番する。
+ */
+id: string; name: string; enabled: boolean; schedule: Schedule; action: ScheduledAction; 
+/**
+ * 次回の実行時刻。無効化中 / 実行済みの `Once` は None。
+ */
+nextRunMs: number | null; lastRunMs: number | null; lastError: string | null }
 export type SearchOptions = { limit?: number; sinceId: string | null; untilId: string | null; sinceDate: number | null; untilDate: number | null; 
 /**
  * 指定ユーザーのノートのみに絞る (notes/search の userId)
@@ -2903,6 +4158,10 @@ export type ServerNotesChartSection = { total: number[]; inc: number[]; dec: num
  */
 export type ServerUsersChart = { local: ServerUsersChartSection; remote: ServerUsersChartSection }
 export type ServerUsersChartSection = { total: number[]; inc: number[]; dec: number[] }
+/**
+ * 1 セクション分の更新。
+ */
+export type SettingsSection = { section: "network"; values: NetworkSettings } | { section: "cache"; values: CacheSettings } | { section: "streaming"; values: StreamingSettings } | { section: "httpServer"; values: HttpServerSettings } | { section: "media"; values: MediaSettings }
 export type Status = "ok" | "warn" | "fail"
 export type StreamChatMessageDeletedEvent = { accountId: string; subscriptionId: string; messageId: string }
 export type StreamChatMessageEvent = { accountId: string; subscriptionId: string; message: ChatMessage }
@@ -2914,6 +4173,31 @@ export type StreamChatMessageUnreactedEvent = { accountId: string; subscriptionI
  * `stream-status` で報告する接続状態 (#781)。
  */
 export type StreamConnectionState = "connected" | "reconnecting" | "disconnected"
+/**
+ * 通知 1 件に付ける音と優先度 (イベント名 "stream-cue")。
+ */
+export type StreamCue = { accountId: string; notificationId: string; 
+/**
+ * Misskey の通知 type そのまま (`mention` / `reaction` / `follow` など)
+ */
+notificationType: string; noteId: string | null; userId: string | null; sound: CueSound; priority: CuePriority; 
+/**
+ * OS の集中モード / DND で OS 通知を抑制している間は true。鳴らすかどうかは受け手が決める
+ */
+quiet: boolean }
+/**
+ * What to drop when a streaming buffer hits its cap.
+ */
+export type StreamDropPolicy = 
+/**
+ * Keep the newest events and flag the delta with a gap marker so the
+ * consumer refetches the missed range.
+ */
+"dropOldest" | 
+/**
+ * Keep what is already buffered and discard new arrivals (also flagged).
+ */
+"dropNewest"
 /**
  * 統合チャネル (イベント名 "stream-envelope")。全イベントを { kind, payload }
  * の tagged union で流す。Inspector の raw tap と未読カウンタが購読する。
@@ -2940,10 +4224,90 @@ export type StreamNoteUpdatedEvent =
 export type StreamNotificationEvent = { accountId: string; subscriptionId: string; notification: NormalizedNotification }
 export type StreamStatus = StreamStatusEvent
 export type StreamStatusEvent = { accountId: string; state: StreamConnectionState }
+export type StreamingSettings = { 
+/**
+ * クエリごとに flush 間で溜める追加 / 更新の上限
+ */
+pendingMax: number; 
+/**
+ * flush 間で溜めるノートキャプチャ更新の上限
+ */
+capturesMax: number; 
+/**
+ * 上限に達したときに捨てる側
+ */
+dropPolicy: StreamDropPolicy }
 export type SummaryData = { title: string | null; description: string | null; icon: string | null; sitename: string | null; thumbnail: string | null; medias: string[]; player: Player | null; url: string; sensitive: boolean }
+export type SystemAppearance = { 
+/**
+ * OS のライト / ダーク設定。取得できない環境では null。
+ */
+colorScheme: ColorScheme | null; 
+/**
+ * OS のアクセントカラー (`#rrggbb`)。取得できない環境では null。
+ */
+accentColor: string | null }
 export type TimelineFilter = { withRenotes: boolean | null; withReplies: boolean | null; withFiles: boolean | null; withBots: boolean | null; withSensitive: boolean | null }
+/**
+ * キャッシュ上で隣り合っているが、間のノートが欠けている 2 ノート。
+ */
+export type TimelineGap = { 
+/**
+ * gap の新しい側 (直後) のノート
+ */
+newerId: string; newerAt: string; 
+/**
+ * gap の古い側 (直前) のノート
+ */
+olderId: string; olderAt: string }
 export type TimelineOptions = { limit?: number; sinceId: string | null; untilId: string | null; filters?: TimelineFilter | null; listId: string | null }
+/**
+ * One column's timeline fetch in `api_get_timelines_bulk`.
+ */
+export type TimelineRequest = { 
+/**
+ * Caller-chosen key the result is returned under
+ */
+id: string; accountId: string; timelineType: TimelineType; options: TimelineOptions | null }
+/**
+ * Per-request outcome; one failing column does not fail the whole batch.
+ */
+export type TimelineResult = { status: "ok"; notes: NormalizedNote[] } | { status: "error"; code: string; message: string }
 export type TimelineType = string
+export type TranslateRequest = { accountId: string; 
+/**
+ * サーバー翻訳に使うノート ID。無ければ `server` は飛ばす
+ */
+noteId: string | null; text: string; 
+/**
+ * 翻訳先の言語 (BCP 47。例: `ja`, `en-US`)
+ */
+targetLang: string; 
+/**
+ * 試す順。空ならサーバー翻訳のみ
+ */
+providers?: TranslationProvider[] }
+export type Translation = { text: string; 
+/**
+ * 検出された原文の言語 (プロバイダが返した場合)
+ */
+sourceLang: string | null; 
+/**
+ * 翻訳したプロバイダ (`server` / `deepl` / `libreTranslate`)
+ */
+provider: string; 
+/**
+ * キャッシュから返したか
+ */
+cached: boolean }
+/**
+ * 翻訳に使うプロバイダ。`connection_id` は vault の接続 ID。
+ */
+export type TranslationProvider = 
+/**
+ * サーバーの `notes/translate` (`note_id` が必要)
+ */
+{ kind: "server" } | { kind: "deepl"; connectionId: string } | { kind: "libreTranslate"; connectionId: string }
 /**
  * 「確認なしで使う」のプラグイン個体単位の記憶。
  * 
@@ -2961,6 +4325,23 @@ id: string;
  * 帰属表示用の配布名スナップショット (記憶時点の名前)。
  */
 name?: string | null }
+export type UploadPrepConfig = { 
+/**
+ * EXIF / GPS 等のメタデータを取り除く (JPEG / PNG / WebP)
+ */
+stripMetadata: boolean; 
+/**
+ * 長辺がこれ (px) を超える画像を縮小する。null で縮小しない
+ */
+maxDimension: number | null; 
+/**
+ * 再エンコード時の JPEG 品質 (1〜100)
+ */
+jpegQuality: number; 
+/**
+ * HEIC / HEIF を JPEG に変換する (`heic` feature 付きビルドのみ)
+ */
+convertHeic: boolean }
 export type UserField = { name: string; value: string }
 /**
  * `charts/user/following`
@@ -3005,6 +4386,10 @@ type: string; note: UserReactionNoteRef }
  */
 export type UserReactionNoteRef = { id: string }
 export type UserRole = { id: string; name: string; color: string | null; iconUrl: string | null; description: string | null; displayOrder?: number }
+/**
+ * 引数 / 戻り値の型。フロントの `ParameterDef['type']` / `ReturnTypeDef['type']` と同じ語彙。
+ */
+export type ValueType = "string" | "number" | "boolean" | "object" | "array" | "void"
 /**
  * Vault 操作のエラー。
  * 
@@ -3128,6 +4513,15 @@ ok: boolean;
  * 失敗時の理由 (SSRF / timeout / DNS など)。secret は含まない。
  */
 error: string | null }
+export type VideoCompressCheck = { 
+/**
+ * ffmpeg が見つかったか
+ */
+available: boolean; 
+/**
+ * 動画で、かつ閾値を超えていて圧縮を勧めるか
+ */
+recommended: boolean }
 
 /** tauri-specta globals **/
 
@@ -12,11 +12,13 @@ import { useDeckStore } from '@/stores/deck'
 const ADDABLE_COLUMN_TYPES: readonly ColumnType[] = [
   // 引数なしで開けるシンプル系
   'timeline',
+  'mergedTimeline',
   'notifications',
   'mentions',
   'specified',
   'search',
   'favorites',
+  'polls',
   'drive',
   'gallery',
   'explore',
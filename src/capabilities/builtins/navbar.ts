@@ -22,11 +22,13 @@ import {
 
 const VALID_COLUMN_TYPES: readonly ColumnType[] = [
   'timeline',
+  'mergedTimeline',
   'notifications',
   'search',
   'list',
   'antenna',
   'favorites',
+  'polls',
   'clip',
   'user',
   'mentions',
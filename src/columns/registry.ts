@@ -179,6 +179,15 @@ export const COLUMN_REGISTRY: Record<ColumnType, ColumnSpec> = {
     defaultProps: { tl: 'home', name: null },
     component: () => import('@/components/deck/DeckTimelineColumn.vue'),
   },
+  mergedTimeline: {
+    label: '統合タイムライン',
+    icon: 'arrows-join',
+    group: 'account',
+    // ログイン中の全アカウントの tl を 1 本にまとめるので、アカウントは選ばない
+    accountIndependent: true,
+    defaultProps: { accountId: null, tl: 'home' },
+    component: () => import('@/components/deck/DeckMergedTimelineColumn.vue'),
+  },
   notifications: {
     label: '通知',
     icon: 'bell',
@@ -243,6 +252,12 @@ export const COLUMN_REGISTRY: Record<ColumnType, ColumnSpec> = {
     guestAllowed: true,
     component: () => import('@/components/deck/DeckFavoritesColumn.vue'),
   },
+  polls: {
+    label: 'アンケート',
+    icon: 'chart-bar',
+    group: 'account',
+    component: () => import('@/components/deck/DeckPollsColumn.vue'),
+  },
   clip: {
     label: 'クリップ',
     icon: 'paperclip',
@@ -0,0 +1,65 @@
+import type { CapabilityInfo, JsonValue } from '@/bindings'
+import { registerCapability } from '@/capabilities/registry'
+import type { ParameterDef, PermissionKey } from '@/capabilities/types'
+import { type Command, useCommandStore } from '@/commands/registry'
+import { commands, unwrap } from '@/utils/tauriInvoke'
+
+/**
+ * Rust 側 capability registry (`capability_registry.rs`) の定義を Command に
+ * 変換する。定義 (id / label / params) はバックエンドが持ち、execute は
+ * `capability_execute` を呼ぶだけ。
+ */
+export function toBackendCommand(info: CapabilityInfo): Command {
+  const params: Record<string, ParameterDef> = {}
+  for (const [name, p] of Object.entries(info.params)) {
+    // 引数に void は来ない (Rust 側の宣言で使わない)
+    if (!p || p.type === 'void') continue
+    params[name] = {
+      type: p.type,
+      description: p.description,
+      optional: p.optional,
+    }
+  }
+  const needsArgs = Object.values(params).some((p) => !p.optional)
+  return {
+    id: info.id,
+    label: info.label,
+    icon: info.icon,
+    category: info.category as Command['category'],
+    shortcuts: [],
+    // パレットからは引数なしで呼ぶので、必須引数を持つものは出さない
+    visible: info.palette && !needsArgs,
+    permissions: info.permissions as PermissionKey[],
+    aiTool: false,
+    signature: {
+      description: info.description,
+      params,
+      returns: {
+        type: info.returns.type,
+        description: info.returns.description ?? undefined,
+      },
+    },
+    execute: async (args) =>
+      unwrap(
+        await commands.capabilityExecute(
+          info.id,
+          (args ?? null) as unknown as JsonValue,
+        ),
+      ),
+  }
+}
+
+/**
+ * バックエンド定義の capability をフロントの registry (dispatcher /
+ * AiScript / slash) とコマンドパレットにミラー登録する。useDeckInit から
+ * 1 回呼ぶ。
+ */
+export async function loadBackendCapabilities(): Promise<void> {
+  const infos = await commands.capabilityList()
+  const commandStore = useCommandStore()
+  for (const info of infos) {
+    const cmd = toBackendCommand(info)
+    registerCapability(cmd)
+    commandStore.register(cmd)
+  }
+}
@@ -5,6 +5,8 @@ import type { DriveFolder, NormalizedDriveFile } from '@/adapters/types'
 const renameFolderMock = vi.fn()
 const deleteFolderMock = vi.fn()
 const renameFileMock = vi.fn()
+const editFileCommentMock = vi.fn()
+const toggleFileSensitiveMock = vi.fn()
 const deleteFileMock = vi.fn<() => Promise<boolean>>()
 
 vi.mock('@/composables/useDriveActions', () => ({
@@ -12,6 +14,8 @@ vi.mock('@/composables/useDriveActions', () => ({
     renameFolder: renameFolderMock,
     deleteFolder: deleteFolderMock,
     renameFile: renameFileMock,
+    editFileComment: editFileCommentMock,
+    toggleFileSensitive: toggleFileSensitiveMock,
     deleteFile: deleteFileMock,
   }),
 }))
@@ -98,9 +102,16 @@ afterEach(() => {
 })
 
 describe('DriveItemMenu (#792)', () => {
-  it('grid+file: 開く / リネーム / 移動 / 削除', () => {
+  it('grid+file: 開く / リネーム / キャプション / センシティブ / 移動 / 削除', () => {
     mountMenu({ kind: 'file', item: file, context: 'grid' })
-    expect(itemLabels()).toEqual(['開く', 'リネーム', '移動', '削除'])
+    expect(itemLabels()).toEqual([
+      '開く',
+      'リネーム',
+      'キャプション',
+      'センシティブとして設定',
+      '移動',
+      '削除',
+    ])
   })
 
   it('grid+folder: 開く / リネーム / 削除（移動なし）', () => {
@@ -108,9 +119,15 @@ describe('DriveItemMenu (#792)', () => {
     expect(itemLabels()).toEqual(['開く', 'リネーム', '削除'])
   })
 
-  it('detail+file: リネーム / 移動 / 削除（開くなし）', () => {
+  it('detail+file: リネーム / キャプション / センシティブ / 移動 / 削除（開くなし）', () => {
     mountMenu({ kind: 'file', item: file, context: 'detail' })
-    expect(itemLabels()).toEqual(['リネーム', '移動', '削除'])
+    expect(itemLabels()).toEqual([
+      'リネーム',
+      'キャプション',
+      'センシティブとして設定',
+      '移動',
+      '削除',
+    ])
   })
 
   it('削除項目は danger スタイル', () => {
@@ -138,6 +155,19 @@ describe('DriveItemMenu (#792)', () => {
     expect(renameFileMock).toHaveBeenCalledWith('acc1', file)
   })
 
+  it('「キャプション」で editFileComment を呼ぶ', () => {
+    mountMenu({ kind: 'file', item: file, context: 'grid' })
+    clickItem('キャプション')
+    expect(editFileCommentMock).toHaveBeenCalledWith('acc1', file)
+  })
+
+  it('センシティブ項目は現在の状態に応じた文言で toggleFileSensitive を呼ぶ', () => {
+    const sensitive = { ...file, isSensitive: true }
+    mountMenu({ kind: 'file', item: sensitive, context: 'grid' })
+    clickItem('センシティブを解除')
+    expect(toggleFileSensitiveMock).toHaveBeenCalledWith('acc1', sensitive)
+  })
+
   it('ファイル削除成功で deleted が emit される（失敗では emit されない）', async () => {
     deleteFileMock.mockResolvedValueOnce(true)
     const emitted = mountMenu({ kind: 'file', item: file, context: 'detail' })
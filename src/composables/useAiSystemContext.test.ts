@@ -380,6 +380,7 @@ describe('pickVisibleBlockTag', () => {
     'mentions',
     'channel',
     'favorites',
+    'polls',
     'clip',
     'user',
     'specified',
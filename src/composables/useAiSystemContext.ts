@@ -104,8 +104,8 @@ export const MAX_MEMOS = 20
  * カラム種別ごとに必要なフィールドだけ抽出する (循環参照と巨大 payload を回避)。
  * 未対応 / 不明な種別は最低限の id / name / type のみ抜き出す raw fallback。
  *
- * - timeline / list / antenna / mentions / channel / favorites / clip /
- *   user / specified / search / role / chat → ノート projection
+ * - timeline / list / antenna / mentions / channel / favorites / polls /
+ *   clip / user / specified / search / role / chat → ノート projection
  *   (text を `[CW: <reason>]` に置換、user.username 抽出)
  * - notifications → 通知 projection (type / userId / noteId / reaction)
  * - drive → ドライブファイル projection (name / type / size)
@@ -128,6 +128,7 @@ const NOTE_LIKE_COLUMN_TYPES: ReadonlySet<string> = new Set([
   'mentions',
   'channel',
   'favorites',
+  'polls',
   'clip',
   'user',
   'specified',
@@ -2,6 +2,11 @@ import { nextTick, type Ref, ref } from 'vue'
 import type { NormalizedUser, ServerEmoji } from '@/adapters/types'
 import { useEmojisStore } from '@/stores/emojis'
 import { getCaretCoordinates } from '@/utils/caretPosition'
+import { searchCustomEmojis } from '@/utils/emojiSearch'
+import {
+  type FrequentUser,
+  rankMentionCandidates,
+} from '@/utils/mentionSuggestions'
 import { commands, unwrap } from '@/utils/tauriInvoke'
 
 /** ポップアップの想定幅 (px)。テキストエリア右端でのはみ出しクランプに使う */
@@ -43,6 +48,25 @@ export interface AutocompleteState {
 
 export type AutocompleteCandidate = ServerEmoji | NormalizedUser | string
 
+// よくリプライする相手はセッション中ほぼ変わらないので、アカウントごとに 1 回だけ取る
+const frequentUsersCache = new Map<string, Promise<FrequentUser[]>>()
+
+function getFrequentUsers(accountId: string): Promise<FrequentUser[]> {
+  let pending = frequentUsersCache.get(accountId)
+  if (!pending) {
+    pending = commands
+      .apiGetFrequentlyRepliedUsers(accountId, null, 20)
+      .then((r) => unwrap(r) as FrequentUser[])
+      .catch(() => {
+        // 失敗したら次の入力で取り直す
+        frequentUsersCache.delete(accountId)
+        return []
+      })
+    frequentUsersCache.set(accountId, pending)
+  }
+  return pending
+}
+
 export function useAutocomplete(
   text: Ref<string>,
   textareaRef: Ref<HTMLTextAreaElement | null>,
@@ -102,45 +126,11 @@ export function useAutocomplete(
     return null
   }
 
-  function searchEmoji(query: string) {
-    const q = query.toLowerCase()
+  async function searchEmoji(query: string): Promise<ServerEmoji[]> {
     const allEmojis = emojisStore.getEmojiList(serverHost.value)
-    const results: ServerEmoji[] = []
-    const seen = new Set<string>()
-
-    // Stage 1: exact match
-    for (const e of allEmojis) {
-      if (e.name === q) {
-        seen.add(e.name)
-        results.push(e)
-      }
-    }
-
-    // Stage 2: startsWith
-    if (results.length < 10) {
-      for (const e of allEmojis) {
-        if (seen.has(e.name)) continue
-        if (e.name.startsWith(q) || e.aliases.some((a) => a.startsWith(q))) {
-          seen.add(e.name)
-          results.push(e)
-          if (results.length >= 10) break
-        }
-      }
-    }
-
-    // Stage 3: includes
-    if (results.length < 10) {
-      for (const e of allEmojis) {
-        if (seen.has(e.name)) continue
-        if (e.name.includes(q) || e.aliases.some((a) => a.includes(q))) {
-          seen.add(e.name)
-          results.push(e)
-          if (results.length >= 10) break
-        }
-      }
-    }
-
-    return results
+    // `:` だけの段階では先頭から候補を出す
+    if (!query) return allEmojis.slice(0, 10)
+    return searchCustomEmojis(activeAccountId.value, allEmojis, query, 10)
   }
 
   function searchMfm(query: string): string[] {
@@ -150,14 +140,19 @@ export function useAutocomplete(
   }
 
   async function searchMention(query: string) {
-    if (!activeAccountId.value) return []
+    const accountId = activeAccountId.value
+    if (!accountId) return []
+    const frequent = await getFrequentUsers(accountId)
+    if (!query) return rankMentionCandidates(query, frequent, [])
+    let searched: NormalizedUser[] = []
     try {
-      return unwrap(
-        await commands.apiSearchUsersByQuery(activeAccountId.value, query, 10),
+      searched = unwrap(
+        await commands.apiSearchUsersByQuery(accountId, query, 10),
       ) as unknown as NormalizedUser[]
     } catch {
-      return []
+      // 検索に失敗しても、よくリプライする相手の候補は出す
     }
+    return rankMentionCandidates(query, frequent, searched)
   }
 
   async function searchHashtag(query: string) {
@@ -177,8 +172,14 @@ export function useAutocomplete(
     if (!textarea) return
 
     const trigger = detectTrigger(text.value, textarea.selectionStart)
-    // $[ triggers with empty query (show all MFM functions), others need at least 1 char
-    if (!trigger || (trigger.type !== '$[' && trigger.query.length === 0)) {
+    // $[ triggers with empty query (show all MFM functions), @ with empty query
+    // (show users you reply to most), others need at least 1 char
+    if (
+      !trigger ||
+      (trigger.type !== '$[' &&
+        trigger.type !== '@' &&
+        trigger.query.length === 0)
+    ) {
       autocompleteState.value = null
       candidates.value = []
       popupPosition.value = null
@@ -210,11 +211,14 @@ export function useAutocomplete(
     if (debounceTimer) clearTimeout(debounceTimer)
 
     if (trigger.type === ':') {
-      // Local search, no debounce needed
-      candidates.value = searchEmoji(trigger.query)
-      if (candidates.value.length === 0) {
-        autocompleteState.value = null
-      }
+      // Rust 側の絵文字キャッシュで引く (debounce 不要)
+      void searchEmoji(trigger.query).then((results) => {
+        if (autocompleteState.value?.query !== trigger.query) return
+        candidates.value = results
+        if (results.length === 0) {
+          autocompleteState.value = null
+        }
+      })
     } else if (trigger.type === '$[') {
       // Local search for MFM functions
       candidates.value = searchMfm(trigger.query)
@@ -1,6 +1,7 @@
 import type { Ref } from 'vue'
 import { onMounted, onUnmounted, watch } from 'vue'
 import { commands, events } from '@/bindings'
+import { loadBackendCapabilities } from '@/commands/backendCapabilities'
 import { loadCliCommands } from '@/commands/cliParser'
 import {
   registerDefaultCommands,
@@ -17,7 +18,7 @@ import { handleDeepLink } from '@/composables/useDeepLink'
 import { initOgpListener } from '@/composables/useOgpPreview'
 import { destroyApiBridge, initApiBridge } from '@/core/apiBridge'
 import { reattachQueryDeltaListener } from '@/core/queryDeltaBus'
-import { useDeckStore } from '@/stores/deck'
+import { type DeckColumn, useDeckStore } from '@/stores/deck'
 import { useOfflineModeStore } from '@/stores/offlineMode'
 import { usePluginsStore } from '@/stores/plugins'
 import { usePostFormStore } from '@/stores/postForm'
@@ -29,6 +30,25 @@ import {
   onNotificationAction,
 } from '@/utils/desktopNotification'
 
+/**
+ * ユーザーカラムのアバター / バナーを Rust 側の画像キャッシュへ先読みする
+ * (avatar_warm.rs)。アカウント自身の分は Rust が起動時に温める。
+ */
+function warmPinnedUserImages(columns: DeckColumn[]) {
+  const byAccount = new Map<string, Set<string>>()
+  for (const column of columns) {
+    if (column.type !== 'user' || !column.accountId || !column.userId) continue
+    const ids = byAccount.get(column.accountId) ?? new Set<string>()
+    ids.add(column.userId)
+    byAccount.set(column.accountId, ids)
+  }
+  for (const [accountId, ids] of byAccount) {
+    void commands.warmUserImages(accountId, [...ids]).catch(() => {
+      // Non-Tauri environment (vitest / ブラウザ)
+    })
+  }
+}
+
 export function useDeckInit(options: {
   openCompose: () => void
   navigateToSearch: () => void
@@ -110,8 +130,10 @@ export function useDeckInit(options: {
       initDesktopNotifications()
       initOgpListener()
       loadCliCommands()
+      void loadBackendCapabilities()
       startTaskCommandSync()
       void useTasksStore().init()
+      warmPinnedUserImages(deckStore.columns)
       // OS 通知クリックの遷移先解決 (#754)。noteId 優先、なければ userId。
       // どちらもない (要約通知・システム通知) 場合はフォーカスのみで何もしない。
       const navigateFromNotification = (ctx: {
@@ -197,6 +219,26 @@ export function useDeckInit(options: {
         listenTauri('nd:toggle-realtime-mode', () => {
           useRealtimeModeStore().toggle()
         })
+        listenTauri('nd:idle-changed', (status) => {
+          useRealtimeModeStore().setIdleAway(status.away)
+        })
+        // トレイのアカウント別メニュー: 既存の通知カラムがあればそこへ、
+        // 無ければ追加する
+        listenTauri('nd:tray-open-notifications', (accountId) => {
+          const existing = deckStore.columns.find(
+            (c) => c.type === 'notifications' && c.accountId === accountId,
+          )
+          if (existing) {
+            deckStore.setActiveColumn(existing.id)
+          } else {
+            deckStore.addColumn({
+              type: 'notifications',
+              name: null,
+              width: 360,
+              accountId,
+            })
+          }
+        })
       })
 
       // Cross-window event listeners (all windows listen for IPC events)
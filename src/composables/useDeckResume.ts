@@ -1,11 +1,13 @@
 import { onMounted, onUnmounted } from 'vue'
 import { useUiStore } from '@/stores/ui'
+import { isTauri } from '@/utils/settingsFs'
 import { startSleepDetector } from '@/utils/sleepDetector'
+import { listenTauri } from '@/utils/tauriEvents'
 
 /**
  * デッキ復帰シグナル (deckResumeSignal) の発生源を一元管理する。
  *
- * 「復帰」は物理的に異なる 3 事象から起きるため検知は 3 系統あるが、
+ * 「復帰」は物理的に異なる 4 事象から起きるため検知は 5 系統あるが、
  * すべて同一の emitDeckResume() に合流し、下流 (ストリーム再接続 /
  * catch-up / タイマー掃除) は冪等なので多重発火は無害。
  *
@@ -16,10 +18,16 @@ import { startSleepDetector } from '@/utils/sleepDetector'
  * 3. nd-app-resumed — Android ネイティブ (MainActivity.onResume) (#506)。
  *    pin 済み tauri-runtime-wry 2.10 が Event::Resumed を握り潰す間の
  *    暫定経路で、windowing 層の pin (#678) 解除後に削除できる
+ * 4. nd:system-resumed — Rust (power.rs) の OS スリープ復帰検知。2 より早く
+ *    届き、Rust 側でストリームを作り直した後に発火するので catch-up だけ担う
+ * 5. nd:network-changed (online) — Rust (network.rs) の回線復旧検知。
+ *    オフライン中に退避したストリームは Rust 側で戻し済み
  */
 export function useDeckResume() {
   const uiStore = useUiStore()
   let stopSleepDetector: (() => void) | null = null
+  let unlistenSystemResumed: (() => void) | null = null
+  let unlistenNetworkChanged: (() => void) | null = null
 
   function onVisibilityChange() {
     if (!document.hidden) uiStore.emitDeckResume()
@@ -37,11 +45,25 @@ export function useDeckResume() {
       // visibilitychange に任せる
       if (!document.hidden) uiStore.emitDeckResume()
     })
+    if (isTauri) {
+      void listenTauri('nd:system-resumed', () => {
+        uiStore.emitDeckResume()
+      }).then((fn) => {
+        unlistenSystemResumed = fn
+      })
+      void listenTauri('nd:network-changed', (status) => {
+        if (status.online) uiStore.emitDeckResume()
+      }).then((fn) => {
+        unlistenNetworkChanged = fn
+      })
+    }
   })
 
   onUnmounted(() => {
     document.removeEventListener('visibilitychange', onVisibilityChange)
     window.removeEventListener('nd-app-resumed', onNativeResume)
     stopSleepDetector?.()
+    unlistenSystemResumed?.()
+    unlistenNetworkChanged?.()
   })
 }
@@ -21,6 +21,7 @@ import { useWindowsStore } from '@/stores/windows'
  *   notedeck://<host>/list/<listId>
  *   notedeck://<host>/antenna/<antennaId>
  *   notedeck://<host>/favorites
+ *   notedeck://<host>/polls
  *   notedeck://<host>/clip/<clipId>
  *   notedeck://<host>/channel/<channelId>
  *   notedeck://<host>/mentions
@@ -131,6 +132,10 @@ export async function handleDeepLink(rawUrl: string): Promise<void> {
       handleAddColumn('favorites', accountId)
       break
 
+    case 'polls':
+      handleAddColumn('polls', accountId)
+      break
+
     case 'clip':
       if (rest[0] && accountId) {
         handleOpenWindow('clip-detail', { accountId, clipId: rest[0] })
@@ -159,6 +159,7 @@ describe('useDriveActions (#792)', () => {
     const actions = useDriveActions()
     expect(await actions.createFolder(null, null)).toBeNull()
     await actions.renameFile(undefined, makeFile('f1'))
+    await actions.editFileComment(null, makeFile('f1'))
     expect(await actions.moveFiles(null, ['f1'], null)).toBe(false)
     expect(await actions.deleteFile(null, makeFile('f1'))).toBe(false)
     expect(promptMock).not.toHaveBeenCalled()
@@ -190,6 +191,55 @@ describe('useDriveActions (#792)', () => {
     expect(uiStore.driveFilesChanged.accountId).toBe('acc1')
   })
 
+  it('editFileComment: 変更なし・キャンセルでは API を呼ばず、空文字はクリアとして送る', async () => {
+    const actions = useDriveActions()
+    const uiStore = useUiStore()
+    const file = { ...makeFile('f1'), comment: '既存の説明' }
+    promptMock.mockResolvedValueOnce('既存の説明')
+    await actions.editFileComment('acc1', file)
+    promptMock.mockResolvedValueOnce(null)
+    await actions.editFileComment('acc1', file)
+    expect(mocked.apiUpdateDriveFile).not.toHaveBeenCalled()
+
+    promptMock.mockResolvedValueOnce('')
+    mocked.apiUpdateDriveFile.mockResolvedValueOnce(ok())
+    await actions.editFileComment('acc1', file)
+    expect(mocked.apiUpdateDriveFile).toHaveBeenCalledWith(
+      'acc1',
+      'f1',
+      null,
+      '',
+      null,
+    )
+    expect(uiStore.driveFilesChanged.accountId).toBe('acc1')
+  })
+
+  it('toggleFileSensitive: 現在値を反転して送り、失敗は toast', async () => {
+    const actions = useDriveActions()
+    mocked.apiUpdateDriveFile.mockResolvedValueOnce(ok())
+    await actions.toggleFileSensitive('acc1', makeFile('f1'))
+    expect(mocked.apiUpdateDriveFile).toHaveBeenCalledWith(
+      'acc1',
+      'f1',
+      null,
+      null,
+      true,
+    )
+    mocked.apiUpdateDriveFile.mockResolvedValueOnce(apiError('NO_SUCH_FILE'))
+    await actions.toggleFileSensitive('acc1', {
+      ...makeFile('f2'),
+      isSensitive: true,
+    })
+    expect(mocked.apiUpdateDriveFile).toHaveBeenLastCalledWith(
+      'acc1',
+      'f2',
+      null,
+      null,
+      false,
+    )
+    expect(toastShowMock).toHaveBeenCalled()
+  })
+
   it('deleteFile: 成功で true、失敗で toast + false', async () => {
     const actions = useDriveActions()
     confirmMock.mockResolvedValue(true)
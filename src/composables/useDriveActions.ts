@@ -115,6 +115,63 @@ export function useDriveActions() {
     }
   }
 
+  /**
+   * キャプション (alt テキスト) の編集。アップロード済みファイルにも後から
+   * 説明を付けられる。空にすると comment を null に戻す。
+   */
+  async function editFileComment(
+    accountId: string | null | undefined,
+    file: NormalizedDriveFile,
+  ): Promise<void> {
+    if (!accountId) return
+    const caption = await prompt({
+      title: 'キャプション',
+      message: '視覚に障害のあるユーザーなどに向けたファイルの説明を設定できます',
+      placeholder: 'ファイルの説明',
+      defaultValue: file.comment ?? '',
+      multiline: true,
+      allowEmpty: true,
+    })
+    if (caption === null || caption === (file.comment ?? '')) return
+    try {
+      // Rust 側の契約: 空文字 = alt クリア
+      unwrap(
+        await commands.apiUpdateDriveFile(
+          accountId,
+          file.id,
+          null,
+          caption,
+          null,
+        ),
+      )
+      uiStore.emitDriveFilesChanged(accountId)
+    } catch (e) {
+      toast.show(errorMessage(e), 'error')
+    }
+  }
+
+  /** センシティブ指定の切り替え。再アップロードせずに NSFW 指定を直せる */
+  async function toggleFileSensitive(
+    accountId: string | null | undefined,
+    file: NormalizedDriveFile,
+  ): Promise<void> {
+    if (!accountId) return
+    try {
+      unwrap(
+        await commands.apiUpdateDriveFile(
+          accountId,
+          file.id,
+          null,
+          null,
+          !file.isSensitive,
+        ),
+      )
+      uiStore.emitDriveFilesChanged(accountId)
+    } catch (e) {
+      toast.show(errorMessage(e), 'error')
+    }
+  }
+
   /**
    * move-bulk（100 件ずつチャンク）。1 チャンク内は上流実装が単一 UPDATE のため
    * 部分成功は存在しない。途中チャンク失敗は中断 + エラー表示、成功可否にかかわらず
@@ -168,6 +225,8 @@ export function useDriveActions() {
     renameFolder,
     deleteFolder,
     renameFile,
+    editFileComment,
+    toggleFileSensitive,
     moveFiles,
     deleteFile,
   }
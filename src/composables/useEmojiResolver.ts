@@ -19,7 +19,7 @@ export function useEmojiResolver() {
       reactionEmojis[shortcode] ||
       reactionEmojis[base] ||
       reactionEmojis[withDot] ||
-      emojisStore.resolve(serverHost, base)
+      emojisStore.resolveOrRequest(serverHost, base)
     )
   }
 
@@ -18,15 +18,30 @@ function makeDriveFile(id: string): NormalizedDriveFile {
   }
 }
 
-function setup(mocks: { uploadFile?: unknown; uploadFileFromPath?: unknown }) {
+function setup(
+  mocks: {
+    uploadFile?: unknown
+    uploadFileFromPath?: unknown
+    shouldCompressVideo?: unknown
+    uploadVideoCompressed?: unknown
+  },
+  thresholdBytes: number | null = null,
+) {
   const adapter = {
     api: {
       uploadFile: mocks.uploadFile ?? vi.fn(),
       uploadFileFromPath: mocks.uploadFileFromPath ?? vi.fn(),
+      shouldCompressVideo: mocks.shouldCompressVideo,
+      uploadVideoCompressed: mocks.uploadVideoCompressed,
     },
   } as unknown as ServerAdapter
   const error = { value: null as string | null }
-  return { attachment: useFileAttachment(() => adapter, error), error }
+  return {
+    attachment: useFileAttachment(() => adapter, error, {
+      videoCompressThresholdBytes: () => thresholdBytes,
+    }),
+    error,
+  }
 }
 
 function makeFile(name: string, bytes: number[] = [1]): File {
@@ -93,6 +108,52 @@ describe('ファイル別アップロード進捗 (#753)', () => {
   })
 })
 
+describe('動画の事前圧縮', () => {
+  it('閾値を超える動画は圧縮経路でアップロードし、進捗をエントリに載せる', async () => {
+    const uploadFileFromPath = vi.fn()
+    const shouldCompressVideo = vi.fn().mockResolvedValue(true)
+    let progressDuringUpload: number | undefined
+    const { attachment } = setup(
+      {
+        uploadFileFromPath,
+        shouldCompressVideo,
+        uploadVideoCompressed: vi
+          .fn()
+          .mockImplementation(
+            async (_path: string, onProgress: (r: number | null) => void) => {
+              onProgress(0.5)
+              progressDuringUpload =
+                attachment.pendingUploads.value[0]?.progress
+              return makeDriveFile('v1')
+            },
+          ),
+      },
+      50 * 1024 * 1024,
+    )
+
+    await attachment.uploadFilesFromPaths(['/tmp/clip.mov'])
+
+    expect(shouldCompressVideo).toHaveBeenCalledWith(
+      '/tmp/clip.mov',
+      50 * 1024 * 1024,
+    )
+    expect(progressDuringUpload).toBe(0.5)
+    expect(uploadFileFromPath).not.toHaveBeenCalled()
+    expect(attachment.attachedFiles.value.map((f) => f.id)).toEqual(['v1'])
+  })
+
+  it('閾値が null なら圧縮を確認せず通常アップロードする', async () => {
+    const uploadFileFromPath = vi.fn().mockResolvedValue(makeDriveFile('v2'))
+    const shouldCompressVideo = vi.fn().mockResolvedValue(true)
+    const { attachment } = setup({ uploadFileFromPath, shouldCompressVideo })
+
+    await attachment.uploadFilesFromPaths(['/tmp/clip.mov'])
+
+    expect(shouldCompressVideo).not.toHaveBeenCalled()
+    expect(uploadFileFromPath).toHaveBeenCalledWith('/tmp/clip.mov')
+  })
+})
+
 describe('並べ替えとメタ更新 (#753)', () => {
   it('reorderFiles でドラッグ位置へ移動できる (範囲外は no-op)', () => {
     const { attachment } = setup({})
@@ -12,6 +12,8 @@ export interface PendingUpload {
   name: string
   status: 'uploading' | 'error'
   error?: string
+  /** 動画の事前圧縮中の進捗 (0〜1)。圧縮しない / 総量不明なら undefined */
+  progress?: number
   source: { kind: 'path'; path: string } | { kind: 'browser'; file: File }
 }
 
@@ -20,6 +22,10 @@ let uploadKeyCounter = 0
 export function useFileAttachment(
   getAdapter: () => ServerAdapter | null,
   error: { value: string | null },
+  options: {
+    /** これを超える動画は ffmpeg で圧縮してから送る。null で圧縮しない */
+    videoCompressThresholdBytes?: () => number | null
+  } = {},
 ) {
   const attachedFiles = ref<NormalizedDriveFile[]>([])
   const pendingUploads = ref<PendingUpload[]>([])
@@ -27,6 +33,28 @@ export function useFileAttachment(
     pendingUploads.value.some((p) => p.status === 'uploading'),
   )
 
+  function setProgress(key: string, progress: number | null) {
+    pendingUploads.value = pendingUploads.value.map((p) =>
+      p.key === key ? { ...p, progress: progress ?? undefined } : p,
+    )
+  }
+
+  async function uploadPath(adapter: ServerAdapter, key: string, path: string) {
+    const threshold = options.videoCompressThresholdBytes?.() ?? null
+    const { shouldCompressVideo, uploadVideoCompressed } = adapter.api
+    if (
+      threshold !== null &&
+      shouldCompressVideo &&
+      uploadVideoCompressed &&
+      (await shouldCompressVideo.call(adapter.api, path, threshold))
+    ) {
+      return uploadVideoCompressed.call(adapter.api, path, (ratio) =>
+        setProgress(key, ratio),
+      )
+    }
+    return adapter.api.uploadFileFromPath(path)
+  }
+
   async function runUpload(entry: PendingUpload) {
     const adapter = getAdapter()
     if (!adapter) {
@@ -38,7 +66,7 @@ export function useFileAttachment(
     try {
       const uploaded =
         entry.source.kind === 'path'
-          ? await adapter.api.uploadFileFromPath(entry.source.path)
+          ? await uploadPath(adapter, entry.key, entry.source.path)
           : await adapter.api.uploadFile(
               entry.source.file.name,
               [...new Uint8Array(await entry.source.file.arrayBuffer())],
@@ -95,7 +123,12 @@ export function useFileAttachment(
     if (entry?.status !== 'error') return
     pendingUploads.value = pendingUploads.value.map((p) =>
       p.key === key
-        ? { ...p, status: 'uploading' as const, error: undefined }
+        ? {
+            ...p,
+            status: 'uploading' as const,
+            error: undefined,
+            progress: undefined,
+          }
         : p,
     )
     const retrying = pendingUploads.value.find((p) => p.key === key)
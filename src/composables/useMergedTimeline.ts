@@ -0,0 +1,196 @@
+import { computed, onMounted, onUnmounted, type Ref, shallowRef } from 'vue'
+import type { NormalizedNote, TimelineType } from '@/adapters/types'
+import { events, type MergedSource } from '@/bindings'
+import { useMultiAccountAdapters } from '@/composables/useMultiAccountAdapters'
+import { useNoteScrollerRef } from '@/composables/useNoteScrollerRef'
+import { useNoteVisibility } from '@/composables/useNoteVisibility'
+import { useAccountsStore } from '@/stores/accounts'
+import { useNoteStore } from '@/stores/notes'
+import { AppError } from '@/utils/errors'
+import { commands, unwrap } from '@/utils/tauriInvoke'
+
+export interface MergedTimelineOptions {
+  /** 各アカウントで統合するタイムライン種別 */
+  timelineType: () => TimelineType
+
+  /** Loading / error / scroller refs from useColumnSetup */
+  isLoading: Ref<boolean>
+  error: Ref<AppError | null>
+  scroller: Ref<HTMLElement | null>
+  onScrollReport: () => void
+}
+
+/**
+ * ログイン中の全アカウントの同じタイムラインを 1 本にまとめる
+ * (`merged_timeline.rs`)。連合経由で各サーバーに届いた同じノートの重複除去と
+ * 時系列の並べ替えはバックエンドが行う。過去方向はソースごとのカーソルで遡り、
+ * 新着は `MergedTimelineDelta` で先頭に積む。
+ */
+export function useMergedTimeline(options: MergedTimelineOptions) {
+  const { timelineType, isLoading, error, scroller, onScrollReport } = options
+
+  const accountsStore = useAccountsStore()
+  const multiAdapters = useMultiAccountAdapters()
+  const noteStore = useNoteStore()
+  const { isHidden } = useNoteVisibility()
+
+  // 表示用 notes はミュート/削除を表示時に除外（useCrossAccountNotes と同じ）
+  const rawNotes = shallowRef<NormalizedNote[]>([])
+  const notes = computed(() => rawNotes.value.filter((n) => !isHidden(n)))
+  const { noteScrollerRef } = useNoteScrollerRef(scroller)
+
+  let sources: MergedSource[] = []
+  let cursors: (string | null)[] = []
+  let mergedId: string | null = null
+  let exhausted = false
+  let disposed = false
+  let unlistenDelta: (() => void) | null = null
+
+  function scrollToTop() {
+    if (noteScrollerRef.value) {
+      noteScrollerRef.value.scrollToIndex(0, {
+        align: 'start',
+        behavior: 'smooth',
+      })
+    } else {
+      scroller.value?.scrollTo({ top: 0, behavior: 'smooth' })
+    }
+  }
+
+  /**
+   * 取得したノートはバックエンドで merged feed の既出扱いになり、
+   * 後から stream で届く別サーバーのコピーが落ちる
+   */
+  async function fetchPage(): Promise<NormalizedNote[]> {
+    const page = unwrap(
+      await commands.apiGetMergedTimeline(sources, cursors, null, mergedId),
+    )
+    cursors = page.cursors
+    exhausted = page.notes.length === 0
+    return page.notes as unknown as NormalizedNote[]
+  }
+
+  async function close() {
+    unlistenDelta?.()
+    unlistenDelta = null
+    if (!mergedId) return
+    const id = mergedId
+    mergedId = null
+    await commands.mergedTimelineClose(id).catch((e) => {
+      console.warn('[merged-timeline] close failed:', e)
+    })
+  }
+
+  async function refetchLatest() {
+    cursors = []
+    try {
+      rawNotes.value = await fetchPage()
+    } catch (e) {
+      error.value = AppError.from(e)
+    }
+  }
+
+  async function listen() {
+    unlistenDelta = await events.mergedTimelineDelta.listen(({ payload }) => {
+      if (payload.mergedId !== mergedId) return
+      // バッファ溢れで取りこぼした分は最新ページの取り直しで埋める
+      if (payload.gap) {
+        refetchLatest()
+        return
+      }
+      const deleted = new Set(payload.deletes)
+      // inserts は届いた順なので、新しい順に直して先頭へ
+      const inserts = [
+        ...(payload.inserts as unknown as NormalizedNote[]),
+      ].reverse()
+      rawNotes.value = [...inserts, ...rawNotes.value].filter(
+        (n) => !deleted.has(n.id),
+      )
+    })
+  }
+
+  async function connect() {
+    await close()
+    error.value = null
+    sources = accountsStore.accounts
+      .filter((a) => a.hasToken)
+      .map((a) => ({
+        accountId: a.id,
+        timelineType: timelineType(),
+        listId: null,
+      }))
+    cursors = []
+    exhausted = false
+    if (sources.length === 0) {
+      rawNotes.value = []
+      return
+    }
+
+    isLoading.value = true
+    try {
+      await listen()
+      const snapshot = unwrap(await commands.mergedTimelineOpen(sources))
+      mergedId = snapshot.mergedId
+      // 開いている間にアンマウントされたら即座に閉じる
+      if (disposed) {
+        await close()
+        return
+      }
+      rawNotes.value = await fetchPage()
+    } catch (e) {
+      error.value = AppError.from(e)
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  async function loadMore() {
+    if (isLoading.value || exhausted || rawNotes.value.length === 0) return
+    isLoading.value = true
+    try {
+      const existingIds = new Set(rawNotes.value.map((n) => n.id))
+      const older = await fetchPage()
+      rawNotes.value = [
+        ...rawNotes.value,
+        ...older.filter((n) => !existingIds.has(n.id)),
+      ]
+    } catch (e) {
+      error.value = AppError.from(e)
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  function handleScroll() {
+    onScrollReport()
+  }
+
+  async function removeNote(note: NormalizedNote) {
+    const adapter = await multiAdapters.getOrCreate(note._accountId)
+    if (!adapter) return
+    try {
+      await adapter.api.deleteNote(note.id)
+    } catch {
+      return
+    }
+    rawNotes.value = rawNotes.value.filter((n) => n.id !== note.id)
+    noteStore.remove(note.id)
+  }
+
+  onMounted(connect)
+
+  onUnmounted(() => {
+    disposed = true
+    close()
+  })
+
+  return {
+    notes,
+    noteScrollerRef,
+    scrollToTop,
+    connect,
+    loadMore,
+    handleScroll,
+    removeNote,
+  }
+}
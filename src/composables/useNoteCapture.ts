@@ -1,7 +1,7 @@
 import { onUnmounted } from 'vue'
 import type {
   NormalizedNote,
-  NoteUpdateEvent,
+  NotePatchEvent,
   StreamAdapter,
 } from '@/adapters/types'
 import { usePerformanceStore } from '@/stores/performance'
@@ -12,9 +12,10 @@ import { usePerformanceStore } from '@/stores/performance'
  *
  * Used by ALL note columns — including streaming ones — to ensure reaction
  * freshness even when the timeline channel subscription is suspended (off-screen
- * >8s). Channel auto-capture and per-note capture can fire the same noteUpdated
- * event; `noteStore.applyUpdate` dedupes by (type × userId × reaction × choice)
- * within a 1.5s window.
+ * >8s). Updates arrive folded into one `NotePatchEvent` per note per flush
+ * window. Channel auto-capture and per-note capture can fire the same noteUpdated
+ * event; the Rust QueryRuntime dedupes by (type × userId × reaction × choice)
+ * within a 1.5s window before folding.
  *
  * Call `sync(notes)` explicitly when notes are added/removed (connect, loadMore,
  * onResume). Do NOT call on reaction/poll updates — those don't change
@@ -22,7 +23,7 @@ import { usePerformanceStore } from '@/stores/performance'
  */
 export function useNoteCapture(
   getStream: () => StreamAdapter | undefined,
-  onUpdate: (event: NoteUpdateEvent) => void,
+  onUpdate: (patch: NotePatchEvent) => void,
 ) {
   const perfStore = usePerformanceStore()
   const capturedIds = new Set<string>()
@@ -14,6 +14,7 @@ import type {
   NoteUpdateEvent,
   ServerAdapter,
 } from '@/adapters/types'
+import type { TimelineGap, TimelineType } from '@/bindings'
 import { useColumnLive } from '@/composables/useColumnMount'
 import { useColumnSetup } from '@/composables/useColumnSetup'
 import { useNavigation } from '@/composables/useNavigation'
@@ -41,6 +42,7 @@ import { dedup } from '@/utils/dedup'
 import { AppError } from '@/utils/errors'
 import { logWarn } from '@/utils/logger'
 import { insertIntoSorted } from '@/utils/sortNotes'
+import { commands, unwrap } from '@/utils/tauriInvoke'
 
 export interface NoteColumnConfig {
   getColumn: () => DeckColumnType
@@ -48,15 +50,34 @@ export interface NoteColumnConfig {
     adapter: ServerAdapter,
     opts: { sinceId?: string; untilId?: string },
   ) => Promise<NormalizedNote[]>
+  /**
+   * 次ページ (`fetch` に `untilId` を渡したときと同じ要求) の先読み。
+   * 末尾に近づいた時点で呼ばれ、結果は後の loadMore が Rust 側から受け取る。
+   */
+  prefetch?: (adapter: ServerAdapter, opts: { untilId: string }) => void
   validate?: () => boolean
   cache?: {
     getKey: () => string | null
   }
+  /**
+   * キャッシュの欠け (gap) を表示して穴埋めする Rust 側のタイムライン
+   * (`timelineGaps` / `fillGap` に渡す。`cache.getKey()` と同じ TL を指す)。
+   */
+  gaps?: {
+    getTimeline: () => {
+      timelineType: TimelineType
+      listId: string | null
+    } | null
+  }
   streaming?: {
     subscribe: (
       adapter: ServerAdapter,
       enqueue: (n: NormalizedNote) => void,
-      callbacks: { onNoteUpdated: (event: NoteUpdateEvent) => void },
+      callbacks: {
+        onNoteUpdated: (event: NoteUpdateEvent) => void
+        /** stream バッファ溢れでノートが欠けた。最新ページを取り直す */
+        onGap: () => void
+      },
     ) => ChannelSubscription
   }
   refreshFetch?: (
@@ -118,6 +139,7 @@ export function useNoteColumn(config: NoteColumnConfig) {
     mergeUpdate,
     setOnNotesChanged,
     onNoteUpdate,
+    onNotePatch,
     handlePosted,
     removeNote,
     removingIds,
@@ -158,11 +180,11 @@ export function useNoteColumn(config: NoteColumnConfig) {
   // Note Capture (subNote/unsubNote) を常に有効にする。
   // streaming カラムでも併用することで、channel subscription が suspend
   // (不可視 8s 経過) されている間も可視ノートの reaction が個別 subNote
-  // 経由で届く。channel と capture の二重発火は noteStore.applyUpdate の
+  // 経由で届く。channel と capture の二重発火は Rust 側 QueryRuntime の
   // dedup (noteId × event sig × 1.5s) で吸収される。
   const { sync: syncNoteCapture } = useNoteCapture(
     () => getAdapter()?.stream,
-    onNoteUpdate,
+    onNotePatch,
   )
   setOnNotesChanged(syncNoteCapture)
 
@@ -225,6 +247,92 @@ export function useNoteColumn(config: NoteColumnConfig) {
   /** True when API is unreachable and displaying cached notes */
   const isOffline = ref(false)
 
+  /** キャッシュ上の gap (Rust 側 timeline_gaps.rs が記録、新しい順) */
+  const gaps = ref<TimelineGap[]>([])
+  /** 穴埋め中の gap の newerId */
+  const fillingGapId = ref<string | null>(null)
+
+  /**
+   * gap の新しい側のノート ID → gap。直後に古い側のノートが並んでいる
+   * (= 表示上で離れた時間帯が隣り合っている) ものだけ。末尾の gap は通常の
+   * 遡り読み込みで埋まるので出さない。
+   */
+  const gapAfter = computed(() => {
+    const map = new Map<string, TimelineGap>()
+    if (gaps.value.length === 0) return map
+    const ids = notes.value.map((n) => n.id)
+    for (const gap of gaps.value) {
+      const index = ids.indexOf(gap.newerId)
+      if (index >= 0 && index < ids.length - 1) map.set(gap.newerId, gap)
+    }
+    return map
+  })
+
+  async function refreshGaps() {
+    const accountId = config.getColumn().accountId
+    const target = config.gaps?.getTimeline()
+    if (!accountId || !target) {
+      gaps.value = []
+      return
+    }
+    const stillCurrent = tabGuard()
+    try {
+      const result = unwrap(
+        await commands.timelineGaps(
+          accountId,
+          target.timelineType,
+          target.listId,
+        ),
+      )
+      if (stillCurrent()) gaps.value = result
+    } catch (e) {
+      logWarn('timeline-gaps', e)
+    }
+  }
+
+  /** `newerId` の直後の gap を 1 ページ分取って埋める (取り切れなければ縮む) */
+  async function fillGap(newerId: string) {
+    const accountId = config.getColumn().accountId
+    const target = config.gaps?.getTimeline()
+    if (!accountId || !target || fillingGapId.value) return
+    const stillCurrent = tabGuard()
+    fillingGapId.value = newerId
+    try {
+      const result = unwrap(
+        await commands.fillGap(
+          accountId,
+          target.timelineType,
+          target.listId,
+          newerId,
+        ),
+      )
+      if (!stillCurrent()) return
+      const filtered = applyFilter(result.notes)
+      if (filtered.length > 0) {
+        setNotes(insertIntoSorted(notes.value, filtered))
+      }
+      gaps.value = gaps.value.flatMap((g) => {
+        if (g.newerId !== newerId) return [g]
+        return result.remaining ? [result.remaining] : []
+      })
+    } catch (e) {
+      logWarn('fill-gap', e)
+      toast.show(
+        `ノートを読み込めませんでした（${AppError.from(e).displayCode}）`,
+        'error',
+      )
+    } finally {
+      fillingGapId.value = null
+    }
+  }
+
+  // 取得が終わるたびに gap を読み直す (最新ページで記録され、遡りで縮む)
+  if (config.gaps) {
+    watch(isLoading, (loading) => {
+      if (!loading) void refreshGaps()
+    })
+  }
+
   /** True when the account exists but has no auth token */
   const isLoggedOut = computed(() => account.value?.hasToken === false)
 
@@ -491,6 +599,7 @@ export function useNoteColumn(config: NoteColumnConfig) {
                 streamingBatch.removePending(event.noteId)
               onNoteUpdate(event)
             },
+            onGap: () => void onResume(),
           }),
         )
         noteSound?.warmup()
@@ -578,6 +687,19 @@ export function useNoteColumn(config: NoteColumnConfig) {
     }
   }
 
+  let lastPrefetchedUntilId: string | null = null
+
+  function prefetchMore() {
+    if (!config.prefetch || isLoading.value || isOffline.value) return
+    if (config.validate && !config.validate()) return
+    const untilId = notes.value.at(-1)?.id
+    if (!untilId || untilId === lastPrefetchedUntilId) return
+    const adapter = getAdapter()
+    if (!adapter) return
+    lastPrefetchedUntilId = untilId
+    config.prefetch(adapter, { untilId })
+  }
+
   function handleScroll() {
     streamingBatch?.handleScroll()
     onScrollReport()
@@ -597,7 +719,25 @@ export function useNoteColumn(config: NoteColumnConfig) {
     })
   }
 
+  /**
+   * 上流サーバーがレート制限中なら手動リフレッシュを見送る。解除予定は
+   * Rust 側 (upstream_rate.rs) が 429 の連続回数から見積もる。
+   */
+  async function isRateLimited(): Promise<boolean> {
+    const accountId = account.value?.id
+    if (!accountId) return false
+    const result = await commands.apiGetRateLimitState(accountId)
+    if (result.status !== 'ok' || !result.data.limited) return false
+    const seconds = Math.ceil(result.data.retryAfterMs / 1000)
+    toast.show(
+      `サーバーのレート制限中です。${seconds} 秒ほど待ってから再読み込みしてください`,
+      'warning',
+    )
+    return true
+  }
+
   async function refresh() {
+    if (await isRateLimited()) return
     if (isStreaming) {
       // ストリーミングカラムのリロードボタン: 復帰 catch-up と同じ経路で
       // 最新ページを取得し gap 判定する。手動操作なのでスロットルは無視 (#791)
@@ -703,6 +843,8 @@ export function useNoteColumn(config: NoteColumnConfig) {
     if (!stillCurrent()) return
     isOffline.value = apiFailed
 
+    if (config.gaps) void refreshGaps()
+
     if (hasGap(fetched, hadNotes)) {
       mergeOrEnqueue(fetched, { replace: true })
       return
@@ -737,6 +879,7 @@ export function useNoteColumn(config: NoteColumnConfig) {
             streamingBatch.removePending(event.noteId)
           onNoteUpdate(event)
         },
+        onGap: () => void onResume(),
       }),
     )
   }
@@ -833,6 +976,7 @@ export function useNoteColumn(config: NoteColumnConfig) {
       const gap = hasGap(fetched, snapshotNotes.length > 0)
       mergeOrEnqueue(fetched, gap ? { replace: true } : undefined)
       isOffline.value = false
+      if (config.gaps) void refreshGaps()
     } catch {
       // API failure with snapshot displayed — mark offline
       isOffline.value = true
@@ -906,6 +1050,9 @@ export function useNoteColumn(config: NoteColumnConfig) {
     isOffline,
     isLoggedOut,
     viewMarkerId,
+    gapAfter,
+    fillingGapId,
+    fillGap,
     error,
     notes,
     orderedIds,
@@ -922,6 +1069,7 @@ export function useNoteColumn(config: NoteColumnConfig) {
     removeNote,
     removingIds,
     loadMore,
+    prefetchMore,
     refresh,
     isPulling,
     isPulledEnough,
@@ -1,6 +1,10 @@
 import type { NormalizedNote, ServerAdapter } from '@/adapters/types'
 import { useNoteStore } from '@/stores/notes'
 import { usePerformanceStore } from '@/stores/performance'
+import {
+  getCachedTimelineBeforeBinary,
+  getCachedTimelineBinary,
+} from '@/utils/binaryIpc'
 import { catchLog } from '@/utils/logger'
 import { commands, unwrap } from '@/utils/tauriInvoke'
 
@@ -12,13 +16,11 @@ export async function loadCachedTimeline(
 ): Promise<NormalizedNote[]> {
   const effectiveLimit =
     limit ?? usePerformanceStore().get('cachedTimelineLimit')
-  return unwrap(
-    await commands.apiGetCachedTimeline(
-      accountId,
-      timelineType,
-      effectiveLimit,
-    ),
-  ) as unknown as NormalizedNote[]
+  return (await getCachedTimelineBinary(
+    accountId,
+    timelineType,
+    effectiveLimit,
+  )) as unknown as NormalizedNote[]
 }
 
 /** Load older cached notes before a given timestamp. */
@@ -30,14 +32,12 @@ export async function loadCachedTimelineBefore(
 ): Promise<NormalizedNote[]> {
   const effectiveLimit =
     limit ?? usePerformanceStore().get('cachedTimelineLimit')
-  return unwrap(
-    await commands.apiGetCachedTimelineBefore(
-      accountId,
-      timelineType,
-      before,
-      effectiveLimit,
-    ),
-  ) as unknown as NormalizedNote[]
+  return (await getCachedTimelineBeforeBinary(
+    accountId,
+    timelineType,
+    before,
+    effectiveLimit,
+  )) as unknown as NormalizedNote[]
 }
 
 /**
@@ -1,6 +1,7 @@
 import { computed, onScopeDispose, shallowRef } from 'vue'
 import type {
   NormalizedNote,
+  NotePatchEvent,
   NoteUpdateEvent,
   ServerAdapter,
 } from '@/adapters/types'
@@ -105,6 +106,14 @@ export function useNoteList(options: UseNoteListOptions) {
     noteStore.applyUpdate(event, options.getMyUserId())
   }
 
+  function onNotePatch(patch: NotePatchEvent) {
+    if (patch.deleted) {
+      onNoteUpdate({ noteId: patch.noteId, type: 'deleted', body: {} })
+      return
+    }
+    noteStore.applyPatch(patch)
+  }
+
   async function handlePosted(editedNoteId?: string) {
     options.closePostForm()
     if (editedNoteId) {
@@ -160,6 +169,7 @@ export function useNoteList(options: UseNoteListOptions) {
     mergeUpdate,
     setOnNotesChanged,
     onNoteUpdate,
+    onNotePatch,
     handlePosted,
     removeNote,
     removingIds,
@@ -106,16 +106,21 @@ export function useNoteSound(
 ) {
   let lastPlayedAt = 0
 
-  async function play() {
+  /**
+   * 音を鳴らす。`override` で鳴らすサーバーや音を 1 回だけ差し替えられる
+   * (通知の種類ごとの音や、全アカウント通知カラムの各アカウント)。
+   */
+  async function play(override?: { host?: string; soundType?: string }) {
     const now = Date.now()
     if (now - lastPlayedAt < 300) return
     lastPlayedAt = now
 
-    const host = getHost()
+    const host = override?.host ?? getHost()
     if (!host) return
+    const sound = override?.soundType ?? soundType
 
     if (IS_ANDROID) {
-      const el = ensureAudioElement(host, soundType)
+      const el = ensureAudioElement(host, sound)
       el.currentTime = 0
       el.play().catch(() => {
         // Autoplay blocked by browser policy — expected on mobile
@@ -126,7 +131,7 @@ export function useNoteSound(
     const ctx = getAudioContext()
     if (ctx.state === 'suspended') await ctx.resume()
 
-    const buffer = await ensureBuffer(host, soundType)
+    const buffer = await ensureBuffer(host, sound)
     if (!buffer) return
     const source = ctx.createBufferSource()
     source.buffer = buffer
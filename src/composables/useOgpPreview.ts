@@ -71,6 +71,8 @@ export function useOgpPreview(initialUrl: string, accountId?: string) {
 
     let promise = pendingRequests.get(targetUrl)
     if (!promise) {
+      // アカウントがあればそのサーバーのプレビュープロキシ経由で取る
+      // (リンク先に自分の IP を出さない。プロキシが無いサーバーは Rust 側で直接取得)
       promise = commands
         .fetchOgp(targetUrl, accountId ?? null)
         .then((result) => {
@@ -94,7 +94,12 @@ export function usePostFormState(
     removeFile,
     reorderFiles,
     applyFileMeta,
-  } = useFileAttachment(() => adapter, error)
+  } = useFileAttachment(() => adapter, error, {
+    videoCompressThresholdBytes: () => {
+      const mb = settingsStore.get('upload.compressVideoThresholdMB')
+      return mb == null ? null : mb * 1024 * 1024
+    },
+  })
 
   /**
    * 添付ファイルの alt / センシティブ / 名前を更新 (#753)。楽観的にローカル
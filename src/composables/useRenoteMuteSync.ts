@@ -18,6 +18,12 @@ export function useRenoteMuteSync() {
     synced.add(accountId)
     try {
       const { adapter } = await initAdapterFor(host, accountId)
+      // following/update 方式のサーバーはホーム TL 側で除外されるので、
+      // renote-mute/list が無くても手元で覚える必要はない
+      if ((await adapter.api.getRenoteMuteSupport()) !== 'endpoint') {
+        mutesStore.setMutedRenoters(accountId, [])
+        return
+      }
       mutesStore.setMutedRenoters(
         accountId,
         await adapter.api.getRenoteMutedUsers(),
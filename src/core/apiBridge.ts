@@ -1,11 +1,10 @@
-import { emit } from '@tauri-apps/api/event'
 import { dispatchCapability } from '@/capabilities/dispatcher'
 import { sanitizeToolName } from '@/capabilities/identifier'
 import { listCapabilities } from '@/capabilities/registry'
 import { useCommandStore } from '@/commands/registry'
 import { listStreamHealth } from '@/core/streamHealth'
 import { useDeckStore } from '@/stores/deck'
-import { listenTauri } from '@/utils/tauriEvents'
+import { emitTauri, listenTauri } from '@/utils/tauriEvents'
 
 export interface QueryRequest {
   id: string
@@ -74,20 +73,37 @@ const handlers: Record<string, QueryHandler> = {
 }
 
 /**
- * Query type からハンドラを引いて実行する。HTTP API (query_bridge) と
- * テストの共通入口。ハンドラ throw は構造化エラーに変換して返す
- * (= 呼び出し元の HTTP handler が 500 QUERY_FAILED にしない)。
+ * query_bridge への応答 envelope。Rust 側は `code` を HTTP status /
+ * エラーコードに写像する (query_bridge.rs の QueryError::Frontend)。
  */
-export async function handleQuery(
+export type QueryResponse =
+  | { ok: true; data: unknown }
+  | { ok: false; code: 'unknown_query' | 'handler_failed'; message: string }
+
+/**
+ * Query type からハンドラを引いて実行し、envelope に包んで返す。
+ * ハンドラの throw は handler_failed に変換する。
+ */
+async function runQuery(
   type: string,
   params: Record<string, unknown>,
-): Promise<unknown> {
+): Promise<QueryResponse> {
   const handler = handlers[type]
-  if (!handler) return { error: `Unknown query type: ${type}` }
+  if (!handler) {
+    return {
+      ok: false,
+      code: 'unknown_query',
+      message: `Unknown query type: ${type}`,
+    }
+  }
   try {
-    return await handler(params)
+    return { ok: true, data: await handler(params) }
   } catch (e) {
-    return { error: e instanceof Error ? e.message : String(e) }
+    return {
+      ok: false,
+      code: 'handler_failed',
+      message: e instanceof Error ? e.message : String(e),
+    }
   }
 }
 
@@ -99,14 +115,15 @@ export async function initApiBridge() {
   const unlistenFn = await listenTauri(
     'nd:query-request',
     async ({ id, type, params }) => {
-      const result = await handleQuery(type, params)
-
-      // 動的イベント名なので TauriEventPayloads の対象外 (型付け不可)
-      await emit(`nd:query-response-${id}`, result)
+      const response = await runQuery(type, params)
+      await emitTauri('nd:query-response', { id, ...response })
     },
   )
 
   unlisten = unlistenFn
+  // 起動 / リロード後に listener が揃ったことを Rust に知らせる。
+  // リロードで取りこぼした query は Rust 側がこれを合図に再送する
+  await emitTauri('nd:query-bridge-ready')
 }
 
 export function destroyApiBridge() {
@@ -6,8 +6,9 @@
  * テンプレ id は `builtin:<id>@<version>` 形式 — v2 で MisStore 配布の
  * `@<author>/<id>@<version>` 形式と名前空間を分離するための予約。
  *
- * v1 では AI プロバイダー 3 種のみ。GitHub / Linear / Slack 等の汎用 API
- * テンプレは需要を見て v1.x で追加する (手動追加 / URL ペーストは現状でも可)。
+ * AI プロバイダー 3 種と、ノート翻訳 (`translate_text`) のフォールバック先の
+ * 翻訳 API 2 種。GitHub / Linear / Slack 等の汎用 API テンプレは需要を見て
+ * 追加する (手動追加 / URL ペーストは現状でも可)。
  */
 
 import type { AuthType, ConnectionProtocol } from '@/bindings'
@@ -85,6 +86,30 @@ export const BUILTIN_TEMPLATES: ConnectionTemplate[] = [
     protocol: 'openai-compat',
     defaultModel: 'deepseek/deepseek-v4-pro',
   },
+  {
+    id: 'builtin:deepl@1',
+    name: 'DeepL',
+    icon: 'language',
+    // Free プランの API。Pro は baseUrl を api.deepl.com に変える
+    baseUrl: 'https://api-free.deepl.com',
+    // header 注入は secret をそのまま値にするので接頭辞ごと保存してもらう
+    authType: { kind: 'header', name: 'Authorization' },
+    allowedHosts: ['api-free.deepl.com', 'api.deepl.com'],
+    testPath: '/v2/usage',
+    secretLabel: 'DeepL-Auth-Key <API Key>',
+    secretHelpUrl: 'https://www.deepl.com/your-account/keys',
+  },
+  {
+    id: 'builtin:libretranslate@1',
+    name: 'LibreTranslate',
+    icon: 'language',
+    baseUrl: 'https://libretranslate.com',
+    authType: { kind: 'query', param: 'api_key' },
+    allowedHosts: ['libretranslate.com'],
+    testPath: '/languages',
+    secretLabel: 'API Key',
+    secretHelpUrl: 'https://portal.libretranslate.com',
+  },
 ]
 
 /**
@@ -1,5 +1,5 @@
 import { createPinia } from 'pinia'
-import { createApp } from 'vue'
+import { createApp, watch } from 'vue'
 import App from './App.vue'
 import { ALL_BUILTIN_CAPABILITIES } from './capabilities/builtins'
 import { registerCapability } from './capabilities/registry'
@@ -11,6 +11,7 @@ import { useServersStore } from './stores/servers'
 import { useSettingsStore } from './stores/settings'
 import { useThemeStore } from './stores/theme'
 import { resolveEvictionConfig } from './utils/cacheEviction'
+import { reportCrash } from './utils/crashReport'
 import { isTauri } from './utils/settingsFs'
 import { commands, unwrap } from './utils/tauriInvoke'
 import '@tabler/icons-webfont/dist/tabler-icons.min.css'
@@ -34,7 +35,8 @@ if (isTauri) {
   // Pre-warm Tauri API module (critical path in App.vue onMounted)
   import('@tauri-apps/api/window')
 
-  const isPipRoute = location.pathname === '/pip'
+  const isPipRoute =
+    location.pathname === '/pip' || location.pathname === '/quick-post'
 
   if (!isPipRoute) {
     // Pre-fetch DeckPage chunk so its CSS <link> is inserted early.
@@ -70,8 +72,10 @@ app.use(pinia)
 
 // Global error handler — catch unhandled promise rejections
 // Vue component errors are caught by onErrorCaptured in App.vue (Vapor Mode compatible)
+// クラッシュレポート (opt-in) にも送る。記録の有無は Rust 側が判断する
 window.addEventListener('unhandledrejection', (event) => {
   console.error('[unhandled] Promise rejection:', event.reason)
+  reportCrash(event.reason)
 })
 
 // Suppress ResizeObserver loop warnings.
@@ -81,7 +85,11 @@ window.addEventListener('unhandledrejection', (event) => {
 // See: https://github.com/TanStack/virtual/issues/426
 const _roError = 'ResizeObserver loop'
 window.addEventListener('error', (e) => {
-  if (e.message?.startsWith(_roError)) e.stopImmediatePropagation()
+  if (e.message?.startsWith(_roError)) {
+    e.stopImmediatePropagation()
+    return
+  }
+  reportCrash(e.error ?? e.message)
 })
 window.addEventListener('unhandledrejection', (e) => {
   if (
@@ -110,6 +118,59 @@ if (isTauri) {
         console.debug('[cache-eviction] apply on startup failed:', e)
     })
 
+  // OS の集中モード / DND 中に OS 通知を抑制するか (Rust 側 dnd.rs に反映)
+  watch(
+    () => settingsStore.settings['notifications.respectDnd'],
+    (respect) => {
+      void commands
+        .dndSetMode(respect === false ? 'alwaysNotify' : 'followSystem')
+        .catch((e) => {
+          if (import.meta.env.DEV) console.debug('[dnd] apply failed:', e)
+        })
+    },
+    { immediate: true },
+  )
+
+  // アップロード前の EXIF 除去・縮小・HEIC 変換 (Rust 側 settings.json の uploadPrep に保存)
+  watch(
+    () =>
+      [
+        settingsStore.settings['upload.stripMetadata'],
+        settingsStore.settings['upload.maxImageDimension'],
+        settingsStore.settings['upload.jpegQuality'],
+        settingsStore.settings['upload.convertHeic'],
+      ] as const,
+    ([strip, maxDimension, jpegQuality, convertHeic]) => {
+      void commands
+        .uploadPrepConfigure({
+          stripMetadata: strip !== false,
+          maxDimension: maxDimension ?? null,
+          jpegQuality: jpegQuality ?? 85,
+          convertHeic: convertHeic !== false,
+        })
+        .then((r) => unwrap(r))
+        .catch((e) => {
+          if (import.meta.env.DEV)
+            console.debug('[upload-prep] apply failed:', e)
+        })
+    },
+    { immediate: true },
+  )
+
+  // 離席判定の閾値 (Rust 側 idle.rs に反映、null で無効)
+  watch(
+    () => settingsStore.settings['modes.idleAwayMinutes'],
+    (minutes) => {
+      void commands
+        .idleConfigure(minutes ?? null)
+        .then((r) => unwrap(r))
+        .catch((e) => {
+          if (import.meta.env.DEV) console.debug('[idle] apply failed:', e)
+        })
+    },
+    { immediate: true },
+  )
+
   // Apply cached theme before mount to prevent FOUC
   useThemeStore().init()
   useKeybindsStore().init()
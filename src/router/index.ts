@@ -30,6 +30,12 @@ export const router = createRouter({
       component: () => import('@/views/PipPage.vue'),
       meta: { pip: true },
     },
+    {
+      path: '/quick-post',
+      name: 'quick-post',
+      component: () => import('@/views/QuickPostPage.vue'),
+      meta: { pip: true },
+    },
     {
       path: '/:pathMatch(.*)*',
       name: 'not-found',
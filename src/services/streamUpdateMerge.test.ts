@@ -1,9 +1,10 @@
 import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
 
-import type { NormalizedNote } from '@/adapters/types'
+import type { NormalizedNote, NotePatchEvent } from '@/adapters/types'
 import {
   createUpdateDeduper,
   mergeChatUpdate,
+  mergeNotePatch,
   mergeNoteUpdate,
 } from '@/services/streamUpdateMerge'
 
@@ -150,6 +151,82 @@ describe('mergeNoteUpdate', () => {
   })
 })
 
+function makePatch(partial: Partial<NotePatchEvent> = {}): NotePatchEvent {
+  return {
+    noteId: 'n1',
+    deleted: false,
+    reactions: {},
+    reactionEmojis: {},
+    pollVotes: {},
+    myPollChoices: [],
+    ...partial,
+  }
+}
+
+describe('mergeNotePatch', () => {
+  it('リアクション件数の差分を足し、0 以下になったキーは消す', () => {
+    const note = makeNote({ reactions: { '👍': 1, '❤': 2 } })
+    const merged = mergeNotePatch(
+      note,
+      makePatch({
+        reactions: { '👍': -1, '❤': 3, ':blobcat:': 1 },
+        reactionEmojis: { blobcat: 'https://example.com/blobcat.png' },
+      }),
+    )
+    expect(merged?.reactions).toEqual({ '❤': 5, ':blobcat:': 1 })
+    expect(merged?.reactionEmojis).toEqual({
+      blobcat: 'https://example.com/blobcat.png',
+    })
+  })
+
+  it('楽観的更新と同じ myReaction は no-op (null)', () => {
+    const note = makeNote({ reactions: { '👍': 1 }, myReaction: '👍' })
+    expect(mergeNotePatch(note, makePatch({ myReaction: '👍' }))).toBeNull()
+  })
+
+  it('他クライアントでの自分のリアクション変更は件数と myReaction に反映する', () => {
+    const note = makeNote({ reactions: { '👍': 1 }, myReaction: '👍' })
+    const changed = mergeNotePatch(note, makePatch({ myReaction: '❤' }))
+    expect(changed?.myReaction).toBe('❤')
+    expect(changed?.reactions).toEqual({ '❤': 1 })
+
+    const cleared = mergeNotePatch(note, makePatch({ myReaction: null }))
+    expect(cleared?.myReaction).toBeNull()
+    expect(cleared?.reactions).toEqual({})
+  })
+
+  it('投票数を choice ごとに足し、自分の choice に isVoted を立てる', () => {
+    const note = makeNote({
+      poll: {
+        multiple: true,
+        expiresAt: null,
+        choices: [
+          { text: 'A', votes: 1, isVoted: false },
+          { text: 'B', votes: 0, isVoted: false },
+        ],
+      },
+    })
+    const merged = mergeNotePatch(
+      note,
+      makePatch({ pollVotes: { 0: 2, 1: 1 }, myPollChoices: [1] }),
+    )
+    expect(merged?.poll?.choices[0]).toEqual({
+      text: 'A',
+      votes: 3,
+      isVoted: false,
+    })
+    expect(merged?.poll?.choices[1]).toEqual({
+      text: 'B',
+      votes: 1,
+      isVoted: true,
+    })
+  })
+
+  it('空の patch は no-op (null)', () => {
+    expect(mergeNotePatch(makeNote(), makePatch())).toBeNull()
+  })
+})
+
 describe('mergeChatUpdate', () => {
   const msg = {
     id: 'm1',
@@ -14,6 +14,7 @@
 import type {
   ChatMessage,
   NormalizedNote,
+  NotePatchEvent,
   NoteUpdateEvent,
 } from '@/adapters/types'
 
@@ -120,6 +121,67 @@ export function mergeNoteUpdate(
   }
 }
 
+/**
+ * Note Capture の集計済み patch をノートへ適用した新オブジェクトを返す。
+ * 変化が無ければ null。`myReaction` が楽観的更新と同値なら件数は動かさず、
+ * 他クライアントでの自分のリアクションだけを件数に反映する。
+ */
+export function mergeNotePatch(
+  note: NormalizedNote,
+  patch: NotePatchEvent,
+): NormalizedNote | null {
+  let changed = false
+  const next: NormalizedNote = { ...note }
+
+  const deltas = { ...patch.reactions }
+  if (
+    patch.myReaction !== undefined &&
+    patch.myReaction !== (note.myReaction ?? null)
+  ) {
+    if (note.myReaction) {
+      deltas[note.myReaction] = (deltas[note.myReaction] ?? 0) - 1
+    }
+    if (patch.myReaction) {
+      deltas[patch.myReaction] = (deltas[patch.myReaction] ?? 0) + 1
+    }
+    next.myReaction = patch.myReaction
+    changed = true
+  }
+  const reactionKeys = Object.keys(deltas)
+  if (reactionKeys.length > 0) {
+    const reactions = { ...note.reactions }
+    for (const reaction of reactionKeys) {
+      const count = (reactions[reaction] ?? 0) + (deltas[reaction] ?? 0)
+      if (count <= 0) delete reactions[reaction]
+      else reactions[reaction] = count
+    }
+    next.reactions = reactions
+    changed = true
+  }
+  if (Object.keys(patch.reactionEmojis).length > 0) {
+    next.reactionEmojis = { ...note.reactionEmojis, ...patch.reactionEmojis }
+    changed = true
+  }
+
+  const voted = Object.keys(patch.pollVotes).length > 0
+  if (note.poll && (voted || patch.myPollChoices.length > 0)) {
+    const choices = note.poll.choices.map((c, i) => {
+      const votes = patch.pollVotes[i] ?? 0
+      const mine = patch.myPollChoices.includes(i)
+      if (votes === 0 && !mine) return c
+      return {
+        ...c,
+        votes: c.votes + votes,
+        ...(mine ? { isVoted: true } : {}),
+      }
+    })
+    next.poll = { ...note.poll, choices }
+    changed = true
+  }
+
+  return changed ? next : null
+}
+
 /**
  * chat の react/unreact イベントをメッセージへ適用した新オブジェクトを返す。
  * 適用不要 (一致する reaction なし等) は null。
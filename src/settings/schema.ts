@@ -1,3 +1,5 @@
+import type { TranslationProvider } from '@/bindings'
+
 /**
  * settings.json の型定義。VSCode `settings.json` と同じく、トップレベルは
  * フラット dot-notation キー空間 (`theme.manual`, `modes.realtime` 等)。
@@ -25,6 +27,11 @@ export interface NotedeckSettings {
   // --- Modes (PoC 移行済み) ---
   'modes.realtime'?: boolean
   'modes.offline'?: boolean
+  /**
+   * 無操作がこの分数続いたら離席扱いにし、ストリーミングをポーリングへ
+   * 落とす (復帰で元に戻る)。null で無効。
+   */
+  'modes.idleAwayMinutes'?: number | null
 
   // --- Note view ---
   /**
@@ -33,6 +40,16 @@ export interface NotedeckSettings {
    */
   'note.nyaize'?: boolean
 
+  // --- Translation ---
+  /**
+   * ノート翻訳で試すプロバイダの順。キー: accountId。未設定のアカウントは
+   * サーバーの `notes/translate` のみ。サーバーが翻訳未対応なら vault に
+   * 登録した DeepL / LibreTranslate の接続を後ろに並べてフォールバックさせる。
+   */
+  'translation.providersByAccount'?: Record<string, TranslationProvider[]>
+  /** 翻訳先の言語 (BCP 47)。null ならブラウザの言語設定。 */
+  'translation.targetLang'?: string | null
+
   // --- Post form ---
   'postForm.preview'?: boolean
   'postForm.autoSaveDraft'?: boolean
@@ -51,6 +68,24 @@ export interface NotedeckSettings {
   >
   'postForm.lastUsedLocalOnlyByAccount'?: Record<string, boolean>
 
+  // --- Upload (Rust 側 upload_prep.rs に反映) ---
+  /**
+   * ドライブへのアップロード前に JPEG / PNG / WebP から EXIF・GPS 等の
+   * メタデータを取り除く。向き (Orientation) だけは残す。
+   */
+  'upload.stripMetadata'?: boolean
+  /** 長辺がこのピクセル数を超える画像を縮小してからアップロードする。null で縮小しない。 */
+  'upload.maxImageDimension'?: number | null
+  /** 縮小・変換で再エンコードするときの JPEG 品質 (1〜100)。 */
+  'upload.jpegQuality'?: number
+  /** HEIC / HEIF を JPEG に変換してからアップロードする (対応ビルドのみ)。 */
+  'upload.convertHeic'?: boolean
+  /**
+   * これ (MB) を超える動画は ffmpeg で圧縮してからアップロードする
+   * (ffmpeg が見つかる場合のみ)。null で圧縮しない。
+   */
+  'upload.compressVideoThresholdMB'?: number | null
+
   // --- Lists ---
   /**
    * アカウントごとにお気に入りしたリスト ID をキャッシュする。Misskey 本家に
@@ -90,6 +125,15 @@ export interface NotedeckSettings {
   /** chat の TTL (日)。null = 無期限保持 (default)。 */
   'chat.ttlDays'?: number | null
 
+  // --- Notifications ---
+  /**
+   * OS の集中モード / 応答不可 (DND) 中は OS 通知 (トースト) を出さない。
+   * アプリ内の未読カウントは変わらない。false で DND 中も通知する。
+   * Windows は「応答不可」/ 集中モード (Focus Assist) を検知できず、
+   * プレゼンテーション / 全画面アプリの間だけ抑制する。
+   */
+  'notifications.respectDnd'?: boolean
+
   // --- Tutorial (新規ユーザー向けセットアップ wizard、/tutorial コマンド) ---
   /**
    * /tutorial を一度でも完走したかのフラグ。再実行は常に可能だが、再実行時に
@@ -117,6 +161,8 @@ export const DEFAULT_SETTINGS: NotedeckSettings = {
   // 体験を変えない)
   'modes.realtime': true,
   'modes.offline': false,
+  // 離席時のポーリング切替は opt-in (既存ユーザーの体験を変えない)
+  'modes.idleAwayMinutes': null,
   // 本家 Web UI と同じ表示を基準にするため default ON (#763)
   'note.nyaize': true,
   // notedeck の差別化要素「過去ノートを一瞬でローカル全文検索」を尊重し、
@@ -128,6 +174,15 @@ export const DEFAULT_SETTINGS: NotedeckSettings = {
   'chat.cacheEnabled': true,
   'chat.perAccountLimit': 1_000_000,
   'chat.ttlDays': null,
+  'notifications.respectDnd': true,
+  // 位置情報の意図しない公開を防ぐため default ON
+  'upload.stripMetadata': true,
+  // 縮小は opt-in (既存ユーザーの画質を変えない)
+  'upload.maxImageDimension': null,
+  'upload.jpegQuality': 85,
+  'upload.convertHeic': true,
+  // 多くのサーバーのアップロード上限 (50MB) に合わせる
+  'upload.compressVideoThresholdMB': 50,
 }
 
 /**
@@ -17,11 +17,13 @@ import { getStorageJson, removeStorage, STORAGE_KEYS } from '@/utils/storage'
 
 export type ColumnType =
   | 'timeline'
+  | 'mergedTimeline'
   | 'notifications'
   | 'search'
   | 'list'
   | 'antenna'
   | 'favorites'
+  | 'polls'
   | 'clip'
   | 'user'
   | 'mentions'
@@ -62,12 +64,14 @@ export type ColumnType =
  */
 export const TIMELINE_LIKE_COLUMN_TYPES: ReadonlySet<ColumnType> = new Set([
   'timeline',
+  'mergedTimeline',
   'notifications',
   'list',
   'antenna',
   'mentions',
   'channel',
   'favorites',
+  'polls',
   'clip',
   'user',
   'specified',
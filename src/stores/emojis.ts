@@ -1,9 +1,16 @@
 import { defineStore } from 'pinia'
 import { shallowRef } from 'vue'
 import type { ServerEmoji } from '@/adapters/types'
+import type { EmojiDiff } from '@/bindings'
+import { useAccountsStore } from '@/stores/accounts'
 import { usePerformanceStore } from '@/stores/performance'
 import { createDebouncedPersist } from '@/utils/debouncedPersist'
+import { createEmojiBatcher } from '@/utils/emojiBatch'
+import { applyEmojiDiff, applyEmojiDiffToLookup } from '@/utils/emojiDiff'
+import { isTauri } from '@/utils/settingsFs'
 import { getStorageJson, STORAGE_KEYS, setStorageJson } from '@/utils/storage'
+import { listenTauri } from '@/utils/tauriEvents'
+import { commands, unwrap } from '@/utils/tauriInvoke'
 
 export const useEmojisStore = defineStore('emojis', () => {
   const perfStore = usePerformanceStore()
@@ -76,6 +83,28 @@ export const useEmojisStore = defineStore('emojis', () => {
     schedulePersist()
   }
 
+  // Rust 側の差分同期 (emoji_cache.rs) を当てる。一覧を持っているホストは
+  // 一覧ごと、lookup だけのホストは lookup だけ更新する。差分は前回 ack した
+  // 版からの累積なので、当てたら ack して基準を進める
+  function applyDiff(diff: EmojiDiff) {
+    const list = emojiList.value.get(diff.host)
+    if (list) {
+      set(diff.host, applyEmojiDiff(list, diff))
+    } else {
+      const lookup = cache.value.get(diff.host)
+      if (!lookup) return
+      const nextCache = new Map(cache.value)
+      nextCache.set(diff.host, applyEmojiDiffToLookup(lookup, diff))
+      cache.value = nextCache
+      schedulePersist()
+    }
+    void commands.ackEmojis(diff.host, diff.version)
+  }
+
+  if (isTauri) {
+    void listenTauri('nd:emojis-updated', applyDiff)
+  }
+
   const RETRY_BACKOFF_MS = 30_000
 
   function ensureLoaded(
@@ -109,6 +138,37 @@ export const useEmojisStore = defineStore('emojis', () => {
     return map[shortcode] || map[base] || map[`${base}@.`] || null
   }
 
+  // host → (参照 → url)。一覧をまだ持っていないホストの絵文字を、描画に
+  // 出てきた分だけ Rust の絵文字キャッシュ (api_resolve_emojis) でまとめて引く
+  const resolvedRefs = shallowRef(new Map<string, Record<string, string>>())
+  const accountsStore = useAccountsStore()
+  const requestResolve = createEmojiBatcher(
+    async (accountId, refs) =>
+      unwrap(await commands.apiResolveEmojis(accountId, refs)),
+    (accountId, urls) => {
+      const host = accountsStore.accountMap.get(accountId)?.host
+      if (!host || Object.keys(urls).length === 0) return
+      const next = new Map(resolvedRefs.value)
+      next.set(host, { ...next.get(host), ...urls })
+      resolvedRefs.value = next
+    },
+  )
+
+  /**
+   * `resolve` で見つからなければバッチ解決の結果を見る。未解決ならそのホストの
+   * アカウントで解決を要求し、届いたら参照元の computed が再評価される。
+   */
+  function resolveOrRequest(host: string, shortcode: string): string | null {
+    const local = resolve(host, shortcode)
+    if (local) return local
+    const base = shortcode.replace(/@\.$/, '')
+    const hit = resolvedRefs.value.get(host)?.[base]
+    if (hit) return hit
+    const account = accountsStore.accountsByServer.get(host)?.[0]
+    if (isTauri && account) requestResolve(account.id, base)
+    return null
+  }
+
   function getEmojiList(host: string): ServerEmoji[] {
     return emojiList.value.get(host) ?? []
   }
@@ -117,5 +177,14 @@ export const useEmojisStore = defineStore('emojis', () => {
     return cache.value.has(host)
   }
 
-  return { cache, emojiList, set, ensureLoaded, resolve, getEmojiList, has }
+  return {
+    cache,
+    emojiList,
+    set,
+    ensureLoaded,
+    resolve,
+    resolveOrRequest,
+    getEmojiList,
+    has,
+  }
 })
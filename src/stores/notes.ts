@@ -1,10 +1,15 @@
 import { defineStore } from 'pinia'
 import { shallowRef, triggerRef } from 'vue'
-import type { NormalizedNote, NoteUpdateEvent } from '@/adapters/types'
+import type {
+  NormalizedNote,
+  NotePatchEvent,
+  NoteUpdateEvent,
+} from '@/adapters/types'
 import { useFrameScheduler } from '@/composables/useFrameScheduler'
 import { evictByLiveness } from '@/services/mapEviction'
 import {
   createUpdateDeduper,
+  mergeNotePatch,
   mergeNoteUpdate,
   noteUpdateSig,
 } from '@/services/streamUpdateMerge'
@@ -187,9 +192,8 @@ export const useNoteStore = defineStore('notes', () => {
       return
     }
 
-    // Channel auto-capture と subNote の両経路から同じ noteUpdated が
-    // 来うるため (例: 共通 timeline 購読中の note を別途 subNote 中の場合)、
-    // 短い窓で同一 sig の重複を弾く。userId / reaction / choice まで含める
+    // 複数カラムの channel 購読から同じ noteUpdated が来うるため、短い窓で
+    // 同一 sig の重複を弾く (subNote 経路との重複は Rust 側で落とし済み)。userId / reaction / choice まで含める
     // ので「同ユーザの逐次 react→unreact」は別 sig として通る。
     if (!updateDeduper.shouldApply(event.noteId, noteUpdateSig(event))) return
 
@@ -202,6 +206,24 @@ export const useNoteStore = defineStore('notes', () => {
     scheduleTrigger()
   }
 
+  /**
+   * Note Capture の集計済み patch を適用する。channel 経路との重複は Rust 側で
+   * 落とし済みなので dedup は通さない。
+   */
+  function applyPatch(patch: NotePatchEvent) {
+    if (patch.deleted) {
+      remove(patch.noteId)
+      return
+    }
+    const note = noteMap.value.get(patch.noteId)
+    if (!note) return
+
+    const merged = mergeNotePatch(note, patch)
+    if (!merged) return
+    noteMap.value.set(patch.noteId, merged)
+    scheduleTrigger()
+  }
+
   /** Update a single note in the store (batched trigger for streaming perf) */
   function update(id: string, note: NormalizedNote) {
     noteMap.value.set(id, note)
@@ -223,6 +245,7 @@ export const useNoteStore = defineStore('notes', () => {
     isDeleted,
     onDelete,
     applyUpdate,
+    applyPatch,
     notifyMutation,
     registerRoot,
   }
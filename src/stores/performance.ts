@@ -1,6 +1,7 @@
 import JSON5 from 'json5'
 import { defineStore } from 'pinia'
 import { computed, ref } from 'vue'
+import type { BackendSettings, SettingsSection } from '@/bindings'
 import { detectQualitySync } from '@/composables/useAdaptiveQuality'
 import { frameEngine } from '@/engine/frameEngine'
 import {
@@ -9,6 +10,7 @@ import {
 } from '@/engine/telemetry/frameTelemetry'
 import { createDebouncedPersist } from '@/utils/debouncedPersist'
 import { isTauri, readPerformance, writePerformance } from '@/utils/settingsFs'
+import { listenTauri } from '@/utils/tauriEvents'
 import { commands, unwrap } from '@/utils/tauriInvoke'
 
 /** All tunable performance keys. */
@@ -51,6 +53,9 @@ export interface PerformanceConfig {
   circuitBreakerThreshold: number
   circuitBreakerDuration: number
   imageCacheTTLDays: number
+  streamPendingMax: number
+  streamCapturesMax: number
+  streamDropPolicy: number
   // Polling
   streamPollingInterval: number
   notificationPollInterval: number
@@ -98,9 +103,96 @@ import {
   interpolateConfig,
 } from '@/stores/performanceData'
 
+/**
+ * Rust 側のバックエンド設定 (settings.json) が持つキー。performance.json5 には
+ * 保存せず、settingsGet で読んで settingsSetSection で書く。
+ */
+const BACKEND_KEYS = [
+  'memoryCacheMaxMB',
+  'memoryCacheMaxItemKB',
+  'maxConcurrentFetches',
+  'rustOgpCacheMax',
+  'maxRequestsPerWindow',
+  'circuitBreakerThreshold',
+  'circuitBreakerDuration',
+  'imageCacheTTLDays',
+  'streamPendingMax',
+  'streamCapturesMax',
+  'streamDropPolicy',
+] as const satisfies readonly PerformanceKey[]
+
+type BackendKey = (typeof BACKEND_KEYS)[number]
+type BackendValues = Pick<PerformanceConfig, BackendKey>
+
+function isBackendKey(key: string): key is BackendKey {
+  return (BACKEND_KEYS as readonly string[]).includes(key)
+}
+
+function pickBackend(
+  config: Partial<PerformanceConfig>,
+): Partial<BackendValues> {
+  const picked: Partial<BackendValues> = {}
+  for (const key of BACKEND_KEYS) {
+    if (config[key] !== undefined) picked[key] = config[key]
+  }
+  return picked
+}
+
+function fromBackend(settings: BackendSettings): BackendValues {
+  return {
+    memoryCacheMaxMB: settings.cache.memoryMaxMb,
+    memoryCacheMaxItemKB: settings.cache.memoryItemMaxKb,
+    maxConcurrentFetches: settings.network.maxConcurrentFetches,
+    rustOgpCacheMax: settings.cache.ogpEntriesMax,
+    maxRequestsPerWindow: settings.httpServer.maxRequestsPerWindow,
+    circuitBreakerThreshold: settings.network.circuitBreakerThreshold,
+    circuitBreakerDuration: settings.network.circuitBreakerSecs,
+    imageCacheTTLDays: settings.cache.imageTtlDays,
+    streamPendingMax: settings.streaming.pendingMax,
+    streamCapturesMax: settings.streaming.capturesMax,
+    streamDropPolicy: settings.streaming.dropPolicy === 'dropNewest' ? 1 : 0,
+  }
+}
+
+function toSections(c: BackendValues): SettingsSection[] {
+  return [
+    {
+      section: 'network',
+      values: {
+        maxConcurrentFetches: c.maxConcurrentFetches,
+        circuitBreakerThreshold: c.circuitBreakerThreshold,
+        circuitBreakerSecs: c.circuitBreakerDuration,
+      },
+    },
+    {
+      section: 'cache',
+      values: {
+        memoryMaxMb: c.memoryCacheMaxMB,
+        memoryItemMaxKb: c.memoryCacheMaxItemKB,
+        ogpEntriesMax: c.rustOgpCacheMax,
+        imageTtlDays: c.imageCacheTTLDays,
+      },
+    },
+    {
+      section: 'streaming',
+      values: {
+        pendingMax: c.streamPendingMax,
+        capturesMax: c.streamCapturesMax,
+        dropPolicy: c.streamDropPolicy === 1 ? 'dropNewest' : 'dropOldest',
+      },
+    },
+    {
+      section: 'httpServer',
+      values: { maxRequestsPerWindow: c.maxRequestsPerWindow },
+    },
+  ]
+}
+
 export const usePerformanceStore = defineStore('performance', () => {
-  /** User overrides (独自 ref — performance.json5 が single source of truth). */
-  const overrides = ref<Partial<PerformanceConfig>>({})
+  /** フロントだけのキーの上書き (performance.json5 に保存)。 */
+  const localOverrides = ref<Partial<PerformanceConfig>>({})
+  /** Rust が持つキーの現在値 (読み込むまでは既定値)。 */
+  const backend = ref<BackendValues>(pickBackend(DEFAULTS) as BackendValues)
   const initialized = ref(false)
 
   const { schedule: schedulePersist } = createDebouncedPersist(persist, {
@@ -109,13 +201,24 @@ export const usePerformanceStore = defineStore('performance', () => {
 
   async function persist(): Promise<void> {
     if (!isTauri) return
-    const content = JSON5.stringify(overrides.value, null, 2)
+    const content = JSON5.stringify(localOverrides.value, null, 2)
     await writePerformance(`${content}\n`)
   }
+
+  /** 既定値から変えているキー (保存先を問わない)。 */
+  const overrides = computed<Partial<PerformanceConfig>>(() => {
+    const result: Partial<PerformanceConfig> = { ...localOverrides.value }
+    for (const key of BACKEND_KEYS) {
+      if (backend.value[key] !== DEFAULTS[key]) result[key] = backend.value[key]
+    }
+    return result
+  })
+
   /** Merged config: overrides on top of defaults. */
   const config = computed<PerformanceConfig>(() => ({
     ...DEFAULTS,
-    ...overrides.value,
+    ...localOverrides.value,
+    ...backend.value,
   }))
 
   /** Get a single value (reactive). */
@@ -194,47 +297,98 @@ export const usePerformanceStore = defineStore('performance', () => {
     }
   }
 
-  /** Apply CSS + Rust side effects after any override change. */
+  /** Apply CSS side effects after any override change. */
   function applySideEffects(): void {
     syncCssProperties()
-    syncToRust().catch((e) =>
-      console.warn('[performance] Rust sync failed:', e),
-    )
   }
 
-  async function syncToRust(): Promise<void> {
+  /** Rust に保存済みのセクション (JSON)。変わったセクションだけ送る。 */
+  const savedSections = new Map<SettingsSection['section'], string>()
+  let flushing: Promise<void> | null = null
+  let dirty = false
+
+  function rememberSaved(settings: BackendSettings): void {
+    savedSections.set('network', JSON.stringify(settings.network))
+    savedSections.set('cache', JSON.stringify(settings.cache))
+    savedSections.set('streaming', JSON.stringify(settings.streaming))
+    savedSections.set('httpServer', JSON.stringify(settings.httpServer))
+  }
+
+  /** 他のウィンドウ / 起動時に読んだ Rust 側の値を反映する。 */
+  function applyBackend(settings: BackendSettings): void {
+    rememberSaved(settings)
+    if (!flushing) backend.value = fromBackend(settings)
+  }
+
+  async function sendSections(): Promise<void> {
+    let saved: BackendSettings | null = null
+    for (const section of toSections(backend.value)) {
+      const json = JSON.stringify(section.values)
+      if (savedSections.get(section.section) === json) continue
+      saved = unwrap(await commands.settingsSetSection(section))
+      rememberSaved(saved)
+    }
+    if (saved && !dirty) backend.value = fromBackend(saved)
+  }
+
+  /** Rust が持つキーを書き換えて保存する。送信は 1 本ずつ、最新の値だけ送る。 */
+  function setBackend(patch: Partial<BackendValues>): void {
+    backend.value = { ...backend.value, ...patch }
     if (!isTauri) return
-    const c = config.value
-    unwrap(
-      await commands.updatePerformanceConfig({
-        memory_cache_max_total: c.memoryCacheMaxMB * 1024 * 1024,
-        memory_cache_max_item: c.memoryCacheMaxItemKB * 1024,
-        max_concurrent_fetches: c.maxConcurrentFetches,
-        rust_ogp_cache_max: c.rustOgpCacheMax,
-        max_requests_per_window: c.maxRequestsPerWindow,
-        circuit_breaker_threshold: c.circuitBreakerThreshold,
-        circuit_breaker_duration: c.circuitBreakerDuration,
-        image_cache_ttl_days: c.imageCacheTTLDays,
-      }),
-    )
+    if (flushing) {
+      dirty = true
+      return
+    }
+    flushing = (async () => {
+      do {
+        dirty = false
+        await sendSections()
+      } while (dirty)
+    })()
+      .catch((e) => {
+        console.warn('[performance] backend settings save failed:', e)
+        // 保存できなかった値は Rust 側の値に戻す
+        commands.settingsGet().then(
+          (settings) => {
+            backend.value = fromBackend(settings)
+          },
+          () => {},
+        )
+      })
+      .finally(() => {
+        flushing = null
+      })
   }
 
   async function initFileStorage(): Promise<void> {
+    listenTauri('nd:backend-settings-changed', applyBackend).catch((e) =>
+      console.warn('[performance] settings listen failed:', e),
+    )
+    await commands
+      .settingsGet()
+      .then(applyBackend)
+      .catch((e) =>
+        console.warn('[performance] backend settings load failed:', e),
+      )
+
     const content = await readPerformance()
     if (content) {
       try {
         const parsed = JSON5.parse(content) as Partial<PerformanceConfig>
-        overrides.value = parsed
+        localOverrides.value = parsed
+        // 以前は Rust のキーも performance.json5 に持っていた。Rust へ移して消す
+        const legacy = pickBackend(parsed)
+        if (Object.keys(legacy).length > 0) {
+          for (const key of BACKEND_KEYS) delete localOverrides.value[key]
+          setBackend(legacy)
+          schedulePersist()
+        }
       } catch (e) {
         console.warn('[performance] failed to parse performance.json5:', e)
       }
     }
     initialized.value = true
     syncCssProperties()
-    // Initial sync to Rust
-    syncToRust().catch((e) =>
-      console.warn('[performance] initial Rust sync failed:', e),
-    )
   }
 
   async function init(): Promise<void> {
@@ -271,25 +425,34 @@ export const usePerformanceStore = defineStore('performance', () => {
   function set<K extends PerformanceKey>(key: K, value: PerformanceConfig[K]) {
     const meta = FIELD_META[key]
     const clamped = Math.max(meta.min, Math.min(meta.max, value as number))
+    if (isBackendKey(key)) {
+      setBackend({ [key]: clamped })
+      return
+    }
     if (clamped === DEFAULTS[key]) {
-      const { [key]: _, ...rest } = overrides.value
-      overrides.value = rest as Partial<PerformanceConfig>
+      const { [key]: _, ...rest } = localOverrides.value
+      localOverrides.value = rest as Partial<PerformanceConfig>
     } else {
-      overrides.value = { ...overrides.value, [key]: clamped }
+      localOverrides.value = { ...localOverrides.value, [key]: clamped }
     }
     schedulePersist()
     applySideEffects()
   }
 
   function resetKey(key: PerformanceKey) {
-    const { [key]: _, ...rest } = overrides.value
-    overrides.value = rest as Partial<PerformanceConfig>
+    if (isBackendKey(key)) {
+      setBackend({ [key]: DEFAULTS[key] })
+      return
+    }
+    const { [key]: _, ...rest } = localOverrides.value
+    localOverrides.value = rest as Partial<PerformanceConfig>
     schedulePersist()
     applySideEffects()
   }
 
   function resetAll() {
-    overrides.value = {}
+    localOverrides.value = {}
+    setBackend(pickBackend(DEFAULTS))
     schedulePersist()
     applySideEffects()
   }
@@ -315,7 +478,7 @@ export const usePerformanceStore = defineStore('performance', () => {
   /** Apply only CSS rendering properties for auto-quality adjustment. */
   function applyCssQuality(quality: QualityLevel): void {
     const css = CSS_QUALITY_PRESETS[quality]
-    const updated = { ...overrides.value }
+    const updated = { ...localOverrides.value }
     for (const [k, v] of Object.entries(css)) {
       const key = k as PerformanceKey
       if (v === DEFAULTS[key]) {
@@ -324,7 +487,7 @@ export const usePerformanceStore = defineStore('performance', () => {
         ;(updated as Record<string, number>)[key] = v
       }
     }
-    overrides.value = updated
+    localOverrides.value = updated
     schedulePersist()
     applySideEffects()
   }
@@ -334,11 +497,12 @@ export const usePerformanceStore = defineStore('performance', () => {
     const target = interpolateConfig(t)
     const updated: Partial<PerformanceConfig> = {}
     for (const key of Object.keys(DEFAULTS) as PerformanceKey[]) {
-      if (target[key] !== DEFAULTS[key]) {
+      if (!isBackendKey(key) && target[key] !== DEFAULTS[key]) {
         ;(updated as Record<string, number>)[key] = target[key]
       }
     }
-    overrides.value = updated
+    localOverrides.value = updated
+    setBackend(pickBackend(target))
     schedulePersist()
     applySideEffects()
   }
@@ -203,6 +203,35 @@ export const FIELD_META: Record<PerformanceKey, FieldMeta> = {
     label: '画像キャッシュ有効期限',
     description: 'ディスク上の画像キャッシュの保持日数',
   },
+  streamPendingMax: {
+    min: 100,
+    max: 2000,
+    step: 100,
+    unit: '件',
+    category: 'backend',
+    label: 'ストリームバッファ上限',
+    description:
+      '配信の 1 フレーム分にカラムごとに溜める新着・更新の上限。超えた分は捨てて最新ページを取り直す',
+  },
+  streamCapturesMax: {
+    min: 500,
+    max: 5000,
+    step: 500,
+    unit: '件',
+    category: 'backend',
+    label: 'ノート更新バッファ上限',
+    description: '配信の 1 フレーム分に溜めるリアクション・投票更新の上限',
+  },
+  streamDropPolicy: {
+    min: 0,
+    max: 1,
+    step: 1,
+    unit: '',
+    category: 'backend',
+    label: 'バッファ溢れ時の破棄',
+    description:
+      '0=古いものを捨てる (新着を優先)、1=新しいものを捨てる。どちらも欠落後に最新ページを再取得する',
+  },
   prefetchAhead: {
     min: 0,
     max: 60,
@@ -502,6 +531,9 @@ export const SLIDER_LOW: PerformanceConfig = {
   circuitBreakerThreshold: 3,
   circuitBreakerDuration: 90,
   imageCacheTTLDays: 3,
+  streamPendingMax: 200,
+  streamCapturesMax: 1000,
+  streamDropPolicy: 0,
   prefetchAhead: 15,
   prefetchBehind: 5,
   prefetchTrackedMax: 150,
@@ -554,6 +586,9 @@ export const SLIDER_HIGH: PerformanceConfig = {
   circuitBreakerThreshold: 5,
   circuitBreakerDuration: 30,
   imageCacheTTLDays: 14,
+  streamPendingMax: 800,
+  streamCapturesMax: 3000,
+  streamDropPolicy: 0,
   prefetchAhead: 40,
   prefetchBehind: 15,
   prefetchTrackedMax: 1000,
@@ -7,6 +7,7 @@ import { isTauri, readPostForm, writePostForm } from '@/utils/settingsFs'
 
 export type PostFormButtonId =
   | 'attach'
+  | 'recent'
   | 'poll'
   | 'cw'
   | 'hashtag'
@@ -22,6 +23,7 @@ export const POST_FORM_BUTTON_META: Record<
 > = {
   emoji: { icon: 'mood-happy', label: '絵文字' },
   attach: { icon: 'photo-plus', label: '添付' },
+  recent: { icon: 'history', label: '最近のアップロード' },
   poll: { icon: 'chart-arrows', label: '投票' },
   cw: { icon: 'eye-off', label: '閲覧注意' },
   hashtag: { icon: 'hash', label: 'ハッシュタグ' },
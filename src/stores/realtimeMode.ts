@@ -1,5 +1,5 @@
 import { defineStore } from 'pinia'
-import { computed } from 'vue'
+import { computed, ref } from 'vue'
 import { useAccountsStore } from '@/stores/accounts'
 import { useOfflineModeStore } from '@/stores/offlineMode'
 import { usePerformanceStore } from '@/stores/performance'
@@ -17,10 +17,16 @@ export const useRealtimeModeStore = defineStore('realtimeMode', () => {
     },
   })
 
-  /** Effective mode: forced off when the app is in offline mode. */
+  /**
+   * OS のアイドル検知 (Rust 側 idle.rs) による離席状態。離席中は設定を
+   * 変えずにストリーミングだけポーリングへ落とす。
+   */
+  const idleAway = ref(false)
+
+  /** Effective mode: forced off when the app is in offline mode or away. */
   const isRealtime = computed(() => {
     if (useOfflineModeStore().isOfflineMode) return false
-    return enabled.value
+    return enabled.value && !idleAway.value
   })
 
   function getPollingIntervalMs(): number {
@@ -28,8 +34,9 @@ export const useRealtimeModeStore = defineStore('realtimeMode', () => {
   }
 
   function applyToAllAccounts(): void {
-    const mode = enabled.value ? 'realtime' : 'polling'
-    const intervalMs = enabled.value ? undefined : getPollingIntervalMs()
+    const realtime = enabled.value && !idleAway.value
+    const mode = realtime ? 'realtime' : 'polling'
+    const intervalMs = realtime ? undefined : getPollingIntervalMs()
     useStreamingStore().setModeAll(
       useAccountsStore().accounts,
       mode,
@@ -46,10 +53,21 @@ export const useRealtimeModeStore = defineStore('realtimeMode', () => {
     setRealtimeMode(!enabled.value)
   }
 
+  /** 離席状態の変化を反映する。ポーリング設定のときは何もしない。 */
+  function setIdleAway(away: boolean): void {
+    if (idleAway.value === away) return
+    idleAway.value = away
+    if (enabled.value && !useOfflineModeStore().isOfflineMode) {
+      applyToAllAccounts()
+    }
+  }
+
   return {
     enabled,
+    idleAway,
     isRealtime,
     setRealtimeMode,
+    setIdleAway,
     toggle,
   }
 })
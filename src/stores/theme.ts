@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia'
 import { computed, ref, shallowRef, watch } from 'vue'
 import { emitNoteDeckEvent } from '@/aiscript/events'
+import type { ColorScheme, SystemAppearance } from '@/bindings'
 import { useSettingsStore } from '@/stores/settings'
 import * as themeFileSync from '@/stores/themeFileSync'
 import { applyTheme } from '@/theme/applier'
@@ -22,6 +23,7 @@ import {
   setStorageJson,
   setStorageString,
 } from '@/utils/storage'
+import { listenTauri } from '@/utils/tauriEvents'
 import { commands, unwrap } from '@/utils/tauriInvoke'
 import { withViewTransition } from '@/utils/viewTransition'
 
@@ -121,6 +123,12 @@ export const useThemeStore = defineStore('theme', () => {
   const customCss = ref('')
   // Whether file-based storage has been initialized
   const initialized = ref(false)
+  // Rust 側 (system_theme.rs) が OS から直接読んだライト / ダーク設定。
+  // WebKitGTK の prefers-color-scheme は OS 設定に追従しないことがあるので
+  // 取得できればこちらを優先し、null の間は matchMedia にフォールバックする
+  const systemColorScheme = ref<ColorScheme | null>(null)
+  /** OS のアクセントカラー (`#rrggbb`)。取得できない環境では null */
+  const systemAccentColor = ref<string | null>(null)
 
   function init(): void {
     // Restore compiled CSS from localStorage first (sync, FOUC prevention).
@@ -184,15 +192,32 @@ export const useThemeStore = defineStore('theme', () => {
       initFileStorage().catch((e) =>
         console.warn('[theme] file storage init failed:', e),
       )
+      initSystemAppearance().catch((e) =>
+        console.warn('[theme] system appearance init failed:', e),
+      )
     } else {
       initialized.value = true
     }
   }
 
+  function applySystemAppearance(appearance: SystemAppearance): void {
+    const changed = appearance.colorScheme !== systemColorScheme.value
+    systemColorScheme.value = appearance.colorScheme
+    systemAccentColor.value = appearance.accentColor
+    if (changed && manualMode.value == null) applyCurrentTheme()
+  }
+
+  /** OS のライト / ダーク・アクセントカラーを取得し、以後の変更を購読する */
+  async function initSystemAppearance(): Promise<void> {
+    applySystemAppearance(await commands.systemAppearance())
+    await listenTauri('nd:system-appearance-changed', applySystemAppearance)
+  }
+
   function wantsDark(): boolean {
-    return manualMode.value != null
-      ? manualMode.value === 'dark'
-      : window.matchMedia('(prefers-color-scheme: dark)').matches
+    if (manualMode.value != null) return manualMode.value === 'dark'
+    if (systemColorScheme.value != null)
+      return systemColorScheme.value === 'dark'
+    return window.matchMedia('(prefers-color-scheme: dark)').matches
   }
 
   let themeAppliedOnce = false
@@ -696,6 +721,8 @@ export const useThemeStore = defineStore('theme', () => {
     selectedDarkThemeId,
     selectedLightThemeId,
     customCss,
+    systemColorScheme,
+    systemAccentColor,
     init,
     applySource,
     toggleTheme,
@@ -0,0 +1,83 @@
+/**
+ * raw バイト列で応答する IPC コマンド (src-tauri/src/ipc_binary.rs) の呼び出し。
+ *
+ * ノート数の多いタイムライン取得は JSON 応答の受け渡しが重いため、Rust 側で
+ * 1 回だけ JSON バイト列にしたものを `ArrayBuffer` で受け取り、ここで
+ * デコードする。これらのコマンドは bindings.ts に載らない (specta 対象外)
+ * ので、引数の型はここで宣言する。失敗時は `unwrap()` と同じくエラー
+ * オブジェクト (`{ code, message }`) を throw する。
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { NormalizedNote, TimelineOptions, TimelineType } from '@/bindings'
+
+interface BinaryCommands {
+  api_get_timeline_bin: {
+    accountId: string
+    timelineType: TimelineType
+    options: TimelineOptions | null
+  }
+  api_get_cached_timeline_bin: {
+    accountId: string
+    timelineType: string
+    limit: number | null
+  }
+  api_get_cached_timeline_before_bin: {
+    accountId: string
+    timelineType: string
+    before: string
+    limit: number | null
+  }
+}
+
+const decoder = new TextDecoder()
+
+/** raw 応答 (JSON バイト列) を返すコマンドを呼び、デコードして返す。 */
+export async function invokeBinary<T, K extends keyof BinaryCommands>(
+  command: K,
+  args: BinaryCommands[K],
+): Promise<T> {
+  const body = await invoke<ArrayBuffer>(command, { ...args })
+  return JSON.parse(decoder.decode(body)) as T
+}
+
+/** `apiGetTimeline` の raw 版。 */
+export function getTimelineBinary(
+  accountId: string,
+  timelineType: TimelineType,
+  options: TimelineOptions | null,
+): Promise<NormalizedNote[]> {
+  return invokeBinary('api_get_timeline_bin', {
+    accountId,
+    timelineType,
+    options,
+  })
+}
+
+/** `apiGetCachedTimeline` の raw 版。 */
+export function getCachedTimelineBinary(
+  accountId: string,
+  timelineType: string,
+  limit: number | null,
+): Promise<NormalizedNote[]> {
+  return invokeBinary('api_get_cached_timeline_bin', {
+    accountId,
+    timelineType,
+    limit,
+  })
+}
+
+/** `apiGetCachedTimelineBefore` の raw 版。 */
+export function getCachedTimelineBeforeBinary(
+  accountId: string,
+  timelineType: string,
+  before: string,
+  limit: number | null,
+): Promise<NormalizedNote[]> {
+  return invokeBinary('api_get_cached_timeline_before_bin', {
+    accountId,
+    timelineType,
+    before,
+    limit,
+  })
+}
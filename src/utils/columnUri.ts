@@ -21,6 +21,7 @@ const accountUriBuilders: Partial<
   list: (col, host) => `notedeck://${host}/list/${col.listId}`,
   antenna: (col, host) => `notedeck://${host}/antenna/${col.antennaId}`,
   favorites: (_, host) => `notedeck://${host}/favorites`,
+  polls: (_, host) => `notedeck://${host}/polls`,
   clip: (col, host) => `notedeck://${host}/clip/${col.clipId}`,
   channel: (col, host) => `notedeck://${host}/channel/${col.channelId}`,
   user: (col, host) => `notedeck://${host}/user/${col.userId}`,
@@ -0,0 +1,39 @@
+import { beforeEach, describe, expect, it, vi } from 'vitest'
+import { AppError } from '@/utils/errors'
+import { _resetReportedCrashes, reportCrash } from './crashReport'
+
+const { capture } = vi.hoisted(() => ({
+  capture: vi.fn(() => Promise.resolve({ status: 'ok', data: true })),
+}))
+
+vi.mock('@/utils/settingsFs', () => ({ isTauri: true }))
+vi.mock('@/utils/tauriInvoke', () => ({
+  commands: { crashReportCapture: capture },
+}))
+
+describe('reportCrash', () => {
+  beforeEach(() => {
+    capture.mockClear()
+    _resetReportedCrashes()
+  })
+
+  it('sends message and stack once per distinct error', () => {
+    const error = new TypeError('x is undefined')
+    reportCrash(error)
+    reportCrash(error)
+    expect(capture).toHaveBeenCalledTimes(1)
+    expect(capture).toHaveBeenCalledWith(
+      'TypeError: x is undefined',
+      error.stack,
+    )
+  })
+
+  it('skips network errors and caps reports per session', () => {
+    reportCrash(new AppError('NETWORK', 'offline'))
+    expect(capture).not.toHaveBeenCalled()
+
+    for (let i = 0; i < 20; i++) reportCrash(`boom ${i}`)
+    expect(capture).toHaveBeenCalledTimes(10)
+    expect(capture).toHaveBeenLastCalledWith('boom 9', null)
+  })
+})
@@ -0,0 +1,32 @@
+import { AppError } from '@/utils/errors'
+import { isTauri } from '@/utils/settingsFs'
+import { commands } from '@/utils/tauriInvoke'
+
+/**
+ * フロントの未捕捉エラーを Rust 側のクラッシュレポート (opt-in) へ送る。
+ * 記録するかどうかと伏せ字処理は Rust 側 (`crash_report.rs`) が決める。
+ *
+ * エラーのループでディスクを埋めないよう、同じメッセージは 1 セッション 1 回、
+ * 合計 MAX_REPORTS_PER_SESSION 件まで。オフライン由来のエラーはクラッシュ
+ * ではないので送らない。
+ */
+const MAX_REPORTS_PER_SESSION = 10
+const reported = new Set<string>()
+
+export function reportCrash(error: unknown): void {
+  if (!isTauri || reported.size >= MAX_REPORTS_PER_SESSION) return
+  if (error instanceof AppError && error.isNetwork) return
+  const message =
+    error instanceof Error ? `${error.name}: ${error.message}` : String(error)
+  if (reported.has(message)) return
+  reported.add(message)
+  const stack = error instanceof Error ? (error.stack ?? null) : null
+  commands.crashReportCapture(message, stack).catch(() => {
+    /* 記録できなくても元のエラー処理は続ける */
+  })
+}
+
+/** @internal テスト用 */
+export function _resetReportedCrashes(): void {
+  reported.clear()
+}
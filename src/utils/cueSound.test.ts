@@ -0,0 +1,17 @@
+import { describe, expect, it } from 'vitest'
+import type { CueSound } from '@/bindings'
+import { cueSoundType, DEFAULT_NOTIFICATION_SOUND } from './cueSound'
+
+describe('cueSoundType', () => {
+  it('gives reactions and follows their own sounds', () => {
+    expect(cueSoundType('mention')).toBe(DEFAULT_NOTIFICATION_SOUND)
+    expect(cueSoundType('reaction')).not.toBe(cueSoundType('mention'))
+    expect(cueSoundType('follow')).toBe(cueSoundType('followRequest'))
+  })
+
+  it('falls back to the default sound for unknown tags', () => {
+    expect(cueSoundType('someFutureSound' as CueSound)).toBe(
+      DEFAULT_NOTIFICATION_SOUND,
+    )
+  })
+})
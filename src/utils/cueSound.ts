@@ -0,0 +1,22 @@
+import type { CueSound } from '@/bindings'
+
+/** 通知カラムの既定音 (cue が分類できなかったときもこれを鳴らす) */
+export const DEFAULT_NOTIFICATION_SOUND = 'syuilo/n-ea'
+
+// サーバーの client-assets/sounds にある Misskey 標準の音
+const CUE_SOUNDS: Record<CueSound, string> = {
+  mention: DEFAULT_NOTIFICATION_SOUND,
+  reply: DEFAULT_NOTIFICATION_SOUND,
+  quote: DEFAULT_NOTIFICATION_SOUND,
+  reaction: 'syuilo/pope1',
+  renote: 'syuilo/pope2',
+  follow: 'syuilo/pirori',
+  followRequest: 'syuilo/pirori',
+  poll: 'syuilo/triple',
+  other: DEFAULT_NOTIFICATION_SOUND,
+}
+
+/** `stream-cue` の音タグを鳴らす音ファイル (拡張子なし) に変える。 */
+export function cueSoundType(sound: CueSound): string {
+  return CUE_SOUNDS[sound] ?? DEFAULT_NOTIFICATION_SOUND
+}
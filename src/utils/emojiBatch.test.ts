@@ -0,0 +1,55 @@
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+import { createEmojiBatcher, MAX_EMOJI_REFS } from './emojiBatch'
+
+describe('createEmojiBatcher', () => {
+  beforeEach(() => {
+    vi.useFakeTimers()
+  })
+
+  afterEach(() => {
+    vi.useRealTimers()
+  })
+
+  it('同じ窓の参照をアカウントごとに 1 回で引き、重複は送らない', async () => {
+    const fetch = vi.fn(async (_accountId: string, refs: string[]) =>
+      Object.fromEntries(refs.map((r) => [r, `https://x.example/${r}.webp`])),
+    )
+    const resolved = vi.fn()
+    const request = createEmojiBatcher(fetch, resolved)
+
+    request('a1', 'blobcat')
+    request('a1', 'neofox@remote.example')
+    request('a1', 'blobcat')
+    await vi.runAllTimersAsync()
+
+    expect(fetch).toHaveBeenCalledTimes(1)
+    expect(fetch).toHaveBeenCalledWith('a1', [
+      'blobcat',
+      'neofox@remote.example',
+    ])
+    expect(resolved).toHaveBeenCalledWith('a1', {
+      blobcat: 'https://x.example/blobcat.webp',
+      'neofox@remote.example': 'https://x.example/neofox@remote.example.webp',
+    })
+
+    request('a1', 'blobcat')
+    await vi.runAllTimersAsync()
+    expect(fetch).toHaveBeenCalledTimes(1)
+  })
+
+  it('上限を超える参照は分割し、失敗した分は引き直せる', async () => {
+    const fetch = vi.fn(async () => ({}))
+    const request = createEmojiBatcher(fetch, () => {})
+    for (let i = 0; i <= MAX_EMOJI_REFS; i++) request('a1', `e${i}`)
+    await vi.runAllTimersAsync()
+    expect(fetch).toHaveBeenCalledTimes(2)
+
+    fetch.mockRejectedValueOnce(new Error('offline'))
+    vi.spyOn(console, 'warn').mockImplementation(() => {})
+    request('a2', 'blobcat')
+    await vi.runAllTimersAsync()
+    request('a2', 'blobcat')
+    await vi.runAllTimersAsync()
+    expect(fetch).toHaveBeenCalledTimes(4)
+  })
+})
@@ -0,0 +1,51 @@
+/**
+ * 描画中に解決できなかったカスタム絵文字を、短い窓で集めて 1 回の
+ * `apiResolveEmojis` で引く。リアクションの多いノートでも IPC は 1 回で済む。
+ * 同じ参照は 2 度要求しない (失敗した分だけ次の描画で引き直す)。
+ */
+
+/** 要求を集める窓 */
+const BATCH_WINDOW_MS = 16
+/** `api_resolve_emojis` が 1 回に受け付ける参照数の上限 (content.rs と同じ) */
+export const MAX_EMOJI_REFS = 1000
+
+export function createEmojiBatcher(
+  fetch: (accountId: string, refs: string[]) => Promise<Record<string, string>>,
+  onResolved: (accountId: string, urls: Record<string, string>) => void,
+): (accountId: string, ref: string) => void {
+  const requested = new Set<string>()
+  let pending = new Map<string, string[]>()
+  let timer: ReturnType<typeof setTimeout> | null = null
+
+  async function flush() {
+    const batch = pending
+    pending = new Map()
+    timer = null
+    for (const [accountId, refs] of batch) {
+      for (let i = 0; i < refs.length; i += MAX_EMOJI_REFS) {
+        const chunk = refs.slice(i, i + MAX_EMOJI_REFS)
+        try {
+          onResolved(accountId, await fetch(accountId, chunk))
+        } catch (e) {
+          console.warn('[emojis] failed to resolve:', accountId, e)
+          for (const ref of chunk) requested.delete(`${accountId}\n${ref}`)
+        }
+      }
+    }
+  }
+
+  return (accountId, ref) => {
+    const key = `${accountId}\n${ref}`
+    if (requested.has(key)) return
+    requested.add(key)
+    const refs = pending.get(accountId)
+    if (refs) {
+      refs.push(ref)
+    } else {
+      pending.set(accountId, [ref])
+    }
+    if (timer === null) {
+      timer = setTimeout(() => void flush(), BATCH_WINDOW_MS)
+    }
+  }
+}
@@ -0,0 +1,43 @@
+import { describe, expect, it } from 'vitest'
+import type { ServerEmoji } from '@/adapters/types'
+import type { EmojiDiff } from '@/bindings'
+import { applyEmojiDiff, applyEmojiDiffToLookup } from './emojiDiff'
+
+function emoji(name: string, url = `https://example.com/${name}.webp`) {
+  return { name, url, category: null, aliases: [] } satisfies ServerEmoji
+}
+
+const diff: EmojiDiff = {
+  host: 'example.com',
+  version: 1,
+  added: [emoji('fresh')],
+  removed: ['gone'],
+  renamed: [
+    { from: 'old_name', emoji: emoji('new_name', emoji('old_name').url) },
+  ],
+  updated: [emoji('keep', 'https://example.com/keep-v2.webp')],
+}
+
+describe('applyEmojiDiff', () => {
+  it('drops removed and renamed entries, replaces updates and appends new ones', () => {
+    const next = applyEmojiDiff(
+      [emoji('keep'), emoji('gone'), emoji('old_name')],
+      diff,
+    )
+    expect(next.map((e) => e.name)).toEqual(['keep', 'fresh', 'new_name'])
+    expect(next[0]?.url).toBe('https://example.com/keep-v2.webp')
+  })
+})
+
+describe('applyEmojiDiffToLookup', () => {
+  it('updates the shortcode to url map', () => {
+    const lookup = Object.fromEntries(
+      ['keep', 'gone', 'old_name'].map((n) => [n, emoji(n).url]),
+    )
+    expect(applyEmojiDiffToLookup(lookup, diff)).toEqual({
+      keep: 'https://example.com/keep-v2.webp',
+      fresh: 'https://example.com/fresh.webp',
+      new_name: 'https://example.com/old_name.webp',
+    })
+  })
+})
@@ -0,0 +1,45 @@
+import type { ServerEmoji } from '@/adapters/types'
+import type { EmojiDiff } from '@/bindings'
+
+/**
+ * Rust の差分同期 (`nd:emojis-updated`) を絵文字一覧に当てる。削除・改名前の
+ * 名前を除き、変更分を差し替え、追加・改名後の絵文字を末尾に足す。
+ */
+export function applyEmojiDiff(
+  emojis: readonly ServerEmoji[],
+  diff: EmojiDiff,
+): ServerEmoji[] {
+  const dropped = new Set([
+    ...diff.removed,
+    ...diff.renamed.map((r) => r.from),
+  ])
+  const updated = new Map(diff.updated.map((e) => [e.name, e]))
+  const next = emojis
+    .filter((e) => !dropped.has(e.name))
+    .map((e) => updated.get(e.name) ?? e)
+  const present = new Set(next.map((e) => e.name))
+  for (const e of [...diff.added, ...diff.renamed.map((r) => r.emoji)]) {
+    if (present.has(e.name)) continue
+    present.add(e.name)
+    next.push(e)
+  }
+  return next
+}
+
+/** shortcode → URL の lookup に差分を当てる (一覧を持っていないホスト用)。 */
+export function applyEmojiDiffToLookup(
+  lookup: Readonly<Record<string, string>>,
+  diff: EmojiDiff,
+): Record<string, string> {
+  const next = { ...lookup }
+  for (const name of diff.removed) delete next[name]
+  for (const r of diff.renamed) delete next[r.from]
+  for (const e of [
+    ...diff.added,
+    ...diff.updated,
+    ...diff.renamed.map((r) => r.emoji),
+  ]) {
+    next[e.name] = e.url
+  }
+  return next
+}
@@ -0,0 +1,63 @@
+import { describe, expect, it, vi } from 'vitest'
+import type { ServerEmoji } from '@/adapters/types'
+import { filterEmojis, searchCustomEmojis } from './emojiSearch'
+
+const { searchEmojis } = vi.hoisted(() => ({
+  searchEmojis: vi.fn(),
+}))
+
+vi.mock('@/utils/settingsFs', () => ({ isTauri: true }))
+vi.mock('@/utils/tauriInvoke', () => ({
+  commands: { searchEmojis },
+  unwrap: <T>(r: { status: string; data?: T; error?: unknown }) => {
+    if (r.status === 'ok') return r.data as T
+    throw r.error
+  },
+}))
+
+function emoji(name: string, aliases: string[] = []): ServerEmoji {
+  return { name, url: `https://example.com/${name}.webp`, category: null, aliases }
+}
+
+const emojis = [
+  emoji('blobcat_happy'),
+  emoji('nekomimi', ['cat_ears']),
+  emoji('cat'),
+  emoji('bocat'),
+]
+
+describe('filterEmojis', () => {
+  it('orders exact, prefix, then substring matches', () => {
+    expect(filterEmojis(emojis, ' Cat ', 10).map((e) => e.name)).toEqual([
+      'cat',
+      'nekomimi',
+      'blobcat_happy',
+      'bocat',
+    ])
+  })
+
+  it('stops at the limit', () => {
+    expect(filterEmojis(emojis, 'cat', 2).map((e) => e.name)).toEqual([
+      'cat',
+      'nekomimi',
+    ])
+  })
+})
+
+describe('searchCustomEmojis', () => {
+  it('uses the Rust search and falls back to the local list on error', async () => {
+    searchEmojis.mockResolvedValueOnce({ status: 'ok', data: [emoji('bocat')] })
+    expect(await searchCustomEmojis('acc', emojis, 'bct', 5)).toEqual([
+      emoji('bocat'),
+    ])
+    expect(searchEmojis).toHaveBeenCalledWith('acc', 'bct', 5)
+
+    searchEmojis.mockResolvedValueOnce({
+      status: 'error',
+      error: { code: 'NETWORK', message: 'offline' },
+    })
+    expect(
+      (await searchCustomEmojis('acc', emojis, 'neko', 5)).map((e) => e.name),
+    ).toEqual(['nekomimi'])
+  })
+})
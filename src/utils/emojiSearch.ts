@@ -0,0 +1,54 @@
+import type { ServerEmoji } from '@/adapters/types'
+import { isTauri } from '@/utils/settingsFs'
+import { commands, unwrap } from '@/utils/tauriInvoke'
+
+/**
+ * JS 側の段階検索 (完全一致 → 前方一致 → 部分一致)。ブラウザ版と、
+ * Rust 側の検索が失敗したときに使う。
+ */
+export function filterEmojis(
+  emojis: readonly ServerEmoji[],
+  query: string,
+  limit: number,
+): ServerEmoji[] {
+  const q = query.toLowerCase().trim()
+  if (!q) return []
+  const results: ServerEmoji[] = []
+  const seen = new Set<string>()
+  const stages: ((e: ServerEmoji) => boolean)[] = [
+    (e) => e.name === q,
+    (e) => e.name.startsWith(q) || e.aliases.some((a) => a.startsWith(q)),
+    (e) => e.name.includes(q) || e.aliases.some((a) => a.includes(q)),
+  ]
+  for (const matches of stages) {
+    for (const e of emojis) {
+      if (results.length >= limit) return results
+      if (seen.has(e.name) || !matches(e)) continue
+      seen.add(e.name)
+      results.push(e)
+    }
+  }
+  return results
+}
+
+/**
+ * アカウントのサーバーのカスタム絵文字を検索する。Tauri では Rust の絵文字
+ * キャッシュ (`search_emojis`) で引き、数万件でも JS で一覧を舐めない。
+ * `local` は Rust 側が使えないときの検索対象。
+ */
+export async function searchCustomEmojis(
+  accountId: string,
+  local: readonly ServerEmoji[],
+  query: string,
+  limit: number,
+): Promise<ServerEmoji[]> {
+  if (!query.trim()) return []
+  if (isTauri) {
+    try {
+      return unwrap(await commands.searchEmojis(accountId, query, limit))
+    } catch (e) {
+      console.warn('[emoji] search failed, falling back to local list:', e)
+    }
+  }
+  return filterEmojis(local, query, limit)
+}
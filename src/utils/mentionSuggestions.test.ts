@@ -0,0 +1,42 @@
+import { describe, expect, it } from 'vitest'
+import type { NormalizedUser } from '@/adapters/types'
+import { rankMentionCandidates } from './mentionSuggestions'
+
+function user(id: string, username: string, name: string | null = null) {
+  return {
+    id,
+    username,
+    host: null,
+    name,
+    avatarUrl: null,
+  } satisfies NormalizedUser
+}
+
+const frequent = [
+  { user: user('1', 'alice', 'Alice'), weight: 0.2 },
+  { user: user('2', 'albert', 'ねこ'), weight: 0.9 },
+  { user: user('3', 'bob'), weight: 0.5 },
+]
+
+describe('rankMentionCandidates', () => {
+  it('lists frequent users by weight for an empty query', () => {
+    expect(
+      rankMentionCandidates('', frequent, []).map((u) => u.username),
+    ).toEqual(['albert', 'bob', 'alice'])
+  })
+
+  it('puts matching frequent users before search results without duplicates', () => {
+    const searched = [user('1', 'alice'), user('4', 'alfred')]
+    expect(
+      rankMentionCandidates('al', frequent, searched).map((u) => u.username),
+    ).toEqual(['albert', 'alice', 'alfred'])
+  })
+
+  it('matches display names and respects the limit', () => {
+    expect(
+      rankMentionCandidates('ね', frequent, [user('5', 'nekochan')], 1).map(
+        (u) => u.id,
+      ),
+    ).toEqual(['2'])
+  })
+})
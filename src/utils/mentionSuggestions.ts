@@ -0,0 +1,41 @@
+import type { NormalizedUser } from '@/adapters/types'
+
+/** `users/get-frequently-replied-users` の 1 件 */
+export interface FrequentUser {
+  user: NormalizedUser
+  weight: number
+}
+
+function matches(user: NormalizedUser, query: string): boolean {
+  const q = query.toLowerCase()
+  return (
+    user.username.toLowerCase().startsWith(q) ||
+    (user.name?.toLowerCase().includes(q) ?? false)
+  )
+}
+
+/**
+ * メンション候補を並べる。よくリプライする相手のうち入力に合うものを
+ * 重み順で先頭に置き、残りに検索結果を重複なしで続ける。空の入力では
+ * よくリプライする相手だけを返す。
+ */
+export function rankMentionCandidates(
+  query: string,
+  frequent: readonly FrequentUser[],
+  searched: readonly NormalizedUser[],
+  limit = 10,
+): NormalizedUser[] {
+  const result: NormalizedUser[] = []
+  const seen = new Set<string>()
+  const push = (user: NormalizedUser) => {
+    if (seen.has(user.id) || result.length >= limit) return
+    seen.add(user.id)
+    result.push(user)
+  }
+  const sorted = [...frequent].sort((a, b) => b.weight - a.weight)
+  for (const { user } of sorted) {
+    if (matches(user, query)) push(user)
+  }
+  for (const user of searched) push(user)
+  return result
+}
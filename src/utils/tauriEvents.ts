@@ -1,7 +1,17 @@
 import { emit, listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type {
+  AutomationFired,
+  BackendSettings,
+  EmojiDiff,
+  IdleStatus,
+  Job,
+  NetworkStatus,
+  ScheduledTask,
+  SystemAppearance,
+} from '@/bindings'
 import type { AiChatEventPayload } from '@/composables/useAiChat'
 import type { HeartbeatTickPayload } from '@/composables/useHeartbeatDaemon'
-import type { QueryRequest } from '@/core/apiBridge'
+import type { QueryRequest, QueryResponse } from '@/core/apiBridge'
 import type { Account } from '@/stores/accounts'
 import type { DeckColumn } from '@/stores/deck'
 import type { OgpData } from '@/utils/ogp'
@@ -9,14 +19,34 @@ import type { OgpData } from '@/utils/ogp'
 /**
  * Tauri イベント名 → payload 型のレジストリ。
  * emit / listen の対応関係をコンパイル時に保証する。
- * 動的イベント名 (`nd:query-response-<id>`) のみ対象外で、apiBridge が
- * 直接 emit する。
  */
 export interface TauriEventPayloads {
   // Rust → JS
   'nd:accounts-early': Account[]
   'nd:hwheel': number
+  /** OS のアイドル検知で離席状態が変わった (idle.rs) */
+  'nd:idle-changed': IdleStatus
   'nd:quick-note': undefined
+  /** 回線断 / 復旧を検知した (network.rs)。復旧時はストリーム再接続済み */
+  'nd:network-changed': NetworkStatus
+  /** OS スリープから復帰し、Rust 側がストリームを作り直した (power.rs) */
+  'nd:system-resumed': { sleptSeconds: number | null }
+  /** OS のライト / ダーク・アクセントカラーが変わった (system_theme.rs) */
+  'nd:system-appearance-changed': SystemAppearance
+  /** クイック投稿ミニウィンドウの再表示 (対象アカウント、null はアクティブ) */
+  'nd:quick-post-open': string | null
+  /** トレイのアカウント別メニューから通知を開く (対象アカウント ID) */
+  'nd:tray-open-notifications': string
+  /** バックグラウンドジョブの状態 / 進捗が変わった (jobs.rs) */
+  'nd:job-updated': Job
+  /** 自動化ルールが発火し、アクションを実行し終えた (automation.rs) */
+  'nd:automation-fired': AutomationFired
+  /** 定期タスクの次回時刻 / 実行結果が変わった (scheduler.rs) */
+  'nd:schedule-updated': ScheduledTask
+  /** バックエンド設定 (settings.json) が変わった (settings.rs)。payload は変更後の全体 */
+  'nd:backend-settings-changed': BackendSettings
+  /** ホストのカスタム絵文字一覧に差分があった (emoji_cache.rs) */
+  'nd:emojis-updated': EmojiDiff
   'nd:toggle-offline-mode': undefined
   'nd:toggle-realtime-mode': undefined
   'nd:deep-link': string
@@ -24,6 +54,10 @@ export interface TauriEventPayloads {
   'nd:ai-chat-event': AiChatEventPayload
   'nd:ai-heartbeat-tick': HeartbeatTickPayload
   'nd:query-request': QueryRequest
+  // JS → Rust (query_bridge.rs)
+  'nd:query-response': { id: string } & QueryResponse
+  /** apiBridge が listener を張った (起動 / リロード後)。Rust が待機中の query を再送する */
+  'nd:query-bridge-ready': undefined
   // JS ↔ JS (ウィンドウ間 IPC)
   'deck:move-column': { columnId: string; targetWindowId: string | null }
   'deck:window-closed': { windowId: string }
@@ -0,0 +1,79 @@
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
+
+const bulk = vi.fn()
+const single = vi.fn()
+
+vi.mock('@/utils/tauriInvoke', () => ({
+  commands: { apiGetTimelinesBulk: (...args: unknown[]) => bulk(...args) },
+  unwrap: (r: { status: string; data?: unknown; error?: unknown }) => {
+    if (r.status === 'ok') return r.data
+    throw r.error
+  },
+}))
+
+vi.mock('@/utils/binaryIpc', () => ({
+  getTimelineBinary: (...args: unknown[]) => single(...args),
+}))
+
+describe('getTimelineBatched', () => {
+  beforeEach(() => {
+    vi.useFakeTimers()
+    bulk.mockReset()
+    single.mockReset()
+  })
+
+  afterEach(() => {
+    vi.useRealTimers()
+  })
+
+  it('窓内に 1 件だけなら raw バイト列 IPC で取る', async () => {
+    const { getTimelineBatched } = await import('./timelineBatch')
+    single.mockResolvedValue([{ id: 'n1' }])
+
+    const p = getTimelineBatched('a1', 'home', null)
+    await vi.runAllTimersAsync()
+
+    await expect(p).resolves.toEqual([{ id: 'n1' }])
+    expect(bulk).not.toHaveBeenCalled()
+  })
+
+  it('同時の要求は 1 回の bulk にまとめ、結果と失敗を要求ごとに返す', async () => {
+    const { getTimelineBatched } = await import('./timelineBatch')
+    bulk.mockResolvedValue({
+      status: 'ok',
+      data: {
+        '0': { status: 'ok', notes: [{ id: 'n1' }] },
+        '1': { status: 'error', code: 'API', message: 'boom' },
+      },
+    })
+
+    const home = getTimelineBatched('a1', 'home', null)
+    const local = getTimelineBatched('a2', 'local', null)
+    await vi.runAllTimersAsync()
+
+    await expect(home).resolves.toEqual([{ id: 'n1' }])
+    await expect(local).rejects.toEqual({ code: 'API', message: 'boom' })
+    expect(bulk).toHaveBeenCalledTimes(1)
+    expect(bulk.mock.calls[0]?.[0]).toHaveLength(2)
+    expect(single).not.toHaveBeenCalled()
+  })
+
+  it('50 件を超える要求は 50 件ずつ分けて bulk に送る', async () => {
+    const { getTimelineBatched } = await import('./timelineBatch')
+    bulk.mockImplementation(async (requests: { id: string }[]) => ({
+      status: 'ok',
+      data: Object.fromEntries(
+        requests.map((r) => [r.id, { status: 'ok', notes: [] }]),
+      ),
+    }))
+
+    const all = Array.from({ length: 120 }, (_, i) =>
+      getTimelineBatched(`a${i}`, 'home', null),
+    )
+    await vi.runAllTimersAsync()
+
+    await expect(Promise.all(all)).resolves.toHaveLength(120)
+    expect(bulk.mock.calls.map((c) => c[0].length)).toEqual([50, 50, 20])
+    expect(single).not.toHaveBeenCalled()
+  })
+})
@@ -0,0 +1,94 @@
+/**
+ * 同じタイミングで出たタイムライン取得を 1 回の `apiGetTimelinesBulk` にまとめる。
+ *
+ * 起動直後は全カラムがほぼ同時に `getTimeline` を呼ぶため、短い窓で集めて
+ * Rust 側に並行取得 (ホストごとに同時数制限あり) させる。窓内に 1 件しか
+ * 無ければ従来どおり raw バイト列 IPC (`getTimelineBinary`) で取る。
+ * Rust 側は 1 回 50 件までなので、それを超えたら 50 件ずつに分けて送る。
+ * 個別の失敗は `unwrap()` と同じ `{ code, message }` で reject する。
+ */
+
+import type {
+  NormalizedNote,
+  TimelineOptions,
+  TimelineType,
+} from '@/bindings'
+import { getTimelineBinary } from '@/utils/binaryIpc'
+import { commands, unwrap } from '@/utils/tauriInvoke'
+
+/** 取得要求を集める窓 */
+const BATCH_WINDOW_MS = 8
+
+/** 1 回の bulk に載せる上限 (Rust 側 `MAX_BULK_TIMELINES` と同じ値) */
+const MAX_BULK_TIMELINES = 50
+
+interface PendingRequest {
+  accountId: string
+  timelineType: TimelineType
+  options: TimelineOptions | null
+  resolve: (notes: NormalizedNote[]) => void
+  reject: (error: unknown) => void
+}
+
+let pending: PendingRequest[] = []
+let timer: ReturnType<typeof setTimeout> | null = null
+
+function flush(): void {
+  const batch = pending
+  pending = []
+  timer = null
+
+  for (let i = 0; i < batch.length; i += MAX_BULK_TIMELINES) {
+    void fetchChunk(batch.slice(i, i + MAX_BULK_TIMELINES))
+  }
+}
+
+async function fetchChunk(batch: PendingRequest[]): Promise<void> {
+  const [only] = batch
+  if (batch.length === 1 && only) {
+    getTimelineBinary(only.accountId, only.timelineType, only.options).then(
+      only.resolve,
+      only.reject,
+    )
+    return
+  }
+
+  try {
+    const results = unwrap(
+      await commands.apiGetTimelinesBulk(
+        batch.map((req, i) => ({
+          id: String(i),
+          accountId: req.accountId,
+          timelineType: req.timelineType,
+          options: req.options,
+        })),
+      ),
+    )
+    batch.forEach((req, i) => {
+      const result = results[String(i)]
+      if (!result) {
+        req.reject({ code: 'UNKNOWN', message: 'Missing timeline result' })
+      } else if (result.status === 'ok') {
+        req.resolve(result.notes)
+      } else {
+        req.reject({ code: result.code, message: result.message })
+      }
+    })
+  } catch (e) {
+    for (const req of batch) req.reject(e)
+  }
+}
+
+/** `getTimelineBinary` と同じ結果を、同時に出た要求とまとめて取得する。 */
+export function getTimelineBatched(
+  accountId: string,
+  timelineType: TimelineType,
+  options: TimelineOptions | null,
+): Promise<NormalizedNote[]> {
+  return new Promise((resolve, reject) => {
+    pending.push({ accountId, timelineType, options, resolve, reject })
+    if (timer === null) {
+      timer = setTimeout(flush, BATCH_WINDOW_MS)
+    }
+  })
+}
@@ -1,12 +1,17 @@
 // @vitest-environment happy-dom
 import { createPinia, setActivePinia } from 'pinia'
-import { afterEach, beforeEach, describe, expect, it } from 'vitest'
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest'
 import {
   _clearCapabilitiesForTest,
   registerCapability,
 } from '@/capabilities/registry'
 import type { Command } from '@/commands/registry'
-import { handleQuery } from '@/core/apiBridge'
+import {
+  destroyApiBridge,
+  initApiBridge,
+  type QueryRequest,
+  type QueryResponse,
+} from '@/core/apiBridge'
 import { recordStreamHealth } from '@/core/streamHealth'
 import type { ProfiledPrincipalId } from '@/permissions/principal'
 import { setPermissionPreset } from '@/permissions/schema'
@@ -15,6 +20,52 @@ import {
   usePermissionsConfig,
 } from '@/permissions/store'
 
+// query_bridge.rs との往復を Tauri event の mock で再現する
+let requestListener: ((request: QueryRequest) => Promise<void>) | null = null
+const emitted: Array<{ event: string; payload: unknown }> = []
+vi.mock('@/utils/tauriEvents', () => ({
+  listenTauri: vi.fn(
+    async (_event: string, cb: (request: QueryRequest) => Promise<void>) => {
+      requestListener = cb
+      return () => {
+        requestListener = null
+      }
+    },
+  ),
+  emitTauri: vi.fn(async (event: string, payload?: unknown) => {
+    emitted.push({ event, payload })
+  }),
+}))
+
+let nextId = 0
+
+/** nd:query-request を 1 件流し、対応する nd:query-response を返す */
+async function query(
+  type: string,
+  params: Record<string, unknown> = {},
+): Promise<QueryResponse> {
+  if (!requestListener) throw new Error('apiBridge is not initialized')
+  const id = `q${nextId++}`
+  await requestListener({ id, type, params })
+  const response = emitted.find(
+    (e) =>
+      e.event === 'nd:query-response' &&
+      (e.payload as { id: string }).id === id,
+  )
+  if (!response) throw new Error(`no response for ${type}`)
+  return response.payload as { id: string } & QueryResponse
+}
+
+/** 成功応答の data を取り出す */
+async function queryData(
+  type: string,
+  params: Record<string, unknown> = {},
+): Promise<unknown> {
+  const response = await query(type, params)
+  if (!response.ok) throw new Error(response.message)
+  return response.data
+}
+
 function makeCapability(overrides: Partial<Command> = {}): Command {
   return {
     id: 'test.cap',
@@ -41,16 +92,19 @@ function setPrincipalPreset(
   )
 }
 
-beforeEach(() => {
+beforeEach(async () => {
   setActivePinia(createPinia())
   _resetPermissionsForTest()
+  emitted.length = 0
+  await initApiBridge()
 })
 
 afterEach(() => {
+  destroyApiBridge()
   _clearCapabilitiesForTest()
 })
 
-describe('handleQuery: capabilities/list', () => {
+describe('query: capabilities/list', () => {
   it('registry の capability をシグネチャ付きで返す', async () => {
     registerCapability(
       makeCapability({
@@ -66,7 +120,7 @@ describe('handleQuery: capabilities/list', () => {
         },
       }),
     )
-    const result = (await handleQuery('capabilities/list', {})) as Array<
+    const result = (await queryData('capabilities/list', {})) as Array<
       Record<string, unknown>
     >
     expect(result).toHaveLength(1)
@@ -88,19 +142,19 @@ describe('handleQuery: capabilities/list', () => {
         requiresConfirmation: () => null,
       }),
     )
-    const result = (await handleQuery('capabilities/list', {})) as Array<
+    const result = (await queryData('capabilities/list', {})) as Array<
       Record<string, unknown>
     >
     expect(result[0].requiresConfirmation).toBe(true)
   })
 })
 
-describe('handleQuery: capabilities/execute', () => {
+describe('query: capabilities/execute', () => {
   it('external プロファイル (default 縮小 custom) で write 系を deny する', async () => {
     registerCapability(
       makeCapability({ id: 'notes.create', permissions: ['notes.write'] }),
     )
-    const result = (await handleQuery('capabilities/execute', {
+    const result = (await queryData('capabilities/execute', {
       capabilityId: 'notes.create',
     })) as { ok: boolean; code?: string }
     expect(result.ok).toBe(false)
@@ -112,7 +166,7 @@ describe('handleQuery: capabilities/execute', () => {
       makeCapability({ id: 'notes.create', permissions: ['notes.write'] }),
     )
     setPrincipalPreset('ai.chat', 'full')
-    const result = (await handleQuery('capabilities/execute', {
+    const result = (await queryData('capabilities/execute', {
       capabilityId: 'notes.create',
     })) as { ok: boolean; code?: string }
     expect(result.ok).toBe(false)
@@ -128,7 +182,7 @@ describe('handleQuery: capabilities/execute', () => {
       }),
     )
     setPrincipalPreset('external', 'full')
-    const result = (await handleQuery('capabilities/execute', {
+    const result = (await queryData('capabilities/execute', {
       capabilityId: 'notes.create',
       params: { text: 'hello' },
     })) as { ok: boolean; result?: unknown }
@@ -137,7 +191,7 @@ describe('handleQuery: capabilities/execute', () => {
   })
 
   it('未登録 capability は unknown_capability を返す', async () => {
-    const result = (await handleQuery('capabilities/execute', {
+    const result = (await queryData('capabilities/execute', {
       capabilityId: 'no.such.cap',
     })) as { ok: boolean; code?: string }
     expect(result.ok).toBe(false)
@@ -145,10 +199,10 @@ describe('handleQuery: capabilities/execute', () => {
   })
 })
 
-describe('handleQuery: health/streams', () => {
+describe('query: health/streams', () => {
   it('記録済みのストリーム状態を accountId 付きで返す', async () => {
     recordStreamHealth('acc1', 'connected')
-    const result = (await handleQuery('health/streams', {})) as Array<{
+    const result = (await queryData('health/streams', {})) as Array<{
       accountId: string
       state: string
       since: number
@@ -159,11 +213,21 @@ describe('handleQuery: health/streams', () => {
   })
 })
 
-describe('handleQuery: 共通挙動', () => {
-  it('未知の query type は error envelope を返す', async () => {
-    const result = (await handleQuery('nope/nothing', {})) as {
-      error?: string
-    }
-    expect(result.error).toContain('nope/nothing')
+describe('query: 応答 envelope', () => {
+  it('初期化時に query_bridge へ ready を知らせる', () => {
+    expect(emitted.map((e) => e.event)).toContain('nd:query-bridge-ready')
+  })
+
+  it('成功は ok: true で data に包む', async () => {
+    recordStreamHealth('acc1', 'connected')
+    const response = await query('health/streams')
+    expect(response.ok).toBe(true)
+    expect(response.ok && Array.isArray(response.data)).toBe(true)
+  })
+
+  it('未知の query type は unknown_query', async () => {
+    const response = await query('nope/nothing')
+    expect(response).toMatchObject({ ok: false, code: 'unknown_query' })
+    expect(!response.ok && response.message).toContain('nope/nothing')
   })
 })